@@ -0,0 +1,69 @@
+//! Compares the throughput of the buffered `yes` writer against the
+//! previous `println!` loop. Both write to the null device so the numbers
+//! reflect the cost of producing output rather than of consuming it.
+//!
+//! Run with `cargo bench --bench yes`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::time::{Duration, Instant};
+
+#[allow(dead_code)]
+#[path = "../src/bin/yes/buffer.rs"]
+mod buffer;
+
+const TOTAL_BYTES: usize = 256 * 1024 * 1024;
+
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+fn null_device() -> File {
+    OpenOptions::new()
+        .write(true)
+        .open(NULL_DEVICE)
+        .expect("Failed to open null device")
+}
+
+/// Mirrors the old implementation: `println!` writes through a
+/// line-buffered stdout, flushing after every line.
+fn line_by_line(line: &str) -> io::Result<Duration> {
+    let mut writer = LineWriter::new(null_device());
+    let per_line = line.len() + 1;
+    let start = Instant::now();
+    let mut written = 0;
+    while written < TOTAL_BYTES {
+        writeln!(writer, "{}", line)?;
+        written += per_line;
+    }
+    Ok(start.elapsed())
+}
+
+fn buffered(line: &str) -> io::Result<Duration> {
+    let buffer = buffer::fill(line);
+    let mut writer = null_device();
+    let start = Instant::now();
+    let mut written = 0;
+    while written < TOTAL_BYTES {
+        writer.write_all(&buffer)?;
+        written += buffer.len();
+    }
+    Ok(start.elapsed())
+}
+
+fn mb_per_sec(elapsed: Duration) -> f64 {
+    (TOTAL_BYTES as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64()
+}
+
+fn main() -> io::Result<()> {
+    for line in &["y", "the quick brown fox jumps over the lazy dog"] {
+        let old = mb_per_sec(line_by_line(line)?);
+        let new = mb_per_sec(buffered(line)?);
+        println!("yes {:?}", line);
+        println!("    println loop   {:>10.1} MB/s", old);
+        println!("    buffered       {:>10.1} MB/s", new);
+        println!("    speedup        {:>10.1}x", new / old);
+    }
+    Ok(())
+}
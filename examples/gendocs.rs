@@ -0,0 +1,444 @@
+//! Regenerates the man pages and Markdown references under `docs/` and the
+//! utility table in the README, all from each utility's help text.
+//!
+//!     cargo run --example gendocs            rewrite the generated files
+//!     cargo run --example gendocs -- --check fail if any are out of date, or
+//!                                            if a utility rejects an option
+//!                                            it documents
+
+// The configs below are each utility's own module, tests and all, and
+// those tests only build inside their utility; a test build of this
+// example has nothing to test, so leave everything out of it.
+#![cfg(not(test))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coreutils::checksum::ChecksumConfig;
+use coreutils::config::Config;
+use coreutils::docs::{self, Invocation, Page};
+
+#[allow(dead_code)]
+#[path = "../src/bin/base32/config.rs"]
+mod base32;
+#[allow(dead_code)]
+#[path = "../src/bin/base64/config.rs"]
+mod base64;
+#[allow(dead_code)]
+#[path = "../src/bin/basenc/config.rs"]
+mod basenc;
+#[allow(dead_code)]
+#[path = "../src/bin/cat/config.rs"]
+mod cat;
+#[allow(dead_code)]
+#[path = "../src/bin/comm/config.rs"]
+mod comm;
+#[allow(dead_code)]
+#[path = "../src/bin/cp/config.rs"]
+mod cp;
+#[allow(dead_code)]
+#[path = "../src/bin/csplit/config.rs"]
+mod csplit;
+#[allow(dead_code)]
+#[path = "../src/bin/cut/config.rs"]
+mod cut;
+#[allow(dead_code)]
+#[path = "../src/bin/echo/config.rs"]
+mod echo;
+#[allow(dead_code)]
+#[path = "../src/bin/fmt/config.rs"]
+mod fmt;
+#[allow(dead_code)]
+#[path = "../src/bin/fold/config.rs"]
+mod fold;
+#[allow(dead_code)]
+#[path = "../src/bin/head/config.rs"]
+mod head;
+#[allow(dead_code)]
+#[path = "../src/bin/join/config.rs"]
+mod join;
+#[allow(dead_code)]
+#[path = "../src/bin/ls/config.rs"]
+mod ls;
+#[allow(dead_code)]
+#[path = "../src/bin/mv/config.rs"]
+mod mv;
+#[allow(dead_code)]
+#[path = "../src/bin/nl/config.rs"]
+mod nl;
+#[allow(dead_code)]
+#[path = "../src/bin/od/config.rs"]
+mod od;
+#[allow(dead_code)]
+#[path = "../src/bin/paste/config.rs"]
+mod paste;
+#[allow(dead_code)]
+#[path = "../src/bin/pr/config.rs"]
+mod pr;
+#[allow(dead_code)]
+#[path = "../src/bin/printf/config.rs"]
+mod printf;
+#[allow(dead_code)]
+#[path = "../src/bin/rev/config.rs"]
+mod rev;
+#[allow(dead_code)]
+#[path = "../src/bin/seq/config.rs"]
+mod seq;
+#[allow(dead_code)]
+#[path = "../src/bin/sort/config.rs"]
+mod sort;
+#[allow(dead_code)]
+#[path = "../src/bin/split/config.rs"]
+mod split;
+#[allow(dead_code)]
+#[path = "../src/bin/tac/config.rs"]
+mod tac;
+#[allow(dead_code)]
+#[path = "../src/bin/tail/config.rs"]
+mod tail;
+#[allow(dead_code)]
+#[path = "../src/bin/tee/config.rs"]
+mod tee;
+#[allow(dead_code)]
+#[path = "../src/bin/tr/config.rs"]
+mod tr;
+#[allow(dead_code)]
+#[path = "../src/bin/uniq/config.rs"]
+mod uniq;
+#[allow(dead_code)]
+#[path = "../src/bin/wc/config.rs"]
+mod wc;
+#[allow(dead_code)]
+#[path = "../src/bin/xxd/config.rs"]
+mod xxd;
+#[allow(dead_code)]
+#[path = "../src/bin/yes/config.rs"]
+mod yes;
+
+/// A utility's config together with its own parser, which rejects what
+/// `Config::parse` lets through, and how to call it with each option.
+struct Utility {
+    config: Box<dyn Config>,
+    parse: Box<dyn Fn(Vec<String>) -> Result<(), String>>,
+    invocation: Invocation,
+}
+
+fn utility<C: Config + 'static>(
+    new: fn() -> C,
+    parse: fn(&mut C, Vec<String>) -> Result<(), String>,
+    fixtures: &'static [&'static [&'static str]],
+    values: &'static [(&'static str, &'static str)],
+) -> Utility {
+    Utility {
+        config: Box::new(new()),
+        parse: Box::new(move |args| parse(&mut new(), args)),
+        invocation: Invocation { fixtures, values },
+    }
+}
+
+const ALONE: &[&[&str]] = &[&[]];
+const ONE_FILE: &[&[&str]] = &[&["myfile.txt"]];
+const TWO_FILES: &[&[&str]] = &[&["a", "b"]];
+/// Some checksum options only go with -c.
+const CHECKING: &[&[&str]] = &[&[], &["-c"]];
+const DIGESTS: &[(&str, &str)] = &[
+    ("-a", "md5"),
+    ("--algorithm", "md5"),
+    ("-l", "256"),
+    ("--length", "256"),
+];
+
+fn utilities() -> Vec<Utility> {
+    vec![
+        utility(
+            || ChecksumConfig::new("b2sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(
+            base32::Base32Config::new,
+            base32::Base32Config::parse,
+            ALONE,
+            &[],
+        ),
+        utility(
+            base64::Base64Config::new,
+            base64::Base64Config::parse,
+            ALONE,
+            &[],
+        ),
+        utility(
+            basenc::BasencConfig::new,
+            basenc::BasencConfig::parse,
+            &[&["--base64"]],
+            &[],
+        ),
+        utility(cat::CatConfig::new, cat::CatConfig::parse, ONE_FILE, &[]),
+        // -l only goes with BLAKE2b.
+        utility(
+            || ChecksumConfig::new("cksum"),
+            ChecksumConfig::parse,
+            &[&["-a", "blake2b"], &["-a", "blake2b", "-c"]],
+            DIGESTS,
+        ),
+        utility(
+            comm::CommConfig::new,
+            comm::CommConfig::parse,
+            TWO_FILES,
+            &[],
+        ),
+        utility(
+            cp::CpConfig::new,
+            cp::CpConfig::parse,
+            TWO_FILES,
+            &[("--sparse", "never"), ("--no-preserve", "mode")],
+        ),
+        // A suffix format needs a directive.
+        utility(
+            csplit::CsplitConfig::new,
+            csplit::CsplitConfig::parse,
+            &[&["in", "5"]],
+            &[("-b", "%d"), ("--suffix-format", "%d")],
+        ),
+        // Every option but the lists needs a list beside it.
+        utility(
+            cut::CutConfig::new,
+            cut::CutConfig::parse,
+            &[&["-f1"], &[]],
+            &[],
+        ),
+        utility(
+            echo::EchoConfig::new,
+            echo::EchoConfig::parse,
+            &[&["text"]],
+            &[],
+        ),
+        utility(fmt::FmtConfig::new, fmt::FmtConfig::parse, ALONE, &[]),
+        utility(fold::FoldConfig::new, fold::FoldConfig::parse, ALONE, &[]),
+        utility(
+            head::HeadConfig::new,
+            head::HeadConfig::parse,
+            ONE_FILE,
+            &[],
+        ),
+        // A format names a file and a field, or the join field.
+        utility(
+            join::JoinConfig::new,
+            join::JoinConfig::parse,
+            TWO_FILES,
+            &[("-o", "0")],
+        ),
+        utility(ls::LsConfig::new, ls::LsConfig::parse, ONE_FILE, &[]),
+        utility(
+            || ChecksumConfig::new("md5sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(mv::MvConfig::new, mv::MvConfig::parse, TWO_FILES, &[]),
+        // Styles and formats take words rather than numbers.
+        utility(
+            nl::NlConfig::new,
+            nl::NlConfig::parse,
+            ALONE,
+            &[
+                ("-b", "a"),
+                ("--body-numbering", "a"),
+                ("-f", "a"),
+                ("--footer-numbering", "a"),
+                ("-h", "a"),
+                ("--header-numbering", "a"),
+                ("-n", "ln"),
+                ("--number-format", "ln"),
+            ],
+        ),
+        // Radixes, byte orders and types are words rather than numbers.
+        utility(
+            od::OdConfig::new,
+            od::OdConfig::parse,
+            ALONE,
+            &[
+                ("-A", "x"),
+                ("--address-radix", "x"),
+                ("--endian", "big"),
+                ("-t", "x1"),
+                ("--format", "x1"),
+            ],
+        ),
+        utility(
+            paste::PasteConfig::new,
+            paste::PasteConfig::parse,
+            ALONE,
+            &[],
+        ),
+        utility(pr::PrConfig::new, pr::PrConfig::parse, ALONE, &[]),
+        utility(
+            printf::PrintfConfig::new,
+            printf::PrintfConfig::parse,
+            &[&["%s"]],
+            &[],
+        ),
+        utility(rev::RevConfig::new, rev::RevConfig::parse, ALONE, &[]),
+        utility(
+            seq::SeqConfig::new,
+            seq::SeqConfig::parse,
+            &[&["10"]],
+            &[("-f", "%g"), ("--format", "%g")],
+        ),
+        utility(
+            || ChecksumConfig::new("sha1sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(
+            || ChecksumConfig::new("sha224sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(
+            || ChecksumConfig::new("sha256sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(
+            || ChecksumConfig::new("sha384sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        utility(
+            || ChecksumConfig::new("sha512sum"),
+            ChecksumConfig::parse,
+            CHECKING,
+            DIGESTS,
+        ),
+        // --sort takes a word rather than a number.
+        utility(
+            sort::SortConfig::new,
+            sort::SortConfig::parse,
+            ALONE,
+            &[("--sort", "month")],
+        ),
+        utility(
+            split::SplitConfig::new,
+            split::SplitConfig::parse,
+            ALONE,
+            &[],
+        ),
+        utility(tac::TacConfig::new, tac::TacConfig::parse, ALONE, &[]),
+        utility(
+            tail::TailConfig::new,
+            tail::TailConfig::parse,
+            ONE_FILE,
+            &[],
+        ),
+        utility(tee::TeeConfig::new, tee::TeeConfig::parse, ONE_FILE, &[]),
+        // Deleting without squeezing takes a single set.
+        utility(
+            tr::TrConfig::new,
+            tr::TrConfig::parse,
+            &[&["a", "b"], &["a"]],
+            &[],
+        ),
+        utility(uniq::UniqConfig::new, uniq::UniqConfig::parse, ALONE, &[]),
+        utility(wc::WcConfig::new, wc::WcConfig::parse, ALONE, &[]),
+        utility(xxd::XxdConfig::new, xxd::XxdConfig::parse, ALONE, &[]),
+        utility(yes::YesConfig::new, yes::YesConfig::parse, ALONE, &[]),
+    ]
+}
+
+/// Report every documented option a utility's parser rejects.
+fn check_options(utilities: &[Utility]) -> bool {
+    let mut ok = true;
+    for utility in utilities {
+        let rejected =
+            docs::rejected_options(&*utility.config, &*utility.parse, &utility.invocation);
+        for (option, error) in rejected {
+            let name = utility.config.bin_name();
+            eprintln!(
+                "gendocs: {} {} is documented but rejected: {}",
+                name, option, error
+            );
+            ok = false;
+        }
+    }
+    ok
+}
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn generated(pages: &[Page]) -> Result<Vec<(PathBuf, String)>, String> {
+    let version = env!("CARGO_PKG_VERSION");
+    let mut files = Vec::new();
+    for page in pages {
+        files.push((
+            root().join("docs").join(format!("{}.md", page.name)),
+            page.markdown(),
+        ));
+        files.push((
+            root()
+                .join("docs")
+                .join("man")
+                .join(format!("{}.1", page.name)),
+            page.man(version),
+        ));
+    }
+
+    let readme_path = root().join("README.md");
+    let readme = fs::read_to_string(&readme_path).map_err(|e| e.to_string())?;
+    files.push((readme_path, docs::readme_table(&readme, pages)?));
+    Ok(files)
+}
+
+fn is_current(path: &Path, contents: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|existing| existing == contents)
+}
+
+fn main() {
+    let check = std::env::args().any(|arg| arg == "--check");
+    let utilities = utilities();
+    let pages: Vec<Page> = utilities
+        .iter()
+        .map(|utility| Page::new(utility.config.bin_name(), utility.config.usage()))
+        .collect();
+
+    let files = match generated(&pages) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("gendocs: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut stale = false;
+    for (path, contents) in files {
+        if is_current(&path, &contents) {
+            continue;
+        }
+        let display = path.strip_prefix(root()).unwrap_or(&path).display();
+        if check {
+            eprintln!("gendocs: {} is out of date", display);
+            stale = true;
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("Failed to create docs directory");
+        }
+        fs::write(&path, contents).expect("Failed to write generated docs");
+        println!("wrote {}", display);
+    }
+
+    if stale {
+        eprintln!("gendocs: run `cargo run --example gendocs` to regenerate");
+    }
+    if check && !check_options(&utilities) || stale {
+        process::exit(1);
+    }
+}
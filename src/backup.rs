@@ -0,0 +1,203 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How to name the backup of a file about to be replaced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    None,
+    /// Append the suffix: `file~`.
+    Simple,
+    /// Count upwards: `file.~1~`, `file.~2~`, ...
+    Numbered,
+    /// Numbered if the file already has numbered backups, else simple.
+    Existing,
+}
+
+const CONTROLS: [(&str, Control); 8] = [
+    ("none", Control::None),
+    ("off", Control::None),
+    ("simple", Control::Simple),
+    ("never", Control::Simple),
+    ("existing", Control::Existing),
+    ("nil", Control::Existing),
+    ("numbered", Control::Numbered),
+    ("t", Control::Numbered),
+];
+
+/// Read a version control method, accepting any unambiguous prefix.
+/// `context` names where it came from for the error message.
+pub fn parse_control(value: &str, context: &str) -> Result<Control, String> {
+    if let Some(&(_, control)) = CONTROLS.iter().find(|(name, _)| *name == value) {
+        return Ok(control);
+    }
+    let mut matches = CONTROLS
+        .iter()
+        .filter(|(name, _)| !value.is_empty() && name.starts_with(value))
+        .map(|&(_, control)| control);
+    let problem = match matches.next() {
+        Some(first) if matches.all(|control| control == first) => return Ok(first),
+        Some(_) => "ambiguous",
+        None => "invalid",
+    };
+    Err(format!(
+        "{} argument '{}' for '{}'\nValid arguments are:\n  - 'none', 'off'\n  - 'simple', 'never'\n  - 'existing', 'nil'\n  - 'numbered', 't'",
+        problem, value, context
+    ))
+}
+
+/// The method `--backup` without an argument picks: `$VERSION_CONTROL`,
+/// or existing.
+pub fn default_control() -> Result<Control, String> {
+    match env::var("VERSION_CONTROL") {
+        Ok(value) if !value.is_empty() => parse_control(&value, "$VERSION_CONTROL"),
+        _ => Ok(Control::Existing),
+    }
+}
+
+/// `$SIMPLE_BACKUP_SUFFIX`, or `~`.
+pub fn default_suffix() -> String {
+    match env::var("SIMPLE_BACKUP_SUFFIX") {
+        Ok(suffix) if !suffix.is_empty() && !suffix.contains('/') => suffix,
+        _ => String::from("~"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub control: Control,
+    pub suffix: String,
+}
+
+impl Backup {
+    pub fn none() -> Backup {
+        Backup {
+            control: Control::None,
+            suffix: String::from("~"),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.control != Control::None
+    }
+
+    /// The name the backup of `path` gets, or None when backups are off.
+    pub fn name(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        let file_name = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => return Ok(None),
+        };
+        let simple = || path.with_file_name(format!("{}{}", file_name, self.suffix));
+        let numbered = |last: u64| path.with_file_name(format!("{}.~{}~", file_name, last + 1));
+
+        match self.control {
+            Control::None => Ok(None),
+            Control::Simple => Ok(Some(simple())),
+            Control::Numbered => Ok(Some(numbered(last_number(path, &file_name)?.unwrap_or(0)))),
+            Control::Existing => match last_number(path, &file_name)? {
+                Some(last) => Ok(Some(numbered(last))),
+                None => Ok(Some(simple())),
+            },
+        }
+    }
+
+    /// Rename `path` out of the way, returning where it went.
+    pub fn make(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        let backup = self.name(path)?;
+        if let Some(backup) = &backup {
+            fs::rename(path, backup)?;
+        }
+        Ok(backup)
+    }
+}
+
+/// The highest N of the `name.~N~` backups next to `path`.
+fn last_number(path: &Path, name: &str) -> io::Result<Option<u64>> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let prefix = format!("{}.~", name);
+    let mut last = None;
+    for entry in fs::read_dir(dir)? {
+        let entry_name = entry?.file_name();
+        let number = entry_name
+            .to_str()
+            .and_then(|entry_name| entry_name.strip_prefix(&prefix))
+            .and_then(|rest| rest.strip_suffix('~'))
+            .filter(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse::<u64>().ok());
+        if number > last {
+            last = number;
+        }
+    }
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls() {
+        assert_eq!(parse_control("t", "backup type"), Ok(Control::Numbered));
+        assert_eq!(parse_control("nu", "backup type"), Ok(Control::Numbered));
+        assert_eq!(parse_control("off", "backup type"), Ok(Control::None));
+        assert_eq!(parse_control("ne", "backup type"), Ok(Control::Simple));
+        let err = parse_control("n", "backup type").unwrap_err();
+        assert!(err.starts_with("ambiguous argument 'n' for 'backup type'"));
+        let err = parse_control("bogus", "$VERSION_CONTROL").unwrap_err();
+        assert!(err.starts_with("invalid argument 'bogus' for '$VERSION_CONTROL'"));
+        assert!(parse_control("", "backup type").is_err());
+    }
+
+    #[test]
+    fn names() {
+        let temp = tempfile::tempdir().unwrap();
+        let file = temp.path().join("f");
+        let backup = |control| Backup {
+            control,
+            suffix: String::from(".bak"),
+        };
+
+        assert_eq!(backup(Control::None).name(&file).unwrap(), None);
+        assert_eq!(
+            backup(Control::Simple).name(&file).unwrap(),
+            Some(temp.path().join("f.bak"))
+        );
+        assert_eq!(
+            backup(Control::Existing).name(&file).unwrap(),
+            Some(temp.path().join("f.bak"))
+        );
+        assert_eq!(
+            backup(Control::Numbered).name(&file).unwrap(),
+            Some(temp.path().join("f.~1~"))
+        );
+
+        for name in &["f.~9~", "f.~10~", "f.~x~", "g.~20~", "f.~~"] {
+            fs::write(temp.path().join(name), "").unwrap();
+        }
+        assert_eq!(
+            backup(Control::Existing).name(&file).unwrap(),
+            Some(temp.path().join("f.~11~"))
+        );
+    }
+
+    #[test]
+    fn make() {
+        let temp = tempfile::tempdir().unwrap();
+        let file = temp.path().join("f");
+        let backup = Backup {
+            control: Control::Numbered,
+            suffix: String::from("~"),
+        };
+        for round in 1..=2 {
+            fs::write(&file, round.to_string()).unwrap();
+            let made = backup.make(&file).unwrap().unwrap();
+            assert_eq!(made, temp.path().join(format!("f.~{}~", round)));
+            assert!(!file.exists());
+        }
+        assert_eq!(fs::read_to_string(temp.path().join("f.~2~")).unwrap(), "2");
+    }
+}
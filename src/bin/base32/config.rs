@@ -0,0 +1,147 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+#[derive(Debug)]
+pub struct Base32Config {
+    pub file: String,
+    pub decode: bool,
+    pub ignore_garbage: bool,
+    /// Characters per line of encoded output; 0 for a single line.
+    pub wrap: usize,
+}
+
+const HELP_TEXT: &str = "Usage: base32 [OPTION]... [FILE]
+Base32 encode or decode FILE, or standard input, to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -d, --decode          decode data
+  -i, --ignore-garbage  when decoding, ignore non-alphabet characters
+  -w, --wrap=COLS       wrap encoded lines after COLS characters (76);
+                          0 disables line wrapping
+      --help     display this help and exit
+      --version  output version information and exit
+
+The data are encoded as described for the base32 alphabet in RFC 4648.
+When decoding, the input may contain newlines in addition to the bytes of
+the formal base32 alphabet.  Use --ignore-garbage to attempt to recover
+from any other non-alphabet bytes in the encoded stream.  Corrupt input
+is reported with the offset of the first bad byte, after the data decoded
+up to it.
+";
+
+impl Config for Base32Config {
+    fn bin_name(&self) -> &'static str {
+        "base32"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+impl Base32Config {
+    pub fn new() -> Base32Config {
+        Base32Config {
+            file: input::STDIN.to_string(),
+            decode: false,
+            ignore_garbage: false,
+            wrap: 76,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--decode", None) | ("-d", None) => self.decode = true,
+                ("--ignore-garbage", None) | ("-i", None) => self.ignore_garbage = true,
+                ("--wrap", Some(value)) | ("-w", Some(value)) => {
+                    self.wrap = value
+                        .parse()
+                        .map_err(|_| format!("invalid wrap size: '{}'", value))?
+                }
+                ("-w", None) => return Err("option requires an argument -- 'w'".to_string()),
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try base32 --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        if let Some(file) = operands.next() {
+            self.file = file;
+        }
+        if let Some(extra) = operands.next() {
+            return Err(format!(
+                "extra operand '{}'\n Try base32 --help for more information",
+                extra
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Base32Config;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<Base32Config, String> {
+        let mut config = Base32Config::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["base32.exe"]).unwrap();
+        assert_eq!(config.file, "-");
+        assert!(!config.decode);
+        assert!(!config.ignore_garbage);
+        assert_eq!(config.wrap, 76);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec!["base32.exe", "-di", "-w0", "file"]).unwrap();
+        assert_eq!(config.file, "file");
+        assert!(config.decode);
+        assert!(config.ignore_garbage);
+        assert_eq!(config.wrap, 0);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse(string_vec!["base32.exe", "-w", "-1"]).unwrap_err(),
+            "invalid wrap size: '-1'"
+        );
+        assert!(parse(string_vec!["base32.exe", "a", "b"])
+            .unwrap_err()
+            .starts_with("extra operand 'b'"));
+        assert_eq!(
+            parse(string_vec!["base32.exe", "-w"]).unwrap_err(),
+            "option requires an argument -- 'w'"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = Base32Config::new();
+        let parse_result = config.parse(string_vec!["base32.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = Base32Config::new();
+        let parse_result = config.parse(string_vec!["base32.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
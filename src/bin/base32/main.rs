@@ -0,0 +1,42 @@
+use std::env;
+use std::process;
+
+use coreutils::encoding::{self, Encoding, Error};
+use coreutils::input;
+use coreutils::output::Output;
+
+mod config;
+
+use config::Base32Config;
+
+fn main() {
+    let mut config = Base32Config::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut out = Output::stdout("base32");
+    let result = input::open(&config.file)
+        .map_err(Error::Io)
+        .and_then(|mut reader| {
+            if config.decode {
+                encoding::decode(
+                    &mut reader,
+                    &mut out,
+                    Encoding::Base32,
+                    config.ignore_garbage,
+                )
+            } else {
+                encoding::encode(&mut reader, &mut out, Encoding::Base32, config.wrap)
+            }
+        });
+    out.flush();
+    if let Err(e) = result {
+        match e {
+            Error::Io(_) => eprintln!("base32: {}: {}", input::display_name(&config.file), e),
+            Error::Invalid { .. } => eprintln!("base32: {}", e),
+        }
+        process::exit(1);
+    }
+}
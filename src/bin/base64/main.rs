@@ -0,0 +1,42 @@
+use std::env;
+use std::process;
+
+use coreutils::encoding::{self, Encoding, Error};
+use coreutils::input;
+use coreutils::output::Output;
+
+mod config;
+
+use config::Base64Config;
+
+fn main() {
+    let mut config = Base64Config::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut out = Output::stdout("base64");
+    let result = input::open(&config.file)
+        .map_err(Error::Io)
+        .and_then(|mut reader| {
+            if config.decode {
+                encoding::decode(
+                    &mut reader,
+                    &mut out,
+                    Encoding::Base64,
+                    config.ignore_garbage,
+                )
+            } else {
+                encoding::encode(&mut reader, &mut out, Encoding::Base64, config.wrap)
+            }
+        });
+    out.flush();
+    if let Err(e) = result {
+        match e {
+            Error::Io(_) => eprintln!("base64: {}: {}", input::display_name(&config.file), e),
+            Error::Invalid { .. } => eprintln!("base64: {}", e),
+        }
+        process::exit(1);
+    }
+}
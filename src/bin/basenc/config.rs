@@ -0,0 +1,177 @@
+use coreutils::config::{self, Config};
+use coreutils::encoding::Encoding;
+use coreutils::input;
+
+#[derive(Debug)]
+pub struct BasencConfig {
+    pub file: String,
+    pub encoding: Encoding,
+    pub decode: bool,
+    pub ignore_garbage: bool,
+    /// Characters per line of encoded output; 0 for a single line.
+    pub wrap: usize,
+}
+
+const HELP_TEXT: &str = "Usage: basenc [OPTION]... [FILE]
+Encode or decode FILE, or standard input, to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+      --base64          same as the base64 program (RFC 4648 section 4)
+      --base64url       file- and url-safe base64 (RFC 4648 section 5)
+      --base32          same as the base32 program (RFC 4648 section 6)
+      --base32hex       extended hex alphabet base32 (RFC 4648 section 7)
+      --base16          hex encoding (RFC 4648 section 8)
+      --base2msbf       bit string with the most significant bit first
+      --base2lsbf       bit string with the least significant bit first
+  -d, --decode          decode data
+  -i, --ignore-garbage  when decoding, ignore non-alphabet characters
+  -w, --wrap=COLS       wrap encoded lines after COLS characters (76);
+                          0 disables line wrapping
+      --z85             ascii85-like encoding (ZeroMQ spec:32/Z85); the
+                          input length must be a multiple of 4 when
+                          encoding and of 5 when decoding
+      --help     display this help and exit
+      --version  output version information and exit
+
+When decoding, the input may contain newlines in addition to the bytes of
+the formal alphabet.  Use --ignore-garbage to attempt to recover from any
+other non-alphabet bytes in the encoded stream.  Base16 input may be in
+either case.  Corrupt input is reported with the offset of the first bad
+byte, after the data decoded up to it.
+";
+
+impl Config for BasencConfig {
+    fn bin_name(&self) -> &'static str {
+        "basenc"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+impl BasencConfig {
+    pub fn new() -> BasencConfig {
+        BasencConfig {
+            file: input::STDIN.to_string(),
+            encoding: Encoding::Base64,
+            decode: false,
+            ignore_garbage: false,
+            wrap: 76,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let mut encoding = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--base64", None) => encoding = Some(Encoding::Base64),
+                ("--base64url", None) => encoding = Some(Encoding::Base64Url),
+                ("--base32", None) => encoding = Some(Encoding::Base32),
+                ("--base32hex", None) => encoding = Some(Encoding::Base32Hex),
+                ("--base16", None) => encoding = Some(Encoding::Base16),
+                ("--base2msbf", None) => encoding = Some(Encoding::Base2Msbf),
+                ("--base2lsbf", None) => encoding = Some(Encoding::Base2Lsbf),
+                ("--z85", None) => encoding = Some(Encoding::Z85),
+                ("--decode", None) | ("-d", None) => self.decode = true,
+                ("--ignore-garbage", None) | ("-i", None) => self.ignore_garbage = true,
+                ("--wrap", Some(value)) | ("-w", Some(value)) => {
+                    self.wrap = value
+                        .parse()
+                        .map_err(|_| format!("invalid wrap size: '{}'", value))?
+                }
+                ("-w", None) => return Err("option requires an argument -- 'w'".to_string()),
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try basenc --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        self.encoding = encoding.ok_or_else(|| {
+            "missing encoding type\n Try basenc --help for more information".to_string()
+        })?;
+
+        let mut operands = base_config.parameters.into_iter();
+        if let Some(file) = operands.next() {
+            self.file = file;
+        }
+        if let Some(extra) = operands.next() {
+            return Err(format!(
+                "extra operand '{}'\n Try basenc --help for more information",
+                extra
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::BasencConfig;
+    use coreutils::encoding::Encoding;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<BasencConfig, String> {
+        let mut config = BasencConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["basenc.exe", "--base32"]).unwrap();
+        assert_eq!(config.file, "-");
+        assert_eq!(config.encoding, Encoding::Base32);
+        assert!(!config.decode);
+        assert!(!config.ignore_garbage);
+        assert_eq!(config.wrap, 76);
+    }
+
+    #[test]
+    fn encodings() {
+        let config = parse(string_vec!["basenc.exe", "--base16", "--z85", "-d", "f"]).unwrap();
+        assert_eq!(config.encoding, Encoding::Z85);
+        assert!(config.decode);
+        assert_eq!(config.file, "f");
+        assert_eq!(
+            parse(string_vec!["basenc.exe", "--base2lsbf"])
+                .unwrap()
+                .encoding,
+            Encoding::Base2Lsbf
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse(string_vec!["basenc.exe", "file"])
+            .unwrap_err()
+            .starts_with("missing encoding type"));
+        assert_eq!(
+            parse(string_vec!["basenc.exe", "--base64", "-w", "x"]).unwrap_err(),
+            "invalid wrap size: 'x'"
+        );
+        assert!(parse(string_vec!["basenc.exe", "--base64", "a", "b"])
+            .unwrap_err()
+            .starts_with("extra operand 'b'"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = BasencConfig::new();
+        let parse_result = config.parse(string_vec!["basenc.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = BasencConfig::new();
+        let parse_result = config.parse(string_vec!["basenc.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
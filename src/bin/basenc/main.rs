@@ -0,0 +1,42 @@
+use std::env;
+use std::process;
+
+use coreutils::encoding::{self, Error};
+use coreutils::input;
+use coreutils::output::Output;
+
+mod config;
+
+use config::BasencConfig;
+
+fn main() {
+    let mut config = BasencConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut out = Output::stdout("basenc");
+    let result = input::open(&config.file)
+        .map_err(Error::Io)
+        .and_then(|mut reader| {
+            if config.decode {
+                encoding::decode(
+                    &mut reader,
+                    &mut out,
+                    config.encoding,
+                    config.ignore_garbage,
+                )
+            } else {
+                encoding::encode(&mut reader, &mut out, config.encoding, config.wrap)
+            }
+        });
+    out.flush();
+    if let Err(e) = result {
+        match e {
+            Error::Io(_) => eprintln!("basenc: {}: {}", input::display_name(&config.file), e),
+            Error::Invalid { .. } => eprintln!("basenc: {}", e),
+        }
+        process::exit(1);
+    }
+}
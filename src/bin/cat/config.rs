@@ -25,6 +25,7 @@ Concatenate FILE(s) to standard output.
     -t                       equivalent to -vT
     -T, --show-tabs          display TAB characters as ^I
     -v, --show-nonprinting   use ^ and M- notation, except for LFD and TAB
+        --no-glob            do not expand wildcards in FILE operands
         --help     display this help and exit
         --version  output version information and exit
 ";
@@ -34,7 +35,10 @@ impl Config for CatConfig {
         "cat"
     }
     fn usage(&self) -> &'static str {
-        &HELP_TEXT
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
     }
 }
 
@@ -52,10 +56,7 @@ impl CatConfig {
     }
 
     pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
-        let base_config = match Config::parse(self, &args) {
-            Ok(config) => config,
-            Err(e) => return Err(e),
-        };
+        let base_config = Config::parse(self, &args)?;
 
         let mut files: Vec<PathBuf> = base_config.parameters.iter().map(PathBuf::from).collect();
         self.files.append(&mut files);
@@ -292,6 +293,60 @@ mod tests {
         assert!(config.show_nonprinting);
     }
 
+    #[test]
+    fn bundled_flags() {
+        let mut config = CatConfig::new();
+        config
+            .parse(string_vec!["cat.exe", "-vET", "myfile.txt"])
+            .unwrap();
+        assert!(config.show_nonprinting);
+        assert!(config.show_ends);
+        assert!(config.show_tabs);
+        assert!(!config.number);
+    }
+
+    #[test]
+    fn dash_operands() {
+        let mut config = CatConfig::new();
+        config
+            .parse(string_vec!["cat.exe", "-", "--", "-n", "--no-glob"])
+            .unwrap();
+        let files: Vec<String> = config
+            .files
+            .iter()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(files, vec!["-", "-n", "--no-glob"]);
+        assert!(!config.number);
+    }
+
+    #[test]
+    fn no_glob() {
+        let mut config = CatConfig::new();
+        config
+            .parse(string_vec!["cat.exe", "*.txt", "--no-glob"])
+            .unwrap();
+        assert_eq!(config.files.len(), 1);
+        assert!(config.files.iter().any(|x| x.to_string_lossy() == "*.txt"));
+    }
+
+    #[test]
+    fn completions() {
+        let config = CatConfig::new();
+        let args: Vec<String> = string_vec!["cat.exe", "--generate-completions=bash"];
+        let script = coreutils::config::Config::check_completions(&config, &args)
+            .unwrap()
+            .unwrap();
+        for option in &["-A", "--show-all", "-v", "--show-nonprinting", "--no-glob"] {
+            assert!(script.contains(option));
+        }
+
+        let args: Vec<String> = string_vec!["cat.exe", "--generate-completions=csh"];
+        assert!(coreutils::config::Config::check_completions(&config, &args)
+            .unwrap()
+            .is_err());
+    }
+
     #[test]
     fn help() {
         let mut config = CatConfig::new();
@@ -14,10 +14,8 @@ pub fn number_nonblank_lines(contents: String) -> String {
 
 pub fn number_lines(contents: String) -> String {
     let mut modified = String::new();
-    let mut line_num = 1;
-    for line in contents.lines() {
-        modified.push_str(format!("\t {} {}\r\n", line_num, line).as_str());
-        line_num += 1
+    for (idx, line) in contents.lines().enumerate() {
+        modified.push_str(format!("\t {} {}\r\n", idx + 1, line).as_str());
     }
     modified
 }
@@ -1,9 +1,11 @@
 use std::env;
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{BufReader, Read};
 use std::path::PathBuf;
 use std::process;
 
+use coreutils::output::Output;
+
 mod config;
 mod format;
 
@@ -15,15 +17,17 @@ macro_rules! file_err {
 
 fn main() {
     let mut config = config::CatConfig::new();
-    if let Err(e) = config.parse(env::args().collect()) {
-        eprintln!("{}", e);
-        process::exit(1);
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
     }
 
+    let mut out = Output::stdout("cat");
     for file in config.files {
         let mut contents = match parse_file(&file) {
             Ok(contents) => contents,
             Err(e) => {
+                out.flush();
                 eprintln!("cat: {}", e.as_str());
                 process::exit(1);
             }
@@ -48,7 +52,7 @@ fn main() {
             contents = format::show_nonprinting(contents);
         }
 
-        print!("{}", contents);
+        out.write_str(&contents);
     }
 }
 
@@ -67,7 +71,10 @@ pub fn parse_file(path: &PathBuf) -> Result<String, String> {
         Err(e) => return file_err!(path, e),
     };
 
-    let bytes: Vec<u8> = file.bytes().filter_map(|bytes| bytes.ok()).collect();
+    let bytes: Vec<u8> = BufReader::new(file)
+        .bytes()
+        .filter_map(|bytes| bytes.ok())
+        .collect();
 
     Ok(String::from_utf8_lossy(&bytes).to_string())
 }
@@ -81,7 +88,7 @@ mod tests {
         let path = test_utils::get_path("ascii.txt");
         let contents = match crate::parse_file(&path) {
             Ok(contents) => contents,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
         assert_eq!(contents, test_utils::ASCII)
     }
@@ -91,7 +98,7 @@ mod tests {
         let path = test_utils::get_path("hello.bin");
         let contents = match crate::parse_file(&path) {
             Ok(contents) => contents,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
         assert!(contents.contains("Hello"))
     }
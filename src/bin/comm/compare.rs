@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+use coreutils::input;
+use coreutils::lines::LineReader;
+use coreutils::output;
+
+use crate::config::{CheckOrder, CommConfig};
+
+/// One sorted input, with a copy of its previous line to check the order.
+struct Input<R: BufRead> {
+    lines: LineReader<R>,
+    name: String,
+    /// Whether `lines` holds a line; false once the input ends.
+    current: bool,
+    previous: Vec<u8>,
+}
+
+impl<R: BufRead> Input<R> {
+    fn new(reader: R, name: &str, delimiter: u8) -> Self {
+        Input {
+            lines: LineReader::new(reader, delimiter),
+            name: name.to_string(),
+            current: false,
+            previous: Vec::new(),
+        }
+    }
+
+    /// Move to the next line, returning whether it sorts before the one
+    /// it follows. Read errors name the file.
+    fn advance(&mut self) -> io::Result<bool> {
+        let started = self.current;
+        self.previous.clear();
+        self.previous.extend_from_slice(self.lines.line());
+        self.current = self.lines.next_line().map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!(
+                    "{}: {}",
+                    input::display_name(&self.name),
+                    output::describe(&e)
+                ),
+            )
+        })?;
+        Ok(started && self.current && self.previous.as_slice() > self.lines.line())
+    }
+}
+
+/// Compare two sorted inputs, writing each line in the column saying
+/// which of them have it. Returns false when either was out of order.
+pub fn compare<A: BufRead, B: BufRead, W: Write>(
+    first: A,
+    second: B,
+    out: &mut W,
+    config: &CommConfig,
+) -> io::Result<bool> {
+    let delimiter = config.delimiter();
+    let mut first = Input::new(first, &config.files[0], delimiter);
+    let mut second = Input::new(second, &config.files[1], delimiter);
+    first.advance()?;
+    second.advance()?;
+
+    let mut counts = [0u64; 3];
+    let mut unpaired = false;
+    let mut reported = [false; 2];
+    loop {
+        let column = match (first.current, second.current) {
+            (false, false) => break,
+            (true, false) => 0,
+            (false, true) => 1,
+            (true, true) => match first.lines.line().cmp(second.lines.line()) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => 2,
+            },
+        };
+        counts[column] += 1;
+        unpaired |= column < 2;
+
+        if !config.suppress[column] {
+            let (line, terminator) = if column == 1 {
+                (second.lines.line(), second.lines.terminator())
+            } else {
+                (first.lines.line(), first.lines.terminator())
+            };
+            for _ in config.suppress[..column]
+                .iter()
+                .filter(|&&suppressed| !suppressed)
+            {
+                out.write_all(&config.output_delimiter)?;
+            }
+            out.write_all(line)?;
+            match terminator {
+                [] => out.write_all(&[delimiter])?,
+                terminator => out.write_all(terminator)?,
+            }
+        }
+
+        let checking = match config.check_order {
+            CheckOrder::Always => true,
+            CheckOrder::Unpaired => unpaired,
+            CheckOrder::Never => false,
+        };
+        let mut disordered = [false; 2];
+        if column != 1 {
+            disordered[0] = first.advance()?;
+        }
+        if column != 0 {
+            disordered[1] = second.advance()?;
+        }
+        for (file, reported) in reported.iter_mut().enumerate() {
+            if !checking || !disordered[file] || *reported {
+                continue;
+            }
+            let message = format!("file {} is not in sorted order", file + 1);
+            if config.check_order == CheckOrder::Always {
+                return Err(io::Error::other(message));
+            }
+            out.flush()?;
+            eprintln!("comm: {}", message);
+            *reported = true;
+        }
+    }
+
+    if config.total {
+        let separator = &config.output_delimiter;
+        for count in counts {
+            out.write_all(count.to_string().as_bytes())?;
+            out.write_all(separator)?;
+        }
+        out.write_all(b"total")?;
+        out.write_all(&[delimiter])?;
+    }
+    Ok(reported == [false; 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn comm(args: &[&str], first: &str, second: &str) -> (String, bool) {
+        let mut all: Vec<String> = string_vec!["comm.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        all.extend(["a", "b"].map(String::from));
+        let mut config = CommConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        let sorted = compare(first.as_bytes(), second.as_bytes(), &mut out, &config).unwrap();
+        (String::from_utf8(out).unwrap(), sorted)
+    }
+
+    fn columns(args: &[&str], first: &str, second: &str) -> String {
+        comm(args, first, second).0
+    }
+
+    #[test]
+    fn three_columns() {
+        assert_eq!(
+            columns(&[], "a\nb\nd\n", "b\nc\nd\ne"),
+            "a\n\t\tb\n\tc\n\t\td\n\te\n"
+        );
+        assert_eq!(columns(&[], "", ""), "");
+        assert_eq!(columns(&[], "x\r\n", "x\n"), "\t\tx\r\n");
+    }
+
+    #[test]
+    fn suppressed() {
+        let first = "a\nb\nd\n";
+        let second = "b\nc\nd\n";
+        assert_eq!(columns(&["-12"], first, second), "b\nd\n");
+        assert_eq!(columns(&["-3"], first, second), "a\n\tc\n");
+        assert_eq!(columns(&["-1"], first, second), "\tb\nc\n\td\n");
+        assert_eq!(
+            columns(&["--output-delimiter=::"], first, second),
+            "a\n::::b\n::c\n::::d\n"
+        );
+    }
+
+    #[test]
+    fn totals() {
+        assert_eq!(
+            columns(&["--total", "-3"], "a\nb\n", "a\nc\nd\n"),
+            "b\n\tc\n\td\n1\t2\t1\ttotal\n"
+        );
+        assert_eq!(
+            columns(&["--total", "-z"], "a\0", "a\0"),
+            "\t\ta\x000\t0\t1\ttotal\0"
+        );
+    }
+
+    #[test]
+    fn order_checks() {
+        // Disorder counts once a line is unpaired, unless asked for sooner.
+        assert_eq!(
+            comm(&[], "b\na\n", "a\nb\n"),
+            ("\ta\n\t\tb\na\n".into(), false)
+        );
+        assert_eq!(
+            comm(&[], "b\na\n", "b\na\n"),
+            ("\t\tb\n\t\ta\n".into(), true)
+        );
+        assert!(!comm(&["--check-order", "-3"], "a\nb\n", "c\n").0.is_empty());
+        assert_eq!(
+            comm(&["--nocheck-order"], "b\na\n", "a\n"),
+            ("\ta\nb\na\n".into(), true)
+        );
+
+        let mut config = CommConfig::new();
+        config
+            .parse(string_vec!["comm.exe", "--check-order", "a", "b"])
+            .unwrap();
+        let error = compare(&b"b\na\n"[..], &b"b\na\n"[..], &mut Vec::new(), &config).unwrap_err();
+        assert_eq!(error.to_string(), "file 1 is not in sorted order");
+    }
+}
@@ -0,0 +1,206 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+/// When lines out of order are reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckOrder {
+    /// Once a line has turned up in only one file, as GNU comm does.
+    Unpaired,
+    /// From the start, stopping at the first line out of order.
+    Always,
+    Never,
+}
+
+#[derive(Debug)]
+pub struct CommConfig {
+    pub files: [String; 2],
+    /// Columns left out: lines only in the first file, lines only in the
+    /// second and lines in both.
+    pub suppress: [bool; 3],
+    pub check_order: CheckOrder,
+    pub output_delimiter: Vec<u8>,
+    pub total: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: comm [OPTION]... FILE1 FILE2
+Compare sorted files FILE1 and FILE2 line by line.
+
+When FILE1 or FILE2 (not both) is -, read standard input.
+
+With no options, produce three-column output.  Column one contains
+lines unique to FILE1, column two contains lines unique to FILE2,
+and column three contains lines common to both files.
+
+  -1                      suppress column 1 (lines unique to FILE1)
+  -2                      suppress column 2 (lines unique to FILE2)
+  -3                      suppress column 3 (lines that appear in both files)
+      --check-order       check that the input is correctly sorted, even
+                            if all input lines are pairable
+      --nocheck-order     do not check that the input is correctly sorted
+      --output-delimiter=STR  separate columns with STR
+      --total             output a summary
+  -z, --zero-terminated   line delimiter is NUL, not newline
+      --help     display this help and exit
+      --version  output version information and exit
+
+Lines are compared byte by byte, the order sort uses.  A carriage return
+before the newline is not part of the line.
+";
+
+impl Config for CommConfig {
+    fn bin_name(&self) -> &'static str {
+        "comm"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try comm --help for more information", message)
+}
+
+impl CommConfig {
+    pub fn new() -> CommConfig {
+        CommConfig {
+            files: [String::new(), String::new()],
+            suppress: [false; 3],
+            check_order: CheckOrder::Unpaired,
+            output_delimiter: vec![b'\t'],
+            total: false,
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("-1", None) => self.suppress[0] = true,
+                ("-2", None) => self.suppress[1] = true,
+                ("-3", None) => self.suppress[2] = true,
+                ("--check-order", None) => self.check_order = CheckOrder::Always,
+                ("--nocheck-order", None) => self.check_order = CheckOrder::Never,
+                ("--output-delimiter", Some(text)) => {
+                    // An empty output delimiter is a NUL byte, as in GNU comm.
+                    self.output_delimiter = match text {
+                        "" => vec![b'\0'],
+                        text => text.as_bytes().to_vec(),
+                    }
+                }
+                ("--total", None) => self.total = true,
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                _ => {
+                    return Err(usage_error(&format!("invalid option: {}", option)));
+                }
+            }
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        match (operands.next(), operands.next(), operands.next()) {
+            (None, _, _) => return Err(usage_error("missing operand")),
+            (Some(first), None, _) => {
+                return Err(usage_error(&format!("missing operand after '{}'", first)))
+            }
+            (Some(_), Some(_), Some(extra)) => {
+                return Err(usage_error(&format!("extra operand '{}'", extra)))
+            }
+            (Some(first), Some(second), None) => self.files = [first, second],
+        }
+        if self.files.iter().all(|file| file == input::STDIN) {
+            return Err(String::from("both files cannot be standard input"));
+        }
+        Ok(())
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{CheckOrder, CommConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<CommConfig, String> {
+        let mut config = CommConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["comm.exe", "a", "b"]).unwrap();
+        assert_eq!(config.files, ["a", "b"]);
+        assert_eq!(config.suppress, [false; 3]);
+        assert_eq!(config.check_order, CheckOrder::Unpaired);
+        assert_eq!(config.output_delimiter, b"\t");
+        assert!(!config.total);
+        assert_eq!(config.delimiter(), b'\n');
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec![
+            "comm.exe",
+            "-13",
+            "--total",
+            "--check-order",
+            "--output-delimiter=|",
+            "-z",
+            "a",
+            "-"
+        ])
+        .unwrap();
+        assert_eq!(config.suppress, [true, false, true]);
+        assert!(config.total);
+        assert_eq!(config.check_order, CheckOrder::Always);
+        assert_eq!(config.output_delimiter, b"|");
+        assert_eq!(config.delimiter(), b'\0');
+
+        let config = parse(string_vec![
+            "comm.exe",
+            "--nocheck-order",
+            "--output-delimiter=",
+            "a",
+            "b"
+        ])
+        .unwrap();
+        assert_eq!(config.check_order, CheckOrder::Never);
+        assert_eq!(config.output_delimiter, b"\0");
+    }
+
+    #[test]
+    fn operands() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert!(error(string_vec!["comm.exe"]).starts_with("missing operand\n"));
+        assert!(error(string_vec!["comm.exe", "a"]).starts_with("missing operand after 'a'"));
+        assert!(error(string_vec!["comm.exe", "a", "b", "c"]).starts_with("extra operand 'c'"));
+        assert_eq!(
+            error(string_vec!["comm.exe", "-", "-"]),
+            "both files cannot be standard input"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = CommConfig::new();
+        let parse_result = config.parse(string_vec!["comm.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = CommConfig::new();
+        let parse_result = config.parse(string_vec!["comm.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
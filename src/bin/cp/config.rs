@@ -0,0 +1,542 @@
+use coreutils::backup::{self, Backup};
+use coreutils::config::{self, Config};
+use coreutils::copy::{Method, Options, Overwrite, Preserve, Reflink, Sparse};
+use coreutils::walk::Follow;
+
+#[derive(Debug)]
+pub struct CpConfig {
+    pub files: Vec<String>,
+    pub target_directory: Option<String>,
+    pub no_target_directory: bool,
+    pub parents: bool,
+    pub options: Options,
+}
+
+const HELP_TEXT: &str = "Usage: cp [OPTION]... [-T] SOURCE DEST
+  or:  cp [OPTION]... SOURCE... DIRECTORY
+  or:  cp [OPTION]... -t DIRECTORY SOURCE...
+Copy SOURCE to DEST, or multiple SOURCE(s) to DIRECTORY.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -a, --archive                same as -dR --preserve=all
+      --backup[=CONTROL]       make a backup of each existing destination file
+  -b                           like --backup but does not accept an argument
+  -d                           same as --no-dereference --preserve=links
+  -f, --force                  if an existing destination file cannot be
+                                 opened, remove it and try again (this option
+                                 is ignored when the -n option is also used)
+  -i, --interactive            prompt before overwrite (overrides a previous -n
+                                  option)
+  -H                           follow command-line symbolic links in SOURCE
+  -l, --link                   hard link files instead of copying
+  -L, --dereference            always follow symbolic links in SOURCE
+  -n, --no-clobber             do not overwrite an existing file (overrides
+                                 a previous -i option)
+  -P, --no-dereference         never follow symbolic links in SOURCE
+  -p                           same as --preserve=mode,ownership,timestamps
+      --preserve[=ATTR_LIST]   preserve the specified attributes (default:
+                                 mode,ownership,timestamps), if possible
+                                 additional attributes: links, xattr, all
+      --no-preserve=ATTR_LIST  don't preserve the specified attributes
+      --parents                use full source file name under DIRECTORY
+  -r, --recursive              copy directories recursively
+  -R                           same as -r
+      --reflink[=WHEN]         control clone/CoW copies. See below
+      --sparse=WHEN            control creation of sparse files. See below
+  -s, --symbolic-link          make symbolic links instead of copying
+  -S, --suffix=SUFFIX          override the usual backup suffix
+  -t, --target-directory=DIRECTORY  copy all SOURCE arguments into DIRECTORY
+  -T, --no-target-directory    treat DEST as a normal file
+  -u, --update                 copy only when the SOURCE file is newer
+                                 than the destination file or when the
+                                 destination file is missing
+  -v, --verbose                explain what is being done
+      --no-glob                do not expand wildcards in operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+By default, sparse SOURCE files are detected by a crude heuristic and the
+corresponding DEST file is made sparse as well.  That is the behavior
+selected by --sparse=auto.  Specify --sparse=always to create a sparse DEST
+file whenever the SOURCE file contains a long enough sequence of zero bytes.
+Use --sparse=never to inhibit creation of sparse files.
+
+When --reflink[=always] is specified, perform a lightweight copy, where the
+data blocks are copied only when modified.  If this is not possible the copy
+fails, or if --reflink=auto is specified, fall back to a standard copy.
+Use --reflink=never to ensure a standard copy is performed.
+
+The backup suffix is '~', unless set with --suffix or SIMPLE_BACKUP_SUFFIX.
+The version control method may be selected via the --backup option or through
+the VERSION_CONTROL environment variable.  Here are the values:
+
+  none, off       never make backups (even if --backup is given)
+  numbered, t     make numbered backups
+  existing, nil   numbered if numbered backups exist, simple otherwise
+  simple, never   always make simple backups
+
+On Windows, ownership is left alone, and xattr stands for the hidden,
+system, archive and not-indexed attributes.
+";
+
+impl Config for CpConfig {
+    fn bin_name(&self) -> &'static str {
+        "cp"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try cp --help for more information", message)
+}
+
+fn invalid_argument(value: &str, option: &str, valid: &[&str]) -> String {
+    let valid: Vec<String> = valid.iter().map(|name| format!("  - '{}'", name)).collect();
+    usage_error(&format!(
+        "invalid argument '{}' for '{}'\nValid arguments are:\n{}",
+        value,
+        option,
+        valid.join("\n")
+    ))
+}
+
+/// Turn each attribute in a `--preserve` or `--no-preserve` list on or off.
+fn set_attributes(
+    preserve: &mut Preserve,
+    list: &str,
+    option: &str,
+    on: bool,
+) -> Result<(), String> {
+    for name in list.split(',') {
+        match name {
+            "mode" => preserve.mode = on,
+            "ownership" => preserve.ownership = on,
+            "timestamps" => preserve.timestamps = on,
+            "links" => preserve.links = on,
+            "xattr" => preserve.xattr = on,
+            "all" => {
+                preserve.mode = on;
+                preserve.ownership = on;
+                preserve.timestamps = on;
+                preserve.links = on;
+                preserve.xattr = on;
+            }
+            _ => {
+                return Err(invalid_argument(
+                    name,
+                    option,
+                    &["mode", "timestamps", "ownership", "links", "xattr", "all"],
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+impl CpConfig {
+    pub fn new() -> CpConfig {
+        CpConfig {
+            files: Vec::new(),
+            target_directory: None,
+            no_target_directory: false,
+            parents: false,
+            options: Options::default(),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let options = &mut self.options;
+        let mut follow = None;
+        let mut make_backups = false;
+        let mut control = None;
+        let mut suffix = None;
+        let mut hard_link = false;
+        let mut symbolic_link = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--archive", None) | ("-a", None) => {
+                    options.recursive = true;
+                    follow = Some(Follow::Never);
+                    options.preserve = Preserve::all();
+                }
+                ("--backup", None) | ("-b", None) => make_backups = true,
+                ("--backup", Some(value)) => {
+                    make_backups = true;
+                    control = Some(
+                        backup::parse_control(value, "backup type").map_err(|e| usage_error(&e))?,
+                    );
+                }
+                ("-d", None) => {
+                    follow = Some(Follow::Never);
+                    options.preserve.links = true;
+                }
+                ("--force", None) | ("-f", None) => options.force = true,
+                ("--interactive", None) | ("-i", None) => options.overwrite = Overwrite::Prompt,
+                ("-H", None) => follow = Some(Follow::Roots),
+                ("--link", None) | ("-l", None) => hard_link = true,
+                ("--dereference", None) | ("-L", None) => follow = Some(Follow::Always),
+                ("--no-clobber", None) | ("-n", None) => options.overwrite = Overwrite::Never,
+                ("--no-dereference", None) | ("-P", None) => follow = Some(Follow::Never),
+                ("-p", None) => set_attributes(
+                    &mut options.preserve,
+                    "mode,ownership,timestamps",
+                    "-p",
+                    true,
+                )?,
+                ("--preserve", list) => {
+                    let list = list.unwrap_or("mode,ownership,timestamps");
+                    set_attributes(&mut options.preserve, list, "--preserve", true)?;
+                    options.preserve.required = true;
+                }
+                ("--no-preserve", Some(list)) => {
+                    set_attributes(&mut options.preserve, list, "--no-preserve", false)?
+                }
+                ("--parents", None) => self.parents = true,
+                ("--recursive", None) | ("-r", None) | ("-R", None) => options.recursive = true,
+                ("--reflink", None) => options.reflink = Reflink::Always,
+                ("--reflink", Some(value)) => {
+                    options.reflink = match value {
+                        "auto" => Reflink::Auto,
+                        "always" => Reflink::Always,
+                        "never" => Reflink::Never,
+                        _ => {
+                            return Err(invalid_argument(
+                                value,
+                                "--reflink",
+                                &["auto", "always", "never"],
+                            ))
+                        }
+                    }
+                }
+                ("--sparse", Some(value)) => {
+                    options.sparse = match value {
+                        "never" => Sparse::Never,
+                        "auto" => Sparse::Auto,
+                        "always" => Sparse::Always,
+                        _ => {
+                            return Err(invalid_argument(
+                                value,
+                                "--sparse",
+                                &["never", "auto", "always"],
+                            ))
+                        }
+                    }
+                }
+                ("--symbolic-link", None) | ("-s", None) => symbolic_link = true,
+                ("--suffix", Some(value)) | ("-S", Some(value)) => {
+                    make_backups = true;
+                    suffix = Some(value.to_string());
+                }
+                ("--target-directory", Some(dir)) | ("-t", Some(dir)) => {
+                    self.target_directory = Some(dir.to_string())
+                }
+                ("--no-target-directory", None) | ("-T", None) => self.no_target_directory = true,
+                ("--update", None) | ("-u", None) => options.update = true,
+                ("--verbose", None) | ("-v", None) => options.verbose = true,
+                (name, None) if name.len() == 2 && "St".contains(&name[1..]) => {
+                    return Err(usage_error(&format!(
+                        "option requires an argument -- '{}'",
+                        &name[1..]
+                    )))
+                }
+                _ => return Err(usage_error(&format!("invalid option: {}", option))),
+            }
+        }
+
+        options.follow = follow.unwrap_or(match options.recursive {
+            true => Follow::Never,
+            false => Follow::Roots,
+        });
+        options.method = match (hard_link, symbolic_link) {
+            (true, true) => return Err(usage_error("cannot make both hard and symbolic links")),
+            (true, false) => Method::HardLink,
+            (false, true) => Method::SymbolicLink,
+            (false, false) => Method::Copy,
+        };
+        if make_backups {
+            if options.overwrite == Overwrite::Never {
+                return Err(usage_error(
+                    "options --backup and --no-clobber are mutually exclusive",
+                ));
+            }
+            let control = match control {
+                Some(control) => control,
+                None => backup::default_control().map_err(|e| usage_error(&e))?,
+            };
+            options.backup = Backup {
+                control,
+                suffix: suffix.unwrap_or_else(backup::default_suffix),
+            };
+        }
+        if options.reflink == Reflink::Always && options.sparse != Sparse::Auto {
+            return Err(usage_error("--reflink can be used only with --sparse=auto"));
+        }
+
+        self.files = base_config.parameters;
+        if self.target_directory.is_some() && self.no_target_directory {
+            return Err(String::from(
+                "cannot combine --target-directory (-t) and --no-target-directory (-T)",
+            ));
+        }
+        match self.files.len() {
+            0 => return Err(usage_error("missing file operand")),
+            1 if self.target_directory.is_none() => {
+                return Err(usage_error(&format!(
+                    "missing destination file operand after '{}'",
+                    self.files[0]
+                )))
+            }
+            count if count > 2 && self.no_target_directory => {
+                return Err(usage_error(&format!("extra operand '{}'", self.files[2])))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::CpConfig;
+    use coreutils::backup::Control;
+    use coreutils::copy::{Method, Overwrite, Preserve, Reflink, Sparse};
+    use coreutils::string_vec;
+    use coreutils::walk::Follow;
+
+    fn parse(args: Vec<String>) -> Result<CpConfig, String> {
+        let mut config = CpConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["cp.exe", "a", "b"]).unwrap();
+        assert_eq!(config.files, vec!["a", "b"]);
+        assert_eq!(config.target_directory, None);
+        assert!(!config.no_target_directory);
+        assert!(!config.parents);
+        let options = config.options;
+        assert!(!options.recursive);
+        assert_eq!(options.follow, Follow::Roots);
+        assert_eq!(options.method, Method::Copy);
+        assert_eq!(options.overwrite, Overwrite::Always);
+        assert_eq!(options.preserve, Preserve::default());
+        assert_eq!(options.reflink, Reflink::Never);
+        assert_eq!(options.sparse, Sparse::Auto);
+        assert_eq!(options.backup.control, Control::None);
+    }
+
+    #[test]
+    fn archive() {
+        let options = parse(string_vec!["cp.exe", "-a", "a", "b"])
+            .unwrap()
+            .options;
+        assert!(options.recursive);
+        assert_eq!(options.follow, Follow::Never);
+        assert_eq!(options.preserve, Preserve::all());
+
+        let options = parse(string_vec![
+            "cp.exe",
+            "-aL",
+            "--no-preserve=links,xattr",
+            "a",
+            "b"
+        ])
+        .unwrap()
+        .options;
+        assert_eq!(options.follow, Follow::Always);
+        assert!(options.preserve.mode && !options.preserve.links && !options.preserve.xattr);
+    }
+
+    #[test]
+    fn following() {
+        let follow = |args: Vec<String>| parse(args).unwrap().options.follow;
+        assert_eq!(follow(string_vec!["cp.exe", "-r", "a", "b"]), Follow::Never);
+        assert_eq!(
+            follow(string_vec!["cp.exe", "-RH", "a", "b"]),
+            Follow::Roots
+        );
+        assert_eq!(follow(string_vec!["cp.exe", "-P", "a", "b"]), Follow::Never);
+        assert_eq!(follow(string_vec!["cp.exe", "-d", "a", "b"]), Follow::Never);
+        assert_eq!(
+            follow(string_vec!["cp.exe", "-P", "-L", "a", "b"]),
+            Follow::Always
+        );
+    }
+
+    #[test]
+    fn preserve() {
+        let preserve = |args: Vec<String>| parse(args).unwrap().options.preserve;
+        let p = preserve(string_vec!["cp.exe", "-p", "a", "b"]);
+        assert!(p.mode && p.ownership && p.timestamps && !p.links && !p.xattr && !p.required);
+        let p = preserve(string_vec!["cp.exe", "--preserve", "a", "b"]);
+        assert!(p.mode && p.ownership && p.timestamps && p.required);
+        let p = preserve(string_vec!["cp.exe", "--preserve=links,xattr", "a", "b"]);
+        assert!(!p.mode && p.links && p.xattr);
+        let p = preserve(string_vec![
+            "cp.exe",
+            "--preserve=all",
+            "--no-preserve=mode",
+            "a",
+            "b"
+        ]);
+        assert!(!p.mode && p.ownership && p.links);
+        let p = preserve(string_vec!["cp.exe", "-d", "a", "b"]);
+        assert!(p.links && !p.mode);
+    }
+
+    #[test]
+    fn overwriting() {
+        let overwrite = |args: Vec<String>| parse(args).unwrap().options.overwrite;
+        assert_eq!(
+            overwrite(string_vec!["cp.exe", "-i", "a", "b"]),
+            Overwrite::Prompt
+        );
+        assert_eq!(
+            overwrite(string_vec!["cp.exe", "-in", "a", "b"]),
+            Overwrite::Never
+        );
+        assert_eq!(
+            overwrite(string_vec!["cp.exe", "-ni", "a", "b"]),
+            Overwrite::Prompt
+        );
+
+        let options = parse(string_vec!["cp.exe", "-fuv", "a", "b"])
+            .unwrap()
+            .options;
+        assert!(options.force && options.update && options.verbose);
+    }
+
+    #[test]
+    fn methods() {
+        let method = |args: Vec<String>| parse(args).unwrap().options.method;
+        assert_eq!(
+            method(string_vec!["cp.exe", "-l", "a", "b"]),
+            Method::HardLink
+        );
+        assert_eq!(
+            method(string_vec!["cp.exe", "--symbolic-link", "a", "b"]),
+            Method::SymbolicLink
+        );
+        assert_eq!(
+            parse(string_vec!["cp.exe", "-ls", "a", "b"]).unwrap_err(),
+            "cannot make both hard and symbolic links\n Try cp --help for more information"
+        );
+    }
+
+    #[test]
+    fn data() {
+        let options = parse(string_vec![
+            "cp.exe",
+            "--reflink",
+            "--sparse=auto",
+            "a",
+            "b"
+        ])
+        .unwrap()
+        .options;
+        assert_eq!(options.reflink, Reflink::Always);
+        let options = parse(string_vec![
+            "cp.exe",
+            "--reflink=auto",
+            "--sparse=never",
+            "a",
+            "b"
+        ])
+        .unwrap()
+        .options;
+        assert_eq!(options.reflink, Reflink::Auto);
+        assert_eq!(options.sparse, Sparse::Never);
+        assert!(parse(string_vec![
+            "cp.exe",
+            "--reflink",
+            "--sparse=always",
+            "a",
+            "b"
+        ])
+        .unwrap_err()
+        .starts_with("--reflink can be used only with --sparse=auto"));
+        assert!(parse(string_vec!["cp.exe", "--sparse=some", "a", "b"])
+            .unwrap_err()
+            .starts_with(
+                "invalid argument 'some' for '--sparse'\nValid arguments are:\n  - 'never'"
+            ));
+    }
+
+    #[test]
+    fn backups() {
+        let backup = |args: Vec<String>| parse(args).unwrap().options.backup;
+        let made = backup(string_vec!["cp.exe", "--backup=numbered", "a", "b"]);
+        assert_eq!(made.control, Control::Numbered);
+        let made = backup(string_vec!["cp.exe", "--backup=t", "-S", ".bak", "a", "b"]);
+        assert_eq!(made.control, Control::Numbered);
+        assert_eq!(made.suffix, ".bak");
+        let made = backup(string_vec!["cp.exe", "--backup=off", "a", "b"]);
+        assert_eq!(made.control, Control::None);
+
+        assert!(parse(string_vec!["cp.exe", "--backup=bogus", "a", "b"])
+            .unwrap_err()
+            .starts_with("invalid argument 'bogus' for 'backup type'"));
+        assert!(parse(string_vec!["cp.exe", "-bn", "a", "b"])
+            .unwrap_err()
+            .starts_with("options --backup and --no-clobber are mutually exclusive"));
+    }
+
+    #[test]
+    fn targets() {
+        let config = parse(string_vec!["cp.exe", "-t", "dir", "a", "b"]).unwrap();
+        assert_eq!(config.target_directory.as_deref(), Some("dir"));
+        assert_eq!(config.files, vec!["a", "b"]);
+        let config = parse(string_vec!["cp.exe", "--parents", "-T", "a", "b"]).unwrap();
+        assert!(config.no_target_directory && config.parents);
+    }
+
+    #[test]
+    fn operand_errors() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert_eq!(
+            error(string_vec!["cp.exe"]),
+            "missing file operand\n Try cp --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["cp.exe", "a"]),
+            "missing destination file operand after 'a'\n Try cp --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["cp.exe", "-T", "a", "b", "c"]),
+            "extra operand 'c'\n Try cp --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["cp.exe", "-t"]),
+            "option requires an argument -- 't'\n Try cp --help for more information"
+        );
+        assert!(error(string_vec!["cp.exe", "-t", "d", "-T", "a"]).starts_with("cannot combine"));
+        assert!(error(string_vec!["cp.exe", "-t", "d"]).starts_with("missing file operand"));
+        assert!(error(string_vec!["cp.exe", "--preserve=color", "a", "b"])
+            .starts_with("invalid argument 'color' for '--preserve'"));
+        assert!(error(string_vec!["cp.exe", "-q", "a", "b"]).starts_with("invalid option: -q"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = CpConfig::new();
+        let parse_result = config.parse(string_vec!["cp.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = CpConfig::new();
+        let parse_result = config.parse(string_vec!["cp.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
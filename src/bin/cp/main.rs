@@ -0,0 +1,57 @@
+use std::env;
+use std::process;
+
+use coreutils::copy::{self, Copier, Target};
+use coreutils::output::Output;
+
+mod config;
+
+use config::CpConfig;
+
+fn main() {
+    let mut config = CpConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let target = copy::target(
+        &config.files,
+        config.target_directory.as_deref(),
+        config.no_target_directory,
+    );
+    let target = match target {
+        Ok(Target::File(..)) if config.parents => {
+            eprintln!(
+                "cp: with --parents, the destination must be a directory\n Try cp --help for more information"
+            );
+            process::exit(1);
+        }
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("cp: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut copier = Copier::new("cp", config.options, Output::stdout("cp"));
+    let mut ok = true;
+    match target {
+        Target::File(source, dest) => ok = copier.copy(&source, &dest),
+        Target::Directory(sources, dir) => {
+            for source in sources {
+                let dest = if config.parents {
+                    copier.make_parents(&source, &dir)
+                } else {
+                    Some(copy::into_directory(&source, &dir))
+                };
+                ok &= match dest {
+                    Some(dest) => copier.copy(&source, &dest),
+                    None => false,
+                };
+            }
+        }
+    }
+    copier.output().flush();
+    process::exit(if ok { 0 } else { 1 });
+}
@@ -0,0 +1,505 @@
+use coreutils::config::{self, Config};
+use coreutils::format::Spec;
+use coreutils::regex::Regex;
+
+/// A `-b` format: one integer directive with text around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuffixFormat {
+    pub prefix: String,
+    pub spec: Spec,
+    pub suffix: String,
+}
+
+impl SuffixFormat {
+    pub fn parse(format: &str) -> Result<SuffixFormat, String> {
+        let mut parts = vec![String::new()];
+        let mut spec = None;
+        let mut rest = format;
+        while let Some(idx) = rest.find('%') {
+            parts.last_mut().unwrap().push_str(&rest[..idx]);
+            let after = &rest[idx + 1..];
+            if let Some(after) = after.strip_prefix('%') {
+                parts.last_mut().unwrap().push('%');
+                rest = after;
+                continue;
+            }
+            if spec.is_some() {
+                return Err(String::from(
+                    "too many % conversion specifications in suffix",
+                ));
+            }
+            let (parsed, used) = Spec::parse(after).unwrap_or_default();
+            if !"diouxX".contains(parsed.conversion)
+                || parsed.width_from_arg
+                || parsed.precision_from_arg
+            {
+                let directive = after[..used].chars().last().unwrap_or('%');
+                return Err(format!(
+                    "invalid conversion specifier in suffix: {}",
+                    directive
+                ));
+            }
+            spec = Some(parsed);
+            parts.push(String::new());
+            rest = &after[used..];
+        }
+        parts.last_mut().unwrap().push_str(rest);
+
+        let spec =
+            spec.ok_or_else(|| String::from("missing % conversion specification in suffix"))?;
+        let suffix = parts.pop().unwrap_or_default();
+        Ok(SuffixFormat {
+            prefix: parts.pop().unwrap_or_default(),
+            spec,
+            suffix,
+        })
+    }
+
+    /// `%0Nd`, the format `-n` asks for.
+    fn digits(digits: usize) -> SuffixFormat {
+        SuffixFormat {
+            prefix: String::new(),
+            spec: Spec {
+                zero: true,
+                width: digits,
+                conversion: 'd',
+                ..Spec::default()
+            },
+            suffix: String::new(),
+        }
+    }
+
+    pub fn render(&self, number: u64) -> String {
+        let number = match self.spec.conversion {
+            'd' | 'i' => self.spec.signed(i128::from(number)),
+            _ => self.spec.unsigned(u128::from(number)),
+        };
+        format!("{}{}{}", self.prefix, number, self.suffix)
+    }
+}
+
+/// Where a pattern ends the piece it applies to.
+#[derive(Debug)]
+pub enum Target {
+    /// Before this line, counting from 1.
+    Line(u64),
+    /// `offset` lines from the next line matching `regex`. With `skip`
+    /// (`%REGEXP%`) the lines before are dropped instead of written.
+    Match {
+        regex: Regex,
+        offset: i64,
+        skip: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repeat {
+    Times(u64),
+    /// `{*}`: until the input runs out.
+    Forever,
+}
+
+#[derive(Debug)]
+pub struct Pattern {
+    /// The operand as given, for messages.
+    pub text: String,
+    pub target: Target,
+    pub repeat: Repeat,
+}
+
+#[derive(Debug)]
+pub struct CsplitConfig {
+    pub file: String,
+    pub patterns: Vec<Pattern>,
+    pub prefix: String,
+    pub suffix: SuffixFormat,
+    pub keep_files: bool,
+    pub suppress_matched: bool,
+    pub quiet: bool,
+    pub elide_empty: bool,
+    /// Complaints about the patterns that do not stop the split.
+    pub warnings: Vec<String>,
+}
+
+const HELP_TEXT: &str = "Usage: csplit [OPTION]... FILE PATTERN...
+Output pieces of FILE separated by PATTERN(s) to files 'xx00', 'xx01', ...,
+and output byte counts of each piece to standard output.
+
+Read standard input if FILE is -
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --suffix-format=FORMAT  use sprintf FORMAT instead of %02d
+  -f, --prefix=PREFIX        use PREFIX instead of 'xx'
+  -k, --keep-files           do not remove output files on errors
+      --suppress-matched     suppress the lines matching PATTERN
+  -n, --digits=DIGITS        use specified number of digits instead of 2
+  -s, --quiet                do not print counts of output file sizes
+      --silent               same as --quiet
+  -z, --elide-empty-files    suppress empty output files
+      --help     display this help and exit
+      --version  output version information and exit
+
+Each PATTERN may be:
+  INTEGER            copy up to but not including specified line number
+  /REGEXP/[OFFSET]   copy up to but not including a matching line
+  %REGEXP%[OFFSET]   skip to, but not including a matching line
+  {INTEGER}          repeat the previous pattern specified number of times
+  {*}                repeat the previous pattern as many times as possible
+
+A line OFFSET is an integer optionally preceded by '+' or '-'.
+REGEXP is a basic regular expression.  A carriage return before the
+newline is not part of the line it is matched against.
+";
+
+impl Config for CsplitConfig {
+    fn bin_name(&self) -> &'static str {
+        "csplit"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try csplit --help for more information", message)
+}
+
+/// Parse `/REGEXP/[OFFSET]` or `%REGEXP%[OFFSET]`.
+fn parse_match(text: &str, delimiter: char) -> Result<Target, String> {
+    let body = &text[1..];
+    let close = body
+        .rfind(delimiter)
+        .ok_or_else(|| format!("{}: closing delimiter '{}' missing", text, delimiter))?;
+    let regex = Regex::new(&body[..close])
+        .map_err(|e| format!("'{}': invalid regular expression: {}", text, e))?;
+    let offset = match &body[close + 1..] {
+        "" => 0,
+        offset => {
+            let digits = offset.strip_prefix(['+', '-']).unwrap_or(offset);
+            match offset.trim_start_matches('+').parse() {
+                Ok(offset) if digits.chars().all(|c| c.is_ascii_digit()) => offset,
+                _ => return Err(format!("'{}': integer expected after delimiter", text)),
+            }
+        }
+    };
+    Ok(Target::Match {
+        regex,
+        offset,
+        skip: delimiter == '%',
+    })
+}
+
+/// Parse `{INTEGER}` or `{*}`.
+fn parse_repeat(text: &str) -> Result<Repeat, String> {
+    let count = text[1..]
+        .strip_suffix('}')
+        .ok_or_else(|| format!("'{}': '}}' is required in repeat count", text))?;
+    match count {
+        "*" => Ok(Repeat::Forever),
+        digits => match digits.parse() {
+            Ok(count) if digits.chars().all(|c| c.is_ascii_digit()) => Ok(Repeat::Times(count)),
+            _ => Err(format!(
+                "'{}': integer required between '{{' and '}}'",
+                text
+            )),
+        },
+    }
+}
+
+impl CsplitConfig {
+    pub fn new() -> CsplitConfig {
+        CsplitConfig {
+            file: String::new(),
+            patterns: Vec::new(),
+            prefix: String::from("xx"),
+            suffix: SuffixFormat::digits(2),
+            keep_files: false,
+            suppress_matched: false,
+            quiet: false,
+            elide_empty: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn parse_patterns(&mut self, operands: impl Iterator<Item = String>) -> Result<(), String> {
+        let mut last_line: Option<(u64, String)> = None;
+        for text in operands {
+            if text.starts_with('{') {
+                match self.patterns.last_mut() {
+                    Some(pattern) => pattern.repeat = parse_repeat(&text)?,
+                    None => return Err(format!("'{}': invalid pattern", text)),
+                }
+                continue;
+            }
+            let target = match text.chars().next() {
+                Some(delimiter @ ('/' | '%')) => parse_match(&text, delimiter)?,
+                _ if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) => {
+                    let line: u64 = text
+                        .parse()
+                        .map_err(|_| format!("'{}': line number out of range", text))?;
+                    if line == 0 {
+                        return Err(format!("{}: line number must be greater than zero", text));
+                    }
+                    match &last_line {
+                        Some((last, last_text)) if line < *last => {
+                            return Err(format!(
+                                "line number '{}' is smaller than preceding line number, {}",
+                                text, last_text
+                            ))
+                        }
+                        Some((last, _)) if line == *last => self.warnings.push(format!(
+                            "line number '{}' is the same as preceding line number",
+                            text
+                        )),
+                        _ => {}
+                    }
+                    last_line = Some((line, text.clone()));
+                    Target::Line(line)
+                }
+                _ => return Err(format!("'{}': invalid pattern", text)),
+            };
+            self.patterns.push(Pattern {
+                text,
+                target,
+                repeat: Repeat::Times(0),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let mut format = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--suffix-format", Some(value)) | ("-b", Some(value)) => {
+                    format = Some(SuffixFormat::parse(value)?)
+                }
+                ("--prefix", Some(prefix)) | ("-f", Some(prefix)) => {
+                    self.prefix = prefix.to_string()
+                }
+                ("--keep-files", None) | ("-k", None) => self.keep_files = true,
+                ("--suppress-matched", None) => self.suppress_matched = true,
+                ("--digits", Some(value)) | ("-n", Some(value)) => {
+                    let digits = value
+                        .parse()
+                        .map_err(|_| format!("invalid number: '{}'", value))?;
+                    self.suffix = SuffixFormat::digits(digits);
+                }
+                ("--quiet", None) | ("--silent", None) | ("-s", None) => self.quiet = true,
+                ("--elide-empty-files", None) | ("-z", None) => self.elide_empty = true,
+                (name, None) if name.len() == 2 && "bfn".contains(&name[1..]) => {
+                    return Err(usage_error(&format!(
+                        "option requires an argument -- '{}'",
+                        &name[1..]
+                    )))
+                }
+                _ => return Err(usage_error(&format!("invalid option: {}", option))),
+            }
+        }
+        // A suffix format wins over a number of digits.
+        if let Some(format) = format {
+            self.suffix = format;
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        self.file = operands
+            .next()
+            .ok_or_else(|| usage_error("missing operand"))?;
+        let mut operands = operands.peekable();
+        if operands.peek().is_none() {
+            return Err(usage_error(&format!(
+                "missing operand after '{}'",
+                self.file
+            )));
+        }
+        self.parse_patterns(operands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{CsplitConfig, Repeat, SuffixFormat, Target};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<CsplitConfig, String> {
+        let mut config = CsplitConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    fn error(args: Vec<String>) -> String {
+        parse(args).unwrap_err()
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["csplit.exe", "in", "5"]).unwrap();
+        assert_eq!(config.file, "in");
+        assert_eq!(config.prefix, "xx");
+        assert_eq!(config.suffix.render(3), "03");
+        assert!(!config.keep_files);
+        assert!(!config.suppress_matched);
+        assert!(!config.quiet);
+        assert!(!config.elide_empty);
+        assert!(config.warnings.is_empty());
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec![
+            "csplit.exe",
+            "-ksz",
+            "--suppress-matched",
+            "-f",
+            "part",
+            "-n",
+            "3",
+            "in",
+            "5"
+        ])
+        .unwrap();
+        assert!(config.keep_files);
+        assert!(config.quiet);
+        assert!(config.elide_empty);
+        assert!(config.suppress_matched);
+        assert_eq!(config.prefix, "part");
+        assert_eq!(config.suffix.render(7), "007");
+
+        let config = parse(string_vec![
+            "csplit.exe",
+            "-b",
+            "%x.txt",
+            "-n",
+            "5",
+            "in",
+            "5"
+        ])
+        .unwrap();
+        assert_eq!(config.suffix.render(26), "1a.txt");
+    }
+
+    #[test]
+    fn suffix_formats() {
+        let render = |format: &str, number| SuffixFormat::parse(format).unwrap().render(number);
+        assert_eq!(render("%03d", 5), "005");
+        assert_eq!(render("part-%-3u-%%", 5), "part-5  -%");
+        assert_eq!(render("%#o", 8), "010");
+        assert_eq!(render("%.3X", 255), "0FF");
+        assert_eq!(
+            SuffixFormat::parse("abc").unwrap_err(),
+            "missing % conversion specification in suffix"
+        );
+        assert_eq!(
+            SuffixFormat::parse("%d%d").unwrap_err(),
+            "too many % conversion specifications in suffix"
+        );
+        assert_eq!(
+            SuffixFormat::parse("%s").unwrap_err(),
+            "invalid conversion specifier in suffix: s"
+        );
+        assert_eq!(
+            SuffixFormat::parse("%*d").unwrap_err(),
+            "invalid conversion specifier in suffix: d"
+        );
+    }
+
+    #[test]
+    fn patterns() {
+        let config = parse(string_vec![
+            "csplit.exe",
+            "in",
+            "3",
+            "{2}",
+            "/a/",
+            "%b%-2",
+            "{*}",
+            "/c/+1"
+        ])
+        .unwrap();
+        let patterns = &config.patterns;
+        assert_eq!(patterns.len(), 4);
+        assert!(matches!(patterns[0].target, Target::Line(3)));
+        assert_eq!(patterns[0].repeat, Repeat::Times(2));
+        assert!(matches!(
+            patterns[1].target,
+            Target::Match {
+                offset: 0,
+                skip: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            patterns[2].target,
+            Target::Match {
+                offset: -2,
+                skip: true,
+                ..
+            }
+        ));
+        assert_eq!(patterns[2].repeat, Repeat::Forever);
+        assert_eq!(patterns[2].text, "%b%-2");
+        assert!(matches!(
+            patterns[3].target,
+            Target::Match { offset: 1, .. }
+        ));
+
+        let config = parse(string_vec!["csplit.exe", "in", "/a/b/", "5", "5"]).unwrap();
+        assert!(matches!(config.patterns[0].target, Target::Match { .. }));
+        assert_eq!(
+            config.warnings,
+            ["line number '5' is the same as preceding line number"]
+        );
+    }
+
+    #[test]
+    fn pattern_errors() {
+        let pattern = |text: &str| error(string_vec!["csplit.exe", "in", "5", text]);
+        assert_eq!(pattern("/x"), "/x: closing delimiter '/' missing");
+        assert_eq!(pattern("%x"), "%x: closing delimiter '%' missing");
+        assert_eq!(pattern("/x/y"), "'/x/y': integer expected after delimiter");
+        assert_eq!(pattern("/x/+"), "'/x/+': integer expected after delimiter");
+        assert_eq!(pattern("{3"), "'{3': '}' is required in repeat count");
+        assert_eq!(
+            pattern("{x}"),
+            "'{x}': integer required between '{' and '}'"
+        );
+        assert_eq!(pattern("0"), "0: line number must be greater than zero");
+        assert_eq!(
+            pattern("3"),
+            "line number '3' is smaller than preceding line number, 5"
+        );
+        assert_eq!(pattern("x"), "'x': invalid pattern");
+        assert!(pattern("/\\(/").starts_with("'/\\(/': invalid regular expression: "));
+        assert_eq!(
+            error(string_vec!["csplit.exe", "in", "{3}"]),
+            "'{3}': invalid pattern"
+        );
+    }
+
+    #[test]
+    fn operands() {
+        assert!(error(string_vec!["csplit.exe"]).starts_with("missing operand\n"));
+        assert!(error(string_vec!["csplit.exe", "in"]).starts_with("missing operand after 'in'"));
+        assert_eq!(
+            error(string_vec!["csplit.exe", "-n", "x", "in", "5"]),
+            "invalid number: 'x'"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = CsplitConfig::new();
+        let parse_result = config.parse(string_vec!["csplit.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = CsplitConfig::new();
+        let parse_result = config.parse(string_vec!["csplit.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,54 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod sections;
+
+use config::CsplitConfig;
+use sections::Files;
+
+fn main() {
+    let mut config = CsplitConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+    for warning in &config.warnings {
+        eprintln!("csplit: warning: {}", warning);
+    }
+
+    // Offsets may reach back before a match, so the input is held whole.
+    let mut contents = Vec::new();
+    if let Err(e) =
+        input::open(&config.file).and_then(|mut reader| reader.read_to_end(&mut contents))
+    {
+        eprintln!(
+            "csplit: {}: {}",
+            input::display_name(&config.file),
+            output::describe(&e)
+        );
+        process::exit(1);
+    }
+    let lines: Vec<&[u8]> = contents.split_inclusive(|&byte| byte == b'\n').collect();
+
+    let mut out = Output::stdout("csplit");
+    let mut files = Files::new(&config, &mut out);
+    let result = sections::split(&lines, &config, &mut files);
+    let created = files.created;
+    out.flush();
+
+    if let Err(e) = result {
+        eprintln!("csplit: {}", e);
+        if !config.keep_files {
+            for name in created {
+                let _ = fs::remove_file(name);
+            }
+        }
+        process::exit(1);
+    }
+}
@@ -0,0 +1,311 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use coreutils::lines;
+use coreutils::output;
+
+use crate::config::{CsplitConfig, Pattern, Repeat, Target};
+
+/// Where the pieces of the input go, one after another.
+pub trait Pieces {
+    fn piece(&mut self, lines: &[&[u8]]) -> Result<(), String>;
+}
+
+/// Pieces written to files named in turn, with each size reported on
+/// `out` unless quiet.
+pub struct Files<'a, W: Write> {
+    config: &'a CsplitConfig,
+    out: &'a mut W,
+    /// Every file made so far, to be removed if the split fails.
+    pub created: Vec<String>,
+}
+
+impl<'a, W: Write> Files<'a, W> {
+    pub fn new(config: &'a CsplitConfig, out: &'a mut W) -> Self {
+        Files {
+            config,
+            out,
+            created: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Pieces for Files<'_, W> {
+    fn piece(&mut self, lines: &[&[u8]]) -> Result<(), String> {
+        let bytes: usize = lines.iter().map(|line| line.len()).sum();
+        if bytes == 0 && self.config.elide_empty {
+            return Ok(());
+        }
+
+        let name = format!(
+            "{}{}",
+            self.config.prefix,
+            self.config.suffix.render(self.created.len() as u64)
+        );
+        let failed = |e: std::io::Error| format!("{}: {}", name, output::describe(&e));
+        let file = File::create(&name).map_err(failed)?;
+        self.created.push(name.clone());
+        let mut writer = BufWriter::new(file);
+        for line in lines {
+            writer.write_all(line).map_err(failed)?;
+        }
+        writer.flush().map_err(failed)?;
+
+        if !self.config.quiet {
+            writeln!(self.out, "{}", bytes).map_err(|e| output::describe(&e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks the input line by line as the patterns ask.
+struct Splitter<'a, P: Pieces> {
+    lines: &'a [&'a [u8]],
+    /// The first line not yet written or skipped.
+    first: usize,
+    /// Where the next search for a pattern starts. A line that matched
+    /// is not tried again, even when an offset leaves it unwritten.
+    search: usize,
+    suppress_matched: bool,
+    pieces: &'a mut P,
+}
+
+fn out_of_range(pattern: &Pattern, repetition: u64) -> String {
+    match repetition {
+        0 => format!("'{}': line number out of range", pattern.text),
+        _ => format!(
+            "'{}': line number out of range on repetition {}",
+            pattern.text, repetition
+        ),
+    }
+}
+
+impl<P: Pieces> Splitter<'_, P> {
+    /// Write the lines up to `end` as a piece.
+    fn piece(&mut self, end: usize) -> Result<(), String> {
+        self.pieces.piece(&self.lines[self.first..end])?;
+        self.first = end;
+        Ok(())
+    }
+
+    /// With `--suppress-matched`, leave out the line a piece ended at.
+    fn suppress(&mut self) {
+        if self.suppress_matched && self.first < self.lines.len() {
+            self.first += 1;
+        }
+        self.search = self.search.max(self.first);
+    }
+
+    /// `INTEGER`: a piece up to that line, or the next multiple of it on
+    /// each repetition.
+    fn line_number(&mut self, pattern: &Pattern, line: u64, repetition: u64) -> Result<(), String> {
+        let len = self.lines.len();
+        if self.suppress_matched && self.first == len {
+            return Err(out_of_range(pattern, repetition));
+        }
+        let end = line.saturating_mul(repetition + 1) - 1;
+        let end = end.min(usize::MAX as u64) as usize;
+        self.piece(end.clamp(self.first, len))?;
+        // The line numbered must be there to start the next piece.
+        if end > len || (!self.suppress_matched && self.first == len) {
+            return Err(out_of_range(pattern, repetition));
+        }
+        self.suppress();
+        Ok(())
+    }
+
+    /// `/REGEXP/` or `%REGEXP%`: a piece, or a skip, up to `offset` lines
+    /// from the next matching line. Returns false when `{*}` has run out
+    /// of matches, which ends the split.
+    fn matching(&mut self, pattern: &Pattern, repetition: u64) -> Result<bool, String> {
+        let (regex, offset, skip) = match &pattern.target {
+            Target::Match {
+                regex,
+                offset,
+                skip,
+            } => (regex, *offset, *skip),
+            Target::Line(_) => unreachable!(),
+        };
+        let len = self.lines.len();
+        let found = (self.search..len).find(|&idx| {
+            let line = self.lines[idx];
+            regex.is_match(&line[..lines::content_len(line, b'\n')])
+        });
+
+        let matched = match found {
+            Some(matched) => matched,
+            None => {
+                if skip {
+                    self.first = len;
+                } else {
+                    self.piece(len)?;
+                }
+                if pattern.repeat == Repeat::Forever {
+                    return Ok(false);
+                }
+                return Err(match repetition {
+                    0 => format!("'{}': match not found", pattern.text),
+                    _ => format!(
+                        "'{}': match not found on repetition {}",
+                        pattern.text, repetition
+                    ),
+                });
+            }
+        };
+
+        let end = matched as i64 + offset;
+        if end < self.first as i64 {
+            if !skip {
+                self.piece(self.first)?;
+            }
+            return Err(out_of_range(pattern, 0));
+        }
+        let end = end as usize;
+        if skip {
+            self.first = end.min(len);
+        } else {
+            self.piece(end.min(len))?;
+        }
+        if end > len {
+            return Err(out_of_range(pattern, 0));
+        }
+        self.search = if offset > 0 { end + 1 } else { matched + 1 };
+        self.suppress();
+        Ok(true)
+    }
+}
+
+/// Split `lines`, each with its terminator, into pieces at the patterns,
+/// then write what is left as the last piece.
+pub fn split<P: Pieces>(
+    lines: &[&[u8]],
+    config: &CsplitConfig,
+    pieces: &mut P,
+) -> Result<(), String> {
+    let mut splitter = Splitter {
+        lines,
+        first: 0,
+        search: 0,
+        suppress_matched: config.suppress_matched,
+        pieces,
+    };
+    for pattern in &config.patterns {
+        let mut repetition = 0;
+        loop {
+            match pattern.target {
+                // `{*}` repeats a line number until it is out of range.
+                Target::Line(line) => splitter.line_number(pattern, line, repetition)?,
+                Target::Match { .. } => {
+                    if !splitter.matching(pattern, repetition)? {
+                        return Ok(());
+                    }
+                }
+            }
+            if pattern.repeat == Repeat::Times(repetition) {
+                break;
+            }
+            repetition += 1;
+        }
+    }
+    splitter.piece(lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    impl Pieces for Vec<String> {
+        fn piece(&mut self, lines: &[&[u8]]) -> Result<(), String> {
+            self.push(String::from_utf8(lines.concat()).unwrap());
+            Ok(())
+        }
+    }
+
+    fn csplit(args: &[&str], text: &str) -> Result<Vec<String>, (String, Vec<String>)> {
+        let mut all: Vec<String> = string_vec!["csplit.exe", "in"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = CsplitConfig::new();
+        config.parse(all).unwrap();
+        let lines: Vec<&[u8]> = text.as_bytes().split_inclusive(|&b| b == b'\n').collect();
+        let mut pieces = Vec::new();
+        match split(&lines, &config, &mut pieces) {
+            Ok(()) => Ok(pieces),
+            Err(e) => Err((e, pieces)),
+        }
+    }
+
+    fn numbers(count: u32) -> String {
+        (1..=count).map(|n| format!("{}\n", n)).collect()
+    }
+
+    #[test]
+    fn line_numbers() {
+        assert_eq!(
+            csplit(&["5", "{2}"], &numbers(20)).unwrap(),
+            [
+                "1\n2\n3\n4\n",
+                "5\n6\n7\n8\n9\n",
+                "10\n11\n12\n13\n14\n",
+                "15\n16\n17\n18\n19\n20\n"
+            ]
+        );
+        assert_eq!(csplit(&["2", "2"], "a\nb\n").unwrap(), ["a\n", "", "b\n"]);
+        let (error, pieces) = csplit(&["5", "{5}"], &numbers(20)).unwrap_err();
+        assert_eq!(error, "'5': line number out of range on repetition 4");
+        assert_eq!(pieces.len(), 5);
+        assert_eq!(
+            csplit(&["3"], "a\nb\n").unwrap_err().0,
+            "'3': line number out of range"
+        );
+    }
+
+    #[test]
+    fn matches() {
+        assert_eq!(
+            csplit(&["/a/", "{*}"], "a\nb\na\nb\n").unwrap(),
+            ["", "a\nb\n", "a\nb\n"]
+        );
+        assert_eq!(csplit(&["2", "/b/"], "a\nb\n").unwrap(), ["a\n", "", "b\n"]);
+        assert_eq!(
+            csplit(&["/7/", "{*}"], &numbers(20)).unwrap(),
+            [
+                "1\n2\n3\n4\n5\n6\n",
+                "7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n",
+                "17\n18\n19\n20\n"
+            ]
+        );
+        let (error, pieces) = csplit(&["/b/", "/b/"], "a\nb\n").unwrap_err();
+        assert_eq!(error, "'/b/': match not found");
+        assert_eq!(pieces, ["a\n", "b\n"]);
+        assert_eq!(csplit(&["/x$/"], "ax\r\nb\n").unwrap(), ["", "ax\r\nb\n"]);
+    }
+
+    #[test]
+    fn offsets_and_skips() {
+        assert_eq!(
+            csplit(&["/5/+2", "%1%", "/1/-1"], &numbers(20)).unwrap(),
+            ["1\n2\n3\n4\n5\n6\n", "", &numbers(20)[18..]]
+        );
+        assert!(csplit(&["%5%", "{*}"], &numbers(20)).unwrap().is_empty());
+        let (error, pieces) = csplit(&["/5/-10"], &numbers(20)).unwrap_err();
+        assert_eq!(error, "'/5/-10': line number out of range");
+        assert_eq!(pieces, [""]);
+    }
+
+    #[test]
+    fn suppressed() {
+        assert_eq!(
+            csplit(&["--suppress-matched", "/5/", "/1/"], &numbers(20)).unwrap(),
+            ["1\n2\n3\n4\n", "6\n7\n8\n9\n", &numbers(20)[21..]]
+        );
+        assert_eq!(
+            csplit(&["--suppress-matched", "5", "{1}"], &numbers(20)).unwrap(),
+            ["1\n2\n3\n4\n", "6\n7\n8\n9\n", &numbers(20)[21..]]
+        );
+        assert_eq!(
+            csplit(&["--suppress-matched", "3"], "a\nb\n").unwrap(),
+            ["a\nb\n", ""]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::ranges::{RangeList, Unit};
+
+/// What the numbers in the list select.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Bytes,
+    Characters,
+    Fields,
+}
+
+#[derive(Debug)]
+pub struct CutConfig {
+    pub files: Vec<String>,
+    pub mode: Mode,
+    /// Selected positions, already complemented for `--complement`.
+    pub list: RangeList,
+    pub delimiter: Vec<u8>,
+    /// Written between selected fields, or between selected ranges of bytes
+    /// and characters when given.
+    pub output_delimiter: Option<Vec<u8>>,
+    pub only_delimited: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: cut OPTION... [FILE]...
+Print selected parts of lines from each FILE to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --bytes=LIST        select only these bytes
+  -c, --characters=LIST   select only these characters
+  -d, --delimiter=DELIM   use DELIM instead of TAB for field delimiter
+  -f, --fields=LIST       select only these fields;  also print any line
+                            that contains no delimiter character, unless
+                            the -s option is specified
+  -n                      (ignored)
+      --complement        complement the set of selected bytes, characters
+                            or fields
+  -s, --only-delimited    do not print lines not containing delimiters
+      --output-delimiter=STRING  use STRING as the output delimiter
+                            the default is to use the input delimiter
+  -z, --zero-terminated   line delimiter is NUL, not newline
+      --no-glob           do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Use one, and only one of -b, -c or -f.  Each LIST is made up of one
+range, or many ranges separated by commas.  Selected input is written
+in the same order that it is read, and is written exactly once.
+Each range is one of:
+
+N     N'th byte, character or field, counted from 1
+N-    from N'th byte, character or field, to end of line
+N-M   from N'th to M'th (included) byte, character or field
+-M    from first to M'th (included) byte, character or field
+
+Characters are read as UTF-8; a byte that is not part of a valid sequence
+counts as one character.  A carriage return before the newline is not part
+of the line.
+";
+
+impl Config for CutConfig {
+    fn bin_name(&self) -> &'static str {
+        "cut"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try cut --help for more information", message)
+}
+
+/// The field delimiter: one character, or NUL when empty.
+fn parse_delimiter(text: &str) -> Result<Vec<u8>, String> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Ok(vec![b'\0']),
+        (Some(_), None) => Ok(text.as_bytes().to_vec()),
+        _ => Err(usage_error("the delimiter must be a single character")),
+    }
+}
+
+impl CutConfig {
+    pub fn new() -> CutConfig {
+        CutConfig {
+            files: Vec::new(),
+            mode: Mode::Fields,
+            list: RangeList::parse("1-", Unit::Fields).unwrap(),
+            delimiter: vec![b'\t'],
+            output_delimiter: None,
+            only_delimited: false,
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        let mut list: Option<(Mode, String)> = None;
+        let mut delimiter = None;
+        let mut complement = false;
+        for option in base_config.options {
+            let selected = match config::split_option(&option) {
+                ("--bytes", Some(value)) | ("-b", Some(value)) => Some((Mode::Bytes, value)),
+                ("--characters", Some(value)) | ("-c", Some(value)) => {
+                    Some((Mode::Characters, value))
+                }
+                ("--fields", Some(value)) | ("-f", Some(value)) => Some((Mode::Fields, value)),
+                _ => None,
+            };
+            if let Some((mode, value)) = selected {
+                if list.is_some() {
+                    return Err(usage_error("only one list may be specified"));
+                }
+                list = Some((mode, value.to_string()));
+                continue;
+            }
+
+            match config::split_option(&option) {
+                ("--delimiter", Some(text)) | ("-d", Some(text)) => {
+                    delimiter = Some(parse_delimiter(text)?)
+                }
+                ("--output-delimiter", Some(text)) => {
+                    // An empty output delimiter is a NUL byte, as in GNU cut.
+                    self.output_delimiter = Some(match text {
+                        "" => vec![b'\0'],
+                        text => text.as_bytes().to_vec(),
+                    })
+                }
+                ("-n", None) => {}
+                ("--complement", None) => complement = true,
+                ("--only-delimited", None) | ("-s", None) => self.only_delimited = true,
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                (name, None) if matches!(name, "-b" | "-c" | "-d" | "-f") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try cut --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        let (mode, list) = list.ok_or_else(|| {
+            usage_error("you must specify a list of bytes, characters, or fields")
+        })?;
+        self.mode = mode;
+        if mode != Mode::Fields {
+            if delimiter.is_some() {
+                return Err(usage_error(
+                    "an input delimiter may be specified only when operating on fields",
+                ));
+            }
+            if self.only_delimited {
+                return Err(usage_error(
+                    "suppressing non-delimited lines makes sense\n\tonly when operating on fields",
+                ));
+            }
+        }
+
+        let unit = match mode {
+            Mode::Fields => Unit::Fields,
+            Mode::Bytes | Mode::Characters => Unit::Positions,
+        };
+        self.list = RangeList::parse(&list, unit).map_err(|e| usage_error(&e))?;
+        if complement {
+            self.list = self.list.complement();
+        }
+        if let Some(delimiter) = delimiter {
+            self.delimiter = delimiter;
+        }
+        Ok(())
+    }
+
+    /// The byte that ends lines.
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{CutConfig, Mode};
+    use coreutils::ranges::Range;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<CutConfig, String> {
+        let mut config = CutConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn lists() {
+        let config = parse(string_vec!["cut.exe", "-f", "1,3-4"]).unwrap();
+        assert_eq!(config.mode, Mode::Fields);
+        assert_eq!(
+            config.list.ranges(),
+            &[Range { start: 1, end: 1 }, Range { start: 3, end: 4 }]
+        );
+        assert_eq!(config.files, vec!["-"]);
+
+        let config = parse(string_vec!["cut.exe", "-b-2", "a.txt"]).unwrap();
+        assert_eq!(config.mode, Mode::Bytes);
+        assert_eq!(config.list.ranges(), &[Range { start: 1, end: 2 }]);
+        assert_eq!(config.files, vec!["a.txt"]);
+
+        let config = parse(string_vec!["cut.exe", "--characters=3-"]).unwrap();
+        assert_eq!(config.mode, Mode::Characters);
+        assert_eq!(config.list.last(), usize::MAX);
+    }
+
+    #[test]
+    fn complement() {
+        let config = parse(string_vec!["cut.exe", "-f2", "--complement"]).unwrap();
+        assert_eq!(
+            config.list.ranges(),
+            &[
+                Range { start: 1, end: 1 },
+                Range {
+                    start: 3,
+                    end: usize::MAX
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn delimiters() {
+        let config = parse(string_vec!["cut.exe", "-f1"]).unwrap();
+        assert_eq!(config.delimiter, b"\t");
+        assert_eq!(config.output_delimiter, None);
+
+        let config = parse(string_vec![
+            "cut.exe",
+            "-f1",
+            "-d",
+            ",",
+            "--output-delimiter=; "
+        ])
+        .unwrap();
+        assert_eq!(config.delimiter, b",");
+        assert_eq!(config.output_delimiter.as_deref(), Some(&b"; "[..]));
+
+        let config = parse(string_vec!["cut.exe", "-f1", "-d", "é"]).unwrap();
+        assert_eq!(config.delimiter, "é".as_bytes());
+
+        let config = parse(string_vec![
+            "cut.exe",
+            "-f1",
+            "-d",
+            "",
+            "--output-delimiter="
+        ])
+        .unwrap();
+        assert_eq!(config.delimiter, b"\0");
+        assert_eq!(config.output_delimiter.as_deref(), Some(&b"\0"[..]));
+
+        assert!(parse(string_vec!["cut.exe", "-f1", "-d", "ab"])
+            .unwrap_err()
+            .contains("the delimiter must be a single character"));
+    }
+
+    #[test]
+    fn flags() {
+        let config = parse(string_vec!["cut.exe", "-sz", "-f1"]).unwrap();
+        assert!(config.only_delimited && config.zero_terminated);
+        assert_eq!(config.terminator(), b'\0');
+        assert_eq!(CutConfig::new().terminator(), b'\n');
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert!(error(string_vec!["cut.exe"]).contains("you must specify a list"));
+        assert!(error(string_vec!["cut.exe", "-b1", "-f1"]).contains("only one list"));
+        assert!(
+            error(string_vec!["cut.exe", "-b1", "-d,"]).contains("only when operating on fields")
+        );
+        assert!(error(string_vec!["cut.exe", "-c1", "-s"]).contains("suppressing non-delimited"));
+        assert!(error(string_vec!["cut.exe", "-f0"]).contains("fields are numbered from 1"));
+        assert!(error(string_vec!["cut.exe", "-c", "0-"]).contains("positions are numbered from 1"));
+        assert!(error(string_vec!["cut.exe", "-f1", "-x"]).contains("invalid option: -x"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = CutConfig::new();
+        let parse_result = config.parse(string_vec!["cut.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = CutConfig::new();
+        let parse_result = config.parse(string_vec!["cut.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
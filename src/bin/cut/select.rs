@@ -0,0 +1,233 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+use coreutils::ranges::{Range, RangeList};
+use coreutils::width;
+
+use crate::config::{CutConfig, Mode};
+
+/// Steps through a list in order, for positions that only ever increase.
+struct Selection<'a> {
+    ranges: &'a [Range],
+    next: usize,
+}
+
+impl<'a> Selection<'a> {
+    fn new(list: &'a RangeList) -> Selection<'a> {
+        Selection {
+            ranges: list.ranges(),
+            next: 0,
+        }
+    }
+
+    fn contains(&mut self, position: usize) -> bool {
+        while self.next < self.ranges.len() && self.ranges[self.next].end < position {
+            self.next += 1;
+        }
+        self.ranges
+            .get(self.next)
+            .is_some_and(|range| range.start <= position)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    match needle {
+        [byte] => haystack.iter().position(|b| b == byte),
+        _ => haystack
+            .windows(needle.len())
+            .position(|window| window == needle),
+    }
+}
+
+/// Walks a line a character at a time, remembering how far it got.
+struct Chars<'a> {
+    line: &'a [u8],
+    offset: usize,
+    count: usize,
+}
+
+impl Chars<'_> {
+    /// Byte offset of the end of character `n`, or of the line if shorter.
+    fn end_of(&mut self, n: usize) -> usize {
+        while self.count < n && self.offset < self.line.len() {
+            self.offset += width::decode(&self.line[self.offset..]).1;
+            self.count += 1;
+        }
+        self.offset
+    }
+}
+
+struct Cutter<'a, W: Write> {
+    out: &'a mut W,
+    config: &'a CutConfig,
+}
+
+impl<W: Write> Cutter<'_, W> {
+    fn end_line(&mut self, terminator: &[u8]) -> io::Result<()> {
+        if terminator.is_empty() {
+            self.out.write_all(&[self.config.terminator()])
+        } else {
+            self.out.write_all(terminator)
+        }
+    }
+
+    /// `-b` and `-c`: each selected range in turn, cut at byte offsets or
+    /// at character boundaries.
+    fn positions(&mut self, line: &[u8]) -> io::Result<()> {
+        let mut chars = Chars {
+            line,
+            offset: 0,
+            count: 0,
+        };
+        let mut printed = false;
+        for range in self.config.list.ranges() {
+            let (start, end) = match self.config.mode {
+                Mode::Characters => (chars.end_of(range.start - 1), chars.end_of(range.end)),
+                _ => ((range.start - 1).min(line.len()), range.end.min(line.len())),
+            };
+            if start == end {
+                break;
+            }
+            if let Some(delimiter) = &self.config.output_delimiter {
+                if printed {
+                    self.out.write_all(delimiter)?;
+                }
+            }
+            self.out.write_all(&line[start..end])?;
+            printed = true;
+        }
+        Ok(())
+    }
+
+    /// `-f`: the selected fields, separated by the output delimiter. Lines
+    /// are scanned for delimiters only as far as the last selected field,
+    /// and fields are written straight from the line.
+    fn fields(&mut self, line: &[u8]) -> io::Result<bool> {
+        let delimiter = &self.config.delimiter[..];
+        let mut end = match find(line, delimiter) {
+            Some(end) => end,
+            None => {
+                if self.config.only_delimited {
+                    return Ok(false);
+                }
+                self.out.write_all(line)?;
+                return Ok(true);
+            }
+        };
+        let output_delimiter = self.config.output_delimiter.as_deref().unwrap_or(delimiter);
+        let last = self.config.list.last();
+        let mut selection = Selection::new(&self.config.list);
+        let mut start = 0;
+        let mut field = 1;
+        let mut printed = false;
+        loop {
+            if selection.contains(field) {
+                if printed {
+                    self.out.write_all(output_delimiter)?;
+                }
+                self.out.write_all(&line[start..end])?;
+                printed = true;
+            }
+            if end == line.len() || field >= last {
+                return Ok(true);
+            }
+            start = end + delimiter.len();
+            end = find(&line[start..], delimiter).map_or(line.len(), |len| start + len);
+            field += 1;
+        }
+    }
+}
+
+/// Copy the selected parts of each line of `input` to `out`.
+pub fn cut<R: BufRead, W: Write>(input: R, out: &mut W, config: &CutConfig) -> io::Result<()> {
+    let mut lines = LineReader::new(input, config.terminator());
+    let mut cutter = Cutter { out, config };
+    while lines.next_line()? {
+        let print = match config.mode {
+            Mode::Fields => cutter.fields(lines.line())?,
+            Mode::Bytes | Mode::Characters => {
+                cutter.positions(lines.line())?;
+                true
+            }
+        };
+        if print {
+            cutter.end_line(lines.terminator())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn cut_text(args: &[&str], input: &[u8]) -> Vec<u8> {
+        let mut all: Vec<String> = string_vec!["cut.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = CutConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        cut(input, &mut out, &config).unwrap();
+        out
+    }
+
+    fn cut_str(args: &[&str], input: &str) -> String {
+        String::from_utf8(cut_text(args, input.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(cut_str(&["-b", "2-3"], "abcd\nx\n"), "bc\n\n");
+        assert_eq!(cut_str(&["-b", "4-,1"], "abcdef"), "adef\n");
+        assert_eq!(cut_str(&["-b", "-2", "--complement"], "abcd\n"), "cd\n");
+        assert_eq!(
+            cut_str(&["-b", "1,2,4-", "--output-delimiter=:"], "abcdef\n"),
+            "a:b:def\n"
+        );
+        assert_eq!(
+            cut_str(&["-b", "1-2,2-3", "--output-delimiter=:"], "abc\n"),
+            "abc\n"
+        );
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(cut_str(&["-c", "2-3"], "héllo\n"), "él\n");
+        assert_eq!(cut_str(&["-b", "2-3"], "héllo\n"), "é\n");
+        assert_eq!(cut_text(&["-c", "2"], b"a\xffb\n"), b"\xff\n");
+        assert_eq!(
+            cut_str(&["-c", "1,3", "--output-delimiter=|"], "日本語\n"),
+            "日|語\n"
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let input = "a\tb\tc\nno tabs\n";
+        assert_eq!(cut_str(&["-f", "2"], input), "b\nno tabs\n");
+        assert_eq!(cut_str(&["-f", "2", "-s"], input), "b\n");
+        assert_eq!(cut_str(&["-f", "1,3"], input), "a\tc\nno tabs\n");
+        assert_eq!(cut_str(&["-f", "5"], input), "\nno tabs\n");
+        assert_eq!(cut_str(&["-d", ",", "-f", "2-"], "1,2,,4\n"), "2,,4\n");
+        assert_eq!(cut_str(&["-d", ",", "-f", "3"], "a,b,\n"), "\n");
+    }
+
+    #[test]
+    fn field_options() {
+        let input = "a,b,c,d\n";
+        assert_eq!(cut_str(&["-d,", "-f2", "--complement"], input), "a,c,d\n");
+        assert_eq!(
+            cut_str(&["-d,", "-f1,3", "--output-delimiter= | "], input),
+            "a | c\n"
+        );
+        assert_eq!(cut_str(&["-d", "é", "-f2"], "aébéc\n"), "b\n");
+        assert_eq!(cut_str(&["-f1", "-d", ""], "a\0b\n"), "a\n");
+    }
+
+    #[test]
+    fn terminators() {
+        assert_eq!(cut_str(&["-d,", "-f2"], "a,b\r\nc,d"), "b\r\nd\n");
+        assert_eq!(cut_str(&["-z", "-d,", "-f1"], "a,b\0c\nd,e"), "a\0c\nd\0");
+    }
+}
@@ -0,0 +1,182 @@
+use coreutils::config::Config;
+
+#[derive(Debug)]
+pub struct EchoConfig {
+    pub strings: Vec<String>,
+    pub newline: bool,
+    pub escapes: bool,
+}
+
+const HELP_TEXT: &str = "Usage: echo [SHORT-OPTION]... [STRING]...
+  or:  echo LONG-OPTION
+Echo the STRING(s) to standard output.
+
+  -n             do not output the trailing newline
+  -e             enable interpretation of backslash escapes
+  -E             disable interpretation of backslash escapes (default)
+      --help     display this help and exit
+      --version  output version information and exit
+
+If -e is in effect, the following sequences are recognized:
+
+  \\\\      backslash
+  \\a      alert (BEL)
+  \\b      backspace
+  \\c      produce no further output
+  \\e      escape
+  \\f      form feed
+  \\n      new line
+  \\r      carriage return
+  \\t      horizontal tab
+  \\v      vertical tab
+  \\0NNN   byte with octal value NNN (1 to 3 digits)
+  \\xHH    byte with hexadecimal value HH (1 to 2 digits)
+
+Options are only recognized before the first STRING, and --help and
+--version only when given alone.
+";
+
+impl Config for EchoConfig {
+    fn bin_name(&self) -> &'static str {
+        "echo"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+impl EchoConfig {
+    pub fn new() -> EchoConfig {
+        EchoConfig {
+            strings: Vec::new(),
+            newline: true,
+            escapes: false,
+        }
+    }
+
+    /// echo prints anything it does not understand, so options are only
+    /// the leading arguments made entirely of `n`, `e` and `E` flags.
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        if args.len() == 2 {
+            Config::parse(self, &args)?;
+        }
+
+        let mut strings = args.into_iter().skip(1).peekable();
+        while let Some(arg) = strings.next_if(|arg| {
+            arg.len() > 1
+                && arg.starts_with('-')
+                && arg[1..].chars().all(|c| matches!(c, 'n' | 'e' | 'E'))
+        }) {
+            for flag in arg[1..].chars() {
+                match flag {
+                    'n' => self.newline = false,
+                    'e' => self.escapes = true,
+                    _ => self.escapes = false,
+                }
+            }
+        }
+        self.strings = strings.collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::EchoConfig;
+    use coreutils::string_vec;
+
+    #[test]
+    fn defaults() {
+        let mut config = EchoConfig::new();
+        config.parse(string_vec!["echo.exe"]).unwrap();
+        assert!(config.strings.is_empty());
+        assert!(config.newline);
+        assert!(!config.escapes);
+    }
+
+    #[test]
+    fn strings() {
+        let mut config = EchoConfig::new();
+        config
+            .parse(string_vec!["echo.exe", "hello", "-n", "--", "world"])
+            .unwrap();
+        assert_eq!(config.strings, vec!["hello", "-n", "--", "world"]);
+        assert!(config.newline);
+    }
+
+    #[test]
+    fn no_newline() {
+        let mut config = EchoConfig::new();
+        config.parse(string_vec!["echo.exe", "-n", "hi"]).unwrap();
+        assert!(!config.newline);
+        assert_eq!(config.strings, vec!["hi"]);
+    }
+
+    #[test]
+    fn escapes() {
+        let mut config = EchoConfig::new();
+        config
+            .parse(string_vec!["echo.exe", "-e", "a\\tb"])
+            .unwrap();
+        assert!(config.escapes);
+
+        let mut config = EchoConfig::new();
+        config
+            .parse(string_vec!["echo.exe", "-neE", "-e", "-E", "x"])
+            .unwrap();
+        assert!(!config.escapes);
+        assert!(!config.newline);
+        assert_eq!(config.strings, vec!["x"]);
+    }
+
+    #[test]
+    fn unknown_flags_are_strings() {
+        let mut config = EchoConfig::new();
+        config
+            .parse(string_vec!["echo.exe", "-nx", "-", "-n"])
+            .unwrap();
+        assert!(config.newline);
+        assert_eq!(config.strings, vec!["-nx", "-", "-n"]);
+    }
+
+    #[test]
+    fn help_only_alone() {
+        let mut config = EchoConfig::new();
+        config
+            .parse(string_vec!["echo.exe", "--help", "me"])
+            .unwrap();
+        assert_eq!(config.strings, vec!["--help", "me"]);
+    }
+
+    #[test]
+    fn options_not_echoed() {
+        for option in ["-n", "-e", "-E"] {
+            let mut config = EchoConfig::new();
+            config
+                .parse(string_vec!["echo.exe", option, "text"])
+                .unwrap();
+            assert_eq!(
+                config.strings,
+                vec!["text"],
+                "{} was not taken as an option",
+                option
+            );
+        }
+    }
+
+    #[test]
+    fn help() {
+        let mut config = EchoConfig::new();
+        let parse_result = config.parse(string_vec!["echo.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = EchoConfig::new();
+        let parse_result = config.parse(string_vec!["echo.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,36 @@
+use std::env;
+use std::process;
+
+use coreutils::format::{self, Escapes, Flow};
+use coreutils::output::Output;
+
+mod config;
+
+fn main() {
+    let mut config = config::EchoConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut text = Vec::new();
+    for (idx, string) in config.strings.iter().enumerate() {
+        if idx > 0 {
+            text.push(b' ');
+        }
+        if !config.escapes {
+            text.extend_from_slice(string.as_bytes());
+            continue;
+        }
+        // echo's escapes have no malformed forms, so this cannot fail.
+        let flow = format::unescape(string, Escapes::Echo, &mut text).unwrap_or(Flow::Continue);
+        if flow == Flow::Stop {
+            Output::stdout("echo").write_all(&text);
+            return;
+        }
+    }
+    if config.newline {
+        text.push(b'\n');
+    }
+    Output::stdout("echo").write_all(&text);
+}
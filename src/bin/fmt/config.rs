@@ -0,0 +1,222 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+/// How the lines of a paragraph are told apart and indented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Every line of a paragraph has the same indentation.
+    Plain,
+    /// Keep the indentation of the first two lines.
+    Crown,
+    /// The first line is indented differently from the rest.
+    Tagged,
+    /// Split long lines without joining short ones.
+    SplitOnly,
+}
+
+#[derive(Debug)]
+pub struct FmtConfig {
+    pub files: Vec<String>,
+    pub mode: Mode,
+    pub uniform: bool,
+    /// Lines must not reach this column.
+    pub width: usize,
+    /// The line length the breaking aims for.
+    pub goal: usize,
+    pub prefix: String,
+}
+
+const HELP_TEXT: &str = "Usage: fmt [-WIDTH] [OPTION]... [FILE]...
+Reformat each paragraph in the FILE(s), writing to standard output.
+The option -WIDTH is an abbreviated form of --width=DIGITS.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -c, --crown-margin        preserve indentation of first two lines
+  -p, --prefix=STRING       reformat only lines beginning with STRING,
+                              reattaching the prefix to reformatted lines
+  -s, --split-only          split long lines, but do not refill
+  -t, --tagged-paragraph    indentation of first line different from second
+  -u, --uniform-spacing     one space between words, two after sentences
+  -w, --width=WIDTH         maximum line width (default of 75 columns)
+  -g, --goal=WIDTH          goal width (default of 93% of width)
+      --no-glob             do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Widths are display columns, so wide characters take two.  A carriage
+return before the newline is not part of the line.
+";
+
+/// Widest line allowed, as in GNU fmt.
+const WIDTH_MAX: usize = 2500;
+
+/// Percentage the default goal may fall short of the width.
+const LEEWAY: usize = 7;
+
+impl Config for FmtConfig {
+    fn bin_name(&self) -> &'static str {
+        "fmt"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_width(value: &str, max: usize) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if width <= max => Ok(width),
+        _ => Err(format!("invalid width: '{}'", value)),
+    }
+}
+
+impl FmtConfig {
+    pub fn new() -> FmtConfig {
+        FmtConfig {
+            files: Vec::new(),
+            mode: Mode::Plain,
+            uniform: false,
+            width: 75,
+            goal: 75 * (2 * (100 - LEEWAY) + 1) / 200,
+            prefix: String::new(),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        let mut width = None;
+        let mut goal = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--crown-margin", None) | ("-c", None) => self.mode = Mode::Crown,
+                ("--split-only", None) | ("-s", None) => self.mode = Mode::SplitOnly,
+                ("--tagged-paragraph", None) | ("-t", None) => self.mode = Mode::Tagged,
+                ("--uniform-spacing", None) | ("-u", None) => self.uniform = true,
+                ("--prefix", Some(prefix)) | ("-p", Some(prefix)) => {
+                    self.prefix = prefix.to_string()
+                }
+                ("--width", Some(value)) | ("-w", Some(value)) => width = Some(value.to_string()),
+                ("--goal", Some(value)) | ("-g", Some(value)) => goal = Some(value.to_string()),
+                (name, None) if matches!(name, "-p" | "-w" | "-g") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                // Obsolete `fmt -72` form.
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    width = Some(number[1..].to_string())
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try fmt --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if let Some(width) = &width {
+            self.width = parse_width(width, WIDTH_MAX)?;
+        }
+        match goal {
+            Some(goal) => {
+                self.goal = parse_width(&goal, self.width)?;
+                if width.is_none() {
+                    self.width = self.goal + 10;
+                }
+            }
+            None => self.goal = self.width * (2 * (100 - LEEWAY) + 1) / 200,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{FmtConfig, Mode};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<FmtConfig, String> {
+        let mut config = FmtConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["fmt.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!((config.width, config.goal), (75, 70));
+        assert_eq!(config.mode, Mode::Plain);
+        assert!(!config.uniform);
+        assert_eq!(config.prefix, "");
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(
+            parse(string_vec!["fmt.exe", "-c"]).unwrap().mode,
+            Mode::Crown
+        );
+        assert_eq!(
+            parse(string_vec!["fmt.exe", "-t"]).unwrap().mode,
+            Mode::Tagged
+        );
+        assert_eq!(
+            parse(string_vec!["fmt.exe", "--split-only"]).unwrap().mode,
+            Mode::SplitOnly
+        );
+        let config = parse(string_vec!["fmt.exe", "-u", "-p", "# "]).unwrap();
+        assert!(config.uniform);
+        assert_eq!(config.prefix, "# ");
+    }
+
+    #[test]
+    fn widths() {
+        let widths = |args: Vec<String>| {
+            let config = parse(args).unwrap();
+            (config.width, config.goal)
+        };
+        assert_eq!(widths(string_vec!["fmt.exe", "-w", "40"]), (40, 37));
+        assert_eq!(widths(string_vec!["fmt.exe", "-60"]), (60, 56));
+        assert_eq!(widths(string_vec!["fmt.exe", "-g", "50"]), (60, 50));
+        assert_eq!(widths(string_vec!["fmt.exe", "-g50", "-w55"]), (55, 50));
+
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert_eq!(
+            error(string_vec!["fmt.exe", "-w", "x"]),
+            "invalid width: 'x'"
+        );
+        assert_eq!(
+            error(string_vec!["fmt.exe", "-w", "9999"]),
+            "invalid width: '9999'"
+        );
+        assert_eq!(
+            error(string_vec!["fmt.exe", "-w", "20", "-g", "30"]),
+            "invalid width: '30'"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = FmtConfig::new();
+        let parse_result = config.parse(string_vec!["fmt.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = FmtConfig::new();
+        let parse_result = config.parse(string_vec!["fmt.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod reflow;
+
+use config::FmtConfig;
+use reflow::Formatter;
+
+fn main() {
+    let mut config = FmtConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("fmt");
+    let mut formatter = Formatter::new(&config);
+    for file in &config.files {
+        let result = input::open(file).and_then(|reader| formatter.format(reader, &mut out));
+        if let Err(e) = result {
+            out.flush();
+            eprintln!(
+                "fmt: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,555 @@
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use coreutils::lines::LineReader;
+use coreutils::width::{self, TAB_STOP};
+
+use crate::config::{FmtConfig, Mode};
+
+/// Costs of a break, as in GNU fmt: lines short of the goal, lines of
+/// uneven length, and breaks that strand a word or split a sentence are
+/// penalised, and breaks after punctuation rewarded.
+type Cost = i64;
+
+const fn equiv(n: Cost) -> Cost {
+    n * n
+}
+
+fn short_cost(n: Cost) -> Cost {
+    equiv(n * 10)
+}
+
+fn ragged_cost(n: Cost) -> Cost {
+    short_cost(n) / 2
+}
+
+const LINE_COST: Cost = equiv(70);
+const SENTENCE_BONUS: Cost = equiv(50);
+const NOBREAK_COST: Cost = equiv(600);
+const PAREN_BONUS: Cost = equiv(40);
+const PUNCT_BONUS: Cost = equiv(40);
+
+fn widow_cost(length: usize) -> Cost {
+    equiv(200) / (length as Cost + 2)
+}
+
+fn orphan_cost(length: usize) -> Cost {
+    equiv(150) / (length as Cost + 2)
+}
+
+/// Default indentation for the other lines of a one line tagged paragraph.
+const TAGGED_INDENT: usize = 3;
+
+#[derive(Debug)]
+struct Word {
+    text: Range<usize>,
+    /// Display columns.
+    length: usize,
+    /// Columns of space after the word.
+    space: usize,
+    /// Opens with a bracket or quote.
+    paren: bool,
+    /// Ends with punctuation.
+    punct: bool,
+    /// Ends a sentence, before any closing brackets or quotes.
+    period: bool,
+    /// Ends a sentence for certain: at the end of a line or followed by
+    /// two spaces.
+    last: bool,
+}
+
+/// A line that belongs to a paragraph: it has the prefix and some text.
+struct TextLine {
+    /// Column of the prefix.
+    prefix_indent: usize,
+    /// Column of the text after the prefix.
+    indent: usize,
+    /// Offset of the text in the line.
+    start: usize,
+}
+
+/// The column after the blanks at the start of `text`, and how many bytes
+/// they take.
+fn skip_blanks(text: &[u8], mut column: usize, tabs: &mut bool) -> (usize, usize) {
+    let mut pos = 0;
+    while pos < text.len() {
+        match text[pos] {
+            b' ' => column += 1,
+            b'\t' => {
+                *tabs = true;
+                column += TAB_STOP - column % TAB_STOP;
+            }
+            _ => break,
+        }
+        pos += 1;
+    }
+    (column, pos)
+}
+
+pub struct Formatter<'a> {
+    config: &'a FmtConfig,
+    /// The prefix without its surrounding spaces.
+    prefix: &'a [u8],
+    /// Spaces before the prefix in `-p`.
+    prefix_lead: usize,
+    /// Length of the prefix with the spaces after it.
+    prefix_full: usize,
+    /// Indentation is written with tabs once the input has used them.
+    tabs: bool,
+    /// Kept between paragraphs for one line tagged paragraphs.
+    other_indent: usize,
+}
+
+/// A paragraph being gathered.
+struct Paragraph {
+    text: Vec<u8>,
+    words: Vec<Word>,
+    prefix_indent: usize,
+    first_indent: usize,
+    newline: Vec<u8>,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(config: &'a FmtConfig) -> Formatter<'a> {
+        let lead = config.prefix.bytes().take_while(|&b| b == b' ').count();
+        let prefix = &config.prefix.as_bytes()[lead..];
+        let trimmed = prefix.len() - prefix.iter().rev().take_while(|&&b| b == b' ').count();
+        Formatter {
+            config,
+            prefix: &prefix[..trimmed],
+            prefix_lead: lead,
+            prefix_full: prefix.len(),
+            tabs: false,
+            other_indent: 0,
+        }
+    }
+
+    /// Where the text of `line` starts, or None when it is not part of a
+    /// paragraph: it lacks the prefix or is blank after it.
+    fn text_line(&mut self, line: &[u8]) -> Option<TextLine> {
+        let (column, pos) = skip_blanks(line, 0, &mut self.tabs);
+        let (prefix_indent, column, pos) = if self.prefix.is_empty() {
+            (self.prefix_lead.min(column), column, pos)
+        } else {
+            if !line[pos..].starts_with(self.prefix) {
+                return None;
+            }
+            let pos = pos + self.prefix.len();
+            let (after, skipped) =
+                skip_blanks(&line[pos..], column + self.prefix.len(), &mut self.tabs);
+            (column, after, pos + skipped)
+        };
+        if prefix_indent < self.prefix_lead
+            || column < prefix_indent + self.prefix_full
+            || pos == line.len()
+        {
+            return None;
+        }
+        Some(TextLine {
+            prefix_indent,
+            indent: column,
+            start: pos,
+        })
+    }
+
+    /// Add the words of a line, whose text starts at `column`.
+    fn add_words(&mut self, paragraph: &mut Paragraph, text: &[u8], mut column: usize) {
+        let mut pos = 0;
+        while pos < text.len() {
+            let start = pos;
+            while pos < text.len() && text[pos] != b' ' && text[pos] != b'\t' {
+                pos += 1;
+            }
+            let word = &text[start..pos];
+            let length = width::bytes_width(word);
+            column += length;
+            let (after, skipped) = skip_blanks(&text[pos..], column, &mut self.tabs);
+            let space = after - column;
+            column = after;
+            pos += skipped;
+
+            let paren = matches!(word[0], b'(' | b'[' | b'`' | b'\'' | b'"');
+            let punct = word[word.len() - 1].is_ascii_punctuation();
+            let closed = word.len()
+                - word[1..]
+                    .iter()
+                    .rev()
+                    .take_while(|&&b| matches!(b, b')' | b']' | b'\'' | b'"'))
+                    .count();
+            let period = matches!(word[closed - 1], b'.' | b'?' | b'!');
+            let end_of_line = pos == text.len();
+            let last = period && (end_of_line || space > 1);
+            let space = if end_of_line || self.config.uniform {
+                if last {
+                    2
+                } else {
+                    1
+                }
+            } else {
+                space
+            };
+
+            let offset = paragraph.text.len();
+            paragraph.text.extend_from_slice(word);
+            paragraph.words.push(Word {
+                text: offset..offset + word.len(),
+                length,
+                space,
+                paren,
+                punct,
+                period,
+                last,
+            });
+        }
+    }
+
+    fn set_other_indent(&mut self, paragraph: &Paragraph, next: Option<&TextLine>) {
+        let first = paragraph.first_indent;
+        self.other_indent = match self.config.mode {
+            Mode::SplitOnly | Mode::Plain => first,
+            Mode::Crown => next.map_or(first, |line| line.indent),
+            Mode::Tagged => match next {
+                Some(line) if line.indent != first => line.indent,
+                // A one line paragraph keeps the indentation used last,
+                // unless that would make its lines alike.
+                _ if first == self.other_indent => {
+                    if first == 0 {
+                        TAGGED_INDENT
+                    } else {
+                        0
+                    }
+                }
+                _ => self.other_indent,
+            },
+        };
+    }
+
+    /// Reformat the paragraphs of `input`, copying other lines unchanged.
+    pub fn format<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> io::Result<()> {
+        let mut lines = LineReader::new(input, b'\n');
+        let mut more = lines.next_line()?;
+        while more {
+            let first = match self.text_line(lines.line()) {
+                Some(first) => first,
+                None => {
+                    out.write_all(lines.raw())?;
+                    if lines.terminator().is_empty() {
+                        out.write_all(b"\n")?;
+                    }
+                    more = lines.next_line()?;
+                    continue;
+                }
+            };
+
+            let mut paragraph = Paragraph {
+                text: Vec::new(),
+                words: Vec::new(),
+                prefix_indent: first.prefix_indent,
+                first_indent: first.indent,
+                newline: match lines.terminator() {
+                    b"\r\n" => b"\r\n".to_vec(),
+                    _ => b"\n".to_vec(),
+                },
+            };
+            self.add_words(&mut paragraph, &lines.line()[first.start..], first.indent);
+
+            more = lines.next_line()?;
+            let mut next = self.next_text_line(more, lines.line(), &paragraph);
+            self.set_other_indent(&paragraph, next.as_ref());
+            let join = match self.config.mode {
+                Mode::SplitOnly => false,
+                Mode::Tagged => next
+                    .as_ref()
+                    .is_some_and(|line| line.indent != paragraph.first_indent),
+                Mode::Crown | Mode::Plain => true,
+            };
+            let mut continuing = join
+                && next.as_ref().is_some_and(|line| {
+                    self.config.mode != Mode::Plain || line.indent == self.other_indent
+                });
+            while continuing {
+                let line = next.take().expect("continuing with a line");
+                self.add_words(&mut paragraph, &lines.line()[line.start..], line.indent);
+                more = lines.next_line()?;
+                next = self.next_text_line(more, lines.line(), &paragraph);
+                continuing = next
+                    .as_ref()
+                    .is_some_and(|line| line.indent == self.other_indent);
+            }
+
+            let last = paragraph.words.last_mut().expect("a paragraph has words");
+            last.period = true;
+            last.last = true;
+            self.write_paragraph(&paragraph, out)?;
+        }
+        Ok(())
+    }
+
+    /// The line after a paragraph line, if it could continue the paragraph.
+    fn next_text_line(
+        &mut self,
+        more: bool,
+        line: &[u8],
+        paragraph: &Paragraph,
+    ) -> Option<TextLine> {
+        if !more {
+            return None;
+        }
+        self.text_line(line)
+            .filter(|next| next.prefix_indent == paragraph.prefix_indent)
+    }
+
+    /// Choose where to break the paragraph, working back from its end so
+    /// each word knows the cheapest way to set everything after it.
+    fn breaks(&self, paragraph: &Paragraph) -> Vec<usize> {
+        let words = &paragraph.words;
+        let count = words.len();
+        let mut best_cost: Vec<Cost> = vec![0; count + 1];
+        let mut next_break = vec![count; count + 1];
+        let mut line_length = vec![0; count + 1];
+        let goal = self.config.goal as Cost;
+
+        let line_cost = |next: usize, len: usize, next_break: &[usize], line_length: &[usize]| {
+            if next == count {
+                return 0;
+            }
+            let mut cost = short_cost(goal - len as Cost);
+            if next_break[next] != count {
+                cost += ragged_cost(len as Cost - line_length[next] as Cost);
+            }
+            cost
+        };
+
+        let base_cost = |this: usize| {
+            let mut cost = LINE_COST;
+            if this > 0 {
+                let before = &words[this - 1];
+                if before.period {
+                    if before.last {
+                        cost -= SENTENCE_BONUS;
+                    } else {
+                        cost += NOBREAK_COST;
+                    }
+                } else if before.punct {
+                    cost -= PUNCT_BONUS;
+                } else if this > 1 && words[this - 2].last {
+                    cost += widow_cost(before.length);
+                }
+            }
+            if words[this].paren {
+                cost -= PAREN_BONUS;
+            } else if words[this].last {
+                cost += orphan_cost(words[this].length);
+            }
+            cost
+        };
+
+        for start in (0..count).rev() {
+            let mut best = Cost::MAX;
+            let mut len = if start == 0 {
+                paragraph.first_indent
+            } else {
+                self.other_indent
+            };
+            // Every line holds at least one word, however long.
+            len += words[start].length;
+            let mut next = start;
+            loop {
+                next += 1;
+                let cost = line_cost(next, len, &next_break, &line_length) + best_cost[next];
+                if cost < best {
+                    best = cost;
+                    next_break[start] = next;
+                    line_length[start] = len;
+                }
+                if next == count {
+                    break;
+                }
+                len += words[next - 1].space + words[next].length;
+                if len >= self.config.width {
+                    break;
+                }
+            }
+            best_cost[start] = best.saturating_add(base_cost(start));
+        }
+
+        let mut breaks = Vec::new();
+        let mut start = 0;
+        while start < count {
+            start = next_break[start];
+            breaks.push(start);
+        }
+        breaks
+    }
+
+    /// Write `space` columns of blanks from `column`, using tabs when the
+    /// input did.
+    fn write_space<W: Write>(
+        &self,
+        out: &mut W,
+        column: &mut usize,
+        space: usize,
+    ) -> io::Result<()> {
+        let target = *column + space;
+        if self.tabs {
+            let tab_target = target / TAB_STOP * TAB_STOP;
+            if *column + 1 < tab_target {
+                while *column < tab_target {
+                    out.write_all(b"\t")?;
+                    *column = (*column / TAB_STOP + 1) * TAB_STOP;
+                }
+            }
+        }
+        while *column < target {
+            out.write_all(b" ")?;
+            *column += 1;
+        }
+        Ok(())
+    }
+
+    fn write_paragraph<W: Write>(&self, paragraph: &Paragraph, out: &mut W) -> io::Result<()> {
+        let mut start = 0;
+        for end in self.breaks(paragraph) {
+            let indent = if start == 0 {
+                paragraph.first_indent
+            } else {
+                self.other_indent
+            };
+            let mut column = 0;
+            self.write_space(out, &mut column, paragraph.prefix_indent)?;
+            out.write_all(self.prefix)?;
+            column += width::bytes_width(self.prefix);
+            let pad = indent.saturating_sub(column);
+            self.write_space(out, &mut column, pad)?;
+
+            let words = &paragraph.words[start..end];
+            for (idx, word) in words.iter().enumerate() {
+                out.write_all(&paragraph.text[word.text.clone()])?;
+                column += word.length;
+                if idx + 1 < words.len() {
+                    self.write_space(out, &mut column, word.space)?;
+                }
+            }
+            out.write_all(&paragraph.newline)?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn fmt(args: &[&str], input: &str) -> String {
+        let mut all: Vec<String> = string_vec!["fmt.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = FmtConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        Formatter::new(&config)
+            .format(input.as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn joins_short_lines() {
+        assert_eq!(fmt(&[], "one\ntwo\nthree\n"), "one two three\n");
+        assert_eq!(fmt(&[], "a\n\nb\n"), "a\n\nb\n");
+        assert_eq!(fmt(&[], "last line"), "last line\n");
+        assert_eq!(fmt(&[], "dos\r\ntext\r\n"), "dos text\r\n");
+    }
+
+    #[test]
+    fn breaks_long_lines() {
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(4);
+        let out = fmt(&["-w", "30"], &text);
+        assert!(out.lines().all(|line| line.len() < 30), "{}", out);
+        let words: Vec<&str> = out.split_whitespace().collect();
+        assert_eq!(words, text.split_whitespace().collect::<Vec<_>>());
+
+        // A word longer than the width sits on its own line.
+        assert_eq!(fmt(&["-w", "5"], "a abcdefgh b\n"), "a\nabcdefgh\nb\n");
+    }
+
+    #[test]
+    fn goal() {
+        assert_eq!(
+            fmt(&["-w", "20"], "aaa bbb ccc ddd eee fff ggg\n"),
+            "aaa bbb ccc ddd eee\nfff ggg\n"
+        );
+        assert_eq!(
+            fmt(&["-w", "20", "-g", "16"], "aaa bbb ccc ddd eee fff ggg\n"),
+            "aaa bbb ccc ddd\neee fff ggg\n"
+        );
+    }
+
+    #[test]
+    fn spacing() {
+        assert_eq!(fmt(&[], "a  b.  c\nd. e\n"), "a  b.  c d. e\n");
+        assert_eq!(fmt(&["-u"], "a  b.  c\nd.   e\n"), "a b.  c d.  e\n");
+        // One space after a period within a line is not a sentence end.
+        assert_eq!(fmt(&["-u"], "Dr. Who\n"), "Dr. Who\n");
+    }
+
+    #[test]
+    fn indentation() {
+        // Indentation changes start a new paragraph.
+        assert_eq!(fmt(&[], "  a\n  b\nc\n"), "  a b\nc\n");
+        assert_eq!(
+            fmt(&["-c"], "  first\nsecond\nthird\n"),
+            "  first second third\n"
+        );
+        assert_eq!(
+            fmt(&["-c", "-w", "12"], "  aaa bbb ccc\n    ddd eee\n"),
+            "  aaa bbb\n    ccc\n    ddd eee\n"
+        );
+        assert_eq!(
+            fmt(&["-t", "-w", "12"], "aaa bbb ccc ddd\n  eee\n"),
+            "aaa bbb ccc\n  ddd eee\n"
+        );
+        assert_eq!(
+            fmt(&["-t", "-w", "10"], "aaa bbb ccc\n"),
+            "aaa bbb\n   ccc\n"
+        );
+    }
+
+    #[test]
+    fn split_only() {
+        assert_eq!(fmt(&["-s"], "one\ntwo\n"), "one\ntwo\n");
+        assert_eq!(
+            fmt(&["-s", "-w", "10"], "aaa bbb ccc\nd\n"),
+            "aaa\nbbb ccc\nd\n"
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(
+            fmt(&["-p", "# "], "# one\n# two\ncode\n#three\n"),
+            "# one two\ncode\n#three\n"
+        );
+        assert_eq!(
+            fmt(&["-p", "//", "-w", "14"], "  // aaa bbb ccc\n  // ddd\n"),
+            "  // aaa bbb\n  // ccc ddd\n"
+        );
+        // Blank lines after the prefix are kept as they are.
+        assert_eq!(fmt(&["-p", "#"], "# a\n#\n# b\n"), "# a\n#\n# b\n");
+    }
+
+    #[test]
+    fn tabs() {
+        assert_eq!(fmt(&[], "\tone\n\ttwo\n"), "\tone two\n");
+        assert_eq!(fmt(&[], "        one\n"), "        one\n");
+    }
+
+    #[test]
+    fn wide_characters() {
+        assert_eq!(
+            fmt(&["-w", "10"], "日本 語です 日本語\n"),
+            "日本\n語です\n日本語\n"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+#[derive(Debug)]
+pub struct FoldConfig {
+    pub files: Vec<String>,
+    /// Count bytes rather than display columns.
+    pub bytes: bool,
+    /// Break after the last blank that fits rather than mid-word.
+    pub spaces: bool,
+    pub width: usize,
+}
+
+const HELP_TEXT: &str = "Usage: fold [OPTION]... [FILE]...
+Wrap input lines in each FILE, writing to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --bytes         count bytes rather than columns
+  -s, --spaces        break at spaces
+  -w, --width=WIDTH   use WIDTH columns instead of 80
+      --no-glob       do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Columns are display columns: wide characters take two and combining marks
+none.  A carriage return before the newline is not part of the line.
+";
+
+impl Config for FoldConfig {
+    fn bin_name(&self) -> &'static str {
+        "fold"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_width(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(format!("invalid number of columns: '{}'", value)),
+    }
+}
+
+impl FoldConfig {
+    pub fn new() -> FoldConfig {
+        FoldConfig {
+            files: Vec::new(),
+            bytes: false,
+            spaces: false,
+            width: 80,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--bytes", None) | ("-b", None) => self.bytes = true,
+                ("--spaces", None) | ("-s", None) => self.spaces = true,
+                ("--width", Some(value)) | ("-w", Some(value)) => self.width = parse_width(value)?,
+                ("-w", None) => return Err("option requires an argument -- 'w'".to_string()),
+                // Obsolete `fold -72` form.
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    self.width = parse_width(&number[1..])?
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try fold --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::FoldConfig;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<FoldConfig, String> {
+        let mut config = FoldConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["fold.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!(config.width, 80);
+        assert!(!config.bytes);
+        assert!(!config.spaces);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec!["fold.exe", "-bs", "-w", "20", "file"]).unwrap();
+        assert!(config.bytes);
+        assert!(config.spaces);
+        assert_eq!(config.width, 20);
+        assert_eq!(config.files, vec!["file"]);
+
+        assert_eq!(parse(string_vec!["fold.exe", "-12"]).unwrap().width, 12);
+        assert_eq!(
+            parse(string_vec!["fold.exe", "--width=7"]).unwrap().width,
+            7
+        );
+    }
+
+    #[test]
+    fn invalid_width() {
+        for width in ["0", "x", "-3", ""] {
+            assert_eq!(
+                parse(string_vec!["fold.exe", "-w", width]).unwrap_err(),
+                format!("invalid number of columns: '{}'", width)
+            );
+        }
+        assert!(parse(string_vec!["fold.exe", "-x"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = FoldConfig::new();
+        let parse_result = config.parse(string_vec!["fold.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = FoldConfig::new();
+        let parse_result = config.parse(string_vec!["fold.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
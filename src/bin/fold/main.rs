@@ -0,0 +1,36 @@
+use std::env;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod wrap;
+
+use config::FoldConfig;
+
+fn main() {
+    let mut config = FoldConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("fold");
+    for file in &config.files {
+        let result = input::open(file).and_then(|reader| wrap::fold(reader, &mut out, &config));
+        if let Err(e) = result {
+            out.flush();
+            eprintln!(
+                "fold: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,170 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+use coreutils::width;
+
+use crate::config::FoldConfig;
+
+fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+/// Wraps lines to a width, holding the part of the current output line
+/// that may still move to the next one under `-s`.
+struct Folder<'a> {
+    config: &'a FoldConfig,
+    held: Vec<u8>,
+    column: usize,
+}
+
+impl Folder<'_> {
+    /// The next piece of `text` and the column after it.
+    fn piece(&self, text: &[u8], column: usize) -> (usize, usize) {
+        if self.config.bytes {
+            return (1, column + 1);
+        }
+        match width::decode(text) {
+            (Some(chr), len) => (len, width::advance(column, chr)),
+            (None, len) => (len, column + 1),
+        }
+    }
+
+    fn column_of(&self, text: &[u8]) -> usize {
+        let mut column = 0;
+        let mut pos = 0;
+        while pos < text.len() {
+            let (len, next) = self.piece(&text[pos..], column);
+            column = next;
+            pos += len;
+        }
+        column
+    }
+
+    fn fold_line<W: Write>(&mut self, line: &[u8], newline: &[u8], out: &mut W) -> io::Result<()> {
+        self.held.clear();
+        self.column = 0;
+        let mut pos = 0;
+        while pos < line.len() {
+            let (len, next) = self.piece(&line[pos..], self.column);
+            if next <= self.config.width {
+                self.held.extend_from_slice(&line[pos..pos + len]);
+                self.column = next;
+                pos += len;
+                continue;
+            }
+
+            if self.config.spaces {
+                if let Some(blank) = self.held.iter().rposition(|&b| is_blank(b)) {
+                    out.write_all(&self.held[..=blank])?;
+                    out.write_all(newline)?;
+                    self.held.drain(..=blank);
+                    self.column = self.column_of(&self.held);
+                    continue;
+                }
+            }
+            if self.held.is_empty() {
+                // A character wider than the line goes on one by itself.
+                self.held.extend_from_slice(&line[pos..pos + len]);
+                self.column = next;
+                pos += len;
+                continue;
+            }
+            out.write_all(&self.held)?;
+            out.write_all(newline)?;
+            self.held.clear();
+            self.column = 0;
+        }
+        out.write_all(&self.held)
+    }
+}
+
+/// Wrap every line of `input` to the configured width. Lines are broken
+/// with the newline that ends them, so CRLF text stays CRLF.
+pub fn fold<R: BufRead, W: Write>(input: R, out: &mut W, config: &FoldConfig) -> io::Result<()> {
+    let mut folder = Folder {
+        config,
+        held: Vec::new(),
+        column: 0,
+    };
+    let mut lines = LineReader::new(input, b'\n');
+    while lines.next_line()? {
+        let newline: &[u8] = if lines.terminator() == b"\r\n" {
+            b"\r\n"
+        } else {
+            b"\n"
+        };
+        folder.fold_line(lines.line(), newline, out)?;
+        out.write_all(lines.terminator())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn fold_bytes(args: &[&str], input: &[u8]) -> Vec<u8> {
+        let mut all: Vec<String> = string_vec!["fold.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = FoldConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        fold(input, &mut out, &config).unwrap();
+        out
+    }
+
+    fn fold_str(args: &[&str], input: &str) -> String {
+        String::from_utf8(fold_bytes(args, input.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(fold_str(&["-w3"], "abcdefgh\n"), "abc\ndef\ngh\n");
+        assert_eq!(fold_str(&["-w3"], "abc\nde"), "abc\nde");
+        assert_eq!(fold_str(&["-w3"], "abcdef\r\n"), "abc\r\ndef\r\n");
+        assert_eq!(fold_str(&["-w5"], "short\n\n"), "short\n\n");
+    }
+
+    #[test]
+    fn wide_characters() {
+        assert_eq!(fold_str(&["-w4"], "日本語です\n"), "日本\n語で\nす\n");
+        assert_eq!(fold_str(&["-w3"], "日本語\n"), "日\n本\n語\n");
+        // A character wider than the line still goes out.
+        assert_eq!(fold_str(&["-w1"], "日本\n"), "日\n本\n");
+        // Combining marks stay with the letter before them.
+        assert_eq!(fold_str(&["-w2"], "ae\u{301}b\n"), "ae\u{301}\nb\n");
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(
+            fold_bytes(&["-b", "-w2"], "日\n".as_bytes()),
+            b"\xe6\x97\n\xa5\n"
+        );
+        assert_eq!(fold_str(&["-b", "-w4"], "a\tbcdef\n"), "a\tbc\ndef\n");
+    }
+
+    #[test]
+    fn tabs_and_control() {
+        assert_eq!(fold_str(&["-w10"], "a\tbcd\n"), "a\tbc\nd\n");
+        assert_eq!(fold_str(&["-w3"], "ab\rcdef\n"), "ab\rcde\nf\n");
+        assert_eq!(fold_str(&["-w3"], "abc\x08d\n"), "abc\x08d\n");
+    }
+
+    #[test]
+    fn spaces() {
+        assert_eq!(
+            fold_str(&["-s", "-w10"], "the quick brown fox\n"),
+            "the quick \nbrown fox\n"
+        );
+        assert_eq!(
+            fold_str(&["-s", "-w4"], "abcdefgh ij\n"),
+            "abcd\nefgh\n ij\n"
+        );
+        assert_eq!(
+            fold_str(&["-s", "-w5"], "ab cd ef gh\n"),
+            "ab \ncd \nef gh\n"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::size;
+
+#[derive(Debug)]
+pub struct HeadConfig {
+    pub files: Vec<String>,
+    pub count: u64,
+    pub bytes: bool,
+    pub all_but_last: bool,
+    pub quiet: bool,
+    pub verbose: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: head [OPTION]... [FILE]...
+Print the first 10 lines of each FILE to standard output.
+With more than one FILE, precede each with a header giving the file name.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -c, --bytes=[-]NUM       print the first NUM bytes of each file;
+                             with the leading '-', print all but the last
+                             NUM bytes of each file
+  -n, --lines=[-]NUM       print the first NUM lines instead of the first 10;
+                             with the leading '-', print all but the last
+                             NUM lines of each file
+  -q, --quiet              never print headers giving file names
+      --silent             same as --quiet
+  -v, --verbose            always print headers giving file names
+  -z, --zero-terminated    line delimiter is NUL, not newline
+      --no-glob            do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+NUM may have a multiplier suffix:
+b 512, kB 1000, K 1024, MB 1000*1000, M 1024*1024,
+GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P, E.
+Binary prefixes can be used, too: KiB=K, MiB=M, and so on.
+";
+
+impl Config for HeadConfig {
+    fn bin_name(&self) -> &'static str {
+        "head"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+impl HeadConfig {
+    pub fn new() -> HeadConfig {
+        HeadConfig {
+            files: Vec::new(),
+            count: 10,
+            bytes: false,
+            all_but_last: false,
+            quiet: false,
+            verbose: false,
+            zero_terminated: false,
+        }
+    }
+
+    fn set_count(&mut self, option: &str, value: Option<&str>, bytes: bool) -> Result<(), String> {
+        let value = match value {
+            Some(value) => value,
+            None => return Err(format!("option requires an argument -- '{}'", option)),
+        };
+        let (all_but_last, number) = match value.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, value),
+        };
+        let kind = if bytes { "bytes" } else { "lines" };
+        self.count =
+            size::parse_size(number).map_err(|e| format!("invalid number of {}: {}", kind, e))?;
+        self.all_but_last = all_but_last;
+        self.bytes = bytes;
+        Ok(())
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--bytes", value) | ("-c", value) => self.set_count(&option, value, true)?,
+                ("--lines", value) | ("-n", value) => self.set_count(&option, value, false)?,
+                ("--quiet", None) | ("--silent", None) | ("-q", None) => {
+                    self.quiet = true;
+                    self.verbose = false;
+                }
+                ("--verbose", None) | ("-v", None) => {
+                    self.verbose = true;
+                    self.quiet = false;
+                }
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                // Obsolete `head -5` form.
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    self.set_count(&option, Some(&number[1..]), false)?
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try head --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::HeadConfig;
+    use coreutils::string_vec;
+
+    #[test]
+    fn defaults() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!(config.count, 10);
+        assert!(!config.bytes);
+        assert!(!config.all_but_last);
+        assert!(!config.quiet);
+        assert!(!config.verbose);
+        assert!(!config.zero_terminated);
+    }
+
+    #[test]
+    fn files() {
+        let mut config = HeadConfig::new();
+        config
+            .parse(string_vec!["head.exe", "a.txt", "-", "b.txt"])
+            .unwrap();
+        assert_eq!(config.files, vec!["a.txt", "-", "b.txt"]);
+    }
+
+    #[test]
+    fn lines() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-n", "5"]).unwrap();
+        assert_eq!(config.count, 5);
+        assert!(!config.bytes);
+
+        config.parse(string_vec!["head.exe", "--lines=7"]).unwrap();
+        assert_eq!(config.count, 7);
+
+        config.parse(string_vec!["head.exe", "-n3"]).unwrap();
+        assert_eq!(config.count, 3);
+    }
+
+    #[test]
+    fn lines_all_but_last() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-n", "-5"]).unwrap();
+        assert_eq!(config.count, 5);
+        assert!(config.all_but_last);
+    }
+
+    #[test]
+    fn obsolete_count() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-3", "file"]).unwrap();
+        assert_eq!(config.count, 3);
+        assert!(!config.bytes);
+    }
+
+    #[test]
+    fn bytes() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-c", "2K"]).unwrap();
+        assert_eq!(config.count, 2048);
+        assert!(config.bytes);
+
+        config
+            .parse(string_vec!["head.exe", "--bytes=-1MiB"])
+            .unwrap();
+        assert_eq!(config.count, 1024 * 1024);
+        assert!(config.bytes);
+        assert!(config.all_but_last);
+    }
+
+    #[test]
+    fn last_count_wins() {
+        let mut config = HeadConfig::new();
+        config
+            .parse(string_vec!["head.exe", "-c", "5", "-n", "2"])
+            .unwrap();
+        assert_eq!(config.count, 2);
+        assert!(!config.bytes);
+    }
+
+    #[test]
+    fn invalid_count() {
+        let mut config = HeadConfig::new();
+        assert!(config
+            .parse(string_vec!["head.exe", "-n", "lots"])
+            .unwrap_err()
+            .contains("invalid number of lines"));
+        assert!(config
+            .parse(string_vec!["head.exe", "-c", "5Q"])
+            .unwrap_err()
+            .contains("invalid number of bytes"));
+        assert!(config
+            .parse(string_vec!["head.exe", "-n"])
+            .unwrap_err()
+            .contains("requires an argument"));
+    }
+
+    #[test]
+    fn quiet_verbose() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-q"]).unwrap();
+        assert!(config.quiet);
+        config
+            .parse(string_vec!["head.exe", "--quiet", "--verbose"])
+            .unwrap();
+        assert!(config.verbose);
+        assert!(!config.quiet);
+        config
+            .parse(string_vec!["head.exe", "-v", "--silent"])
+            .unwrap();
+        assert!(config.quiet);
+        assert!(!config.verbose);
+    }
+
+    #[test]
+    fn zero_terminated() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-z"]).unwrap();
+        assert!(config.zero_terminated);
+    }
+
+    #[test]
+    fn bundled() {
+        let mut config = HeadConfig::new();
+        config.parse(string_vec!["head.exe", "-qzn4"]).unwrap();
+        assert!(config.quiet);
+        assert!(config.zero_terminated);
+        assert_eq!(config.count, 4);
+    }
+
+    #[test]
+    fn invalid_option() {
+        let mut config = HeadConfig::new();
+        assert!(config
+            .parse(string_vec!["head.exe", "--bogus"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = HeadConfig::new();
+        let parse_result = config.parse(string_vec!["head.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = HeadConfig::new();
+        let parse_result = config.parse(string_vec!["head.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,71 @@
+use std::env;
+use std::io::{BufRead, Write};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod stream;
+
+fn head<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    config: &config::HeadConfig,
+) -> std::io::Result<()> {
+    let delimiter = if config.zero_terminated { b'\0' } else { b'\n' };
+    match (config.bytes, config.all_but_last) {
+        (true, false) => stream::first_bytes(reader, writer, config.count),
+        (true, true) => stream::all_but_last_bytes(reader, writer, config.count),
+        (false, false) => stream::first_lines(reader, writer, config.count, delimiter),
+        (false, true) => stream::all_but_last_lines(reader, writer, config.count, delimiter),
+    }
+}
+
+fn main() {
+    let mut config = config::HeadConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let headers = config.verbose || (!config.quiet && config.files.len() > 1);
+    let mut first = true;
+    let mut out = Output::stdout("head");
+
+    for file in &config.files {
+        let mut reader = match input::open(file) {
+            Ok(reader) => reader,
+            Err(e) => {
+                out.flush();
+                eprintln!(
+                    "head: cannot open '{}' for reading: {}",
+                    file,
+                    output::describe(&e)
+                );
+                exit_code = 1;
+                continue;
+            }
+        };
+
+        if headers {
+            let separator = if first { "" } else { "\n" };
+            writeln!(out, "{}==> {} <==", separator, input::display_name(file));
+        }
+        first = false;
+
+        if let Err(e) = head(&mut reader, &mut out, &config) {
+            out.flush();
+            eprintln!(
+                "head: error reading '{}': {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+pub fn first_bytes<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+) -> io::Result<()> {
+    let mut remaining = count;
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let take = buf.len().min(remaining.min(usize::MAX as u64) as usize);
+        writer.write_all(&buf[..take])?;
+        reader.consume(take);
+        remaining -= take as u64;
+    }
+    Ok(())
+}
+
+/// Copy lines until `count` delimiters have been written, reading no
+/// further than the buffer holding the last one.
+pub fn first_lines<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut remaining = count;
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+
+        let mut end = buf.len();
+        for (pos, _) in buf.iter().enumerate().filter(|(_, &b)| b == delimiter) {
+            remaining -= 1;
+            if remaining == 0 {
+                end = pos + 1;
+                break;
+            }
+        }
+        writer.write_all(&buf[..end])?;
+        reader.consume(end);
+    }
+    Ok(())
+}
+
+/// Hold back the most recent `count` bytes, writing everything older.
+pub fn all_but_last_bytes<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+) -> io::Result<()> {
+    let mut held: VecDeque<u8> = VecDeque::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+        held.extend(buf);
+        let len = buf.len();
+        reader.consume(len);
+
+        let excess = (held.len() as u64).saturating_sub(count) as usize;
+        if excess > 0 {
+            let (front, back) = held.as_slices();
+            let from_front = excess.min(front.len());
+            writer.write_all(&front[..from_front])?;
+            writer.write_all(&back[..excess - from_front])?;
+            held.drain(..excess);
+        }
+    }
+}
+
+/// Hold back the most recent `count` lines in a ring, writing each line
+/// as it falls out.
+pub fn all_but_last_lines<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut held: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut line = Vec::new();
+    loop {
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            return Ok(());
+        }
+        held.push_back(line);
+
+        line = if held.len() as u64 > count {
+            let mut oldest = held.pop_front().expect("ring is never empty here");
+            writer.write_all(&oldest)?;
+            oldest.clear();
+            oldest
+        } else {
+            Vec::new()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::test_utils;
+    use std::io::Cursor;
+
+    fn run<F>(input: &str, f: F) -> String
+    where
+        F: Fn(&mut Cursor<&[u8]>, &mut Vec<u8>) -> io::Result<()>,
+    {
+        let mut reader = Cursor::new(input.as_bytes());
+        let mut output = Vec::new();
+        f(&mut reader, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn lines() {
+        let output = run(test_utils::MULTI, |r, w| first_lines(r, w, 2, b'\n'));
+        assert_eq!(output, "this is a text file\r\nwith multiple lines\r\n");
+    }
+
+    #[test]
+    fn lines_more_than_input() {
+        let output = run(test_utils::MULTI, |r, w| first_lines(r, w, 100, b'\n'));
+        assert_eq!(output, test_utils::MULTI);
+    }
+
+    #[test]
+    fn lines_zero() {
+        let output = run(test_utils::MULTI, |r, w| first_lines(r, w, 0, b'\n'));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn lines_stop_reading() {
+        let mut reader = Cursor::new("one\ntwo\nthree\n".as_bytes());
+        let mut output = Vec::new();
+        first_lines(&mut reader, &mut output, 1, b'\n').unwrap();
+        assert_eq!(output, b"one\n");
+        assert_eq!(reader.position(), 4);
+    }
+
+    #[test]
+    fn lines_nul_terminated() {
+        let output = run("a\nb\0c\0d\0", |r, w| first_lines(r, w, 2, b'\0'));
+        assert_eq!(output, "a\nb\0c\0");
+    }
+
+    #[test]
+    fn lines_no_trailing_newline() {
+        let output = run("one\ntwo", |r, w| first_lines(r, w, 5, b'\n'));
+        assert_eq!(output, "one\ntwo");
+    }
+
+    #[test]
+    fn bytes() {
+        let output = run(test_utils::ASCII, |r, w| first_bytes(r, w, 9));
+        assert_eq!(output, "The quick");
+    }
+
+    #[test]
+    fn bytes_more_than_input() {
+        let output = run(test_utils::ASCII, |r, w| first_bytes(r, w, 1000));
+        assert_eq!(output, test_utils::ASCII);
+    }
+
+    #[test]
+    fn all_but_last_lines_drops_tail() {
+        let output = run(test_utils::MULTI, |r, w| all_but_last_lines(r, w, 1, b'\n'));
+        assert_eq!(output, "this is a text file\r\nwith multiple lines\r\n");
+    }
+
+    #[test]
+    fn all_but_last_lines_zero() {
+        let output = run(test_utils::MULTI, |r, w| all_but_last_lines(r, w, 0, b'\n'));
+        assert_eq!(output, test_utils::MULTI);
+    }
+
+    #[test]
+    fn all_but_last_lines_everything() {
+        let output = run(test_utils::MULTI, |r, w| all_but_last_lines(r, w, 3, b'\n'));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn all_but_last_bytes_drops_tail() {
+        let output = run(test_utils::ASCII, |r, w| all_but_last_bytes(r, w, 2));
+        assert_eq!(output, "The quick brown fox jumps over the lazy dog");
+    }
+
+    #[test]
+    fn all_but_last_bytes_small_buffer() {
+        let input = "0123456789".repeat(10);
+        let mut reader = io::BufReader::with_capacity(3, input.as_bytes());
+        let mut output = Vec::new();
+        all_but_last_bytes(&mut reader, &mut output, 15).unwrap();
+        assert_eq!(output, &input.as_bytes()[..85]);
+    }
+
+    #[test]
+    fn all_but_last_bytes_everything() {
+        let output = run(test_utils::ASCII, |r, w| all_but_last_bytes(r, w, 1000));
+        assert_eq!(output, "");
+    }
+}
@@ -0,0 +1,392 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+/// When lines out of order are reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckOrder {
+    /// Once a line has failed to pair, as GNU join does.
+    Unpaired,
+    /// From the start, stopping at the first line out of order.
+    Always,
+    Never,
+}
+
+/// One field of `-o` output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputField {
+    /// The join field, from whichever file has the line.
+    Join,
+    /// A file, 0 or 1, and a field of its line counted from 0.
+    Field(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Format {
+    /// The join field, then the other fields of each line.
+    Default,
+    /// As the default, with as many fields from each file as its first
+    /// line has.
+    Auto,
+    List(Vec<OutputField>),
+}
+
+#[derive(Debug)]
+pub struct JoinConfig {
+    pub files: [String; 2],
+    /// The join field of each file, counted from 0.
+    pub fields: [usize; 2],
+    /// Splits fields and separates them in output. Fields are runs of
+    /// non-blanks when not given.
+    pub separator: Option<u8>,
+    /// Print the lines of each file that pair with none.
+    pub unpaired: [bool; 2],
+    /// Print the lines that pair; turned off by `-v`.
+    pub paired: bool,
+    /// Printed for fields a line does not have.
+    pub empty: Option<Vec<u8>>,
+    pub format: Format,
+    pub ignore_case: bool,
+    pub check_order: CheckOrder,
+    pub header: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: join [OPTION]... FILE1 FILE2
+For each pair of input lines with identical join fields, write a line to
+standard output.  The default join field is the first, delimited by blanks.
+
+When FILE1 or FILE2 (not both) is -, read standard input.
+
+  -a FILENUM        also print unpairable lines from file FILENUM, where
+                      FILENUM is 1 or 2, corresponding to FILE1 or FILE2
+  -e STRING         replace missing input fields with STRING
+  -i, --ignore-case  ignore differences in case when comparing fields
+  -j FIELD          equivalent to '-1 FIELD -2 FIELD'
+  -o FORMAT         obey FORMAT while constructing output line
+  -t CHAR           use CHAR as input and output field separator
+  -v FILENUM        like -a FILENUM, but suppress joined output lines
+  -1 FIELD          join on this FIELD of file 1
+  -2 FIELD          join on this FIELD of file 2
+      --check-order   check that the input is correctly sorted, even
+                        if all input lines are pairable
+      --nocheck-order  do not check that the input is correctly sorted
+      --header      treat the first line in each file as field headers,
+                      print them without trying to pair them
+  -z, --zero-terminated  line delimiter is NUL, not newline
+      --help     display this help and exit
+      --version  output version information and exit
+
+Unless -t CHAR is given, leading blanks separate fields and are ignored,
+else fields are separated by CHAR; -t '' makes the whole line one field.
+Any FIELD is a field number counted from 1.  FORMAT is one or more comma
+or blank separated specifications, each being 'FILENUM.FIELD' or '0'.
+Default FORMAT outputs the join field, the remaining fields from FILE1,
+the remaining fields from FILE2, all separated by CHAR.  If FORMAT is the
+keyword 'auto', then the first line of each file determines the number
+of fields output for each line.
+
+Important: FILE1 and FILE2 must be sorted on the join fields, byte by
+byte, as 'sort -k 1b,1' sorts them.  Only one line of each file is held
+at a time, besides runs of lines with the same join field.  A carriage
+return before the newline is not part of the line.
+";
+
+impl Config for JoinConfig {
+    fn bin_name(&self) -> &'static str {
+        "join"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try join --help for more information", message)
+}
+
+fn parse_field(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(field) if field > 0 => Ok(field - 1),
+        _ => Err(format!("invalid field number: '{}'", value)),
+    }
+}
+
+fn parse_file_number(value: &str) -> Result<usize, String> {
+    match value {
+        "1" => Ok(0),
+        "2" => Ok(1),
+        _ => Err(format!("invalid file number: '{}'", value)),
+    }
+}
+
+fn parse_separator(text: &str) -> Result<u8, String> {
+    match text.as_bytes() {
+        // No byte ends a field, so each line is one field.
+        [] => Ok(b'\n'),
+        [byte] => Ok(*byte),
+        b"\\0" => Ok(0),
+        _ => Err(format!("multi-character tab '{}'", text)),
+    }
+}
+
+/// Add the fields of one `-o` argument to `list`.
+fn parse_format(value: &str, list: &mut Vec<OutputField>) -> Result<(), String> {
+    for spec in value
+        .split([',', ' ', '\t'])
+        .filter(|spec| !spec.is_empty())
+    {
+        if spec == "0" {
+            list.push(OutputField::Join);
+            continue;
+        }
+        let (file, field) = spec
+            .split_once('.')
+            .ok_or_else(|| format!("invalid field specifier: '{}'", spec))?;
+        let file = parse_file_number(file)
+            .map_err(|_| format!("invalid file number in field spec: '{}'", spec))?;
+        list.push(OutputField::Field(file, parse_field(field)?));
+    }
+    Ok(())
+}
+
+impl JoinConfig {
+    pub fn new() -> JoinConfig {
+        JoinConfig {
+            files: [String::new(), String::new()],
+            fields: [0, 0],
+            separator: None,
+            unpaired: [false, false],
+            paired: true,
+            empty: None,
+            format: Format::Default,
+            ignore_case: false,
+            check_order: CheckOrder::Unpaired,
+            header: false,
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let mut list = Vec::new();
+        let mut auto = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("-a", Some(file)) => self.unpaired[parse_file_number(file)?] = true,
+                ("-v", Some(file)) => {
+                    self.unpaired[parse_file_number(file)?] = true;
+                    self.paired = false;
+                }
+                ("-e", Some(text)) => self.empty = Some(text.as_bytes().to_vec()),
+                ("--ignore-case", None) | ("-i", None) => self.ignore_case = true,
+                ("-j", Some(field)) => self.fields = [parse_field(field)?; 2],
+                ("-1", Some(field)) => self.fields[0] = parse_field(field)?,
+                ("-2", Some(field)) => self.fields[1] = parse_field(field)?,
+                ("-o", Some("auto")) => auto = true,
+                ("-o", Some(format)) => parse_format(format, &mut list)?,
+                ("-t", Some(text)) => {
+                    let separator = parse_separator(text)?;
+                    if self.separator.is_some_and(|tab| tab != separator) {
+                        return Err(String::from("incompatible tabs"));
+                    }
+                    self.separator = Some(separator);
+                }
+                ("--check-order", None) => self.check_order = CheckOrder::Always,
+                ("--nocheck-order", None) => self.check_order = CheckOrder::Never,
+                ("--header", None) => self.header = true,
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                (name, None)
+                    if matches!(name, "-a" | "-e" | "-j" | "-o" | "-t" | "-v" | "-1" | "-2") =>
+                {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => return Err(usage_error(&format!("invalid option: {}", option))),
+            }
+        }
+        self.format = match (auto, list.is_empty()) {
+            (false, true) => Format::Default,
+            (true, true) => Format::Auto,
+            (false, false) => Format::List(list),
+            (true, false) => return Err(String::from("invalid field specifier: 'auto'")),
+        };
+
+        let mut operands = base_config.parameters.into_iter();
+        match (operands.next(), operands.next(), operands.next()) {
+            (None, _, _) => return Err(usage_error("missing operand")),
+            (Some(first), None, _) => {
+                return Err(usage_error(&format!("missing operand after '{}'", first)))
+            }
+            (Some(_), Some(_), Some(extra)) => {
+                return Err(usage_error(&format!("extra operand '{}'", extra)))
+            }
+            (Some(first), Some(second), None) => self.files = [first, second],
+        }
+        if self.files.iter().all(|file| file == input::STDIN) {
+            return Err(String::from("both files cannot be standard input"));
+        }
+        Ok(())
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{CheckOrder, Format, JoinConfig, OutputField};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<JoinConfig, String> {
+        let mut config = JoinConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["join.exe", "a", "b"]).unwrap();
+        assert_eq!(config.files, ["a", "b"]);
+        assert_eq!(config.fields, [0, 0]);
+        assert_eq!(config.separator, None);
+        assert_eq!(config.unpaired, [false, false]);
+        assert!(config.paired);
+        assert_eq!(config.format, Format::Default);
+        assert_eq!(config.check_order, CheckOrder::Unpaired);
+        assert!(!config.header && !config.ignore_case);
+        assert_eq!(config.delimiter(), b'\n');
+    }
+
+    #[test]
+    fn fields() {
+        let config = parse(string_vec!["join.exe", "-j", "2", "a", "b"]).unwrap();
+        assert_eq!(config.fields, [1, 1]);
+        let config = parse(string_vec!["join.exe", "-1", "3", "-2", "1", "a", "b"]).unwrap();
+        assert_eq!(config.fields, [2, 0]);
+        assert_eq!(
+            parse(string_vec!["join.exe", "-1", "0", "a", "b"]).unwrap_err(),
+            "invalid field number: '0'"
+        );
+    }
+
+    #[test]
+    fn unpaired() {
+        let config = parse(string_vec!["join.exe", "-a1", "-a", "2", "a", "b"]).unwrap();
+        assert_eq!(config.unpaired, [true, true]);
+        assert!(config.paired);
+        let config = parse(string_vec!["join.exe", "-v", "2", "a", "b"]).unwrap();
+        assert_eq!(config.unpaired, [false, true]);
+        assert!(!config.paired);
+        assert_eq!(
+            parse(string_vec!["join.exe", "-a", "3", "a", "b"]).unwrap_err(),
+            "invalid file number: '3'"
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let config = parse(string_vec![
+            "join.exe", "-o", "1.2,0", "-o", "2.1 2.3", "-e", "-", "a", "b"
+        ])
+        .unwrap();
+        assert_eq!(
+            config.format,
+            Format::List(vec![
+                OutputField::Field(0, 1),
+                OutputField::Join,
+                OutputField::Field(1, 0),
+                OutputField::Field(1, 2),
+            ])
+        );
+        assert_eq!(config.empty.as_deref(), Some(&b"-"[..]));
+
+        let config = parse(string_vec!["join.exe", "-o", "auto", "a", "b"]).unwrap();
+        assert_eq!(config.format, Format::Auto);
+
+        let error = |format: &str| {
+            parse(vec![
+                "join.exe".to_string(),
+                "-o".to_string(),
+                format.to_string(),
+                "a".to_string(),
+                "b".to_string(),
+            ])
+            .unwrap_err()
+        };
+        assert_eq!(error("1"), "invalid field specifier: '1'");
+        assert_eq!(error("3.1"), "invalid file number in field spec: '3.1'");
+        assert_eq!(error("1.0"), "invalid field number: '0'");
+    }
+
+    #[test]
+    fn separators() {
+        let separator = |tab: &str| {
+            parse(vec![
+                "join.exe".to_string(),
+                "-t".to_string(),
+                tab.to_string(),
+                "a".to_string(),
+                "b".to_string(),
+            ])
+            .map(|config| config.separator)
+        };
+        assert_eq!(separator(","), Ok(Some(b',')));
+        assert_eq!(separator("\\0"), Ok(Some(0)));
+        assert_eq!(separator(""), Ok(Some(b'\n')));
+        assert_eq!(separator("ab"), Err("multi-character tab 'ab'".to_string()));
+        assert_eq!(
+            parse(string_vec!["join.exe", "-t,", "-t;", "a", "b"]).unwrap_err(),
+            "incompatible tabs"
+        );
+    }
+
+    #[test]
+    fn flags() {
+        let config = parse(string_vec![
+            "join.exe",
+            "-iz",
+            "--header",
+            "--check-order",
+            "a",
+            "-"
+        ])
+        .unwrap();
+        assert!(config.ignore_case && config.header);
+        assert_eq!(config.delimiter(), b'\0');
+        assert_eq!(config.check_order, CheckOrder::Always);
+        let config = parse(string_vec!["join.exe", "--nocheck-order", "a", "b"]).unwrap();
+        assert_eq!(config.check_order, CheckOrder::Never);
+    }
+
+    #[test]
+    fn operands() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert!(error(string_vec!["join.exe"]).starts_with("missing operand\n"));
+        assert!(error(string_vec!["join.exe", "a"]).starts_with("missing operand after 'a'"));
+        assert!(error(string_vec!["join.exe", "a", "b", "c"]).starts_with("extra operand 'c'"));
+        assert_eq!(
+            error(string_vec!["join.exe", "-", "-"]),
+            "both files cannot be standard input"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = JoinConfig::new();
+        let parse_result = config.parse(string_vec!["join.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = JoinConfig::new();
+        let parse_result = config.parse(string_vec!["join.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod pairs;
+
+use config::JoinConfig;
+
+fn main() {
+    let mut config = JoinConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut readers = Vec::new();
+    for file in &config.files {
+        match input::open(file) {
+            Ok(reader) => readers.push(reader),
+            Err(e) => {
+                eprintln!(
+                    "join: {}: {}",
+                    input::display_name(file),
+                    output::describe(&e)
+                );
+                process::exit(1);
+            }
+        }
+    }
+    let second = readers.pop().unwrap();
+    let first = readers.pop().unwrap();
+
+    let mut out = Output::stdout("join");
+    let result = pairs::join([first, second], &mut out, &config);
+    out.flush();
+    match result {
+        Ok(true) => {}
+        Ok(false) => {
+            eprintln!("join: input is not in sorted order");
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("join: {}", output::describe(&e));
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+use coreutils::input;
+use coreutils::lines::LineReader;
+
+use crate::config::{CheckOrder, Format, JoinConfig, OutputField};
+
+/// Bytes that separate fields when no `-t` is given.
+fn is_blank(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n')
+}
+
+fn compare(a: &[u8], b: &[u8], ignore_case: bool) -> Ordering {
+    if ignore_case {
+        let upper = |text: &[u8]| text.iter().map(u8::to_ascii_uppercase).collect::<Vec<u8>>();
+        upper(a).cmp(&upper(b))
+    } else {
+        a.cmp(b)
+    }
+}
+
+/// A line with the bounds of its fields.
+#[derive(Default)]
+struct Line {
+    text: Vec<u8>,
+    fields: Vec<(usize, usize)>,
+}
+
+impl Line {
+    fn set(&mut self, text: &[u8], separator: Option<u8>) {
+        self.text.clear();
+        self.text.extend_from_slice(text);
+        self.fields.clear();
+        match separator {
+            Some(_) if text.is_empty() => {}
+            Some(separator) => {
+                let mut start = 0;
+                for (pos, &byte) in text.iter().enumerate() {
+                    if byte == separator {
+                        self.fields.push((start, pos));
+                        start = pos + 1;
+                    }
+                }
+                self.fields.push((start, text.len()));
+            }
+            None => {
+                let mut pos = 0;
+                loop {
+                    while pos < text.len() && is_blank(text[pos]) {
+                        pos += 1;
+                    }
+                    if pos == text.len() {
+                        break;
+                    }
+                    let start = pos;
+                    while pos < text.len() && !is_blank(text[pos]) {
+                        pos += 1;
+                    }
+                    self.fields.push((start, pos));
+                }
+            }
+        }
+    }
+
+    fn field(&self, field: usize) -> Option<&[u8]> {
+        self.fields
+            .get(field)
+            .map(|&(start, end)| &self.text[start..end])
+    }
+
+    /// The join field; a line without one joins on the empty string.
+    fn key(&self, field: usize) -> &[u8] {
+        self.field(field).unwrap_or(b"")
+    }
+}
+
+/// One sorted input, holding a run of lines with the same join field.
+struct Input<R: BufRead> {
+    lines: LineReader<R>,
+    name: String,
+    number: u64,
+    field: usize,
+    /// The current line, then the rest of its run; lines past `count` are
+    /// kept only to reuse their memory.
+    group: Vec<Line>,
+    count: usize,
+    /// The join field of the line read last, to check the order.
+    previous: Option<Vec<u8>>,
+}
+
+/// Joins two inputs, remembering whether any line has failed to pair and
+/// which inputs have been reported out of order.
+struct Joiner<'a, R: BufRead, W: Write> {
+    config: &'a JoinConfig,
+    inputs: [Input<R>; 2],
+    out: &'a mut W,
+    unpaired: bool,
+    reported: [bool; 2],
+    /// Fields printed from each file under `-o auto`.
+    widths: [usize; 2],
+}
+
+impl<R: BufRead, W: Write> Joiner<'_, R, W> {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        compare(a, b, self.config.ignore_case)
+    }
+
+    /// Read the next line of input `file` onto the end of its run,
+    /// returning false at the end of the input.
+    fn read(&mut self, file: usize) -> io::Result<bool> {
+        let separator = self.config.separator;
+        let input = &mut self.inputs[file];
+        if !input.lines.next_line().map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!(
+                    "{}: {}",
+                    input::display_name(&input.name),
+                    coreutils::output::describe(&e)
+                ),
+            )
+        })? {
+            return Ok(false);
+        }
+        input.number += 1;
+        if input.group.len() == input.count {
+            input.group.push(Line::default());
+        }
+        input.group[input.count].set(input.lines.line(), separator);
+        input.count += 1;
+
+        let checking = match self.config.check_order {
+            CheckOrder::Always => true,
+            CheckOrder::Unpaired => self.unpaired,
+            CheckOrder::Never => false,
+        };
+        let ignore_case = self.config.ignore_case;
+        let Input {
+            group,
+            count,
+            field,
+            previous,
+            ..
+        } = &mut self.inputs[file];
+        let key = group[*count - 1].key(*field);
+        let disordered = checking
+            && !self.reported[file]
+            && previous
+                .as_ref()
+                .is_some_and(|previous| compare(previous, key, ignore_case) == Ordering::Greater);
+        let previous = previous.get_or_insert_with(Vec::new);
+        previous.clear();
+        previous.extend_from_slice(key);
+
+        if disordered {
+            let input = &self.inputs[file];
+            let message = format!(
+                "{}:{}: is not sorted: {}",
+                input.name,
+                input.number,
+                String::from_utf8_lossy(input.lines.line())
+            );
+            if self.config.check_order == CheckOrder::Always {
+                return Err(io::Error::other(message));
+            }
+            self.out.flush()?;
+            eprintln!("join: {}", message);
+            self.reported[file] = true;
+        }
+        Ok(true)
+    }
+
+    /// Drop the current line of input `file` and read the next.
+    fn advance(&mut self, file: usize) -> io::Result<()> {
+        self.inputs[file].count = 0;
+        self.read(file)?;
+        Ok(())
+    }
+
+    fn write_field(&mut self, line: Option<&Line>, field: usize) -> io::Result<()> {
+        match line.and_then(|line| line.field(field)) {
+            Some(text) if !text.is_empty() => self.out.write_all(text),
+            _ => match &self.config.empty {
+                Some(empty) => self.out.write_all(empty),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Write the joined line for a pair, or for a line with no partner.
+    fn write_line(&mut self, first: Option<&Line>, second: Option<&Line>) -> io::Result<()> {
+        let lines = [first, second];
+        let fields = [self.inputs[0].field, self.inputs[1].field];
+        let config = self.config;
+        let separator = [config.separator.unwrap_or(b' ')];
+        match &config.format {
+            Format::List(list) => {
+                for (idx, output) in list.iter().enumerate() {
+                    if idx > 0 {
+                        self.out.write_all(&separator)?;
+                    }
+                    match *output {
+                        OutputField::Join => match first {
+                            Some(line) => self.write_field(Some(line), fields[0])?,
+                            None => self.write_field(second, fields[1])?,
+                        },
+                        OutputField::Field(file, field) => self.write_field(lines[file], field)?,
+                    }
+                }
+            }
+            Format::Default | Format::Auto => {
+                match first {
+                    Some(line) => self.write_field(Some(line), fields[0])?,
+                    None => self.write_field(second, fields[1])?,
+                }
+                for file in 0..2 {
+                    let count = match config.format {
+                        Format::Auto => self.widths[file],
+                        _ => lines[file].map_or(0, |line| line.fields.len()),
+                    };
+                    for field in (0..count).filter(|&field| field != fields[file]) {
+                        self.out.write_all(&separator)?;
+                        self.write_field(lines[file], field)?;
+                    }
+                }
+            }
+        }
+        self.out.write_all(&[self.config.delimiter()])
+    }
+
+    /// Write the current line of input `file`, which pairs with nothing.
+    fn write_unpaired(&mut self, file: usize) -> io::Result<()> {
+        if !self.config.unpaired[file] {
+            return Ok(());
+        }
+        let line = std::mem::take(&mut self.inputs[file].group[0]);
+        let result = match file {
+            0 => self.write_line(Some(&line), None),
+            _ => self.write_line(None, Some(&line)),
+        };
+        self.inputs[file].group[0] = line;
+        result
+    }
+
+    /// Read the rest of the run of lines whose join field matches the
+    /// current line of input `file`. The line after the run, if any, is
+    /// left after `count`.
+    fn read_run(&mut self, file: usize) -> io::Result<usize> {
+        loop {
+            if !self.read(file)? {
+                return Ok(self.inputs[file].count);
+            }
+            let input = &self.inputs[file];
+            let key = input.group[0].key(input.field);
+            let next = input.group[input.count - 1].key(input.field);
+            if self.compare(key, next) != Ordering::Equal {
+                return Ok(input.count - 1);
+            }
+        }
+    }
+
+    /// Keep only the line after a run, making it the current line.
+    fn finish_run(&mut self, file: usize, run: usize) {
+        let input = &mut self.inputs[file];
+        if input.count > run {
+            input.group.swap(0, run);
+            input.count = 1;
+        } else {
+            input.count = 0;
+        }
+    }
+
+    fn write_pairs(&mut self, runs: [usize; 2]) -> io::Result<()> {
+        let [first, second] = &mut self.inputs;
+        let (first, second) = (
+            std::mem::take(&mut first.group),
+            std::mem::take(&mut second.group),
+        );
+        let mut result = Ok(());
+        'pairs: for a in &first[..runs[0]] {
+            for b in &second[..runs[1]] {
+                result = self.write_line(Some(a), Some(b));
+                if result.is_err() {
+                    break 'pairs;
+                }
+            }
+        }
+        self.inputs[0].group = first;
+        self.inputs[1].group = second;
+        result
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        for file in 0..2 {
+            self.read(file)?;
+            let input = &self.inputs[file];
+            if input.count > 0 {
+                self.widths[file] = input.group[0].fields.len();
+            }
+        }
+
+        if self.config.header && self.inputs.iter().any(|input| input.count > 0) {
+            let [first, second] = &mut self.inputs;
+            let first = (first.count > 0).then(|| std::mem::take(&mut first.group[0]));
+            let second = (second.count > 0).then(|| std::mem::take(&mut second.group[0]));
+            self.write_line(first.as_ref(), second.as_ref())?;
+            for file in 0..2 {
+                if self.inputs[file].count > 0 {
+                    // Lines after the header are not checked against it.
+                    self.inputs[file].previous = None;
+                    self.advance(file)?;
+                }
+            }
+        }
+
+        while self.inputs.iter().all(|input| input.count > 0) {
+            let order = {
+                let [first, second] = &self.inputs;
+                self.compare(
+                    first.group[0].key(first.field),
+                    second.group[0].key(second.field),
+                )
+            };
+            match order {
+                Ordering::Less => {
+                    self.unpaired = true;
+                    self.write_unpaired(0)?;
+                    self.advance(0)?;
+                }
+                Ordering::Greater => {
+                    self.unpaired = true;
+                    self.write_unpaired(1)?;
+                    self.advance(1)?;
+                }
+                Ordering::Equal => {
+                    let runs = [self.read_run(0)?, self.read_run(1)?];
+                    if self.config.paired {
+                        self.write_pairs(runs)?;
+                    }
+                    self.finish_run(0, runs[0]);
+                    self.finish_run(1, runs[1]);
+                }
+            }
+        }
+
+        // Whatever is left of either input pairs with nothing. It is read
+        // through even when not printed, to check its order, though as in
+        // GNU join it does not start the checking by itself.
+        for file in 0..2 {
+            while self.inputs[file].count > 0 {
+                self.write_unpaired(file)?;
+                self.advance(file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Join the lines of two inputs sorted on their join fields. Returns
+/// false when either was out of order.
+pub fn join<R: BufRead, W: Write>(
+    readers: [R; 2],
+    out: &mut W,
+    config: &JoinConfig,
+) -> io::Result<bool> {
+    let [first, second] = readers;
+    let input = |reader, file: usize| Input {
+        lines: LineReader::new(reader, config.delimiter()),
+        name: config.files[file].clone(),
+        number: 0,
+        field: config.fields[file],
+        group: Vec::new(),
+        count: 0,
+        previous: None,
+    };
+    let mut joiner = Joiner {
+        config,
+        inputs: [input(first, 0), input(second, 1)],
+        out,
+        unpaired: false,
+        reported: [false, false],
+        widths: [0, 0],
+    };
+    joiner.run()?;
+    Ok(joiner.reported == [false, false])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn run(args: &[&str], first: &str, second: &str) -> io::Result<(String, bool)> {
+        let mut all: Vec<String> = string_vec!["join.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        all.extend(["a", "b"].map(String::from));
+        let mut config = JoinConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        let sorted = join([first.as_bytes(), second.as_bytes()], &mut out, &config)?;
+        Ok((String::from_utf8(out).unwrap(), sorted))
+    }
+
+    fn joined(args: &[&str], first: &str, second: &str) -> String {
+        run(args, first, second).unwrap().0
+    }
+
+    const FIRST: &str = "a 1\na 2\nb 3\nd 4\n";
+    const SECOND: &str = "a x\na y\nc z\nd w\nd v\n";
+
+    #[test]
+    fn pairs() {
+        assert_eq!(
+            joined(&[], FIRST, SECOND),
+            "a 1 x\na 1 y\na 2 x\na 2 y\nd 4 w\nd 4 v\n"
+        );
+        assert_eq!(joined(&[], "", SECOND), "");
+        assert_eq!(joined(&[], "  a  1\r\n", "a\tX\n"), "a 1 X\n");
+    }
+
+    #[test]
+    fn unpaired() {
+        assert_eq!(joined(&["-v1"], FIRST, SECOND), "b 3\n");
+        assert_eq!(joined(&["-v1", "-v2"], FIRST, SECOND), "b 3\nc z\n");
+        assert_eq!(
+            joined(&["-a2"], "b 1\n", "a x\nb y\nc z\n"),
+            "a x\nb 1 y\nc z\n"
+        );
+    }
+
+    #[test]
+    fn join_fields() {
+        assert_eq!(
+            joined(&["-1", "2", "-2", "1"], "x a\ny b\n", "a 1\nb 2\n"),
+            "a x 1\nb y 2\n"
+        );
+        // A missing join field joins on the empty string.
+        assert_eq!(joined(&["-j", "2"], "a\n", "b\n"), " a b\n");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            joined(&["-o", "0,2.2,1.2"], "a 1\nb 2\n", "a x\nb\n"),
+            "a x 1\nb  2\n"
+        );
+        assert_eq!(
+            joined(
+                &["-o", "1.1,2.1,1.3", "-e", "-", "-a2"],
+                "a 1\n",
+                "a x\nb y\n"
+            ),
+            "a a -\n- b -\n"
+        );
+        assert_eq!(
+            joined(
+                &["-o", "auto", "-e", "E", "-a1", "-a2"],
+                "a 1 p\nb 2\n",
+                "a x\nc\n"
+            ),
+            "a 1 p x\nb 2 E E\nc E E E\n"
+        );
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(
+            joined(&["-t", ","], "k,1,,x\nm,2\n", "k,A\nm,B,C\n"),
+            "k,1,,x,A\nm,2,B,C\n"
+        );
+        assert_eq!(
+            joined(
+                &["-t", ",", "-e", "X", "-o", "1.3,2.3"],
+                "k,1,,x\n",
+                "k,A\n"
+            ),
+            "X,X\n"
+        );
+        assert_eq!(joined(&["-t", ""], "a b\n", "a b\nc\n"), "a b\n");
+        assert_eq!(joined(&["-z"], "a 1\0", "a 2\0"), "a 1 2\0");
+    }
+
+    #[test]
+    fn ignore_case() {
+        assert_eq!(
+            joined(&["-i"], "A 1\nb 2\n", "a x\nB y\n"),
+            "A 1 x\nb 2 y\n"
+        );
+        assert_eq!(joined(&[], "A 1\nb 2\n", "a x\nB y\n"), "");
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!(
+            joined(&["--header"], "H1 x\nb 1\n", "H2 y\nb 2\n"),
+            "H1 x y\nb 1 2\n"
+        );
+        assert_eq!(joined(&["--header", "-a2"], "", "H y\nb 2\n"), "H y\nb 2\n");
+    }
+
+    #[test]
+    fn order_checks() {
+        // Disorder counts once a line has failed to pair.
+        assert_eq!(run(&[], "b\na\n", "a\nb\n").unwrap(), ("b\n".into(), false));
+        assert_eq!(run(&[], "a\nc\nb\n", "a\n").unwrap(), ("a\n".into(), true));
+        assert_eq!(
+            run(&["--nocheck-order"], "b\na\n", "a\nb\n").unwrap(),
+            ("b\n".into(), true)
+        );
+        let error = run(&["--check-order"], "a\nc\nb\n", "a\n").unwrap_err();
+        assert_eq!(error.to_string(), "a:3: is not sorted: b");
+    }
+}
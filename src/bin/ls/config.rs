@@ -37,6 +37,7 @@ List information about the FILEs (the current directory by default).
   -u                         with -t: sort by, and show, access time;
                                otherwise: show access time and sort by name;
   -1                         list one file per line.
+      --no-glob              do not expand wildcards in FILE operands
       --help     display this help and exit
       --version  output version information and exit
 
@@ -51,7 +52,10 @@ impl Config for LsConfig {
         "ls"
     }
     fn usage(&self) -> &'static str {
-        &HELP_TEXT
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
     }
 }
 
@@ -75,13 +79,10 @@ impl LsConfig {
     }
 
     pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
-        let base_config = match Config::parse(self, &args) {
-            Ok(config) => config,
-            Err(e) => return Err(e),
-        };
+        let base_config = Config::parse(self, &args)?;
 
         let mut paths: Vec<PathBuf> = base_config.parameters.iter().map(PathBuf::from).collect();
-        if paths.len() == 0 {
+        if paths.is_empty() {
             if let Ok(cwd) = env::current_dir() {
                 paths.push(cwd);
             }
@@ -268,4 +269,26 @@ mod tests {
             .unwrap();
         assert!(config.one_per_line);
     }
+
+    #[test]
+    fn bundled_options() {
+        let mut config = LsConfig::new();
+        config
+            .parse(string_vec!["ls.exe", "-1rt", "myfile.txt"])
+            .unwrap();
+        assert!(config.one_per_line);
+        assert!(config.reverse);
+        assert!(config.modify_sort);
+        assert!(!config.all);
+        assert_eq!(config.paths.len(), 1);
+    }
+
+    #[test]
+    fn bundled_invalid_option() {
+        let mut config = LsConfig::new();
+        let err = config
+            .parse(string_vec!["ls.exe", "-1x", "myfile.txt"])
+            .unwrap_err();
+        assert!(err.starts_with("invalid option: -x"));
+    }
 }
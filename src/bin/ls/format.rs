@@ -42,9 +42,7 @@ fn permissions(item: &DirEntry) -> String {
 fn size(item: &DirEntry) -> String {
     const POSTFIXES: &[&str] = &["B", "KB", "MB", "GB", "TB"];
     if let Ok(meta) = item.metadata() {
-        if meta.file_type().is_dir() {
-            String::from("<DIR>")
-        } else if meta.file_type().is_symlink() {
+        if meta.file_type().is_dir() || meta.file_type().is_symlink() {
             String::from("<DIR>")
         } else {
             let mut size = meta.len() as f64;
@@ -4,6 +4,8 @@ use std::io;
 use std::path::PathBuf;
 use std::process;
 
+use coreutils::output::Output;
+
 use std::ffi::{OsStr, OsString};
 use std::iter::once;
 use std::mem;
@@ -32,11 +34,15 @@ fn main() {
     let mut exit_code = NORMAL;
 
     let mut config = config::LsConfig::new();
-    if let Err(e) = config.parse(env::args().collect()) {
-        eprintln!("{}", e);
-        process::exit(FATAL);
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(match coreutils::config::report(&config, &args, &e) {
+            0 => 0,
+            _ => FATAL,
+        });
     }
 
+    let mut out = Output::stdout("ls");
     for path in config.paths {
         let entries = match dir(&path) {
             Ok(entries) => entries,
@@ -48,11 +54,12 @@ fn main() {
         };
 
         if config.one_per_line {
-            print!("{}", format::one_per_line(entries));
+            out.write_str(&format::one_per_line(entries));
         } else {
-            print!("{}", format::long_list(entries));
+            out.write_str(&format::long_list(entries));
         }
 
+        out.flush();
         process::exit(exit_code);
     }
 }
@@ -150,11 +157,10 @@ fn owner(entry: &DirEntry) -> String {
 mod tests {
     use super::*;
     use coreutils::test_utils;
-    use std::path::PathBuf;
 
     #[test]
     fn normal_dir() {
-        let path = PathBuf::from(test_utils::get_dir());
+        let path = test_utils::get_dir();
         let entries: Vec<DirEntry> = crate::dir(&path).unwrap();
         let names: Vec<String> = entries
             .iter()
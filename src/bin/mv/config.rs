@@ -0,0 +1,325 @@
+use coreutils::backup::{self, Backup};
+use coreutils::config::{self, Config};
+use coreutils::copy::Overwrite;
+use coreutils::rename::Options;
+
+#[derive(Debug)]
+pub struct MvConfig {
+    pub files: Vec<String>,
+    pub target_directory: Option<String>,
+    pub no_target_directory: bool,
+    pub options: Options,
+}
+
+const HELP_TEXT: &str = "Usage: mv [OPTION]... [-T] SOURCE DEST
+  or:  mv [OPTION]... SOURCE... DIRECTORY
+  or:  mv [OPTION]... -t DIRECTORY SOURCE...
+Rename SOURCE to DEST, or move SOURCE(s) to DIRECTORY.
+
+Mandatory arguments to long options are mandatory for short options too.
+      --backup[=CONTROL]       make a backup of each existing destination file
+  -b                           like --backup but does not accept an argument
+      --exchange               exchange source and destination
+  -f, --force                  do not prompt before overwriting
+  -i, --interactive            prompt before overwrite
+  -n, --no-clobber             do not overwrite an existing file
+If you specify more than one of -i, -f, -n, only the final one takes effect.
+      --strip-trailing-slashes  remove any trailing slashes from each SOURCE
+                                 argument
+  -S, --suffix=SUFFIX          override the usual backup suffix
+  -t, --target-directory=DIRECTORY  move all SOURCE arguments into DIRECTORY
+  -T, --no-target-directory    treat DEST as a normal file
+  -u, --update                 move only when the SOURCE file is newer
+                                 than the destination file or when the
+                                 destination file is missing
+  -v, --verbose                explain what is being done
+      --no-glob                do not expand wildcards in operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+A move across file systems copies SOURCE with everything --archive keeps
+and then removes it.  --exchange swaps the two in one step where the
+system can, which is Linux only; elsewhere it fails.  With a DIRECTORY it
+swaps SOURCE with the file of the same name inside, so give -T to swap
+with DIRECTORY itself.
+
+The backup suffix is '~', unless set with --suffix or SIMPLE_BACKUP_SUFFIX.
+The version control method may be selected via the --backup option or through
+the VERSION_CONTROL environment variable.  Here are the values:
+
+  none, off       never make backups (even if --backup is given)
+  numbered, t     make numbered backups
+  existing, nil   numbered if numbered backups exist, simple otherwise
+  simple, never   always make simple backups
+";
+
+impl Config for MvConfig {
+    fn bin_name(&self) -> &'static str {
+        "mv"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try mv --help for more information", message)
+}
+
+/// `name` without the slashes at its end, unless that leaves nothing.
+fn strip_trailing_slashes(name: &str) -> String {
+    let stripped = name.trim_end_matches(std::path::is_separator);
+    if stripped.is_empty() {
+        name[..1].to_string()
+    } else {
+        stripped.to_string()
+    }
+}
+
+impl MvConfig {
+    pub fn new() -> MvConfig {
+        MvConfig {
+            files: Vec::new(),
+            target_directory: None,
+            no_target_directory: false,
+            options: Options::default(),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let options = &mut self.options;
+        let mut make_backups = false;
+        let mut control = None;
+        let mut suffix = None;
+        let mut strip = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--backup", None) | ("-b", None) => make_backups = true,
+                ("--backup", Some(value)) => {
+                    make_backups = true;
+                    control = Some(
+                        backup::parse_control(value, "backup type").map_err(|e| usage_error(&e))?,
+                    );
+                }
+                ("--exchange", None) => options.exchange = true,
+                ("--force", None) | ("-f", None) => options.overwrite = Overwrite::Always,
+                ("--interactive", None) | ("-i", None) => options.overwrite = Overwrite::Prompt,
+                ("--no-clobber", None) | ("-n", None) => options.overwrite = Overwrite::Never,
+                ("--strip-trailing-slashes", None) => strip = true,
+                ("--suffix", Some(value)) | ("-S", Some(value)) => {
+                    make_backups = true;
+                    suffix = Some(value.to_string());
+                }
+                ("--target-directory", Some(dir)) | ("-t", Some(dir)) => {
+                    self.target_directory = Some(dir.to_string())
+                }
+                ("--no-target-directory", None) | ("-T", None) => self.no_target_directory = true,
+                ("--update", None) | ("-u", None) => options.update = true,
+                ("--verbose", None) | ("-v", None) => options.verbose = true,
+                (name, None) if name.len() == 2 && "St".contains(&name[1..]) => {
+                    return Err(usage_error(&format!(
+                        "option requires an argument -- '{}'",
+                        &name[1..]
+                    )))
+                }
+                _ => return Err(usage_error(&format!("invalid option: {}", option))),
+            }
+        }
+
+        if make_backups {
+            if options.overwrite == Overwrite::Never {
+                return Err(usage_error(
+                    "options --backup and --no-clobber are mutually exclusive",
+                ));
+            }
+            let control = match control {
+                Some(control) => control,
+                None => backup::default_control().map_err(|e| usage_error(&e))?,
+            };
+            options.backup = Backup {
+                control,
+                suffix: suffix.unwrap_or_else(backup::default_suffix),
+            };
+        }
+
+        self.files = base_config.parameters;
+        if self.target_directory.is_some() && self.no_target_directory {
+            return Err(String::from(
+                "cannot combine --target-directory (-t) and --no-target-directory (-T)",
+            ));
+        }
+        match self.files.len() {
+            0 => return Err(usage_error("missing file operand")),
+            1 if self.target_directory.is_none() => {
+                return Err(usage_error(&format!(
+                    "missing destination file operand after '{}'",
+                    self.files[0]
+                )))
+            }
+            count if count > 2 && self.no_target_directory => {
+                return Err(usage_error(&format!("extra operand '{}'", self.files[2])))
+            }
+            _ => {}
+        }
+        if strip {
+            // Only sources: a destination's slash still says it must be
+            // a directory.
+            let sources = match self.target_directory {
+                Some(_) => self.files.len(),
+                None => self.files.len() - 1,
+            };
+            for file in &mut self.files[..sources] {
+                *file = strip_trailing_slashes(file);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::MvConfig;
+    use coreutils::backup::Control;
+    use coreutils::copy::Overwrite;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<MvConfig, String> {
+        let mut config = MvConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["mv.exe", "a", "b"]).unwrap();
+        assert_eq!(config.files, vec!["a", "b"]);
+        assert_eq!(config.target_directory, None);
+        assert!(!config.no_target_directory);
+        let options = config.options;
+        assert_eq!(options.overwrite, Overwrite::Always);
+        assert!(!options.update && !options.verbose && !options.exchange);
+        assert_eq!(options.backup.control, Control::None);
+    }
+
+    #[test]
+    fn overwriting() {
+        let overwrite = |args: Vec<String>| parse(args).unwrap().options.overwrite;
+        assert_eq!(
+            overwrite(string_vec!["mv.exe", "-i", "a", "b"]),
+            Overwrite::Prompt
+        );
+        assert_eq!(
+            overwrite(string_vec!["mv.exe", "-if", "a", "b"]),
+            Overwrite::Always
+        );
+        assert_eq!(
+            overwrite(string_vec!["mv.exe", "-fn", "a", "b"]),
+            Overwrite::Never
+        );
+        assert_eq!(
+            overwrite(string_vec!["mv.exe", "-n", "--interactive", "a", "b"]),
+            Overwrite::Prompt
+        );
+
+        let options = parse(string_vec!["mv.exe", "-uv", "--exchange", "a", "b"])
+            .unwrap()
+            .options;
+        assert!(options.update && options.verbose && options.exchange);
+    }
+
+    #[test]
+    fn backups() {
+        let backup = |args: Vec<String>| parse(args).unwrap().options.backup;
+        let made = backup(string_vec!["mv.exe", "--backup=numbered", "a", "b"]);
+        assert_eq!(made.control, Control::Numbered);
+        let made = backup(string_vec!["mv.exe", "--backup=s", "-S", ".bak", "a", "b"]);
+        assert_eq!(made.control, Control::Simple);
+        assert_eq!(made.suffix, ".bak");
+
+        assert!(parse(string_vec!["mv.exe", "--backup=bogus", "a", "b"])
+            .unwrap_err()
+            .starts_with("invalid argument 'bogus' for 'backup type'"));
+        assert!(parse(string_vec!["mv.exe", "-nb", "a", "b"])
+            .unwrap_err()
+            .starts_with("options --backup and --no-clobber are mutually exclusive"));
+    }
+
+    #[test]
+    fn targets() {
+        let config = parse(string_vec!["mv.exe", "-t", "dir", "a", "b"]).unwrap();
+        assert_eq!(config.target_directory.as_deref(), Some("dir"));
+        assert_eq!(config.files, vec!["a", "b"]);
+        let config = parse(string_vec!["mv.exe", "-T", "a", "b"]).unwrap();
+        assert!(config.no_target_directory);
+    }
+
+    #[test]
+    fn trailing_slashes() {
+        let files = |args: Vec<String>| parse(args).unwrap().files;
+        assert_eq!(
+            files(string_vec![
+                "mv.exe",
+                "--strip-trailing-slashes",
+                "a//",
+                "/",
+                "d/"
+            ]),
+            vec!["a", "/", "d/"]
+        );
+        assert_eq!(
+            files(string_vec![
+                "mv.exe",
+                "--strip-trailing-slashes",
+                "-t",
+                "d/",
+                "a/"
+            ]),
+            vec!["a"]
+        );
+        assert_eq!(files(string_vec!["mv.exe", "a/", "d"]), vec!["a/", "d"]);
+    }
+
+    #[test]
+    fn operand_errors() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert_eq!(
+            error(string_vec!["mv.exe"]),
+            "missing file operand\n Try mv --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["mv.exe", "a"]),
+            "missing destination file operand after 'a'\n Try mv --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["mv.exe", "-T", "a", "b", "c"]),
+            "extra operand 'c'\n Try mv --help for more information"
+        );
+        assert_eq!(
+            error(string_vec!["mv.exe", "-S"]),
+            "option requires an argument -- 'S'\n Try mv --help for more information"
+        );
+        assert!(error(string_vec!["mv.exe", "-t", "d", "-T", "a"]).starts_with("cannot combine"));
+        assert!(error(string_vec!["mv.exe", "-t", "d"]).starts_with("missing file operand"));
+        assert!(error(string_vec!["mv.exe", "-r", "a", "b"]).starts_with("invalid option: -r"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = MvConfig::new();
+        let parse_result = config.parse(string_vec!["mv.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = MvConfig::new();
+        let parse_result = config.parse(string_vec!["mv.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
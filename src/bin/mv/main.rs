@@ -0,0 +1,44 @@
+use std::env;
+use std::process;
+
+use coreutils::copy::{self, Target};
+use coreutils::output::Output;
+use coreutils::rename::Mover;
+
+mod config;
+
+use config::MvConfig;
+
+fn main() {
+    let mut config = MvConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let target = copy::target(
+        &config.files,
+        config.target_directory.as_deref(),
+        config.no_target_directory,
+    );
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("mv: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut mover = Mover::new("mv", config.options, Output::stdout("mv"));
+    let mut ok = true;
+    match target {
+        Target::File(source, dest) => ok = mover.rename(&source, &dest),
+        Target::Directory(sources, dir) => {
+            for source in sources {
+                ok &= mover.rename(&source, &copy::into_directory(&source, &dir));
+            }
+        }
+    }
+    mover.output().flush();
+    process::exit(if ok { 0 } else { 1 });
+}
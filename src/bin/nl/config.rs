@@ -0,0 +1,323 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::regex::Regex;
+
+/// Which lines of a section get a number.
+#[derive(Debug)]
+pub enum Style {
+    All,
+    NonEmpty,
+    None,
+    Matching(Regex),
+}
+
+/// How line numbers are justified within their width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Left,
+    Right,
+    RightZeros,
+}
+
+#[derive(Debug)]
+pub struct NlConfig {
+    pub files: Vec<String>,
+    pub header: Style,
+    pub body: Style,
+    pub footer: Style,
+    /// The two or more bytes that make up section delimiter lines; empty
+    /// when sections are not recognised.
+    pub delimiter: Vec<u8>,
+    pub increment: i64,
+    /// With `-ba`, how many consecutive empty lines count as one.
+    pub join_blank: u64,
+    pub format: Format,
+    pub renumber: bool,
+    pub separator: Vec<u8>,
+    pub start: i64,
+    pub width: usize,
+}
+
+const HELP_TEXT: &str = "Usage: nl [OPTION]... [FILE]...
+Write each FILE to standard output, with line numbers added.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --body-numbering=STYLE      use STYLE for numbering body lines
+  -d, --section-delimiter=CC      use CC for logical page delimiters
+  -f, --footer-numbering=STYLE    use STYLE for numbering footer lines
+  -h, --header-numbering=STYLE    use STYLE for numbering header lines
+  -i, --line-increment=NUMBER     line number increment at each line
+  -l, --join-blank-lines=NUMBER   group of NUMBER empty lines counted as one
+  -n, --number-format=FORMAT      insert line numbers according to FORMAT
+  -p, --no-renumber               do not reset line numbers for each section
+  -s, --number-separator=STRING   add STRING after (possible) line number
+  -v, --starting-line-number=NUMBER  first line number for each section
+  -w, --number-width=NUMBER       use NUMBER columns for line numbers
+      --no-glob                   do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Default options are: -bt -d'\\:' -fn -hn -i1 -l1 -n'rn' -s<TAB> -v1 -w6
+
+CC are two delimiter characters used to construct logical page delimiters;
+a missing second character implies ':'.  As a GNU extension one can specify
+more than two characters, and also specifying the empty string (-d '')
+disables section matching.
+
+STYLE is one of:
+
+  a      number all lines
+  t      number only nonempty lines
+  n      number no lines
+  pBRE   number only lines that contain a match for the basic regular
+         expression, BRE
+
+FORMAT is one of:
+
+  ln     left justified, no leading zeros
+  rn     right justified, no leading zeros
+  rz     right justified, leading zeros
+
+A carriage return before the newline is not part of the line.
+";
+
+impl Config for NlConfig {
+    fn bin_name(&self) -> &'static str {
+        "nl"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_style(value: &str, section: &str) -> Result<Style, String> {
+    match value {
+        "a" => Ok(Style::All),
+        "t" => Ok(Style::NonEmpty),
+        "n" => Ok(Style::None),
+        _ => match value.strip_prefix('p') {
+            Some(pattern) => Regex::new(pattern).map(Style::Matching),
+            None => Err(format!(
+                "invalid {} numbering style: '{}'\n Try nl --help for more information",
+                section, value
+            )),
+        },
+    }
+}
+
+fn parse_number(value: &str, what: &str) -> Result<i64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {}: '{}'", what, value))
+}
+
+/// A number that must be at least one.
+fn parse_positive(value: &str, what: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("invalid {}: '{}'", what, value)),
+    }
+}
+
+impl NlConfig {
+    pub fn new() -> NlConfig {
+        NlConfig {
+            files: Vec::new(),
+            header: Style::None,
+            body: Style::NonEmpty,
+            footer: Style::None,
+            delimiter: b"\\:".to_vec(),
+            increment: 1,
+            join_blank: 1,
+            format: Format::Right,
+            renumber: true,
+            separator: b"\t".to_vec(),
+            start: 1,
+            width: 6,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--body-numbering", Some(style)) | ("-b", Some(style)) => {
+                    self.body = parse_style(style, "body")?
+                }
+                ("--header-numbering", Some(style)) | ("-h", Some(style)) => {
+                    self.header = parse_style(style, "header")?
+                }
+                ("--footer-numbering", Some(style)) | ("-f", Some(style)) => {
+                    self.footer = parse_style(style, "footer")?
+                }
+                ("--section-delimiter", Some(cc)) | ("-d", Some(cc)) => {
+                    self.delimiter = cc.as_bytes().to_vec();
+                    // A lone delimiter character is paired with ':'.
+                    if cc.chars().count() == 1 {
+                        self.delimiter.push(b':');
+                    }
+                }
+                ("--line-increment", Some(n)) | ("-i", Some(n)) => {
+                    self.increment = parse_number(n, "line number increment")?
+                }
+                ("--join-blank-lines", Some(n)) | ("-l", Some(n)) => {
+                    self.join_blank = parse_positive(n, "line number of blank lines")?
+                }
+                ("--number-format", Some(format)) | ("-n", Some(format)) => {
+                    self.format = match format {
+                        "ln" => Format::Left,
+                        "rn" => Format::Right,
+                        "rz" => Format::RightZeros,
+                        _ => return Err(format!("invalid line numbering format: '{}'", format)),
+                    }
+                }
+                ("--no-renumber", None) | ("-p", None) => self.renumber = false,
+                ("--number-separator", Some(text)) | ("-s", Some(text)) => {
+                    self.separator = text.as_bytes().to_vec()
+                }
+                ("--starting-line-number", Some(n)) | ("-v", Some(n)) => {
+                    self.start = parse_number(n, "starting line number")?
+                }
+                ("--number-width", Some(n)) | ("-w", Some(n)) => {
+                    let width = parse_positive(n, "line number field width")?;
+                    // Numbers are padded in memory, so absurd widths are refused.
+                    if width > i32::MAX as u64 {
+                        return Err(format!("invalid line number field width: '{}'", n));
+                    }
+                    self.width = width as usize;
+                }
+                (name, None)
+                    if matches!(
+                        name,
+                        "-b" | "-d" | "-f" | "-h" | "-i" | "-l" | "-n" | "-s" | "-v" | "-w"
+                    ) =>
+                {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try nl --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Format, NlConfig, Style};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<NlConfig, String> {
+        let mut config = NlConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["nl.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert!(matches!(config.header, Style::None));
+        assert!(matches!(config.body, Style::NonEmpty));
+        assert!(matches!(config.footer, Style::None));
+        assert_eq!(config.delimiter, b"\\:");
+        assert_eq!((config.start, config.increment, config.width), (1, 1, 6));
+        assert_eq!(config.format, Format::Right);
+        assert_eq!(config.separator, b"\t");
+        assert!(config.renumber);
+    }
+
+    #[test]
+    fn styles() {
+        let config = parse(string_vec![
+            "nl.exe",
+            "-ba",
+            "-hpfoo",
+            "--footer-numbering=t"
+        ])
+        .unwrap();
+        assert!(matches!(config.body, Style::All));
+        assert!(matches!(config.header, Style::Matching(_)));
+        assert!(matches!(config.footer, Style::NonEmpty));
+        if let Style::Matching(regex) = config.header {
+            assert!(regex.is_match(b"a foo"));
+        }
+
+        assert!(parse(string_vec!["nl.exe", "-bx"])
+            .unwrap_err()
+            .contains("invalid body numbering style: 'x'"));
+        assert!(parse(string_vec!["nl.exe", "-bp["])
+            .unwrap_err()
+            .contains("Unmatched ["));
+    }
+
+    #[test]
+    fn numbers() {
+        let config = parse(string_vec![
+            "nl.exe", "-v", "-5", "-i", "10", "-w", "3", "-l", "2", "-nrz", "-s", ": ", "-p"
+        ])
+        .unwrap();
+        assert_eq!((config.start, config.increment, config.width), (-5, 10, 3));
+        assert_eq!(config.join_blank, 2);
+        assert_eq!(config.format, Format::RightZeros);
+        assert_eq!(config.separator, b": ");
+        assert!(!config.renumber);
+
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert!(error(string_vec!["nl.exe", "-w0"]).contains("invalid line number field width"));
+        assert!(error(string_vec!["nl.exe", "-l0"]).contains("invalid line number of blank"));
+        assert!(error(string_vec!["nl.exe", "-vx"]).contains("invalid starting line number"));
+        assert!(error(string_vec!["nl.exe", "-i1.5"]).contains("invalid line number increment"));
+        assert!(error(string_vec!["nl.exe", "-nxx"]).contains("invalid line numbering format"));
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(
+            parse(string_vec!["nl.exe", "-d", "x"]).unwrap().delimiter,
+            b"x:"
+        );
+        assert_eq!(
+            parse(string_vec!["nl.exe", "-d", "é"]).unwrap().delimiter,
+            "é:".as_bytes()
+        );
+        assert_eq!(
+            parse(string_vec!["nl.exe", "-d", "xyz"]).unwrap().delimiter,
+            b"xyz"
+        );
+        assert!(parse(string_vec!["nl.exe", "-d", ""])
+            .unwrap()
+            .delimiter
+            .is_empty());
+    }
+
+    #[test]
+    fn help() {
+        let mut config = NlConfig::new();
+        let parse_result = config.parse(string_vec!["nl.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = NlConfig::new();
+        let parse_result = config.parse(string_vec!["nl.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
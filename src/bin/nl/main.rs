@@ -0,0 +1,38 @@
+use std::env;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod number;
+
+use config::NlConfig;
+use number::Numberer;
+
+fn main() {
+    let mut config = NlConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("nl");
+    let mut numberer = Numberer::new(&config);
+    for file in &config.files {
+        let result = input::open(file).and_then(|reader| numberer.write(reader, &mut out));
+        if let Err(e) = result {
+            out.flush();
+            eprintln!(
+                "nl: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
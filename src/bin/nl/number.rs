@@ -0,0 +1,267 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+
+use crate::config::{Format, NlConfig, Style};
+
+/// The parts of a logical page, each numbered in its own style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Header,
+    Body,
+    Footer,
+}
+
+/// Numbers lines across every input, since sections and line numbers
+/// carry on from one file to the next.
+pub struct Numberer<'a> {
+    config: &'a NlConfig,
+    section: Section,
+    number: Option<i64>,
+    /// Empty lines seen since the last one numbered under `-l`.
+    blanks: u64,
+    /// Spaces written in place of a number.
+    padding: Vec<u8>,
+}
+
+impl<'a> Numberer<'a> {
+    pub fn new(config: &'a NlConfig) -> Numberer<'a> {
+        Numberer {
+            config,
+            section: Section::Body,
+            number: Some(config.start),
+            blanks: 0,
+            padding: vec![b' '; config.width + config.separator.len()],
+        }
+    }
+
+    /// The section a line starts when it is a delimiter line: the
+    /// delimiter three times for a header, twice for a body and once for
+    /// a footer.
+    fn delimiter_line(&self, line: &[u8]) -> Option<Section> {
+        let delimiter = &self.config.delimiter[..];
+        if delimiter.len() < 2 || !line.len().is_multiple_of(delimiter.len()) {
+            return None;
+        }
+        if !line.chunks(delimiter.len()).all(|chunk| chunk == delimiter) {
+            return None;
+        }
+        match line.len() / delimiter.len() {
+            3 => Some(Section::Header),
+            2 => Some(Section::Body),
+            1 => Some(Section::Footer),
+            _ => None,
+        }
+    }
+
+    fn style(&self) -> &'a Style {
+        match self.section {
+            Section::Header => &self.config.header,
+            Section::Body => &self.config.body,
+            Section::Footer => &self.config.footer,
+        }
+    }
+
+    /// Whether `line` gets a number in the current section.
+    fn numbered(&mut self, line: &[u8]) -> bool {
+        match self.style() {
+            Style::All if self.config.join_blank > 1 => {
+                if !line.is_empty() {
+                    self.blanks = 0;
+                    return true;
+                }
+                self.blanks += 1;
+                if self.blanks == self.config.join_blank {
+                    self.blanks = 0;
+                    return true;
+                }
+                false
+            }
+            Style::All => true,
+            Style::NonEmpty => !line.is_empty(),
+            Style::None => false,
+            Style::Matching(regex) => regex.is_match(line),
+        }
+    }
+
+    fn write_number<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        let number = self
+            .number
+            .ok_or_else(|| io::Error::other("line number overflow"))?;
+        let width = self.config.width;
+        match self.config.format {
+            Format::Left => write!(out, "{:<width$}", number, width = width)?,
+            Format::Right => write!(out, "{:>width$}", number, width = width)?,
+            Format::RightZeros => write!(out, "{:0width$}", number, width = width)?,
+        }
+        out.write_all(&self.config.separator)?;
+        // Overflow is only an error once the number is needed.
+        self.number = number.checked_add(self.config.increment);
+        Ok(())
+    }
+
+    /// Number the lines of `input` onto `out`.
+    pub fn write<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> io::Result<()> {
+        let mut lines = LineReader::new(input, b'\n');
+        while lines.next_line()? {
+            if let Some(section) = self.delimiter_line(lines.line()) {
+                self.section = section;
+                if self.config.renumber {
+                    self.number = Some(self.config.start);
+                }
+                // Delimiter lines come out empty.
+                match lines.terminator() {
+                    [] => out.write_all(b"\n")?,
+                    terminator => out.write_all(terminator)?,
+                }
+                continue;
+            }
+
+            if self.numbered(lines.line()) {
+                self.write_number(out)?;
+            } else {
+                out.write_all(&self.padding)?;
+            }
+            out.write_all(lines.raw())?;
+            if lines.terminator().is_empty() {
+                out.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn nl_all(args: &[&str], inputs: &[&str]) -> String {
+        let mut all: Vec<String> = string_vec!["nl.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = NlConfig::new();
+        config.parse(all).unwrap();
+        let mut numberer = Numberer::new(&config);
+        let mut out = Vec::new();
+        for input in inputs {
+            numberer.write(input.as_bytes(), &mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    fn nl(args: &[&str], input: &str) -> String {
+        nl_all(args, &[input])
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(nl(&[], "a\n\nb"), "     1\ta\n       \n     2\tb\n");
+        assert_eq!(
+            nl(&[], "a\r\n\r\nb\r\n"),
+            "     1\ta\r\n       \r\n     2\tb\r\n"
+        );
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(nl(&["-nln", "-w3", "-s|"], "a\n"), "1  |a\n");
+        assert_eq!(nl(&["-nrn", "-w3", "-s|"], "a\n"), "  1|a\n");
+        assert_eq!(nl(&["-nrz", "-w3", "-s|"], "a\n"), "001|a\n");
+        assert_eq!(nl(&["-nrz", "-w4", "-v-5", "-s|"], "a\n"), "-005|a\n");
+        // Numbers wider than the field are not cut.
+        assert_eq!(nl(&["-w1", "-v100", "-s "], "a\n"), "100 a\n");
+    }
+
+    #[test]
+    fn start_and_increment() {
+        assert_eq!(
+            nl(&["-v10", "-i5", "-w2", "-s "], "a\nb\nc\n"),
+            "10 a\n15 b\n20 c\n"
+        );
+        assert_eq!(nl(&["-v1", "-i-1", "-w2", "-s "], "a\nb\n"), " 1 a\n 0 b\n");
+    }
+
+    #[test]
+    fn styles() {
+        let input = "apple\n\nbanana\ncherry\n";
+        assert_eq!(
+            nl(&["-ba", "-w1", "-s "], input),
+            "1 apple\n2 \n3 banana\n4 cherry\n"
+        );
+        assert_eq!(
+            nl(&["-bn", "-w1", "-s "], input),
+            "  apple\n  \n  banana\n  cherry\n"
+        );
+        assert_eq!(
+            nl(&["-bp^[bc]", "-w1", "-s "], input),
+            "  apple\n  \n1 banana\n2 cherry\n"
+        );
+        assert_eq!(
+            nl(&["-bp\\(an\\)\\{2\\}", "-w1", "-s "], input),
+            "  apple\n  \n1 banana\n  cherry\n"
+        );
+    }
+
+    #[test]
+    fn join_blank_lines() {
+        let input = "a\n\n\n\n\n\nb\n";
+        assert_eq!(
+            nl(&["-ba", "-l2", "-w1", "-s "], input),
+            "1 a\n  \n2 \n  \n3 \n  \n4 b\n"
+        );
+        // Only -ba joins blank lines.
+        assert_eq!(
+            nl(&["-l2", "-w1", "-s "], "a\n\n\nb\n"),
+            "1 a\n  \n  \n2 b\n"
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let input = "\\:\\:\\:\nhead\n\\:\\:\nbody\n\\:\nfoot\n";
+        assert_eq!(
+            nl(&["-ha", "-fa", "-w1", "-s "], input),
+            "\n1 head\n\n1 body\n\n1 foot\n"
+        );
+        assert_eq!(
+            nl(&["-ha", "-fa", "-p", "-w1", "-s "], input),
+            "\n1 head\n\n2 body\n\n3 foot\n"
+        );
+        // Header and footer lines go unnumbered by default.
+        assert_eq!(nl(&["-w1", "-s "], input), "\n  head\n\n1 body\n\n  foot\n");
+        // Lines that only resemble a delimiter are text.
+        assert_eq!(
+            nl(&["-w1", "-s "], "\\:\\:\\:\\:\n\\:x\n"),
+            "1 \\:\\:\\:\\:\n2 \\:x\n"
+        );
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(nl(&["-d", "@", "-w1", "-s "], "@:\nx\n"), "\n  x\n");
+        assert_eq!(nl(&["-d", "abc", "-w1", "-s "], "abcabc\nx\n"), "\n1 x\n");
+        assert_eq!(nl(&["-d", "", "-w1", "-s "], "\\:\n"), "1 \\:\n");
+    }
+
+    #[test]
+    fn across_files() {
+        assert_eq!(nl_all(&["-w1", "-s "], &["a\n", "b"]), "1 a\n2 b\n");
+        assert_eq!(
+            nl_all(&["-w1", "-s ", "-ha"], &["\\:\\:\\:\n", "h\n"]),
+            "\n1 h\n"
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let mut config = NlConfig::new();
+        config
+            .parse(string_vec!["nl.exe", "-v", "9223372036854775807"])
+            .unwrap();
+        let mut numberer = Numberer::new(&config);
+        let mut out = Vec::new();
+        let err = numberer.write(&b"a\nb\n"[..], &mut out).unwrap_err();
+        assert_eq!(err.to_string(), "line number overflow");
+        assert_eq!(out, b"9223372036854775807\ta\n");
+    }
+}
@@ -0,0 +1,564 @@
+use coreutils::config::{self, Config};
+use coreutils::hexdump;
+use coreutils::input;
+use coreutils::size;
+
+/// How the units of one output format are shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// `a`: ASCII names such as `nul` and `sp`, ignoring the high bit.
+    Named,
+    /// `c`: the character itself, a C escape, or three octal digits.
+    Char,
+    Signed,
+    Unsigned,
+    Octal,
+    Hex,
+    Float,
+}
+
+/// One `-t` specification: what to print for each `size` bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Format {
+    pub kind: Kind,
+    pub size: usize,
+    /// `z`: follow the line with its printable characters.
+    pub trailer: bool,
+}
+
+/// The formats the single letter options stand for.
+pub fn traditional(letter: &str) -> Option<Format> {
+    let (kind, size) = match letter {
+        "a" => (Kind::Named, 1),
+        "b" => (Kind::Octal, 1),
+        "c" => (Kind::Char, 1),
+        "d" => (Kind::Unsigned, 2),
+        "f" => (Kind::Float, 4),
+        "i" => (Kind::Signed, 4),
+        "l" => (Kind::Signed, 8),
+        "o" => (Kind::Octal, 2),
+        "s" => (Kind::Signed, 2),
+        "x" => (Kind::Hex, 2),
+        _ => return None,
+    };
+    Some(Format {
+        kind,
+        size,
+        trailer: false,
+    })
+}
+
+/// The error for a `-t` argument asking for `size` bytes of a type there
+/// is no such size of.
+fn unsupported(text: &str, size: &str, kind: Kind) -> String {
+    format!(
+        "invalid type string '{}';\nthis system doesn't provide a {}-byte {} type",
+        text,
+        size,
+        if kind == Kind::Float {
+            "floating point"
+        } else {
+            "integral"
+        }
+    )
+}
+
+/// Parse a `-t` argument, which may hold several specifications in a row
+/// such as `x1z` or `d2c`.
+pub fn parse_type(text: &str) -> Result<Vec<Format>, String> {
+    let mut formats = Vec::new();
+    let mut rest = text;
+    while let Some(letter) = rest.chars().next() {
+        rest = &rest[1..];
+        let kind = match letter {
+            'a' => Kind::Named,
+            'c' => Kind::Char,
+            'd' => Kind::Signed,
+            'u' => Kind::Unsigned,
+            'o' => Kind::Octal,
+            'x' => Kind::Hex,
+            'f' => Kind::Float,
+            _ => {
+                return Err(format!(
+                    "invalid character '{}' in type string '{}'",
+                    letter, text
+                ))
+            }
+        };
+
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let named = match rest.chars().next() {
+            Some(size) if digits == 0 => match (kind, size) {
+                (Kind::Float, 'F') => Some(4),
+                (Kind::Float, 'D') => Some(8),
+                (Kind::Float, 'L') => return Err(unsupported(text, "16", kind)),
+                (Kind::Float, _) | (Kind::Named, _) | (Kind::Char, _) => None,
+                (_, 'C') => Some(1),
+                (_, 'S') => Some(2),
+                (_, 'I') => Some(4),
+                (_, 'L') => Some(8),
+                _ => None,
+            },
+            _ => None,
+        };
+        let size = match (kind, named) {
+            (_, Some(size)) => {
+                rest = &rest[1..];
+                size
+            }
+            (Kind::Named, _) | (Kind::Char, _) => 1,
+            _ if digits > 0 => {
+                let size = rest[..digits].parse().unwrap_or(usize::MAX);
+                let fits = match kind {
+                    Kind::Float => size == 4 || size == 8,
+                    _ => [1, 2, 4, 8].contains(&size),
+                };
+                if !fits {
+                    return Err(unsupported(text, &rest[..digits], kind));
+                }
+                rest = &rest[digits..];
+                size
+            }
+            (Kind::Float, _) => 8,
+            _ => 4,
+        };
+
+        let trailer = rest.starts_with('z');
+        if trailer {
+            rest = &rest[1..];
+        }
+        formats.push(Format {
+            kind,
+            size,
+            trailer,
+        });
+    }
+    Ok(formats)
+}
+
+/// How `-A` prints the offset at the start of each line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Decimal,
+    Octal,
+    Hex,
+    None,
+}
+
+impl Radix {
+    /// Columns the address takes; the lines of a second format are
+    /// indented to match.
+    pub fn width(self) -> usize {
+        match self {
+            Radix::Decimal | Radix::Octal => 7,
+            Radix::Hex => 6,
+            Radix::None => 0,
+        }
+    }
+
+    pub fn format(self, offset: u64) -> String {
+        match self {
+            Radix::Decimal => format!("{:07}", offset),
+            Radix::Octal => format!("{:07o}", offset),
+            Radix::Hex => format!("{:06x}", offset),
+            Radix::None => String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OdConfig {
+    pub files: Vec<String>,
+    pub radix: Radix,
+    /// Every format asked for, each printed as a line of its own.
+    pub formats: Vec<Format>,
+    pub big_endian: bool,
+    pub skip: u64,
+    pub limit: Option<u64>,
+    /// Bytes shown per line.
+    pub width: usize,
+    /// Print repeated lines rather than a `*` in their place.
+    pub duplicates: bool,
+    /// Lay lines out as `hexdump -C` does instead.
+    pub canonical: bool,
+    pub warnings: Vec<String>,
+}
+
+const HELP_TEXT: &str = "Usage: od [OPTION]... [FILE]...
+Write an unambiguous representation, octal bytes by default,
+of FILE to standard output.  With more than one FILE argument,
+concatenate them in the listed order to form the input.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -A, --address-radix=RADIX   output format for file offsets; RADIX is one
+                                of [doxn], for Decimal, Octal, Hex or None
+  -C, --canonical             hexdump -C style: hex bytes in two groups of
+                                eight and the characters between bars, in
+                                place of what -A, -t and -w ask for
+      --endian={big|little}   swap input bytes according to the specified order
+  -j, --skip-bytes=BYTES      skip BYTES input bytes first
+  -N, --read-bytes=BYTES      limit dump to BYTES input bytes
+  -t, --format=TYPE           select output format or formats
+  -v, --output-duplicates     do not use * to mark line suppression
+  -w, --width[=BYTES]         output BYTES bytes per output line;
+                                32 is implied when BYTES is not specified
+      --no-glob               do not expand wildcards in FILE operands
+      --help                  display this help and exit
+      --version               output version information and exit
+
+Traditional format specifications may be intermixed; they accumulate:
+  -a   same as -t a,  select named characters, ignoring high-order bit
+  -b   same as -t o1, select octal bytes
+  -c   same as -t c,  select printable characters or backslash escapes
+  -d   same as -t u2, select unsigned decimal 2-byte units
+  -f   same as -t fF, select floats
+  -i   same as -t dI, select decimal ints
+  -l   same as -t dL, select decimal longs
+  -o   same as -t o2, select octal 2-byte units
+  -s   same as -t d2, select decimal 2-byte units
+  -x   same as -t x2, select hexadecimal 2-byte units
+
+TYPE is made up of one or more of these specifications:
+  a          named character, ignoring high-order bit
+  c          printable character or backslash escape
+  d[SIZE]    signed decimal, SIZE bytes per integer
+  f[SIZE]    floating point, SIZE bytes per float
+  o[SIZE]    octal, SIZE bytes per integer
+  u[SIZE]    unsigned decimal, SIZE bytes per integer
+  x[SIZE]    hexadecimal, SIZE bytes per integer
+
+SIZE is a number.  For TYPE in [doux], SIZE may also be C for
+sizeof(char), S for sizeof(short), I for sizeof(int) or L for
+sizeof(long), here 1, 2, 4 and 8.  If TYPE is f, SIZE may also be F for
+sizeof(float) or D for sizeof(double), here 4 and 8; long double (L) is
+not supported.
+
+Adding a z suffix to any type displays printable characters at the end
+of each output line.
+
+BYTES is hex with 0x or 0X prefix, octal with a leading 0, and may have a
+multiplier suffix:
+  b    512
+  KB   1000
+  K    1024
+  MB   1000*1000
+  M    1024*1024
+and so on for G, T, P, E.
+";
+
+impl Config for OdConfig {
+    fn bin_name(&self) -> &'static str {
+        "od"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_radix(value: &str) -> Result<Radix, String> {
+    match value {
+        "d" => Ok(Radix::Decimal),
+        "o" => Ok(Radix::Octal),
+        "x" => Ok(Radix::Hex),
+        "n" => Ok(Radix::None),
+        _ => Err(format!(
+            "invalid output address radix '{}'; it must be one character from [doxn]",
+            value
+        )),
+    }
+}
+
+fn parse_bytes(value: &str, what: &str) -> Result<u64, String> {
+    size::parse_offset(value).map_err(|e| format!("invalid {} argument: {}", what, e))
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let rest = x % y;
+        x = y;
+        y = rest;
+    }
+    a / x * b
+}
+
+impl OdConfig {
+    pub fn new() -> OdConfig {
+        OdConfig {
+            files: Vec::new(),
+            radix: Radix::Octal,
+            formats: Vec::new(),
+            big_endian: cfg!(target_endian = "big"),
+            skip: 0,
+            limit: None,
+            width: 16,
+            duplicates: false,
+            canonical: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        // `-w` takes its optional value attached, as in `-w8`.
+        let args: Vec<String> = args
+            .into_iter()
+            .enumerate()
+            .map(|(idx, arg)| match arg.strip_prefix("-w") {
+                Some(value) if idx > 0 && !value.is_empty() => format!("--width={}", value),
+                _ => arg,
+            })
+            .collect();
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        let mut width = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--address-radix", Some(value)) | ("-A", Some(value)) => {
+                    self.radix = parse_radix(value)?
+                }
+                ("--canonical", None) | ("-C", None) => self.canonical = true,
+                ("--endian", Some("big")) => self.big_endian = true,
+                ("--endian", Some("little")) => self.big_endian = false,
+                ("--endian", Some(value)) => {
+                    return Err(format!("invalid argument '{}' for '--endian'", value))
+                }
+                ("--skip-bytes", Some(value)) | ("-j", Some(value)) => {
+                    self.skip = parse_bytes(value, "skip-bytes")?
+                }
+                ("--read-bytes", Some(value)) | ("-N", Some(value)) => {
+                    self.limit = Some(parse_bytes(value, "read-bytes")?)
+                }
+                ("--format", Some(value)) | ("-t", Some(value)) => {
+                    self.formats.extend(parse_type(value)?)
+                }
+                ("--output-duplicates", None) | ("-v", None) => self.duplicates = true,
+                ("--width", Some(value)) => width = Some(parse_bytes(value, "width")?),
+                ("--width", None) | ("-w", None) => width = Some(32),
+                (name, None) if matches!(name, "-A" | "-j" | "-N" | "-t") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                (name, None) if name.len() == 2 && traditional(&name[1..]).is_some() => {
+                    self.formats.extend(traditional(&name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try od --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if self.formats.is_empty() {
+            self.formats.extend(traditional("o"));
+        }
+        if self.canonical {
+            self.width = hexdump::CANONICAL_WIDTH;
+            return Ok(());
+        }
+        let multiple = self.formats.iter().map(|format| format.size).fold(1, lcm);
+        self.width = match width {
+            Some(width) if width > 0 && width % multiple as u64 == 0 => width as usize,
+            Some(width) => {
+                self.warnings.push(format!(
+                    "invalid width {}; using {} instead",
+                    width, multiple
+                ));
+                multiple
+            }
+            None if multiple < 16 => 16 / multiple * multiple,
+            None => multiple,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{parse_type, Kind, OdConfig, Radix};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<OdConfig, String> {
+        let mut config = OdConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["od.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!(config.radix, Radix::Octal);
+        assert_eq!(config.formats.len(), 1);
+        assert_eq!(config.formats[0].kind, Kind::Octal);
+        assert_eq!(config.formats[0].size, 2);
+        assert_eq!(config.width, 16);
+        assert_eq!(config.skip, 0);
+        assert_eq!(config.limit, None);
+        assert!(!config.duplicates);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec![
+            "od.exe",
+            "-Ax",
+            "-j",
+            "0x10",
+            "-N",
+            "1K",
+            "-v",
+            "--endian=big",
+            "a",
+            "b"
+        ])
+        .unwrap();
+        assert_eq!(config.radix, Radix::Hex);
+        assert_eq!(config.skip, 16);
+        assert_eq!(config.limit, Some(1024));
+        assert!(config.duplicates);
+        assert!(config.big_endian);
+        assert_eq!(config.files, vec!["a", "b"]);
+        assert!(
+            !parse(string_vec!["od.exe", "--endian=little"])
+                .unwrap()
+                .big_endian
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let config = parse(string_vec!["od.exe", "-c", "-tx1z", "-bd"]).unwrap();
+        let kinds: Vec<(Kind, usize)> = config
+            .formats
+            .iter()
+            .map(|format| (format.kind, format.size))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (Kind::Char, 1),
+                (Kind::Hex, 1),
+                (Kind::Octal, 1),
+                (Kind::Unsigned, 2)
+            ]
+        );
+        assert!(config.formats[1].trailer);
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(parse(string_vec!["od.exe", "-w"]).unwrap().width, 32);
+        assert_eq!(parse(string_vec!["od.exe", "-w8"]).unwrap().width, 8);
+        assert_eq!(
+            parse(string_vec!["od.exe", "--width=4", "-tx4"])
+                .unwrap()
+                .width,
+            4
+        );
+        let config = parse(string_vec!["od.exe", "-w7", "-tx2"]).unwrap();
+        assert_eq!(config.width, 2);
+        assert_eq!(config.warnings, ["invalid width 7; using 2 instead"]);
+        assert_eq!(parse(string_vec!["od.exe", "-tx8"]).unwrap().width, 16);
+        let config = parse(string_vec!["od.exe", "-C", "-w7", "-tx2"]).unwrap();
+        assert!(config.canonical);
+        assert_eq!(config.width, 16);
+        assert!(config.warnings.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse(string_vec!["od.exe", "-Aq"]).unwrap_err(),
+            "invalid output address radix 'q'; it must be one character from [doxn]"
+        );
+        assert_eq!(
+            parse(string_vec!["od.exe", "--endian=middle"]).unwrap_err(),
+            "invalid argument 'middle' for '--endian'"
+        );
+        assert_eq!(
+            parse(string_vec!["od.exe", "-j", "x"]).unwrap_err(),
+            "invalid skip-bytes argument: invalid number: 'x'"
+        );
+        assert_eq!(
+            parse(string_vec!["od.exe", "-t"]).unwrap_err(),
+            "option requires an argument -- 't'"
+        );
+        assert!(parse(string_vec!["od.exe", "-q"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn types() {
+        let formats = parse_type("x1zcd2fu").unwrap();
+        let kinds: Vec<(Kind, usize, bool)> = formats
+            .iter()
+            .map(|format| (format.kind, format.size, format.trailer))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (Kind::Hex, 1, true),
+                (Kind::Char, 1, false),
+                (Kind::Signed, 2, false),
+                (Kind::Float, 8, false),
+                (Kind::Unsigned, 4, false)
+            ]
+        );
+        let size = |text: &str| parse_type(text).unwrap()[0].size;
+        assert_eq!(size("dL"), 8);
+        assert_eq!(size("oS"), 2);
+        assert_eq!(size("fF"), 4);
+        assert_eq!(size("fD"), 8);
+    }
+
+    #[test]
+    fn invalid_types() {
+        assert_eq!(
+            parse_type("x3").unwrap_err(),
+            "invalid type string 'x3';\nthis system doesn't provide a 3-byte integral type"
+        );
+        assert_eq!(
+            parse_type("f2").unwrap_err(),
+            "invalid type string 'f2';\nthis system doesn't provide a 2-byte floating point type"
+        );
+        assert_eq!(
+            parse_type("fL").unwrap_err(),
+            "invalid type string 'fL';\nthis system doesn't provide a 16-byte floating point type"
+        );
+        assert_eq!(
+            parse_type("x1q").unwrap_err(),
+            "invalid character 'q' in type string 'x1q'"
+        );
+        assert!(parse_type("fS").is_err());
+        assert!(parse_type("z").is_err());
+    }
+
+    #[test]
+    fn help() {
+        let mut config = OdConfig::new();
+        let parse_result = config.parse(string_vec!["od.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = OdConfig::new();
+        let parse_result = config.parse(string_vec!["od.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
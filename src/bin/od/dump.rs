@@ -0,0 +1,242 @@
+use std::io::{self, Read, Write};
+
+use coreutils::hexdump;
+use coreutils::input;
+
+use crate::config::Format;
+use crate::config::OdConfig;
+
+/// Writes blocks of `config.width` bytes, one line per format.
+struct Dumper<'a, W: Write> {
+    config: &'a OdConfig,
+    out: &'a mut W,
+    /// Padding shared out among the units of each format so that every
+    /// format's line is as wide as the widest.
+    pads: Vec<usize>,
+    previous: Option<Vec<u8>>,
+    /// A `*` already stands for the block just read.
+    squeezed: bool,
+}
+
+impl<W: Write> Dumper<'_, W> {
+    fn fields(
+        &mut self,
+        format: &Format,
+        pad: usize,
+        block: &[u8],
+        shown: usize,
+    ) -> io::Result<()> {
+        let fields = self.config.width / format.size;
+        let blank = (self.config.width - shown) / format.size;
+        let mut pad_left = pad;
+        for (idx, unit) in block.chunks(format.size).take(fields - blank).enumerate() {
+            let next_pad = pad * (fields - idx - 1) / fields;
+            let width = format.width() + pad_left - next_pad;
+            write!(
+                self.out,
+                "{:>width$}",
+                format.render(unit, self.config.big_endian),
+                width = width
+            )?;
+            pad_left = next_pad;
+        }
+
+        if format.trailer {
+            let rest = blank * format.width() + pad * blank / fields;
+            let text: String = block[..shown]
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7e => char::from(byte),
+                    _ => '.',
+                })
+                .collect();
+            write!(self.out, "{:rest$}  >{}<", "", text, rest = rest)?;
+        }
+        Ok(())
+    }
+
+    /// Write the `shown` bytes at the start of `block`, which is padded
+    /// with zeros to a whole number of units.
+    fn block(&mut self, offset: u64, block: &[u8], shown: usize) -> io::Result<()> {
+        let repeated = !self.config.duplicates
+            && shown == self.config.width
+            && self.previous.as_deref() == Some(block);
+        if repeated {
+            if !self.squeezed {
+                writeln!(self.out, "*")?;
+                self.squeezed = true;
+            }
+            return Ok(());
+        }
+        self.squeezed = false;
+        self.previous = Some(block.to_vec());
+
+        if self.config.canonical {
+            let line = hexdump::canonical_line(offset, &block[..shown], false);
+            return self.out.write_all(line.as_bytes());
+        }
+        for (idx, format) in self.config.formats.iter().enumerate() {
+            if idx == 0 {
+                write!(self.out, "{}", self.config.radix.format(offset))?;
+            } else {
+                write!(self.out, "{:1$}", "", self.config.radix.width())?;
+            }
+            self.fields(format, self.pads[idx], block, shown)?;
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Dump `input`, which starts at `config.skip`, up to `config.limit`
+/// bytes of it.
+pub fn dump<R: Read, W: Write>(input: &mut R, out: &mut W, config: &OdConfig) -> io::Result<()> {
+    let units = |format: &Format| config.width / format.size;
+    let line_width = config
+        .formats
+        .iter()
+        .map(|format| units(format) * (format.width() + 1))
+        .max()
+        .unwrap_or(0);
+    let mut dumper = Dumper {
+        config,
+        out,
+        pads: config
+            .formats
+            .iter()
+            .map(|format| line_width - units(format) * format.width())
+            .collect(),
+        previous: None,
+        squeezed: false,
+    };
+
+    let mut offset = config.skip;
+    let mut left = config.limit.unwrap_or(u64::MAX);
+    let mut block = vec![0; config.width];
+    loop {
+        let wanted = left.min(config.width as u64) as usize;
+        let read = input::fill(input, &mut block[..wanted])?;
+        if read == 0 {
+            break;
+        }
+        block[read..].iter_mut().for_each(|byte| *byte = 0);
+        let unit = config
+            .formats
+            .iter()
+            .map(|format| format.size)
+            .max()
+            .unwrap_or(1);
+        let padded = read.div_ceil(unit) * unit;
+        dumper.block(offset, &block[..padded.min(config.width)], read)?;
+        offset += read as u64;
+        left -= read as u64;
+        if read < config.width {
+            break;
+        }
+    }
+
+    let address = match config.canonical {
+        // As hexdump, which shows no address for no input.
+        true if offset > config.skip => format!("{:08x}", offset),
+        true => String::new(),
+        false => config.radix.format(offset),
+    };
+    if !address.is_empty() {
+        writeln!(dumper.out, "{}", address)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn od(args: &[&str], input: &[u8]) -> String {
+        let mut all: Vec<String> = string_vec!["od.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = OdConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        dump(&mut &input[..], &mut out, &config).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const TEXT: &[u8] = b"hello world\n\0\x01\x02\xffabc";
+
+    #[test]
+    fn default_octal() {
+        assert_eq!(
+            od(&[], TEXT),
+            "0000000 062550 066154 020157 067567 066162 005144 000400 177402\n\
+             0000020 061141 000143\n\
+             0000023\n"
+        );
+        assert_eq!(od(&[], b""), "0000000\n");
+    }
+
+    #[test]
+    fn several_formats() {
+        assert_eq!(
+            od(&["-c", "-tx1z", "-Ax"], &TEXT[..12]),
+            concat!(
+                "000000   h   e   l   l   o       w   o   r   l   d  \\n\n",
+                "        68  65  6c  6c  6f  20  77  6f  72  6c  64  0a",
+                "                  >hello world.<\n",
+                "00000c\n"
+            )
+        );
+        assert_eq!(od(&["-An", "-td1", "-w4"], b"\xff\x01"), "   -1    1\n");
+    }
+
+    #[test]
+    fn partial_units() {
+        assert_eq!(
+            od(&["-tx4", "-Ad"], b"abcdef"),
+            "0000000 64636261 00006665\n0000006\n"
+        );
+        assert_eq!(od(&["-tx2", "--endian=big", "-An"], b"abc"), " 6162 6300\n");
+    }
+
+    #[test]
+    fn canonical() {
+        assert_eq!(
+            od(&["-C"], TEXT),
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 00 01 02 ff  |hello world.....|\n\
+             00000010  61 62 63                                          |abc|\n\
+             00000013\n"
+        );
+        assert_eq!(
+            od(&["-C", "-j", "16"], &[0; 56][16..]),
+            "00000010  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             *\n\
+             00000030  00 00 00 00 00 00 00 00                           |........|\n\
+             00000038\n"
+        );
+        assert_eq!(od(&["-C"], b""), "");
+    }
+
+    #[test]
+    fn duplicates() {
+        let zeros = [0u8; 40];
+        assert_eq!(
+            od(&["-tx1", "-w8"], &zeros),
+            "0000000 00 00 00 00 00 00 00 00\n*\n0000050\n"
+        );
+        assert_eq!(od(&["-v", "-tx1", "-w8"], &zeros).lines().count(), 6);
+    }
+
+    #[test]
+    fn skip_and_limit() {
+        let mut config = OdConfig::new();
+        config
+            .parse(string_vec!["od.exe", "-c", "-j3", "-N5"])
+            .unwrap();
+        let mut out = Vec::new();
+        dump(&mut &TEXT[3..], &mut out, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0000003   l   o       w   o\n0000010\n"
+        );
+    }
+}
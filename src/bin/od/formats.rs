@@ -0,0 +1,170 @@
+use coreutils::format::Spec;
+
+use crate::config::{Format, Kind};
+
+const NAMES: [&str; 33] = [
+    "nul", "soh", "stx", "etx", "eot", "enq", "ack", "bel", "bs", "ht", "nl", "vt", "ff", "cr",
+    "so", "si", "dle", "dc1", "dc2", "dc3", "dc4", "nak", "syn", "etb", "can", "em", "sub", "esc",
+    "fs", "gs", "rs", "us", "sp",
+];
+
+/// The shortest `%g` rendering that reads back as the same value, starting
+/// from the digits the type always holds.
+fn shortest<T: PartialEq + std::str::FromStr>(value: f64, original: T, digits: usize) -> String {
+    if !value.is_finite() {
+        return Spec {
+            conversion: 'g',
+            ..Spec::default()
+        }
+        .float(value);
+    }
+    let mut precision = digits;
+    loop {
+        let text = Spec {
+            precision: Some(precision),
+            conversion: 'g',
+            ..Spec::default()
+        }
+        .float(value);
+        if precision >= 17 || text.parse::<T>().is_ok_and(|back| back == original) {
+            return text;
+        }
+        precision += 1;
+    }
+}
+
+impl Format {
+    /// The widest a unit can print.
+    pub fn width(&self) -> usize {
+        match (self.kind, self.size) {
+            (Kind::Named, _) | (Kind::Char, _) => 3,
+            (Kind::Signed, 1) => 4,
+            (Kind::Signed, 2) => 6,
+            (Kind::Signed, 4) => 11,
+            (Kind::Signed, _) => 20,
+            (Kind::Unsigned, 1) | (Kind::Octal, 1) => 3,
+            (Kind::Unsigned, 2) => 5,
+            (Kind::Unsigned, 4) => 10,
+            (Kind::Unsigned, _) => 20,
+            (Kind::Octal, 2) => 6,
+            (Kind::Octal, 4) => 11,
+            (Kind::Octal, _) => 22,
+            (Kind::Hex, size) => size * 2,
+            (Kind::Float, 4) => 15,
+            (Kind::Float, _) => 24,
+        }
+    }
+
+    /// One unit of `size` bytes, not yet padded.
+    pub fn render(&self, bytes: &[u8], big_endian: bool) -> String {
+        let mut value: u64 = 0;
+        for idx in 0..self.size {
+            let byte = if big_endian {
+                bytes[idx]
+            } else {
+                bytes[self.size - 1 - idx]
+            };
+            value = value << 8 | u64::from(byte);
+        }
+        let bits = self.size * 8;
+
+        match self.kind {
+            Kind::Named => {
+                let byte = bytes[0] & 0x7f;
+                match byte {
+                    0..=32 => NAMES[byte as usize].to_string(),
+                    0x7f => String::from("del"),
+                    _ => char::from(byte).to_string(),
+                }
+            }
+            Kind::Char => match bytes[0] {
+                0 => String::from("\\0"),
+                7 => String::from("\\a"),
+                8 => String::from("\\b"),
+                b'\t' => String::from("\\t"),
+                b'\n' => String::from("\\n"),
+                11 => String::from("\\v"),
+                12 => String::from("\\f"),
+                b'\r' => String::from("\\r"),
+                byte @ 0x20..=0x7e => char::from(byte).to_string(),
+                byte => format!("{:03o}", byte),
+            },
+            Kind::Signed => {
+                let shift = 64 - bits;
+                ((value << shift) as i64 >> shift).to_string()
+            }
+            Kind::Unsigned => value.to_string(),
+            Kind::Octal => format!("{:0width$o}", value, width = self.width()),
+            Kind::Hex => format!("{:0width$x}", value, width = self.width()),
+            Kind::Float if self.size == 4 => {
+                let float = f32::from_bits(value as u32);
+                let digits = if float.abs() < f32::MIN_POSITIVE {
+                    1
+                } else {
+                    6
+                };
+                shortest(f64::from(float), float, digits)
+            }
+            Kind::Float => {
+                let float = f64::from_bits(value);
+                let digits = if float.abs() < f64::MIN_POSITIVE {
+                    1
+                } else {
+                    15
+                };
+                shortest(float, float, digits)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_type;
+
+    fn format(text: &str) -> Format {
+        parse_type(text).unwrap()[0]
+    }
+
+    #[test]
+    fn integers() {
+        let bytes = [0xff, 0x01, 0x00, 0x80];
+        assert_eq!(format("d1").render(&bytes, false), "-1");
+        assert_eq!(format("u1").render(&bytes, false), "255");
+        assert_eq!(format("o1").render(&bytes, false), "377");
+        assert_eq!(format("x2").render(&bytes, false), "01ff");
+        assert_eq!(format("x2").render(&bytes, true), "ff01");
+        assert_eq!(format("d4").render(&bytes, false), "-2147483137");
+        assert_eq!(format("o2").render(&bytes[1..], false), "000001");
+    }
+
+    #[test]
+    fn characters() {
+        let render = |text: &str, byte: u8| format(text).render(&[byte], false);
+        assert_eq!(render("c", b'a'), "a");
+        assert_eq!(render("c", b'\n'), "\\n");
+        assert_eq!(render("c", 0), "\\0");
+        assert_eq!(render("c", 0xff), "377");
+        assert_eq!(render("a", b' '), "sp");
+        assert_eq!(render("a", 0xff), "del");
+        assert_eq!(render("a", 0x8a), "nl");
+        assert_eq!(render("a", b'~'), "~");
+    }
+
+    #[test]
+    fn floats() {
+        let render = |text: &str, bytes: &[u8]| format(text).render(bytes, false);
+        assert_eq!(render("f4", &1.5f32.to_le_bytes()), "1.5");
+        assert_eq!(render("f4", &4.371022e24f32.to_le_bytes()), "4.371022e+24");
+        assert_eq!(render("f4", &[1, 0, 0, 0]), "1e-45");
+        assert_eq!(render("f4", &(-0.0f32).to_le_bytes()), "-0");
+        assert_eq!(render("f8", &0.1f64.to_le_bytes()), "0.1");
+        assert_eq!(
+            render("f8", &(-6.17322917554311e303f64).to_le_bytes()),
+            "-6.17322917554311e+303"
+        );
+        assert_eq!(render("f4", &f32::INFINITY.to_le_bytes()), "inf");
+        assert_eq!(render("f4", &[0, 0, 0xc0, 0xff]), "-nan");
+    }
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod dump;
+mod formats;
+
+use config::OdConfig;
+
+/// The operands read one after another as a single input. A file that
+/// cannot be read is reported and passed over.
+struct Inputs<'a> {
+    files: &'a [String],
+    current: Option<Box<dyn BufRead>>,
+    name: &'a str,
+    failed: bool,
+}
+
+impl<'a> Inputs<'a> {
+    fn new(files: &'a [String]) -> Self {
+        Inputs {
+            files,
+            current: None,
+            name: "",
+            failed: false,
+        }
+    }
+
+    fn report(&mut self, e: &io::Error) {
+        eprintln!(
+            "od: {}: {}",
+            input::display_name(self.name),
+            output::describe(e)
+        );
+        self.failed = true;
+    }
+
+    /// Move on to the next file that opens, or return false at the end.
+    fn advance(&mut self) -> bool {
+        self.current = None;
+        while let Some((name, rest)) = self.files.split_first() {
+            self.files = rest;
+            self.name = name;
+            match input::open(name) {
+                Ok(reader) => {
+                    self.current = Some(reader);
+                    return true;
+                }
+                Err(e) => self.report(&e),
+            }
+        }
+        false
+    }
+
+    /// Pass over `count` bytes, skipping whole files by their size where
+    /// it is known. Returns false if the input ends first.
+    fn skip(&mut self, mut count: u64) -> bool {
+        while count > 0 {
+            if self.current.is_none() {
+                let size = match self.files.first() {
+                    Some(name) if name != input::STDIN => fs::metadata(name)
+                        .ok()
+                        .filter(|meta| meta.is_file())
+                        .map(|meta| meta.len()),
+                    _ => None,
+                };
+                match size {
+                    Some(size) if size <= count => {
+                        self.files = &self.files[1..];
+                        count -= size;
+                        continue;
+                    }
+                    _ if !self.advance() => return false,
+                    _ => {}
+                }
+            }
+            let reader = self.current.as_mut().expect("an open file");
+            match io::copy(&mut reader.take(count), &mut io::sink()) {
+                Ok(0) => self.current = None,
+                Ok(skipped) => count -= skipped,
+                Err(e) => {
+                    self.report(&e);
+                    self.current = None;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Read for Inputs<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.current.is_none() && !self.advance() {
+                return Ok(0);
+            }
+            let reader = self.current.as_mut().expect("an open file");
+            match reader.read(buf) {
+                Ok(0) => self.current = None,
+                Ok(read) => return Ok(read),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.report(&e);
+                    self.current = None;
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut config = OdConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+    for warning in &config.warnings {
+        eprintln!("od: warning: {}", warning);
+    }
+
+    let mut inputs = Inputs::new(&config.files);
+    if !inputs.skip(config.skip) {
+        eprintln!("od: cannot skip past end of combined input");
+        process::exit(1);
+    }
+
+    let mut out = Output::stdout("od");
+    if let Err(e) = dump::dump(&mut inputs, &mut out, &config) {
+        out.flush();
+        eprintln!("od: {}", output::describe(&e));
+        process::exit(1);
+    }
+    out.flush();
+    process::exit(if inputs.failed { 1 } else { 0 });
+}
@@ -0,0 +1,205 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::width;
+
+#[derive(Debug)]
+pub struct PasteConfig {
+    pub files: Vec<String>,
+    /// Written between lines in turn, starting over on each output line.
+    /// An empty delimiter writes nothing.
+    pub delimiters: Vec<Vec<u8>>,
+    pub serial: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: paste [OPTION]... [FILE]...
+Write lines consisting of the sequentially corresponding lines from
+each FILE, separated by TABs, to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -d, --delimiters=LIST   reuse characters from LIST instead of TABs
+  -s, --serial            paste one file at a time instead of in parallel
+  -z, --zero-terminated   line delimiter is NUL, not newline
+      --no-glob           do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+LIST may use the escapes \\n, \\t, \\\\ and \\0, which stands for no
+delimiter at all.  A carriage return before the newline is not part of
+the line; lines pasted from CRLF text end in CRLF.
+";
+
+impl Config for PasteConfig {
+    fn bin_name(&self) -> &'static str {
+        "paste"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+/// Split a delimiter list into its characters, expanding escapes.
+fn parse_delimiters(list: &str) -> Result<Vec<Vec<u8>>, String> {
+    let bytes = list.as_bytes();
+    let mut delimiters = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] != b'\\' {
+            let len = width::decode(&bytes[pos..]).1;
+            delimiters.push(bytes[pos..pos + len].to_vec());
+            pos += len;
+            continue;
+        }
+        let escaped = &bytes[pos + 1..];
+        if escaped.is_empty() {
+            return Err(format!(
+                "delimiter list ends with an unescaped backslash: {}",
+                list
+            ));
+        }
+        let len = width::decode(escaped).1;
+        delimiters.push(match escaped[0] {
+            b'0' => vec![],
+            b'b' => vec![0x08],
+            b'f' => vec![0x0c],
+            b'n' => vec![b'\n'],
+            b'r' => vec![b'\r'],
+            b't' => vec![b'\t'],
+            b'v' => vec![0x0b],
+            _ => escaped[..len].to_vec(),
+        });
+        pos += 1 + len;
+    }
+    if delimiters.is_empty() {
+        delimiters.push(Vec::new());
+    }
+    Ok(delimiters)
+}
+
+impl PasteConfig {
+    pub fn new() -> PasteConfig {
+        PasteConfig {
+            files: Vec::new(),
+            delimiters: vec![vec![b'\t']],
+            serial: false,
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--delimiters", Some(list)) | ("-d", Some(list)) => {
+                    self.delimiters = parse_delimiters(list)?
+                }
+                ("--serial", None) | ("-s", None) => self.serial = true,
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                ("-d", None) => return Err("option requires an argument -- 'd'".to_string()),
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try paste --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PasteConfig;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<PasteConfig, String> {
+        let mut config = PasteConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    fn delimiters(list: &str) -> Vec<Vec<u8>> {
+        parse(string_vec!["paste.exe", "-d", list])
+            .unwrap()
+            .delimiters
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["paste.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!(config.delimiters, vec![b"\t".to_vec()]);
+        assert!(!config.serial);
+        assert_eq!(config.delimiter(), b'\n');
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec!["paste.exe", "-sz", "a", "-"]).unwrap();
+        assert!(config.serial);
+        assert_eq!(config.delimiter(), b'\0');
+        assert_eq!(config.files, vec!["a", "-"]);
+    }
+
+    #[test]
+    fn delimiter_lists() {
+        assert_eq!(delimiters(",;"), vec![b",".to_vec(), b";".to_vec()]);
+        assert_eq!(
+            delimiters("\\n\\t\\\\x"),
+            vec![
+                b"\n".to_vec(),
+                b"\t".to_vec(),
+                b"\\".to_vec(),
+                b"x".to_vec()
+            ]
+        );
+        assert_eq!(
+            delimiters("a\\0b"),
+            vec![b"a".to_vec(), vec![], b"b".to_vec()]
+        );
+        assert_eq!(delimiters(""), vec![Vec::<u8>::new()]);
+        assert_eq!(
+            delimiters("é,\\日"),
+            vec!["é".into(), b",".to_vec(), "日".as_bytes().to_vec()]
+        );
+        assert_eq!(
+            parse(string_vec!["paste.exe", "-d", "ab\\"]).unwrap_err(),
+            "delimiter list ends with an unescaped backslash: ab\\"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = PasteConfig::new();
+        let parse_result = config.parse(string_vec!["paste.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = PasteConfig::new();
+        let parse_result = config.parse(string_vec!["paste.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
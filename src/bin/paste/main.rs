@@ -0,0 +1,75 @@
+use std::env;
+use std::io::BufRead;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod merge;
+
+use config::PasteConfig;
+
+fn main() {
+    let mut config = PasteConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("paste");
+    if config.serial {
+        for file in &config.files {
+            let result =
+                input::open(file).and_then(|reader| merge::serial(reader, &mut out, &config));
+            if let Err(e) = result {
+                out.flush();
+                eprintln!(
+                    "paste: {}: {}",
+                    input::display_name(file),
+                    output::describe(&e)
+                );
+                exit_code = 1;
+            }
+        }
+        out.flush();
+        process::exit(exit_code);
+    }
+
+    // Every `-` operand reads from the one standard input.
+    let mut inputs: Vec<Box<dyn BufRead>> = Vec::new();
+    let mut stdin = None;
+    let mut columns = Vec::new();
+    for file in &config.files {
+        if file == input::STDIN {
+            if let Some(column) = stdin {
+                columns.push(column);
+                continue;
+            }
+            stdin = Some(inputs.len());
+        }
+        match input::open(file) {
+            Ok(reader) => {
+                columns.push(inputs.len());
+                inputs.push(reader);
+            }
+            Err(e) => {
+                eprintln!(
+                    "paste: {}: {}",
+                    input::display_name(file),
+                    output::describe(&e)
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Err(e) = merge::parallel(inputs, &columns, &mut out, &config) {
+        out.flush();
+        eprintln!("paste: {}", output::describe(&e));
+        exit_code = 1;
+    }
+    out.flush();
+    process::exit(exit_code);
+}
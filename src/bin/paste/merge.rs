@@ -0,0 +1,148 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+
+use crate::config::PasteConfig;
+
+/// Ends an output line: with CRLF when the lines pasted into it had
+/// CRLF, otherwise with the configured delimiter.
+fn end_line(row: &mut Vec<u8>, crlf: bool, config: &PasteConfig) {
+    if crlf {
+        row.extend_from_slice(b"\r\n");
+    } else {
+        row.push(config.delimiter());
+    }
+}
+
+/// Paste the lines of several inputs side by side until all of them end.
+/// `columns` names the input each column reads from, so an input listed
+/// more than once gives its lines to those columns in turn.
+pub fn parallel<R: BufRead, W: Write>(
+    inputs: Vec<R>,
+    columns: &[usize],
+    out: &mut W,
+    config: &PasteConfig,
+) -> io::Result<()> {
+    let mut inputs: Vec<Option<LineReader<R>>> = inputs
+        .into_iter()
+        .map(|input| Some(LineReader::new(input, config.delimiter())))
+        .collect();
+    let delimiters = &config.delimiters;
+    let mut row = Vec::new();
+    loop {
+        row.clear();
+        let mut pasted = false;
+        let mut crlf = false;
+        for (idx, &column) in columns.iter().enumerate() {
+            if idx > 0 {
+                row.extend_from_slice(&delimiters[(idx - 1) % delimiters.len()]);
+            }
+            let Some(lines) = &mut inputs[column] else {
+                continue;
+            };
+            if lines.next_line()? {
+                row.extend_from_slice(lines.line());
+                crlf |= lines.terminator() == b"\r\n";
+                pasted = true;
+            } else {
+                inputs[column] = None;
+            }
+        }
+        if !pasted {
+            return Ok(());
+        }
+        end_line(&mut row, crlf, config);
+        out.write_all(&row)?;
+    }
+}
+
+/// Paste all the lines of one input into a single line.
+pub fn serial<R: BufRead, W: Write>(input: R, out: &mut W, config: &PasteConfig) -> io::Result<()> {
+    let mut lines = LineReader::new(input, config.delimiter());
+    let delimiters = &config.delimiters;
+    let mut row = Vec::new();
+    let mut crlf = false;
+    let mut count = 0;
+    while lines.next_line()? {
+        if count > 0 {
+            row.extend_from_slice(&delimiters[(count - 1) % delimiters.len()]);
+        }
+        row.extend_from_slice(lines.line());
+        crlf |= lines.terminator() == b"\r\n";
+        count += 1;
+    }
+    end_line(&mut row, crlf, config);
+    out.write_all(&row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn config(args: &[&str]) -> PasteConfig {
+        let mut all: Vec<String> = string_vec!["paste.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = PasteConfig::new();
+        config.parse(all).unwrap();
+        config
+    }
+
+    fn parallel_str(args: &[&str], inputs: &[&str], columns: &[usize]) -> String {
+        let inputs = inputs.iter().map(|input| input.as_bytes()).collect();
+        let mut out = Vec::new();
+        parallel(inputs, columns, &mut out, &config(args)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn serial_str(args: &[&str], input: &str) -> String {
+        let mut out = Vec::new();
+        serial(input.as_bytes(), &mut out, &config(args)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn side_by_side() {
+        assert_eq!(
+            parallel_str(&[], &["a\nb\nc\n", "1\n2"], &[0, 1]),
+            "a\t1\nb\t2\nc\t\n"
+        );
+        assert_eq!(parallel_str(&[], &["", "1\n"], &[0, 1]), "\t1\n");
+        assert_eq!(parallel_str(&[], &["", ""], &[0, 1]), "");
+    }
+
+    #[test]
+    fn delimiters_cycle() {
+        assert_eq!(
+            parallel_str(&["-d", ",;"], &["a\n", "b\n", "c\n", "d\n"], &[0, 1, 2, 3]),
+            "a,b;c,d\n"
+        );
+        assert_eq!(
+            parallel_str(&["-d", "\\0"], &["a\n", "b\n"], &[0, 1]),
+            "ab\n"
+        );
+    }
+
+    #[test]
+    fn repeated_input() {
+        // `paste - -` takes lines from standard input in turn.
+        assert_eq!(parallel_str(&[], &["1\n2\n3\n"], &[0, 0]), "1\t2\n3\t\n");
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(parallel_str(&[], &["a\r\n", "b\n"], &[0, 1]), "a\tb\r\n");
+        assert_eq!(
+            parallel_str(&["-z"], &["a\0b\0", "1\n2\0"], &[0, 1]),
+            "a\t1\n2\0b\t\0"
+        );
+    }
+
+    #[test]
+    fn one_at_a_time() {
+        assert_eq!(serial_str(&["-s"], "a\nb\nc\n"), "a\tb\tc\n");
+        assert_eq!(serial_str(&["-s", "-d", ",:"], "a\nb\nc\nd"), "a,b:c,d\n");
+        assert_eq!(serial_str(&["-s"], ""), "\n");
+        assert_eq!(serial_str(&["-s"], "a\r\nb\r\n"), "a\tb\r\n");
+    }
+}
@@ -0,0 +1,352 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::width::TAB_STOP;
+
+/// How lines are numbered under `-n`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Numbering {
+    pub separator: char,
+    pub digits: usize,
+}
+
+impl Numbering {
+    /// Columns taken by a number and its separator; a tab separator
+    /// reaches the next tab stop.
+    pub fn width(&self) -> usize {
+        match self.separator {
+            '\t' => self.digits + TAB_STOP - self.digits % TAB_STOP,
+            _ => self.digits + 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PrConfig {
+    pub files: Vec<String>,
+    pub columns: usize,
+    pub merge: bool,
+    pub double_space: bool,
+    /// Replaces the file name in page headers.
+    pub header: Option<String>,
+    pub number: Option<Numbering>,
+    pub omit_header: bool,
+    pub page_length: usize,
+    pub page_width: usize,
+}
+
+const HELP_TEXT: &str = "Usage: pr [OPTION]... [FILE]...
+Paginate or columnate FILE(s) for printing.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -COLUMN                 output COLUMN columns and print columns down,
+                            balancing the number of lines in the columns
+                            on the last page
+      --columns=COLUMN    same as -COLUMN
+  -d, --double-space      double space the output
+  -h, --header=HEADER     use a centered HEADER instead of filename in page
+                            header, -h \"\" prints a blank header
+  -l, --length=PAGE_LENGTH  set the page length to PAGE_LENGTH (66) lines
+                            (default number of lines of text 56);
+                            implies -t if PAGE_LENGTH <= 10
+  -m, --merge             print all files in parallel, one in each column,
+                            truncating lines
+  -n, --number-lines[=SEP[DIGITS]]  number lines, use DIGITS (5) digits,
+                            then SEP (TAB)
+  -t, --omit-header       omit page headers and trailers
+  -w, --width=PAGE_WIDTH  set page width to PAGE_WIDTH (72) characters for
+                            multiple text-column output only
+      --no-glob           do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Widths are display columns, so wide characters take two.  A carriage
+return before the newline is not part of the line.
+";
+
+/// Lines of header and of trailer on each page.
+pub const HEADER_LINES: usize = 5;
+
+impl Config for PrConfig {
+    fn bin_name(&self) -> &'static str {
+        "pr"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_positive(value: &str, what: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("invalid {}: '{}'", what, value)),
+    }
+}
+
+/// `-n` takes an optional separator character and then a digit count.
+fn parse_numbering(value: &str) -> Result<Numbering, String> {
+    let mut numbering = Numbering {
+        separator: '\t',
+        digits: 5,
+    };
+    let mut digits = value;
+    if let Some(first) = value.chars().next().filter(|c| !c.is_ascii_digit()) {
+        numbering.separator = first;
+        digits = &value[first.len_utf8()..];
+    }
+    if !digits.is_empty() {
+        numbering.digits = parse_positive(digits, "line number digits")?;
+    }
+    Ok(numbering)
+}
+
+impl PrConfig {
+    pub fn new() -> PrConfig {
+        PrConfig {
+            files: Vec::new(),
+            columns: 1,
+            merge: false,
+            double_space: false,
+            header: None,
+            number: None,
+            omit_header: false,
+            page_length: 66,
+            page_width: 72,
+        }
+    }
+
+    /// Whether pages have headers and trailers; short pages have none.
+    pub fn headers(&self) -> bool {
+        !self.omit_header && self.page_length > 2 * HEADER_LINES
+    }
+
+    /// Lines of text on each page.
+    pub fn body_length(&self) -> usize {
+        if self.headers() {
+            self.page_length - 2 * HEADER_LINES
+        } else {
+            self.page_length
+        }
+    }
+
+    /// Columns of text side by side: one per file when merging.
+    pub fn text_columns(&self) -> usize {
+        if self.merge {
+            self.files.len()
+        } else {
+            self.columns
+        }
+    }
+
+    /// Columns each line of text is cut to when there is more than one
+    /// side by side. Numbers come before every column, or once per line
+    /// when merging.
+    pub fn column_width(&self) -> Option<usize> {
+        let columns = self.text_columns();
+        let numbers = match &self.number {
+            Some(number) if self.merge => number.width(),
+            Some(number) => number.width() * columns,
+            None => 0,
+        };
+        self.page_width
+            .checked_sub(numbers + columns - 1)
+            .map(|width| width / columns)
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        // `-n` takes its optional value attached, as in `-n:3`.
+        let args: Vec<String> = args
+            .into_iter()
+            .enumerate()
+            .map(|(idx, arg)| match arg.strip_prefix("-n") {
+                Some(value) if idx > 0 && !value.is_empty() => {
+                    format!("--number-lines={}", value)
+                }
+                _ => arg,
+            })
+            .collect();
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        let mut columns = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--columns", Some(value)) => {
+                    columns = Some(parse_positive(value, "number of columns")?)
+                }
+                ("--double-space", None) | ("-d", None) => self.double_space = true,
+                ("--header", Some(text)) | ("-h", Some(text)) => {
+                    self.header = Some(text.to_string())
+                }
+                ("--length", Some(value)) | ("-l", Some(value)) => {
+                    self.page_length = parse_positive(value, "page length")?
+                }
+                ("--merge", None) | ("-m", None) => self.merge = true,
+                ("--number-lines", value) | ("-n", value) => {
+                    self.number = Some(parse_numbering(value.unwrap_or(""))?)
+                }
+                ("--omit-header", None) | ("-t", None) => self.omit_header = true,
+                ("--width", Some(value)) | ("-w", Some(value)) => {
+                    self.page_width = parse_positive(value, "page width")?
+                }
+                (name, None) if matches!(name, "-h" | "-l" | "-w") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    columns = Some(parse_positive(&number[1..], "number of columns")?)
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try pr --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if let Some(columns) = columns {
+            if self.merge {
+                return Err(
+                    "cannot specify number of columns when printing in parallel".to_string()
+                );
+            }
+            self.columns = columns;
+        }
+        if self.text_columns() > 1 && self.column_width().unwrap_or(0) == 0 {
+            return Err("page width too narrow".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Numbering, PrConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<PrConfig, String> {
+        let mut config = PrConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["pr.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!((config.page_length, config.page_width), (66, 72));
+        assert_eq!(config.columns, 1);
+        assert!(config.headers());
+        assert_eq!(config.body_length(), 56);
+        assert_eq!(config.number, None);
+    }
+
+    #[test]
+    fn columns() {
+        let config = parse(string_vec!["pr.exe", "-3"]).unwrap();
+        assert_eq!(config.columns, 3);
+        assert_eq!(config.column_width(), Some(23));
+        let config = parse(string_vec!["pr.exe", "--columns=2", "-w", "30", "-n"]).unwrap();
+        assert_eq!(config.column_width(), Some(6));
+
+        let config = parse(string_vec!["pr.exe", "-m", "-n", "a", "b"]).unwrap();
+        assert_eq!(config.text_columns(), 2);
+        assert_eq!(config.column_width(), Some(31));
+
+        assert_eq!(
+            parse(string_vec!["pr.exe", "-m", "-2"]).unwrap_err(),
+            "cannot specify number of columns when printing in parallel"
+        );
+        assert_eq!(
+            parse(string_vec!["pr.exe", "-5", "-w", "8"]).unwrap_err(),
+            "page width too narrow"
+        );
+        assert_eq!(
+            parse(string_vec!["pr.exe", "-0"]).unwrap_err(),
+            "invalid number of columns: '0'"
+        );
+    }
+
+    #[test]
+    fn numbering() {
+        let number = |args: Vec<String>| parse(args).unwrap().number.unwrap();
+        assert_eq!(
+            number(string_vec!["pr.exe", "-n"]),
+            Numbering {
+                separator: '\t',
+                digits: 5
+            }
+        );
+        assert_eq!(
+            number(string_vec!["pr.exe", "-n:3"]),
+            Numbering {
+                separator: ':',
+                digits: 3
+            }
+        );
+        assert_eq!(
+            number(string_vec!["pr.exe", "-n2"]),
+            Numbering {
+                separator: '\t',
+                digits: 2
+            }
+        );
+        assert_eq!(
+            number(string_vec!["pr.exe", "--number-lines=,"]),
+            Numbering {
+                separator: ',',
+                digits: 5
+            }
+        );
+        assert_eq!(number(string_vec!["pr.exe", "-n"]).width(), 8);
+        assert_eq!(number(string_vec!["pr.exe", "-n:3"]).width(), 4);
+        assert!(parse(string_vec!["pr.exe", "-n:x"]).is_err());
+    }
+
+    #[test]
+    fn pages() {
+        let config = parse(string_vec!["pr.exe", "-l", "20", "-d", "-h", "Title"]).unwrap();
+        assert_eq!(config.body_length(), 10);
+        assert!(config.double_space);
+        assert_eq!(config.header.as_deref(), Some("Title"));
+
+        // Pages too short for a header and trailer have neither.
+        let config = parse(string_vec!["pr.exe", "-l", "10"]).unwrap();
+        assert!(!config.headers());
+        assert_eq!(config.body_length(), 10);
+        let config = parse(string_vec!["pr.exe", "-t"]).unwrap();
+        assert_eq!(config.body_length(), 66);
+
+        assert_eq!(
+            parse(string_vec!["pr.exe", "-l", "0"]).unwrap_err(),
+            "invalid page length: '0'"
+        );
+        assert_eq!(
+            parse(string_vec!["pr.exe", "-w", "x"]).unwrap_err(),
+            "invalid page width: 'x'"
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = PrConfig::new();
+        let parse_result = config.parse(string_vec!["pr.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = PrConfig::new();
+        let parse_result = config.parse(string_vec!["pr.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
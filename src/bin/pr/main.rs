@@ -0,0 +1,80 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod page;
+
+use config::PrConfig;
+use page::Paginator;
+
+/// Header dates are when a file was last modified, or now for standard
+/// input and merged files.
+fn header_date(file: Option<&str>) -> String {
+    let time = file
+        .filter(|&file| file != input::STDIN)
+        .and_then(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .unwrap_or_else(SystemTime::now);
+    let datetime: DateTime<Local> = time.into();
+    datetime.format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn main() {
+    let mut config = PrConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("pr");
+    let mut paginator = Paginator::new(&config);
+
+    if config.merge {
+        let mut inputs = Vec::new();
+        for file in &config.files {
+            match input::open(file) {
+                Ok(reader) => inputs.push(reader),
+                Err(e) => {
+                    eprintln!(
+                        "pr: {}: {}",
+                        input::display_name(file),
+                        output::describe(&e)
+                    );
+                    exit_code = 1;
+                }
+            }
+        }
+        if let Err(e) = paginator.merge(inputs, &header_date(None), &mut out) {
+            out.flush();
+            eprintln!("pr: {}", output::describe(&e));
+            exit_code = 1;
+        }
+        out.flush();
+        process::exit(exit_code);
+    }
+
+    for file in &config.files {
+        let title = if file == input::STDIN { "" } else { file };
+        let result = input::open(file).and_then(|reader| {
+            paginator.paginate(reader, title, &header_date(Some(file)), &mut out)
+        });
+        if let Err(e) = result {
+            out.flush();
+            eprintln!(
+                "pr: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,461 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+use coreutils::width::{self, TAB_STOP};
+
+use crate::config::{Numbering, PrConfig, HEADER_LINES};
+
+/// One output line. Whitespace is held back until text follows it, so
+/// runs of it become tabs and none is left at the end of the line.
+struct Row {
+    text: Vec<u8>,
+    /// Column after the last text written.
+    written: usize,
+    /// Column after the whitespace held back.
+    position: usize,
+}
+
+impl Row {
+    fn clear(&mut self) {
+        self.text.clear();
+        self.written = 0;
+        self.position = 0;
+    }
+
+    fn pad_to(&mut self, column: usize) {
+        self.position = self.position.max(column);
+    }
+
+    fn write_blanks(&mut self) {
+        while self.position - self.written > 1 {
+            let tab = self.written + TAB_STOP - self.written % TAB_STOP;
+            if tab > self.position {
+                break;
+            }
+            self.text.push(b'\t');
+            self.written = tab;
+        }
+        self.text
+            .resize(self.text.len() + self.position - self.written, b' ');
+        self.written = self.position;
+    }
+
+    fn push(&mut self, bytes: &[u8], width: usize) {
+        self.write_blanks();
+        self.text.extend_from_slice(bytes);
+        self.position += width;
+        self.written = self.position;
+    }
+
+    /// A line number, keeping only as many of its low digits as fit.
+    fn push_number(&mut self, number: usize, numbering: &Numbering) {
+        let start = self.position;
+        let number = match 10usize.checked_pow(numbering.digits as u32) {
+            Some(limit) => number % limit,
+            None => number,
+        };
+        let digits = number.to_string();
+        self.pad_to(start + numbering.digits.saturating_sub(digits.len()));
+        self.push(digits.as_bytes(), digits.len());
+        match numbering.separator {
+            '\t' => self.pad_to(start + numbering.width()),
+            separator => {
+                let mut buffer = [0; 4];
+                let bytes = separator.encode_utf8(&mut buffer).as_bytes();
+                self.push(bytes, width::char_width(separator));
+            }
+        }
+    }
+
+    /// Start the `index`th column of output at `position`. As in GNU pr
+    /// the space separating it from the one before is written even when
+    /// no text follows, and the whitespace before it with it.
+    fn start_column(&mut self, index: usize, position: usize) {
+        self.pad_to(position);
+        if index > 0 {
+            self.write_blanks();
+        }
+    }
+
+    /// Text cut where it would reach `limit` columns past `start`, with
+    /// spaces and tabs as whitespace and tab stops counted from `start`.
+    fn push_cut(&mut self, line: &[u8], start: usize, limit: usize) {
+        let mut pos = 0;
+        while pos < line.len() {
+            let used = self.position - start;
+            let (chr, len) = width::decode(&line[pos..]);
+            let columns = match chr {
+                // GNU pr measures a tab opening the line before the number.
+                Some('\t') if pos == 0 => TAB_STOP,
+                Some('\t') => TAB_STOP - used % TAB_STOP,
+                Some(chr) => width::char_width(chr),
+                None => 1,
+            };
+            if used + columns > limit {
+                break;
+            }
+            if chr == Some('\t') || chr == Some(' ') {
+                self.position += columns;
+            } else {
+                self.push(&line[pos..pos + len], columns);
+            }
+            pos += len;
+        }
+    }
+
+    /// Text as it is, for a single column that is never cut.
+    fn push_whole(&mut self, line: &[u8]) {
+        self.push(line, width::bytes_width(line));
+    }
+}
+
+/// Lays text out in pages of the configured size.
+pub struct Paginator<'a> {
+    config: &'a PrConfig,
+    row: Row,
+    /// Columns of text in each column of output.
+    column_width: usize,
+    /// Rows of text on each page, before double spacing.
+    rows: usize,
+}
+
+impl<'a> Paginator<'a> {
+    pub fn new(config: &'a PrConfig) -> Self {
+        let spacing = if config.double_space { 2 } else { 1 };
+        Paginator {
+            config,
+            row: Row {
+                text: Vec::new(),
+                written: 0,
+                position: 0,
+            },
+            column_width: config.column_width().unwrap_or(0),
+            rows: (config.body_length() / spacing).max(1),
+        }
+    }
+
+    /// Distance from one column of output to the next.
+    fn stride(&self) -> usize {
+        let number = match &self.config.number {
+            Some(number) if !self.config.merge => number.width(),
+            _ => 0,
+        };
+        number + self.column_width + 1
+    }
+
+    fn write_header<W: Write>(
+        &self,
+        title: &str,
+        date: &str,
+        page: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        if !self.config.headers() {
+            return Ok(());
+        }
+        let title = self.config.header.as_deref().unwrap_or(title);
+        let page = format!("Page {}", page);
+        let available = self.config.page_width.saturating_sub(
+            width::str_width(date) + width::str_width(title) + width::str_width(&page),
+        );
+        let left = available / 2;
+        let right = available - left;
+        writeln!(
+            out,
+            "\n\n{}{:left$}{}{:right$}{}\n\n",
+            date,
+            "",
+            title,
+            "",
+            page,
+            left = left.max(1),
+            right = right.max(1)
+        )
+    }
+
+    /// Finish a page with `rows` rows of text on it.
+    fn write_trailer<W: Write>(&self, rows: usize, out: &mut W) -> io::Result<()> {
+        if !self.config.headers() {
+            return Ok(());
+        }
+        let spacing = if self.config.double_space { 2 } else { 1 };
+        let blank = self.config.body_length() - rows * spacing + HEADER_LINES;
+        out.write_all(&b"\n".repeat(blank))
+    }
+
+    fn write_row<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        out.write_all(&self.row.text)?;
+        out.write_all(if self.config.double_space {
+            b"\n\n"
+        } else {
+            b"\n"
+        })?;
+        self.row.clear();
+        Ok(())
+    }
+
+    /// Print one file, with its columns running down each page. The last
+    /// page is balanced so its columns are as even as they can be.
+    pub fn paginate<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        title: &str,
+        date: &str,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let columns = self.config.columns;
+        let stride = self.stride();
+        let mut lines = LineReader::new(input, b'\n');
+        let mut page_lines: Vec<Vec<u8>> = Vec::new();
+        let mut number = 1;
+        let mut page = 1;
+        loop {
+            page_lines.clear();
+            while page_lines.len() < self.rows * columns && lines.next_line()? {
+                page_lines.push(lines.line().to_vec());
+            }
+            if page_lines.is_empty() {
+                return Ok(());
+            }
+
+            let mut lengths = vec![page_lines.len() / columns; columns];
+            for length in lengths.iter_mut().take(page_lines.len() % columns) {
+                *length += 1;
+            }
+            let mut starts = Vec::with_capacity(columns);
+            let mut start = 0;
+            for length in &lengths {
+                starts.push(start);
+                start += length;
+            }
+
+            self.write_header(title, date, page, out)?;
+            for row in 0..lengths[0] {
+                for column in (0..columns).filter(|&column| row < lengths[column]) {
+                    let line = starts[column] + row;
+                    let start = column * stride;
+                    self.row.start_column(column, start);
+                    if let Some(numbering) = &self.config.number {
+                        self.row.push_number(number + line, numbering);
+                    }
+                    // As in GNU pr a number is part of its column of text.
+                    if columns > 1 {
+                        let limit = self.row.position - start + self.column_width;
+                        self.row.push_cut(&page_lines[line], start, limit);
+                    } else {
+                        self.row.push_whole(&page_lines[line]);
+                    }
+                }
+                self.write_row(out)?;
+            }
+            self.write_trailer(lengths[0], out)?;
+            number += page_lines.len();
+            page += 1;
+        }
+    }
+
+    /// Print files side by side, a line of each on every row, until all
+    /// of them end.
+    pub fn merge<W: Write>(
+        &mut self,
+        inputs: Vec<Box<dyn BufRead>>,
+        date: &str,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let stride = self.stride();
+        let numbers = self.config.number.as_ref().map_or(0, Numbering::width);
+        let mut inputs: Vec<Option<LineReader<Box<dyn BufRead>>>> = inputs
+            .into_iter()
+            .map(|input| Some(LineReader::new(input, b'\n')))
+            .collect();
+        let mut number = 1;
+        let mut page = 1;
+        loop {
+            let mut rows = 0;
+            while rows < self.rows {
+                let mut any = false;
+                for input in inputs.iter_mut() {
+                    if let Some(lines) = input {
+                        if lines.next_line()? {
+                            any = true;
+                        } else {
+                            *input = None;
+                        }
+                    }
+                }
+                if !any {
+                    break;
+                }
+                if rows == 0 {
+                    self.write_header("", date, page, out)?;
+                }
+                if let Some(numbering) = &self.config.number {
+                    self.row.push_number(number, numbering);
+                }
+                // Files that have ended still keep their place on the row.
+                for (column, input) in inputs.iter().enumerate() {
+                    let start = numbers + column * stride;
+                    self.row.start_column(column, start);
+                    if let Some(lines) = input {
+                        self.row.push_cut(lines.line(), start, self.column_width);
+                    }
+                }
+                self.write_row(out)?;
+                number += 1;
+                rows += 1;
+            }
+            if rows == 0 {
+                return Ok(());
+            }
+            self.write_trailer(rows, out)?;
+            page += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    const DATE: &str = "2024-01-02 03:04";
+
+    fn config(args: &[&str]) -> PrConfig {
+        let mut all: Vec<String> = string_vec!["pr.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = PrConfig::new();
+        config.parse(all).unwrap();
+        config
+    }
+
+    fn paginate(args: &[&str], input: &str) -> String {
+        let config = config(args);
+        let mut out = Vec::new();
+        Paginator::new(&config)
+            .paginate(input.as_bytes(), "file", DATE, &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn merge(args: &[&str], inputs: &[&'static str]) -> String {
+        let config = config(args);
+        let inputs = inputs
+            .iter()
+            .map(|input| Box::new(input.as_bytes()) as Box<dyn BufRead>)
+            .collect();
+        let mut out = Vec::new();
+        Paginator::new(&config)
+            .merge(inputs, DATE, &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn numbered(count: usize) -> String {
+        (1..=count).map(|n| format!("{}\n", n)).collect()
+    }
+
+    #[test]
+    fn pages() {
+        let header = |page| format!("\n\n{}{:23}file{:23}Page {}\n\n\n", DATE, "", "", page);
+        assert_eq!(
+            paginate(&["-l", "13"], "a\nb\nc\nd\n"),
+            format!(
+                "{}a\nb\nc\n\n\n\n\n\n{}d\n\n\n\n\n\n\n\n",
+                header(1),
+                header(2)
+            )
+        );
+        assert_eq!(paginate(&[], ""), "");
+        assert!(paginate(&["-h", "Title"], "a\n").contains(" Title "));
+        assert_eq!(
+            paginate(&["-l", "10"], "a\tb\n"),
+            "a\tb\n",
+            "short pages have no header"
+        );
+    }
+
+    #[test]
+    fn double_space() {
+        assert_eq!(paginate(&["-t", "-d"], "a\nb\n"), "a\n\nb\n\n");
+        let page = paginate(&["-l", "14", "-d"], "a\nb\nc\n");
+        assert_eq!(page.matches("Page").count(), 2);
+        assert_eq!(page.lines().count(), 28);
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(
+            paginate(&["-t", "-3"], &numbered(7)),
+            "1\t\t\t4\t\t\t6\n2\t\t\t5\t\t\t7\n3\n"
+        );
+        assert_eq!(
+            paginate(&["-t", "-2", "-w", "12", "-l", "2"], &numbered(5)),
+            "1     3\n2     4\n5\n"
+        );
+        // Columns are cut to fit, with their blanks turned into tabs where
+        // they reach a tab stop and dropped at the end of the line.
+        assert_eq!(
+            paginate(&["-t", "-2", "-w", "20"], "abcdefghijkl\na\tb\n"),
+            "abcdefghi a\t  b\n"
+        );
+        assert_eq!(
+            paginate(
+                &["-t", "-2", "-w", "24"],
+                "The quick\tfox\nend of it all\nab  \tc\n  x\n"
+            ),
+            "The quick   ab\t    c\nend of it a   x\n"
+        );
+        assert_eq!(
+            paginate(&["-t", "-2", "-n:3", "-w", "30"], "\tb\na\tb\n"),
+            "  1:\t    b  \t 2:a   b\n"
+        );
+        assert_eq!(
+            paginate(&["-t", "-2", "-w", "9"], "日本語\nab\n"),
+            "日本 ab\n"
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(paginate(&["-t", "-n"], "a\nb\n"), "    1\ta\n    2\tb\n");
+        assert_eq!(paginate(&["-t", "-n:2"], "a\n"), " 1:a\n");
+        assert_eq!(
+            paginate(&["-t", "-n,1"], &numbered(11)).lines().last(),
+            Some("1,11")
+        );
+        assert_eq!(
+            paginate(&["-t", "-2", "-n:2", "-w", "20"], "a\nb\nc\n"),
+            " 1:a\t   3:c\n 2:b\n"
+        );
+    }
+
+    #[test]
+    fn merged() {
+        assert_eq!(
+            merge(&["-m", "-t", "-w", "20", "x", "y"], &["a\nb\nc\n", "1\n"]),
+            "a\t  1\nb\t  \nc\t  \n"
+        );
+        assert_eq!(
+            merge(
+                &["-m", "-t", "-w", "24", "x", "y"],
+                &["one\ntwo  \n", "a\tb\n\n"]
+            ),
+            "one\t    a\t    b\ntwo\t    \n"
+        );
+        assert_eq!(
+            merge(
+                &["-m", "-t", "-w", "20", "x", "y", "z"],
+                &["a\n", "", "c\n"]
+            ),
+            "a      \t      c\n"
+        );
+        assert_eq!(
+            merge(
+                &["-m", "-t", "-n:2", "-w", "20", "x", "y"],
+                &["a\n", "b\nc\n"]
+            ),
+            " 1:a\t    b\n 2:\t    c\n"
+        );
+        assert_eq!(merge(&["-m", "x", "y"], &["", ""]), "");
+    }
+}
@@ -0,0 +1,142 @@
+use coreutils::config::Config;
+
+#[derive(Debug)]
+pub struct PrintfConfig {
+    pub format: String,
+    pub arguments: Vec<String>,
+}
+
+const HELP_TEXT: &str = "Usage: printf FORMAT [ARGUMENT]...
+  or:  printf OPTION
+Print ARGUMENT(s) according to FORMAT.
+
+      --help     display this help and exit
+      --version  output version information and exit
+
+FORMAT controls the output as in C printf.  Interpreted sequences are:
+
+  \\\"      double quote
+  \\\\      backslash
+  \\a      alert (BEL)
+  \\b      backspace
+  \\c      produce no further output
+  \\e      escape
+  \\f      form feed
+  \\n      new line
+  \\r      carriage return
+  \\t      horizontal tab
+  \\v      vertical tab
+  \\NNN    byte with octal value NNN (1 to 3 digits)
+  \\xHH    byte with hexadecimal value HH (1 to 2 digits)
+  \\uHHHH  Unicode (ISO/IEC 10646) character with hex value HHHH (4 digits)
+  \\UHHHHHHHH  Unicode character with hex value HHHHHHHH (8 digits)
+  %%      a single %
+  %b      ARGUMENT as a string with '\\' escapes interpreted,
+          except that octal escapes are of the form \\0 or \\0NNN
+  %q      ARGUMENT is printed in a format that can be reused as shell input,
+          escaping non-printable characters with the proposed POSIX $'' syntax.
+
+and all C format specifications ending with one of diouxXfeEgGcs, with
+ARGUMENTs converted to proper type first.  Variable widths are handled.
+The FORMAT is reused as necessary to consume all of the ARGUMENTs.
+Field widths and precisions count characters, not bytes.
+";
+
+impl Config for PrintfConfig {
+    fn bin_name(&self) -> &'static str {
+        "printf"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+impl PrintfConfig {
+    pub fn new() -> PrintfConfig {
+        PrintfConfig {
+            format: String::new(),
+            arguments: Vec::new(),
+        }
+    }
+
+    /// Arguments may start with `-`, so nothing but a lone `--help` or
+    /// `--version` and a leading `--` is treated as an option.
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        if args.len() == 2 {
+            Config::parse(self, &args)?;
+        }
+
+        let mut operands = args.into_iter().skip(1).peekable();
+        operands.next_if(|arg| arg == "--");
+        self.format = operands
+            .next()
+            .ok_or("missing operand\n Try printf --help for more information")?;
+        self.arguments = operands.collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PrintfConfig;
+    use coreutils::string_vec;
+
+    #[test]
+    fn format_and_arguments() {
+        let mut config = PrintfConfig::new();
+        config
+            .parse(string_vec!["printf.exe", "%s %d\\n", "-n", "--", "5"])
+            .unwrap();
+        assert_eq!(config.format, "%s %d\\n");
+        assert_eq!(config.arguments, vec!["-n", "--", "5"]);
+    }
+
+    #[test]
+    fn leading_double_dash() {
+        let mut config = PrintfConfig::new();
+        config
+            .parse(string_vec!["printf.exe", "--", "--help"])
+            .unwrap();
+        assert_eq!(config.format, "--help");
+        assert!(config.arguments.is_empty());
+    }
+
+    #[test]
+    fn dash_format() {
+        let mut config = PrintfConfig::new();
+        config
+            .parse(string_vec!["printf.exe", "-x", "--help"])
+            .unwrap();
+        assert_eq!(config.format, "-x");
+        assert_eq!(config.arguments, vec!["--help"]);
+    }
+
+    #[test]
+    fn missing_operand() {
+        let mut config = PrintfConfig::new();
+        assert!(config
+            .parse(string_vec!["printf.exe"])
+            .unwrap_err()
+            .contains("missing operand"));
+        assert!(config
+            .parse(string_vec!["printf.exe", "--"])
+            .unwrap_err()
+            .contains("missing operand"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = PrintfConfig::new();
+        let parse_result = config.parse(string_vec!["printf.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = PrintfConfig::new();
+        let parse_result = config.parse(string_vec!["printf.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
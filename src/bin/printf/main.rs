@@ -0,0 +1,51 @@
+use std::env;
+use std::process;
+
+use coreutils::format::Format;
+use coreutils::output::Output;
+
+mod config;
+
+fn main() {
+    let mut config = config::PrintfConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let format = match Format::parse(&config.format) {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("printf: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if !format.takes_arguments() && !config.arguments.is_empty() {
+        eprintln!(
+            "printf: warning: ignoring excess arguments, starting with '{}'",
+            config.arguments[0]
+        );
+    }
+
+    let mut text = Vec::new();
+    let result = format.render(&config.arguments, &mut text);
+    let mut out = Output::stdout("printf");
+    out.write_all(&text);
+    out.flush();
+
+    match result {
+        Ok(warnings) => {
+            for warning in &warnings {
+                eprintln!("printf: {}", warning);
+            }
+            if !warnings.is_empty() {
+                process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("printf: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+#[derive(Debug)]
+pub struct RevConfig {
+    pub files: Vec<String>,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: rev [OPTION]... [FILE]...
+Reverse the characters of each line of each FILE.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -0, --zero       line delimiter is NUL, not newline
+      --no-glob    do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Lines are reversed by grapheme cluster, so combining marks and emoji
+sequences keep their order.  Bytes that are not valid UTF-8 are reversed
+one at a time.  A carriage return before the newline is not part of the
+line.
+";
+
+impl Config for RevConfig {
+    fn bin_name(&self) -> &'static str {
+        "rev"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+impl RevConfig {
+    pub fn new() -> RevConfig {
+        RevConfig {
+            files: Vec::new(),
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--zero", None) | ("-0", None) => self.zero_terminated = true,
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try rev --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RevConfig;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<RevConfig, String> {
+        let mut config = RevConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["rev.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert!(!config.zero_terminated);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(string_vec!["rev.exe", "-0", "a", "b"]).unwrap();
+        assert!(config.zero_terminated);
+        assert_eq!(config.files, vec!["a", "b"]);
+        assert!(
+            parse(string_vec!["rev.exe", "--zero"])
+                .unwrap()
+                .zero_terminated
+        );
+        assert!(parse(string_vec!["rev.exe", "-x"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = RevConfig::new();
+        let parse_result = config.parse(string_vec!["rev.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = RevConfig::new();
+        let parse_result = config.parse(string_vec!["rev.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
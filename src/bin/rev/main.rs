@@ -0,0 +1,37 @@
+use std::env;
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod reverse;
+
+use config::RevConfig;
+
+fn main() {
+    let mut config = RevConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let delimiter = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut exit_code = 0;
+    let mut out = Output::stdout("rev");
+    for file in &config.files {
+        let result = input::open(file).and_then(|reader| reverse::rev(reader, &mut out, delimiter));
+        if let Err(e) = result {
+            out.flush();
+            eprintln!(
+                "rev: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,89 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Fill `reversed` with the pieces of `line` in reverse order: grapheme
+/// clusters of valid UTF-8, and invalid bytes one by one.
+fn reverse_line(line: &[u8], reversed: &mut Vec<u8>) {
+    reversed.clear();
+    reversed.resize(line.len(), 0);
+    let mut end = line.len();
+    let mut place = |piece: &[u8]| {
+        reversed[end - piece.len()..end].copy_from_slice(piece);
+        end -= piece.len();
+    };
+    for chunk in line.utf8_chunks() {
+        for grapheme in chunk.valid().graphemes(true) {
+            place(grapheme.as_bytes());
+        }
+        for byte in chunk.invalid() {
+            place(std::slice::from_ref(byte));
+        }
+    }
+}
+
+/// Reverse every line of `input`, keeping each terminator at the end.
+pub fn rev<R: BufRead, W: Write>(input: R, out: &mut W, delimiter: u8) -> io::Result<()> {
+    let mut lines = LineReader::new(input, delimiter);
+    let mut reversed = Vec::new();
+    while lines.next_line()? {
+        reverse_line(lines.line(), &mut reversed);
+        out.write_all(&reversed)?;
+        out.write_all(lines.terminator())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rev_bytes(input: &[u8], delimiter: u8) -> Vec<u8> {
+        let mut out = Vec::new();
+        rev(input, &mut out, delimiter).unwrap();
+        out
+    }
+
+    fn rev_str(input: &str) -> String {
+        String::from_utf8(rev_bytes(input.as_bytes(), b'\n')).unwrap()
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(rev_str("abc\nde\n\nf"), "cba\ned\n\nf");
+        assert_eq!(rev_str("ab\r\ncd\r\n"), "ba\r\ndc\r\n");
+        assert_eq!(rev_str(""), "");
+    }
+
+    #[test]
+    fn graphemes() {
+        // "e" and a combining acute accent stay together.
+        assert_eq!(rev_str("ae\u{301}b\n"), "be\u{301}a\n");
+        // A family emoji joined with zero width joiners.
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(
+            rev_str(&format!("x{}y\n", family)),
+            format!("y{}x\n", family)
+        );
+        // Flags are pairs of regional indicators.
+        assert_eq!(
+            rev_str("\u{1f1eb}\u{1f1f7}\u{1f1e9}\u{1f1ea}"),
+            "\u{1f1e9}\u{1f1ea}\u{1f1eb}\u{1f1f7}"
+        );
+        assert_eq!(rev_str("日本語\n"), "語本日\n");
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(
+            rev_bytes(b"a\xff\xfeb\xc3\xa9\n", b'\n'),
+            b"\xc3\xa9b\xfe\xffa\n"
+        );
+    }
+
+    #[test]
+    fn zero_terminated() {
+        assert_eq!(rev_bytes(b"ab\ncd\0ef\0", b'\0'), b"dc\nba\0fe\0");
+    }
+}
@@ -0,0 +1,361 @@
+use coreutils::config::{self, Config};
+use coreutils::format::Spec;
+
+/// A `-f` format: one floating point directive with text around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatFormat {
+    pub prefix: String,
+    pub spec: Spec,
+    pub suffix: String,
+}
+
+impl FloatFormat {
+    pub fn parse(format: &str) -> Result<FloatFormat, String> {
+        let mut parts = vec![String::new()];
+        let mut spec = None;
+        let mut rest = format;
+        while let Some(idx) = rest.find('%') {
+            parts.last_mut().unwrap().push_str(&rest[..idx]);
+            let after = &rest[idx + 1..];
+            if let Some(after) = after.strip_prefix('%') {
+                parts.last_mut().unwrap().push('%');
+                rest = after;
+                continue;
+            }
+            if spec.is_some() {
+                return Err(format!("format '{}' has too many % directives", format));
+            }
+            let (parsed, used) =
+                Spec::parse(after).ok_or_else(|| format!("format '{}' ends in %", format))?;
+            if !"aAeEfFgG".contains(parsed.conversion)
+                || parsed.width_from_arg
+                || parsed.precision_from_arg
+            {
+                let directive = after[..used].chars().last().unwrap_or('%');
+                return Err(format!(
+                    "format '{}' has unknown %{} directive",
+                    format, directive
+                ));
+            }
+            spec = Some(parsed);
+            parts.push(String::new());
+            rest = &after[used..];
+        }
+        parts.last_mut().unwrap().push_str(rest);
+
+        let spec = spec.ok_or_else(|| format!("format '{}' has no % directive", format))?;
+        let suffix = parts.pop().unwrap_or_default();
+        Ok(FloatFormat {
+            prefix: parts.pop().unwrap_or_default(),
+            spec,
+            suffix,
+        })
+    }
+
+    pub fn render(&self, value: f64) -> String {
+        format!("{}{}{}", self.prefix, self.spec.float(value), self.suffix)
+    }
+}
+
+#[derive(Debug)]
+pub struct SeqConfig {
+    pub first: String,
+    pub increment: String,
+    pub last: String,
+    pub separator: String,
+    pub equal_width: bool,
+    pub format: Option<FloatFormat>,
+}
+
+const HELP_TEXT: &str = "Usage: seq [OPTION]... LAST
+  or:  seq [OPTION]... FIRST LAST
+  or:  seq [OPTION]... FIRST INCREMENT LAST
+Print numbers from FIRST to LAST, in steps of INCREMENT.
+
+  -f, --format=FORMAT      use printf style floating-point FORMAT
+  -s, --separator=STRING   use STRING to separate numbers (default: \\n)
+  -w, --equal-width        equalize width by padding with leading zeroes
+      --help     display this help and exit
+      --version  output version information and exit
+
+If FIRST or INCREMENT is omitted, it defaults to 1.  That is, an
+omitted INCREMENT defaults to 1 even when LAST is smaller than FIRST.
+The sequence of numbers ends when the sum of the current number and
+INCREMENT would become greater than LAST.
+Decimal arguments are added exactly, so steps such as 0.1 do not drift.
+INCREMENT must not be 0; none of FIRST, INCREMENT and LAST may be NaN.
+FORMAT must be suitable for printing one floating point argument; without
+it numbers are printed with as many decimals as FIRST or INCREMENT has.
+";
+
+impl Config for SeqConfig {
+    fn bin_name(&self) -> &'static str {
+        "seq"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+/// `-1` and `-.5` are numbers to seq, not options.
+fn is_negative_number(arg: &str) -> bool {
+    let mut chars = arg.chars();
+    chars.next() == Some('-') && chars.next().is_some_and(|c| c == '.' || c.is_ascii_digit())
+}
+
+impl SeqConfig {
+    pub fn new() -> SeqConfig {
+        SeqConfig {
+            first: String::from("1"),
+            increment: String::from("1"),
+            last: String::new(),
+            separator: String::from("\n"),
+            equal_width: false,
+            format: None,
+        }
+    }
+
+    /// Separate options from operands, treating negative numbers as
+    /// operands. Values of options that take one stay with the option.
+    fn split(&self, args: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let specs = self.options();
+        let takes_value = |arg: &str| match arg.strip_prefix("--") {
+            Some(name) => specs
+                .iter()
+                .any(|spec| spec.long.as_deref() == Some(name) && spec.takes_value()),
+            None => {
+                let flags: Vec<char> = arg.chars().skip(1).collect();
+                flags
+                    .iter()
+                    .position(|&c| {
+                        specs
+                            .iter()
+                            .any(|spec| spec.short == Some(c) && spec.takes_value())
+                    })
+                    .is_some_and(|pos| pos + 1 == flags.len())
+            }
+        };
+
+        let mut args = args.into_iter();
+        let mut options: Vec<String> = args.next().into_iter().collect();
+        let mut operands = Vec::new();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                operands.extend(args.by_ref());
+            } else if arg == "-" || !arg.starts_with('-') || is_negative_number(&arg) {
+                operands.push(arg);
+            } else {
+                let value = if takes_value(&arg) { args.next() } else { None };
+                options.push(arg);
+                options.extend(value);
+            }
+        }
+        (options, operands)
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let (options, operands) = self.split(args);
+        let base_config = Config::parse(self, &options)?;
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--format", Some(format)) | ("-f", Some(format)) => {
+                    self.format = Some(FloatFormat::parse(format)?)
+                }
+                ("--separator", Some(separator)) | ("-s", Some(separator)) => {
+                    self.separator = separator.to_string()
+                }
+                ("--equal-width", None) | ("-w", None) => self.equal_width = true,
+                ("--format", None) | ("-f", None) | ("--separator", None) | ("-s", None) => {
+                    return Err(format!("option requires an argument -- '{}'", option))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try seq --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if self.format.is_some() && self.equal_width {
+            return Err(String::from(
+                "format string may not be specified when printing equal width strings\n Try seq --help for more information",
+            ));
+        }
+
+        let mut operands = operands.into_iter();
+        match (
+            operands.next(),
+            operands.next(),
+            operands.next(),
+            operands.next(),
+        ) {
+            (None, ..) => {
+                return Err(String::from(
+                    "missing operand\n Try seq --help for more information",
+                ))
+            }
+            (Some(last), None, ..) => self.last = last,
+            (Some(first), Some(last), None, _) => {
+                self.first = first;
+                self.last = last;
+            }
+            (Some(first), Some(increment), Some(last), None) => {
+                self.first = first;
+                self.increment = increment;
+                self.last = last;
+            }
+            (.., Some(extra)) => {
+                return Err(format!(
+                    "extra operand '{}'\n Try seq --help for more information",
+                    extra
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{FloatFormat, SeqConfig};
+    use coreutils::string_vec;
+
+    #[test]
+    fn defaults() {
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "5"]).unwrap();
+        assert_eq!(config.first, "1");
+        assert_eq!(config.increment, "1");
+        assert_eq!(config.last, "5");
+        assert_eq!(config.separator, "\n");
+        assert!(!config.equal_width);
+        assert!(config.format.is_none());
+    }
+
+    #[test]
+    fn operands() {
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "2", "9"]).unwrap();
+        assert_eq!((config.first.as_str(), config.last.as_str()), ("2", "9"));
+
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "2", "3", "9"]).unwrap();
+        assert_eq!(config.increment, "3");
+    }
+
+    #[test]
+    fn negative_operands() {
+        let mut config = SeqConfig::new();
+        config
+            .parse(string_vec!["seq.exe", "-w", "-5", "-.5", "-1"])
+            .unwrap();
+        assert!(config.equal_width);
+        assert_eq!(config.first, "-5");
+        assert_eq!(config.increment, "-.5");
+        assert_eq!(config.last, "-1");
+
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "--", "-inf"]).unwrap();
+        assert_eq!(config.last, "-inf");
+    }
+
+    #[test]
+    fn missing_and_extra_operands() {
+        let mut config = SeqConfig::new();
+        assert!(config
+            .parse(string_vec!["seq.exe"])
+            .unwrap_err()
+            .contains("missing operand"));
+        assert!(config
+            .parse(string_vec!["seq.exe", "1", "2", "3", "4"])
+            .unwrap_err()
+            .contains("extra operand '4'"));
+    }
+
+    #[test]
+    fn separator() {
+        let mut config = SeqConfig::new();
+        config
+            .parse(string_vec!["seq.exe", "-s", "-1", "3"])
+            .unwrap();
+        assert_eq!(config.separator, "-1");
+        assert_eq!(config.last, "3");
+
+        let mut config = SeqConfig::new();
+        config
+            .parse(string_vec!["seq.exe", "--separator=, ", "3"])
+            .unwrap();
+        assert_eq!(config.separator, ", ");
+
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "-ws:", "3"]).unwrap();
+        assert!(config.equal_width);
+        assert_eq!(config.separator, ":");
+    }
+
+    #[test]
+    fn format() {
+        let mut config = SeqConfig::new();
+        config
+            .parse(string_vec!["seq.exe", "-f", "%%x%05.1f%%", "3"])
+            .unwrap();
+        let format = config.format.unwrap();
+        assert_eq!(format.prefix, "%x");
+        assert_eq!(format.suffix, "%");
+        assert_eq!(format.render(2.0), "%x002.0%");
+    }
+
+    #[test]
+    fn invalid_formats() {
+        let cases = [
+            ("x", "has no % directive"),
+            ("%d", "has unknown %d directive"),
+            ("%f%g", "has too many % directives"),
+            ("%*f", "has unknown"),
+            ("%5", "ends in %"),
+        ];
+        for (format, message) in &cases {
+            assert!(
+                FloatFormat::parse(format).unwrap_err().contains(message),
+                "{}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn format_with_equal_width() {
+        let mut config = SeqConfig::new();
+        assert!(config
+            .parse(string_vec!["seq.exe", "-w", "-f", "%g", "3"])
+            .unwrap_err()
+            .contains("equal width"));
+    }
+
+    #[test]
+    fn invalid_option() {
+        let mut config = SeqConfig::new();
+        assert!(config
+            .parse(string_vec!["seq.exe", "--bogus", "3"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = SeqConfig::new();
+        let parse_result = config.parse(string_vec!["seq.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = SeqConfig::new();
+        let parse_result = config.parse(string_vec!["seq.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,41 @@
+use std::env;
+use std::process;
+
+use coreutils::output::{self, Output};
+
+mod config;
+mod number;
+mod sequence;
+
+use number::Number;
+
+fn main() {
+    let mut config = config::SeqConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let parse = |text: &str| {
+        Number::parse(text).unwrap_or_else(|e| {
+            eprintln!("seq: {}\n Try seq --help for more information", e);
+            process::exit(1);
+        })
+    };
+    let first = parse(&config.first);
+    let increment = parse(&config.increment);
+    let last = parse(&config.last);
+    if increment.is_zero() {
+        eprintln!(
+            "seq: invalid Zero increment value: '{}'\n Try seq --help for more information",
+            config.increment
+        );
+        process::exit(1);
+    }
+
+    let mut out = Output::stdout("seq");
+    if let Err(e) = sequence::write(&mut out, first, increment, last, &config) {
+        output::write_failed("seq", &e);
+    }
+    out.flush();
+}
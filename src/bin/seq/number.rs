@@ -0,0 +1,366 @@
+use std::cmp::Ordering;
+use std::iter;
+
+use coreutils::format;
+
+/// Exponents beyond this are left to floating point rather than spelled
+/// out digit by digit.
+const MAX_EXPONENT: i64 = 10_000;
+
+/// An exact decimal: an integer of any size scaled down by 10^scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimal {
+    negative: bool,
+    /// ASCII digits, most significant first, without leading zeros; zero
+    /// has none.
+    digits: Vec<u8>,
+    scale: usize,
+}
+
+/// Add the ASCII digit string `addend` to `digits` in place.
+pub fn add_digits(digits: &mut Vec<u8>, addend: &[u8]) {
+    if digits.len() < addend.len() {
+        let grow = addend.len() - digits.len();
+        digits.splice(0..0, iter::repeat_n(b'0', grow));
+    }
+    let offset = digits.len() - addend.len();
+    let mut carry = 0;
+    for idx in (0..digits.len()).rev() {
+        let add = if idx >= offset {
+            addend[idx - offset] - b'0'
+        } else if carry == 0 {
+            break;
+        } else {
+            0
+        };
+        let sum = digits[idx] - b'0' + add + carry;
+        digits[idx] = b'0' + sum % 10;
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        digits.insert(0, b'0' + carry);
+    }
+}
+
+/// Subtract `subtrahend` from the larger or equal `digits` in place.
+fn subtract_digits(digits: &mut Vec<u8>, subtrahend: &[u8]) {
+    let offset = digits.len() - subtrahend.len();
+    let mut borrow = 0;
+    for idx in (0..digits.len()).rev() {
+        let sub = if idx >= offset {
+            subtrahend[idx - offset] - b'0'
+        } else if borrow == 0 {
+            break;
+        } else {
+            0
+        };
+        let mut value = (digits[idx] - b'0') as i8 - sub as i8 - borrow;
+        borrow = 0;
+        if value < 0 {
+            value += 10;
+            borrow = 1;
+        }
+        digits[idx] = b'0' + value as u8;
+    }
+    let zeros = digits.iter().take_while(|&&d| d == b'0').count();
+    digits.drain(..zeros);
+}
+
+pub fn compare_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+impl Decimal {
+    /// Read a plain decimal such as `-12.50` or `1e-3`. Anything else,
+    /// like hex or `inf`, gives None.
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text = text.trim_start();
+        let (negative, body) = match text.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (mantissa, exponent) = match body.find(['e', 'E']) {
+            Some(idx) => (&body[..idx], body[idx + 1..].parse::<i64>().ok()?),
+            None => (body, 0),
+        };
+        if exponent.abs() > MAX_EXPONENT {
+            return None;
+        }
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(idx) => (&mantissa[..idx], &mantissa[idx + 1..]),
+            None => (mantissa, ""),
+        };
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.len() + fraction.len() == 0 || !all_digits(integer) || !all_digits(fraction) {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = integer.bytes().chain(fraction.bytes()).collect();
+        let scale = fraction.len() as i64 - exponent;
+        if scale < 0 {
+            digits.extend(iter::repeat_n(b'0', (-scale) as usize));
+        }
+        let zeros = digits.iter().take_while(|&&d| d == b'0').count();
+        digits.drain(..zeros);
+        Some(Decimal {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale: scale.max(0) as usize,
+        })
+    }
+
+    /// Digits after the decimal point.
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The digits of the absolute value, `0` for zero.
+    pub fn digits(&self) -> &[u8] {
+        if self.digits.is_empty() {
+            b"0"
+        } else {
+            &self.digits
+        }
+    }
+
+    /// Add trailing zeros so there are `scale` digits after the point.
+    pub fn rescale(&mut self, scale: usize) {
+        if scale > self.scale && !self.digits.is_empty() {
+            self.digits.extend(iter::repeat_n(b'0', scale - self.scale));
+        }
+        self.scale = self.scale.max(scale);
+    }
+
+    /// The integer part, rounded toward zero.
+    pub fn truncate(&self) -> Decimal {
+        let keep = self.digits.len().saturating_sub(self.scale);
+        Decimal {
+            negative: self.negative && keep > 0,
+            digits: self.digits[..keep].to_vec(),
+            scale: 0,
+        }
+    }
+
+    /// Add a decimal of the same scale.
+    pub fn add(&mut self, other: &Decimal) {
+        debug_assert_eq!(self.scale, other.scale);
+        if self.negative == other.negative || self.is_zero() {
+            self.negative = other.negative;
+            add_digits(&mut self.digits, &other.digits);
+        } else if compare_digits(&self.digits, &other.digits) == Ordering::Less {
+            let mut digits = other.digits.clone();
+            subtract_digits(&mut digits, &self.digits);
+            self.digits = digits;
+            self.negative = other.negative;
+        } else {
+            subtract_digits(&mut self.digits, &other.digits);
+            self.negative = self.negative && !self.digits.is_empty();
+        }
+    }
+
+    /// Compare with a decimal of the same scale.
+    pub fn compare(&self, other: &Decimal) -> Ordering {
+        debug_assert_eq!(self.scale, other.scale);
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_digits(&self.digits, &other.digits),
+            (true, true) => compare_digits(&other.digits, &self.digits),
+        }
+    }
+
+    /// Render with `precision` digits after the point, dropping the rest
+    /// of the scale, which must be zeros, or padding with zeros.
+    pub fn to_text(&self, precision: usize) -> String {
+        let mut digits = self.digits.clone();
+        if digits.len() <= self.scale {
+            let pad = self.scale + 1 - digits.len();
+            digits.splice(0..0, iter::repeat_n(b'0', pad));
+        }
+        let point = digits.len() - self.scale;
+        let mut text = String::with_capacity(digits.len() + 2);
+        if self.negative {
+            text.push('-');
+        }
+        text.push_str(std::str::from_utf8(&digits[..point]).expect("ASCII digits"));
+        if precision > 0 {
+            text.push('.');
+            let fraction = &digits[point..point + precision.min(self.scale)];
+            text.push_str(std::str::from_utf8(fraction).expect("ASCII digits"));
+            text.extend(iter::repeat_n('0', precision.saturating_sub(self.scale)));
+        }
+        text
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_text(self.scale).parse().expect("decimal text")
+    }
+}
+
+/// A seq operand: an exact decimal where possible, otherwise a float
+/// such as infinity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Exact(Decimal),
+    Float(f64),
+}
+
+impl Number {
+    pub fn parse(text: &str) -> Result<Number, String> {
+        if let Some(decimal) = Decimal::parse(text) {
+            return Ok(Number::Exact(decimal));
+        }
+
+        let invalid = || format!("invalid floating point argument: '{}'", text);
+        if text.starts_with(['\'', '"']) {
+            return Err(invalid());
+        }
+        let (value, warning) = format::parse_float(text);
+        if warning.is_some() {
+            return Err(invalid());
+        }
+        if value.is_nan() {
+            return Err(format!("invalid 'not-a-number' argument: '{}'", text));
+        }
+        // Hex and huge exponents come through a double, which then prints
+        // as an exact decimal.
+        match Decimal::parse(&value.to_string()) {
+            Some(decimal) if value.is_finite() => Ok(Number::Exact(decimal)),
+            _ => Ok(Number::Float(value)),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(decimal) => decimal.is_zero(),
+            Number::Float(value) => *value == 0.0,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(decimal) => decimal.to_f64(),
+            Number::Float(value) => *value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_decimals() {
+        assert_eq!(decimal("12.50").to_text(2), "12.50");
+        assert_eq!(decimal("12.50").scale(), 2);
+        assert_eq!(decimal("-0.5").to_text(1), "-0.5");
+        assert_eq!(decimal("+.5").to_text(1), "0.5");
+        assert_eq!(decimal("7.").to_text(0), "7");
+        assert_eq!(decimal("-0").to_text(0), "0");
+        assert!(!decimal("-0.0").is_negative());
+        assert!(Decimal::parse("1x").is_none());
+        assert!(Decimal::parse(".").is_none());
+        assert!(Decimal::parse("0x10").is_none());
+    }
+
+    #[test]
+    fn exponents() {
+        assert_eq!(decimal("1e2").to_text(0), "100");
+        assert_eq!(decimal("1e2").scale(), 0);
+        assert_eq!(decimal("1.5e1").to_text(0), "15");
+        assert_eq!(decimal("-1e-1").to_text(1), "-0.1");
+        assert_eq!(decimal("25E-3").scale(), 3);
+    }
+
+    #[test]
+    fn huge_integers() {
+        let mut value = decimal("99999999999999999999999999999999");
+        value.add(&decimal("1"));
+        assert_eq!(value.to_text(0), "100000000000000000000000000000000");
+    }
+
+    #[test]
+    fn adds_exactly() {
+        let mut value = decimal("0");
+        value.rescale(1);
+        let step = decimal("0.1");
+        for _ in 0..10 {
+            value.add(&step);
+        }
+        assert_eq!(value.to_text(1), "1.0");
+        assert_eq!(value.compare(&decimal("1.0")), Ordering::Equal);
+    }
+
+    #[test]
+    fn adds_across_zero() {
+        let mut value = decimal("-1.5");
+        let step = decimal("0.5");
+        let expected = ["-1.0", "-0.5", "0.0", "0.5", "1.0"];
+        for text in &expected {
+            value.add(&step);
+            assert_eq!(value.to_text(1), *text);
+        }
+
+        let mut value = decimal("3");
+        value.add(&decimal("-10"));
+        assert_eq!(value.to_text(0), "-7");
+        value.add(&decimal("7"));
+        assert!(value.is_zero());
+        assert!(!value.is_negative());
+    }
+
+    #[test]
+    fn compares_signed() {
+        assert_eq!(decimal("-2").compare(&decimal("1")), Ordering::Less);
+        assert_eq!(decimal("-2").compare(&decimal("-10")), Ordering::Greater);
+        assert_eq!(decimal("10").compare(&decimal("9")), Ordering::Greater);
+    }
+
+    #[test]
+    fn rescales_and_truncates() {
+        let mut value = decimal("1.5");
+        value.rescale(3);
+        assert_eq!(value.to_text(3), "1.500");
+        assert_eq!(value.to_text(1), "1.5");
+        assert_eq!(decimal("-1").to_text(2), "-1.00");
+        assert_eq!(decimal("-10.75").truncate().to_text(0), "-10");
+        assert_eq!(decimal("-0.75").truncate().to_text(0), "0");
+    }
+
+    #[test]
+    fn ascii_addition() {
+        let mut digits = b"999".to_vec();
+        add_digits(&mut digits, b"1");
+        assert_eq!(digits, b"1000");
+        add_digits(&mut digits, b"99999");
+        assert_eq!(digits, b"100999");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Number::parse("0x10").unwrap(), Number::Exact(decimal("16")));
+        assert_eq!(Number::parse("inf").unwrap(), Number::Float(f64::INFINITY));
+        assert_eq!(
+            Number::parse("-inf").unwrap(),
+            Number::Float(f64::NEG_INFINITY)
+        );
+        assert!(Number::parse("nan").unwrap_err().contains("not-a-number"));
+        assert!(Number::parse("1.2.3")
+            .unwrap_err()
+            .contains("invalid floating point argument: '1.2.3'"));
+        assert!(Number::parse("'a").is_err());
+        assert!(Number::parse("-0.0").unwrap().is_zero());
+    }
+}
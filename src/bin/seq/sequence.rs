@@ -0,0 +1,337 @@
+use std::cmp::Ordering;
+use std::io::{self, Write};
+use std::iter;
+
+use coreutils::format::Spec;
+
+use crate::config::{FloatFormat, SeqConfig};
+use crate::number::{self, Decimal, Number};
+
+/// Output is gathered into chunks this large before it is written.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How each number is printed.
+enum Style<'a> {
+    Plain,
+    EqualWidth(usize),
+    Format(&'a FloatFormat),
+}
+
+/// Numbers joined by the separator, with a newline after the last.
+struct Lines<'a, W: Write> {
+    out: W,
+    buffer: Vec<u8>,
+    separator: &'a [u8],
+    started: bool,
+}
+
+impl<'a, W: Write> Lines<'a, W> {
+    fn new(out: W, separator: &'a [u8]) -> Self {
+        Lines {
+            out,
+            buffer: Vec::with_capacity(CHUNK_SIZE + 1024),
+            separator,
+            started: false,
+        }
+    }
+
+    /// The buffer to append the next number to.
+    fn next(&mut self) -> io::Result<&mut Vec<u8>> {
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.out.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        if self.started {
+            self.buffer.extend_from_slice(self.separator);
+        }
+        self.started = true;
+        Ok(&mut self.buffer)
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if self.started {
+            self.buffer.push(b'\n');
+        }
+        self.out.write_all(&self.buffer)
+    }
+}
+
+/// Zero fill after any sign up to `width`.
+fn pad(text: &str, width: usize) -> String {
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text),
+    };
+    let zeros = width.saturating_sub(text.len());
+    format!("{}{}{}", sign, "0".repeat(zeros), digits)
+}
+
+/// Count up from a non-negative integer by a positive integer, adding in
+/// place on the ASCII digits. This is the common case and needs neither
+/// formatting nor allocation per line.
+fn count_up<W: Write>(
+    lines: &mut Lines<W>,
+    first: &Decimal,
+    step: &Decimal,
+    last: Option<&Decimal>,
+    width: usize,
+) -> io::Result<()> {
+    let mut current = first.digits().to_vec();
+    let step = step.digits();
+    let last = last.map(Decimal::digits);
+    while last.is_none_or(|last| number::compare_digits(&current, last) != Ordering::Greater) {
+        let buffer = lines.next()?;
+        buffer.extend(iter::repeat_n(b'0', width.saturating_sub(current.len())));
+        buffer.extend_from_slice(&current);
+        number::add_digits(&mut current, step);
+    }
+    Ok(())
+}
+
+/// Step exactly through decimals sharing one scale.
+fn decimals<W: Write>(
+    lines: &mut Lines<W>,
+    first: &Decimal,
+    step: &Decimal,
+    last: Option<&Decimal>,
+    precision: usize,
+    style: &Style,
+) -> io::Result<()> {
+    let past_end = if step.is_negative() {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    };
+    let mut value = first.clone();
+    while last.is_none_or(|last| value.compare(last) != past_end) {
+        let text = match style {
+            Style::Plain => value.to_text(precision),
+            Style::EqualWidth(width) => pad(&value.to_text(precision), *width),
+            Style::Format(format) => format.render(value.to_f64()),
+        };
+        lines.next()?.extend_from_slice(text.as_bytes());
+        value.add(step);
+    }
+    Ok(())
+}
+
+/// Sequences that start or step by infinity. Each value is computed from
+/// the start so nothing accumulates.
+fn floats<W: Write>(
+    lines: &mut Lines<W>,
+    first: f64,
+    step: f64,
+    last: f64,
+    style: &Style,
+) -> io::Result<()> {
+    let general = Spec {
+        conversion: 'g',
+        ..Spec::default()
+    };
+    for idx in 0u64.. {
+        let value = if idx == 0 {
+            first
+        } else {
+            first + step * idx as f64
+        };
+        if (step > 0.0 && value > last) || (step < 0.0 && value < last) {
+            break;
+        }
+        let text = match style {
+            Style::Format(format) => format.render(value),
+            _ => general.float(value),
+        };
+        lines.next()?.extend_from_slice(text.as_bytes());
+    }
+    Ok(())
+}
+
+/// Write the sequence from `first` to `last`. `step` must not be zero.
+pub fn write<W: Write>(
+    out: W,
+    first: Number,
+    step: Number,
+    last: Number,
+    config: &SeqConfig,
+) -> io::Result<()> {
+    let mut lines = Lines::new(out, config.separator.as_bytes());
+    let plain = match &config.format {
+        Some(format) => Style::Format(format),
+        None => Style::Plain,
+    };
+
+    let (mut first, mut step) = match (first, step) {
+        (Number::Exact(first), Number::Exact(step)) => (first, step),
+        (first, step) => {
+            floats(
+                &mut lines,
+                first.to_f64(),
+                step.to_f64(),
+                last.to_f64(),
+                &plain,
+            )?;
+            return lines.finish();
+        }
+    };
+    let ascending = !step.is_negative();
+    let mut last = match last {
+        Number::Exact(last) => Some(last),
+        // Counting toward an infinite end never stops.
+        Number::Float(end) if (end > 0.0) == ascending => None,
+        Number::Float(_) => return Ok(()),
+    };
+
+    let precision = first.scale().max(step.scale());
+    let width = first.to_text(precision).len().max(
+        last.as_ref()
+            .map_or(0, |last| last.to_text(precision).len()),
+    );
+    let style = match plain {
+        Style::Plain if config.equal_width => Style::EqualWidth(width),
+        style => style,
+    };
+
+    let counting = matches!(style, Style::Plain | Style::EqualWidth(_))
+        && precision == 0
+        && ascending
+        && !first.is_negative()
+        && last.as_ref().is_none_or(|last| !last.is_negative());
+    if counting {
+        let width = if config.equal_width { width } else { 0 };
+        let last = last.map(|last| last.truncate());
+        count_up(
+            &mut lines,
+            &first.truncate(),
+            &step.truncate(),
+            last.as_ref(),
+            width,
+        )?;
+        return lines.finish();
+    }
+
+    let scale = precision.max(last.as_ref().map_or(0, Decimal::scale));
+    first.rescale(scale);
+    step.rescale(scale);
+    if let Some(last) = last.as_mut() {
+        last.rescale(scale);
+    }
+    decimals(&mut lines, &first, &step, last.as_ref(), precision, &style)?;
+    lines.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn seq(args: &[&str]) -> String {
+        let mut all: Vec<String> = string_vec!["seq.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = SeqConfig::new();
+        config.parse(all).unwrap();
+
+        let number = |text: &str| Number::parse(text).unwrap();
+        let mut out = Vec::new();
+        write(
+            &mut out,
+            number(&config.first),
+            number(&config.increment),
+            number(&config.last),
+            &config,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap().replace('\n', " ")
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(seq(&["3"]), "1 2 3 ");
+        assert_eq!(seq(&["8", "11"]), "8 9 10 11 ");
+        assert_eq!(seq(&["1", "4", "12"]), "1 5 9 ");
+        assert_eq!(seq(&["5", "1"]), "");
+        assert_eq!(seq(&["0"]), "");
+        assert_eq!(seq(&["0", "-1"]), "");
+    }
+
+    #[test]
+    fn counting_down() {
+        assert_eq!(seq(&["3", "-1", "1"]), "3 2 1 ");
+        assert_eq!(seq(&["-1", "-2", "-6"]), "-1 -3 -5 ");
+        assert_eq!(seq(&["-2", "2"]), "-2 -1 0 1 2 ");
+    }
+
+    #[test]
+    fn decimals_do_not_drift() {
+        assert_eq!(
+            seq(&["0", "0.1", "1"]),
+            "0.0 0.1 0.2 0.3 0.4 0.5 0.6 0.7 0.8 0.9 1.0 "
+        );
+        assert_eq!(seq(&["1", "0.5", "3"]), "1.0 1.5 2.0 2.5 3.0 ");
+        assert_eq!(seq(&["1.000", "0.1", "1.2"]), "1.000 1.100 1.200 ");
+        assert_eq!(seq(&["0.1", "1.00"]), "0.1 ");
+        assert_eq!(seq(&["1", "10.5"]).split(' ').count(), 11);
+    }
+
+    #[test]
+    fn exponents() {
+        assert_eq!(seq(&["1e2", "1e2", "3e2"]), "100 200 300 ");
+        assert_eq!(seq(&["-1e-1", "0"]), "-0.1 ");
+    }
+
+    #[test]
+    fn huge_integers() {
+        assert_eq!(
+            seq(&["99999999999999999999", "100000000000000000001"]),
+            "99999999999999999999 100000000000000000000 100000000000000000001 "
+        );
+    }
+
+    #[test]
+    fn separator() {
+        assert_eq!(seq(&["-s", ", ", "3"]), "1, 2, 3 ");
+        assert_eq!(seq(&["-s", "", "0.5", "0.5", "1.5"]), "0.51.01.5 ");
+    }
+
+    #[test]
+    fn equal_width() {
+        assert_eq!(seq(&["-w", "9", "11"]), "09 10 11 ");
+        assert_eq!(seq(&["-w", "-10", "3", "1"]), "-10 -07 -04 -01 ");
+        assert_eq!(seq(&["-w", "-1.5", "1", "1"]), "-1.5 -0.5 00.5 ");
+        assert_eq!(
+            seq(&["-w", "0.5", "-0.25", "-1"]),
+            "00.50 00.25 00.00 -0.25 -0.50 -0.75 -1.00 "
+        );
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(seq(&["-f", "%05.1f", "1", "3"]), "001.0 002.0 003.0 ");
+        assert_eq!(seq(&["-f", "%%%g%%", "1", "2"]), "%1% %2% ");
+        assert_eq!(
+            seq(&["-f", "%.2e", "0.1", "0.1", "0.3"]),
+            "1.00e-01 2.00e-01 3.00e-01 "
+        );
+    }
+
+    #[test]
+    fn infinite_bounds() {
+        assert_eq!(seq(&["--", "1", "-inf"]), "");
+        assert_eq!(seq(&["3", "-1", "inf"]), "");
+
+        let mut config = SeqConfig::new();
+        config.parse(string_vec!["seq.exe", "inf"]).unwrap();
+        let mut out = Vec::new();
+        let first = Number::parse("inf").unwrap();
+        let last = Number::parse("1").unwrap();
+        write(&mut out, first, Number::parse("1").unwrap(), last, &config).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn large_output_is_chunked() {
+        let text = seq(&["100000"]);
+        assert!(text.starts_with("1 2 3 "));
+        assert!(text.ends_with(" 99999 100000 "));
+        assert_eq!(text.split(' ').count(), 100_001);
+    }
+}
@@ -0,0 +1,13 @@
+use std::env;
+use std::process;
+
+use coreutils::checksum::{self, ChecksumConfig};
+
+fn main() {
+    let mut config = ChecksumConfig::new("sha1sum");
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+    process::exit(checksum::run(&config));
+}
@@ -0,0 +1,603 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+use coreutils::format;
+
+use crate::config::{Key, KeyOptions, SortConfig};
+
+const MONTHS: [&[u8]; 12] = [
+    b"JAN", b"FEB", b"MAR", b"APR", b"MAY", b"JUN", b"JUL", b"AUG", b"SEP", b"OCT", b"NOV", b"DEC",
+];
+
+/// Characters that separate fields when no `-t` is given.
+fn is_blank(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n')
+}
+
+fn skip_blanks(text: &[u8]) -> &[u8] {
+    let blanks = text.iter().take_while(|&&b| is_blank(b)).count();
+    &text[blanks..]
+}
+
+fn is_nondictionary(byte: u8) -> bool {
+    !byte.is_ascii_alphanumeric() && !is_blank(byte)
+}
+
+fn is_nonprinting(byte: u8) -> bool {
+    !(b' '..=b'~').contains(&byte)
+}
+
+/// Orders lines the way the sort options ask.
+pub struct Comparator {
+    keys: Vec<Key>,
+    separator: Option<u8>,
+    /// Equal keys stay equal instead of falling back to comparing lines.
+    keys_only: bool,
+    reverse: bool,
+    random: RandomState,
+}
+
+impl Comparator {
+    pub fn new(config: &SortConfig) -> Comparator {
+        Comparator {
+            keys: config.effective_keys(),
+            separator: config.separator,
+            keys_only: config.stable || config.unique,
+            reverse: config.global.reverse,
+            random: RandomState::new(),
+        }
+    }
+
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        if !self.keys.is_empty() {
+            let order = self.compare_keys(a, b);
+            if order != Ordering::Equal || self.keys_only {
+                return order;
+            }
+        }
+        let order = a.cmp(b);
+        if self.reverse {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> Ordering {
+        for key in &self.keys {
+            let order = compare_key(self.field(a, key), self.field(b, key), key, &self.random);
+            if order != Ordering::Equal {
+                return if key.options.reverse {
+                    order.reverse()
+                } else {
+                    order
+                };
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// The part of `line` that `key` covers.
+    fn field<'a>(&self, line: &'a [u8], key: &Key) -> &'a [u8] {
+        let start = self.key_start(line, key);
+        let end = self.key_end(line, key).max(start);
+        &line[start..end]
+    }
+
+    /// Skip a field: up to and past the separator, or over leading blanks
+    /// and then the non-blanks. `past_separator` false stops on it.
+    fn skip_field(&self, line: &[u8], mut pos: usize, past_separator: bool) -> usize {
+        match self.separator {
+            Some(separator) => {
+                while pos < line.len() && line[pos] != separator {
+                    pos += 1;
+                }
+                if pos < line.len() && past_separator {
+                    pos += 1;
+                }
+            }
+            None => {
+                while pos < line.len() && is_blank(line[pos]) {
+                    pos += 1;
+                }
+                while pos < line.len() && !is_blank(line[pos]) {
+                    pos += 1;
+                }
+            }
+        }
+        pos
+    }
+
+    fn key_start(&self, line: &[u8], key: &Key) -> usize {
+        let mut pos = 0;
+        for _ in 0..key.start_field {
+            if pos >= line.len() {
+                break;
+            }
+            pos = self.skip_field(line, pos, true);
+        }
+        if key.options.skip_start_blanks {
+            while pos < line.len() && is_blank(line[pos]) {
+                pos += 1;
+            }
+        }
+        line.len().min(pos.saturating_add(key.start_char))
+    }
+
+    fn key_end(&self, line: &[u8], key: &Key) -> usize {
+        let end_field = match key.end_field {
+            Some(field) => field,
+            None => return line.len(),
+        };
+        // Without a character position the key takes all of its last field.
+        let fields = if key.end_char == 0 {
+            end_field.saturating_add(1)
+        } else {
+            end_field
+        };
+        let mut pos = 0;
+        for remaining in (0..fields).rev() {
+            if pos >= line.len() {
+                break;
+            }
+            pos = self.skip_field(line, pos, remaining > 0 || key.end_char > 0);
+        }
+        if key.end_char > 0 {
+            if key.options.skip_end_blanks {
+                while pos < line.len() && is_blank(line[pos]) {
+                    pos += 1;
+                }
+            }
+            pos = line.len().min(pos.saturating_add(key.end_char));
+        }
+        pos
+    }
+}
+
+fn compare_key(a: &[u8], b: &[u8], key: &Key, random: &RandomState) -> Ordering {
+    let options = &key.options;
+    let ignore: Option<fn(u8) -> bool> = if options.dictionary {
+        Some(is_nondictionary)
+    } else if options.ignore_nonprinting {
+        Some(is_nonprinting)
+    } else {
+        None
+    };
+
+    let special = options.numeric
+        || options.general_numeric
+        || options.human_numeric
+        || options.month
+        || options.version
+        || options.random;
+    if !special {
+        return compare_text(a, b, ignore, options.fold_case);
+    }
+
+    let (a, b): (Cow<[u8]>, Cow<[u8]>) = if ignore.is_some() || options.fold_case {
+        (
+            Cow::Owned(kept(a, ignore, options.fold_case).collect()),
+            Cow::Owned(kept(b, ignore, options.fold_case).collect()),
+        )
+    } else {
+        (Cow::Borrowed(a), Cow::Borrowed(b))
+    };
+    compare_special(&a, &b, options, random)
+}
+
+fn compare_special(a: &[u8], b: &[u8], options: &KeyOptions, random: &RandomState) -> Ordering {
+    if options.numeric {
+        compare_numbers(a, b)
+    } else if options.human_numeric {
+        unit_order(a)
+            .cmp(&unit_order(b))
+            .then_with(|| compare_numbers(a, b))
+    } else if options.general_numeric {
+        compare_floats(a, b)
+    } else if options.month {
+        month(a).cmp(&month(b))
+    } else if options.version {
+        compare_versions(a, b)
+    } else {
+        random
+            .hash_one(a)
+            .cmp(&random.hash_one(b))
+            .then_with(|| a.cmp(b))
+    }
+}
+
+/// Compare bytes, skipping those `ignore` matches and folding lower case
+/// to upper when asked.
+fn compare_text(a: &[u8], b: &[u8], ignore: Option<fn(u8) -> bool>, fold: bool) -> Ordering {
+    if ignore.is_none() && !fold {
+        return a.cmp(b);
+    }
+    kept(a, ignore, fold).cmp(kept(b, ignore, fold))
+}
+
+fn kept(text: &[u8], ignore: Option<fn(u8) -> bool>, fold: bool) -> impl Iterator<Item = u8> + '_ {
+    text.iter()
+        .filter(move |&&b| !ignore.is_some_and(|ignore| ignore(b)))
+        .map(move |&b| if fold { b.to_ascii_uppercase() } else { b })
+}
+
+/// A number as `-n` reads it: optional minus, digits without leading
+/// zeros, and fraction digits without trailing zeros, with what follows.
+struct Numeric<'a> {
+    negative: bool,
+    integer: &'a [u8],
+    fraction: &'a [u8],
+    rest: &'a [u8],
+}
+
+impl<'a> Numeric<'a> {
+    fn parse(text: &'a [u8]) -> Numeric<'a> {
+        let text = skip_blanks(text);
+        let (negative, text) = match text.strip_prefix(b"-") {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let digits = |text: &[u8]| text.iter().take_while(|b| b.is_ascii_digit()).count();
+
+        let len = digits(text);
+        let zeros = text[..len].iter().take_while(|&&b| b == b'0').count();
+        let integer = &text[zeros..len];
+        let mut rest = &text[len..];
+        let mut fraction: &[u8] = &[];
+        if let Some(after) = rest.strip_prefix(b".") {
+            let len = digits(after);
+            let zeros = after[..len]
+                .iter()
+                .rev()
+                .take_while(|&&b| b == b'0')
+                .count();
+            fraction = &after[..len - zeros];
+            rest = &after[len..];
+        }
+        Numeric {
+            negative: negative && !(integer.is_empty() && fraction.is_empty()),
+            integer,
+            fraction,
+            rest,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.integer.is_empty() && self.fraction.is_empty()
+    }
+
+    fn magnitude(&self, other: &Numeric) -> Ordering {
+        self.integer
+            .len()
+            .cmp(&other.integer.len())
+            .then_with(|| self.integer.cmp(other.integer))
+            .then_with(|| self.fraction.cmp(other.fraction))
+    }
+}
+
+/// Compare leading decimal numbers exactly, however long. Text that is
+/// not a number counts as zero.
+fn compare_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    let (a, b) = (Numeric::parse(a), Numeric::parse(b));
+    match (a.negative, b.negative) {
+        (false, true) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        (false, false) => a.magnitude(&b),
+        (true, true) => b.magnitude(&a),
+    }
+}
+
+/// The power of the SI suffix after a number, negated for negative
+/// numbers, so `-2M < -1K < 5 < 1K < 2M`.
+fn unit_order(text: &[u8]) -> i32 {
+    let number = Numeric::parse(text);
+    if number.is_zero() {
+        return 0;
+    }
+    let order = match number.rest.first() {
+        Some(b'k') => 1,
+        Some(unit) => b"KMGTPEZYRQ"
+            .iter()
+            .position(|u| u == unit)
+            .map_or(0, |idx| idx as i32 + 1),
+        None => 0,
+    };
+    if number.negative {
+        -order
+    } else {
+        order
+    }
+}
+
+/// Numbers that do not parse sort first, then NaN, then the rest by value.
+fn compare_floats(a: &[u8], b: &[u8]) -> Ordering {
+    let value = |text: &[u8]| {
+        let ascii = text.iter().take_while(|b| b.is_ascii()).count();
+        let text = std::str::from_utf8(&text[..ascii]).expect("ASCII prefix");
+        format::leading_float(
+            text.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '\x0b'),
+        )
+        .map(|(value, _)| value)
+    };
+    match (value(a), value(b)) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => a
+            .partial_cmp(&b)
+            .unwrap_or_else(|| b.is_nan().cmp(&a.is_nan())),
+    }
+}
+
+/// 1 to 12 for a month abbreviation at the start of `text`, else 0.
+fn month(text: &[u8]) -> usize {
+    let text = skip_blanks(text);
+    text.get(..3)
+        .and_then(|name| {
+            MONTHS
+                .iter()
+                .position(|month| month.eq_ignore_ascii_case(name))
+        })
+        .map_or(0, |idx| idx + 1)
+}
+
+/// Weight of a byte in a version: digits lowest, then letters, then
+/// everything else, with the end of the text and `~` below all of them.
+fn version_order(text: &[u8], pos: usize) -> i32 {
+    match text.get(pos) {
+        None => -1,
+        Some(b'~') => -2,
+        Some(byte) if byte.is_ascii_digit() => 0,
+        Some(&byte) if byte.is_ascii_alphabetic() => i32::from(byte),
+        Some(&byte) => i32::from(byte) + 256,
+    }
+}
+
+/// Compare alternating runs of non-digits and digits, the digit runs by
+/// numeric value.
+fn compare_version_runs(a: &[u8], b: &[u8]) -> Ordering {
+    let is_digit = |text: &[u8], pos: usize| text.get(pos).is_some_and(u8::is_ascii_digit);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let order = version_order(a, i).cmp(&version_order(b, j));
+            if order != Ordering::Equal {
+                return order;
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_difference = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_difference == Ordering::Equal {
+                first_difference = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_difference != Ordering::Equal {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Length of `name` without a suffix like `.tar.gz`: a run of dots each
+/// followed by a letter or `~` and then letters, digits or `~`.
+fn prefix_len(name: &[u8]) -> usize {
+    let suffix_char = |b: u8| b.is_ascii_alphanumeric() || b == b'~';
+    let mut prefix = 0;
+    let mut idx = 0;
+    while idx < name.len() {
+        idx += 1;
+        prefix = idx;
+        while idx + 1 < name.len()
+            && name[idx] == b'.'
+            && (name[idx + 1].is_ascii_alphabetic() || name[idx + 1] == b'~')
+        {
+            idx += 2;
+            while idx < name.len() && suffix_char(name[idx]) {
+                idx += 1;
+            }
+        }
+    }
+    prefix
+}
+
+/// Natural ordering of version numbers within names, as `ls -v` and
+/// `sort -V` share it: `.` and `..` first, then hidden names, and file
+/// suffixes only considered when the rest is equal.
+pub fn compare_versions(a: &[u8], b: &[u8]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        _ => {}
+    }
+    for special in [&b"."[..], b".."] {
+        match (a == special, b == special) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            _ => {}
+        }
+    }
+    match (a[0] == b'.', b[0] == b'.') {
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        _ => {}
+    }
+
+    let (a_prefix, b_prefix) = (prefix_len(a), prefix_len(b));
+    let order = compare_version_runs(&a[..a_prefix], &b[..b_prefix]);
+    if order != Ordering::Equal || (a_prefix == a.len() && b_prefix == b.len()) {
+        return order;
+    }
+    compare_version_runs(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn comparator(args: &[&str]) -> Comparator {
+        let mut all: Vec<String> = string_vec!["sort.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = SortConfig::new();
+        config.parse(all).unwrap();
+        Comparator::new(&config)
+    }
+
+    fn sorted(args: &[&str], lines: &[&str]) -> Vec<String> {
+        let comparator = comparator(args);
+        let mut lines: Vec<&str> = lines.to_vec();
+        lines.sort_by(|a, b| comparator.compare(a.as_bytes(), b.as_bytes()));
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn fields(args: &[&str], line: &str) -> String {
+        let comparator = comparator(args);
+        let field = comparator.field(line.as_bytes(), &comparator.keys[0]);
+        String::from_utf8(field.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn plain_bytes() {
+        assert_eq!(
+            sorted(&[], &["b", "B", "a", "", "ab"]),
+            ["", "B", "a", "ab", "b"]
+        );
+        assert_eq!(sorted(&["-r"], &["b", "a", "c"]), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn key_fields() {
+        assert_eq!(fields(&["-k2"], "a  b c"), "  b c");
+        assert_eq!(fields(&["-k2b"], "a  b c"), "b c");
+        assert_eq!(fields(&["-k2,2"], "a  b c"), "  b");
+        assert_eq!(fields(&["-k2.2,2.3"], "a  bcd e"), " b");
+        assert_eq!(fields(&["-k2.2b,2.3b"], "a  bcd e"), "cd");
+        assert_eq!(fields(&["-k1.3,1"], "ab cd"), "");
+        assert_eq!(fields(&["-k3"], "a b"), "");
+        assert_eq!(fields(&["-k2,1"], "a b"), "");
+    }
+
+    #[test]
+    fn separated_fields() {
+        assert_eq!(fields(&["-t:", "-k2,2"], "a::c"), "");
+        assert_eq!(fields(&["-t:", "-k3"], "a::c:d"), "c:d");
+        assert_eq!(fields(&["-t:", "-k2,3"], "a: b:c:d"), " b:c");
+        assert_eq!(fields(&["-t:", "-k2.2,2.3"], "a:bcd:e"), "cd");
+        assert_eq!(fields(&["-t:", "-k2,2.9"], "a:bc:d"), "bc:d");
+    }
+
+    #[test]
+    fn keys_in_order() {
+        let lines = ["b 2", "a 2", "c 1"];
+        assert_eq!(sorted(&["-k2,2", "-k1,1"], &lines), ["c 1", "a 2", "b 2"]);
+        assert_eq!(sorted(&["-k2,2r", "-k1,1"], &lines), ["a 2", "b 2", "c 1"]);
+    }
+
+    #[test]
+    fn last_resort() {
+        let lines = ["x b", "y a", "x a"];
+        assert_eq!(sorted(&["-k1,1"], &lines), ["x a", "x b", "y a"]);
+        assert_eq!(sorted(&["-s", "-k1,1"], &lines), ["x b", "x a", "y a"]);
+        assert_eq!(sorted(&["-r", "-k1,1"], &lines), ["y a", "x b", "x a"]);
+    }
+
+    #[test]
+    fn ignore_and_fold() {
+        assert_eq!(sorted(&["-f"], &["b", "A", "a", "B"]), ["A", "a", "B", "b"]);
+        assert_eq!(sorted(&["-d"], &["a-c", "ab"]), ["ab", "a-c"]);
+        assert_eq!(sorted(&["-i"], &["a\x01c", "ab"]), ["ab", "a\x01c"]);
+        assert_eq!(sorted(&["-s", "-f"], &["b", "B", "a"]), ["a", "b", "B"]);
+    }
+
+    #[test]
+    fn numeric() {
+        assert_eq!(
+            sorted(
+                &["-n"],
+                &["10", "-5", "9.5", "abc", "-0", "0.05", " 3", "-10.1"]
+            ),
+            ["-10.1", "-5", "-0", "abc", "0.05", " 3", "9.5", "10"]
+        );
+        assert_eq!(
+            compare_numbers(
+                b"123456789012345678901234567890",
+                b"123456789012345678901234567891"
+            ),
+            Ordering::Less
+        );
+        assert_eq!(compare_numbers(b"1.50", b"1.5"), Ordering::Equal);
+        assert_eq!(compare_numbers(b"+1", b"0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn human_numeric() {
+        assert_eq!(
+            sorted(&["-h"], &["1G", "2K", "-1M", "1000", "3M", "0K", "-2K"]),
+            ["-1M", "-2K", "0K", "1000", "2K", "3M", "1G"]
+        );
+    }
+
+    #[test]
+    fn general_numeric() {
+        assert_eq!(
+            sorted(&["-g"], &["1e3", "nan", "x", "-inf", "2.5", "0x10"]),
+            ["x", "nan", "-inf", "2.5", "0x10", "1e3"]
+        );
+    }
+
+    #[test]
+    fn months() {
+        assert_eq!(
+            sorted(&["-M"], &["dec", " Feb", "xyz", "JANUARY"]),
+            ["xyz", "JANUARY", " Feb", "dec"]
+        );
+    }
+
+    #[test]
+    fn versions() {
+        let order = [
+            "", ".", "..", ".hidden", "1.2", "1.2.3", "1.10", "a", "a1", "a2", "a10", "a10b", "b~",
+            "b", "b.tar.gz",
+        ];
+        for pair in order.windows(2) {
+            assert_eq!(
+                compare_versions(pair[0].as_bytes(), pair[1].as_bytes()),
+                Ordering::Less,
+                "{:?}",
+                pair
+            );
+        }
+        assert_eq!(compare_versions(b"a01", b"a1"), Ordering::Equal);
+        assert_eq!(sorted(&["-V"], &["a01", "a1"]), ["a01", "a1"]);
+    }
+
+    #[test]
+    fn random_groups_equal_keys() {
+        let lines = ["b", "a", "c", "a", "b", "A"];
+        let shuffled = sorted(&["-R", "-f"], &lines);
+        let mut runs = shuffled.clone();
+        runs.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(runs.len(), 3);
+        assert_eq!(shuffled.len(), 6);
+    }
+}
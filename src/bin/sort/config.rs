@@ -0,0 +1,682 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::size;
+
+/// Ordering options, given globally or per key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyOptions {
+    pub skip_start_blanks: bool,
+    pub skip_end_blanks: bool,
+    pub dictionary: bool,
+    pub fold_case: bool,
+    pub ignore_nonprinting: bool,
+    pub numeric: bool,
+    pub general_numeric: bool,
+    pub human_numeric: bool,
+    pub month: bool,
+    pub random: bool,
+    pub version: bool,
+    pub reverse: bool,
+}
+
+impl KeyOptions {
+    /// Apply one ordering letter. `b` means the blanks at whichever end
+    /// of the key the letter follows.
+    fn set(&mut self, letter: char, end: bool) -> bool {
+        match letter {
+            'b' if end => self.skip_end_blanks = true,
+            'b' => self.skip_start_blanks = true,
+            'd' => self.dictionary = true,
+            'f' => self.fold_case = true,
+            'g' => self.general_numeric = true,
+            'h' => self.human_numeric = true,
+            'i' => self.ignore_nonprinting = true,
+            'M' => self.month = true,
+            'n' => self.numeric = true,
+            'R' => self.random = true,
+            'r' => self.reverse = true,
+            'V' => self.version = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// The options as the letters that would set them.
+    fn letters(&self) -> String {
+        let flags = [
+            (self.skip_start_blanks || self.skip_end_blanks, 'b'),
+            (self.dictionary, 'd'),
+            (self.fold_case, 'f'),
+            (self.general_numeric, 'g'),
+            (self.ignore_nonprinting, 'i'),
+            (self.month, 'M'),
+            (self.human_numeric, 'h'),
+            (self.numeric, 'n'),
+            (self.random, 'R'),
+            (self.reverse, 'r'),
+            (self.version, 'V'),
+        ];
+        flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, letter)| letter)
+            .collect()
+    }
+
+    /// At most one way of reading a key as a number, month or version,
+    /// and none of them with characters ignored.
+    fn check_compatible(&self) -> Result<(), String> {
+        let orderings = [
+            self.numeric,
+            self.general_numeric,
+            self.human_numeric,
+            self.month,
+            self.random,
+            self.version,
+            self.dictionary || self.ignore_nonprinting,
+        ];
+        if orderings.iter().filter(|&&set| set).count() > 1 {
+            return Err(format!("options '-{}' are incompatible", self.letters()));
+        }
+        Ok(())
+    }
+}
+
+/// A `-k` sort key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key {
+    /// Zero-based field and character the key starts at.
+    pub start_field: usize,
+    pub start_char: usize,
+    /// Zero-based field the key ends in; None runs to the end of the line.
+    pub end_field: Option<usize>,
+    /// Characters of the end field in the key; 0 means all of them.
+    pub end_char: usize,
+    pub options: KeyOptions,
+}
+
+impl Key {
+    /// The key covering whole lines, used when no `-k` is given.
+    pub fn whole_line(options: KeyOptions) -> Key {
+        Key {
+            start_field: 0,
+            start_char: 0,
+            end_field: None,
+            end_char: 0,
+            options,
+        }
+    }
+
+    /// Parse `F[.C][OPTS][,F[.C][OPTS]]`.
+    pub fn parse(spec: &str) -> Result<Key, String> {
+        let invalid =
+            |message: &str| format!("{}: invalid field specification '{}'", message, spec);
+        let number = |text: &str, message: &str| -> Result<(usize, usize), String> {
+            let len = text.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return Err(invalid(message));
+            }
+            // Counts too large for memory behave like the largest count.
+            Ok((text[..len].parse().unwrap_or(usize::MAX), len))
+        };
+
+        let mut key = Key::whole_line(KeyOptions::default());
+        let (field, used) = number(spec, "invalid number at field start")?;
+        if field == 0 {
+            return Err(invalid("field number is zero"));
+        }
+        key.start_field = field - 1;
+        let mut rest = &spec[used..];
+        if let Some(after) = rest.strip_prefix('.') {
+            let (chr, used) = number(after, "invalid number after '.'")?;
+            if chr == 0 {
+                return Err(invalid("character offset is zero"));
+            }
+            key.start_char = chr - 1;
+            rest = &after[used..];
+        }
+        rest = rest.trim_start_matches(|c| key.options.set(c, false));
+
+        if let Some(after) = rest.strip_prefix(',') {
+            let (field, used) = number(after, "invalid number after ','")?;
+            if field == 0 {
+                return Err(invalid("field number is zero"));
+            }
+            key.end_field = Some(field - 1);
+            rest = &after[used..];
+            if let Some(after) = rest.strip_prefix('.') {
+                let (chr, used) = number(after, "invalid number after '.'")?;
+                key.end_char = chr;
+                rest = &after[used..];
+            }
+            rest = rest.trim_start_matches(|c| key.options.set(c, true));
+        }
+
+        if !rest.is_empty() {
+            return Err(invalid("stray character in field spec"));
+        }
+        Ok(key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Check {
+    /// Report the first line out of order.
+    Diagnose,
+    Quiet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BufferSize {
+    Bytes(u64),
+    /// A percentage of physical memory.
+    Percent(u64),
+}
+
+#[derive(Debug)]
+pub struct SortConfig {
+    pub files: Vec<String>,
+    /// Keys in order of precedence, with global options filled in.
+    pub keys: Vec<Key>,
+    pub global: KeyOptions,
+    pub separator: Option<u8>,
+    pub unique: bool,
+    pub stable: bool,
+    pub check: Option<Check>,
+    pub merge: bool,
+    pub output: Option<String>,
+    pub zero_terminated: bool,
+    pub temp_dirs: Vec<String>,
+    pub buffer_size: Option<BufferSize>,
+    pub parallel: Option<usize>,
+}
+
+const HELP_TEXT: &str = "Usage: sort [OPTION]... [FILE]...
+Write sorted concatenation of all FILE(s) to standard output.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -b, --ignore-leading-blanks  ignore leading blanks
+  -d, --dictionary-order      consider only blanks and alphanumeric characters
+  -f, --ignore-case           fold lower case to upper case characters
+  -g, --general-numeric-sort  compare according to general numerical value
+  -i, --ignore-nonprinting    consider only printable characters
+  -M, --month-sort            compare (unknown) < 'JAN' < ... < 'DEC'
+  -h, --human-numeric-sort    compare human readable numbers (e.g., 2K 1G)
+  -n, --numeric-sort          compare according to string numerical value
+  -R, --random-sort           shuffle, but group identical keys
+  -r, --reverse               reverse the result of comparisons
+      --sort=WORD             sort according to WORD:
+                                general-numeric -g, human-numeric -h, month -M,
+                                numeric -n, random -R, version -V
+  -V, --version-sort          natural sort of (version) numbers within text
+  -c, --check[=diagnose-first]  check for sorted input; do not sort
+  -C                          like -c, but do not report first bad line;
+                                same as --check=quiet or --check=silent
+  -k, --key=KEYDEF            sort via a key; KEYDEF gives location and type
+  -m, --merge                 merge already sorted files; do not sort
+  -o, --output=FILE           write result to FILE instead of standard output
+  -s, --stable                stabilize sort by disabling last-resort comparison
+  -S, --buffer-size=SIZE      use SIZE for main memory buffer
+  -t, --field-separator=SEP   use SEP instead of non-blank to blank transition
+  -T, --temporary-directory=DIR  use DIR for temporaries, not $TMPDIR or the
+                                system default; multiple options specify
+                                multiple directories
+      --parallel=N            change the number of sorts run concurrently to N
+  -u, --unique                with -c, check for strict ordering;
+                                without -c, output only the first of an equal run
+  -z, --zero-terminated       line delimiter is NUL, not newline
+      --no-glob               do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+KEYDEF is F[.C][OPTS][,F[.C][OPTS]] for start and stop position, where F is a
+field number and C a character position in the field; both are origin 1, and
+the stop position defaults to the line's end.  If neither -t nor -b is in
+effect, characters in a field are counted from the beginning of the preceding
+whitespace.  OPTS is one or more single-letter ordering options [bdfgiMhnRrV],
+which override global ordering options for that key.  If no key is given, use
+the entire line as the key.
+
+SIZE may be followed by the following multiplicative suffixes:
+% 1% of memory, b 1, K 1024 (default), and so on for M, G, T, P, E.
+
+Comparisons are byte by byte, as in the C locale, and a carriage return
+before the newline is not part of the line.  Input larger than the buffer
+is sorted in runs written to temporary files and merged.
+Exit status is 0 on success, 1 if -c finds disorder, and 2 on trouble.
+";
+
+impl Config for SortConfig {
+    fn bin_name(&self) -> &'static str {
+        "sort"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+fn parse_buffer_size(text: &str) -> Result<BufferSize, String> {
+    let invalid = |what: &str| format!("invalid {} in -S argument '{}'", what, text);
+    if let Some(number) = text.strip_suffix('%') {
+        return number
+            .parse()
+            .map(BufferSize::Percent)
+            .map_err(|_| invalid("number"));
+    }
+    if let Some(number) = text.strip_suffix('b') {
+        return number
+            .parse()
+            .map(BufferSize::Bytes)
+            .map_err(|_| invalid("number"));
+    }
+    match text.chars().last() {
+        None => Err(invalid("number")),
+        Some(last) if last.is_ascii_digit() => text
+            .parse::<u64>()
+            .map(|kib| BufferSize::Bytes(kib.saturating_mul(1024)))
+            .map_err(|_| invalid("number")),
+        Some(_) => size::parse_size(text)
+            .map(BufferSize::Bytes)
+            .map_err(|_| invalid("suffix")),
+    }
+}
+
+fn parse_separator(text: &str) -> Result<u8, String> {
+    match text.as_bytes() {
+        [] => Err(String::from("empty tab")),
+        [byte] => Ok(*byte),
+        b"\\0" => Ok(0),
+        _ => Err(format!("multi-character tab '{}'", text)),
+    }
+}
+
+impl SortConfig {
+    pub fn new() -> SortConfig {
+        SortConfig {
+            files: Vec::new(),
+            keys: Vec::new(),
+            global: KeyOptions::default(),
+            separator: None,
+            unique: false,
+            stable: false,
+            check: None,
+            merge: false,
+            output: None,
+            zero_terminated: false,
+            temp_dirs: Vec::new(),
+            buffer_size: None,
+            parallel: None,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            let letter = option
+                .strip_prefix('-')
+                .filter(|flag| flag.len() == 1)
+                .and_then(|flag| flag.chars().next());
+            if let Some(letter) = letter {
+                if self.global.set(letter, false) {
+                    if letter == 'b' {
+                        self.global.skip_end_blanks = true;
+                    }
+                    continue;
+                }
+            }
+
+            match config::split_option(&option) {
+                ("--ignore-leading-blanks", None) => {
+                    self.global.skip_start_blanks = true;
+                    self.global.skip_end_blanks = true;
+                }
+                ("--dictionary-order", None) => self.global.dictionary = true,
+                ("--ignore-case", None) => self.global.fold_case = true,
+                ("--general-numeric-sort", None) => self.global.general_numeric = true,
+                ("--ignore-nonprinting", None) => self.global.ignore_nonprinting = true,
+                ("--month-sort", None) => self.global.month = true,
+                ("--human-numeric-sort", None) => self.global.human_numeric = true,
+                ("--numeric-sort", None) => self.global.numeric = true,
+                ("--random-sort", None) => self.global.random = true,
+                ("--reverse", None) => self.global.reverse = true,
+                ("--version-sort", None) => self.global.version = true,
+                ("--sort", Some(word)) => {
+                    let letter = match word {
+                        "general-numeric" => 'g',
+                        "human-numeric" => 'h',
+                        "month" => 'M',
+                        "numeric" => 'n',
+                        "random" => 'R',
+                        "version" => 'V',
+                        _ => return Err(format!("invalid argument '{}' for '--sort'", word)),
+                    };
+                    self.global.set(letter, false);
+                }
+                ("--check", None) | ("-c", None) => self.check = Some(Check::Diagnose),
+                ("--check", Some("diagnose-first")) => self.check = Some(Check::Diagnose),
+                ("--check", Some("quiet")) | ("--check", Some("silent")) | ("-C", None) => {
+                    self.check = Some(Check::Quiet)
+                }
+                ("--check", Some(value)) => {
+                    return Err(format!(
+                        "invalid argument '{}' for '--check'\nValid arguments are:\n  - 'quiet', 'silent'\n  - 'diagnose-first'",
+                        value
+                    ))
+                }
+                ("--key", Some(spec)) | ("-k", Some(spec)) => self.keys.push(Key::parse(spec)?),
+                ("--merge", None) | ("-m", None) => self.merge = true,
+                ("--output", Some(file)) | ("-o", Some(file)) => {
+                    if self.output.as_deref().is_some_and(|output| output != file) {
+                        return Err(String::from("multiple output files specified"));
+                    }
+                    self.output = Some(file.to_string());
+                }
+                ("--stable", None) | ("-s", None) => self.stable = true,
+                ("--buffer-size", Some(size)) | ("-S", Some(size)) => {
+                    self.buffer_size = Some(parse_buffer_size(size)?)
+                }
+                ("--field-separator", Some(tab)) | ("-t", Some(tab)) => {
+                    let tab = parse_separator(tab)?;
+                    if self.separator.is_some_and(|separator| separator != tab) {
+                        return Err(String::from("incompatible tabs"));
+                    }
+                    self.separator = Some(tab);
+                }
+                ("--temporary-directory", Some(dir)) | ("-T", Some(dir)) => {
+                    self.temp_dirs.push(dir.to_string())
+                }
+                ("--parallel", Some(count)) => {
+                    let count: usize = count
+                        .parse()
+                        .map_err(|_| format!("invalid number after '--parallel': '{}'", count))?;
+                    if count == 0 {
+                        return Err(String::from("number in parallel must be nonzero"));
+                    }
+                    self.parallel = Some(count);
+                }
+                ("--unique", None) | ("-u", None) => self.unique = true,
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                (name, None) if matches!(name, "-k" | "-o" | "-S" | "-t" | "-T") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try sort --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        self.global.check_compatible()?;
+        for key in &mut self.keys {
+            // A key without ordering options of its own takes the global ones.
+            if key.options == KeyOptions::default() {
+                key.options = self.global.clone();
+            }
+            key.options.check_compatible()?;
+        }
+
+        if self.check.is_some() && self.files.len() > 1 {
+            return Err(format!(
+                "extra operand '{}' not allowed with -c",
+                self.files[1]
+            ));
+        }
+        Ok(())
+    }
+
+    /// The keys to compare by, in order. Without `-k`, global ordering
+    /// options other than `-r` make the whole line a key; with none at all
+    /// lines are compared as plain bytes.
+    pub fn effective_keys(&self) -> Vec<Key> {
+        let plain = KeyOptions {
+            reverse: self.global.reverse,
+            ..KeyOptions::default()
+        };
+        if !self.keys.is_empty() {
+            self.keys.clone()
+        } else if self.global != plain {
+            vec![Key::whole_line(self.global.clone())]
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{BufferSize, Check, Key, KeyOptions, SortConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<SortConfig, String> {
+        let mut config = SortConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["sort.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert!(config.keys.is_empty());
+        assert_eq!(config.global, KeyOptions::default());
+        assert_eq!(config.separator, None);
+        assert!(!config.unique && !config.stable && !config.merge);
+        assert_eq!(config.check, None);
+        assert_eq!(config.output, None);
+        assert_eq!(config.delimiter(), b'\n');
+        assert!(config.effective_keys().is_empty());
+    }
+
+    #[test]
+    fn ordering_flags() {
+        let config = parse(string_vec!["sort.exe", "-bfr", "--dictionary-order"]).unwrap();
+        assert!(config.global.skip_start_blanks && config.global.skip_end_blanks);
+        assert!(config.global.fold_case);
+        assert!(config.global.reverse);
+        assert!(config.global.dictionary);
+
+        for (flag, long) in &[
+            ("-n", "--numeric-sort"),
+            ("-g", "--general-numeric-sort"),
+            ("-h", "--human-numeric-sort"),
+            ("-M", "--month-sort"),
+            ("-R", "--random-sort"),
+            ("-V", "--version-sort"),
+            ("-i", "--ignore-nonprinting"),
+        ] {
+            let short = parse(string_vec!["sort.exe", flag]).unwrap();
+            let long = parse(string_vec!["sort.exe", long]).unwrap();
+            assert_ne!(short.global, KeyOptions::default());
+            assert_eq!(short.global, long.global);
+        }
+    }
+
+    #[test]
+    fn sort_word() {
+        let config = parse(string_vec!["sort.exe", "--sort=human-numeric"]).unwrap();
+        assert!(config.global.human_numeric);
+        assert!(parse(string_vec!["sort.exe", "--sort=fast"]).is_err());
+    }
+
+    #[test]
+    fn incompatible_options() {
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-ng"]).unwrap_err(),
+            "options '-gn' are incompatible"
+        );
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-dn"]).unwrap_err(),
+            "options '-dn' are incompatible"
+        );
+        assert!(parse(string_vec!["sort.exe", "-k1,1Mn"]).is_err());
+        assert!(parse(string_vec!["sort.exe", "-fR"]).is_ok());
+    }
+
+    #[test]
+    fn keys() {
+        let key = Key::parse("2").unwrap();
+        assert_eq!((key.start_field, key.start_char), (1, 0));
+        assert_eq!(key.end_field, None);
+
+        let key = Key::parse("2.3,4.5").unwrap();
+        assert_eq!((key.start_field, key.start_char), (1, 2));
+        assert_eq!((key.end_field, key.end_char), (Some(3), 5));
+
+        let key = Key::parse("1.2bn,3.0rb").unwrap();
+        assert!(key.options.skip_start_blanks && key.options.skip_end_blanks);
+        assert!(key.options.numeric && key.options.reverse);
+        assert_eq!((key.end_field, key.end_char), (Some(2), 0));
+
+        let key = Key::parse("3b,3").unwrap();
+        assert!(key.options.skip_start_blanks && !key.options.skip_end_blanks);
+    }
+
+    #[test]
+    fn invalid_keys() {
+        let cases = [
+            ("0", "field number is zero: invalid field specification '0'"),
+            ("1.0", "character offset is zero"),
+            ("1,0", "field number is zero"),
+            ("1x", "stray character in field spec"),
+            ("1,1bz", "stray character"),
+            ("x", "invalid number at field start"),
+            ("1.", "invalid number after '.'"),
+            ("1,", "invalid number after ','"),
+        ];
+        for (spec, message) in &cases {
+            assert!(Key::parse(spec).unwrap_err().contains(message), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn keys_inherit_global_options() {
+        let config = parse(string_vec!["sort.exe", "-nr", "-k2", "-k3,3f"]).unwrap();
+        assert!(config.keys[0].options.numeric && config.keys[0].options.reverse);
+        assert!(config.keys[1].options.fold_case);
+        assert!(!config.keys[1].options.numeric);
+        assert_eq!(config.effective_keys(), config.keys);
+
+        let config = parse(string_vec!["sort.exe", "-r"]).unwrap();
+        assert!(config.effective_keys().is_empty());
+        let config = parse(string_vec!["sort.exe", "-f"]).unwrap();
+        assert_eq!(config.effective_keys().len(), 1);
+    }
+
+    #[test]
+    fn separator() {
+        let config = parse(string_vec!["sort.exe", "-t", ":"]).unwrap();
+        assert_eq!(config.separator, Some(b':'));
+        let config = parse(string_vec!["sort.exe", "-t", "\\0"]).unwrap();
+        assert_eq!(config.separator, Some(0));
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-t", "ab"]).unwrap_err(),
+            "multi-character tab 'ab'"
+        );
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-t", ""]).unwrap_err(),
+            "empty tab"
+        );
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-t", "x", "-t", "y"]).unwrap_err(),
+            "incompatible tabs"
+        );
+    }
+
+    #[test]
+    fn check() {
+        let config = parse(string_vec!["sort.exe", "-c"]).unwrap();
+        assert_eq!(config.check, Some(Check::Diagnose));
+        let config = parse(string_vec!["sort.exe", "-C"]).unwrap();
+        assert_eq!(config.check, Some(Check::Quiet));
+        let config = parse(string_vec!["sort.exe", "--check=silent"]).unwrap();
+        assert_eq!(config.check, Some(Check::Quiet));
+        assert!(parse(string_vec!["sort.exe", "--check=bad"])
+            .unwrap_err()
+            .contains("Valid arguments are"));
+        assert_eq!(
+            parse(string_vec!["sort.exe", "-c", "a", "b"]).unwrap_err(),
+            "extra operand 'b' not allowed with -c"
+        );
+    }
+
+    #[test]
+    fn buffer_size() {
+        let size = |arg: &str| {
+            parse(string_vec!["sort.exe", "-S", arg]).map(|config| config.buffer_size.unwrap())
+        };
+        assert_eq!(size("10").unwrap(), BufferSize::Bytes(10 * 1024));
+        assert_eq!(size("100b").unwrap(), BufferSize::Bytes(100));
+        assert_eq!(size("2M").unwrap(), BufferSize::Bytes(2 * 1024 * 1024));
+        assert_eq!(size("25%").unwrap(), BufferSize::Percent(25));
+        assert_eq!(
+            size("10x").unwrap_err(),
+            "invalid suffix in -S argument '10x'"
+        );
+    }
+
+    #[test]
+    fn output_merge_and_temp() {
+        let config = parse(string_vec![
+            "sort.exe", "-m", "-o", "out", "-T", "a", "-T", "b", "-s", "-u", "-z"
+        ])
+        .unwrap();
+        assert!(config.merge && config.stable && config.unique && config.zero_terminated);
+        assert_eq!(config.output.as_deref(), Some("out"));
+        assert_eq!(config.temp_dirs, vec!["a", "b"]);
+        assert_eq!(config.delimiter(), b'\0');
+        assert!(parse(string_vec!["sort.exe", "-o", "a", "-o", "b"]).is_err());
+    }
+
+    #[test]
+    fn parallel() {
+        let config = parse(string_vec!["sort.exe", "--parallel=3"]).unwrap();
+        assert_eq!(config.parallel, Some(3));
+        assert_eq!(
+            parse(string_vec!["sort.exe", "--parallel=0"]).unwrap_err(),
+            "number in parallel must be nonzero"
+        );
+        assert!(parse(string_vec!["sort.exe", "--parallel=x"]).is_err());
+    }
+
+    #[test]
+    fn invalid_option() {
+        assert!(parse(string_vec!["sort.exe", "--bogus"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = SortConfig::new();
+        let parse_result = config.parse(string_vec!["sort.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = SortConfig::new();
+        let parse_result = config.parse(string_vec!["sort.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
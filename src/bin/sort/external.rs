@@ -0,0 +1,596 @@
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+use coreutils::input;
+use coreutils::lines::{self, LineReader};
+use coreutils::output::{self, Output};
+
+use crate::compare::Comparator;
+
+/// Most runs merged at once; more take several passes, as in GNU sort.
+const MERGE_WAYS: usize = 16;
+
+/// Chunks with fewer lines are not worth splitting across threads.
+const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+/// Smallest `-S` honoured, so tiny buffers cannot make a run per line.
+pub const MIN_BUFFER_SIZE: usize = 16 * 1024;
+
+/// A line held in a chunk: where it starts, where its text ends and
+/// where its terminator ends.
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    start: usize,
+    content: usize,
+    end: usize,
+}
+
+/// Lines read into one buffer, sorted in place by reordering the index.
+struct Chunk {
+    data: Vec<u8>,
+    lines: Vec<Line>,
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            data: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    fn memory(&self) -> usize {
+        self.data.len() + self.lines.len() * mem::size_of::<Line>()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+        self.lines.clear();
+    }
+
+    /// Append the next line of `reader`. Returns false at its end.
+    fn read_line(&mut self, reader: &mut dyn BufRead, delimiter: u8) -> io::Result<bool> {
+        let start = self.data.len();
+        if reader.read_until(delimiter, &mut self.data)? == 0 {
+            return Ok(false);
+        }
+        self.lines.push(Line {
+            start,
+            content: start + lines::content_len(&self.data[start..], delimiter),
+            end: self.data.len(),
+        });
+        Ok(true)
+    }
+
+    fn sort(&mut self, comparator: &Comparator, threads: usize) {
+        let Chunk { data, lines } = self;
+        let data = &*data;
+        let compare = |a: &Line, b: &Line| {
+            comparator.compare(&data[a.start..a.content], &data[b.start..b.content])
+        };
+        if threads <= 1 || lines.len() < PARALLEL_THRESHOLD {
+            lines.sort_by(compare);
+            return;
+        }
+
+        // Sort a slice per thread, then merge neighbouring slices pairwise
+        // so equal lines keep their input order.
+        let size = lines.len().div_ceil(threads);
+        thread::scope(|scope| {
+            for part in lines.chunks_mut(size) {
+                scope.spawn(move || part.sort_by(compare));
+            }
+        });
+        let mut runs: Vec<Vec<Line>> = lines.chunks(size).map(<[Line]>::to_vec).collect();
+        while runs.len() > 1 {
+            runs = thread::scope(|scope| {
+                let merges: Vec<_> = runs
+                    .chunks(2)
+                    .map(|pair| scope.spawn(move || merge_sorted(pair, compare)))
+                    .collect();
+                merges
+                    .into_iter()
+                    .map(|merge| merge.join().expect("sort thread panicked"))
+                    .collect()
+            });
+        }
+        *lines = runs.pop().unwrap_or_default();
+    }
+
+    fn write(&self, out: &mut LineWriter) -> Result<(), String> {
+        for line in &self.lines {
+            out.write(
+                &self.data[line.start..line.content],
+                &self.data[line.content..line.end],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Merge one or two sorted runs, taking from the first on ties.
+fn merge_sorted<F>(runs: &[Vec<Line>], compare: F) -> Vec<Line>
+where
+    F: Fn(&Line, &Line) -> Ordering,
+{
+    let (a, b) = match runs {
+        [a, b] => (a, b),
+        [a] => return a.clone(),
+        _ => return Vec::new(),
+    };
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if compare(&b[j], &a[i]) == Ordering::Less {
+            merged.push(b[j]);
+            j += 1;
+        } else {
+            merged.push(a[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&a[i..]);
+    merged.extend_from_slice(&b[j..]);
+    merged
+}
+
+/// Writes lines with a terminator each, dropping repeats for `-u`.
+pub struct LineWriter<'a> {
+    out: Box<dyn Write>,
+    name: String,
+    delimiter: u8,
+    unique: Option<&'a Comparator>,
+    last: Option<Vec<u8>>,
+}
+
+impl<'a> LineWriter<'a> {
+    /// A writer for `-o FILE`, or standard output.
+    pub fn open(output: Option<&str>, delimiter: u8) -> Result<LineWriter<'a>, String> {
+        let (out, name): (Box<dyn Write>, String) = match output {
+            Some(name) => {
+                let file = File::create(name)
+                    .map_err(|e| format!("open failed: {}: {}", name, output::describe(&e)))?;
+                (
+                    Box::new(BufWriter::with_capacity(input::BUFFER_SIZE, file)),
+                    name.to_string(),
+                )
+            }
+            None => (
+                Box::new(Output::stdout("sort")),
+                String::from("standard output"),
+            ),
+        };
+        Ok(LineWriter::new(out, name, delimiter))
+    }
+
+    fn new(out: Box<dyn Write>, name: String, delimiter: u8) -> LineWriter<'a> {
+        LineWriter {
+            out,
+            name,
+            delimiter,
+            unique: None,
+            last: None,
+        }
+    }
+
+    /// Only write the first of each run of lines `comparator` finds equal.
+    pub fn unique(mut self, comparator: Option<&'a Comparator>) -> Self {
+        self.unique = comparator;
+        self
+    }
+
+    fn failed(&self, err: &io::Error) -> String {
+        format!("write failed: {}: {}", self.name, output::describe(err))
+    }
+
+    fn write(&mut self, content: &[u8], terminator: &[u8]) -> Result<(), String> {
+        if let Some(comparator) = self.unique {
+            match &mut self.last {
+                Some(last) if comparator.compare(last, content) == Ordering::Equal => {
+                    return Ok(());
+                }
+                Some(last) => {
+                    last.clear();
+                    last.extend_from_slice(content);
+                }
+                None => self.last = Some(content.to_vec()),
+            }
+        }
+        let written = if terminator.is_empty() {
+            self.out
+                .write_all(content)
+                .and_then(|_| self.out.write_all(&[self.delimiter]))
+        } else {
+            self.out
+                .write_all(content)
+                .and_then(|_| self.out.write_all(terminator))
+        };
+        written.map_err(|e| self.failed(&e))
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        self.out.flush().map_err(|e| self.failed(&e))
+    }
+}
+
+/// A sorted run spilled to disk, deleted when dropped.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// One input of a merge and its current line.
+struct Source {
+    name: String,
+    lines: LineReader<Box<dyn BufRead>>,
+}
+
+impl Source {
+    fn open(name: &str, delimiter: u8) -> Result<Option<Source>, String> {
+        let reader = input::open(name).map_err(|e| cannot_read(name, &e))?;
+        let mut source = Source {
+            name: name.to_string(),
+            lines: LineReader::new(reader, delimiter),
+        };
+        Ok(if source.advance()? {
+            Some(source)
+        } else {
+            None
+        })
+    }
+
+    fn advance(&mut self) -> Result<bool, String> {
+        self.lines
+            .next_line()
+            .map_err(|e| cannot_read(&self.name, &e))
+    }
+}
+
+pub fn cannot_read(name: &str, err: &io::Error) -> String {
+    format!("cannot read: {}: {}", name, output::describe(err))
+}
+
+pub struct Sorter<'a> {
+    comparator: &'a Comparator,
+    delimiter: u8,
+    unique: bool,
+    buffer_size: usize,
+    threads: usize,
+    temp_dirs: Vec<PathBuf>,
+    temp_count: usize,
+}
+
+impl<'a> Sorter<'a> {
+    pub fn new(
+        comparator: &'a Comparator,
+        delimiter: u8,
+        unique: bool,
+        buffer_size: usize,
+        threads: usize,
+        temp_dirs: Vec<PathBuf>,
+    ) -> Sorter<'a> {
+        Sorter {
+            comparator,
+            delimiter,
+            unique,
+            buffer_size: buffer_size.max(MIN_BUFFER_SIZE),
+            threads: threads.max(1),
+            temp_dirs,
+            temp_count: 0,
+        }
+    }
+
+    fn writer(&self, out: LineWriter<'a>) -> LineWriter<'a> {
+        out.unique(Some(self.comparator).filter(|_| self.unique))
+    }
+
+    /// Create an empty temporary file, taking the directories in turn.
+    fn temp_file(&mut self) -> Result<(TempFile, File), String> {
+        let dir = &self.temp_dirs[self.temp_count % self.temp_dirs.len()];
+        loop {
+            self.temp_count += 1;
+            let path = dir.join(format!("sort{}-{}", process::id(), self.temp_count));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((TempFile { path }, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(format!(
+                        "cannot create temporary file in '{}': {}",
+                        dir.display(),
+                        output::describe(&e)
+                    ))
+                }
+            }
+        }
+    }
+
+    fn temp_writer(&mut self) -> Result<(TempFile, LineWriter<'a>), String> {
+        let (temp, file) = self.temp_file()?;
+        let name = temp.path.display().to_string();
+        let out = LineWriter::new(
+            Box::new(BufWriter::with_capacity(input::BUFFER_SIZE, file)),
+            name,
+            self.delimiter,
+        );
+        Ok((temp, self.writer(out)))
+    }
+
+    /// Sort a full chunk and write it out as a run.
+    fn spill(&mut self, chunk: &mut Chunk) -> Result<TempFile, String> {
+        chunk.sort(self.comparator, self.threads);
+        let (temp, mut out) = self.temp_writer()?;
+        chunk.write(&mut out)?;
+        out.finish()?;
+        chunk.clear();
+        Ok(temp)
+    }
+
+    /// Sort the lines of all `files`. Output is only opened once every
+    /// input has been read, so it may be one of them.
+    pub fn sort<F>(&mut self, files: &[String], open: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<LineWriter<'a>, String>,
+    {
+        let mut chunk = Chunk::new();
+        let mut runs = Vec::new();
+        for name in files {
+            let mut reader = input::open(name).map_err(|e| cannot_read(name, &e))?;
+            while chunk
+                .read_line(&mut reader, self.delimiter)
+                .map_err(|e| cannot_read(name, &e))?
+            {
+                if chunk.memory() >= self.buffer_size {
+                    runs.push(self.spill(&mut chunk)?);
+                }
+            }
+        }
+
+        if runs.is_empty() {
+            chunk.sort(self.comparator, self.threads);
+            let mut out = self.writer(open()?);
+            chunk.write(&mut out)?;
+            return out.finish();
+        }
+        if !chunk.is_empty() {
+            runs.push(self.spill(&mut chunk)?);
+        }
+        drop(chunk);
+
+        // Merge in passes until one pass can take every run.
+        while runs.len() > MERGE_WAYS {
+            let mut merged = Vec::new();
+            let mut pending = runs.into_iter().peekable();
+            while pending.peek().is_some() {
+                let group: Vec<TempFile> = pending.by_ref().take(MERGE_WAYS).collect();
+                let (temp, out) = self.temp_writer()?;
+                self.merge_runs(&group, out)?;
+                merged.push(temp);
+            }
+            runs = merged;
+        }
+        let out = self.writer(open()?);
+        self.merge_runs(&runs, out)
+    }
+
+    fn merge_runs(&self, runs: &[TempFile], out: LineWriter) -> Result<(), String> {
+        let names: Vec<String> = runs
+            .iter()
+            .map(|run| run.path.display().to_string())
+            .collect();
+        self.merge_files(&names, out)
+    }
+
+    /// Merge already sorted `files`. On equal lines the earlier file's
+    /// comes first.
+    pub fn merge_files(&self, files: &[String], mut out: LineWriter) -> Result<(), String> {
+        let mut sources = Vec::with_capacity(files.len());
+        for name in files {
+            sources.extend(Source::open(name, self.delimiter)?);
+        }
+
+        while !sources.is_empty() {
+            let mut least = 0;
+            for idx in 1..sources.len() {
+                let order = self
+                    .comparator
+                    .compare(sources[idx].lines.line(), sources[least].lines.line());
+                if order == Ordering::Less {
+                    least = idx;
+                }
+            }
+            let source = &mut sources[least];
+            out.write(source.lines.line(), source.lines.terminator())?;
+            if !source.advance()? {
+                sources.remove(least);
+            }
+        }
+        out.finish()
+    }
+
+    /// Merge `files` that may number more than can be open at once.
+    /// Inputs that are also the output are copied aside first.
+    pub fn merge<F>(
+        &mut self,
+        files: &[String],
+        output: Option<&str>,
+        open: F,
+    ) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<LineWriter<'a>, String>,
+    {
+        let output = output.and_then(|name| fs::canonicalize(name).ok());
+        let mut copies = Vec::new();
+        let mut names = Vec::with_capacity(files.len());
+        for name in files {
+            let is_output =
+                name != input::STDIN && output.is_some() && fs::canonicalize(name).ok() == output;
+            if !is_output {
+                names.push(name.clone());
+                continue;
+            }
+            let (temp, mut file) = self.temp_file()?;
+            let mut reader = input::open(name).map_err(|e| cannot_read(name, &e))?;
+            io::copy(&mut reader, &mut file).map_err(|e| cannot_read(name, &e))?;
+            names.push(temp.path.display().to_string());
+            copies.push(temp);
+        }
+
+        // Merge the first files into one while there are too many, so
+        // earlier inputs still win ties.
+        while names.len() > MERGE_WAYS {
+            let group: Vec<String> = names.drain(..MERGE_WAYS).collect();
+            let (temp, out) = self.temp_writer()?;
+            self.merge_files(&group, out)?;
+            names.insert(0, temp.path.display().to_string());
+            copies.push(temp);
+        }
+        let out = self.writer(open()?);
+        self.merge_files(&names, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SortConfig;
+    use coreutils::string_vec;
+    use std::sync::{Arc, Mutex};
+
+    /// A writer tests can read back after the LineWriter owning it is gone.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn config(args: &[&str]) -> SortConfig {
+        let mut all: Vec<String> = string_vec!["sort.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = SortConfig::new();
+        config.parse(all).unwrap();
+        config
+    }
+
+    /// Sort `text` written to a file with a tiny buffer, so it spills.
+    fn sort_file(args: &[&str], text: &str, threads: usize) -> (String, usize) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input");
+        fs::write(&path, text).unwrap();
+
+        let config = config(args);
+        let comparator = Comparator::new(&config);
+        let temp = tempfile::tempdir().unwrap();
+        let mut sorter = Sorter::new(
+            &comparator,
+            config.delimiter(),
+            config.unique,
+            0,
+            threads,
+            vec![temp.path().to_path_buf()],
+        );
+        let shared = Shared::default();
+        let out = shared.clone();
+        sorter
+            .sort(&[path.display().to_string()], || {
+                Ok(LineWriter::new(Box::new(out), String::from("test"), b'\n'))
+            })
+            .unwrap();
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+        let text = String::from_utf8(shared.0.lock().unwrap().clone()).unwrap();
+        (text, sorter.temp_count)
+    }
+
+    fn numbers(count: usize) -> String {
+        // A fixed shuffle of 0..count with every value appearing twice.
+        (0..count * 2)
+            .map(|idx| format!("{}\n", (idx * 7919) % count))
+            .collect()
+    }
+
+    #[test]
+    fn external_sort() {
+        let text = numbers(20_000);
+        let (sorted, runs) = sort_file(&["-n"], &text, 1);
+        assert!(runs > MERGE_WAYS, "only {} runs", runs);
+        let mut expected: Vec<usize> = text.lines().map(|l| l.parse().unwrap()).collect();
+        expected.sort_unstable();
+        let got: Vec<usize> = sorted.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn external_unique() {
+        let (sorted, _) = sort_file(&["-nu"], &numbers(20_000), 1);
+        let got: Vec<usize> = sorted.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(got, (0..20_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let mut chunk = Chunk::new();
+        let text: String = (0..50_000)
+            .map(|idx| format!("{} {}\n", (idx * 31) % 97, idx))
+            .collect();
+        let mut reader = io::Cursor::new(text.as_bytes());
+        while chunk.read_line(&mut reader, b'\n').unwrap() {}
+        let config = config(&["-s", "-k1,1n"]);
+        let comparator = Comparator::new(&config);
+
+        let mut serial = Chunk {
+            data: chunk.data.clone(),
+            lines: chunk.lines.clone(),
+        };
+        serial.sort(&comparator, 1);
+        chunk.sort(&comparator, 5);
+        let starts = |chunk: &Chunk| chunk.lines.iter().map(|l| l.start).collect::<Vec<_>>();
+        assert_eq!(starts(&chunk), starts(&serial));
+    }
+
+    #[test]
+    fn terminators() {
+        let (sorted, _) = sort_file(&[], "b\r\na\nc", 1);
+        assert_eq!(sorted, "a\nb\r\nc\n");
+    }
+
+    #[test]
+    fn merge_prefers_earlier_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut names = Vec::new();
+        for (idx, text) in ["a 1\nc 1\n", "a 2\nb 2\n", "a 3\nd 3"].iter().enumerate() {
+            let path = dir.path().join(idx.to_string());
+            fs::write(&path, text).unwrap();
+            names.push(path.display().to_string());
+        }
+        let config = config(&["-k1,1"]);
+        let comparator = Comparator::new(&config);
+        let sorter = Sorter::new(&comparator, b'\n', false, 0, 1, Vec::new());
+        let shared = Shared::default();
+        let out = LineWriter::new(Box::new(shared.clone()), String::from("test"), b'\n');
+        sorter.merge_files(&names, out).unwrap();
+        assert_eq!(
+            String::from_utf8(shared.0.lock().unwrap().clone()).unwrap(),
+            "a 1\na 2\na 3\nb 2\nc 1\nd 3\n"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+use coreutils::input;
+use coreutils::lines::LineReader;
+
+mod compare;
+mod config;
+mod external;
+
+use compare::Comparator;
+use config::{BufferSize, Check, SortConfig};
+use external::{LineWriter, Sorter};
+
+const DISORDER: i32 = 1;
+const FAILURE: i32 = 2;
+
+/// Worker threads used when `--parallel` is not given.
+const MAX_DEFAULT_THREADS: usize = 8;
+
+/// Buffer used when `-S` is not given and memory cannot be measured.
+const FALLBACK_BUFFER_SIZE: u64 = 128 * 1024 * 1024;
+
+#[cfg(unix)]
+fn physical_memory() -> Option<u64> {
+    let (pages, page_size) = unsafe {
+        (
+            libc::sysconf(libc::_SC_PHYS_PAGES),
+            libc::sysconf(libc::_SC_PAGESIZE),
+        )
+    };
+    if pages > 0 && page_size > 0 {
+        Some(pages as u64 * page_size as u64)
+    } else {
+        None
+    }
+}
+
+#[cfg(windows)]
+fn physical_memory() -> Option<u64> {
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+    if unsafe { GlobalMemoryStatusEx(&mut status) } != 0 {
+        Some(status.ullTotalPhys)
+    } else {
+        None
+    }
+}
+
+/// Bytes of input to hold in memory before sorting it into a run on
+/// disk: `-S`, or an eighth of physical memory.
+fn buffer_size(config: &SortConfig) -> usize {
+    let bytes = match config.buffer_size {
+        Some(BufferSize::Bytes(bytes)) => bytes,
+        Some(BufferSize::Percent(percent)) => {
+            physical_memory().map_or(FALLBACK_BUFFER_SIZE, |memory| memory / 100 * percent)
+        }
+        None => physical_memory().map_or(FALLBACK_BUFFER_SIZE, |memory| memory / 8),
+    };
+    bytes.min(usize::MAX as u64) as usize
+}
+
+/// Whether the single input is in order, reporting the first line that
+/// is not unless checking quietly.
+fn check(config: &SortConfig, comparator: &Comparator) -> Result<bool, String> {
+    let name = &config.files[0];
+    let reader = input::open(name).map_err(|e| external::cannot_read(name, &e))?;
+    let mut lines = LineReader::new(reader, config.delimiter());
+    let mut previous = Vec::new();
+    let mut number: u64 = 0;
+    while lines
+        .next_line()
+        .map_err(|e| external::cannot_read(name, &e))?
+    {
+        number += 1;
+        if number > 1 {
+            let order = comparator.compare(&previous, lines.line());
+            if order == Ordering::Greater || (config.unique && order == Ordering::Equal) {
+                if config.check == Some(Check::Diagnose) {
+                    eprintln!(
+                        "sort: {}:{}: disorder: {}",
+                        name,
+                        number,
+                        String::from_utf8_lossy(lines.line())
+                    );
+                }
+                return Ok(false);
+            }
+        }
+        previous.clear();
+        previous.extend_from_slice(lines.line());
+    }
+    Ok(true)
+}
+
+fn main() {
+    let mut config = SortConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(match coreutils::config::report(&config, &args, &e) {
+            0 => 0,
+            _ => FAILURE,
+        });
+    }
+    let comparator = Comparator::new(&config);
+
+    if config.check.is_some() {
+        match check(&config, &comparator) {
+            Ok(true) => return,
+            Ok(false) => process::exit(DISORDER),
+            Err(e) => {
+                eprintln!("sort: {}", e);
+                process::exit(FAILURE);
+            }
+        }
+    }
+
+    let temp_dirs = if config.temp_dirs.is_empty() {
+        vec![env::temp_dir()]
+    } else {
+        config.temp_dirs.iter().map(PathBuf::from).collect()
+    };
+    let threads = config.parallel.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(MAX_DEFAULT_THREADS)
+    });
+    let mut sorter = Sorter::new(
+        &comparator,
+        config.delimiter(),
+        config.unique,
+        buffer_size(&config),
+        threads,
+        temp_dirs,
+    );
+
+    let output = config.output.as_deref();
+    let open = || LineWriter::open(output, config.delimiter());
+    let result = if config.merge {
+        sorter.merge(&config.files, output, open)
+    } else {
+        sorter.sort(&config.files, open)
+    };
+    if let Err(e) = result {
+        eprintln!("sort: {}", e);
+        process::exit(FAILURE);
+    }
+}
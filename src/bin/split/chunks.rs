@@ -0,0 +1,479 @@
+use std::io::{self, BufRead, Read, Write};
+
+/// Where the pieces of the input go, one after another.
+pub trait Pieces {
+    type Piece: Write;
+
+    /// Start the next piece.
+    fn open(&mut self) -> io::Result<Self::Piece>;
+
+    /// Finish a piece once all of it has been written.
+    fn close(&mut self, piece: Self::Piece) -> io::Result<()>;
+}
+
+/// Copy the input into pieces of `size` units each. `take` says how
+/// much of a buffer fits in the units left, counting them off. Pieces
+/// are opened only once there is something to put in them.
+fn fixed<R, P, F>(mut input: R, size: u64, pieces: &mut P, take: F) -> io::Result<()>
+where
+    R: BufRead,
+    P: Pieces,
+    F: Fn(&[u8], &mut u64) -> usize,
+{
+    let mut current = None;
+    let mut left = 0;
+    loop {
+        let buffer = input.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        if current.is_none() {
+            current = Some(pieces.open()?);
+            left = size;
+        }
+        let len = take(buffer, &mut left);
+        if let Some(piece) = current.as_mut() {
+            piece.write_all(&buffer[..len])?;
+        }
+        input.consume(len);
+        if left == 0 {
+            if let Some(piece) = current.take() {
+                pieces.close(piece)?;
+            }
+        }
+    }
+    match current {
+        Some(piece) => pieces.close(piece),
+        None => Ok(()),
+    }
+}
+
+/// `-b`: pieces of `size` bytes.
+pub fn by_bytes<R: BufRead, P: Pieces>(input: R, size: u64, pieces: &mut P) -> io::Result<()> {
+    fixed(input, size, pieces, |buffer, left| {
+        let len = (*left).min(buffer.len() as u64);
+        *left -= len;
+        len as usize
+    })
+}
+
+/// `-l`: pieces of `lines` records.
+pub fn by_lines<R: BufRead, P: Pieces>(
+    input: R,
+    lines: u64,
+    separator: u8,
+    pieces: &mut P,
+) -> io::Result<()> {
+    fixed(input, lines, pieces, |buffer, left| {
+        for (idx, _) in buffer
+            .iter()
+            .enumerate()
+            .filter(|&(_, &byte)| byte == separator)
+        {
+            *left -= 1;
+            if *left == 0 {
+                return idx + 1;
+            }
+        }
+        buffer.len()
+    })
+}
+
+/// `-C`: whole records up to `size` bytes a piece. A record that does
+/// not fit starts a new piece; one longer than `size` is broken.
+pub fn by_line_bytes<R: BufRead, P: Pieces>(
+    mut input: R,
+    size: u64,
+    separator: u8,
+    pieces: &mut P,
+) -> io::Result<()> {
+    let mut current = None;
+    let mut used = 0;
+    let mut record = Vec::new();
+    loop {
+        record.clear();
+        if input.read_until(separator, &mut record)? == 0 {
+            break;
+        }
+        if used + record.len() as u64 > size {
+            if let Some(piece) = current.take() {
+                pieces.close(piece)?;
+            }
+        }
+        let mut rest = &record[..];
+        while !rest.is_empty() {
+            let piece = match current.as_mut() {
+                Some(piece) => piece,
+                None => {
+                    used = 0;
+                    current.insert(pieces.open()?)
+                }
+            };
+            let len = (size - used).min(rest.len() as u64) as usize;
+            piece.write_all(&rest[..len])?;
+            used += len as u64;
+            rest = &rest[len..];
+            if used == size {
+                if let Some(piece) = current.take() {
+                    pieces.close(piece)?;
+                }
+            }
+        }
+    }
+    match current {
+        Some(piece) => pieces.close(piece),
+        None => Ok(()),
+    }
+}
+
+/// Bytes in each of `count` chunks of `size` bytes, and where chunk
+/// `number` (from 0) starts. The last chunk takes the remainder; input
+/// smaller than `count` bytes goes a byte a chunk.
+fn chunk_span(size: u64, count: u64, number: u64) -> (u64, u64) {
+    let chunk = (size / count).max(1);
+    let start = chunk.saturating_mul(number).min(size);
+    if number + 1 == count {
+        (start, size - start)
+    } else {
+        (start, chunk.min(size - start))
+    }
+}
+
+/// The chunk (from 0) a record starting at `offset` belongs to.
+fn chunk_of(offset: u64, size: u64, count: u64) -> u64 {
+    (offset / (size / count).max(1)).min(count - 1)
+}
+
+/// Close the current chunk, creating it first if nothing went into it
+/// unless empty chunks are elided.
+fn end_chunk<P: Pieces>(
+    current: &mut Option<P::Piece>,
+    elide: bool,
+    pieces: &mut P,
+) -> io::Result<()> {
+    let piece = match current.take() {
+        Some(piece) => piece,
+        None if elide => return Ok(()),
+        None => pieces.open()?,
+    };
+    pieces.close(piece)
+}
+
+/// `-n N`: `count` pieces of equal size from input of `size` bytes.
+pub fn chunk_bytes<R: Read, P: Pieces>(
+    mut input: R,
+    size: u64,
+    count: u64,
+    elide: bool,
+    pieces: &mut P,
+) -> io::Result<()> {
+    for number in 0..count {
+        let (_, len) = chunk_span(size, count, number);
+        if len == 0 && elide {
+            continue;
+        }
+        let mut piece = pieces.open()?;
+        io::copy(&mut input.by_ref().take(len), &mut piece)?;
+        pieces.close(piece)?;
+    }
+    Ok(())
+}
+
+/// `-n K/N`: chunk `number` (from 1) of `count` to `out`.
+pub fn extract_bytes<R: Read, W: Write>(
+    mut input: R,
+    size: u64,
+    count: u64,
+    number: u64,
+    out: &mut W,
+) -> io::Result<()> {
+    let (start, len) = chunk_span(size, count, number - 1);
+    io::copy(&mut input.by_ref().take(start), &mut io::sink())?;
+    io::copy(&mut input.take(len), out)?;
+    Ok(())
+}
+
+/// `-n l/N`: `count` pieces, each record in the one its first byte
+/// would be in with `-n N`.
+pub fn chunk_lines<R: BufRead, P: Pieces>(
+    mut input: R,
+    size: u64,
+    count: u64,
+    separator: u8,
+    elide: bool,
+    pieces: &mut P,
+) -> io::Result<()> {
+    let mut current = None;
+    let mut number = 0;
+    let mut offset = 0;
+    let mut record = Vec::new();
+    loop {
+        record.clear();
+        if input.read_until(separator, &mut record)? == 0 {
+            break;
+        }
+        while number < chunk_of(offset, size, count) {
+            end_chunk(&mut current, elide, pieces)?;
+            number += 1;
+        }
+        let piece = match current.as_mut() {
+            Some(piece) => piece,
+            None => current.insert(pieces.open()?),
+        };
+        piece.write_all(&record)?;
+        offset += record.len() as u64;
+    }
+    while number < count {
+        end_chunk(&mut current, elide, pieces)?;
+        number += 1;
+    }
+    Ok(())
+}
+
+/// `-n l/K/N`: the records of chunk `number` (from 1) of `count` to
+/// `out`.
+pub fn extract_lines<R: BufRead, W: Write>(
+    mut input: R,
+    size: u64,
+    count: u64,
+    separator: u8,
+    number: u64,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut offset = 0;
+    let mut record = Vec::new();
+    loop {
+        record.clear();
+        if input.read_until(separator, &mut record)? == 0 {
+            return Ok(());
+        }
+        let chunk = chunk_of(offset, size, count);
+        if chunk + 1 == number {
+            out.write_all(&record)?;
+        } else if chunk >= number {
+            return Ok(());
+        }
+        offset += record.len() as u64;
+    }
+}
+
+/// `-n r/N`: records dealt out to `count` pieces in turn. Every piece
+/// is open until the end, and created up front unless empty ones are
+/// elided.
+pub fn round_robin<R: BufRead, P: Pieces>(
+    mut input: R,
+    count: u64,
+    separator: u8,
+    elide: bool,
+    unbuffered: bool,
+    pieces: &mut P,
+) -> io::Result<()> {
+    let mut open: Vec<Option<P::Piece>> = (0..count).map(|_| None).collect();
+    if !elide {
+        for slot in open.iter_mut() {
+            *slot = Some(pieces.open()?);
+        }
+    }
+    let mut record = Vec::new();
+    let mut next = 0;
+    loop {
+        record.clear();
+        if input.read_until(separator, &mut record)? == 0 {
+            break;
+        }
+        let piece = match open[next].as_mut() {
+            Some(piece) => piece,
+            None => open[next].insert(pieces.open()?),
+        };
+        piece.write_all(&record)?;
+        if unbuffered {
+            piece.flush()?;
+        }
+        next = (next + 1) % open.len();
+    }
+    for piece in open.into_iter().flatten() {
+        pieces.close(piece)?;
+    }
+    Ok(())
+}
+
+/// `-n r/K/N`: every `count`th record, starting with record `number`
+/// (from 1), to `out`.
+pub fn extract_round_robin<R: BufRead, W: Write>(
+    mut input: R,
+    count: u64,
+    separator: u8,
+    number: u64,
+    unbuffered: bool,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut record = Vec::new();
+    let mut index = 0;
+    loop {
+        record.clear();
+        if input.read_until(separator, &mut record)? == 0 {
+            return Ok(());
+        }
+        if index % count + 1 == number {
+            out.write_all(&record)?;
+            if unbuffered {
+                out.flush()?;
+            }
+        }
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pieces kept in memory, in the order they were finished.
+    impl Pieces for Vec<Vec<u8>> {
+        type Piece = Vec<u8>;
+
+        fn open(&mut self) -> io::Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn close(&mut self, piece: Vec<u8>) -> io::Result<()> {
+            self.push(piece);
+            Ok(())
+        }
+    }
+
+    fn strings(pieces: Vec<Vec<u8>>) -> Vec<String> {
+        pieces
+            .into_iter()
+            .map(|piece| String::from_utf8(piece).unwrap())
+            .collect()
+    }
+
+    fn split<F>(split: F) -> Vec<String>
+    where
+        F: FnOnce(&mut Vec<Vec<u8>>) -> io::Result<()>,
+    {
+        let mut pieces = Vec::new();
+        split(&mut pieces).unwrap();
+        strings(pieces)
+    }
+
+    fn extract<F>(extract: F) -> String
+    where
+        F: FnOnce(&mut Vec<u8>) -> io::Result<()>,
+    {
+        let mut out = Vec::new();
+        extract(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn bytes() {
+        let input = &b"abcdefg"[..];
+        assert_eq!(
+            split(|pieces| by_bytes(input, 3, pieces)),
+            ["abc", "def", "g"]
+        );
+        assert_eq!(split(|pieces| by_bytes(input, 7, pieces)), ["abcdefg"]);
+        assert!(split(|pieces| by_bytes(&b""[..], 7, pieces)).is_empty());
+    }
+
+    #[test]
+    fn lines() {
+        let input = &b"1\n2\n3\n4\n5"[..];
+        assert_eq!(
+            split(|pieces| by_lines(input, 2, b'\n', pieces)),
+            ["1\n2\n", "3\n4\n", "5"]
+        );
+        assert_eq!(
+            split(|pieces| by_lines(&b"1\n2\n"[..], 1, b'\n', pieces)),
+            ["1\n", "2\n"]
+        );
+        assert_eq!(
+            split(|pieces| by_lines(&b"a\0b\0c"[..], 2, b'\0', pieces)),
+            ["a\0b\0", "c"]
+        );
+    }
+
+    #[test]
+    fn line_bytes() {
+        let input = &b"abcdefghijkl\nab\nabc\nabcd\na\n"[..];
+        assert_eq!(
+            split(|pieces| by_line_bytes(input, 5, b'\n', pieces)),
+            ["abcde", "fghij", "kl\n", "ab\n", "abc\n", "abcd\n", "a\n"]
+        );
+        assert_eq!(
+            split(|pieces| by_line_bytes(&b"abcdefg\na\n"[..], 5, b'\n', pieces)),
+            ["abcde", "fg\na\n"]
+        );
+    }
+
+    #[test]
+    fn byte_chunks() {
+        let input = &b"0123456789"[..];
+        assert_eq!(
+            split(|pieces| chunk_bytes(input, 10, 3, false, pieces)),
+            ["012", "345", "6789"]
+        );
+        assert_eq!(
+            split(|pieces| chunk_bytes(&b"abc"[..], 3, 5, false, pieces)),
+            ["a", "b", "c", "", ""]
+        );
+        assert_eq!(
+            split(|pieces| chunk_bytes(&b"abc"[..], 3, 5, true, pieces)),
+            ["a", "b", "c"]
+        );
+        assert_eq!(extract(|out| extract_bytes(input, 10, 3, 2, out)), "345");
+        assert_eq!(extract(|out| extract_bytes(input, 10, 3, 3, out)), "6789");
+    }
+
+    #[test]
+    fn line_chunks() {
+        let input = &b"aaaaaaaaaaaaaaaaaaaaa\nb\nc\nd\n"[..];
+        assert_eq!(
+            split(|pieces| chunk_lines(input, 28, 3, b'\n', false, pieces)),
+            ["aaaaaaaaaaaaaaaaaaaaa\n", "", "b\nc\nd\n"]
+        );
+        assert_eq!(
+            split(|pieces| chunk_lines(input, 28, 3, b'\n', true, pieces)),
+            ["aaaaaaaaaaaaaaaaaaaaa\n", "b\nc\nd\n"]
+        );
+        assert_eq!(
+            split(|pieces| chunk_lines(&b"aaaa\nb\nc\nd\n"[..], 11, 3, b'\n', false, pieces)),
+            ["aaaa\n", "b\n", "c\nd\n"]
+        );
+        assert_eq!(
+            extract(|out| extract_lines(&b"aaaa\nb\nc\nd\n"[..], 11, 3, b'\n', 3, out)),
+            "c\nd\n"
+        );
+        assert_eq!(
+            extract(|out| extract_lines(input, 28, 3, b'\n', 2, out)),
+            ""
+        );
+        assert_eq!(
+            split(|pieces| chunk_lines(&b"a\nb\nc\n"[..], 6, 5, b'\n', false, pieces)),
+            ["a\n", "", "b\n", "", "c\n"]
+        );
+    }
+
+    #[test]
+    fn dealt_out() {
+        let input = &b"1\n2\n3\n4\n5\n"[..];
+        assert_eq!(
+            split(|pieces| round_robin(input, 2, b'\n', false, false, pieces)),
+            ["1\n3\n5\n", "2\n4\n"]
+        );
+        assert_eq!(
+            split(|pieces| round_robin(&b"1\n"[..], 3, b'\n', false, false, pieces)),
+            ["1\n", "", ""]
+        );
+        assert_eq!(
+            split(|pieces| round_robin(&b"1\n"[..], 3, b'\n', true, false, pieces)),
+            ["1\n"]
+        );
+        assert_eq!(
+            extract(|out| extract_round_robin(input, 2, b'\n', 2, false, out)),
+            "2\n4\n"
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use std::mem;
+
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::size;
+
+/// How `-n` shares the input between its chunks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// Equal runs of bytes, the last taking what is left over.
+    Bytes,
+    /// Records go to the chunk of bytes they start in.
+    Lines,
+    /// Records are dealt out in turn.
+    RoundRobin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Lines(u64),
+    Bytes(u64),
+    /// `-C`: as many whole records as fit in the size, breaking only
+    /// records longer than that.
+    LineBytes(u64),
+    /// `-n`: `count` chunks, or just the one numbered `only` (from 1)
+    /// written to standard output.
+    Chunks {
+        distribution: Distribution,
+        count: u64,
+        only: Option<u64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuffixKind {
+    Alphabetic,
+    Numeric,
+    Hex,
+}
+
+impl SuffixKind {
+    pub fn alphabet(self) -> &'static [u8] {
+        match self {
+            SuffixKind::Alphabetic => b"abcdefghijklmnopqrstuvwxyz",
+            SuffixKind::Numeric => b"0123456789",
+            SuffixKind::Hex => b"0123456789abcdef",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SplitConfig {
+    pub file: String,
+    pub prefix: String,
+    pub mode: Mode,
+    pub suffix_kind: SuffixKind,
+    /// `-a`; without it suffixes get longer as they run out.
+    pub suffix_length: Option<usize>,
+    /// `--numeric-suffixes=FROM` or `--hex-suffixes=FROM`.
+    pub suffix_start: Option<u64>,
+    pub additional_suffix: String,
+    pub elide_empty: bool,
+    pub filter: Option<String>,
+    pub separator: u8,
+    pub unbuffered: bool,
+    pub verbose: bool,
+}
+
+const HELP_TEXT: &str = "Usage: split [OPTION]... [FILE [PREFIX]]
+Output pieces of FILE to PREFIXaa, PREFIXab, ...;
+default size is 1000 lines, and default PREFIX is 'x'.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -a, --suffix-length=N   generate suffixes of length N (default 2)
+      --additional-suffix=SUFFIX  append an additional SUFFIX to file names
+  -b, --bytes=SIZE        put SIZE bytes per output file
+  -C, --line-bytes=SIZE   put at most SIZE bytes of records per output file
+  -d                      use numeric suffixes starting at 0, not alphabetic
+      --numeric-suffixes[=FROM]  same as -d, but allow setting the start value
+  -x                      use hex suffixes starting at 0, not alphabetic
+      --hex-suffixes[=FROM]  same as -x, but allow setting the start value
+  -e, --elide-empty-files  do not generate empty output files with '-n'
+      --filter=COMMAND    write to shell COMMAND; file name is $FILE
+  -l, --lines=NUMBER      put NUMBER lines/records per output file
+  -n, --number=CHUNKS     generate CHUNKS output files; see explanation below
+  -t, --separator=SEP     use SEP instead of newline as the record separator;
+                            '\\0' (zero) specifies the NUL character
+  -u, --unbuffered        immediately copy input to output with '-n r/...'
+      --verbose           print a diagnostic just before each
+                            output file is opened
+      --help     display this help and exit
+      --version  output version information and exit
+
+The SIZE argument is an integer and optional unit (example: 10K is 10*1024).
+Units are K,M,G,T,P,E (powers of 1024) or KB,MB,... (powers of 1000).
+Binary prefixes can be used, too: KiB=K, MiB=M, and so on.
+
+CHUNKS may be:
+  N       split into N files based on size of input
+  K/N     output Kth of N to stdout
+  l/N     split into N files without splitting lines/records
+  l/K/N   output Kth of N to stdout without splitting lines/records
+  r/N     like 'l' but use round robin distribution
+  r/K/N   likewise but only output Kth of N to stdout
+
+Input that is not a regular file is read into memory to find its size
+for -n N and -n l/N.  COMMAND is run by sh, or by cmd on Windows.
+";
+
+impl Config for SplitConfig {
+    fn bin_name(&self) -> &'static str {
+        "split"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try split --help for more information", message)
+}
+
+/// A size that must be at least one.
+fn parse_count(value: &str, what: &str) -> Result<u64, String> {
+    match size::parse_size(value) {
+        Ok(0) => Err(format!("invalid number of {}: '{}'", what, value)),
+        Ok(count) => Ok(count),
+        Err(e) => Err(format!("invalid number of {}: {}", what, e)),
+    }
+}
+
+/// Parse `N`, `K/N`, `l/N`, `l/K/N`, `r/N` or `r/K/N`.
+fn parse_chunks(value: &str) -> Result<Mode, String> {
+    let (distribution, rest) = match value.split_once('/') {
+        Some(("l", rest)) => (Distribution::Lines, rest),
+        Some(("r", rest)) => (Distribution::RoundRobin, rest),
+        _ => (Distribution::Bytes, value),
+    };
+    let (only, count) = match rest.split_once('/') {
+        Some((only, count)) => (Some(only), count),
+        None => (None, rest),
+    };
+    let count = match count.parse::<u64>() {
+        Ok(0) | Err(_) => return Err(format!("invalid number of chunks: '{}'", count)),
+        Ok(count) => count,
+    };
+    let only = match only.map(|only| (only, only.parse::<u64>())) {
+        None => None,
+        Some((_, Ok(only))) if only > 0 && only <= count => Some(only),
+        Some((only, _)) => return Err(format!("invalid chunk number: '{}'", only)),
+    };
+    Ok(Mode::Chunks {
+        distribution,
+        count,
+        only,
+    })
+}
+
+/// Parse a `-t` separator: one byte, or `\0` for NUL.
+fn parse_separator(value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        [] => Err(String::from("empty record separator")),
+        [byte] => Ok(*byte),
+        b"\\0" => Ok(b'\0'),
+        _ => Err(format!("multi-character separator '{}'", value)),
+    }
+}
+
+impl SplitConfig {
+    pub fn new() -> SplitConfig {
+        SplitConfig {
+            file: input::STDIN.to_string(),
+            prefix: String::from("x"),
+            mode: Mode::Lines(1000),
+            suffix_kind: SuffixKind::Alphabetic,
+            suffix_length: None,
+            suffix_start: None,
+            additional_suffix: String::new(),
+            elide_empty: false,
+            filter: None,
+            separator: b'\n',
+            unbuffered: false,
+            verbose: false,
+        }
+    }
+
+    /// Set the mode, refusing a second option that splits another way.
+    fn set_mode(&mut self, mode: Mode, chosen: &mut bool) -> Result<(), String> {
+        if *chosen && mem::discriminant(&mode) != mem::discriminant(&self.mode) {
+            return Err(usage_error("cannot split in more than one way"));
+        }
+        *chosen = true;
+        self.mode = mode;
+        Ok(())
+    }
+
+    fn set_suffixes(&mut self, kind: SuffixKind, start: Option<&str>) -> Result<(), String> {
+        self.suffix_kind = kind;
+        self.suffix_start = match start {
+            None => None,
+            Some(start) => {
+                let radix = if kind == SuffixKind::Hex { 16 } else { 10 };
+                let start = u64::from_str_radix(start, radix)
+                    .map_err(|_| format!("invalid start value for suffixes: '{}'", start))?;
+                Some(start)
+            }
+        };
+        Ok(())
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let mut chosen = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--suffix-length", Some(value)) | ("-a", Some(value)) => {
+                    let length: usize = value
+                        .parse()
+                        .map_err(|_| format!("invalid suffix length: '{}'", value))?;
+                    self.suffix_length = Some(length).filter(|&length| length > 0);
+                }
+                ("--additional-suffix", Some(suffix)) => {
+                    if suffix.contains(['/', '\\']) {
+                        return Err(usage_error(&format!(
+                            "invalid suffix '{}', contains directory separator",
+                            suffix
+                        )));
+                    }
+                    self.additional_suffix = suffix.to_string();
+                }
+                ("--bytes", Some(value)) | ("-b", Some(value)) => {
+                    let mode = Mode::Bytes(parse_count(value, "bytes")?);
+                    self.set_mode(mode, &mut chosen)?
+                }
+                ("--line-bytes", Some(value)) | ("-C", Some(value)) => {
+                    let mode = Mode::LineBytes(parse_count(value, "bytes")?);
+                    self.set_mode(mode, &mut chosen)?
+                }
+                ("--lines", Some(value)) | ("-l", Some(value)) => {
+                    let mode = Mode::Lines(parse_count(value, "lines")?);
+                    self.set_mode(mode, &mut chosen)?
+                }
+                ("--number", Some(value)) | ("-n", Some(value)) => {
+                    self.set_mode(parse_chunks(value)?, &mut chosen)?
+                }
+                ("-d", None) => self.set_suffixes(SuffixKind::Numeric, None)?,
+                ("--numeric-suffixes", start) => self.set_suffixes(SuffixKind::Numeric, start)?,
+                ("-x", None) => self.set_suffixes(SuffixKind::Hex, None)?,
+                ("--hex-suffixes", start) => self.set_suffixes(SuffixKind::Hex, start)?,
+                ("--elide-empty-files", None) | ("-e", None) => self.elide_empty = true,
+                ("--filter", Some(command)) => self.filter = Some(command.to_string()),
+                ("--separator", Some(value)) | ("-t", Some(value)) => {
+                    self.separator = parse_separator(value)?
+                }
+                ("--unbuffered", None) | ("-u", None) => self.unbuffered = true,
+                ("--verbose", None) => self.verbose = true,
+                (name, None) if name.len() == 2 && "abClnt".contains(&name[1..]) => {
+                    return Err(usage_error(&format!(
+                        "option requires an argument -- '{}'",
+                        &name[1..]
+                    )))
+                }
+                // Obsolete `split -100` form.
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    let mode = Mode::Lines(parse_count(&number[1..], "lines")?);
+                    self.set_mode(mode, &mut chosen)?
+                }
+                _ => return Err(usage_error(&format!("invalid option: {}", option))),
+            }
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        if let Some(file) = operands.next() {
+            self.file = file;
+        }
+        if let Some(prefix) = operands.next() {
+            self.prefix = prefix;
+        }
+        if let Some(extra) = operands.next() {
+            return Err(usage_error(&format!("extra operand '{}'", extra)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Distribution, Mode, SplitConfig, SuffixKind};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<SplitConfig, String> {
+        let mut config = SplitConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    fn mode(args: Vec<String>) -> Mode {
+        parse(args).unwrap().mode
+    }
+
+    fn chunks(distribution: Distribution, count: u64, only: Option<u64>) -> Mode {
+        Mode::Chunks {
+            distribution,
+            count,
+            only,
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["split.exe"]).unwrap();
+        assert_eq!(config.file, "-");
+        assert_eq!(config.prefix, "x");
+        assert_eq!(config.mode, Mode::Lines(1000));
+        assert_eq!(config.suffix_kind, SuffixKind::Alphabetic);
+        assert_eq!(config.suffix_length, None);
+        assert_eq!(config.suffix_start, None);
+        assert_eq!(config.separator, b'\n');
+        assert!(config.filter.is_none());
+        assert!(!config.elide_empty);
+    }
+
+    #[test]
+    fn operands() {
+        let config = parse(string_vec!["split.exe", "in.txt", "part."]).unwrap();
+        assert_eq!(config.file, "in.txt");
+        assert_eq!(config.prefix, "part.");
+        assert!(parse(string_vec!["split.exe", "a", "b", "c"])
+            .unwrap_err()
+            .starts_with("extra operand 'c'"));
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(mode(string_vec!["split.exe", "-l", "5"]), Mode::Lines(5));
+        assert_eq!(mode(string_vec!["split.exe", "-20"]), Mode::Lines(20));
+        assert_eq!(
+            mode(string_vec!["split.exe", "-b", "2K"]),
+            Mode::Bytes(2048)
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "--line-bytes=1MB"]),
+            Mode::LineBytes(1000 * 1000)
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "-n", "4"]),
+            chunks(Distribution::Bytes, 4, None)
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "-n", "2/4"]),
+            chunks(Distribution::Bytes, 4, Some(2))
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "-n", "l/3"]),
+            chunks(Distribution::Lines, 3, None)
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "--number=r/1/3"]),
+            chunks(Distribution::RoundRobin, 3, Some(1))
+        );
+        assert_eq!(
+            mode(string_vec!["split.exe", "-l", "2", "-l", "3"]),
+            Mode::Lines(3)
+        );
+    }
+
+    #[test]
+    fn suffixes() {
+        let config = parse(string_vec![
+            "split.exe",
+            "-a",
+            "3",
+            "-d",
+            "--additional-suffix=.txt"
+        ])
+        .unwrap();
+        assert_eq!(config.suffix_length, Some(3));
+        assert_eq!(config.suffix_kind, SuffixKind::Numeric);
+        assert_eq!(config.additional_suffix, ".txt");
+
+        let config = parse(string_vec!["split.exe", "-d", "--hex-suffixes=1f"]).unwrap();
+        assert_eq!(config.suffix_kind, SuffixKind::Hex);
+        assert_eq!(config.suffix_start, Some(31));
+
+        let config = parse(string_vec!["split.exe", "--numeric-suffixes=7", "-a0"]).unwrap();
+        assert_eq!(config.suffix_start, Some(7));
+        assert_eq!(config.suffix_length, None);
+    }
+
+    #[test]
+    fn other_options() {
+        let config = parse(string_vec![
+            "split.exe",
+            "-eu",
+            "--verbose",
+            "-t",
+            "\\0",
+            "--filter=gzip > $FILE.gz"
+        ])
+        .unwrap();
+        assert!(config.elide_empty);
+        assert!(config.unbuffered);
+        assert!(config.verbose);
+        assert_eq!(config.separator, b'\0');
+        assert_eq!(config.filter.as_deref(), Some("gzip > $FILE.gz"));
+        assert_eq!(
+            parse(string_vec!["split.exe", "-t", ";"])
+                .unwrap()
+                .separator,
+            b';'
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert_eq!(
+            error(string_vec!["split.exe", "-l", "0"]),
+            "invalid number of lines: '0'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-b", "x"]),
+            "invalid number of bytes: invalid number: 'x'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-n", "0"]),
+            "invalid number of chunks: '0'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-n", "r/x"]),
+            "invalid number of chunks: 'x'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-n", "l/4/3"]),
+            "invalid chunk number: '4'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-a", "x"]),
+            "invalid suffix length: 'x'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-t", ""]),
+            "empty record separator"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "-t", "ab"]),
+            "multi-character separator 'ab'"
+        );
+        assert_eq!(
+            error(string_vec!["split.exe", "--numeric-suffixes=a"]),
+            "invalid start value for suffixes: 'a'"
+        );
+        assert!(error(string_vec!["split.exe", "-b", "1", "-l", "2"])
+            .starts_with("cannot split in more than one way\n"));
+        assert!(error(string_vec!["split.exe", "--additional-suffix=a/b"])
+            .starts_with("invalid suffix 'a/b', contains directory separator"));
+        assert!(
+            error(string_vec!["split.exe", "-l"]).starts_with("option requires an argument -- 'l'")
+        );
+    }
+
+    #[test]
+    fn help() {
+        let mut config = SplitConfig::new();
+        let parse_result = config.parse(string_vec!["split.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = SplitConfig::new();
+        let parse_result = config.parse(string_vec!["split.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
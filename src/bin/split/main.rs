@@ -0,0 +1,152 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod chunks;
+mod config;
+mod names;
+mod outputs;
+
+use config::{Distribution, Mode, SplitConfig};
+use outputs::Outputs;
+
+/// The input, naming itself in read errors so they can be told apart
+/// from errors writing a piece.
+struct Input {
+    reader: Box<dyn BufRead>,
+    name: String,
+}
+
+fn read_error(name: &str, err: io::Error) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("{}: {}", input::display_name(name), output::describe(&err)),
+    )
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let name = &self.name;
+        self.reader.read(buf).map_err(|e| read_error(name, e))
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let name = &self.name;
+        self.reader.fill_buf().map_err(|e| read_error(name, e))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+/// Open the input along with its size in bytes, which `-n N` and
+/// `-n l/N` need up front. Anything but a regular file is read into
+/// memory to measure it.
+fn open_sized(name: &str) -> io::Result<(Box<dyn BufRead>, u64)> {
+    if name != input::STDIN {
+        if let Some(len) = input::seekable_len(&fs::metadata(name)?) {
+            return Ok((input::open(name)?, len));
+        }
+    }
+    let (contents, len) = input::read_whole(input::open(name)?)?;
+    Ok((Box::new(contents), len))
+}
+
+fn split<W: Write>(config: &SplitConfig, out: &mut W) -> Result<(), String> {
+    let opened = match config.mode {
+        Mode::Chunks {
+            distribution: Distribution::Bytes,
+            ..
+        }
+        | Mode::Chunks {
+            distribution: Distribution::Lines,
+            ..
+        } => open_sized(&config.file),
+        _ => input::open(&config.file).map(|reader| (reader, 0)),
+    };
+    let (reader, size) = opened.map_err(|e| {
+        format!(
+            "{}: {}",
+            input::display_name(&config.file),
+            output::describe(&e)
+        )
+    })?;
+    let input = Input {
+        reader,
+        name: config.file.clone(),
+    };
+
+    let separator = config.separator;
+    let elide = config.elide_empty;
+    let result = match config.mode {
+        Mode::Chunks {
+            distribution,
+            count,
+            only: Some(number),
+        } => match distribution {
+            Distribution::Bytes => chunks::extract_bytes(input, size, count, number, out),
+            Distribution::Lines => {
+                chunks::extract_lines(input, size, count, separator, number, out)
+            }
+            Distribution::RoundRobin => {
+                chunks::extract_round_robin(input, count, separator, number, config.unbuffered, out)
+            }
+        },
+        Mode::Chunks {
+            distribution,
+            count,
+            only: None,
+        } => {
+            let mut pieces = Outputs::new(config, Some(count))?;
+            match distribution {
+                Distribution::Bytes => chunks::chunk_bytes(input, size, count, elide, &mut pieces),
+                Distribution::Lines => {
+                    chunks::chunk_lines(input, size, count, separator, elide, &mut pieces)
+                }
+                Distribution::RoundRobin => chunks::round_robin(
+                    input,
+                    count,
+                    separator,
+                    elide,
+                    config.unbuffered,
+                    &mut pieces,
+                ),
+            }
+        }
+        Mode::Lines(lines) => {
+            chunks::by_lines(input, lines, separator, &mut Outputs::new(config, None)?)
+        }
+        Mode::Bytes(bytes) => chunks::by_bytes(input, bytes, &mut Outputs::new(config, None)?),
+        Mode::LineBytes(bytes) => {
+            chunks::by_line_bytes(input, bytes, separator, &mut Outputs::new(config, None)?)
+        }
+    };
+    result.map_err(|e| output::describe(&e))
+}
+
+fn main() {
+    let mut config = SplitConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    // A filter that stops reading early must not kill us with SIGPIPE.
+    let mut out = match config.filter {
+        Some(_) => Output::new("split", BufWriter::new(io::stdout().lock())),
+        None => Output::stdout("split"),
+    };
+    let result = split(&config, &mut out);
+    out.flush();
+    if let Err(e) = result {
+        eprintln!("split: {}", e);
+        process::exit(1);
+    }
+}
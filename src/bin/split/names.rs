@@ -0,0 +1,177 @@
+use crate::config::SplitConfig;
+
+/// Output file names: the prefix, a suffix counting up in the chosen
+/// alphabet, then any additional suffix.
+pub struct Names {
+    prefix: String,
+    alphabet: &'static [u8],
+    digits: Vec<usize>,
+    additional: String,
+    /// Without `-a` or a start value the suffix gets a digit longer when
+    /// its first digit reaches the last letter, and that letter moves to
+    /// the prefix: `xyz` is followed by `xzaaa`. Names stay in order.
+    widen: bool,
+    started: bool,
+}
+
+/// Digits needed to write `value` in base `base`.
+fn digits_needed(mut value: u64, base: u64) -> usize {
+    let mut digits = 1;
+    while value >= base {
+        value /= base;
+        digits += 1;
+    }
+    digits
+}
+
+impl Names {
+    /// Names for `count` pieces, or for as many as it takes when the
+    /// count is not known up front.
+    pub fn new(config: &SplitConfig, count: Option<u64>) -> Result<Names, String> {
+        let alphabet = config.suffix_kind.alphabet();
+        let base = alphabet.len() as u64;
+        let start = config.suffix_start.unwrap_or(0);
+
+        let for_start = digits_needed(start, base);
+        let for_count = count.map_or(1, |count| {
+            digits_needed(start.saturating_add(count.saturating_sub(1)), base)
+        });
+        let length = match config.suffix_length {
+            Some(length) if length < for_count && count.is_some() => {
+                return Err(format!(
+                    "the suffix length needs to be at least {}",
+                    for_count
+                ))
+            }
+            Some(length) if length < for_start => {
+                return Err(String::from(
+                    "numerical suffix start value is too large for the suffix length",
+                ))
+            }
+            Some(length) => length,
+            None => for_start.max(for_count).max(2),
+        };
+
+        let mut digits = vec![0; length];
+        let mut value = start;
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % base) as usize;
+            value /= base;
+        }
+        Ok(Names {
+            prefix: config.prefix.clone(),
+            alphabet,
+            digits,
+            additional: config.additional_suffix.clone(),
+            widen: config.suffix_length.is_none()
+                && config.suffix_start.is_none()
+                && count.is_none(),
+            started: false,
+        })
+    }
+
+    fn increment(&mut self) -> Result<(), String> {
+        let last = self.alphabet.len() - 1;
+        for idx in (0..self.digits.len()).rev() {
+            if self.digits[idx] < last {
+                self.digits[idx] += 1;
+                if self.widen && idx == 0 && self.digits[0] == last {
+                    self.prefix.push(char::from(self.alphabet[last]));
+                    self.digits = vec![0; self.digits.len() + 1];
+                }
+                return Ok(());
+            }
+            self.digits[idx] = 0;
+        }
+        Err(String::from("output file suffixes exhausted"))
+    }
+
+    /// The name of the next piece.
+    pub fn next_name(&mut self) -> Result<String, String> {
+        if self.started {
+            self.increment()?;
+        }
+        self.started = true;
+        let suffix: String = self
+            .digits
+            .iter()
+            .map(|&digit| char::from(self.alphabet[digit]))
+            .collect();
+        Ok(format!("{}{}{}", self.prefix, suffix, self.additional))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn names(args: &[&str], count: Option<u64>, take: usize) -> Result<Vec<String>, String> {
+        let mut all: Vec<String> = string_vec!["split.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = SplitConfig::new();
+        config.parse(all).unwrap();
+        let mut names = Names::new(&config, count)?;
+        (0..take).map(|_| names.next_name()).collect()
+    }
+
+    #[test]
+    fn alphabetic() {
+        assert_eq!(names(&[], None, 3).unwrap(), ["xaa", "xab", "xac"]);
+        let all = names(&["-", "part", "--additional-suffix=.txt"], None, 27).unwrap();
+        assert_eq!(all[26], "partba.txt");
+    }
+
+    #[test]
+    fn numeric_and_hex() {
+        assert_eq!(names(&["-d"], None, 2).unwrap(), ["x00", "x01"]);
+        assert_eq!(
+            names(&["--numeric-suffixes=9", "-a3"], None, 2).unwrap(),
+            ["x009", "x010"]
+        );
+        assert_eq!(
+            names(&["--hex-suffixes=f"], None, 2).unwrap(),
+            ["x0f", "x10"]
+        );
+    }
+
+    #[test]
+    fn widening() {
+        let all = names(&[], None, 652).unwrap();
+        assert_eq!(all[649], "xyz");
+        assert_eq!(all[650], "xzaaa");
+        assert_eq!(all[651], "xzaab");
+        let all = names(&["-d"], None, 91).unwrap();
+        assert_eq!(all[89], "x89");
+        assert_eq!(all[90], "x9000");
+    }
+
+    #[test]
+    fn exhausted() {
+        assert_eq!(
+            names(&["-a1"], None, 27).unwrap_err(),
+            "output file suffixes exhausted"
+        );
+        assert_eq!(
+            names(&["--numeric-suffixes=0"], None, 101).unwrap_err(),
+            "output file suffixes exhausted"
+        );
+    }
+
+    #[test]
+    fn sized_for_chunks() {
+        assert_eq!(names(&[], Some(700), 1).unwrap(), ["xaaa"]);
+        assert_eq!(
+            names(&["-a2"], Some(700), 1).unwrap_err(),
+            "the suffix length needs to be at least 3"
+        );
+        assert_eq!(
+            names(&["--numeric-suffixes=300", "-a2"], None, 1).unwrap_err(),
+            "numerical suffix start value is too large for the suffix length"
+        );
+        assert_eq!(
+            names(&["--numeric-suffixes=300"], None, 1).unwrap(),
+            ["x300"]
+        );
+    }
+}
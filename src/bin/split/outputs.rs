@@ -0,0 +1,175 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use coreutils::input;
+use coreutils::output;
+
+use crate::chunks::Pieces;
+use crate::config::SplitConfig;
+use crate::names::Names;
+
+/// Put the name of a piece in front of an error.
+fn named(name: &str, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {}", name, output::describe(&err)))
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new(std::env::var_os("SHELL").unwrap_or_else(|| "/bin/sh".into()));
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// One output file, or the `--filter` command writing it.
+pub enum Piece {
+    File {
+        name: String,
+        writer: BufWriter<File>,
+    },
+    Filter {
+        name: String,
+        child: Child,
+        /// None once the command has stopped reading.
+        stdin: Option<BufWriter<ChildStdin>>,
+    },
+}
+
+impl Write for Piece {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Piece::File { name, writer } => writer.write(buf).map_err(|e| named(name, e)),
+            Piece::Filter { name, stdin, .. } => {
+                let result = match stdin {
+                    Some(pipe) => pipe.write(buf),
+                    None => return Ok(buf.len()),
+                };
+                match result {
+                    // A command need not read all of its input.
+                    Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                        *stdin = None;
+                        Ok(buf.len())
+                    }
+                    result => result.map_err(|e| named(name, e)),
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Piece::File { name, writer } => writer.flush().map_err(|e| named(name, e)),
+            Piece::Filter { name, stdin, .. } => {
+                let result = match stdin {
+                    Some(pipe) => pipe.flush(),
+                    None => return Ok(()),
+                };
+                match result {
+                    Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                        *stdin = None;
+                        Ok(())
+                    }
+                    result => result.map_err(|e| named(name, e)),
+                }
+            }
+        }
+    }
+}
+
+/// The output files, each named in turn.
+pub struct Outputs<'a> {
+    config: &'a SplitConfig,
+    names: Names,
+    /// The input file, which no piece may overwrite.
+    input: Option<PathBuf>,
+}
+
+impl<'a> Outputs<'a> {
+    /// Outputs for `count` pieces, or as many as it takes.
+    pub fn new(config: &'a SplitConfig, count: Option<u64>) -> Result<Outputs<'a>, String> {
+        let input = match config.file.as_str() {
+            input::STDIN => None,
+            file => fs::canonicalize(file).ok(),
+        };
+        Ok(Outputs {
+            config,
+            names: Names::new(config, count)?,
+            input,
+        })
+    }
+}
+
+impl Pieces for Outputs<'_> {
+    type Piece = Piece;
+
+    fn open(&mut self) -> io::Result<Piece> {
+        let name = self.names.next_name().map_err(io::Error::other)?;
+
+        if let Some(command) = &self.config.filter {
+            let mut child = shell(command)
+                .env("FILE", &name)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!(
+                            "failed to run command: {}: {}",
+                            command,
+                            output::describe(&e)
+                        ),
+                    )
+                })?;
+            let stdin = child.stdin.take().map(BufWriter::new);
+            return Ok(Piece::Filter { name, child, stdin });
+        }
+
+        if self.input.is_some() && fs::canonicalize(&name).ok() == self.input {
+            return Err(io::Error::other(format!(
+                "'{}' would overwrite input; aborting",
+                name
+            )));
+        }
+        if self.config.verbose {
+            let _ = writeln!(io::stdout(), "creating file '{}'", name);
+        }
+        let file = File::create(&name).map_err(|e| named(&name, e))?;
+        Ok(Piece::File {
+            name,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    fn close(&mut self, mut piece: Piece) -> io::Result<()> {
+        piece.flush()?;
+        if let Piece::Filter {
+            name,
+            mut child,
+            stdin,
+        } = piece
+        {
+            drop(stdin);
+            let status = child.wait()?;
+            if !status.success() {
+                let command = self.config.filter.as_deref().unwrap_or_default();
+                let how = match status.code() {
+                    Some(code) => format!("exit {}", code),
+                    None => String::from("killed by a signal"),
+                };
+                return Err(io::Error::other(format!(
+                    "with FILE={}, {} from command: {}",
+                    name, how, command
+                )));
+            }
+        }
+        Ok(())
+    }
+}
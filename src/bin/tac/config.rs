@@ -0,0 +1,162 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::regex::Regex;
+
+/// What ends each record.
+#[derive(Debug)]
+pub enum Separator {
+    Bytes(Vec<u8>),
+    Pattern(Regex),
+}
+
+#[derive(Debug)]
+pub struct TacConfig {
+    pub files: Vec<String>,
+    /// Attach the separator to the start of the record it follows rather
+    /// than the end of the one it precedes.
+    pub before: bool,
+    pub separator: Separator,
+}
+
+const HELP_TEXT: &str = "Usage: tac [OPTION]... [FILE]...
+Write each FILE to standard output, last line first.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --before             attach the separator before instead of after
+  -r, --regex              interpret the separator as a regular expression
+  -s, --separator=STRING   use STRING as the separator instead of newline
+      --no-glob            do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+Regular files are read backwards a block at a time; other input is read
+in full first.  With -r, STRING is a basic regular expression, and a
+match longer than a block may be cut short where two blocks meet.
+";
+
+impl Config for TacConfig {
+    fn bin_name(&self) -> &'static str {
+        "tac"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+impl TacConfig {
+    pub fn new() -> TacConfig {
+        TacConfig {
+            files: Vec::new(),
+            before: false,
+            separator: Separator::Bytes(b"\n".to_vec()),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        let mut separator = String::from("\n");
+        let mut regex = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--before", None) | ("-b", None) => self.before = true,
+                ("--regex", None) | ("-r", None) => regex = true,
+                ("--separator", Some(text)) | ("-s", Some(text)) => separator = text.to_string(),
+                ("-s", None) => return Err("option requires an argument -- 's'".to_string()),
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try tac --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if separator.is_empty() {
+            return Err("separator cannot be empty".to_string());
+        }
+        self.separator = if regex {
+            Separator::Pattern(Regex::new(&separator)?)
+        } else {
+            Separator::Bytes(separator.into_bytes())
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Separator, TacConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<TacConfig, String> {
+        let mut config = TacConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["tac.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert!(!config.before);
+        assert!(matches!(config.separator, Separator::Bytes(ref bytes) if bytes == b"\n"));
+    }
+
+    #[test]
+    fn separators() {
+        let config = parse(string_vec!["tac.exe", "-b", "-s", "::", "a", "b"]).unwrap();
+        assert!(config.before);
+        assert!(matches!(config.separator, Separator::Bytes(ref bytes) if bytes == b"::"));
+        assert_eq!(config.files, vec!["a", "b"]);
+
+        let config = parse(string_vec!["tac.exe", "-r", "--separator=x\\+"]).unwrap();
+        match config.separator {
+            Separator::Pattern(regex) => assert_eq!(regex.find(b"axxb"), Some(1..3)),
+            Separator::Bytes(_) => panic!("expected a pattern"),
+        }
+
+        // The default newline is a pattern too under -r.
+        let config = parse(string_vec!["tac.exe", "-r"]).unwrap();
+        assert!(matches!(config.separator, Separator::Pattern(_)));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            parse(string_vec!["tac.exe", "-s", ""]).unwrap_err(),
+            "separator cannot be empty"
+        );
+        assert!(parse(string_vec!["tac.exe", "-r", "-s", "["])
+            .unwrap_err()
+            .contains("Unmatched ["));
+        assert!(parse(string_vec!["tac.exe", "-x"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = TacConfig::new();
+        let parse_result = config.parse(string_vec!["tac.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = TacConfig::new();
+        let parse_result = config.parse(string_vec!["tac.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
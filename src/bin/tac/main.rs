@@ -0,0 +1,59 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, Write};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod reverse;
+
+use config::TacConfig;
+
+/// Reverse one operand. Regular files are read backwards in place;
+/// anything that cannot seek is read into memory first.
+fn tac_file<W: Write>(name: &str, out: &mut W, config: &TacConfig) -> io::Result<()> {
+    if name == input::STDIN {
+        let (mut contents, len) = input::read_whole(io::stdin().lock())?;
+        return reverse::tac(&mut contents, len, out, config);
+    }
+
+    let mut file = File::open(name)?;
+    let metadata = file.metadata()?;
+    if metadata.is_dir() {
+        return Err(io::Error::other("Is a directory"));
+    }
+    match input::seekable_len(&metadata) {
+        Some(len) => reverse::tac(&mut file, len, out, config),
+        None => {
+            let (mut contents, len) = input::read_whole(file)?;
+            reverse::tac(&mut contents, len, out, config)
+        }
+    }
+}
+
+fn main() {
+    let mut config = TacConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let mut out = Output::stdout("tac");
+    for file in &config.files {
+        if let Err(e) = tac_file(file, &mut out, &config) {
+            out.flush();
+            eprintln!(
+                "tac: {}: {}",
+                input::display_name(file),
+                output::describe(&e)
+            );
+            exit_code = 1;
+        }
+    }
+
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,250 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+use coreutils::input;
+
+use crate::config::{Separator, TacConfig};
+
+impl Separator {
+    /// How far past the block it starts in a match is looked for: the
+    /// rest of a literal separator, or up to a block of a pattern.
+    fn reach(&self) -> usize {
+        match self {
+            Separator::Bytes(bytes) => bytes.len() - 1,
+            Separator::Pattern(_) => input::BUFFER_SIZE,
+        }
+    }
+
+    /// Every separator in `text` starting no later than `last`, front to
+    /// back. A literal separator is searched for from the right as GNU tac
+    /// does, so one that overlaps itself splits the same way wherever the
+    /// blocks end. A match that starts at 0 is left out when `partial`,
+    /// since the text before it has not been read and the match might
+    /// reach further back.
+    fn matches(&self, text: &[u8], last: usize, partial: bool) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        match self {
+            Separator::Bytes(bytes) => {
+                let mut end = text.len();
+                while end >= bytes.len() {
+                    let pos = end - bytes.len();
+                    if pos <= last && text[pos..end] == bytes[..] {
+                        found.push(pos..end);
+                        end = pos;
+                    } else {
+                        end -= 1;
+                    }
+                }
+                found.reverse();
+            }
+            Separator::Pattern(regex) => {
+                let mut pos = 0;
+                while let Some(range) = regex.find_at(text, pos) {
+                    if range.start > last {
+                        break;
+                    }
+                    // Empty matches would give empty records forever.
+                    if range.is_empty() {
+                        pos = range.end + 1;
+                        if pos > text.len() {
+                            break;
+                        }
+                        continue;
+                    }
+                    pos = range.end;
+                    if !(partial && range.start == 0) {
+                        found.push(range);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Write the records of `reader` last first, reading it backwards from
+/// `len` one block at a time so only the record being assembled is held
+/// in memory.
+pub fn tac<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    len: u64,
+    out: &mut W,
+    config: &TacConfig,
+) -> io::Result<()> {
+    // The unwritten front of the input as the blocks it was read in, last
+    // read first, so a new block is added without moving the others.
+    let mut pending: Vec<Vec<u8>> = Vec::new();
+    // How much of the front of `pending` is not part of a separator
+    // already found, and so may be part of another.
+    let mut free = 0;
+    let mut start = len;
+    while start > 0 {
+        let read = start.min(input::BUFFER_SIZE as u64) as usize;
+        start -= read as u64;
+        let mut window = vec![0; read];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(&mut window)?;
+
+        // Separators are only looked for from the new block, along with
+        // as much of the old text as one of them could run into; the old
+        // text holds none, bar a pattern match left at its start.
+        let reach = config.separator.reach().min(free);
+        for block in pending.iter().rev() {
+            let wanted = reach - (window.len() - read);
+            if wanted == 0 {
+                break;
+            }
+            window.extend_from_slice(&block[..wanted.min(block.len())]);
+        }
+        let matches = config.separator.matches(&window, read, start > 0);
+        if matches.is_empty() {
+            window.truncate(read);
+            pending.push(window);
+            free += read;
+            continue;
+        }
+
+        // The last record runs on past the window into the old text.
+        let mut end = window.len();
+        for (idx, range) in matches.iter().rev().enumerate() {
+            let boundary = if config.before {
+                range.start
+            } else {
+                range.end
+            };
+            out.write_all(&window[boundary..end])?;
+            if idx == 0 {
+                write_from(&pending, window.len() - read, out)?;
+            }
+            end = boundary;
+        }
+        window.truncate(end);
+        pending = vec![window];
+        free = matches[0].start;
+    }
+    write_from(&pending, 0, out)
+}
+
+/// Write the text `pending` holds from `skip` bytes in.
+fn write_from<W: Write>(pending: &[Vec<u8>], mut skip: usize, out: &mut W) -> io::Result<()> {
+    for block in pending.iter().rev() {
+        let from = skip.min(block.len());
+        out.write_all(&block[from..])?;
+        skip -= from;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+    use std::io::Cursor;
+
+    fn tac_bytes(args: &[&str], input: &[u8]) -> Vec<u8> {
+        let mut all: Vec<String> = string_vec!["tac.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = TacConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        tac(
+            &mut Cursor::new(input),
+            input.len() as u64,
+            &mut out,
+            &config,
+        )
+        .unwrap();
+        out
+    }
+
+    fn tac_str(args: &[&str], input: &str) -> String {
+        String::from_utf8(tac_bytes(args, input.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(tac_str(&[], "a\nb\nc\n"), "c\nb\na\n");
+        assert_eq!(tac_str(&[], "a\nb"), "ba\n");
+        assert_eq!(tac_str(&[], "a\r\nb\r\n"), "b\r\na\r\n");
+        assert_eq!(tac_str(&[], "\n\n"), "\n\n");
+        assert_eq!(tac_str(&[], ""), "");
+    }
+
+    #[test]
+    fn before() {
+        assert_eq!(tac_str(&["-b"], "a\nb\n"), "\n\nba");
+        assert_eq!(tac_str(&["-b"], "\na\nb"), "\nb\na");
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(tac_str(&["-s", "::"], "a::b::c"), "cb::a::");
+        assert_eq!(tac_str(&["-s", ":", "-b"], "a:b:c"), ":c:ba");
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(tac_str(&["-r", "-s", "[0-9]\\+"], "a1b22c"), "cb22a1");
+        assert_eq!(tac_str(&["-r", "-b", "-s", "[.!]"], "a.b!c"), "!c.ba");
+        // Empty matches separate nothing.
+        assert_eq!(tac_str(&["-r", "-s", "x*"], "axb"), "bax");
+    }
+
+    #[test]
+    fn across_blocks() {
+        // Records longer than a block and separators across block edges.
+        let long = "x".repeat(input::BUFFER_SIZE + 10);
+        let text = format!("{}\nshort\n{}\n", long, long);
+        assert_eq!(tac_str(&[], &text), format!("{}\nshort\n{}\n", long, long));
+
+        let tail = "z".repeat(input::BUFFER_SIZE - 1);
+        assert_eq!(
+            tac_bytes(&["-s", "::"], format!("yyyyy::{}", tail).as_bytes()),
+            format!("{}yyyyy::", tail).as_bytes()
+        );
+
+        let lines: Vec<String> = (0..50_000).map(|n| format!("{}\n", n)).collect();
+        let reversed: String = lines.iter().rev().cloned().collect();
+        assert_eq!(tac_str(&[], &lines.concat()), reversed);
+        assert_eq!(tac_str(&["-r", "-s", "\n"], &lines.concat()), reversed);
+    }
+
+    #[test]
+    fn overlapping_separators() {
+        assert_eq!(tac_str(&["-s", "aa"], "baaac"), "cbaaa");
+        assert_eq!(tac_str(&["-b", "-s", "aa"], "baaac"), "aacba");
+
+        // Split the same way when the separator straddles a block edge.
+        let tail = "c".repeat(input::BUFFER_SIZE - 2);
+        assert_eq!(
+            tac_str(&["-s", "aa"], &format!("baaa{}", tail)),
+            format!("{}baaa", tail)
+        );
+        let tail = "c".repeat(input::BUFFER_SIZE - 1);
+        assert_eq!(
+            tac_str(&["-s", "aa"], &format!("baaa{}", tail)),
+            format!("{}baaa", tail)
+        );
+        assert_eq!(
+            tac_str(&["-b", "-s", "aa"], &format!("baaa{}", tail)),
+            format!("aa{}ba", tail)
+        );
+    }
+
+    #[test]
+    fn long_records() {
+        // Each block is scanned once, with a separator split across the
+        // edge of two blocks still found whole.
+        let long = "x".repeat(40 * input::BUFFER_SIZE);
+        let tail = "y".repeat(input::BUFFER_SIZE - 2);
+        let text = format!("{}1234{}", long, tail);
+        assert_eq!(
+            tac_str(&["-r", "-s", "[0-9]\\+"], &text),
+            format!("{}{}1234", tail, long)
+        );
+        assert_eq!(
+            tac_str(&["-r", "-b", "-s", "[0-9]\\+"], &text),
+            format!("1234{}{}", tail, long)
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use coreutils::config::{self, Config};
+use coreutils::input;
+use coreutils::size;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Follow {
+    /// Keep reading the open file even if it is renamed or unlinked.
+    Descriptor,
+    /// Reopen the path whenever the file behind it is replaced.
+    Name,
+}
+
+#[derive(Debug)]
+pub struct TailConfig {
+    pub files: Vec<String>,
+    pub count: u64,
+    pub bytes: bool,
+    /// `+NUM`: start at the NUMth line or byte instead of counting from the end.
+    pub from_start: bool,
+    pub follow: Option<Follow>,
+    pub retry: bool,
+    pub pid: Option<u32>,
+    pub sleep_interval: Duration,
+    pub quiet: bool,
+    pub verbose: bool,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: tail [OPTION]... [FILE]...
+Print the last 10 lines of each FILE to standard output.
+With more than one FILE, precede each with a header giving the file name.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -c, --bytes=[+]NUM       output the last NUM bytes; or use -c +NUM to
+                             output starting with byte NUM of each file
+  -f, --follow[={name|descriptor}]  output appended data as the file grows;
+                             an absent option argument means 'descriptor'
+  -F                       same as --follow=name --retry
+  -n, --lines=[+]NUM       output the last NUM lines, instead of the last 10;
+                             or use -n +NUM to skip NUM-1 lines at the start
+      --pid=PID            with -f, terminate after process ID, PID dies
+  -q, --quiet              never output headers giving file names
+      --silent             same as --quiet
+      --retry              keep trying to open a file if it is inaccessible
+  -s, --sleep-interval=N   with -f, sleep for approximately N seconds
+                             (default 1.0) between iterations
+  -v, --verbose            always output headers giving file names
+  -z, --zero-terminated    line delimiter is NUL, not newline
+      --no-glob            do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+
+NUM may have a multiplier suffix:
+b 512, kB 1000, K 1024, MB 1000*1000, M 1024*1024,
+GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P, E.
+Binary prefixes can be used, too: KiB=K, MiB=M, and so on.
+
+With --follow (-f), tail defaults to following the file descriptor, which
+means that even if a tail'ed file is renamed, tail will continue to track
+its end.  This default behavior is not desirable when you really want to
+track the actual name of the file, not the file descriptor (e.g., log
+rotation).  Use --follow=name in that case.  That causes tail to track the
+named file in a way that accommodates renaming, removal and creation.
+";
+
+impl Config for TailConfig {
+    fn bin_name(&self) -> &'static str {
+        "tail"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+impl TailConfig {
+    pub fn new() -> TailConfig {
+        TailConfig {
+            files: Vec::new(),
+            count: 10,
+            bytes: false,
+            from_start: false,
+            follow: None,
+            retry: false,
+            pid: None,
+            sleep_interval: Duration::from_secs(1),
+            quiet: false,
+            verbose: false,
+            zero_terminated: false,
+        }
+    }
+
+    fn set_count(&mut self, option: &str, value: Option<&str>, bytes: bool) -> Result<(), String> {
+        let value = value.ok_or(format!("option requires an argument -- '{}'", option))?;
+        let (from_start, number) = match value.strip_prefix('+') {
+            Some(number) => (true, number),
+            None => (false, value.strip_prefix('-').unwrap_or(value)),
+        };
+        let kind = if bytes { "bytes" } else { "lines" };
+        self.count =
+            size::parse_size(number).map_err(|e| format!("invalid number of {}: {}", kind, e))?;
+        self.from_start = from_start;
+        self.bytes = bytes;
+        Ok(())
+    }
+
+    fn set_sleep_interval(&mut self, value: Option<&str>) -> Result<(), String> {
+        let value = value.ok_or("option requires an argument -- 's'")?;
+        let seconds = value.parse::<f64>().ok();
+        match seconds.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+            Some(interval) => {
+                self.sleep_interval = interval;
+                Ok(())
+            }
+            None => Err(format!("invalid number of seconds: '{}'", value)),
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        if self.files.is_empty() {
+            self.files.push(input::STDIN.to_string());
+        }
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--bytes", value) | ("-c", value) => self.set_count(&option, value, true)?,
+                ("--lines", value) | ("-n", value) => self.set_count(&option, value, false)?,
+                ("--follow", None) | ("-f", None) => self.follow = Some(Follow::Descriptor),
+                ("--follow", Some("descriptor")) => self.follow = Some(Follow::Descriptor),
+                ("--follow", Some("name")) => self.follow = Some(Follow::Name),
+                ("--follow", Some(how)) => {
+                    return Err(format!(
+                        "invalid argument '{}' for '--follow'\nValid arguments are: 'name', 'descriptor'",
+                        how
+                    ))
+                }
+                ("-F", None) => {
+                    self.follow = Some(Follow::Name);
+                    self.retry = true;
+                }
+                ("--retry", None) => self.retry = true,
+                ("--pid", Some(pid)) => {
+                    self.pid = Some(
+                        pid.parse()
+                            .map_err(|_| format!("invalid PID: '{}'", pid))?,
+                    )
+                }
+                ("--sleep-interval", value) | ("-s", value) => self.set_sleep_interval(value)?,
+                ("--quiet", None) | ("--silent", None) | ("-q", None) => {
+                    self.quiet = true;
+                    self.verbose = false;
+                }
+                ("--verbose", None) | ("-v", None) => {
+                    self.verbose = true;
+                    self.quiet = false;
+                }
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                // Obsolete `tail -5` form.
+                (number, None) if number[1..].chars().all(|c| c.is_ascii_digit()) => {
+                    self.set_count(&option, Some(&number[1..]), false)?
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try tail --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Follow, TailConfig};
+    use coreutils::string_vec;
+    use std::time::Duration;
+
+    #[test]
+    fn defaults() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe"]).unwrap();
+        assert_eq!(config.files, vec!["-"]);
+        assert_eq!(config.count, 10);
+        assert!(!config.bytes);
+        assert!(!config.from_start);
+        assert_eq!(config.follow, None);
+        assert!(!config.retry);
+        assert_eq!(config.pid, None);
+        assert_eq!(config.sleep_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn lines() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-n", "5"]).unwrap();
+        assert_eq!(config.count, 5);
+        assert!(!config.from_start);
+
+        config.parse(string_vec!["tail.exe", "--lines=-7"]).unwrap();
+        assert_eq!(config.count, 7);
+        assert!(!config.from_start);
+    }
+
+    #[test]
+    fn lines_from_start() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-n", "+3"]).unwrap();
+        assert_eq!(config.count, 3);
+        assert!(config.from_start);
+    }
+
+    #[test]
+    fn bytes() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-c", "+1k"]).unwrap();
+        assert_eq!(config.count, 1024);
+        assert!(config.bytes);
+        assert!(config.from_start);
+    }
+
+    #[test]
+    fn obsolete_count() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-3", "file"]).unwrap();
+        assert_eq!(config.count, 3);
+    }
+
+    #[test]
+    fn invalid_count() {
+        let mut config = TailConfig::new();
+        assert!(config
+            .parse(string_vec!["tail.exe", "-n", "x"])
+            .unwrap_err()
+            .contains("invalid number of lines"));
+    }
+
+    #[test]
+    fn follow() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-f"]).unwrap();
+        assert_eq!(config.follow, Some(Follow::Descriptor));
+        assert!(!config.retry);
+
+        config
+            .parse(string_vec!["tail.exe", "--follow=name"])
+            .unwrap();
+        assert_eq!(config.follow, Some(Follow::Name));
+
+        assert!(config
+            .parse(string_vec!["tail.exe", "--follow=inode"])
+            .unwrap_err()
+            .contains("invalid argument"));
+    }
+
+    #[test]
+    fn follow_name_retry() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-F"]).unwrap();
+        assert_eq!(config.follow, Some(Follow::Name));
+        assert!(config.retry);
+    }
+
+    #[test]
+    fn pid() {
+        let mut config = TailConfig::new();
+        config
+            .parse(string_vec!["tail.exe", "--pid", "42"])
+            .unwrap();
+        assert_eq!(config.pid, Some(42));
+        assert!(config
+            .parse(string_vec!["tail.exe", "--pid=abc"])
+            .unwrap_err()
+            .contains("invalid PID"));
+    }
+
+    #[test]
+    fn sleep_interval() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-s", "0.25"]).unwrap();
+        assert_eq!(config.sleep_interval, Duration::from_millis(250));
+        assert!(config
+            .parse(string_vec!["tail.exe", "-s", "-1"])
+            .unwrap_err()
+            .contains("invalid number of seconds"));
+        assert_eq!(
+            config
+                .parse(string_vec!["tail.exe", "-s", "1e30", "-f"])
+                .unwrap_err(),
+            "invalid number of seconds: '1e30'"
+        );
+    }
+
+    #[test]
+    fn quiet_verbose() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-v", "-q"]).unwrap();
+        assert!(config.quiet);
+        assert!(!config.verbose);
+    }
+
+    #[test]
+    fn zero_terminated() {
+        let mut config = TailConfig::new();
+        config.parse(string_vec!["tail.exe", "-z"]).unwrap();
+        assert!(config.zero_terminated);
+    }
+
+    #[test]
+    fn help() {
+        let mut config = TailConfig::new();
+        let parse_result = config.parse(string_vec!["tail.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = TailConfig::new();
+        let parse_result = config.parse(string_vec!["tail.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
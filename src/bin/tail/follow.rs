@@ -0,0 +1,380 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use coreutils::output;
+
+use crate::config::Follow;
+
+/// What makes an open file the same file as the one a path names now.
+type Identity = (u64, u64);
+
+#[cfg(unix)]
+fn identity(file: &File) -> io::Result<Identity> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = file.metadata()?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn identity(file: &File) -> io::Result<Identity> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Ok((u64::from(info.dwVolumeSerialNumber), index))
+}
+
+/// Whether the process `pid` is still running.
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+        return true;
+    }
+    io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether the process `pid` is still running.
+#[cfg(windows)]
+pub fn process_alive(pid: u32) -> bool {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::shared::winerror::ERROR_ACCESS_DENIED;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::STILL_ACTIVE;
+    use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut code = 0;
+        let queried = GetExitCodeProcess(handle, &mut code);
+        CloseHandle(handle);
+        queried != 0 && code == STILL_ACTIVE
+    }
+}
+
+struct Watched {
+    name: String,
+    file: Option<File>,
+    identity: Option<Identity>,
+    position: u64,
+}
+
+/// Polls a set of files for appended data. Following by name reopens the
+/// path when the file behind it is replaced, removed or recreated.
+pub struct Follower {
+    files: Vec<Watched>,
+    mode: Follow,
+    retry: bool,
+    headers: bool,
+    last_shown: Option<String>,
+}
+
+impl Follower {
+    pub fn new(mode: Follow, retry: bool, headers: bool) -> Follower {
+        Follower {
+            files: Vec::new(),
+            mode,
+            retry,
+            headers,
+            last_shown: None,
+        }
+    }
+
+    /// Record the file whose contents were printed last, so a header is
+    /// only repeated when output switches to another file.
+    pub fn shown(&mut self, name: &str) {
+        self.last_shown = Some(name.to_string());
+    }
+
+    /// Start watching `name`, continuing from the current position of
+    /// `file`. Without an open file the path is only watched when
+    /// following by name with `--retry`.
+    pub fn add(&mut self, name: &str, file: Option<File>) -> io::Result<()> {
+        let (identity, position) = match &file {
+            Some(file) => {
+                let mut handle = file;
+                (Some(identity(file)?), handle.stream_position()?)
+            }
+            None if self.mode == Follow::Name && self.retry => (None, 0),
+            None => return Ok(()),
+        };
+        self.files.push(Watched {
+            name: name.to_string(),
+            file,
+            identity,
+            position,
+        });
+        Ok(())
+    }
+
+    /// Whether polling could still produce output.
+    pub fn active(&self) -> bool {
+        match self.mode {
+            Follow::Name => !self.files.is_empty(),
+            Follow::Descriptor => self.files.iter().any(|watched| watched.file.is_some()),
+        }
+    }
+
+    /// Copy whatever was appended since the last poll to `writer`,
+    /// returning diagnostics about files that changed underneath us.
+    pub fn poll<W: Write>(&mut self, writer: &mut W) -> Vec<String> {
+        let mut messages = Vec::new();
+        for idx in 0..self.files.len() {
+            if self.mode == Follow::Name {
+                self.reopen(idx, writer, &mut messages);
+            }
+            if let Err(e) = self.read_new(idx, writer, &mut messages) {
+                let watched = &mut self.files[idx];
+                messages.push(format!(
+                    "error reading '{}': {}",
+                    watched.name,
+                    output::describe(&e)
+                ));
+                watched.file = None;
+                watched.identity = None;
+            }
+        }
+        messages
+    }
+
+    fn reopen<W: Write>(&mut self, idx: usize, writer: &mut W, messages: &mut Vec<String>) {
+        let opened = File::open(&self.files[idx].name).and_then(|file| {
+            let id = identity(&file)?;
+            Ok((file, id))
+        });
+
+        match opened {
+            Err(e) => {
+                let watched = &mut self.files[idx];
+                if watched.file.take().is_some() {
+                    messages.push(format!(
+                        "'{}' has become inaccessible: {}",
+                        watched.name,
+                        output::describe(&e)
+                    ));
+                    watched.identity = None;
+                }
+            }
+            Ok((file, id)) if self.files[idx].identity != Some(id) => {
+                let replaced = self.files[idx].file.is_some();
+                if replaced {
+                    // Drain what was written to the old file before it moved.
+                    let _ = self.read_new(idx, writer, messages);
+                }
+                let watched = &mut self.files[idx];
+                messages.push(format!(
+                    "'{}' has {};  following new file",
+                    watched.name,
+                    if replaced {
+                        "been replaced"
+                    } else {
+                        "appeared"
+                    }
+                ));
+                watched.file = Some(file);
+                watched.identity = Some(id);
+                watched.position = 0;
+            }
+            Ok(_) => {}
+        }
+    }
+
+    fn read_new<W: Write>(
+        &mut self,
+        idx: usize,
+        writer: &mut W,
+        messages: &mut Vec<String>,
+    ) -> io::Result<()> {
+        let watched = &mut self.files[idx];
+        let file = match &mut watched.file {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+
+        let len = file.metadata()?.len();
+        if len < watched.position {
+            messages.push(format!("{}: file truncated", watched.name));
+            watched.position = 0;
+        }
+        if len == watched.position {
+            return Ok(());
+        }
+
+        if self.headers && self.last_shown.as_ref() != Some(&watched.name) {
+            let separator = if self.last_shown.is_some() { "\n" } else { "" };
+            writeln!(writer, "{}==> {} <==", separator, watched.name)?;
+            self.last_shown = Some(watched.name.clone());
+        }
+
+        file.seek(SeekFrom::Start(watched.position))?;
+        watched.position += io::copy(&mut file.take(len - watched.position), writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::path::Path;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn open_at_end(path: &Path) -> File {
+        let mut file = File::open(path).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file
+    }
+
+    fn poll(follower: &mut Follower) -> (String, Vec<String>) {
+        let mut output = Vec::new();
+        let messages = follower.poll(&mut output);
+        (String::from_utf8(output).unwrap(), messages)
+    }
+
+    #[test]
+    fn appended_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "old\n").unwrap();
+
+        let mut follower = Follower::new(Follow::Descriptor, false, false);
+        follower.add("log", Some(open_at_end(&path))).unwrap();
+        assert_eq!(poll(&mut follower), (String::new(), vec![]));
+
+        append(&path, "one\n");
+        append(&path, "two\n");
+        assert_eq!(poll(&mut follower).0, "one\ntwo\n");
+        assert_eq!(poll(&mut follower).0, "");
+    }
+
+    #[test]
+    fn truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "a long first line\n").unwrap();
+
+        let mut follower = Follower::new(Follow::Descriptor, false, false);
+        follower.add("log", Some(open_at_end(&path))).unwrap();
+
+        OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        append(&path, "new\n");
+        let (output, messages) = poll(&mut follower);
+        assert_eq!(output, "new\n");
+        assert_eq!(messages, vec!["log: file truncated"]);
+    }
+
+    #[test]
+    fn descriptor_survives_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        let moved = dir.path().join("log.1");
+        fs::write(&path, "").unwrap();
+
+        let mut follower = Follower::new(Follow::Descriptor, false, false);
+        follower.add("log", Some(open_at_end(&path))).unwrap();
+
+        fs::rename(&path, &moved).unwrap();
+        fs::write(&path, "replacement\n").unwrap();
+        append(&moved, "still here\n");
+        assert_eq!(poll(&mut follower).0, "still here\n");
+    }
+
+    #[test]
+    fn name_follows_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        let moved = dir.path().join("log.1");
+        fs::write(&path, "").unwrap();
+
+        let name = path.to_string_lossy().to_string();
+        let mut follower = Follower::new(Follow::Name, false, false);
+        follower.add(&name, Some(open_at_end(&path))).unwrap();
+
+        append(&path, "before\n");
+        fs::rename(&path, &moved).unwrap();
+        append(&moved, "late\n");
+        fs::write(&path, "rotated\n").unwrap();
+
+        let (output, messages) = poll(&mut follower);
+        assert_eq!(output, "before\nlate\nrotated\n");
+        assert_eq!(
+            messages,
+            vec![format!("'{}' has been replaced;  following new file", name)]
+        );
+
+        append(&path, "more\n");
+        assert_eq!(poll(&mut follower).0, "more\n");
+    }
+
+    #[test]
+    fn name_retry_until_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        let name = path.to_string_lossy().to_string();
+
+        let mut follower = Follower::new(Follow::Name, true, false);
+        follower.add(&name, None).unwrap();
+        assert!(follower.active());
+        assert_eq!(poll(&mut follower), (String::new(), vec![]));
+
+        fs::write(&path, "hello\n").unwrap();
+        let (output, messages) = poll(&mut follower);
+        assert_eq!(output, "hello\n");
+        assert_eq!(
+            messages,
+            vec![format!("'{}' has appeared;  following new file", name)]
+        );
+
+        fs::remove_file(&path).unwrap();
+        let (_, messages) = poll(&mut follower);
+        assert!(messages[0].contains("has become inaccessible"));
+    }
+
+    #[test]
+    fn missing_without_retry() {
+        let mut follower = Follower::new(Follow::Name, false, false);
+        follower.add("missing", None).unwrap();
+        assert!(!follower.active());
+    }
+
+    #[test]
+    fn headers_on_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a");
+        let second = dir.path().join("b");
+        fs::write(&first, "").unwrap();
+        fs::write(&second, "").unwrap();
+
+        let mut follower = Follower::new(Follow::Descriptor, false, true);
+        follower.shown("b");
+        follower.add("a", Some(open_at_end(&first))).unwrap();
+        follower.add("b", Some(open_at_end(&second))).unwrap();
+
+        append(&second, "b1\n");
+        assert_eq!(poll(&mut follower).0, "b1\n");
+        append(&first, "a1\n");
+        append(&second, "b2\n");
+        assert_eq!(poll(&mut follower).0, "\n==> a <==\na1\n\n==> b <==\nb2\n");
+    }
+
+    #[test]
+    fn own_process_is_alive() {
+        assert!(process_alive(std::process::id()));
+    }
+}
@@ -0,0 +1,174 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+use std::thread;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod follow;
+mod stream;
+
+use config::{Follow, TailConfig};
+
+fn delimiter(config: &TailConfig) -> u8 {
+    if config.zero_terminated {
+        b'\0'
+    } else {
+        b'\n'
+    }
+}
+
+/// Tail an input that can only be read front to back.
+fn tail_stream<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    config: &TailConfig,
+) -> io::Result<()> {
+    let skip = config.count.saturating_sub(1);
+    match (config.bytes, config.from_start) {
+        (true, false) => stream::last_bytes(reader, writer, config.count),
+        (true, true) => stream::skip_bytes(reader, writer, skip),
+        (false, false) => stream::last_lines(reader, writer, config.count, delimiter(config)),
+        (false, true) => stream::skip_lines(reader, writer, skip, delimiter(config)),
+    }
+}
+
+/// Tail a file, seeking straight to the interesting part when it is a
+/// regular file. The file is left positioned at its end.
+fn tail_file<W: Write>(file: &mut File, writer: &mut W, config: &TailConfig) -> io::Result<()> {
+    let metadata = file.metadata()?;
+    if metadata.is_dir() {
+        return Err(io::Error::other("Is a directory"));
+    }
+
+    let len = match input::seekable_len(&metadata) {
+        Some(len) if config.bytes || !config.from_start => len,
+        _ => {
+            let mut reader = BufReader::with_capacity(input::BUFFER_SIZE, &*file);
+            return tail_stream(&mut reader, writer, config);
+        }
+    };
+
+    let offset = match (config.bytes, config.from_start) {
+        (true, false) => len.saturating_sub(config.count),
+        (true, true) => config.count.saturating_sub(1).min(len),
+        (false, _) => stream::last_lines_offset(file, len, config.count, delimiter(config))?,
+    };
+    stream::copy_from(file, writer, offset)?;
+    Ok(())
+}
+
+fn main() {
+    let mut config = TailConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let headers = config.verbose || (!config.quiet && config.files.len() > 1);
+    let mut out = Output::stdout("tail");
+    let mut follower = config
+        .follow
+        .map(|mode| follow::Follower::new(mode, config.retry, headers));
+    let mut first = true;
+
+    for name in &config.files {
+        let mut header = |out: &mut Output<_>| {
+            if headers {
+                let separator = if first { "" } else { "\n" };
+                writeln!(out, "{}==> {} <==", separator, input::display_name(name));
+            }
+            first = false;
+        };
+
+        if name == input::STDIN {
+            header(&mut out);
+            let mut reader = BufReader::with_capacity(input::BUFFER_SIZE, io::stdin().lock());
+            if let Err(e) = tail_stream(&mut reader, &mut out, &config) {
+                out.flush();
+                eprintln!(
+                    "tail: error reading 'standard input': {}",
+                    output::describe(&e)
+                );
+                exit_code = 1;
+            }
+            if let Some(follower) = follower.as_mut() {
+                follower.shown(name);
+            }
+            if config.follow == Some(Follow::Name) {
+                out.flush();
+                eprintln!("tail: warning: cannot follow '-' by name");
+            }
+            continue;
+        }
+
+        let mut file = match File::open(name) {
+            Ok(file) => file,
+            Err(e) => {
+                out.flush();
+                eprintln!(
+                    "tail: cannot open '{}' for reading: {}",
+                    name,
+                    output::describe(&e)
+                );
+                exit_code = 1;
+                if let Some(follower) = follower.as_mut() {
+                    let _ = follower.add(name, None);
+                }
+                continue;
+            }
+        };
+
+        header(&mut out);
+        if let Err(e) = tail_file(&mut file, &mut out, &config) {
+            out.flush();
+            eprintln!("tail: error reading '{}': {}", name, output::describe(&e));
+            exit_code = 1;
+            continue;
+        }
+
+        if let Some(follower) = follower.as_mut() {
+            follower.shown(name);
+            if file.metadata().is_ok_and(|metadata| metadata.is_file()) {
+                if let Err(e) = follower.add(name, Some(file)) {
+                    out.flush();
+                    eprintln!("tail: cannot follow '{}': {}", name, output::describe(&e));
+                    exit_code = 1;
+                }
+            }
+        }
+    }
+    out.flush();
+
+    if let Some(mut follower) = follower {
+        if !follower.active() {
+            eprintln!("tail: no files remaining");
+            process::exit(1);
+        }
+        loop {
+            // Check first so output written just before the process exits
+            // is still picked up by this final poll.
+            let alive = config.pid.is_none_or(follow::process_alive);
+            for message in follower.poll(&mut out) {
+                out.flush();
+                eprintln!("tail: {}", message);
+            }
+            out.flush();
+
+            if !alive {
+                break;
+            }
+            if !follower.active() {
+                eprintln!("tail: no files remaining");
+                process::exit(1);
+            }
+            thread::sleep(config.sleep_interval);
+        }
+    }
+
+    process::exit(exit_code);
+}
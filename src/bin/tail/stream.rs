@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+const BLOCK_SIZE: u64 = 8 * 1024;
+
+/// Offset at which the last `count` lines of a seekable input begin, found
+/// by reading backward from the end one block at a time.
+pub fn last_lines_offset<R: Read + Seek>(
+    reader: &mut R,
+    len: u64,
+    count: u64,
+    delimiter: u8,
+) -> io::Result<u64> {
+    if count == 0 {
+        return Ok(len);
+    }
+
+    let mut block = vec![0; BLOCK_SIZE as usize];
+    let mut remaining = count;
+    let mut end = len;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE);
+        let block = &mut block[..(end - start) as usize];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(block)?;
+
+        for (pos, _) in block
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &b)| b == delimiter)
+        {
+            let offset = start + pos as u64 + 1;
+            // A delimiter at the very end closes the last line.
+            if offset == len {
+                continue;
+            }
+            remaining -= 1;
+            if remaining == 0 {
+                return Ok(offset);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// Copy everything from `offset` to the end, returning the final position.
+pub fn copy_from<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    offset: u64,
+) -> io::Result<u64> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let copied = io::copy(reader, writer)?;
+    Ok(offset + copied)
+}
+
+pub fn last_bytes<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+) -> io::Result<()> {
+    let mut held: VecDeque<u8> = VecDeque::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        held.extend(buf);
+        let len = buf.len();
+        reader.consume(len);
+
+        let excess = (held.len() as u64).saturating_sub(count) as usize;
+        held.drain(..excess);
+    }
+    let (front, back) = held.as_slices();
+    writer.write_all(front)?;
+    writer.write_all(back)
+}
+
+/// Keep the most recent `count` lines of a stream in a ring, reusing the
+/// buffer of each line that falls out.
+pub fn last_lines<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    count: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    let mut held: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut line = Vec::new();
+    while reader.read_until(delimiter, &mut line)? > 0 {
+        held.push_back(line);
+        line = if held.len() as u64 > count {
+            let mut oldest = held.pop_front().expect("ring is never empty here");
+            oldest.clear();
+            oldest
+        } else {
+            Vec::new()
+        };
+    }
+    for line in held {
+        writer.write_all(&line)?;
+    }
+    Ok(())
+}
+
+pub fn skip_bytes<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    skip: u64,
+) -> io::Result<()> {
+    io::copy(&mut reader.take(skip), &mut io::sink())?;
+    io::copy(reader, writer)?;
+    Ok(())
+}
+
+pub fn skip_lines<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    skip: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut remaining = skip;
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+        let mut end = buf.len();
+        for (pos, _) in buf.iter().enumerate().filter(|(_, &b)| b == delimiter) {
+            remaining -= 1;
+            if remaining == 0 {
+                end = pos + 1;
+                break;
+            }
+        }
+        reader.consume(end);
+    }
+    io::copy(reader, writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::test_utils;
+    use std::io::Cursor;
+
+    fn lines_offset(input: &str, count: u64) -> u64 {
+        let mut reader = Cursor::new(input.as_bytes());
+        last_lines_offset(&mut reader, input.len() as u64, count, b'\n').unwrap()
+    }
+
+    #[test]
+    fn offset_of_last_lines() {
+        assert_eq!(lines_offset("one\ntwo\nthree\n", 1), 8);
+        assert_eq!(lines_offset("one\ntwo\nthree\n", 2), 4);
+        assert_eq!(lines_offset("one\ntwo\nthree\n", 3), 0);
+        assert_eq!(lines_offset("one\ntwo\nthree\n", 10), 0);
+        assert_eq!(lines_offset("one\ntwo\nthree\n", 0), 14);
+    }
+
+    #[test]
+    fn offset_without_trailing_newline() {
+        assert_eq!(lines_offset("one\ntwo", 1), 4);
+        assert_eq!(lines_offset("one\ntwo", 2), 0);
+    }
+
+    #[test]
+    fn offset_across_blocks() {
+        let input: String = (0..5000).map(|n| format!("line {}\n", n)).collect();
+        let offset = lines_offset(&input, 3000);
+        assert!(input[offset as usize..].starts_with("line 2000\n"));
+    }
+
+    #[test]
+    fn copy_to_end() {
+        let mut reader = Cursor::new(test_utils::ASCII.as_bytes());
+        let mut output = Vec::new();
+        let position = copy_from(&mut reader, &mut output, 40).unwrap();
+        assert_eq!(output, b"dog\r\n");
+        assert_eq!(position, test_utils::ASCII.len() as u64);
+    }
+
+    fn run<F>(input: &str, f: F) -> String
+    where
+        F: Fn(&mut Cursor<&[u8]>, &mut Vec<u8>) -> io::Result<()>,
+    {
+        let mut reader = Cursor::new(input.as_bytes());
+        let mut output = Vec::new();
+        f(&mut reader, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn stream_last_lines() {
+        let output = run(test_utils::MULTI, |r, w| last_lines(r, w, 2, b'\n'));
+        assert_eq!(output, "with multiple lines\r\nof text to show.\r\n");
+        let output = run(test_utils::MULTI, |r, w| last_lines(r, w, 0, b'\n'));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn stream_last_bytes() {
+        let output = run(test_utils::ASCII, |r, w| last_bytes(r, w, 5));
+        assert_eq!(output, "dog\r\n");
+        let output = run(test_utils::ASCII, |r, w| last_bytes(r, w, 1000));
+        assert_eq!(output, test_utils::ASCII);
+    }
+
+    #[test]
+    fn stream_skip_lines() {
+        let output = run("one\ntwo\nthree\n", |r, w| skip_lines(r, w, 2, b'\n'));
+        assert_eq!(output, "three\n");
+        let output = run("one\ntwo\nthree\n", |r, w| skip_lines(r, w, 0, b'\n'));
+        assert_eq!(output, "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn stream_skip_bytes() {
+        let output = run(test_utils::ASCII, |r, w| skip_bytes(r, w, 40));
+        assert_eq!(output, "dog\r\n");
+    }
+}
@@ -0,0 +1,191 @@
+use coreutils::config::{self, Config};
+
+/// How to react to an output that cannot be written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputError {
+    /// Die quietly on a closed pipe; warn about other failures.
+    Sigpipe,
+    Warn,
+    WarnNoPipe,
+    Exit,
+    ExitNoPipe,
+}
+
+#[derive(Debug)]
+pub struct TeeConfig {
+    pub files: Vec<String>,
+    pub append: bool,
+    pub ignore_interrupts: bool,
+    pub output_error: OutputError,
+}
+
+const HELP_TEXT: &str = "Usage: tee [OPTION]... [FILE]...
+Copy standard input to each FILE, and also to standard output.
+
+  -a, --append              append to the given FILEs, do not overwrite
+  -i, --ignore-interrupts   ignore interrupt signals
+  -p                        operate in a more appropriate MODE with pipes
+      --output-error[=MODE]  set behavior on write error.  See MODE below
+      --help     display this help and exit
+      --version  output version information and exit
+
+MODE determines behavior with write errors on the outputs:
+  warn           diagnose errors writing to any output
+  warn-nopipe    diagnose errors writing to any output not a pipe
+  exit           exit on error writing to any output
+  exit-nopipe    exit on error writing to any output not a pipe
+The default MODE for the -p option is 'warn-nopipe'.
+With \"nopipe\" MODEs, exit immediately if all outputs become broken pipes.
+The default operation when --output-error is not specified, is to
+exit immediately on error writing to a pipe, and diagnose errors
+writing to non pipe outputs.
+";
+
+impl Config for TeeConfig {
+    fn bin_name(&self) -> &'static str {
+        "tee"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+impl TeeConfig {
+    pub fn new() -> TeeConfig {
+        TeeConfig {
+            files: Vec::new(),
+            append: false,
+            ignore_interrupts: false,
+            output_error: OutputError::Sigpipe,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--append", None) | ("-a", None) => self.append = true,
+                ("--ignore-interrupts", None) | ("-i", None) => self.ignore_interrupts = true,
+                ("--output-error", None) | ("-p", None) => {
+                    self.output_error = OutputError::WarnNoPipe
+                }
+                ("--output-error", Some(mode)) => {
+                    self.output_error = match mode {
+                        "warn" => OutputError::Warn,
+                        "warn-nopipe" => OutputError::WarnNoPipe,
+                        "exit" => OutputError::Exit,
+                        "exit-nopipe" => OutputError::ExitNoPipe,
+                        _ => {
+                            return Err(format!(
+                                "invalid argument '{}' for '--output-error'\n\
+                                 Valid arguments are: 'warn', 'warn-nopipe', 'exit', 'exit-nopipe'",
+                                mode
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try tee --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{OutputError, TeeConfig};
+    use coreutils::string_vec;
+
+    #[test]
+    fn defaults() {
+        let mut config = TeeConfig::new();
+        config.parse(string_vec!["tee.exe"]).unwrap();
+        assert!(config.files.is_empty());
+        assert!(!config.append);
+        assert!(!config.ignore_interrupts);
+        assert_eq!(config.output_error, OutputError::Sigpipe);
+    }
+
+    #[test]
+    fn files() {
+        let mut config = TeeConfig::new();
+        config.parse(string_vec!["tee.exe", "a.txt", "-"]).unwrap();
+        assert_eq!(config.files, vec!["a.txt", "-"]);
+    }
+
+    #[test]
+    fn append() {
+        let mut config = TeeConfig::new();
+        config.parse(string_vec!["tee.exe", "-a", "log"]).unwrap();
+        assert!(config.append);
+    }
+
+    #[test]
+    fn ignore_interrupts() {
+        let mut config = TeeConfig::new();
+        config
+            .parse(string_vec!["tee.exe", "--ignore-interrupts"])
+            .unwrap();
+        assert!(config.ignore_interrupts);
+    }
+
+    #[test]
+    fn pipe_mode() {
+        let mut config = TeeConfig::new();
+        config.parse(string_vec!["tee.exe", "-p"]).unwrap();
+        assert_eq!(config.output_error, OutputError::WarnNoPipe);
+    }
+
+    #[test]
+    fn output_error() {
+        let mut config = TeeConfig::new();
+        config
+            .parse(string_vec!["tee.exe", "--output-error"])
+            .unwrap();
+        assert_eq!(config.output_error, OutputError::WarnNoPipe);
+
+        for (mode, expected) in &[
+            ("warn", OutputError::Warn),
+            ("warn-nopipe", OutputError::WarnNoPipe),
+            ("exit", OutputError::Exit),
+            ("exit-nopipe", OutputError::ExitNoPipe),
+        ] {
+            let mut config = TeeConfig::new();
+            config
+                .parse(vec![
+                    "tee.exe".to_string(),
+                    format!("--output-error={}", mode),
+                ])
+                .unwrap();
+            assert_eq!(config.output_error, *expected);
+        }
+
+        assert!(config
+            .parse(string_vec!["tee.exe", "--output-error=never"])
+            .unwrap_err()
+            .contains("invalid argument 'never'"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = TeeConfig::new();
+        let parse_result = config.parse(string_vec!["tee.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = TeeConfig::new();
+        let parse_result = config.parse(string_vec!["tee.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,91 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, ErrorKind, Read};
+use std::process;
+
+use coreutils::output;
+
+mod config;
+mod outputs;
+
+use config::{OutputError, TeeConfig};
+use outputs::Outputs;
+
+fn ignore_interrupts() {
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_IGN);
+    }
+    #[cfg(windows)]
+    unsafe {
+        winapi::um::consoleapi::SetConsoleCtrlHandler(None, winapi::shared::minwindef::TRUE);
+    }
+}
+
+fn main() {
+    let mut config = TeeConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    if config.output_error == OutputError::Sigpipe {
+        output::restore_sigpipe();
+    }
+    if config.ignore_interrupts {
+        ignore_interrupts();
+    }
+
+    let mut exit_code = 0;
+    let mut outputs = Outputs::new(config.output_error);
+    outputs.add("standard output", Box::new(io::stdout()));
+    for name in &config.files {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(config.append)
+            .truncate(!config.append)
+            .open(name);
+        match file {
+            Ok(file) => outputs.add(name, Box::new(file)),
+            Err(e) => {
+                eprintln!("tee: {}: {}", name, output::describe(&e));
+                exit_code = 1;
+            }
+        }
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut buffer = vec![0; 8 * 1024];
+    while !outputs.is_empty() {
+        let len = match stdin.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => {
+                eprintln!("tee: standard input: {}", output::describe(&e));
+                exit_code = 1;
+                break;
+            }
+        };
+
+        match outputs.write(&buffer[..len]) {
+            Ok(warnings) => {
+                for warning in warnings {
+                    eprintln!("{}", warning);
+                }
+            }
+            Err((message, status)) => {
+                if let Some(message) = message {
+                    eprintln!("{}", message);
+                }
+                process::exit(status);
+            }
+        }
+    }
+
+    if outputs.failed {
+        exit_code = 1;
+    }
+    process::exit(exit_code);
+}
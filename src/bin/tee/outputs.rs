@@ -0,0 +1,207 @@
+use std::io::{self, ErrorKind, Write};
+
+use coreutils::output;
+
+use crate::config::OutputError;
+
+/// What to do after an output fails.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    /// Stop writing to that output, optionally reporting why.
+    Drop(Option<String>),
+    /// Give up entirely with the given status.
+    Exit(Option<String>, i32),
+}
+
+pub fn on_error(mode: OutputError, name: &str, err: &io::Error) -> Action {
+    let pipe = err.kind() == ErrorKind::BrokenPipe;
+    let message = format!("tee: {}: {}", name, output::describe(err));
+    match mode {
+        OutputError::Sigpipe if pipe => Action::Exit(None, output::BROKEN_PIPE),
+        OutputError::Sigpipe | OutputError::Warn => Action::Drop(Some(message)),
+        OutputError::WarnNoPipe | OutputError::ExitNoPipe if pipe => Action::Drop(None),
+        OutputError::WarnNoPipe => Action::Drop(Some(message)),
+        OutputError::Exit | OutputError::ExitNoPipe => {
+            Action::Exit(Some(message), output::WRITE_ERROR)
+        }
+    }
+}
+
+/// The set of outputs still being written to.
+pub struct Outputs {
+    outputs: Vec<(String, Box<dyn Write>)>,
+    mode: OutputError,
+    /// Set once a failure has been reported, making the exit status 1.
+    pub failed: bool,
+}
+
+impl Outputs {
+    pub fn new(mode: OutputError) -> Outputs {
+        Outputs {
+            outputs: Vec::new(),
+            mode,
+            failed: false,
+        }
+    }
+
+    pub fn add(&mut self, name: &str, writer: Box<dyn Write>) {
+        self.outputs.push((name.to_string(), writer));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Write `chunk` to every output and push it through immediately,
+    /// returning the warnings to print. An `Err` means stop now.
+    pub fn write(&mut self, chunk: &[u8]) -> Result<Vec<String>, (Option<String>, i32)> {
+        let mut warnings = Vec::new();
+        let mode = self.mode;
+        let mut idx = 0;
+        while idx < self.outputs.len() {
+            let (name, writer) = &mut self.outputs[idx];
+            let result = writer.write_all(chunk).and_then(|_| writer.flush());
+            match result.map_err(|e| on_error(mode, name, &e)) {
+                Ok(()) => idx += 1,
+                Err(Action::Drop(warning)) => {
+                    if let Some(warning) = warning {
+                        warnings.push(warning);
+                        self.failed = true;
+                    }
+                    self.outputs.remove(idx);
+                }
+                Err(Action::Exit(message, status)) => return Err((message, status)),
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Failing(ErrorKind);
+
+    impl Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(self.0, "failed"))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn broken_pipe() -> io::Error {
+        io::Error::new(ErrorKind::BrokenPipe, "Broken pipe")
+    }
+
+    fn full() -> io::Error {
+        io::Error::other("No space left on device")
+    }
+
+    #[test]
+    fn default_mode() {
+        assert_eq!(
+            on_error(OutputError::Sigpipe, "out", &broken_pipe()),
+            Action::Exit(None, output::BROKEN_PIPE)
+        );
+        assert_eq!(
+            on_error(OutputError::Sigpipe, "out", &full()),
+            Action::Drop(Some("tee: out: No space left on device".to_string()))
+        );
+    }
+
+    #[test]
+    fn warn_modes() {
+        assert_eq!(
+            on_error(OutputError::Warn, "out", &broken_pipe()),
+            Action::Drop(Some("tee: out: Broken pipe".to_string()))
+        );
+        assert_eq!(
+            on_error(OutputError::WarnNoPipe, "out", &broken_pipe()),
+            Action::Drop(None)
+        );
+        assert_eq!(
+            on_error(OutputError::WarnNoPipe, "out", &full()),
+            Action::Drop(Some("tee: out: No space left on device".to_string()))
+        );
+    }
+
+    #[test]
+    fn exit_modes() {
+        assert_eq!(
+            on_error(OutputError::Exit, "out", &broken_pipe()),
+            Action::Exit(Some("tee: out: Broken pipe".to_string()), 1)
+        );
+        assert_eq!(
+            on_error(OutputError::ExitNoPipe, "out", &broken_pipe()),
+            Action::Drop(None)
+        );
+        assert_eq!(
+            on_error(OutputError::ExitNoPipe, "out", &full()),
+            Action::Exit(Some("tee: out: No space left on device".to_string()), 1)
+        );
+    }
+
+    #[test]
+    fn writes_every_output() {
+        let first = Shared::default();
+        let second = Shared::default();
+        let mut outputs = Outputs::new(OutputError::Sigpipe);
+        outputs.add("first", Box::new(first.clone()));
+        outputs.add("second", Box::new(second.clone()));
+
+        assert!(outputs.write(b"hello ").unwrap().is_empty());
+        assert!(outputs.write(b"world").unwrap().is_empty());
+        assert_eq!(*first.0.borrow(), b"hello world");
+        assert_eq!(*second.0.borrow(), b"hello world");
+        assert!(!outputs.failed);
+    }
+
+    #[test]
+    fn continues_past_failed_output() {
+        let good = Shared::default();
+        let mut outputs = Outputs::new(OutputError::Sigpipe);
+        outputs.add("bad", Box::new(Failing(ErrorKind::Other)));
+        outputs.add("good", Box::new(good.clone()));
+
+        assert_eq!(outputs.write(b"one").unwrap(), vec!["tee: bad: failed"]);
+        assert!(outputs.write(b"two").unwrap().is_empty());
+        assert_eq!(*good.0.borrow(), b"onetwo");
+        assert!(outputs.failed);
+    }
+
+    #[test]
+    fn stops_when_every_pipe_is_gone() {
+        let mut outputs = Outputs::new(OutputError::WarnNoPipe);
+        outputs.add("pipe", Box::new(Failing(ErrorKind::BrokenPipe)));
+        assert!(outputs.write(b"data").unwrap().is_empty());
+        assert!(outputs.is_empty());
+        assert!(!outputs.failed);
+    }
+
+    #[test]
+    fn exit_on_error() {
+        let mut outputs = Outputs::new(OutputError::Exit);
+        outputs.add("bad", Box::new(Failing(ErrorKind::Other)));
+        assert_eq!(
+            outputs.write(b"data").unwrap_err(),
+            (Some("tee: bad: failed".to_string()), 1)
+        );
+    }
+}
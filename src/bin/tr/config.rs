@@ -0,0 +1,229 @@
+use coreutils::config::{self, Config};
+
+#[derive(Debug)]
+pub struct TrConfig {
+    pub set1: String,
+    pub set2: Option<String>,
+    pub complement: bool,
+    pub delete: bool,
+    pub squeeze: bool,
+    pub truncate: bool,
+}
+
+const HELP_TEXT: &str = "Usage: tr [OPTION]... SET1 [SET2]
+Translate, squeeze, and/or delete characters from standard input,
+writing to standard output.  SET1 and SET2 specify arrays of bytes
+that control the action.
+
+  -c, --complement        use the complement of SET1
+  -C                      same as -c
+  -d, --delete            delete bytes in SET1, do not translate
+  -s, --squeeze-repeats   replace each sequence of a repeated byte
+                            that is listed in the last specified SET,
+                            with a single occurrence of that byte
+  -t, --truncate-set1     first truncate SET1 to length of SET2
+      --help     display this help and exit
+      --version  output version information and exit
+
+SETs are specified as strings of characters.  Most represent themselves.
+Interpreted sequences are:
+
+  \\NNN            byte with octal value NNN (1 to 3 octal digits)
+  \\\\              backslash
+  \\a              audible BEL
+  \\b              backspace
+  \\f              form feed
+  \\n              new line
+  \\r              return
+  \\t              horizontal tab
+  \\v              vertical tab
+  CHAR1-CHAR2     all bytes from CHAR1 to CHAR2 in ascending order
+  [CHAR*]         in SET2, copies of CHAR until length of SET1
+  [CHAR*REPEAT]   REPEAT copies of CHAR, REPEAT octal if starting with 0
+  [:alnum:]       all letters and digits
+  [:alpha:]       all letters
+  [:blank:]       all horizontal whitespace
+  [:cntrl:]       all control characters
+  [:digit:]       all digits
+  [:graph:]       all printable characters, not including space
+  [:lower:]       all lower case letters
+  [:print:]       all printable characters, including space
+  [:punct:]       all punctuation characters
+  [:space:]       all horizontal or vertical whitespace
+  [:upper:]       all upper case letters
+  [:xdigit:]      all hexadecimal digits
+  [=CHAR=]        all characters which are equivalent to CHAR
+
+Translation occurs if -d is not given and both SET1 and SET2 appear.
+-t may be used only when translating.  SET2 is extended to length of
+SET1 by repeating its last byte as necessary.  Excess bytes of SET2
+are ignored.  Only [:lower:] and [:upper:] are guaranteed to expand in
+ascending order; used in SET2 while translating, they may only be used
+in pairs to specify case conversion.  -s uses SET1 if not translating
+nor deleting; else squeezing uses SET2 and occurs after translation or
+deletion.
+
+Input is treated as bytes, and classes cover ASCII only, as in the C
+locale; a multibyte character in a SET stands for each of its bytes.
+";
+
+impl Config for TrConfig {
+    fn bin_name(&self) -> &'static str {
+        "tr"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn usage_error(message: &str) -> String {
+    format!("{}\n Try tr --help for more information", message)
+}
+
+impl TrConfig {
+    pub fn new() -> TrConfig {
+        TrConfig {
+            set1: String::new(),
+            set2: None,
+            complement: false,
+            delete: false,
+            squeeze: false,
+            truncate: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--complement", None) | ("-c", None) | ("-C", None) => self.complement = true,
+                ("--delete", None) | ("-d", None) => self.delete = true,
+                ("--squeeze-repeats", None) | ("-s", None) => self.squeeze = true,
+                ("--truncate-set1", None) | ("-t", None) => self.truncate = true,
+                _ => {
+                    return Err(usage_error(&format!("invalid option: {}", option)));
+                }
+            }
+        }
+
+        // Translating needs two sets and deleting alone takes one; squeezing
+        // takes one more than whatever it accompanies.
+        let (needed, allowed) = match (self.delete, self.squeeze) {
+            (true, false) => (1, 1),
+            (true, true) => (2, 2),
+            (false, true) => (1, 2),
+            (false, false) => (2, 2),
+        };
+        let operands = base_config.parameters;
+        if operands.is_empty() {
+            return Err(usage_error("missing operand"));
+        }
+        if operands.len() < needed {
+            let action = if self.delete {
+                "both deleting and squeezing repeats"
+            } else {
+                "translating"
+            };
+            return Err(usage_error(&format!(
+                "missing operand after '{}'\nTwo strings must be given when {}.",
+                operands[0], action
+            )));
+        }
+        if let Some(extra) = operands.get(allowed) {
+            let mut message = format!("extra operand '{}'", extra);
+            if allowed == 1 {
+                message.push_str(
+                    "\nOnly one string may be given when deleting without squeezing repeats.",
+                );
+            }
+            return Err(usage_error(&message));
+        }
+
+        let mut operands = operands.into_iter();
+        self.set1 = operands.next().unwrap_or_default();
+        self.set2 = operands.next();
+        Ok(())
+    }
+
+    /// Whether SET1 is mapped onto SET2.
+    pub fn translating(&self) -> bool {
+        !self.delete && self.set2.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TrConfig;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<TrConfig, String> {
+        let mut config = TrConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn operands() {
+        let config = parse(string_vec!["tr.exe", "a-z", "A-Z"]).unwrap();
+        assert_eq!(config.set1, "a-z");
+        assert_eq!(config.set2.as_deref(), Some("A-Z"));
+        assert!(config.translating());
+
+        let config = parse(string_vec!["tr.exe", "-d", "abc"]).unwrap();
+        assert!(config.delete && !config.translating());
+        assert_eq!(config.set2, None);
+
+        let config = parse(string_vec!["tr.exe", "-s", "a"]).unwrap();
+        assert!(config.squeeze && !config.translating());
+
+        let config = parse(string_vec!["tr.exe", "-ds", "a", "b"]).unwrap();
+        assert!(config.delete && config.squeeze && !config.translating());
+
+        let config = parse(string_vec!["tr.exe", "-s", "a", "b"]).unwrap();
+        assert!(config.translating());
+
+        let config = parse(string_vec!["tr.exe", "--", "-a", "b"]).unwrap();
+        assert_eq!(config.set1, "-a");
+    }
+
+    #[test]
+    fn flags() {
+        let config = parse(string_vec!["tr.exe", "-Ct", "a", "b"]).unwrap();
+        assert!(config.complement && config.truncate);
+        let config = parse(string_vec!["tr.exe", "--complement", "-d", "a"]).unwrap();
+        assert!(config.complement && config.delete);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: Vec<String>| parse(args).unwrap_err();
+        assert!(error(string_vec!["tr.exe"]).contains("missing operand"));
+        assert!(error(string_vec!["tr.exe", "a"])
+            .contains("missing operand after 'a'\nTwo strings must be given when translating."));
+        assert!(error(string_vec!["tr.exe", "-ds", "a"])
+            .contains("when both deleting and squeezing repeats"));
+        assert!(error(string_vec!["tr.exe", "-d", "a", "b"]).contains(
+            "extra operand 'b'\nOnly one string may be given when deleting without squeezing"
+        ));
+        let message = error(string_vec!["tr.exe", "a", "b", "c"]);
+        assert!(message.contains("extra operand 'c'"));
+        assert!(!message.contains("Only one string"));
+        assert!(error(string_vec!["tr.exe", "-x", "a", "b"]).contains("invalid option: -x"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = TrConfig::new();
+        let parse_result = config.parse(string_vec!["tr.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = TrConfig::new();
+        let parse_result = config.parse(string_vec!["tr.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+use std::io;
+use std::process;
+
+use coreutils::output::{self, Output};
+
+mod config;
+mod sets;
+mod translate;
+
+use config::TrConfig;
+use translate::Tables;
+
+fn main() {
+    let mut config = TrConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut warnings = Vec::new();
+    let tables = Tables::new(&config, &mut warnings);
+    for warning in warnings {
+        eprintln!("tr: {}", warning);
+    }
+    let tables = tables.unwrap_or_else(|e| {
+        eprintln!("tr: {}", e);
+        process::exit(1);
+    });
+
+    let mut out = Output::stdout("tr");
+    if let Err(e) = tables.run(io::stdin().lock(), &mut out) {
+        out.flush();
+        eprintln!("tr: read error: {}", output::describe(&e));
+        process::exit(1);
+    }
+    out.flush();
+}
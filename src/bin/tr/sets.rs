@@ -0,0 +1,429 @@
+/// A `[:name:]` character class, with the members it has in the C locale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Alnum,
+    Alpha,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Xdigit,
+}
+
+impl Class {
+    fn from_name(name: &[u8]) -> Option<Class> {
+        Some(match name {
+            b"alnum" => Class::Alnum,
+            b"alpha" => Class::Alpha,
+            b"blank" => Class::Blank,
+            b"cntrl" => Class::Cntrl,
+            b"digit" => Class::Digit,
+            b"graph" => Class::Graph,
+            b"lower" => Class::Lower,
+            b"print" => Class::Print,
+            b"punct" => Class::Punct,
+            b"space" => Class::Space,
+            b"upper" => Class::Upper,
+            b"xdigit" => Class::Xdigit,
+            _ => return None,
+        })
+    }
+
+    pub fn contains(self, byte: u8) -> bool {
+        match self {
+            Class::Alnum => byte.is_ascii_alphanumeric(),
+            Class::Alpha => byte.is_ascii_alphabetic(),
+            Class::Blank => matches!(byte, b' ' | b'\t'),
+            Class::Cntrl => byte.is_ascii_control(),
+            Class::Digit => byte.is_ascii_digit(),
+            Class::Graph => byte.is_ascii_graphic(),
+            Class::Lower => byte.is_ascii_lowercase(),
+            Class::Print => byte.is_ascii_graphic() || byte == b' ',
+            Class::Punct => byte.is_ascii_punctuation(),
+            // Unlike is_ascii_whitespace, this includes the vertical tab.
+            Class::Space => matches!(byte, b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r'),
+            Class::Upper => byte.is_ascii_uppercase(),
+            Class::Xdigit => byte.is_ascii_hexdigit(),
+        }
+    }
+
+    /// The members in ascending order.
+    pub fn members(self) -> impl Iterator<Item = u8> {
+        (0..=u8::MAX).filter(move |&byte| self.contains(byte))
+    }
+
+    /// `[:upper:]` and `[:lower:]` may pair up for case conversion.
+    pub fn is_case(self) -> bool {
+        matches!(self, Class::Upper | Class::Lower)
+    }
+}
+
+/// One construct of a set operand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Element {
+    Byte(u8),
+    /// `CHAR1-CHAR2`, both ends included.
+    Range(u8, u8),
+    Class(Class),
+    /// `[=CHAR=]`, which in the C locale is just CHAR.
+    Equiv(u8),
+    /// `[CHAR*REPEAT]`; None for `[CHAR*]`, which fills SET2 out to the
+    /// length of SET1.
+    Repeat(u8, Option<usize>),
+}
+
+impl Element {
+    /// How many positions of the array the element takes up. A fill
+    /// counts as none until its length is known.
+    pub fn len(&self) -> usize {
+        match *self {
+            Element::Byte(_) | Element::Equiv(_) => 1,
+            Element::Range(first, last) => usize::from(last - first) + 1,
+            Element::Class(class) => class.members().count(),
+            Element::Repeat(_, count) => count.unwrap_or(0),
+        }
+    }
+}
+
+/// A set operand with its escapes expanded. Escaped bytes never start or
+/// end a construct, so `\-` and `\[` stand for themselves.
+struct Unescaped {
+    bytes: Vec<u8>,
+    escaped: Vec<bool>,
+}
+
+impl Unescaped {
+    fn new(text: &str, warnings: &mut Vec<String>) -> Unescaped {
+        let text = text.as_bytes();
+        let mut unescaped = Unescaped {
+            bytes: Vec::with_capacity(text.len()),
+            escaped: Vec::with_capacity(text.len()),
+        };
+        let mut idx = 0;
+        while idx < text.len() {
+            if text[idx] != b'\\' {
+                unescaped.push(text[idx], false);
+                idx += 1;
+                continue;
+            }
+            let (byte, used) = escape(&text[idx + 1..], warnings);
+            unescaped.push(byte, true);
+            idx += 1 + used;
+        }
+        unescaped
+    }
+
+    fn push(&mut self, byte: u8, escaped: bool) {
+        self.bytes.push(byte);
+        self.escaped.push(escaped);
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// True when the byte at `idx` is an unescaped `byte`.
+    fn is(&self, idx: usize, byte: u8) -> bool {
+        idx < self.len() && self.bytes[idx] == byte && !self.escaped[idx]
+    }
+
+    /// The index of the first unescaped `delimiter` followed by `]` at or
+    /// after `start`.
+    fn closing(&self, start: usize, delimiter: u8) -> Option<usize> {
+        (start..self.len()).find(|&idx| self.is(idx, delimiter) && self.is(idx + 1, b']'))
+    }
+}
+
+/// The byte the escape after a backslash stands for, and how many bytes
+/// of `rest` it used.
+fn escape(rest: &[u8], warnings: &mut Vec<String>) -> (u8, usize) {
+    let byte = match rest.first() {
+        None => {
+            warnings.push(String::from(
+                "warning: an unescaped backslash at end of string is not portable",
+            ));
+            return (b'\\', 0);
+        }
+        Some(&byte) => byte,
+    };
+    let value = match byte {
+        b'a' => 0x07,
+        b'b' => 0x08,
+        b'f' => 0x0c,
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'v' => 0x0b,
+        b'0'..=b'7' => {
+            let digits = rest
+                .iter()
+                .take(3)
+                .take_while(|b| (b'0'..=b'7').contains(b))
+                .count();
+            let value = |len: usize| {
+                rest[..len]
+                    .iter()
+                    .fold(0u32, |value, b| value * 8 + u32::from(b - b'0'))
+            };
+            if digits == 3 && value(3) > 0o377 {
+                // Three digits would not fit a byte, so only two are used.
+                let shown = String::from_utf8_lossy(&rest[..3]);
+                warnings.push(format!(
+                    "warning: the ambiguous octal escape \\{} is being\n\tinterpreted as the 2-byte sequence \\0{}, {}",
+                    shown,
+                    &shown[..2],
+                    &shown[2..]
+                ));
+                return (value(2) as u8, 2);
+            }
+            return (value(digits) as u8, digits);
+        }
+        other => other,
+    };
+    (value, 1)
+}
+
+fn printable(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// `[CHAR*REPEAT]` or `[CHAR*]` starting with the bracket at `start`:
+/// the element and the index of the closing bracket. None when the text
+/// is not shaped like one; an error when its count is not a number.
+fn repeat(set: &Unescaped, start: usize) -> Result<Option<(Element, usize)>, String> {
+    if !set.is(start + 2, b'*') {
+        return Ok(None);
+    }
+    let mut idx = start + 3;
+    while idx < set.len() && !set.escaped[idx] {
+        if set.bytes[idx] == b']' {
+            let digits = &set.bytes[start + 3..idx];
+            let count = match digits {
+                [] => None,
+                _ => {
+                    let radix = if digits[0] == b'0' { 8 } else { 10 };
+                    let count = std::str::from_utf8(digits)
+                        .ok()
+                        .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+                        .and_then(|digits| usize::from_str_radix(digits, radix).ok())
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid repeat count '{}' in [c*n] construct",
+                                printable(digits)
+                            )
+                        })?;
+                    Some(count).filter(|&count| count > 0)
+                }
+            };
+            return Ok(Some((Element::Repeat(set.bytes[start + 1], count), idx)));
+        }
+        idx += 1;
+    }
+    Ok(None)
+}
+
+/// Parse a SET operand into its constructs, in order. Warnings about
+/// unportable escapes are added to `warnings`.
+pub fn parse(text: &str, warnings: &mut Vec<String>) -> Result<Vec<Element>, String> {
+    let set = Unescaped::new(text, warnings);
+    let mut elements = Vec::new();
+    let mut idx = 0;
+    // A construct needs at least three bytes; anything shorter at the end
+    // stands for itself.
+    while idx + 2 < set.len() {
+        if set.is(idx, b'[') {
+            let kind = set.bytes[idx + 1];
+            if set.is(idx + 1, b':') || set.is(idx + 1, b'=') {
+                if let Some(close) = set.closing(idx + 2, kind) {
+                    let operand = &set.bytes[idx + 2..close];
+                    let element = match (kind, operand) {
+                        (b':', []) => {
+                            return Err(String::from("missing character class name '[::]'"))
+                        }
+                        (b'=', []) => {
+                            return Err(String::from("missing equivalence class character '[==]'"))
+                        }
+                        (b':', name) => Class::from_name(name).map(Element::Class),
+                        (_, &[byte]) => Some(Element::Equiv(byte)),
+                        _ => None,
+                    };
+                    match element {
+                        Some(element) => {
+                            elements.push(element);
+                            idx = close + 2;
+                            continue;
+                        }
+                        // `[:*2]` and `[=*]` are repeats of `:` and `=`.
+                        None if repeat(&set, idx)?.is_none() => {
+                            return Err(match kind {
+                                b':' => {
+                                    format!("invalid character class '{}'", printable(operand))
+                                }
+                                _ => format!(
+                                    "{}: equivalence class operand must be a single character",
+                                    printable(operand)
+                                ),
+                            });
+                        }
+                        None => {}
+                    }
+                }
+            }
+            if let Some((element, close)) = repeat(&set, idx)? {
+                elements.push(element);
+                idx = close + 1;
+                continue;
+            }
+        }
+
+        if set.is(idx + 1, b'-') {
+            let (first, last) = (set.bytes[idx], set.bytes[idx + 2]);
+            if last < first {
+                return Err(format!(
+                    "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                    printable(&[first]),
+                    printable(&[last])
+                ));
+            }
+            elements.push(Element::Range(first, last));
+            idx += 3;
+        } else {
+            elements.push(Element::Byte(set.bytes[idx]));
+            idx += 1;
+        }
+    }
+    elements.extend(set.bytes[idx..].iter().map(|&byte| Element::Byte(byte)));
+    Ok(elements)
+}
+
+/// Which bytes appear anywhere in `elements`.
+pub fn members(elements: &[Element]) -> [bool; 256] {
+    let mut members = [false; 256];
+    for element in elements {
+        match *element {
+            Element::Byte(byte) | Element::Equiv(byte) | Element::Repeat(byte, _) => {
+                members[usize::from(byte)] = true
+            }
+            Element::Range(first, last) => {
+                for byte in first..=last {
+                    members[usize::from(byte)] = true;
+                }
+            }
+            Element::Class(class) => {
+                for byte in class.members() {
+                    members[usize::from(byte)] = true;
+                }
+            }
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_ok(text: &str) -> Vec<Element> {
+        let mut warnings = Vec::new();
+        let elements = parse(text, &mut warnings).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        elements
+    }
+
+    fn parse_err(text: &str) -> String {
+        parse(text, &mut Vec::new()).unwrap_err()
+    }
+
+    #[test]
+    fn bytes_and_ranges() {
+        use Element::*;
+        assert_eq!(parse_ok("ab"), vec![Byte(b'a'), Byte(b'b')]);
+        assert_eq!(parse_ok("a-z0"), vec![Range(b'a', b'z'), Byte(b'0')]);
+        assert_eq!(parse_ok("a-a"), vec![Range(b'a', b'a')]);
+        assert_eq!(parse_ok("--a-"), vec![Range(b'-', b'a'), Byte(b'-')]);
+        assert_eq!(parse_ok("a-"), vec![Byte(b'a'), Byte(b'-')]);
+        assert_eq!(parse_ok("a\\-z"), vec![Byte(b'a'), Byte(b'-'), Byte(b'z')]);
+        assert_eq!(parse_ok("\\n-\\r"), vec![Range(b'\n', b'\r')]);
+        assert!(parse_err("z-a").contains("'z-a' are in reverse collating sequence order"));
+    }
+
+    #[test]
+    fn escapes() {
+        use Element::*;
+        assert_eq!(
+            parse_ok("\\a\\b\\f\\n\\r\\t\\v\\\\\\q"),
+            [7, 8, 12, 10, 13, 9, 11, b'\\', b'q'].map(Byte)
+        );
+        assert_eq!(parse_ok("\\0\\101\\1012"), [0, b'A', b'A', b'2'].map(Byte));
+        assert_eq!(parse_ok("\\8"), vec![Byte(b'8')]);
+
+        let mut warnings = Vec::new();
+        let elements = parse("\\400a\\", &mut warnings).unwrap();
+        assert_eq!(elements, [0o40, b'0', b'a', b'\\'].map(Byte));
+        assert!(warnings[0].contains("interpreted as the 2-byte sequence \\040, 0"));
+        assert!(warnings[1].contains("unescaped backslash at end of string"));
+    }
+
+    #[test]
+    fn classes() {
+        use Element::*;
+        assert_eq!(
+            parse_ok("[:alpha:][:digit:]x"),
+            vec![
+                Class(super::Class::Alpha),
+                Class(super::Class::Digit),
+                Byte(b'x')
+            ]
+        );
+        assert_eq!(parse_ok("[=a=]"), vec![Equiv(b'a')]);
+        assert!(parse_err("[:bogus:]").contains("invalid character class 'bogus'"));
+        assert!(parse_err("[::]").contains("missing character class name"));
+        assert!(parse_err("[==]").contains("missing equivalence class character"));
+        assert!(parse_err("[=ab=]").contains("ab: equivalence class operand must be a single"));
+        // Unclosed or escaped brackets stand for themselves.
+        assert_eq!(parse_ok("[:al").len(), 4);
+        assert_eq!(parse_ok("\\[:alpha:]").len(), 9);
+    }
+
+    #[test]
+    fn class_members() {
+        let count = |class: super::Class| class.members().count();
+        assert_eq!(count(Class::Alnum), 62);
+        assert_eq!(count(Class::Punct), 32);
+        assert_eq!(count(Class::Space), 6);
+        assert_eq!(count(Class::Cntrl), 33);
+        assert_eq!(count(Class::Print), 95);
+        assert_eq!(count(Class::Xdigit), 22);
+        assert_eq!(Element::Class(Class::Upper).len(), 26);
+    }
+
+    #[test]
+    fn repeats() {
+        use Element::*;
+        assert_eq!(parse_ok("[a*]"), vec![Repeat(b'a', None)]);
+        assert_eq!(parse_ok("[a*0]"), vec![Repeat(b'a', None)]);
+        assert_eq!(parse_ok("[a*3]"), vec![Repeat(b'a', Some(3))]);
+        assert_eq!(parse_ok("[a*010]"), vec![Repeat(b'a', Some(8))]);
+        assert_eq!(parse_ok("[\\n*2]"), vec![Repeat(b'\n', Some(2))]);
+        assert_eq!(parse_ok("[:*2]"), vec![Repeat(b':', Some(2))]);
+        assert_eq!(parse_ok("[=*]"), vec![Repeat(b'=', None)]);
+        assert!(parse_err("[a*9x]").contains("invalid repeat count '9x' in [c*n] construct"));
+        assert!(parse_err("[a*08]").contains("invalid repeat count '08'"));
+        // Not shaped like a repeat: the bracket stands for itself.
+        assert_eq!(parse_ok("[a*")[0], Byte(b'['));
+    }
+
+    #[test]
+    fn membership() {
+        let members = members(&parse_ok("a-c[:digit:]\\n"));
+        let count = members.iter().filter(|&&member| member).count();
+        assert_eq!(count, 14);
+        assert!(members[usize::from(b'b')] && members[usize::from(b'7')] && members[10]);
+        assert!(!members[usize::from(b'd')]);
+    }
+}
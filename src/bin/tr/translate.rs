@@ -0,0 +1,418 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use coreutils::input;
+
+use crate::config::TrConfig;
+use crate::sets::{self, Class, Element};
+
+/// A set expanded into the array of bytes it stands for. `cases` marks
+/// the start of each `[:lower:]` and `[:upper:]` class, which pair up as a
+/// whole when translating.
+struct Array {
+    bytes: Vec<u8>,
+    cases: Vec<Option<Class>>,
+}
+
+impl Array {
+    /// Expand `elements` with `fill` copies for a `[c*]`, stopping after
+    /// `limit` bytes so that large repeat counts cost nothing.
+    fn expand(elements: &[Element], fill: usize, limit: usize, cases: bool) -> Array {
+        let mut array = Array {
+            bytes: Vec::new(),
+            cases: Vec::new(),
+        };
+        for element in elements {
+            let room = limit - array.len();
+            match *element {
+                Element::Byte(byte) | Element::Equiv(byte) => array.push(byte),
+                Element::Range(first, last) => (first..=last).for_each(|byte| array.push(byte)),
+                Element::Class(class) => {
+                    let start = array.len();
+                    class.members().for_each(|byte| array.push(byte));
+                    if cases && class.is_case() {
+                        array.cases[start] = Some(class);
+                    }
+                }
+                Element::Repeat(byte, count) => {
+                    let count = count.unwrap_or(fill).min(room);
+                    array.bytes.resize(array.len() + count, byte);
+                    array.cases.resize(array.len(), None);
+                }
+            }
+            if array.len() >= limit {
+                array.bytes.truncate(limit);
+                array.cases.truncate(limit);
+                break;
+            }
+        }
+        array
+    }
+
+    /// The bytes in `members`, in ascending order.
+    fn members(members: &[bool; 256]) -> Array {
+        let bytes: Vec<u8> = (0..=u8::MAX)
+            .filter(|&byte| members[usize::from(byte)])
+            .collect();
+        Array {
+            cases: vec![None; bytes.len()],
+            bytes,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.bytes.push(byte);
+        self.cases.push(None);
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+/// Map each byte of SET1 onto the byte of SET2 in the same position,
+/// applying GNU's rules for short, long and case converting SET2s.
+fn translation(
+    set1: &[Element],
+    members1: &[bool; 256],
+    set2: &[Element],
+    config: &TrConfig,
+) -> Result<[u8; 256], String> {
+    if set2
+        .iter()
+        .any(|element| matches!(element, Element::Equiv(_)))
+    {
+        return Err(String::from(
+            "[=c=] expressions may not appear in string2 when translating",
+        ));
+    }
+    if set2
+        .iter()
+        .any(|element| matches!(element, Element::Class(class) if !class.is_case()))
+    {
+        return Err(String::from(
+            "when translating, the only character classes that may appear in\nstring2 are 'upper' and 'lower'",
+        ));
+    }
+
+    let array1 = if config.complement {
+        Array::members(members1)
+    } else {
+        Array::expand(set1, 0, usize::MAX, true)
+    };
+    let len2 = set2.iter().map(Element::len).fold(0, usize::saturating_add);
+    let fill = array1.len().saturating_sub(len2);
+    let mut array2 = Array::expand(set2, fill, array1.len() + 1, !config.complement);
+
+    if array2.len() < array1.len() && !config.truncate {
+        let last = *array2
+            .bytes
+            .last()
+            .ok_or("when not truncating set1, string2 must be non-empty")?;
+        while array2.len() < array1.len() {
+            array2.push(last);
+        }
+    }
+
+    // Every byte outside a complemented class has to go somewhere, and
+    // the order of a class is unspecified, so they must all go to one.
+    let has_class = set1
+        .iter()
+        .any(|element| matches!(element, Element::Class(_)));
+    if config.complement && has_class {
+        let homogeneous = array2.bytes.iter().all(|&byte| byte == array2.bytes[0]);
+        if array2.len() != array1.len() || !homogeneous {
+            return Err(String::from(
+                "when translating with complemented character classes,\nstring2 must map all characters in the domain to one",
+            ));
+        }
+    }
+
+    let mut map = identity();
+    let (mut idx1, mut idx2) = (0, 0);
+    while idx1 < array1.len() && idx2 < array2.len() {
+        match (array1.cases[idx1], array2.cases[idx2]) {
+            (Some(from), Some(to)) => {
+                for byte in from.members() {
+                    map[usize::from(byte)] = match to {
+                        Class::Upper => byte.to_ascii_uppercase(),
+                        _ => byte.to_ascii_lowercase(),
+                    };
+                }
+                idx1 += from.members().count();
+                idx2 += to.members().count();
+            }
+            (None, Some(_)) => {
+                return Err(String::from(
+                    "misaligned [:upper:] and/or [:lower:] construct",
+                ))
+            }
+            _ => {
+                map[usize::from(array1.bytes[idx1])] = array2.bytes[idx2];
+                idx1 += 1;
+                idx2 += 1;
+            }
+        }
+    }
+    Ok(map)
+}
+
+fn identity() -> [u8; 256] {
+    let mut map = [0; 256];
+    for (idx, byte) in map.iter_mut().enumerate() {
+        *byte = idx as u8;
+    }
+    map
+}
+
+/// Everything `tr` does to a byte, decided up front so that each input
+/// byte costs a few table lookups.
+pub struct Tables {
+    map: [u8; 256],
+    delete: [bool; 256],
+    squeeze: [bool; 256],
+}
+
+impl Tables {
+    /// Build the tables for `config`. Warnings about its sets are added to
+    /// `warnings` even when the sets turn out to be unusable.
+    pub fn new(config: &TrConfig, warnings: &mut Vec<String>) -> Result<Tables, String> {
+        let set1 = sets::parse(&config.set1, warnings)?;
+        let set2 = match &config.set2 {
+            Some(text) => Some(sets::parse(text, warnings)?),
+            None => None,
+        };
+
+        // A repeat with a count is just that many bytes; only one filling
+        // out the set belongs in SET2.
+        if set1
+            .iter()
+            .any(|element| matches!(element, Element::Repeat(_, None)))
+        {
+            return Err(String::from(
+                "the [c*] repeat construct may not appear in string1",
+            ));
+        }
+        if let Some(set2) = &set2 {
+            let fills = set2
+                .iter()
+                .filter(|element| matches!(element, Element::Repeat(_, None)))
+                .count();
+            if fills > 1 {
+                return Err(String::from(
+                    "only one [c*] repeat construct may appear in string2",
+                ));
+            }
+            if fills > 0 && !config.translating() {
+                return Err(String::from(
+                    "the [c*] construct may appear in string2 only when translating",
+                ));
+            }
+        }
+
+        let mut members1 = sets::members(&set1);
+        if config.complement {
+            members1.iter_mut().for_each(|member| *member = !*member);
+        }
+
+        let mut tables = Tables {
+            map: identity(),
+            delete: [false; 256],
+            squeeze: [false; 256],
+        };
+        match &set2 {
+            Some(set2) if config.translating() => {
+                tables.map = translation(&set1, &members1, set2, config)?
+            }
+            _ => {}
+        }
+        if config.delete {
+            tables.delete = members1;
+        }
+        if config.squeeze {
+            tables.squeeze = match &set2 {
+                Some(set2) => sets::members(set2),
+                None => members1,
+            };
+        }
+        Ok(tables)
+    }
+
+    /// Copy `input` to `out`, translating, deleting and squeezing.
+    pub fn run<R: Read, W: Write>(&self, mut input: R, out: &mut W) -> io::Result<()> {
+        let filtering = self.delete.contains(&true) || self.squeeze.contains(&true);
+        let mut buffer = vec![0; input::BUFFER_SIZE];
+        let mut kept = Vec::with_capacity(input::BUFFER_SIZE);
+        let mut last = None;
+        loop {
+            let len = match input.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(len) => len,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let chunk = &mut buffer[..len];
+            if !filtering {
+                for byte in chunk.iter_mut() {
+                    *byte = self.map[usize::from(*byte)];
+                }
+                out.write_all(chunk)?;
+                continue;
+            }
+
+            kept.clear();
+            for &byte in chunk.iter() {
+                if self.delete[usize::from(byte)] {
+                    continue;
+                }
+                let byte = self.map[usize::from(byte)];
+                if self.squeeze[usize::from(byte)] && last == Some(byte) {
+                    continue;
+                }
+                last = Some(byte);
+                kept.push(byte);
+            }
+            out.write_all(&kept)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn tables(args: &[&str]) -> Result<Tables, String> {
+        let mut all: Vec<String> = string_vec!["tr.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = TrConfig::new();
+        config.parse(all).unwrap();
+        Tables::new(&config, &mut Vec::new())
+    }
+
+    fn tr(args: &[&str], input: &str) -> String {
+        let mut out = Vec::new();
+        tables(args)
+            .unwrap()
+            .run(input.as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn error(args: &[&str]) -> String {
+        tables(args).err().unwrap()
+    }
+
+    #[test]
+    fn translate() {
+        assert_eq!(tr(&["abc", "xyz"], "aabbcc-d"), "xxyyzz-d");
+        assert_eq!(tr(&["a-z", "A-Z"], "Hello, World"), "HELLO, WORLD");
+        assert_eq!(tr(&["\\n", " "], "a\nb\n"), "a b ");
+        // A later mapping of the same byte wins.
+        assert_eq!(tr(&["aa", "xy"], "a"), "y");
+    }
+
+    #[test]
+    fn short_set2_is_extended() {
+        assert_eq!(tr(&["abcd", "xy"], "abcd"), "xyyy");
+        assert_eq!(tr(&["a-z", "_"], "tr -d"), "__ -_");
+        assert!(error(&["abc", ""]).contains("when not truncating set1, string2 must be non-empty"));
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!(tr(&["-t", "abcd", "xy"], "abcd"), "xycd");
+        assert_eq!(tr(&["-t", "abc", ""], "abc"), "abc");
+        // Excess bytes of SET2 are ignored either way.
+        assert_eq!(tr(&["-t", "ab", "wxyz"], "ab"), "wx");
+        assert_eq!(tr(&["ab", "wxyz"], "ab"), "wx");
+    }
+
+    #[test]
+    fn repeats() {
+        assert_eq!(tr(&["a-e", "[x*]"], "abcdef"), "xxxxxf");
+        assert_eq!(tr(&["a-e", "x[y*]z"], "abcdef"), "xyyyzf");
+        assert_eq!(tr(&["a-e", "[x*2]y"], "abcdef"), "xxyyyf");
+        assert_eq!(tr(&["a-j", "[x*010]y"], "hij"), "xyy");
+        assert_eq!(tr(&["ab", "[x*99999999999]"], "ab"), "xx");
+        assert!(error(&["[a*]", "x"]).contains("may not appear in string1"));
+        assert!(error(&["[a*0]", "x"]).contains("may not appear in string1"));
+        assert_eq!(tr(&["[a*3]", "x"], "abc"), "xbc");
+        assert_eq!(tr(&["[a*2]b", "xyz"], "abc"), "yzc");
+        assert_eq!(tr(&["b[a*2]c", "wxyz"], "abcd"), "ywzd");
+        assert_eq!(tr(&["a[b*3]", "x[y*]"], "abc"), "xyc");
+        assert_eq!(tr(&["-d", "a[b*2]"], "abc"), "c");
+        assert!(error(&["a", "[x*][y*]"]).contains("only one [c*] repeat construct"));
+        assert!(error(&["-ds", "a", "[x*]"]).contains("only when translating"));
+    }
+
+    #[test]
+    fn case_classes() {
+        assert_eq!(tr(&["[:lower:]", "[:upper:]"], "Hello 1"), "HELLO 1");
+        assert_eq!(tr(&["[:upper:]", "[:lower:]"], "Hello 1"), "hello 1");
+        assert_eq!(tr(&["[:upper:]", "[:upper:]"], "Hello"), "Hello");
+        assert_eq!(tr(&["0[:lower:]", "_[:upper:]"], "0ab"), "_AB");
+        // A class in SET1 alone is just its bytes in order.
+        assert_eq!(tr(&["[:digit:]", "a-j"], "2024"), "cace");
+        assert_eq!(tr(&["[:upper:]", "a-c"], "ABCDZ"), "abccc");
+        // SET2 is extended with the last byte of its class.
+        assert_eq!(tr(&["[:lower:]0", "[:upper:]"], "az0"), "AZZ");
+    }
+
+    #[test]
+    fn class_alignment() {
+        let misaligned = "misaligned [:upper:] and/or [:lower:] construct";
+        assert!(error(&["a-z", "[:upper:]"]).contains(misaligned));
+        assert!(error(&["[:lower:]", "A[:upper:]"]).contains(misaligned));
+        assert!(error(&["ab[:lower:]", "[:upper:]xy"]).contains(misaligned));
+        assert!(error(&["a", "[:digit:]"]).contains("only character classes"));
+        assert!(error(&["a", "[=a=]"]).contains("[=c=] expressions may not appear"));
+    }
+
+    #[test]
+    fn complement() {
+        assert_eq!(tr(&["-c", "a-z\\n", "_"], "hi there!\n"), "hi_there_\n");
+        assert_eq!(tr(&["-c", "[:alpha:]", "[_*]"], "a1b"), "a_b");
+        assert_eq!(tr(&["-C", "[:alnum:]", "\\n"], "a b"), "a\nb");
+        assert!(error(&["-c", "[:alpha:]", "xy"]).contains("must map all characters"));
+        assert!(error(&["-ct", "[:alpha:]", "x"]).contains("must map all characters"));
+        // Without a class the order is known, so any SET2 will do.
+        assert_eq!(tr(&["-c", "\\001-\\377", "xy"], "\0a"), "xa");
+    }
+
+    #[test]
+    fn delete() {
+        assert_eq!(tr(&["-d", "a-c"], "abcdcba"), "d");
+        assert_eq!(tr(&["-d", "[:digit:][:space:]"], "1 a\t2 b\n"), "ab");
+        assert_eq!(tr(&["-dc", "a-z\\n"], "Hi, there!\n"), "ithere\n");
+    }
+
+    #[test]
+    fn squeeze() {
+        assert_eq!(tr(&["-s", " "], "a   b  c"), "a b c");
+        assert_eq!(tr(&["-s", "a-z"], "aabbbc  dd"), "abc  d");
+        assert_eq!(tr(&["-sc", "a"], "aa  bb"), "aa b");
+        // With two sets, SET2 is squeezed after translating.
+        assert_eq!(tr(&["-s", "ab", "xx"], "aabbc"), "xc");
+        assert_eq!(tr(&["-ds", "0-9", " "], "1  2 3  x"), " x");
+    }
+
+    #[test]
+    fn squeeze_across_reads() {
+        let tables = tables(&["-s", "a"]).unwrap();
+        let input = "a".repeat(input::BUFFER_SIZE * 2 + 3);
+        let mut out = Vec::new();
+        tables.run(input.as_bytes(), &mut out).unwrap();
+        assert_eq!(out, b"a");
+    }
+
+    #[test]
+    fn bytes() {
+        let mut out = Vec::new();
+        let input: Vec<u8> = vec![0x00, 0x80, 0xff, b'a'];
+        tables(&["\\000\\200\\377", "xyz"])
+            .unwrap()
+            .run(&input[..], &mut out)
+            .unwrap();
+        assert_eq!(out, b"xyza");
+    }
+}
@@ -0,0 +1,384 @@
+use coreutils::config::{self, Config};
+use coreutils::input;
+
+/// Empty lines between the groups `--all-repeated` prints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delimit {
+    None,
+    Prepend,
+    Separate,
+}
+
+/// Empty lines around the groups `--group` prints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grouping {
+    Prepend,
+    Append,
+    Separate,
+    Both,
+}
+
+#[derive(Debug)]
+pub struct UniqConfig {
+    pub input: String,
+    pub output: Option<String>,
+    pub count: bool,
+    /// Print lines that match neither neighbour.
+    pub show_unique: bool,
+    /// Print the first line of each run of matching lines.
+    pub show_first_repeated: bool,
+    /// Print the other lines of each run.
+    pub show_later_repeated: bool,
+    pub delimit: Delimit,
+    pub grouping: Option<Grouping>,
+    pub ignore_case: bool,
+    pub skip_fields: usize,
+    pub skip_chars: usize,
+    pub check_chars: usize,
+    pub zero_terminated: bool,
+}
+
+const HELP_TEXT: &str = "Usage: uniq [OPTION]... [INPUT [OUTPUT]]
+Filter adjacent matching lines from INPUT (or standard input),
+writing to OUTPUT (or standard output).
+
+With no options, matching lines are merged to the first occurrence.
+
+  -c, --count           prefix lines by the number of occurrences
+  -d, --repeated        only print duplicate lines, one for each group
+  -D                    print all duplicate lines
+      --all-repeated[=METHOD]  like -D, but allow separating groups
+                                 with an empty line;
+                                 METHOD={none(default),prepend,separate}
+  -f, --skip-fields=N   avoid comparing the first N fields
+      --group[=METHOD]  show all items, separating groups with an empty line;
+                          METHOD={separate(default),prepend,append,both}
+  -i, --ignore-case     ignore differences in case when comparing
+  -s, --skip-chars=N    avoid comparing the first N characters
+  -u, --unique          only print unique lines
+  -z, --zero-terminated  line delimiter is NUL, not newline
+  -w, --check-chars=N   compare no more than N characters in lines
+      --help     display this help and exit
+      --version  output version information and exit
+
+A field is a run of blanks (usually spaces and/or tabs), then non-blank
+characters.  Fields are skipped before chars.  A carriage return before
+the newline is not part of the line.
+
+Note: 'uniq' does not detect repeated lines unless they are adjacent.
+You may want to sort the input first, or use 'sort -u' without 'uniq'.
+";
+
+impl Config for UniqConfig {
+    fn bin_name(&self) -> &'static str {
+        "uniq"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+/// A count of fields or bytes. Counts too large for memory behave like
+/// the largest count.
+fn parse_count(value: &str, what: &str) -> Result<usize, String> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("{}: invalid number of {}", value, what));
+    }
+    Ok(value.parse().unwrap_or(usize::MAX))
+}
+
+fn invalid_method(value: &str, option: &str, valid: &[&str]) -> String {
+    let valid: Vec<String> = valid.iter().map(|word| format!("  - '{}'", word)).collect();
+    format!(
+        "invalid argument '{}' for '{}'\nValid arguments are:\n{}\n Try uniq --help for more information",
+        value,
+        option,
+        valid.join("\n")
+    )
+}
+
+impl UniqConfig {
+    pub fn new() -> UniqConfig {
+        UniqConfig {
+            input: input::STDIN.to_string(),
+            output: None,
+            count: false,
+            show_unique: true,
+            show_first_repeated: true,
+            show_later_repeated: false,
+            delimit: Delimit::None,
+            grouping: None,
+            ignore_case: false,
+            skip_fields: 0,
+            skip_chars: 0,
+            check_chars: usize::MAX,
+            zero_terminated: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        let mut selected = false;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--count", None) | ("-c", None) => self.count = true,
+                ("--repeated", None) | ("-d", None) => {
+                    self.show_unique = false;
+                    selected = true;
+                }
+                ("--all-repeated", method) | ("-D", method @ None) => {
+                    self.delimit = match method {
+                        None | Some("none") => Delimit::None,
+                        Some("prepend") => Delimit::Prepend,
+                        Some("separate") => Delimit::Separate,
+                        Some(value) => {
+                            return Err(invalid_method(
+                                value,
+                                "--all-repeated",
+                                &["none", "prepend", "separate"],
+                            ))
+                        }
+                    };
+                    self.show_unique = false;
+                    self.show_later_repeated = true;
+                    selected = true;
+                }
+                ("--unique", None) | ("-u", None) => {
+                    self.show_first_repeated = false;
+                    selected = true;
+                }
+                ("--group", method) => {
+                    self.grouping = Some(match method {
+                        None | Some("separate") => Grouping::Separate,
+                        Some("prepend") => Grouping::Prepend,
+                        Some("append") => Grouping::Append,
+                        Some("both") => Grouping::Both,
+                        Some(value) => {
+                            return Err(invalid_method(
+                                value,
+                                "--group",
+                                &["prepend", "append", "separate", "both"],
+                            ))
+                        }
+                    })
+                }
+                ("--ignore-case", None) | ("-i", None) => self.ignore_case = true,
+                ("--skip-fields", Some(n)) | ("-f", Some(n)) => {
+                    self.skip_fields = parse_count(n, "fields to skip")?
+                }
+                ("--skip-chars", Some(n)) | ("-s", Some(n)) => {
+                    self.skip_chars = parse_count(n, "bytes to skip")?
+                }
+                ("--check-chars", Some(n)) | ("-w", Some(n)) => {
+                    self.check_chars = parse_count(n, "bytes to compare")?
+                }
+                ("--zero-terminated", None) | ("-z", None) => self.zero_terminated = true,
+                (name, None) if matches!(name, "-f" | "-s" | "-w") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try uniq --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if self.grouping.is_some() && (selected || self.count) {
+            return Err(String::from(
+                "--group is mutually exclusive with -c/-d/-D/-u\n Try uniq --help for more information",
+            ));
+        }
+        if self.count && self.show_later_repeated {
+            return Err(String::from(
+                "printing all duplicated lines and repeat counts is meaningless\n Try uniq --help for more information",
+            ));
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        if let Some(input) = operands.next() {
+            self.input = input;
+        }
+        self.output = operands.next().filter(|output| output != input::STDIN);
+        if let Some(extra) = operands.next() {
+            return Err(format!(
+                "extra operand '{}'\n Try uniq --help for more information",
+                extra
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Delimit, Grouping, UniqConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<UniqConfig, String> {
+        let mut config = UniqConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["uniq.exe"]).unwrap();
+        assert_eq!(config.input, "-");
+        assert_eq!(config.output, None);
+        assert!(!config.count && !config.ignore_case && !config.zero_terminated);
+        assert!(config.show_unique && config.show_first_repeated);
+        assert!(!config.show_later_repeated);
+        assert_eq!(config.delimit, Delimit::None);
+        assert_eq!(config.grouping, None);
+        assert_eq!(
+            (config.skip_fields, config.skip_chars, config.check_chars),
+            (0, 0, usize::MAX)
+        );
+    }
+
+    #[test]
+    fn operands() {
+        let config = parse(string_vec!["uniq.exe", "in.txt", "out.txt"]).unwrap();
+        assert_eq!(config.input, "in.txt");
+        assert_eq!(config.output.as_deref(), Some("out.txt"));
+
+        let config = parse(string_vec!["uniq.exe", "in.txt", "-"]).unwrap();
+        assert_eq!(config.output, None);
+
+        assert!(parse(string_vec!["uniq.exe", "a", "b", "c"])
+            .unwrap_err()
+            .contains("extra operand 'c'"));
+    }
+
+    #[test]
+    fn selection() {
+        let config = parse(string_vec!["uniq.exe", "-d"]).unwrap();
+        assert!(!config.show_unique && config.show_first_repeated);
+
+        let config = parse(string_vec!["uniq.exe", "-u"]).unwrap();
+        assert!(config.show_unique && !config.show_first_repeated);
+
+        let config = parse(string_vec!["uniq.exe", "-D"]).unwrap();
+        assert!(!config.show_unique && config.show_later_repeated);
+        assert_eq!(config.delimit, Delimit::None);
+    }
+
+    #[test]
+    fn all_repeated() {
+        let config = parse(string_vec!["uniq.exe", "--all-repeated=separate"]).unwrap();
+        assert!(config.show_later_repeated);
+        assert_eq!(config.delimit, Delimit::Separate);
+
+        let config = parse(string_vec!["uniq.exe", "--all-repeated=prepend"]).unwrap();
+        assert_eq!(config.delimit, Delimit::Prepend);
+
+        assert!(parse(string_vec!["uniq.exe", "--all-repeated=bad"])
+            .unwrap_err()
+            .contains("invalid argument 'bad' for '--all-repeated'"));
+    }
+
+    #[test]
+    fn group() {
+        let config = parse(string_vec!["uniq.exe", "--group"]).unwrap();
+        assert_eq!(config.grouping, Some(Grouping::Separate));
+
+        for (method, grouping) in &[
+            ("prepend", Grouping::Prepend),
+            ("append", Grouping::Append),
+            ("both", Grouping::Both),
+        ] {
+            let config = parse(vec![
+                String::from("uniq.exe"),
+                format!("--group={}", method),
+            ])
+            .unwrap();
+            assert_eq!(config.grouping, Some(*grouping));
+        }
+        assert!(parse(string_vec!["uniq.exe", "--group=bad"]).is_err());
+    }
+
+    #[test]
+    fn conflicts() {
+        for flag in &["-c", "-d", "-D", "-u"] {
+            assert!(parse(string_vec!["uniq.exe", "--group", flag])
+                .unwrap_err()
+                .contains("mutually exclusive"));
+        }
+        assert!(parse(string_vec!["uniq.exe", "-cD"])
+            .unwrap_err()
+            .contains("meaningless"));
+    }
+
+    #[test]
+    fn comparison() {
+        let config = parse(string_vec![
+            "uniq.exe",
+            "-i",
+            "-f",
+            "2",
+            "-s3",
+            "--check-chars=4"
+        ])
+        .unwrap();
+        assert!(config.ignore_case);
+        assert_eq!(
+            (config.skip_fields, config.skip_chars, config.check_chars),
+            (2, 3, 4)
+        );
+
+        let config = parse(string_vec!["uniq.exe", "-w", "99999999999999999999999"]).unwrap();
+        assert_eq!(config.check_chars, usize::MAX);
+
+        assert_eq!(
+            parse(string_vec!["uniq.exe", "-f", "x"]).unwrap_err(),
+            "x: invalid number of fields to skip"
+        );
+        assert_eq!(
+            parse(string_vec!["uniq.exe", "-s", "-1"]).unwrap_err(),
+            "-1: invalid number of bytes to skip"
+        );
+        assert_eq!(
+            parse(string_vec!["uniq.exe", "-w", ""]).unwrap_err(),
+            ": invalid number of bytes to compare"
+        );
+    }
+
+    #[test]
+    fn zero_terminated() {
+        let config = parse(string_vec!["uniq.exe", "-z"]).unwrap();
+        assert_eq!(config.delimiter(), b'\0');
+    }
+
+    #[test]
+    fn invalid_option() {
+        assert!(parse(string_vec!["uniq.exe", "--bogus"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = UniqConfig::new();
+        let parse_result = config.parse(string_vec!["uniq.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = UniqConfig::new();
+        let parse_result = config.parse(string_vec!["uniq.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,294 @@
+use std::io::{self, BufRead, Write};
+
+use coreutils::lines::LineReader;
+
+use crate::config::{Delimit, Grouping, UniqConfig};
+
+/// Characters that end a field.
+fn is_blank(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n')
+}
+
+/// A copy of an earlier line, kept to compare later ones with.
+struct Held {
+    raw: Vec<u8>,
+    content: usize,
+}
+
+impl Held {
+    fn new() -> Held {
+        Held {
+            raw: Vec::new(),
+            content: 0,
+        }
+    }
+
+    fn set<R: BufRead>(&mut self, lines: &LineReader<R>) {
+        self.raw.clear();
+        self.raw.extend_from_slice(lines.raw());
+        self.content = lines.line().len();
+    }
+
+    fn line(&self) -> &[u8] {
+        &self.raw[..self.content]
+    }
+
+    fn terminator(&self) -> &[u8] {
+        &self.raw[self.content..]
+    }
+}
+
+struct Writer<'a, W: Write> {
+    out: &'a mut W,
+    delimiter: u8,
+}
+
+impl<'a, W: Write> Writer<'a, W> {
+    /// Write a line, ending it with the delimiter if the input did not.
+    fn line(&mut self, content: &[u8], terminator: &[u8]) -> io::Result<()> {
+        self.out.write_all(content)?;
+        self.separator(terminator)
+    }
+
+    /// An empty line, ended like the line next to it.
+    fn separator(&mut self, terminator: &[u8]) -> io::Result<()> {
+        if terminator.is_empty() {
+            self.out.write_all(&[self.delimiter])
+        } else {
+            self.out.write_all(terminator)
+        }
+    }
+}
+
+/// Decides which lines match.
+struct Matcher<'a> {
+    config: &'a UniqConfig,
+}
+
+impl Matcher<'_> {
+    /// The part of `line` compared: after the skipped fields and bytes,
+    /// and no longer than `-w`.
+    fn key<'l>(&self, line: &'l [u8]) -> &'l [u8] {
+        let mut pos = 0;
+        for _ in 0..self.config.skip_fields {
+            if pos >= line.len() {
+                break;
+            }
+            while pos < line.len() && is_blank(line[pos]) {
+                pos += 1;
+            }
+            while pos < line.len() && !is_blank(line[pos]) {
+                pos += 1;
+            }
+        }
+        let key = &line[line.len().min(pos.saturating_add(self.config.skip_chars))..];
+        &key[..key.len().min(self.config.check_chars)]
+    }
+
+    fn matches(&self, a: &[u8], b: &[u8]) -> bool {
+        let (a, b) = (self.key(a), self.key(b));
+        if self.config.ignore_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+}
+
+/// Copy `input` to `out` filtered as `config` asks. Only the current line
+/// and one earlier line are held in memory.
+pub fn write<R: BufRead, W: Write>(input: R, out: &mut W, config: &UniqConfig) -> io::Result<()> {
+    let mut lines = LineReader::new(input, config.delimiter());
+    let mut out = Writer {
+        out,
+        delimiter: config.delimiter(),
+    };
+    let matcher = Matcher { config };
+    match config.grouping {
+        Some(grouping) => all_groups(&mut lines, &mut out, &matcher, grouping),
+        None => repeats(&mut lines, &mut out, &matcher, config),
+    }
+}
+
+/// `--group`: every line, with empty lines around the groups.
+fn all_groups<R: BufRead, W: Write>(
+    lines: &mut LineReader<R>,
+    out: &mut Writer<W>,
+    matcher: &Matcher,
+    grouping: Grouping,
+) -> io::Result<()> {
+    let mut first = Held::new();
+    let mut started = false;
+    while lines.next_line()? {
+        let new_group = !started || !matcher.matches(first.line(), lines.line());
+        if new_group {
+            let separate = match grouping {
+                Grouping::Prepend | Grouping::Both => true,
+                Grouping::Append | Grouping::Separate => started,
+            };
+            if separate {
+                out.separator(lines.terminator())?;
+            }
+            first.set(lines);
+            started = true;
+        }
+        out.line(lines.line(), lines.terminator())?;
+    }
+    if started && matches!(grouping, Grouping::Append | Grouping::Both) {
+        out.separator(first.terminator())?;
+    }
+    Ok(())
+}
+
+/// Merge runs of matching lines, printing the lines `-d`, `-D` and `-u`
+/// select. Each line is written once the next shows whether it repeats.
+fn repeats<R: BufRead, W: Write>(
+    lines: &mut LineReader<R>,
+    out: &mut Writer<W>,
+    matcher: &Matcher,
+    config: &UniqConfig,
+) -> io::Result<()> {
+    let mut previous = Held::new();
+    if !lines.next_line()? {
+        return Ok(());
+    }
+    previous.set(lines);
+
+    // Lines after the first of the current run that matched it.
+    let mut matched: u64 = 0;
+    let mut first_group = true;
+    let write = |out: &mut Writer<W>, held: &Held, repeat: bool, matched: u64| {
+        let show = if matched == 0 {
+            config.show_unique
+        } else if !repeat {
+            config.show_first_repeated
+        } else {
+            config.show_later_repeated
+        };
+        if !show {
+            return Ok(());
+        }
+        if config.count {
+            write!(out.out, "{:7} ", matched + 1)?;
+        }
+        out.line(held.line(), held.terminator())
+    };
+
+    while lines.next_line()? {
+        let repeat = matcher.matches(previous.line(), lines.line());
+        if repeat {
+            matched += 1;
+        }
+
+        if config.delimit != Delimit::None {
+            if !repeat {
+                if matched > 0 {
+                    first_group = false;
+                }
+            } else if matched == 1 && (config.delimit == Delimit::Prepend || !first_group) {
+                out.separator(lines.terminator())?;
+            }
+        }
+
+        if !repeat || config.show_later_repeated {
+            write(out, &previous, repeat, matched)?;
+            previous.set(lines);
+            if !repeat {
+                matched = 0;
+            }
+        }
+    }
+    write(out, &previous, false, matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn uniq(args: &[&str], input: &str) -> String {
+        let mut all: Vec<String> = string_vec!["uniq.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = UniqConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        write(input.as_bytes(), &mut out, &config).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const RUNS: &str = "a\na\nb\nc\nc\nc\nd\n";
+
+    #[test]
+    fn merges_adjacent() {
+        assert_eq!(uniq(&[], RUNS), "a\nb\nc\nd\n");
+        assert_eq!(uniq(&[], "a\nb\na\n"), "a\nb\na\n");
+        assert_eq!(uniq(&[], ""), "");
+        assert_eq!(uniq(&[], "x\nx"), "x\n");
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(
+            uniq(&["-c"], RUNS),
+            "      2 a\n      1 b\n      3 c\n      1 d\n"
+        );
+        assert_eq!(uniq(&["-cd"], RUNS), "      2 a\n      3 c\n");
+    }
+
+    #[test]
+    fn selections() {
+        assert_eq!(uniq(&["-d"], RUNS), "a\nc\n");
+        assert_eq!(uniq(&["-u"], RUNS), "b\nd\n");
+        assert_eq!(uniq(&["-D"], RUNS), "a\na\nc\nc\nc\n");
+        assert_eq!(uniq(&["-D", "-u"], RUNS), "a\nc\nc\n");
+        assert_eq!(uniq(&["-d", "-u"], RUNS), "");
+    }
+
+    #[test]
+    fn all_repeated_methods() {
+        assert_eq!(
+            uniq(&["--all-repeated=separate"], RUNS),
+            "a\na\n\nc\nc\nc\n"
+        );
+        assert_eq!(
+            uniq(&["--all-repeated=prepend"], RUNS),
+            "\na\na\n\nc\nc\nc\n"
+        );
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(uniq(&["--group"], RUNS), "a\na\n\nb\n\nc\nc\nc\n\nd\n");
+        assert_eq!(uniq(&["--group=both"], "a\nb\nb\n"), "\na\n\nb\nb\n\n");
+        assert_eq!(uniq(&["--group=prepend"], "a\nb\n"), "\na\n\nb\n");
+        assert_eq!(uniq(&["--group=append"], "a\nb\n"), "a\n\nb\n\n");
+        assert_eq!(uniq(&["--group=append"], ""), "");
+    }
+
+    #[test]
+    fn skipping() {
+        let input = "1 apple\n22 apple\n3 pear\n";
+        assert_eq!(uniq(&["-f1"], input), "1 apple\n3 pear\n");
+        assert_eq!(uniq(&["-f", "1", "-s", "2"], "x ab\ny cb\n"), "x ab\n");
+        assert_eq!(uniq(&["-s2"], "xxa\nyya\n"), "xxa\n");
+        assert_eq!(uniq(&["-w2"], "abX\nabY\nac\n"), "abX\nac\n");
+        assert_eq!(uniq(&["-s5"], "abc\nde\n"), "abc\n");
+    }
+
+    #[test]
+    fn ignore_case() {
+        assert_eq!(uniq(&["-i"], "Hello\nhello\nHELLO\nbye\n"), "Hello\nbye\n");
+        assert_eq!(uniq(&[], "Hello\nhello\n"), "Hello\nhello\n");
+    }
+
+    #[test]
+    fn carriage_returns() {
+        assert_eq!(uniq(&[], "a\r\na\nb"), "a\r\nb\n");
+        assert_eq!(uniq(&["--group"], "a\r\nb\r\n"), "a\r\n\r\nb\r\n");
+    }
+
+    #[test]
+    fn zero_terminated() {
+        assert_eq!(uniq(&["-z"], "a\0a\0b\nc"), "a\0b\nc\0");
+    }
+}
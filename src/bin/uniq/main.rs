@@ -0,0 +1,49 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod groups;
+
+use config::UniqConfig;
+
+fn main() {
+    let mut config = UniqConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let reader = input::open(&config.input).unwrap_or_else(|e| {
+        eprintln!("uniq: {}: {}", config.input, output::describe(&e));
+        process::exit(1);
+    });
+
+    output::restore_sigpipe();
+    let writer: Box<dyn Write> = match &config.output {
+        Some(name) => match File::create(name) {
+            Ok(file) => Box::new(BufWriter::with_capacity(input::BUFFER_SIZE, file)),
+            Err(e) => {
+                eprintln!("uniq: {}: {}", name, output::describe(&e));
+                process::exit(1);
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut out = Output::new("uniq", writer);
+
+    if let Err(e) = groups::write(reader, &mut out, &config) {
+        out.flush();
+        eprintln!(
+            "uniq: {}: {}",
+            input::display_name(&config.input),
+            output::describe(&e)
+        );
+        process::exit(1);
+    }
+    out.flush();
+}
@@ -0,0 +1,227 @@
+use coreutils::config::{self, Config};
+
+#[derive(Debug)]
+pub struct WcConfig {
+    /// Empty when counting standard input without naming it.
+    pub files: Vec<String>,
+    pub lines: bool,
+    pub words: bool,
+    pub chars: bool,
+    pub bytes: bool,
+    pub max_line_length: bool,
+    pub files0_from: Option<String>,
+}
+
+const HELP_TEXT: &str = "Usage: wc [OPTION]... [FILE]...
+  or:  wc [OPTION]... --files0-from=F
+Print newline, word, and byte counts for each FILE, and a total line if
+more than one FILE is specified.  A word is a non-zero-length sequence of
+printable characters delimited by white space.
+
+With no FILE, or when FILE is -, read standard input.
+
+The options below may be used to select which counts are printed, always in
+the following order: newline, word, character, byte, maximum line length.
+  -c, --bytes            print the byte counts
+  -m, --chars            print the character counts
+  -l, --lines            print the newline counts
+      --files0-from=F    read input from the files specified by
+                           NUL-terminated names in file F;
+                           If F is - then read names from standard input
+  -L, --max-line-length  print the maximum display width
+  -w, --words            print the word counts
+      --no-glob          do not expand wildcards in FILE operands
+      --help     display this help and exit
+      --version  output version information and exit
+";
+
+impl Config for WcConfig {
+    fn bin_name(&self) -> &'static str {
+        "wc"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+impl WcConfig {
+    pub fn new() -> WcConfig {
+        WcConfig {
+            files: Vec::new(),
+            lines: false,
+            words: false,
+            chars: false,
+            bytes: false,
+            max_line_length: false,
+            files0_from: None,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+
+        self.files = base_config.parameters;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--bytes", None) | ("-c", None) => self.bytes = true,
+                ("--chars", None) | ("-m", None) => self.chars = true,
+                ("--lines", None) | ("-l", None) => self.lines = true,
+                ("--max-line-length", None) | ("-L", None) => self.max_line_length = true,
+                ("--words", None) | ("-w", None) => self.words = true,
+                ("--files0-from", Some(file)) => self.files0_from = Some(file.to_string()),
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try wc --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        if let (Some(_), Some(file)) = (&self.files0_from, self.files.first()) {
+            return Err(format!(
+                "extra operand '{}'\nfile operands cannot be combined with --files0-from",
+                file
+            ));
+        }
+
+        if !(self.lines || self.words || self.chars || self.bytes || self.max_line_length) {
+            self.lines = true;
+            self.words = true;
+            self.bytes = true;
+        }
+        Ok(())
+    }
+
+    /// Number of counts printed on each line.
+    pub fn columns(&self) -> usize {
+        [
+            self.lines,
+            self.words,
+            self.chars,
+            self.bytes,
+            self.max_line_length,
+        ]
+        .iter()
+        .filter(|&&selected| selected)
+        .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::WcConfig;
+    use coreutils::string_vec;
+
+    #[test]
+    fn defaults() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe"]).unwrap();
+        assert!(config.files.is_empty());
+        assert!(config.lines);
+        assert!(config.words);
+        assert!(!config.chars);
+        assert!(config.bytes);
+        assert!(!config.max_line_length);
+        assert_eq!(config.columns(), 3);
+    }
+
+    #[test]
+    fn files() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "a.txt", "-"]).unwrap();
+        assert_eq!(config.files, vec!["a.txt", "-"]);
+    }
+
+    #[test]
+    fn bytes() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "-c"]).unwrap();
+        assert!(config.bytes);
+        assert!(!config.lines);
+        assert!(!config.words);
+    }
+
+    #[test]
+    fn chars() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "--chars"]).unwrap();
+        assert!(config.chars);
+        assert_eq!(config.columns(), 1);
+    }
+
+    #[test]
+    fn lines() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "-l"]).unwrap();
+        assert!(config.lines);
+        assert!(!config.bytes);
+    }
+
+    #[test]
+    fn words() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "-w"]).unwrap();
+        assert!(config.words);
+        assert!(!config.lines);
+    }
+
+    #[test]
+    fn max_line_length() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "-L"]).unwrap();
+        assert!(config.max_line_length);
+        assert_eq!(config.columns(), 1);
+    }
+
+    #[test]
+    fn bundled() {
+        let mut config = WcConfig::new();
+        config.parse(string_vec!["wc.exe", "-lmL"]).unwrap();
+        assert!(config.lines && config.chars && config.max_line_length);
+        assert_eq!(config.columns(), 3);
+    }
+
+    #[test]
+    fn files0_from() {
+        let mut config = WcConfig::new();
+        config
+            .parse(string_vec!["wc.exe", "--files0-from", "list"])
+            .unwrap();
+        assert_eq!(config.files0_from.as_deref(), Some("list"));
+
+        let mut config = WcConfig::new();
+        assert!(config
+            .parse(string_vec!["wc.exe", "--files0-from=list", "extra"])
+            .unwrap_err()
+            .contains("extra operand 'extra'"));
+    }
+
+    #[test]
+    fn invalid_option() {
+        let mut config = WcConfig::new();
+        assert!(config
+            .parse(string_vec!["wc.exe", "-x"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = WcConfig::new();
+        let parse_result = config.parse(string_vec!["wc.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = WcConfig::new();
+        let parse_result = config.parse(string_vec!["wc.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::{self, BufRead};
+use std::ops::AddAssign;
+
+use coreutils::width;
+
+use crate::config::WcConfig;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub lines: u64,
+    pub words: u64,
+    pub chars: u64,
+    pub bytes: u64,
+    pub max_line_length: u64,
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+    }
+}
+
+/// Count newlines a word at a time; the compiler vectorises this loop.
+pub fn count_newlines(bytes: &[u8]) -> u64 {
+    bytes.iter().filter(|&&b| b == b'\n').count() as u64
+}
+
+/// Count only lines and bytes, which needs no decoding.
+pub fn count_lines<R: BufRead>(reader: &mut R) -> io::Result<Counts> {
+    let mut counts = Counts::default();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(counts);
+        }
+        counts.lines += count_newlines(buf);
+        counts.bytes += buf.len() as u64;
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+/// White space that separates words. As in GNU wc that takes in the
+/// non-breaking spaces and the word joiner, but not the next line control.
+fn is_separator(chr: char) -> bool {
+    (chr.is_whitespace() && chr != '\u{85}') || chr == '\u{2060}'
+}
+
+/// Length of the UTF-8 sequence introduced by `lead`.
+fn sequence_len(lead: u8) -> usize {
+    match lead {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+/// Decodes UTF-8 across buffer boundaries and counts everything. Bytes
+/// that are not valid UTF-8 count towards bytes and words but not chars.
+#[derive(Default)]
+pub struct Counter {
+    counts: Counts,
+    in_word: bool,
+    column: usize,
+    /// The start of a character split across two buffers.
+    pending: Vec<u8>,
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.counts.bytes += bytes.len() as u64;
+
+        let mut bytes = bytes;
+        // A split character that turns out invalid can leave the start of
+        // another behind it, so go on until what is pending is settled.
+        while let Some(&lead) = self.pending.first() {
+            let take = (sequence_len(lead) - self.pending.len()).min(bytes.len());
+            self.pending.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if self.pending.len() < sequence_len(lead) {
+                return;
+            }
+            let pending = std::mem::take(&mut self.pending);
+            let incomplete = self.decode(&pending);
+            self.pending
+                .extend_from_slice(&pending[pending.len() - incomplete..]);
+        }
+
+        let incomplete = self.decode(bytes);
+        self.pending
+            .extend_from_slice(&bytes[bytes.len() - incomplete..]);
+    }
+
+    pub fn finish(mut self) -> Counts {
+        if !self.pending.is_empty() {
+            self.invalid();
+        }
+        self.end_line();
+        self.counts
+    }
+
+    /// Count the valid text in `bytes`, returning the length of a
+    /// truncated character at the end.
+    fn decode(&mut self, mut bytes: &[u8]) -> usize {
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => {
+                    self.text(text);
+                    return 0;
+                }
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    self.text(std::str::from_utf8(valid).expect("validated prefix"));
+                    match e.error_len() {
+                        Some(len) => {
+                            self.invalid();
+                            bytes = &rest[len..];
+                        }
+                        None => return rest.len(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        for chr in text.chars() {
+            self.counts.chars += 1;
+            if chr == '\n' {
+                self.counts.lines += 1;
+                self.end_line();
+            } else {
+                self.column = width::advance(self.column, chr);
+                self.counts.max_line_length = self.counts.max_line_length.max(self.column as u64);
+            }
+
+            if is_separator(chr) {
+                self.in_word = false;
+            } else if !self.in_word {
+                self.counts.words += 1;
+                self.in_word = true;
+            }
+        }
+    }
+
+    fn invalid(&mut self) {
+        if !self.in_word {
+            self.counts.words += 1;
+            self.in_word = true;
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.column = 0;
+    }
+}
+
+pub fn count_all<R: BufRead>(reader: &mut R) -> io::Result<Counts> {
+    let mut counter = Counter::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(counter.finish());
+        }
+        counter.feed(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+/// One output row: the selected counts right-aligned in `width` columns,
+/// followed by the name if there is one.
+pub fn row(counts: &Counts, config: &WcConfig, width: usize, name: Option<&str>) -> String {
+    let selected = [
+        (config.lines, counts.lines),
+        (config.words, counts.words),
+        (config.chars, counts.chars),
+        (config.bytes, counts.bytes),
+        (config.max_line_length, counts.max_line_length),
+    ];
+    let mut row = selected
+        .iter()
+        .filter(|(selected, _)| *selected)
+        .map(|(_, count)| format!("{:>width$}", count, width = width))
+        .collect::<Vec<String>>()
+        .join(" ");
+    if let Some(name) = name {
+        row.push(' ');
+        row.push_str(name);
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::test_utils;
+    use std::io::{BufReader, Cursor};
+
+    fn count(input: &str) -> Counts {
+        count_all(&mut Cursor::new(input.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn ascii() {
+        let counts = count(test_utils::MULTI);
+        assert_eq!(counts.lines, 3);
+        assert_eq!(counts.words, 12);
+        assert_eq!(counts.chars, test_utils::MULTI.len() as u64);
+        assert_eq!(counts.bytes, test_utils::MULTI.len() as u64);
+        assert_eq!(counts.max_line_length, 19);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(count(""), Counts::default());
+    }
+
+    #[test]
+    fn no_trailing_newline() {
+        let counts = count("one two\nthree");
+        assert_eq!(counts.lines, 1);
+        assert_eq!(counts.words, 3);
+        assert_eq!(counts.max_line_length, 7);
+    }
+
+    #[test]
+    fn unicode_chars_and_width() {
+        let counts = count("日本語 テキスト\n");
+        assert_eq!(counts.chars, 9);
+        assert_eq!(counts.bytes, 23);
+        assert_eq!(counts.words, 2);
+        assert_eq!(counts.max_line_length, 15);
+    }
+
+    #[test]
+    fn unicode_separators() {
+        assert_eq!(count("a\u{3000}b").words, 2);
+        assert_eq!(count("a\u{a0}b").words, 2);
+        assert_eq!(count("a\u{202f}b\u{2060}c").words, 3);
+        assert_eq!(count("a\u{85}b\u{200b}c").words, 1);
+    }
+
+    #[test]
+    fn tabs_widen_lines() {
+        assert_eq!(count("a\tb\n").max_line_length, 9);
+    }
+
+    #[test]
+    fn characters_split_across_buffers() {
+        let input = "é€😀".repeat(50);
+        let mut reader = BufReader::with_capacity(3, input.as_bytes());
+        let counts = count_all(&mut reader).unwrap();
+        assert_eq!(counts.chars, 150);
+        assert_eq!(counts.bytes, input.len() as u64);
+        assert_eq!(counts.max_line_length, 200);
+    }
+
+    #[test]
+    fn invalid_lead_split_across_buffers() {
+        let input = b"\xf0A\xe2\x82\xac x\n";
+        for size in 1..input.len() {
+            let mut counter = Counter::new();
+            for chunk in input.chunks(size) {
+                counter.feed(chunk);
+            }
+            let counts = counter.finish();
+            assert_eq!(counts.chars, 5, "chunks of {}", size);
+            assert_eq!(counts.words, 2, "chunks of {}", size);
+            assert_eq!(counts.bytes, input.len() as u64);
+        }
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let mut counter = Counter::new();
+        counter.feed(b"ab\xff cd \xe2\x82");
+        let counts = counter.finish();
+        assert_eq!(counts.bytes, 9);
+        assert_eq!(counts.chars, 6);
+        assert_eq!(counts.words, 3);
+    }
+
+    #[test]
+    fn newline_fast_path() {
+        let mut reader = Cursor::new(test_utils::MULTI.as_bytes());
+        let counts = count_lines(&mut reader).unwrap();
+        assert_eq!(counts.lines, 3);
+        assert_eq!(counts.bytes, test_utils::MULTI.len() as u64);
+        assert_eq!(count_newlines(b"\n\n\nno newline"), 3);
+    }
+
+    #[test]
+    fn rows() {
+        let mut config = WcConfig::new();
+        config.lines = true;
+        config.words = true;
+        config.bytes = true;
+        let counts = count(test_utils::MULTI);
+        assert_eq!(row(&counts, &config, 3, Some("a.txt")), "  3  12  60 a.txt");
+        assert_eq!(row(&counts, &config, 1, None), "3 12 60");
+
+        config.words = false;
+        config.max_line_length = true;
+        assert_eq!(
+            row(&counts, &config, 7, Some("-")),
+            "      3      60      19 -"
+        );
+    }
+
+    #[test]
+    fn totals() {
+        let mut total = count("one\n");
+        total += count("two words and a much longer line\n");
+        assert_eq!(total.lines, 2);
+        assert_eq!(total.words, 8);
+        assert_eq!(total.max_line_length, 32);
+    }
+}
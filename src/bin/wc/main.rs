@@ -0,0 +1,155 @@
+use std::env;
+use std::fs::{self, Metadata};
+use std::io::{self, Read};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod count;
+
+use config::WcConfig;
+use count::Counts;
+
+/// Read the NUL-terminated names listed in `list`.
+fn read_names(list: &str) -> io::Result<Vec<String>> {
+    let mut contents = Vec::new();
+    if list == input::STDIN {
+        io::stdin().lock().read_to_end(&mut contents)?;
+    } else {
+        contents = fs::read(list)?;
+    }
+    if contents.last() == Some(&b'\0') {
+        contents.pop();
+    }
+    if contents.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(contents
+        .split(|&b| b == b'\0')
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
+
+/// Columns for each count, following GNU: wide enough for the combined
+/// size of all regular files, but at least 7 when some input has no
+/// known size. Names streamed from standard input are not sized up front.
+fn number_width(config: &WcConfig, names: &[String], metadata: &[Option<Metadata>]) -> usize {
+    let streamed = config.files0_from.as_deref() == Some(input::STDIN);
+    if streamed || (names.len() <= 1 && config.columns() == 1) {
+        return 1;
+    }
+
+    let mut minimum = 1;
+    let mut total: u64 = 0;
+    for (name, metadata) in names.iter().zip(metadata) {
+        match metadata {
+            Some(metadata) if metadata.is_file() => total = total.saturating_add(metadata.len()),
+            Some(_) => minimum = 7,
+            None if name == input::STDIN => minimum = 7,
+            None => {}
+        }
+    }
+    total.to_string().len().max(minimum)
+}
+
+fn count_file(name: &str, metadata: Option<&Metadata>, config: &WcConfig) -> io::Result<Counts> {
+    let needs_text = config.words || config.chars || config.max_line_length;
+
+    if let Some(len) = metadata.and_then(input::seekable_len) {
+        if !needs_text && !config.lines {
+            return Ok(Counts {
+                bytes: len,
+                ..Counts::default()
+            });
+        }
+    }
+
+    let mut reader = input::open(name)?;
+    if needs_text {
+        count::count_all(&mut reader)
+    } else {
+        count::count_lines(&mut reader)
+    }
+}
+
+fn main() {
+    let mut config = WcConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let mut exit_code = 0;
+    let show_names = !config.files.is_empty() || config.files0_from.is_some();
+    let names = match &config.files0_from {
+        Some(list) => match read_names(list) {
+            Ok(names) => names,
+            Err(e) => {
+                eprintln!(
+                    "wc: cannot open '{}' for reading: {}",
+                    list,
+                    output::describe(&e)
+                );
+                process::exit(1);
+            }
+        },
+        None if config.files.is_empty() => vec![input::STDIN.to_string()],
+        None => config.files.clone(),
+    };
+
+    let metadata: Vec<Option<Metadata>> = names
+        .iter()
+        .map(|name| {
+            if name == input::STDIN {
+                None
+            } else {
+                fs::metadata(name).ok()
+            }
+        })
+        .collect();
+    let width = number_width(&config, &names, &metadata);
+
+    let mut out = Output::stdout("wc");
+    let mut total = Counts::default();
+    for (name, metadata) in names.iter().zip(&metadata) {
+        if name.is_empty() {
+            out.flush();
+            eprintln!("wc: invalid zero-length file name");
+            exit_code = 1;
+            continue;
+        }
+        if config.files0_from.as_deref() == Some(input::STDIN) && name == input::STDIN {
+            out.flush();
+            eprintln!(
+                "wc: when reading file names from standard input, no file name of '-' allowed"
+            );
+            exit_code = 1;
+            continue;
+        }
+
+        match count_file(name, metadata.as_ref(), &config) {
+            Ok(counts) => {
+                let label = if show_names {
+                    Some(name.as_str())
+                } else {
+                    None
+                };
+                writeln!(out, "{}", count::row(&counts, &config, width, label));
+                total += counts;
+            }
+            Err(e) => {
+                out.flush();
+                eprintln!("wc: {}: {}", name, output::describe(&e));
+                exit_code = 1;
+            }
+        }
+    }
+
+    if names.len() > 1 {
+        writeln!(out, "{}", count::row(&total, &config, width, Some("total")));
+    }
+    out.flush();
+    process::exit(exit_code);
+}
@@ -0,0 +1,299 @@
+use coreutils::config::{self, Config};
+use coreutils::hexdump;
+use coreutils::input;
+use coreutils::size;
+
+/// The layout of a dump, and the layout `-r` reads back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// Address, grouped hex and the characters, as xxd prints them.
+    Normal,
+    /// Hex digits alone.
+    Plain,
+    /// `hexdump -C`: hex bytes in two groups of eight and the characters
+    /// between bars.
+    Canonical,
+}
+
+#[derive(Debug)]
+pub struct XxdConfig {
+    pub input: String,
+    pub output: Option<String>,
+    pub style: Style,
+    /// Show a `*` in place of a run of all-zero lines.
+    pub autoskip: bool,
+    pub cols: usize,
+    /// Bytes per group of hex digits; 0 puts the whole line in one.
+    pub group: usize,
+    pub len: Option<u64>,
+    /// Added to the addresses shown.
+    pub offset: u64,
+    /// Where in the input the dump starts, counted back from the end if
+    /// negative. With `-r` it is added to the addresses read.
+    pub seek: i64,
+    pub revert: bool,
+    pub uppercase: bool,
+}
+
+const HELP_TEXT: &str = "Usage: xxd [OPTION]... [INFILE [OUTFILE]]
+  or:  xxd -r [OPTION]... [INFILE [OUTFILE]]
+Make a hex dump of INFILE, or with -r turn a dump back into binary.
+
+With no INFILE, or when INFILE is -, read standard input.  With no OUTFILE,
+write standard output.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -a, --autoskip          show a single '*' in place of a run of all-zero
+                            lines
+  -C, --canonical         hexdump -C style: bytes in two groups of eight,
+                            characters between bars and a '*' in place of
+                            repeated lines
+  -c, --cols=COLS         show COLS bytes per line (16, or 30 with -p)
+  -g, --groupsize=BYTES   put BYTES bytes in each group of hex digits (2);
+                            0 puts the whole line in one group
+  -l, --len=LEN           stop after LEN bytes
+  -o, --offset=OFFSET     add OFFSET to the addresses shown
+  -p, --plain             plain hex digits, without addresses or characters
+  -r, --revert            turn a dump in the chosen style back into binary
+  -s, --seek=[+|-]OFFSET  start OFFSET bytes into INFILE, or OFFSET bytes
+                            before its end; with -r, add OFFSET to the
+                            addresses read
+  -u, --uppercase         use upper case hex digits
+      --help     display this help and exit
+      --version  output version information and exit
+
+-ps is accepted for -p.  Numbers may be hex with a 0x prefix or octal with
+a leading 0.
+
+With -r an OUTFILE is patched in place rather than truncated, so a dump
+of only the lines that changed can be applied to a copy of the original.
+Standard output is padded with zeros up to each address instead, and
+addresses may not go backwards.
+";
+
+impl Config for XxdConfig {
+    fn bin_name(&self) -> &'static str {
+        "xxd"
+    }
+    fn usage(&self) -> &'static str {
+        HELP_TEXT
+    }
+}
+
+fn parse_number(value: &str, what: &str) -> Result<u64, String> {
+    size::parse_offset(value).map_err(|_| format!("invalid {}: '{}'", what, value))
+}
+
+fn parse_seek(value: &str) -> Result<i64, String> {
+    let unsigned = value.strip_prefix('+').unwrap_or(value);
+    let (negative, digits) = match unsigned.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, unsigned),
+    };
+    let seek = parse_number(digits, "seek offset")?;
+    if seek > i64::MAX as u64 {
+        return Err(format!("invalid seek offset: '{}'", value));
+    }
+    Ok(if negative {
+        -(seek as i64)
+    } else {
+        seek as i64
+    })
+}
+
+impl XxdConfig {
+    pub fn new() -> XxdConfig {
+        XxdConfig {
+            input: input::STDIN.to_string(),
+            output: None,
+            style: Style::Normal,
+            autoskip: false,
+            cols: 16,
+            group: 2,
+            len: None,
+            offset: 0,
+            seek: 0,
+            revert: false,
+            uppercase: false,
+        }
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        // xxd's own spelling of `-p`.
+        let args: Vec<String> = args
+            .into_iter()
+            .map(|arg| match arg.as_str() {
+                "-ps" => String::from("--plain"),
+                _ => arg,
+            })
+            .collect();
+        let base_config = Config::parse(self, &args)?;
+
+        let mut cols = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--autoskip", None) | ("-a", None) => self.autoskip = true,
+                ("--canonical", None) | ("-C", None) => self.style = Style::Canonical,
+                ("--cols", Some(value)) | ("-c", Some(value)) => {
+                    cols = Some(parse_number(value, "number of columns")?)
+                }
+                ("--groupsize", Some(value)) | ("-g", Some(value)) => {
+                    self.group = parse_number(value, "group size")? as usize
+                }
+                ("--len", Some(value)) | ("-l", Some(value)) => {
+                    self.len = Some(parse_number(value, "length")?)
+                }
+                ("--offset", Some(value)) | ("-o", Some(value)) => {
+                    self.offset = parse_number(value, "offset")?
+                }
+                ("--plain", None) | ("-p", None) => self.style = Style::Plain,
+                ("--revert", None) | ("-r", None) => self.revert = true,
+                ("--seek", Some(value)) | ("-s", Some(value)) => self.seek = parse_seek(value)?,
+                ("--uppercase", None) | ("-u", None) => self.uppercase = true,
+                (name, None) if matches!(name, "-c" | "-g" | "-l" | "-o" | "-s") => {
+                    return Err(format!("option requires an argument -- '{}'", &name[1..]))
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid option: {}\n Try xxd --help for more information",
+                        option
+                    ))
+                }
+            }
+        }
+
+        self.cols = match (cols, self.style) {
+            (Some(cols), Style::Plain) if cols > 0 => cols as usize,
+            (Some(cols), Style::Normal) if cols > 0 && cols <= 256 => cols as usize,
+            (Some(_), Style::Canonical) | (None, Style::Canonical) => hexdump::CANONICAL_WIDTH,
+            (Some(cols), _) => {
+                return Err(format!(
+                    "invalid number of columns: '{}'; it must be from 1 to 256",
+                    cols
+                ))
+            }
+            (None, Style::Plain) => 30,
+            (None, Style::Normal) => 16,
+        };
+        if self.group == 0 || self.group > self.cols {
+            self.group = self.cols;
+        }
+
+        let mut operands = base_config.parameters.into_iter();
+        if let Some(file) = operands.next() {
+            self.input = file;
+        }
+        self.output = operands.next();
+        if let Some(extra) = operands.next() {
+            return Err(format!(
+                "extra operand '{}'\n Try xxd --help for more information",
+                extra
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Style, XxdConfig};
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> Result<XxdConfig, String> {
+        let mut config = XxdConfig::new();
+        config.parse(args).map(|_| config)
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(string_vec!["xxd.exe"]).unwrap();
+        assert_eq!(config.input, "-");
+        assert_eq!(config.output, None);
+        assert_eq!(config.style, Style::Normal);
+        assert_eq!(config.cols, 16);
+        assert_eq!(config.group, 2);
+        assert_eq!(config.len, None);
+        assert_eq!(config.seek, 0);
+        assert!(!config.autoskip);
+        assert!(!config.revert);
+        assert!(!config.uppercase);
+    }
+
+    #[test]
+    fn operands() {
+        let config = parse(string_vec!["xxd.exe", "in", "out"]).unwrap();
+        assert_eq!(config.input, "in");
+        assert_eq!(config.output.as_deref(), Some("out"));
+        assert!(parse(string_vec!["xxd.exe", "a", "b", "c"])
+            .unwrap_err()
+            .starts_with("extra operand 'c'"));
+    }
+
+    #[test]
+    fn styles() {
+        let config = parse(string_vec!["xxd.exe", "-ps"]).unwrap();
+        assert_eq!(config.style, Style::Plain);
+        assert_eq!(config.cols, 30);
+        let config = parse(string_vec!["xxd.exe", "-C", "-c", "8"]).unwrap();
+        assert_eq!(config.style, Style::Canonical);
+        assert_eq!(config.cols, 16);
+        assert_eq!(
+            parse(string_vec!["xxd.exe", "-p", "-c", "1000"])
+                .unwrap()
+                .cols,
+            1000
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let config = parse(string_vec![
+            "xxd.exe", "-c8", "-g0", "-l", "0x10", "-o", "010", "-s", "+5", "-ua"
+        ])
+        .unwrap();
+        assert_eq!(config.cols, 8);
+        assert_eq!(config.group, 8);
+        assert_eq!(config.len, Some(16));
+        assert_eq!(config.offset, 8);
+        assert_eq!(config.seek, 5);
+        assert!(config.uppercase);
+        assert!(config.autoskip);
+        assert_eq!(parse(string_vec!["xxd.exe", "-s", "-5"]).unwrap().seek, -5);
+        assert_eq!(parse(string_vec!["xxd.exe", "-g", "40"]).unwrap().group, 16);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse(string_vec!["xxd.exe", "-c", "300"]).unwrap_err(),
+            "invalid number of columns: '300'; it must be from 1 to 256"
+        );
+        assert_eq!(
+            parse(string_vec!["xxd.exe", "-l", "x"]).unwrap_err(),
+            "invalid length: 'x'"
+        );
+        assert_eq!(
+            parse(string_vec!["xxd.exe", "-s"]).unwrap_err(),
+            "option requires an argument -- 's'"
+        );
+        assert!(parse(string_vec!["xxd.exe", "-q"])
+            .unwrap_err()
+            .contains("invalid option"));
+    }
+
+    #[test]
+    fn help() {
+        let mut config = XxdConfig::new();
+        let parse_result = config.parse(string_vec!["xxd.exe", "--help"]);
+        assert!(parse_result.unwrap_err().contains("Usage:"));
+    }
+
+    #[test]
+    fn version() {
+        let mut config = XxdConfig::new();
+        let parse_result = config.parse(string_vec!["xxd.exe", "--version"]);
+        assert!(parse_result
+            .unwrap_err()
+            .contains(env!("CARGO_PKG_VERSION")));
+    }
+}
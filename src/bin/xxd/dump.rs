@@ -0,0 +1,272 @@
+use std::io::{self, Read, Write};
+
+use coreutils::hexdump;
+use coreutils::input;
+
+use crate::config::{Style, XxdConfig};
+
+fn printable(byte: u8) -> char {
+    match byte {
+        0x20..=0x7e => char::from(byte),
+        _ => '.',
+    }
+}
+
+struct Dumper<'a, W: Write> {
+    config: &'a XxdConfig,
+    out: &'a mut W,
+    digits: &'static [u8; 16],
+    /// All-zero lines seen in a row, as xxd counts them for `-a`.
+    zero_seen: i32,
+    /// The second line of a run of zeros, shown if the run ends there.
+    held: String,
+    previous: Option<Vec<u8>>,
+    squeezed: bool,
+}
+
+impl<W: Write> Dumper<'_, W> {
+    fn hex(&self, byte: u8) -> [u8; 2] {
+        [
+            self.digits[usize::from(byte >> 4)],
+            self.digits[usize::from(byte & 0xf)],
+        ]
+    }
+
+    /// `00000010: 6162 6364  abcd`, with the characters lined up for
+    /// a full line whatever the grouping.
+    fn normal(&self, address: u64, bytes: &[u8]) -> String {
+        let mut line = format!("{:08x}:", address).into_bytes();
+        let start = line.len() + 1;
+        let cols = self.config.cols;
+        let group = self.config.group;
+        let group_width = 2 * group + 1;
+        let text = start + 2 + (group_width * cols - 1) / group;
+        line.resize(text + bytes.len(), b' ');
+        for (idx, &byte) in bytes.iter().enumerate() {
+            let column = start + group_width * idx / group;
+            line[column..column + 2].copy_from_slice(&self.hex(byte));
+            line[text + idx] = printable(byte) as u8;
+        }
+        line.push(b'\n');
+        String::from_utf8(line).expect("ASCII dump line")
+    }
+
+    /// Port of xxd's line filter: a run of zero lines shows its first
+    /// line, then a `*` and its last if there are more than two. `zero`
+    /// is 1 for a line with something in it or that must be shown, 0 for
+    /// a zero line and -1 to flush at the end.
+    fn autoskip(&mut self, line: &str, zero: i32) -> io::Result<()> {
+        if zero == 0 && self.zero_seen == 1 {
+            self.held = line.to_string();
+        }
+        let show = zero != 0 || {
+            self.zero_seen += 1;
+            self.zero_seen == 1
+        };
+        if !show {
+            return Ok(());
+        }
+        if zero != 0 {
+            if zero < 0 {
+                self.zero_seen -= 1;
+            }
+            if self.zero_seen == 2 {
+                self.out.write_all(self.held.as_bytes())?;
+            }
+            if self.zero_seen > 2 {
+                self.out.write_all(b"*\n")?;
+            }
+        }
+        if zero >= 0 || self.zero_seen > 0 {
+            self.out.write_all(line.as_bytes())?;
+        }
+        if zero != 0 {
+            self.zero_seen = 0;
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, address: u64, bytes: &[u8]) -> io::Result<()> {
+        match self.config.style {
+            Style::Normal => {
+                let line = self.normal(address, bytes);
+                let zero = match self.config.autoskip && bytes.len() == self.config.cols {
+                    true => i32::from(bytes.iter().any(|&byte| byte != 0)),
+                    false => 1,
+                };
+                self.autoskip(&line, zero)
+            }
+            Style::Plain => {
+                for &byte in bytes {
+                    self.out.write_all(&self.hex(byte))?;
+                }
+                writeln!(self.out)
+            }
+            Style::Canonical => {
+                if self.previous.as_deref() == Some(bytes) {
+                    if !self.squeezed {
+                        writeln!(self.out, "*")?;
+                        self.squeezed = true;
+                    }
+                    return Ok(());
+                }
+                self.squeezed = false;
+                let line = hexdump::canonical_line(address, bytes, self.config.uppercase);
+                self.out.write_all(line.as_bytes())?;
+                self.previous = Some(bytes.to_vec());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Dump `input`, whose first byte is at `start` in the file, in the
+/// configured style.
+pub fn dump<R: Read, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    config: &XxdConfig,
+    start: u64,
+) -> io::Result<()> {
+    let mut dumper = Dumper {
+        config,
+        out,
+        digits: if config.uppercase {
+            b"0123456789ABCDEF"
+        } else {
+            b"0123456789abcdef"
+        },
+        zero_seen: 0,
+        held: String::new(),
+        previous: None,
+        squeezed: false,
+    };
+
+    let mut address = start + config.offset;
+    let mut left = config.len.unwrap_or(u64::MAX);
+    let mut line = vec![0; config.cols];
+    let mut last = String::new();
+    loop {
+        let wanted = left.min(config.cols as u64) as usize;
+        let read = input::fill(input, &mut line[..wanted])?;
+        if read == 0 {
+            break;
+        }
+        dumper.line(address, &line[..read])?;
+        if config.style == Style::Normal && config.autoskip {
+            last = dumper.normal(address, &line[..read]);
+        }
+        address += read as u64;
+        left -= read as u64;
+        if read < config.cols {
+            last.clear();
+            break;
+        }
+    }
+
+    match config.style {
+        // A run of zeros at the very end still shows its last line.
+        Style::Normal if config.autoskip && !last.is_empty() => dumper.autoskip(&last, -1),
+        Style::Canonical if address > start + config.offset => {
+            writeln!(dumper.out, "{:08x}", address)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coreutils::string_vec;
+
+    fn xxd(args: &[&str], input: &[u8]) -> String {
+        let mut all: Vec<String> = string_vec!["xxd.exe"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = XxdConfig::new();
+        config.parse(all).unwrap();
+        let mut out = Vec::new();
+        dump(&mut &input[..], &mut out, &config, 0).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const TEXT: &[u8] = b"hello world\n\0\x01\x02\xffabc";
+
+    #[test]
+    fn normal() {
+        assert_eq!(
+            xxd(&[], TEXT),
+            "00000000: 6865 6c6c 6f20 776f 726c 640a 0001 02ff  hello world.....\n\
+             00000010: 6162 63                                  abc\n"
+        );
+        assert_eq!(
+            xxd(&["-g3", "-c8", "-u", "-l10"], TEXT),
+            "00000000: 68656C 6C6F20 776F  hello wo\n\
+             00000008: 726C                rl\n"
+        );
+        assert_eq!(xxd(&[], b""), "");
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(
+            xxd(&["-o", "0xab", "-l4"], TEXT),
+            "000000ab: 6865 6c6c                                hell\n"
+        );
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(
+            xxd(&["-p", "-c", "8"], TEXT),
+            "68656c6c6f20776f\n726c640a000102ff\n616263\n"
+        );
+    }
+
+    #[test]
+    fn autoskip() {
+        let zeros = |count: usize| {
+            let mut bytes = vec![0u8; count];
+            bytes.push(b'x');
+            bytes
+        };
+        let lines = |text: String| -> Vec<String> {
+            text.lines()
+                .map(|line| line[..line.len().min(8)].to_string())
+                .collect()
+        };
+        assert_eq!(
+            lines(xxd(&["-a", "-c4"], &zeros(20))),
+            ["00000000", "*", "00000014"]
+        );
+        assert_eq!(
+            lines(xxd(&["-a", "-c4"], &zeros(8))),
+            ["00000000", "00000004", "00000008"]
+        );
+        assert_eq!(
+            lines(xxd(&["-a", "-c4"], &[0; 16])),
+            ["00000000", "*", "0000000c"]
+        );
+        assert_eq!(
+            lines(xxd(&["-a", "-c4"], &[0; 12])),
+            ["00000000", "00000004", "00000008"]
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        assert_eq!(
+            xxd(&["-C"], TEXT),
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 00 01 02 ff  |hello world.....|\n\
+             00000010  61 62 63                                          |abc|\n\
+             00000013\n"
+        );
+        assert_eq!(
+            xxd(&["-C"], &[0; 40]),
+            "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             *\n\
+             00000020  00 00 00 00 00 00 00 00                           |........|\n\
+             00000028\n"
+        );
+        assert_eq!(xxd(&["-C"], b""), "");
+    }
+}
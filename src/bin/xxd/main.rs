@@ -0,0 +1,100 @@
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::process;
+
+use coreutils::input;
+use coreutils::output::{self, Output};
+
+mod config;
+mod dump;
+mod revert;
+
+use config::XxdConfig;
+use revert::Stream;
+
+fn fail(name: &str, e: &io::Error) -> ! {
+    eprintln!(
+        "xxd: {}: {}",
+        input::display_name(name),
+        output::describe(e)
+    );
+    process::exit(1);
+}
+
+/// Dump from where `-s` says, reading everything first when that is
+/// counted back from the end.
+fn dump_from<W: Write>(
+    mut reader: Box<dyn BufRead>,
+    out: &mut W,
+    config: &XxdConfig,
+) -> io::Result<()> {
+    if config.seek < 0 {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        let start = contents
+            .len()
+            .saturating_sub(config.seek.unsigned_abs() as usize);
+        return dump::dump(&mut &contents[start..], out, config, start as u64);
+    }
+    let start = io::copy(
+        &mut reader.by_ref().take(config.seek as u64),
+        &mut io::sink(),
+    )?;
+    dump::dump(&mut reader, out, config, start)
+}
+
+fn main() {
+    let mut config = XxdConfig::new();
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
+    }
+
+    let reader = input::open(&config.input).unwrap_or_else(|e| fail(&config.input, &e));
+
+    if config.revert {
+        let result = match &config.output {
+            // The file is patched, so it is not truncated.
+            Some(name) => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(name)
+                    .unwrap_or_else(|e| fail(name, &e));
+                revert::revert(reader, &mut BufWriter::new(file), &config)
+            }
+            None => {
+                let mut out = Stream(Output::stdout("xxd"));
+                let result = revert::revert(reader, &mut out, &config);
+                out.0.flush();
+                result
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("xxd: {}", output::describe(&e));
+            process::exit(1);
+        }
+        return;
+    }
+
+    let result = match &config.output {
+        Some(name) => {
+            let file = File::create(name).unwrap_or_else(|e| fail(name, &e));
+            let mut out = Output::new("xxd", BufWriter::new(file));
+            let result = dump_from(reader, &mut out, &config);
+            out.flush();
+            result
+        }
+        None => {
+            let mut out = Output::stdout("xxd");
+            let result = dump_from(reader, &mut out, &config);
+            out.flush();
+            result
+        }
+    };
+    if let Err(e) = result {
+        fail(&config.input, &e);
+    }
+}
@@ -0,0 +1,322 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Read, Seek, SeekFrom, Write};
+
+use crate::config::{Style, XxdConfig};
+
+/// Where reverted bytes go. A file is patched at the addresses the dump
+/// gives; a stream can only be padded with zeros up to them.
+pub trait Target: Write {
+    /// Get ready to write at `to`, having written up to `at`.
+    fn move_to(&mut self, at: u64, to: u64) -> io::Result<()>;
+}
+
+impl Target for BufWriter<File> {
+    fn move_to(&mut self, _at: u64, to: u64) -> io::Result<()> {
+        self.seek(SeekFrom::Start(to)).map(|_| ())
+    }
+}
+
+/// A target that cannot seek, such as standard output.
+pub struct Stream<W: Write>(pub W);
+
+impl<W: Write> Write for Stream<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: Write> Target for Stream<W> {
+    fn move_to(&mut self, at: u64, to: u64) -> io::Result<()> {
+        if to < at {
+            return Err(io::Error::other("cannot seek backwards"));
+        }
+        io::copy(&mut io::repeat(0).take(to - at), &mut self.0).map(|_| ())
+    }
+}
+
+fn nibble(byte: u8) -> Option<u8> {
+    char::from(byte).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Writes bytes at the positions asked for, moving only when needed.
+struct Patch<'a, T: Target> {
+    out: &'a mut T,
+    at: u64,
+    /// Added to every position, from `-s`.
+    base: i64,
+}
+
+impl<T: Target> Patch<'_, T> {
+    fn seek(&mut self, position: u64) -> io::Result<()> {
+        let position = position
+            .checked_add_signed(self.base)
+            .ok_or_else(|| io::Error::other("cannot seek backwards"))?;
+        if position != self.at {
+            self.out.move_to(self.at, position)?;
+            self.at = position;
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, position: u64, bytes: &[u8]) -> io::Result<()> {
+        self.seek(position)?;
+        self.out.write_all(bytes)?;
+        self.at += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Pass over the rest of a line, returning the newline if there is one.
+fn skip_line<R: BufRead>(bytes: &mut io::Bytes<R>) -> io::Result<Option<u8>> {
+    for byte in bytes {
+        if byte? == b'\n' {
+            return Ok(Some(b'\n'));
+        }
+    }
+    Ok(None)
+}
+
+/// The normal and plain styles, read the way xxd reads them: a line's
+/// address runs up to the first character that is not a hex digit, hex
+/// digits then pair up into bytes, and three characters in a row that are
+/// not hex digits end the line. Plain dumps have no addresses and may be
+/// spaced and broken anywhere.
+fn hex_lines<R: BufRead, T: Target>(
+    input: R,
+    patch: &mut Patch<T>,
+    config: &XxdConfig,
+) -> io::Result<()> {
+    let plain = config.style == Style::Plain;
+    let cols = config.cols;
+    // The last three characters as hex digits, newest first.
+    let (mut n1, mut n2): (Option<u8>, Option<u8>) = (None, None);
+    let mut col = cols;
+    let mut address: u64 = 0;
+    let mut ignore = true;
+
+    let mut bytes = input.bytes();
+    while let Some(byte) = bytes.next() {
+        let mut byte = byte?;
+        if byte == b'\r' || (plain && matches!(byte, b' ' | b'\t' | b'\n')) {
+            continue;
+        }
+        let n3 = n2;
+        n2 = n1;
+        n1 = nibble(byte);
+        if n1.is_none() && ignore {
+            continue;
+        }
+        ignore = false;
+
+        if !plain && col >= cols {
+            match n1 {
+                Some(digit) => address = address << 4 | u64::from(digit),
+                None => col = 0,
+            }
+            continue;
+        }
+
+        // Like xxd, move to the address even if the line has no data.
+        patch.seek(address)?;
+        match (n2, n1) {
+            (Some(high), Some(low)) => {
+                patch.write_at(address, &[high << 4 | low])?;
+                address += 1;
+                n1 = None;
+                if !plain {
+                    col += 1;
+                    if col >= cols {
+                        byte = skip_line(&mut bytes)?.unwrap_or(0);
+                    }
+                }
+            }
+            (None, None) if n3.is_none() => byte = skip_line(&mut bytes)?.unwrap_or(0),
+            _ => {}
+        }
+
+        if byte == b'\n' {
+            if !plain {
+                address = 0;
+            }
+            col = cols;
+            ignore = true;
+        }
+    }
+    Ok(())
+}
+
+/// `hexdump -C` lines. A `*` stands for copies of the line before it up
+/// to the next address, and a line with only an address ends the dump.
+fn canonical<R: BufRead, T: Target>(input: R, patch: &mut Patch<T>) -> io::Result<()> {
+    let mut previous: Vec<u8> = Vec::new();
+    let mut next: u64 = 0;
+    let mut repeat = false;
+    for line in input.split(b'\n') {
+        let line = line?;
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.trim_ascii() == b"*" {
+            repeat = true;
+            continue;
+        }
+
+        let digits = line
+            .iter()
+            .take_while(|&&byte| nibble(byte).is_some())
+            .count();
+        if digits == 0 {
+            continue;
+        }
+        let address = line[..digits].iter().fold(0u64, |address, &byte| {
+            address << 4 | u64::from(nibble(byte).unwrap())
+        });
+        if repeat && !previous.is_empty() {
+            while next < address {
+                let count = previous.len().min((address - next) as usize);
+                patch.write_at(next, &previous[..count])?;
+                next += count as u64;
+            }
+        }
+        repeat = false;
+
+        let hex = line[digits..]
+            .split(|&byte| byte == b'|')
+            .next()
+            .unwrap_or(&[]);
+        let mut data = Vec::new();
+        for pair in hex
+            .split(u8::is_ascii_whitespace)
+            .filter(|pair| !pair.is_empty())
+        {
+            match pair {
+                [high, low] => match (nibble(*high), nibble(*low)) {
+                    (Some(high), Some(low)) => data.push(high << 4 | low),
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+        if !data.is_empty() {
+            patch.write_at(address, &data)?;
+            next = address + data.len() as u64;
+            previous = data;
+        }
+    }
+    Ok(())
+}
+
+/// Turn the dump in `input` back into bytes written to `out`.
+pub fn revert<R: BufRead, T: Target>(input: R, out: &mut T, config: &XxdConfig) -> io::Result<()> {
+    let mut patch = Patch {
+        out,
+        at: 0,
+        base: config.seek,
+    };
+    match config.style {
+        Style::Canonical => canonical(input, &mut patch)?,
+        _ => hex_lines(input, &mut patch, config)?,
+    }
+    patch.out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump;
+    use coreutils::string_vec;
+
+    fn config(args: &[&str]) -> XxdConfig {
+        let mut all: Vec<String> = string_vec!["xxd.exe", "-r"];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = XxdConfig::new();
+        config.parse(all).unwrap();
+        config
+    }
+
+    fn revert_stream(args: &[&str], text: &str) -> io::Result<Vec<u8>> {
+        let mut out = Stream(Vec::new());
+        revert(text.as_bytes(), &mut out, &config(args))?;
+        Ok(out.0)
+    }
+
+    fn round_trip(args: &[&str], bytes: &[u8]) -> Vec<u8> {
+        let config = config(args);
+        let mut text = Vec::new();
+        dump::dump(&mut &bytes[..], &mut text, &config, 0).unwrap();
+        let mut out = Stream(Vec::new());
+        revert(&text[..], &mut out, &config).unwrap();
+        out.0
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut bytes: Vec<u8> = (0..=255).collect();
+        bytes.extend([0; 100]);
+        bytes.extend(b"tail");
+        for args in [
+            &[][..],
+            &["-g0"],
+            &["-c7", "-g3", "-u"],
+            &["-a"],
+            &["-p"],
+            &["-C"],
+        ] {
+            assert_eq!(round_trip(args, &bytes), bytes, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn normal() {
+        assert_eq!(
+            revert_stream(&[], "00000004: 4142  AB\n").unwrap(),
+            b"\0\0\0\0AB"
+        );
+        assert_eq!(
+            revert_stream(&[], "00000000: 41 42 4 3  4142\n").unwrap(),
+            b"ABAB"
+        );
+        assert_eq!(
+            revert_stream(&[], "00000000: 6865 6c6c  he  ll\n").unwrap(),
+            b"hell"
+        );
+        assert_eq!(revert_stream(&["-s", "2"], "1: 41").unwrap(), b"\0\0\0A");
+        assert_eq!(
+            revert_stream(&[], "00000002: 41\n00000000: 42\n")
+                .unwrap_err()
+                .to_string(),
+            "cannot seek backwards"
+        );
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(revert_stream(&["-p"], "41 4\n2 434\n").unwrap(), b"ABC");
+    }
+
+    #[test]
+    fn canonical_repeats() {
+        let text =
+            "00000000  61 62 63 64 65 66 67 68  61 62 63 64 65 66 67 68  |abcdefghabcdefgh|\n\
+                    *\n\
+                    00000030  7a                                                |z|\n\
+                    00000031\n";
+        let mut expected = b"abcdefgh".repeat(6);
+        expected.push(b'z');
+        assert_eq!(revert_stream(&["-C"], text).unwrap(), expected);
+    }
+
+    #[test]
+    fn patches_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, b"hello world").unwrap();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        let mut out = BufWriter::new(file);
+        revert(&b"00000006: 5752\n"[..], &mut out, &config(&[])).unwrap();
+        drop(out);
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello WRrld");
+    }
+}
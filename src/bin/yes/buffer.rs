@@ -0,0 +1,77 @@
+use std::io::{self, Write};
+
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Fill a buffer with as many complete copies of `line` as fit in
+/// `BUFFER_SIZE`. A line longer than the buffer is stored once.
+pub fn fill(line: &str) -> Vec<u8> {
+    let mut single = Vec::with_capacity(line.len() + 1);
+    single.extend_from_slice(line.as_bytes());
+    single.push(b'\n');
+
+    let copies = (BUFFER_SIZE / single.len()).max(1);
+    single.repeat(copies)
+}
+
+pub fn write_forever<W: Write>(writer: &mut W, buffer: &[u8]) -> io::Result<()> {
+    loop {
+        writer.write_all(buffer)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Limited {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written.len() >= self.limit {
+                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fill_default() {
+        let buffer = fill("y");
+        assert_eq!(buffer.len(), BUFFER_SIZE);
+        assert!(buffer.chunks(2).all(|chunk| chunk == b"y\n"));
+    }
+
+    #[test]
+    fn fill_whole_lines() {
+        let buffer = fill("hello world");
+        assert!(buffer.len() <= BUFFER_SIZE);
+        assert_eq!(buffer.len() % 12, 0);
+        assert!(buffer.ends_with(b"hello world\n"));
+    }
+
+    #[test]
+    fn fill_long_line() {
+        let line = "x".repeat(BUFFER_SIZE * 2);
+        let buffer = fill(&line);
+        assert_eq!(buffer.len(), line.len() + 1);
+    }
+
+    #[test]
+    fn write_until_error() {
+        let mut writer = Limited {
+            written: Vec::new(),
+            limit: 10,
+        };
+        let err = write_forever(&mut writer, b"y\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(writer.written, b"y\n".repeat(5));
+    }
+}
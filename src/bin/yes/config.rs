@@ -18,7 +18,7 @@ impl Config for YesConfig {
         "yes"
     }
     fn usage(&self) -> &'static str {
-        &HELP_TEXT
+        HELP_TEXT
     }
 }
 
@@ -30,24 +30,48 @@ impl YesConfig {
     }
 
     pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
-        let base_config = match Config::parse(self, &args) {
-            Ok(config) => config,
-            Err(e) => return Err(e),
-        };
+        let base_config = Config::parse(self, &args)?;
 
-        if base_config.parameters.len() == 0 {
+        if base_config.parameters.is_empty() {
             self.output_string.push('y');
             return Ok(());
         }
-        
+
         let mut first_word: bool = false;
         for param in base_config.parameters {
             match first_word {
                 true => self.output_string.push(' '),
-                false =>first_word = true,
+                false => first_word = true,
             }
             self.output_string.push_str(param.as_str())
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::YesConfig;
+    use coreutils::string_vec;
+
+    fn parse(args: Vec<String>) -> String {
+        let mut config = YesConfig::new();
+        config.parse(args).unwrap();
+        config.output_string
+    }
+
+    #[test]
+    fn default_y() {
+        assert_eq!(parse(string_vec!["yes.exe"]), "y");
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(parse(string_vec!["yes.exe", "a", "b", "-"]), "a b -");
+    }
+
+    #[test]
+    fn double_dash() {
+        assert_eq!(parse(string_vec!["yes.exe", "--", "-n", "x"]), "-n x");
+    }
+}
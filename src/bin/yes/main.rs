@@ -1,15 +1,25 @@
 use std::env;
+use std::io;
+use std::process;
 
-mod config;
+use coreutils::output;
 
+mod buffer;
+mod config;
 
 fn main() {
     let mut config = config::YesConfig::new();
-    if let Err(e) = config.parse(env::args().collect()) {
-        eprintln!("{}", e);
-        return;
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = config.parse(args.clone()) {
+        process::exit(coreutils::config::report(&config, &args, &e));
     }
-    loop {
-        println!("{}", config.output_string);
+
+    output::restore_sigpipe();
+    let buffer = buffer::fill(&config.output_string);
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+
+    if let Err(e) = buffer::write_forever(&mut handle, &buffer) {
+        output::write_failed("yes", &e);
     }
 }
@@ -0,0 +1,990 @@
+use std::io::{self, BufRead, Write};
+
+use crate::config::{self, Config};
+use crate::digest::{self, Algorithm, NAMES};
+use crate::input;
+use crate::lines::LineReader;
+use crate::output::{self, Output};
+
+/// Help for the single-algorithm tools; `$options` holds any options only
+/// some of them take.
+macro_rules! sum_help {
+    ($name:literal, $title:literal, $options:literal, $spec:literal) => {
+        concat!(
+            "Usage: ",
+            $name,
+            " [OPTION]... [FILE]...
+Print or check ",
+            $title,
+            " checksums.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -b, --binary          read in binary mode
+  -c, --check           read checksums from the FILEs and check them
+",
+            $options,
+            "      --tag             create a BSD-style checksum
+  -t, --text            read in text mode (default)
+  -z, --zero            end each output line with NUL, not newline,
+                          and disable file name escaping
+      --no-glob         do not expand wildcards in FILE operands
+
+The following five options are useful only when verifying checksums:
+      --ignore-missing  don't fail or report status for missing files
+      --quiet           don't print OK for each successfully verified file
+      --status          don't output anything, status code shows success
+      --strict          exit non-zero for improperly formatted checksum lines
+  -w, --warn            warn about improperly formatted checksum lines
+
+      --help     display this help and exit
+      --version  output version information and exit
+
+The sums are computed as described in ",
+            $spec,
+            ".
+When checking, the input should be a former output of this program.
+The default mode is to print a line with: checksum, a space, a character
+indicating input mode ('*' for binary, ' ' for text or where binary is
+insignificant), and name for each FILE.
+
+Binary and text mode read the same bytes, so a sum made on Windows checks
+on any other system.  Checksum files may end their lines with CRLF or LF.
+"
+        )
+    };
+}
+
+const B2SUM_HELP: &str = sum_help!(
+    "b2sum",
+    "BLAKE2b (512-bit)",
+    "  -l, --length=BITS     digest length in bits; must not exceed the maximum for
+                          the blake2 algorithm and must be a multiple of 8
+",
+    "RFC 7693"
+);
+const MD5SUM_HELP: &str = sum_help!("md5sum", "MD5 (128-bit)", "", "RFC 1321");
+const SHA1SUM_HELP: &str = sum_help!("sha1sum", "SHA1 (160-bit)", "", "FIPS-180-1");
+const SHA224SUM_HELP: &str = sum_help!("sha224sum", "SHA224 (224-bit)", "", "RFC 3874");
+const SHA256SUM_HELP: &str = sum_help!("sha256sum", "SHA256 (256-bit)", "", "FIPS-180-2");
+const SHA384SUM_HELP: &str = sum_help!("sha384sum", "SHA384 (384-bit)", "", "FIPS-180-2");
+const SHA512SUM_HELP: &str = sum_help!("sha512sum", "SHA512 (512-bit)", "", "FIPS-180-2");
+
+const CKSUM_HELP: &str = "Usage: cksum [OPTION]... [FILE]...
+Print or verify checksums.
+By default use the 32 bit CRC algorithm.
+
+With no FILE, or when FILE is -, read standard input.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -a, --algorithm=TYPE  select the digest type to use.  See DIGEST below.
+  -c, --check           read checksums from the FILEs and check them
+  -l, --length=BITS     digest length in bits; must not exceed the maximum for
+                          the blake2 algorithm and must be a multiple of 8
+      --tag             create a BSD style checksum (the default)
+      --untagged        create a reversed style checksum, without digest type
+  -z, --zero            end each output line with NUL, not newline,
+                          and disable file name escaping
+      --no-glob         do not expand wildcards in FILE operands
+
+The following five options are useful only when verifying checksums:
+      --ignore-missing  don't fail or report status for missing files
+      --quiet           don't print OK for each successfully verified file
+      --status          don't output anything, status code shows success
+      --strict          exit non-zero for improperly formatted checksum lines
+  -w, --warn            warn about improperly formatted checksum lines
+
+      --help     display this help and exit
+      --version  output version information and exit
+
+DIGEST determines the digest algorithm and default output format:
+  sysv      (equivalent to sum -s)
+  bsd       (equivalent to sum -r)
+  crc       (equivalent to cksum)
+  md5       (equivalent to md5sum)
+  sha1      (equivalent to sha1sum)
+  sha224    (equivalent to sha224sum)
+  sha256    (equivalent to sha256sum)
+  sha384    (equivalent to sha384sum)
+  sha512    (equivalent to sha512sum)
+  blake2b   (equivalent to b2sum)
+
+When checking, the input should be a former output of this program, or of
+the equivalent single-algorithm program.  Without -a only BSD style lines
+are understood.
+";
+
+/// The options of `cksum` and the `*sum` tools, which differ only in the
+/// algorithm and in a few options.
+#[derive(Debug)]
+pub struct ChecksumConfig {
+    name: &'static str,
+    help: &'static str,
+    /// Operands; none means standard input, which the old checksums then
+    /// print without a name.
+    pub files: Vec<String>,
+    pub algorithm: Algorithm,
+    /// Whether `cksum -a` chose the algorithm, so untagged lines can be
+    /// checked.
+    algorithm_given: bool,
+    /// Length of a BLAKE2b digest.
+    pub bits: usize,
+    pub binary: bool,
+    pub check: bool,
+    pub tag: bool,
+    pub zero: bool,
+    pub ignore_missing: bool,
+    pub quiet: bool,
+    pub status: bool,
+    pub strict: bool,
+    pub warn: bool,
+}
+
+impl Config for ChecksumConfig {
+    fn bin_name(&self) -> &'static str {
+        self.name
+    }
+    fn usage(&self) -> &'static str {
+        self.help
+    }
+    fn expand_wildcards(&self) -> bool {
+        true
+    }
+}
+
+/// One line of a checksum file.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    algorithm: Algorithm,
+    bits: usize,
+    /// The expected digest in lower case hex.
+    expected: String,
+    file: String,
+}
+
+/// What checking one checksum file came to.
+#[derive(Default)]
+struct Tally {
+    improper: usize,
+    proper: usize,
+    unreadable: usize,
+    mismatched: usize,
+    verified: usize,
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+/// A file name as it goes in a checksum line: backslashes and newlines
+/// are escaped, and a line with escapes starts with a backslash.
+fn escape(name: &str) -> (&'static str, String) {
+    if !name.contains(['\\', '\n']) {
+        return ("", name.to_string());
+    }
+    ("\\", name.replace('\\', "\\\\").replace('\n', "\\n"))
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut plain = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => match chars.next()? {
+                '\\' => plain.push('\\'),
+                'n' => plain.push('\n'),
+                _ => return None,
+            },
+            chr => plain.push(chr),
+        }
+    }
+    Some(plain)
+}
+
+fn is_hex(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+impl ChecksumConfig {
+    /// The configuration of the tool called `name`: `cksum` or one of the
+    /// `*sum` tools.
+    pub fn new(name: &'static str) -> ChecksumConfig {
+        let (algorithm, help) = match name {
+            "b2sum" => (Algorithm::Blake2b, B2SUM_HELP),
+            "md5sum" => (Algorithm::Md5, MD5SUM_HELP),
+            "sha1sum" => (Algorithm::Sha1, SHA1SUM_HELP),
+            "sha224sum" => (Algorithm::Sha224, SHA224SUM_HELP),
+            "sha256sum" => (Algorithm::Sha256, SHA256SUM_HELP),
+            "sha384sum" => (Algorithm::Sha384, SHA384SUM_HELP),
+            "sha512sum" => (Algorithm::Sha512, SHA512SUM_HELP),
+            "cksum" => (Algorithm::Crc, CKSUM_HELP),
+            _ => panic!("no checksum tool is called {}", name),
+        };
+        ChecksumConfig {
+            name,
+            help,
+            files: Vec::new(),
+            algorithm,
+            algorithm_given: false,
+            bits: algorithm.bits(),
+            binary: false,
+            check: false,
+            tag: name == "cksum",
+            zero: false,
+            ignore_missing: false,
+            quiet: false,
+            status: false,
+            strict: false,
+            warn: false,
+        }
+    }
+
+    fn is_cksum(&self) -> bool {
+        self.name == "cksum"
+    }
+
+    fn usage_error(&self, message: &str) -> String {
+        format!(
+            "{}\n Try {} --help for more information",
+            message, self.name
+        )
+    }
+
+    fn parse_algorithm(&self, value: &str) -> Result<Algorithm, String> {
+        Algorithm::from_name(value).ok_or_else(|| {
+            let valid: Vec<String> = NAMES
+                .iter()
+                .map(|(name, _)| format!("  - '{}'", name))
+                .collect();
+            self.usage_error(&format!(
+                "invalid argument '{}' for '--algorithm'\nValid arguments are:\n{}",
+                value,
+                valid.join("\n")
+            ))
+        })
+    }
+
+    fn parse_length(&self, value: &str) -> Result<usize, String> {
+        let bits: usize = value
+            .parse()
+            .map_err(|_| format!("invalid length: '{}'", value))?;
+        let max = Algorithm::Blake2b.bits();
+        if bits > max {
+            return Err(format!(
+                "invalid length: '{}'; the maximum for BLAKE2b is {} bits",
+                value, max
+            ));
+        }
+        if !bits.is_multiple_of(8) {
+            return Err(format!(
+                "invalid length: '{}'; it must be a multiple of 8",
+                value
+            ));
+        }
+        Ok(if bits == 0 { max } else { bits })
+    }
+
+    pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
+        let base_config = Config::parse(self, &args)?;
+        let cksum = self.is_cksum();
+        let takes_length = cksum || self.algorithm == Algorithm::Blake2b;
+
+        // Some(true) for -b and Some(false) for -t, whichever came last.
+        let mut mode = None;
+        let mut tag_given = false;
+        let mut length = None;
+        for option in base_config.options {
+            match config::split_option(&option) {
+                ("--algorithm", Some(value)) | ("-a", Some(value)) if cksum => {
+                    self.algorithm = self.parse_algorithm(value)?;
+                    self.algorithm_given = true;
+                }
+                ("--binary", None) | ("-b", None) if !cksum => mode = Some(true),
+                ("--check", None) | ("-c", None) => self.check = true,
+                ("--length", Some(value)) | ("-l", Some(value)) if takes_length => {
+                    length = Some(value.to_string())
+                }
+                ("--tag", None) => {
+                    self.tag = true;
+                    tag_given = true;
+                }
+                ("--untagged", None) if cksum => self.tag = false,
+                ("--text", None) | ("-t", None) if !cksum => mode = Some(false),
+                ("--zero", None) | ("-z", None) => self.zero = true,
+                ("--ignore-missing", None) => self.ignore_missing = true,
+                ("--quiet", None) => self.quiet = true,
+                ("--status", None) => self.status = true,
+                ("--strict", None) => self.strict = true,
+                ("--warn", None) | ("-w", None) => self.warn = true,
+                ("-a", None) if cksum => {
+                    return Err("option requires an argument -- 'a'".to_string())
+                }
+                ("-l", None) if takes_length => {
+                    return Err("option requires an argument -- 'l'".to_string())
+                }
+                _ => {
+                    return Err(self.usage_error(&format!("invalid option: {}", option)));
+                }
+            }
+        }
+
+        if let Some(value) = length {
+            if self.algorithm != Algorithm::Blake2b {
+                return Err(self.usage_error("--length is only supported with --algorithm=blake2b"));
+            }
+            self.bits = self.parse_length(&value)?;
+        } else {
+            self.bits = self.algorithm.bits();
+        }
+        self.binary = mode == Some(true);
+
+        if tag_given && mode == Some(false) {
+            return Err(self.usage_error("--tag does not support --text mode"));
+        }
+        if self.check {
+            if tag_given {
+                return Err(
+                    self.usage_error("the --tag option is meaningless when verifying checksums")
+                );
+            }
+            if self.zero {
+                return Err(
+                    self.usage_error("the --zero option is not supported when verifying checksums")
+                );
+            }
+            if mode.is_some() {
+                return Err(self.usage_error(
+                    "the --binary and --text options are meaningless when verifying checksums",
+                ));
+            }
+            if self.algorithm_given && !self.algorithm.is_hash() {
+                return Err(
+                    self.usage_error("--check is not supported with --algorithm={bsd,sysv,crc}")
+                );
+            }
+        } else {
+            for (given, name) in [
+                (self.ignore_missing, "--ignore-missing"),
+                (self.quiet, "--quiet"),
+                (self.status, "--status"),
+                (self.strict, "--strict"),
+                (self.warn, "--warn"),
+            ] {
+                if given {
+                    return Err(self.usage_error(&format!(
+                        "the {} option is meaningful only when verifying checksums",
+                        name
+                    )));
+                }
+            }
+        }
+
+        self.files = base_config.parameters;
+        Ok(())
+    }
+
+    /// The name a BSD-style line gives the algorithm, with the length of a
+    /// BLAKE2b digest that is not the full 512 bits.
+    fn tag_name(&self) -> String {
+        match self.algorithm {
+            Algorithm::Blake2b if self.bits != self.algorithm.bits() => {
+                format!("{}-{}", self.algorithm.tag(), self.bits)
+            }
+            algorithm => algorithm.tag().to_string(),
+        }
+    }
+
+    /// The digest of a file and its length in bytes.
+    fn digest_file(
+        &self,
+        name: &str,
+        algorithm: Algorithm,
+        bits: usize,
+    ) -> io::Result<(Vec<u8>, u64)> {
+        let mut reader = input::open(name)?;
+        let mut digest = algorithm.digest(bits);
+        let mut length = 0;
+        loop {
+            let buf = match reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if buf.is_empty() {
+                break;
+            }
+            digest.update(buf);
+            let read = buf.len();
+            length += read as u64;
+            reader.consume(read);
+        }
+        Ok((digest.finish(), length))
+    }
+
+    /// The output line for one file, without its terminator. `name` is
+    /// None for standard input read without operands.
+    fn format_line(&self, name: Option<&str>, sum: &[u8], length: u64) -> String {
+        let value = sum
+            .iter()
+            .fold(0u64, |value, &byte| value << 8 | u64::from(byte));
+        let suffix = name.map(|name| format!(" {}", name)).unwrap_or_default();
+        match self.algorithm {
+            Algorithm::Bsd => format!("{:05} {:5}{}", value, length.div_ceil(1024), suffix),
+            Algorithm::Sysv => format!("{} {}{}", value, length.div_ceil(512), suffix),
+            Algorithm::Crc => format!("{} {}{}", value, length, suffix),
+            _ => {
+                let name = name.unwrap_or(input::STDIN);
+                let (prefix, name) = match self.zero {
+                    true => ("", name.to_string()),
+                    false => escape(name),
+                };
+                let hex = digest::hex(sum);
+                if self.tag {
+                    format!("{}{} ({}) = {}", prefix, self.tag_name(), name, hex)
+                } else {
+                    let mode = if self.binary { '*' } else { ' ' };
+                    format!("{}{} {}{}", prefix, hex, mode, name)
+                }
+            }
+        }
+    }
+
+    fn sum_files<W: Write>(&self, out: &mut Output<W>) -> bool {
+        let files: Vec<Option<&str>> = match self.files.is_empty() {
+            true => vec![None],
+            false => self.files.iter().map(|name| Some(name.as_str())).collect(),
+        };
+        let terminator = if self.zero { '\0' } else { '\n' };
+        let mut ok = true;
+        for name in files {
+            match self.digest_file(name.unwrap_or(input::STDIN), self.algorithm, self.bits) {
+                Ok((sum, length)) => {
+                    write!(
+                        out,
+                        "{}{}",
+                        self.format_line(name, &sum, length),
+                        terminator
+                    )
+                }
+                Err(e) => {
+                    out.flush();
+                    eprintln!(
+                        "{}: {}: {}",
+                        self.name,
+                        input::display_name(name.unwrap_or(input::STDIN)),
+                        output::describe(&e)
+                    );
+                    ok = false;
+                }
+            }
+        }
+        ok
+    }
+
+    /// `ALGO (name) = hex`, or `ALGO-BITS (name) = hex` for BLAKE2b.
+    fn parse_tagged(&self, line: &str) -> Option<Entry> {
+        let (tag, rest) = line.split_once(" (")?;
+        let (tag, bits) = match tag.split_once('-') {
+            Some((tag, bits)) => (tag, Some(bits)),
+            None => (tag, None),
+        };
+        let algorithm = NAMES
+            .iter()
+            .map(|&(_, algorithm)| algorithm)
+            .find(|algorithm| algorithm.is_hash() && algorithm.tag() == tag)?;
+        // Plain cksum -c takes whatever algorithm each line names.
+        if algorithm != self.algorithm && (self.algorithm_given || !self.is_cksum()) {
+            return None;
+        }
+        let bits = match bits {
+            Some(bits) if algorithm == Algorithm::Blake2b => self.parse_length(bits).ok()?,
+            Some(_) => return None,
+            None => algorithm.bits(),
+        };
+
+        let split = rest.rfind(") = ")?;
+        let (file, expected) = (&rest[..split], &rest[split + 4..]);
+        if !is_hex(expected) || expected.len() * 4 != bits {
+            return None;
+        }
+        Some(Entry {
+            algorithm,
+            bits,
+            expected: expected.to_ascii_lowercase(),
+            file: file.to_string(),
+        })
+    }
+
+    /// `hex  name` or `hex *name`, as the tools print it, or `hex name` as
+    /// BSD `md5 -r` does.
+    fn parse_untagged(&self, line: &str) -> Option<Entry> {
+        if self.is_cksum() && !self.algorithm_given {
+            return None;
+        }
+        let digits = line
+            .find(|chr: char| !chr.is_ascii_hexdigit())
+            .unwrap_or(line.len());
+        let expected = &line[..digits];
+        // A BLAKE2b digest may be any whole number of bytes, whatever -l
+        // says.
+        let bits = match self.algorithm {
+            Algorithm::Blake2b => self.parse_length(&(digits * 4).to_string()).ok()?,
+            _ => self.bits,
+        };
+        if expected.is_empty() || digits * 4 != bits {
+            return None;
+        }
+
+        let rest = line[digits..].strip_prefix(' ')?;
+        let file = rest.strip_prefix([' ', '*']).unwrap_or(rest);
+        if file.is_empty() {
+            return None;
+        }
+        Some(Entry {
+            algorithm: self.algorithm,
+            bits,
+            expected: expected.to_ascii_lowercase(),
+            file: file.to_string(),
+        })
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<Entry> {
+        let line = std::str::from_utf8(line).ok()?;
+        let line = line.trim_start_matches([' ', '\t']);
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let mut entry = self
+            .parse_tagged(line)
+            .or_else(|| self.parse_untagged(line))?;
+        if escaped {
+            entry.file = unescape(&entry.file)?;
+        }
+        Some(entry)
+    }
+
+    /// Check one line's file, reporting the result unless told not to.
+    fn check_entry<W: Write>(&self, entry: &Entry, out: &mut Output<W>, tally: &mut Tally) {
+        let (prefix, shown) = match entry.file.contains('\n') {
+            true => escape(&entry.file),
+            false => ("", entry.file.clone()),
+        };
+        let result = match self.digest_file(&entry.file, entry.algorithm, entry.bits) {
+            Ok((sum, _)) => Some(digest::hex(&sum) == entry.expected),
+            Err(e) if self.ignore_missing && e.kind() == io::ErrorKind::NotFound => return,
+            Err(e) => {
+                out.flush();
+                eprintln!(
+                    "{}: {}: {}",
+                    self.name,
+                    input::display_name(&entry.file),
+                    output::describe(&e)
+                );
+                None
+            }
+        };
+
+        let verdict = match result {
+            Some(true) => {
+                tally.verified += 1;
+                "OK"
+            }
+            Some(false) => {
+                tally.verified += 1;
+                tally.mismatched += 1;
+                "FAILED"
+            }
+            None => {
+                tally.unreadable += 1;
+                "FAILED open or read"
+            }
+        };
+        if !(self.status || self.quiet && result == Some(true)) {
+            writeln!(out, "{}{}: {}", prefix, shown, verdict);
+        }
+    }
+
+    /// Check the files listed in one checksum file.
+    fn check_file<W: Write>(&self, name: &str, out: &mut Output<W>) -> bool {
+        let display = input::display_name(name);
+        let complain = |out: &mut Output<W>, message: String| {
+            out.flush();
+            eprintln!("{}: {}", self.name, message);
+        };
+        let reader = match input::open(name) {
+            Ok(reader) => reader,
+            Err(e) => {
+                complain(out, format!("{}: {}", display, output::describe(&e)));
+                return false;
+            }
+        };
+
+        let mut tally = Tally::default();
+        let mut lines = LineReader::new(reader, b'\n');
+        let mut number = 0;
+        loop {
+            match lines.next_line() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    complain(out, format!("{}: {}", display, output::describe(&e)));
+                    return false;
+                }
+            }
+            number += 1;
+            let line = lines.line();
+            if line.is_empty() || line[0] == b'#' {
+                continue;
+            }
+            match self.parse_line(line) {
+                Some(entry) => {
+                    tally.proper += 1;
+                    self.check_entry(&entry, out, &mut tally);
+                }
+                None => {
+                    tally.improper += 1;
+                    if self.warn {
+                        let kind = match self.is_cksum() {
+                            true => String::new(),
+                            false => format!("{} ", self.algorithm.tag()),
+                        };
+                        complain(
+                            out,
+                            format!(
+                                "{}: {}: improperly formatted {}checksum line",
+                                display, number, kind
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        if tally.proper == 0 {
+            complain(
+                out,
+                format!("{}: no properly formatted checksum lines found", display),
+            );
+            return false;
+        }
+        if !self.status {
+            if tally.improper > 0 {
+                let lines = plural(
+                    tally.improper,
+                    "line is improperly formatted",
+                    "lines are improperly formatted",
+                );
+                complain(out, format!("WARNING: {}", lines));
+            }
+            if tally.unreadable > 0 {
+                let files = plural(
+                    tally.unreadable,
+                    "listed file could not be read",
+                    "listed files could not be read",
+                );
+                complain(out, format!("WARNING: {}", files));
+            }
+            if tally.mismatched > 0 {
+                let sums = plural(
+                    tally.mismatched,
+                    "computed checksum did NOT match",
+                    "computed checksums did NOT match",
+                );
+                complain(out, format!("WARNING: {}", sums));
+            }
+        }
+        if self.ignore_missing && tally.verified == 0 {
+            complain(out, format!("{}: no file was verified", display));
+            return false;
+        }
+        tally.mismatched == 0 && tally.unreadable == 0 && !(self.strict && tally.improper > 0)
+    }
+
+    fn check_files<W: Write>(&self, out: &mut Output<W>) -> bool {
+        if self.files.is_empty() {
+            return self.check_file(input::STDIN, out);
+        }
+        let mut ok = true;
+        for name in &self.files {
+            ok &= self.check_file(name, out);
+        }
+        ok
+    }
+}
+
+/// Print or check sums as configured, returning the exit status.
+pub fn run(config: &ChecksumConfig) -> i32 {
+    let mut out = Output::stdout(config.name);
+    let ok = if config.check {
+        config.check_files(&mut out)
+    } else {
+        config.sum_files(&mut out)
+    };
+    out.flush();
+    if ok {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string_vec;
+
+    const TOOLS: [&str; 8] = [
+        "b2sum",
+        "cksum",
+        "md5sum",
+        "sha1sum",
+        "sha224sum",
+        "sha256sum",
+        "sha384sum",
+        "sha512sum",
+    ];
+
+    fn parse(name: &'static str, args: &[&str]) -> Result<ChecksumConfig, String> {
+        let mut all: Vec<String> = string_vec![format!("{}.exe", name)];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        let mut config = ChecksumConfig::new(name);
+        ChecksumConfig::parse(&mut config, all).map(|_| config)
+    }
+
+    /// Checked with GNU coreutils.
+    const ABC_MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+
+    #[test]
+    fn defaults() {
+        let config = parse("md5sum", &[]).unwrap();
+        assert_eq!(config.algorithm, Algorithm::Md5);
+        assert!(config.files.is_empty());
+        assert!(!config.binary && !config.check && !config.tag && !config.zero);
+        let config = parse("cksum", &[]).unwrap();
+        assert_eq!(config.algorithm, Algorithm::Crc);
+        assert!(config.tag);
+        assert_eq!(parse("b2sum", &[]).unwrap().bits, 512);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse("sha256sum", &["-b", "--tag", "-z", "f", "g"]).unwrap();
+        assert!(config.binary && config.tag && config.zero);
+        assert_eq!(config.files, vec!["f", "g"]);
+        let config = parse("md5sum", &["-c", "--quiet", "--status", "--strict", "-w"]).unwrap();
+        assert!(config.check && config.quiet && config.status && config.strict && config.warn);
+        let config = parse("cksum", &["-a", "blake2b", "-l", "256", "--untagged"]).unwrap();
+        assert_eq!(config.algorithm, Algorithm::Blake2b);
+        assert_eq!(config.bits, 256);
+        assert!(!config.tag);
+        assert_eq!(parse("b2sum", &["-l", "0"]).unwrap().bits, 512);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |name, args: &[&str]| parse(name, args).unwrap_err();
+        assert!(error("md5sum", &["--tag", "-t"]).starts_with("--tag does not support --text mode"));
+        assert!(error("md5sum", &["--quiet"])
+            .starts_with("the --quiet option is meaningful only when verifying checksums"));
+        assert!(error("md5sum", &["-c", "-z"])
+            .starts_with("the --zero option is not supported when verifying checksums"));
+        assert!(error("md5sum", &["-c", "-b"]).starts_with("the --binary and --text options"));
+        assert!(error("md5sum", &["-l", "8"]).contains("invalid option"));
+        assert_eq!(
+            error("b2sum", &["-l", "7"]),
+            "invalid length: '7'; it must be a multiple of 8"
+        );
+        assert_eq!(
+            error("b2sum", &["-l", "1024"]),
+            "invalid length: '1024'; the maximum for BLAKE2b is 512 bits"
+        );
+        assert!(error("cksum", &["-l", "128"]).starts_with("--length is only supported"));
+        assert!(
+            error("cksum", &["-a", "sm3"]).starts_with("invalid argument 'sm3' for '--algorithm'")
+        );
+        assert!(error("cksum", &["-a", "crc", "-c"]).starts_with("--check is not supported"));
+        assert!(error("cksum", &["-b"]).contains("invalid option"));
+    }
+
+    #[test]
+    fn sum_lines() {
+        let md5 = parse("md5sum", &[]).unwrap();
+        let abc = {
+            let mut digest = Algorithm::Md5.digest(0);
+            digest.update(b"abc");
+            digest.finish()
+        };
+        assert_eq!(
+            md5.format_line(Some("a"), &abc, 3),
+            format!("{}  a", ABC_MD5)
+        );
+        assert_eq!(md5.format_line(None, &abc, 3), format!("{}  -", ABC_MD5));
+        assert_eq!(
+            md5.format_line(Some("new\nline\\"), &abc, 3),
+            format!("\\{}  new\\nline\\\\", ABC_MD5)
+        );
+        let tagged = parse("md5sum", &["--tag"]).unwrap();
+        assert_eq!(
+            tagged.format_line(Some("a"), &abc, 3),
+            format!("MD5 (a) = {}", ABC_MD5)
+        );
+        let zero = parse("md5sum", &["-bz"]).unwrap();
+        assert_eq!(
+            zero.format_line(Some("a\\b"), &abc, 3),
+            format!("{} *a\\b", ABC_MD5)
+        );
+        let b2sum = parse("b2sum", &["--tag", "-l", "8"]).unwrap();
+        assert_eq!(
+            b2sum.format_line(Some("a"), &[0x6b], 3),
+            "BLAKE2b-8 (a) = 6b"
+        );
+
+        let cksum = |args: &[&str], sum: &[u8], length| {
+            parse("cksum", args)
+                .unwrap()
+                .format_line(Some("a"), sum, length)
+        };
+        assert_eq!(
+            cksum(&[], &1219131554u32.to_be_bytes(), 3),
+            "1219131554 3 a"
+        );
+        assert_eq!(
+            cksum(&["-a", "sysv"], &294u16.to_be_bytes(), 513),
+            "294 2 a"
+        );
+        assert_eq!(
+            cksum(&["-a", "bsd"], &16556u16.to_be_bytes(), 3),
+            "16556     1 a"
+        );
+        assert_eq!(
+            parse("cksum", &[]).unwrap().format_line(None, &[0; 4], 0),
+            "0 0"
+        );
+    }
+
+    #[test]
+    fn checksum_lines() {
+        let md5 = parse("md5sum", &[]).unwrap();
+        let entry = entry_md5;
+        let line = |text: String| md5.parse_line(text.as_bytes());
+        assert_eq!(line(format!("{}  a b", ABC_MD5)), Some(entry("a b")));
+        assert_eq!(line(format!("{} *a", ABC_MD5)), Some(entry("a")));
+        assert_eq!(line(format!("{} a", ABC_MD5)), Some(entry("a")));
+        assert_eq!(
+            line(format!("{}  a", ABC_MD5.to_uppercase())),
+            Some(entry("a"))
+        );
+        assert_eq!(line(format!("MD5 (a) = {}", ABC_MD5)), Some(entry("a")));
+        assert_eq!(
+            line(format!("\\MD5 (x\\\\y\\nz) = {}", ABC_MD5)),
+            Some(entry("x\\y\nz"))
+        );
+        assert_eq!(line(format!("SHA1 (a) = {}", ABC_MD5)), None);
+        assert_eq!(line(format!("{}0  a", ABC_MD5)), None);
+        assert_eq!(line(format!("{}  ", ABC_MD5)), None);
+        assert_eq!(line(String::from("garbage")), None);
+
+        // b2sum reads the length from the line.
+        let b2sum = parse("b2sum", &["-c", "-l", "256"]).unwrap();
+        let entry = b2sum.parse_line(b"6b  a").unwrap();
+        assert_eq!(entry.bits, 8);
+        let entry = b2sum.parse_line(b"BLAKE2b-16 (a) = 6b6b").unwrap();
+        assert_eq!(entry.bits, 16);
+        assert_eq!(b2sum.parse_line(b"6b6  a"), None);
+
+        // cksum understands any tag, and untagged lines only with -a.
+        let cksum = parse("cksum", &["-c"]).unwrap();
+        let tagged = format!("MD5 (a) = {}", ABC_MD5);
+        assert_eq!(cksum.parse_line(tagged.as_bytes()), Some(entry_md5("a")));
+        assert_eq!(cksum.parse_line(format!("{}  a", ABC_MD5).as_bytes()), None);
+        let cksum = parse("cksum", &["-c", "-a", "md5"]).unwrap();
+        assert_eq!(
+            cksum.parse_line(format!("{}  a", ABC_MD5).as_bytes()),
+            Some(entry_md5("a"))
+        );
+    }
+
+    fn entry_md5(file: &str) -> Entry {
+        Entry {
+            algorithm: Algorithm::Md5,
+            bits: 128,
+            expected: ABC_MD5.to_string(),
+            file: file.to_string(),
+        }
+    }
+
+    /// Output of checking `lines`, with paths relative to `dir`, and
+    /// whether it passed.
+    fn check(args: &[&str], dir: &std::path::Path, lines: &str) -> (String, bool) {
+        let list = dir.join("sums");
+        std::fs::write(&list, lines.replace("DIR/", &format!("{}/", dir.display()))).unwrap();
+        let mut all = vec!["-c"];
+        all.extend_from_slice(args);
+        let list = list.to_string_lossy().to_string();
+        all.push(&list);
+        let config = parse("md5sum", &all).unwrap();
+        let mut out = Output::new("md5sum", Vec::new());
+        let ok = config.check_files(&mut out);
+        out.flush();
+        let text = String::from_utf8(out.get_ref().clone()).unwrap();
+        (text.replace(&format!("{}/", dir.display()), ""), ok)
+    }
+
+    #[test]
+    fn checking() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a"), b"abc").unwrap();
+        let good = format!("{}  DIR/a\r\n", ABC_MD5);
+        let bad = format!("{}  DIR/a\n", "0".repeat(32));
+        let missing = format!("{}  DIR/missing\n", ABC_MD5);
+
+        assert_eq!(
+            check(&[], dir.path(), &good),
+            (String::from("a: OK\n"), true)
+        );
+        assert_eq!(
+            check(&["--quiet"], dir.path(), &good),
+            (String::new(), true)
+        );
+        assert_eq!(
+            check(&[], dir.path(), &format!("# comment\n\n{}{}", good, bad)),
+            (String::from("a: OK\na: FAILED\n"), false)
+        );
+        assert_eq!(
+            check(&["--status"], dir.path(), &bad),
+            (String::new(), false)
+        );
+        assert_eq!(
+            check(&[], dir.path(), &missing),
+            (String::from("missing: FAILED open or read\n"), false)
+        );
+        assert_eq!(
+            check(
+                &["--ignore-missing"],
+                dir.path(),
+                &format!("{}{}", missing, good)
+            ),
+            (String::from("a: OK\n"), true)
+        );
+        assert!(!check(&["--ignore-missing"], dir.path(), &missing).1);
+        assert!(check(&[], dir.path(), &format!("{}junk\n", good)).1);
+        assert!(!check(&["--strict"], dir.path(), &format!("{}junk\n", good)).1);
+        assert!(!check(&[], dir.path(), "junk\n").1);
+    }
+
+    #[test]
+    fn help() {
+        for name in TOOLS {
+            let error = parse(name, &["--help"]).unwrap_err();
+            assert!(error.starts_with(&format!("Usage: {} ", name)));
+        }
+    }
+
+    #[test]
+    fn version() {
+        let error = parse("sha1sum", &["--version"]).unwrap_err();
+        assert!(error.contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::usage::OptionSpec;
+
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+/// Render a completion script for `bin` in the named shell. Every script
+/// offers the documented options and falls back to file names.
+pub fn generate(shell: &str, bin: &str, options: &[OptionSpec]) -> Result<String, String> {
+    match shell {
+        "bash" => Ok(bash(bin, options)),
+        "zsh" => Ok(zsh(bin, options)),
+        "fish" => Ok(fish(bin, options)),
+        "powershell" => Ok(powershell(bin, options)),
+        _ => Err(format!(
+            "unsupported shell '{}'; expected one of: {}",
+            shell,
+            SHELLS.join(", ")
+        )),
+    }
+}
+
+fn function_name(bin: &str) -> String {
+    bin.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
+
+fn bash(bin: &str, options: &[OptionSpec]) -> String {
+    let words: Vec<String> = options
+        .iter()
+        .flat_map(|spec| {
+            spec.names().into_iter().map(move |name| {
+                if spec.value.is_some() && name.starts_with("--") {
+                    format!("{}=", name)
+                } else {
+                    name
+                }
+            })
+        })
+        .collect();
+    let valued: Vec<String> = options
+        .iter()
+        .filter(|spec| spec.takes_value())
+        .flat_map(OptionSpec::names)
+        .collect();
+
+    let func = function_name(bin);
+    format!(
+        r#"_{func}() {{
+    local cur prev
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    case "$prev" in
+        {valued})
+            return 0
+            ;;
+    esac
+
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=( $(compgen -W "{words}" -- "$cur") )
+        [[ "${{COMPREPLY[0]}}" == *= ]] && compopt -o nospace
+        return 0
+    fi
+
+    COMPREPLY=( $(compgen -f -- "$cur") )
+}}
+complete -o filenames -F _{func} {bin} {bin}.exe
+"#,
+        func = func,
+        bin = bin,
+        valued = if valued.is_empty() {
+            String::from("--")
+        } else {
+            valued.join("|")
+        },
+        words = words.join(" "),
+    )
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh(bin: &str, options: &[OptionSpec]) -> String {
+    let mut script = format!("#compdef {bin} {bin}.exe\n\n_arguments -s \\\n", bin = bin);
+    for spec in options {
+        let names = spec.names();
+        let description = zsh_escape(&spec.description);
+        let value = match &spec.value {
+            Some(value) => format!(":{}: ", zsh_escape(value)),
+            None => String::new(),
+        };
+
+        for name in &names {
+            let exclusions = if names.len() > 1 {
+                format!("({})", names.join(" "))
+            } else {
+                String::new()
+            };
+            let suffix = match (&spec.value, name.starts_with("--")) {
+                (Some(_), true) if spec.optional_value => "=-",
+                (Some(_), true) => "=",
+                (Some(_), false) => "+",
+                _ => "",
+            };
+            script.push_str(&format!(
+                "  '{}{}{}[{}]{}' \\\n",
+                exclusions, name, suffix, description, value
+            ));
+        }
+    }
+    script.push_str("  '*:file:_files'\n");
+    script
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(bin: &str, options: &[OptionSpec]) -> String {
+    let mut script = String::new();
+    for spec in options {
+        let mut line = format!("complete -c {}", bin);
+        if let Some(short) = spec.short {
+            line.push_str(&format!(" -s {}", short));
+        }
+        if let Some(long) = &spec.long {
+            line.push_str(&format!(" -l {}", long));
+        }
+        if spec.takes_value() {
+            line.push_str(" -r");
+        }
+        line.push_str(&format!(" -d '{}'", fish_escape(&spec.description)));
+        script.push_str(&line);
+        script.push('\n');
+    }
+    script
+}
+
+fn powershell_escape(text: &str) -> String {
+    text.replace('\'', "''")
+}
+
+fn powershell(bin: &str, options: &[OptionSpec]) -> String {
+    let mut entries = String::new();
+    for spec in options {
+        for name in spec.names() {
+            let description = if spec.description.is_empty() {
+                name.clone()
+            } else {
+                powershell_escape(&spec.description)
+            };
+            entries.push_str(&format!(
+                "        [CompletionResult]::new('{name}', '{name}', [CompletionResultType]::ParameterName, '{description}')\n",
+                name = name,
+                description = description,
+            ));
+        }
+    }
+
+    format!(
+        r#"using namespace System.Management.Automation
+
+Register-ArgumentCompleter -Native -CommandName '{bin}', '{bin}.exe' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    if (-not $wordToComplete.StartsWith('-')) {{
+        return
+    }}
+
+    @(
+{entries}    ) | Where-Object {{ $_.CompletionText -like "$wordToComplete*" }}
+}}
+"#,
+        bin = bin,
+        entries = entries,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage;
+
+    const HELP: &str = "Usage: demo [OPTION]... [FILE]...
+
+  -a, --all                  don't ignore [hidden] entries
+  -n, --lines=NUM            print NUM lines
+      --color[=WHEN]         colorize the output
+      --help     display this help and exit
+";
+
+    fn script(shell: &str) -> String {
+        generate(shell, "demo", &usage::parse_options(HELP)).unwrap()
+    }
+
+    #[test]
+    fn unknown_shell() {
+        assert!(generate("tcsh", "demo", &[]).unwrap_err().contains("tcsh"));
+    }
+
+    #[test]
+    fn bash_lists_options() {
+        let script = script("bash");
+        assert!(script.contains("-a --all -n --lines= --color= --help"));
+        assert!(script.contains("-n|--lines)"));
+        assert!(script.contains("compgen -f"));
+        assert!(script.contains("complete -o filenames -F _demo demo demo.exe"));
+    }
+
+    #[test]
+    fn zsh_lists_options() {
+        let script = script("zsh");
+        assert!(script.starts_with("#compdef demo"));
+        assert!(script.contains("'(-a --all)--all[don'\\''t ignore \\[hidden\\] entries]'"));
+        assert!(script.contains("'(-n --lines)--lines=[print NUM lines]:NUM: '"));
+        assert!(script.contains("'--color=-[colorize the output]:WHEN: '"));
+        assert!(script.contains("'*:file:_files'"));
+    }
+
+    #[test]
+    fn fish_lists_options() {
+        let script = script("fish");
+        assert!(
+            script.contains("complete -c demo -s a -l all -d 'don\\'t ignore [hidden] entries'")
+        );
+        assert!(script.contains("complete -c demo -s n -l lines -r -d 'print NUM lines'"));
+        assert!(script.contains("complete -c demo -l help -d"));
+    }
+
+    #[test]
+    fn powershell_lists_options() {
+        let script = script("powershell");
+        assert!(script.contains("-CommandName 'demo', 'demo.exe'"));
+        assert!(script.contains("[CompletionResult]::new('--all', '--all', [CompletionResultType]::ParameterName, 'don''t ignore [hidden] entries')"));
+        assert!(script.contains("[CompletionResult]::new('-n'"));
+    }
+}
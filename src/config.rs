@@ -1,40 +1,160 @@
+use crate::completion;
+use crate::glob;
+use crate::output::Output;
+use crate::usage::{self, OptionSpec};
+
+pub const NO_GLOB: &str = "--no-glob";
+pub const GENERATE_COMPLETIONS: &str = "--generate-completions";
+
+/// Split a gathered option into its name and value, so `--lines=5` gives
+/// `("--lines", Some("5"))` and `-v` gives `("-v", None)`.
+pub fn split_option(option: &str) -> (&str, Option<&str>) {
+    match option.find('=') {
+        Some(idx) => (&option[..idx], Some(&option[idx + 1..])),
+        None => (option, None),
+    }
+}
+
+/// Separate `args` into options and operands using the documented options.
+/// Short flags may be bundled (`-vET`), a value may be attached (`-n5`,
+/// `--lines=5`) or be the next argument, and `--` ends the options. Options
+/// with a value are returned as `name=value`; undocumented ones verbatim.
+/// Operands keep their index into `args`.
+pub fn split_args(specs: &[OptionSpec], args: &[String]) -> (Vec<String>, Vec<(usize, String)>) {
+    let short = |chr: char| specs.iter().find(|spec| spec.short == Some(chr));
+    let long = |name: &str| specs.iter().find(|spec| spec.long.as_deref() == Some(name));
+
+    let mut options = Vec::new();
+    let mut parameters = Vec::new();
+    let mut idx = 1;
+    let mut only_operands = false;
+
+    while idx < args.len() {
+        let arg = &args[idx];
+        idx += 1;
+
+        if only_operands || arg == "-" || !arg.starts_with('-') {
+            parameters.push((idx - 1, arg.clone()));
+        } else if arg == "--" {
+            only_operands = true;
+        } else if let Some(name) = arg.strip_prefix("--") {
+            match long(name) {
+                Some(spec) if spec.takes_value() && idx < args.len() => {
+                    options.push(format!("{}={}", arg, args[idx]));
+                    idx += 1;
+                }
+                _ => options.push(arg.clone()),
+            }
+        } else {
+            let cluster: Vec<char> = arg.chars().skip(1).collect();
+            if short(cluster[0]).is_none() {
+                options.push(arg.clone());
+                continue;
+            }
+            for (pos, &chr) in cluster.iter().enumerate() {
+                match short(chr) {
+                    Some(spec) if spec.takes_value() => {
+                        let attached: String = cluster[pos + 1..].iter().collect();
+                        if !attached.is_empty() {
+                            options.push(format!("-{}={}", chr, attached));
+                        } else if idx < args.len() {
+                            options.push(format!("-{}={}", chr, args[idx]));
+                            idx += 1;
+                        } else {
+                            options.push(format!("-{}", chr));
+                        }
+                        break;
+                    }
+                    _ => options.push(format!("-{}", chr)),
+                }
+            }
+        }
+    }
+    (options, parameters)
+}
+
 pub struct BaseConfig {
     pub parameters: Vec<String>,
     pub options: Vec<String>,
 }
 
+/// Print what stopped `config` parsing `args` and return the exit status:
+/// text asked for with `--help`, `--version` or `--generate-completions`
+/// goes to standard output as it is, anything else is a mistake reported on
+/// standard error after the utility's name.
+pub fn report(config: &dyn Config, args: &[String], message: &str) -> i32 {
+    let asked_for = config.check_help(args).is_some()
+        || config.check_version(args).is_some()
+        || matches!(config.check_completions(args), Some(Ok(_)));
+    if asked_for {
+        let mut out = Output::stdout(config.bin_name());
+        out.write_str(message);
+        if !message.ends_with('\n') {
+            out.write_str("\n");
+        }
+        return 0;
+    }
+    eprintln!("{}: {}", config.bin_name(), message);
+    1
+}
+
 pub trait Config {
     fn bin_name(&self) -> &'static str;
     fn usage(&self) -> &'static str;
 
+    /// Utilities taking file operands opt in to expanding wildcards the
+    /// Windows shells leave alone. `--no-glob` turns it off per invocation.
+    fn expand_wildcards(&self) -> bool {
+        false
+    }
+
+    /// The options documented in `usage`.
+    fn options(&self) -> Vec<OptionSpec> {
+        usage::parse_options(self.usage())
+    }
+
     fn parse(&self, args: &[String]) -> Result<BaseConfig, String> {
-        if let Some(err) = self.check_help(&args) {
+        if let Some(err) = self.check_help(args) {
             return Err(err);
         }
 
-        if let Some(err) = self.check_version(&args) {
+        if let Some(err) = self.check_version(args) {
             return Err(err);
         }
 
+        if let Some(script) = self.check_completions(args) {
+            return Err(script?);
+        }
+
         Ok(BaseConfig {
-            parameters: self.gather_parameters(&args),
-            options: self.gather_options(&args),
+            parameters: self.gather_parameters(args),
+            options: self.gather_options(args),
         })
     }
 
     fn gather_options(&self, args: &[String]) -> Vec<String> {
-        args.iter()
-            .skip(1)
-            .filter(|arg| arg.starts_with('-'))
-            .map(String::from)
+        let (options, _) = split_args(&self.options(), args);
+        options
+            .into_iter()
+            .filter(|arg| !(self.expand_wildcards() && arg == NO_GLOB))
             .collect()
     }
 
     fn gather_parameters(&self, args: &[String]) -> Vec<String> {
-        args.iter()
-            .skip(1)
-            .filter(|arg| !arg.starts_with('-'))
-            .map(String::from)
+        let expand = self.expand_wildcards() && !args.iter().any(|arg| arg == NO_GLOB);
+        let literal = if expand {
+            glob::literal_args(args)
+        } else {
+            vec![true; args.len()]
+        };
+
+        let (_, parameters) = split_args(&self.options(), args);
+        parameters
+            .into_iter()
+            .flat_map(|(idx, arg)| match literal[idx] {
+                true => vec![arg],
+                false => glob::expand_operand(&arg),
+            })
             .collect()
     }
 
@@ -45,6 +165,24 @@ pub trait Config {
         None
     }
 
+    /// Hidden `--generate-completions=SHELL` option, also accepted with the
+    /// shell as the next argument.
+    fn check_completions(&self, args: &[String]) -> Option<Result<String, String>> {
+        let shell = args.iter().enumerate().skip(1).find_map(|(idx, arg)| {
+            match arg.strip_prefix(GENERATE_COMPLETIONS)? {
+                "" => Some(args.get(idx + 1).map(String::as_str)),
+                value => value.strip_prefix('=').map(Some),
+            }
+        })?;
+        Some(match shell {
+            Some(shell) => completion::generate(shell, self.bin_name(), &self.options()),
+            None => Err(format!(
+                "option '{}' requires an argument",
+                GENERATE_COMPLETIONS
+            )),
+        })
+    }
+
     fn check_version(&self, args: &[String]) -> Option<String> {
         if args.contains(&"--version".to_string()) {
             return Some(format!(
@@ -56,3 +194,140 @@ pub trait Config {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "Usage: demo [OPTION]... [FILE]...
+
+  -a, --all                  include everything
+  -b                         be brief
+  -n, --lines=NUM            print NUM lines
+      --color[=WHEN]         colorize the output
+";
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn split(args: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let (options, parameters) = split_args(&usage::parse_options(HELP), &args);
+        (
+            options,
+            parameters.into_iter().map(|(_, arg)| arg).collect(),
+        )
+    }
+
+    struct Demo;
+
+    impl Config for Demo {
+        fn bin_name(&self) -> &'static str {
+            "demo"
+        }
+        fn usage(&self) -> &'static str {
+            HELP
+        }
+    }
+
+    #[test]
+    fn report_status() {
+        let help = args(&["demo", "--help"]);
+        assert_eq!(report(&Demo, &help, HELP), 0);
+        let version = args(&["demo", "-a", "--version"]);
+        assert_eq!(report(&Demo, &version, "demo (Win32CoreUtils) v0"), 0);
+        let wrong = args(&["demo", "-q"]);
+        assert_eq!(report(&Demo, &wrong, "invalid option: -q"), 1);
+        let script = args(&["demo", "--generate-completions=fish"]);
+        assert_eq!(report(&Demo, &script, "complete -c demo"), 0);
+        let unknown = args(&["demo", "--generate-completions=csh"]);
+        assert_eq!(report(&Demo, &unknown, "unsupported shell 'csh'"), 1);
+    }
+
+    #[test]
+    fn completions() {
+        let attached = Demo.parse(&args(&["demo", "--generate-completions=bash"]));
+        let separate = Demo.parse(&args(&["demo", "--generate-completions", "bash"]));
+        let script = attached.err().unwrap();
+        assert!(script.contains("--lines"));
+        assert_eq!(separate.err(), Some(script));
+
+        let missing = Demo.parse(&args(&["demo", "--generate-completions"]));
+        assert_eq!(
+            missing.err().unwrap(),
+            "option '--generate-completions' requires an argument"
+        );
+        let unknown = Demo.parse(&args(&["demo", "--generate-completions", "csh"]));
+        assert!(unknown
+            .err()
+            .unwrap()
+            .starts_with("unsupported shell 'csh'"));
+    }
+
+    #[test]
+    fn flags_and_operands() {
+        let (options, parameters) = split(args(&["demo", "-a", "file", "--all", "-"]));
+        assert_eq!(options, vec!["-a", "--all"]);
+        assert_eq!(parameters, vec!["file", "-"]);
+    }
+
+    #[test]
+    fn bundled_flags() {
+        let (options, _) = split(args(&["demo", "-ab"]));
+        assert_eq!(options, vec!["-a", "-b"]);
+        let (options, _) = split(args(&["demo", "-abn5"]));
+        assert_eq!(options, vec!["-a", "-b", "-n=5"]);
+        let (options, _) = split(args(&["demo", "-ax"]));
+        assert_eq!(options, vec!["-a", "-x"]);
+    }
+
+    #[test]
+    fn values() {
+        let (options, parameters) = split(args(&[
+            "demo",
+            "-n",
+            "-5",
+            "--lines",
+            "7",
+            "--lines=8",
+            "-n9",
+            "file",
+        ]));
+        assert_eq!(options, vec!["-n=-5", "--lines=7", "--lines=8", "-n=9"]);
+        assert_eq!(parameters, vec!["file"]);
+    }
+
+    #[test]
+    fn optional_value_not_consumed() {
+        let (options, parameters) = split(args(&["demo", "--color", "file", "--color=never"]));
+        assert_eq!(options, vec!["--color", "--color=never"]);
+        assert_eq!(parameters, vec!["file"]);
+    }
+
+    #[test]
+    fn missing_value() {
+        let (options, _) = split(args(&["demo", "-n"]));
+        assert_eq!(options, vec!["-n"]);
+        assert_eq!(split_option("-n"), ("-n", None));
+    }
+
+    #[test]
+    fn double_dash() {
+        let (options, parameters) = split(args(&["demo", "-a", "--", "-b", "--all"]));
+        assert_eq!(options, vec!["-a"]);
+        assert_eq!(parameters, vec!["-b", "--all"]);
+    }
+
+    #[test]
+    fn undocumented_verbatim() {
+        let (options, _) = split(args(&["demo", "-5", "--bogus", "--bogus=1"]));
+        assert_eq!(options, vec!["-5", "--bogus", "--bogus=1"]);
+    }
+
+    #[test]
+    fn split_values() {
+        assert_eq!(split_option("--lines=5"), ("--lines", Some("5")));
+        assert_eq!(split_option("-n==5"), ("-n", Some("=5")));
+        assert_eq!(split_option("--all"), ("--all", None));
+    }
+}
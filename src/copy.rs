@@ -0,0 +1,1457 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::backup::Backup;
+use crate::format;
+use crate::output::{describe, Output};
+use crate::walk::{self, Entry, Event, FileId, Follow, Walk};
+
+/// Bytes read and written at a time.
+const BUFFER_SIZE: usize = 128 * 1024;
+
+/// Runs of zeros this long, and aligned to it, become holes in a sparse
+/// copy.
+const HOLE_SIZE: usize = 4096;
+
+/// What to do about a destination that already exists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overwrite {
+    Always,
+    Prompt,
+    Never,
+}
+
+/// How each file that is not a directory gets to its destination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    Copy,
+    HardLink,
+    SymbolicLink,
+}
+
+/// Whether to share the data blocks of the source on file systems that
+/// can, rather than copying them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reflink {
+    Never,
+    Auto,
+    Always,
+}
+
+/// Whether runs of zeros become holes in the copy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sparse {
+    Never,
+    /// Only when the source looks sparse itself.
+    Auto,
+    Always,
+}
+
+/// The attributes carried over to each copy.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Preserve {
+    pub mode: bool,
+    pub ownership: bool,
+    pub timestamps: bool,
+    /// Copy files with several names inside the tree to one file with
+    /// as many names.
+    pub links: bool,
+    pub xattr: bool,
+    /// Report failures to keep ownership and extended attributes, which
+    /// are otherwise attempted quietly.
+    pub required: bool,
+}
+
+impl Preserve {
+    pub fn all() -> Preserve {
+        Preserve {
+            mode: true,
+            ownership: true,
+            timestamps: true,
+            links: true,
+            xattr: true,
+            required: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub recursive: bool,
+    pub follow: Follow,
+    pub method: Method,
+    pub overwrite: Overwrite,
+    /// Remove a destination that cannot be opened and try again.
+    pub force: bool,
+    /// Leave destinations at least as new as their source alone.
+    pub update: bool,
+    pub verbose: bool,
+    /// The copy is the first half of a move across file systems, which
+    /// verbose output tells in mv's words.
+    pub moving: bool,
+    pub preserve: Preserve,
+    pub reflink: Reflink,
+    pub sparse: Sparse,
+    pub backup: Backup,
+}
+
+impl Default for Options {
+    /// What plain cp does: copy one file, looking through a link given
+    /// as the source.
+    fn default() -> Options {
+        Options {
+            recursive: false,
+            follow: Follow::Roots,
+            method: Method::Copy,
+            overwrite: Overwrite::Always,
+            force: false,
+            update: false,
+            verbose: false,
+            moving: false,
+            preserve: Preserve::default(),
+            reflink: Reflink::Never,
+            sparse: Sparse::Auto,
+            backup: Backup::none(),
+        }
+    }
+}
+
+/// `path` quoted for a message.
+pub fn quoted(path: &Path) -> String {
+    format::quote(&path.to_string_lossy())
+}
+
+/// Ask a question on standard error; true if the answer read from
+/// standard input starts with a y.
+pub fn confirm(question: &str) -> bool {
+    eprint!("{}", question);
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).is_ok() && answer.starts_with(['y', 'Y'])
+}
+
+/// What the operands of cp or mv ask for.
+#[derive(Debug, PartialEq)]
+pub enum Target {
+    /// One source to one destination name.
+    File(PathBuf, PathBuf),
+    /// Every source into a directory.
+    Directory(Vec<PathBuf>, PathBuf),
+}
+
+/// Sort out the operands once they are known to be enough: everything
+/// goes into `target_directory` if given, else into the last operand if
+/// that is a directory, unless `no_target_directory` says it is a name.
+pub fn target(
+    files: &[String],
+    target_directory: Option<&str>,
+    no_target_directory: bool,
+) -> Result<Target, String> {
+    let is_dir = |name: &str| fs::metadata(name).map(|metadata| metadata.is_dir());
+    let paths = |names: &[String]| names.iter().map(PathBuf::from).collect();
+
+    if let Some(dir) = target_directory {
+        return match is_dir(dir) {
+            Ok(true) => Ok(Target::Directory(paths(files), PathBuf::from(dir))),
+            Ok(false) => Err(format!(
+                "target directory {}: Not a directory",
+                format::quote(dir)
+            )),
+            Err(e) => Err(format!(
+                "target directory {}: {}",
+                format::quote(dir),
+                describe(&e)
+            )),
+        };
+    }
+
+    let (last, sources) = files.split_last().expect("operands checked by the caller");
+    if no_target_directory {
+        return Ok(Target::File(
+            PathBuf::from(&sources[0]),
+            PathBuf::from(last),
+        ));
+    }
+    match is_dir(last) {
+        Ok(true) => Ok(Target::Directory(paths(sources), PathBuf::from(last))),
+        _ if sources.len() == 1 => Ok(Target::File(
+            PathBuf::from(&sources[0]),
+            PathBuf::from(last),
+        )),
+        Ok(false) => Err(format!("target {}: Not a directory", format::quote(last))),
+        Err(e) => Err(format!("target {}: {}", format::quote(last), describe(&e))),
+    }
+}
+
+/// Where `source` goes inside `dir`: under its own last name.
+pub fn into_directory(source: &Path, dir: &Path) -> PathBuf {
+    match source.components().next_back() {
+        Some(name) => dir.join(name),
+        None => dir.to_path_buf(),
+    }
+}
+
+/// The path of an entry in a walk started at `source`, moved to `dest`.
+fn moved(entry: &Entry, source: &Path, dest: &Path) -> PathBuf {
+    if entry.depth == 0 {
+        return dest.to_path_buf();
+    }
+    let relative = entry
+        .path
+        .strip_prefix(source)
+        .expect("walked paths start with the root");
+    dest.join(relative)
+}
+
+/// Copies trees and files, reporting problems on standard error as it
+/// goes and what it did to `out` when verbose.
+pub struct Copier<W: Write> {
+    prog: &'static str,
+    options: Options,
+    out: Output<W>,
+    /// Where the first copy of each file with several names went.
+    linked: HashMap<FileId, PathBuf>,
+    /// Everything made so far, which a later source must not replace.
+    created: HashSet<PathBuf>,
+    /// The operands copied so far, each of which is copied once.
+    sources: HashSet<PathBuf>,
+    #[cfg(unix)]
+    umask: u32,
+}
+
+impl<W: Write> Copier<W> {
+    pub fn new(prog: &'static str, options: Options, out: Output<W>) -> Copier<W> {
+        Copier {
+            prog,
+            options,
+            out,
+            linked: HashMap::new(),
+            created: HashSet::new(),
+            sources: HashSet::new(),
+            #[cfg(unix)]
+            umask: current_umask(),
+        }
+    }
+
+    pub fn output(&mut self) -> &mut Output<W> {
+        &mut self.out
+    }
+
+    fn fail(&mut self, message: String) -> bool {
+        self.out.flush();
+        eprintln!("{}: {}", self.prog, message);
+        false
+    }
+
+    fn report(&mut self, source: &Path, dest: &Path, backup: Option<&Path>) {
+        if !self.options.verbose {
+            return;
+        }
+        if self.options.moving {
+            self.out.write_str("copied ");
+        }
+        match backup {
+            Some(backup) => writeln!(
+                self.out,
+                "{} -> {} (backup: {})",
+                quoted(source),
+                quoted(dest),
+                quoted(backup)
+            ),
+            None => writeln!(self.out, "{} -> {}", quoted(source), quoted(dest)),
+        }
+    }
+
+    /// Copy `source` to `dest`, the whole tree if it is a directory and
+    /// the copy is recursive. False if anything went wrong.
+    pub fn copy(&mut self, source: &Path, dest: &Path) -> bool {
+        let metadata = match self.options.follow {
+            Follow::Never => fs::symlink_metadata(source),
+            _ => fs::metadata(source),
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return self.fail(format!("cannot stat {}: {}", quoted(source), describe(&e)))
+            }
+        };
+        if metadata.is_dir() {
+            if !self.options.recursive {
+                return self.fail(format!(
+                    "-r not specified; omitting directory {}",
+                    quoted(source)
+                ));
+            }
+            if walk::is_within(dest, source).unwrap_or(false) {
+                return self.fail(format!(
+                    "cannot copy a directory, {}, into itself, {}",
+                    quoted(source),
+                    quoted(dest)
+                ));
+            }
+        }
+        if !self.sources.insert(source.to_path_buf()) {
+            let kind = if metadata.is_dir() {
+                "directory"
+            } else {
+                "file"
+            };
+            self.out.flush();
+            eprintln!(
+                "{}: warning: source {} {} specified more than once",
+                self.prog,
+                kind,
+                quoted(source)
+            );
+            return true;
+        }
+        if self.created.contains(dest) {
+            return self.fail(format!(
+                "will not overwrite just-created {} with {}",
+                quoted(dest),
+                quoted(source)
+            ));
+        }
+
+        let mut ok = true;
+        let mut walk = Walk::new(source, self.options.follow);
+        // Whether each directory entered was made here, innermost last.
+        let mut made = Vec::new();
+        while let Some(event) = walk.next() {
+            let event = match event {
+                Ok(event) => event,
+                Err(walk::Error::Stat(path, e)) => {
+                    ok = self.fail(format!("cannot stat {}: {}", quoted(&path), describe(&e)));
+                    continue;
+                }
+                Err(walk::Error::Read(path, e)) => {
+                    ok = self.fail(format!("cannot access {}: {}", quoted(&path), describe(&e)));
+                    continue;
+                }
+                Err(walk::Error::Cycle(path)) => {
+                    ok = self.fail(format!(
+                        "cannot copy cyclic symbolic link {}",
+                        quoted(&path)
+                    ));
+                    continue;
+                }
+            };
+            match event {
+                Event::Enter(entry) => match self.enter(&entry, &moved(&entry, source, dest)) {
+                    Some(new) => made.push(new),
+                    None => {
+                        ok = false;
+                        walk.skip_dir();
+                    }
+                },
+                Event::Leave(entry) => {
+                    let new = made.pop().unwrap_or(false);
+                    ok &= self.preserve(&entry, &moved(&entry, source, dest), new);
+                }
+                Event::File(entry) => ok &= self.copy_file(&entry, &moved(&entry, source, dest)),
+            }
+        }
+        ok
+    }
+
+    /// For `--parents`: make the directories leading to `source` inside
+    /// `dir`, and return where `source` itself goes.
+    pub fn make_parents(&mut self, source: &Path, dir: &Path) -> Option<PathBuf> {
+        let mut from = PathBuf::new();
+        let mut to = dir.to_path_buf();
+        let mut components = source.components().peekable();
+        while let Some(component) = components.next() {
+            from.push(component);
+            if matches!(component, Component::Prefix(_) | Component::RootDir) {
+                continue;
+            }
+            to.push(component);
+            if components.peek().is_none() || to.is_dir() {
+                continue;
+            }
+
+            let metadata = match fs::metadata(&from) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    self.fail(format!("cannot stat {}: {}", quoted(&from), describe(&e)));
+                    return None;
+                }
+            };
+            if let Err(e) = create_dir(&to, &metadata) {
+                self.fail(format!(
+                    "cannot make directory {}: {}",
+                    quoted(&to),
+                    describe(&e)
+                ));
+                return None;
+            }
+            if self.options.verbose {
+                writeln!(self.out, "{} -> {}", from.display(), to.display());
+            }
+            let entry = Entry {
+                path: from.clone(),
+                depth: 0,
+                metadata,
+            };
+            if !self.preserve(&entry, &to, true) {
+                return None;
+            }
+        }
+        Some(to)
+    }
+
+    /// Make the directory a walk has entered, unless it is there already.
+    /// Some(true) when made, None on failure.
+    fn enter(&mut self, entry: &Entry, dest: &Path) -> Option<bool> {
+        // A link to a directory is a directory to copy into.
+        if let Ok(existing) = fs::metadata(dest) {
+            if existing.is_dir() {
+                return Some(false);
+            }
+            self.fail(format!(
+                "cannot overwrite non-directory {} with directory {}",
+                quoted(dest),
+                quoted(&entry.path)
+            ));
+            return None;
+        }
+        if let Err(e) = create_dir(dest, &entry.metadata) {
+            self.fail(format!(
+                "cannot create directory {}: {}",
+                quoted(dest),
+                describe(&e)
+            ));
+            return None;
+        }
+        self.created.insert(dest.to_path_buf());
+        if self.options.moving {
+            if self.options.verbose {
+                writeln!(self.out, "created directory {}", quoted(dest));
+            }
+        } else {
+            self.report(&entry.path, dest, None);
+        }
+        Some(true)
+    }
+
+    /// Carry the attributes asked for over to `dest`. A directory made
+    /// here also gets its own permissions back, having been made writable
+    /// to fill it.
+    fn preserve(&mut self, entry: &Entry, dest: &Path, new: bool) -> bool {
+        let metadata = &entry.metadata;
+        let link = metadata.file_type().is_symlink();
+        let preserve = self.options.preserve;
+        let mut ok = true;
+
+        if preserve.ownership {
+            if let Err(e) = set_owner(dest, metadata) {
+                if preserve.required {
+                    ok = self.fail(format!(
+                        "failed to preserve ownership for {}: {}",
+                        quoted(dest),
+                        describe(&e)
+                    ));
+                }
+            }
+        }
+        if preserve.xattr {
+            if let Err(e) = copy_xattrs(&entry.path, dest, link) {
+                if preserve.required {
+                    ok = self.fail(format!(
+                        "preserving extended attributes for {}: {}",
+                        quoted(dest),
+                        describe(&e)
+                    ));
+                }
+            }
+        }
+        if !link {
+            let permissions = if preserve.mode {
+                Some(metadata.permissions())
+            } else if new && metadata.is_dir() {
+                self.new_dir_permissions(metadata)
+            } else {
+                None
+            };
+            if let Some(permissions) = permissions {
+                if let Err(e) = fs::set_permissions(dest, permissions) {
+                    ok = self.fail(format!(
+                        "preserving permissions for {}: {}",
+                        quoted(dest),
+                        describe(&e)
+                    ));
+                }
+            }
+        }
+        if preserve.timestamps {
+            if let Err(e) = set_times(dest, metadata, link) {
+                ok = self.fail(format!(
+                    "preserving times for {}: {}",
+                    quoted(dest),
+                    describe(&e)
+                ));
+            }
+        }
+        ok
+    }
+
+    #[cfg(unix)]
+    fn new_dir_permissions(&self, metadata: &Metadata) -> Option<fs::Permissions> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = metadata.permissions().mode() & 0o7777;
+        if mode & 0o700 == 0o700 {
+            return None;
+        }
+        Some(fs::Permissions::from_mode(mode & !self.umask))
+    }
+
+    #[cfg(not(unix))]
+    fn new_dir_permissions(&self, _metadata: &Metadata) -> Option<fs::Permissions> {
+        None
+    }
+
+    /// Copy or link anything but a directory.
+    fn copy_file(&mut self, entry: &Entry, dest: &Path) -> bool {
+        let source = &entry.path;
+        let metadata = &entry.metadata;
+        let link = metadata.file_type().is_symlink();
+        let method = self.options.method;
+
+        let existing = fs::symlink_metadata(dest).is_ok();
+        let mut backup = None;
+        if existing {
+            if same_file(entry, dest) {
+                if method == Method::HardLink {
+                    return true;
+                }
+                return self.fail(format!(
+                    "{} and {} are the same file",
+                    quoted(source),
+                    quoted(dest)
+                ));
+            }
+            if fs::metadata(dest).is_ok_and(|existing| existing.is_dir()) {
+                return self.fail(format!(
+                    "cannot overwrite directory {} with non-directory",
+                    quoted(dest)
+                ));
+            }
+            if self.options.update && is_up_to_date(dest, metadata) {
+                return true;
+            }
+            match self.options.overwrite {
+                Overwrite::Always => {}
+                Overwrite::Never => return true,
+                Overwrite::Prompt => {
+                    self.out.flush();
+                    let question = format!("{}: overwrite {}? ", self.prog, quoted(dest));
+                    if !confirm(&question) {
+                        return true;
+                    }
+                }
+            }
+            match self.options.backup.make(dest) {
+                Ok(made) => backup = made,
+                Err(e) => {
+                    return self.fail(format!("cannot backup {}: {}", quoted(dest), describe(&e)))
+                }
+            }
+        }
+        let existing = existing && backup.is_none();
+
+        // The first copy of a file with several names is made as usual
+        // and the others are linked to it.
+        let mut first_copy = None;
+        if self.options.preserve.links && method == Method::Copy {
+            if let Ok((id, links)) = walk::identify(source, metadata) {
+                if links > 1 {
+                    match self.linked.get(&id) {
+                        Some(first) => first_copy = Some(first.clone()),
+                        None => {
+                            self.linked.insert(id, dest.to_path_buf());
+                        }
+                    }
+                }
+            }
+        }
+
+        let force = self.options.force && existing;
+        let result = if let Some(first) = &first_copy {
+            replace(dest, existing)
+                .and_then(|_| fs::hard_link(first, dest))
+                .map_err(|e| {
+                    format!(
+                        "cannot create hard link {} to {}: {}",
+                        quoted(dest),
+                        quoted(first),
+                        describe(&e)
+                    )
+                })
+        } else if method == Method::HardLink {
+            retry(force, dest, || fs::hard_link(source, dest)).map_err(|e| {
+                format!(
+                    "cannot create hard link {} to {}: {}",
+                    quoted(dest),
+                    quoted(source),
+                    describe(&e)
+                )
+            })
+        } else if method == Method::SymbolicLink {
+            self.symlink_to_source(source, dest, metadata, force)
+        } else if link {
+            copy_symlink(source, dest, existing)
+        } else if self.options.recursive && is_special(metadata) {
+            replace(dest, existing)
+                .and_then(|_| make_node(dest, metadata))
+                .map_err(|e| {
+                    format!(
+                        "cannot create special file {}: {}",
+                        quoted(dest),
+                        describe(&e)
+                    )
+                })
+        } else {
+            self.copy_data(source, dest, metadata, existing)
+        };
+        if let Err(message) = result {
+            return self.fail(message);
+        }
+
+        self.created.insert(dest.to_path_buf());
+        self.report(source, dest, backup.as_deref());
+        match (method, &first_copy) {
+            (Method::Copy, None) => self.preserve(entry, dest, true),
+            _ => true,
+        }
+    }
+
+    fn symlink_to_source(
+        &self,
+        source: &Path,
+        dest: &Path,
+        metadata: &Metadata,
+        force: bool,
+    ) -> Result<(), String> {
+        let elsewhere = dest
+            .parent()
+            .is_some_and(|parent| !parent.as_os_str().is_empty() && parent != Path::new("."));
+        if source.is_relative() && elsewhere {
+            return Err(format!(
+                "{}: can make relative symbolic links only in current directory",
+                dest.display()
+            ));
+        }
+        retry(force, dest, || symlink(source, dest, metadata.is_dir())).map_err(|e| {
+            format!(
+                "cannot create symbolic link {} to {}: {}",
+                quoted(dest),
+                quoted(source),
+                describe(&e)
+            )
+        })
+    }
+
+    fn copy_data(
+        &self,
+        source: &Path,
+        dest: &Path,
+        metadata: &Metadata,
+        existing: bool,
+    ) -> Result<(), String> {
+        let mut input = File::open(source).map_err(|e| {
+            format!(
+                "cannot open {} for reading: {}",
+                quoted(source),
+                describe(&e)
+            )
+        })?;
+        let output = match create_file(dest, metadata) {
+            Err(_) if existing && self.options.force => {
+                fs::remove_file(dest).and_then(|_| create_file(dest, metadata))
+            }
+            result => result,
+        };
+        let mut output = output.map_err(|e| {
+            format!(
+                "cannot create regular file {}: {}",
+                quoted(dest),
+                describe(&e)
+            )
+        })?;
+
+        if self.options.reflink != Reflink::Never {
+            match clone(&input, &output) {
+                Ok(()) => return Ok(()),
+                Err(e) if self.options.reflink == Reflink::Always => {
+                    return Err(format!(
+                        "failed to clone {} from {}: {}",
+                        quoted(dest),
+                        quoted(source),
+                        describe(&e)
+                    ))
+                }
+                Err(_) => {}
+            }
+        }
+
+        let holes = match self.options.sparse {
+            Sparse::Never => false,
+            Sparse::Auto => is_sparse(metadata),
+            Sparse::Always => true,
+        };
+        let write_error =
+            |e: io::Error| format!("error writing {}: {}", quoted(dest), describe(&e));
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut length = 0;
+        loop {
+            let len = match input.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(format!(
+                        "error reading {}: {}",
+                        quoted(source),
+                        describe(&e)
+                    ))
+                }
+            };
+            for block in buffer[..len].chunks(HOLE_SIZE) {
+                if holes && block.iter().all(|&byte| byte == 0) {
+                    output
+                        .seek(SeekFrom::Current(block.len() as i64))
+                        .map_err(write_error)?;
+                } else {
+                    output.write_all(block).map_err(write_error)?;
+                }
+            }
+            length += len as u64;
+        }
+        if holes {
+            // Seeking past the end leaves nothing behind unless the
+            // length covers it.
+            output.set_len(length).map_err(write_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether copying `entry` to `dest` would copy a file onto itself,
+/// looking through links at either end.
+fn same_file(entry: &Entry, dest: &Path) -> bool {
+    let mut sources = vec![walk::identify(&entry.path, &entry.metadata)];
+    if entry.metadata.file_type().is_symlink() {
+        if let Ok(target) = fs::metadata(&entry.path) {
+            sources.push(walk::identify(&entry.path, &target));
+        }
+    }
+    let sources: Vec<FileId> = sources
+        .into_iter()
+        .filter_map(|id| id.ok().map(|(id, _)| id))
+        .collect();
+
+    [fs::symlink_metadata(dest), fs::metadata(dest)]
+        .iter()
+        .filter_map(|metadata| metadata.as_ref().ok())
+        .filter_map(|metadata| walk::identify(dest, metadata).ok())
+        .any(|(id, _)| sources.contains(&id))
+}
+
+fn is_up_to_date(dest: &Path, source: &Metadata) -> bool {
+    match (
+        fs::metadata(dest).and_then(|dest| dest.modified()),
+        source.modified(),
+    ) {
+        (Ok(dest), Ok(source)) => dest >= source,
+        _ => false,
+    }
+}
+
+/// Clear `dest` out of the way of a new file if something is there.
+fn replace(dest: &Path, existing: bool) -> io::Result<()> {
+    if existing {
+        fs::remove_file(dest)?;
+    }
+    Ok(())
+}
+
+/// Make `dest` with `make`, and when `force` is set and something is in
+/// the way, remove it and try once more.
+fn retry<F: Fn() -> io::Result<()>>(force: bool, dest: &Path, make: F) -> io::Result<()> {
+    match make() {
+        Err(e) if force && e.kind() == ErrorKind::AlreadyExists => {
+            fs::remove_file(dest)?;
+            make()
+        }
+        result => result,
+    }
+}
+
+fn copy_symlink(source: &Path, dest: &Path, existing: bool) -> Result<(), String> {
+    let target = fs::read_link(source).map_err(|e| {
+        format!(
+            "cannot read symbolic link {}: {}",
+            quoted(source),
+            describe(&e)
+        )
+    })?;
+    let dir = fs::metadata(source).is_ok_and(|metadata| metadata.is_dir());
+    replace(dest, existing)
+        .and_then(|_| symlink(&target, dest, dir))
+        .map_err(|e| {
+            format!(
+                "cannot create symbolic link {} to {}: {}",
+                quoted(dest),
+                quoted(&target),
+                describe(&e)
+            )
+        })
+}
+
+#[cfg(unix)]
+fn current_umask() -> u32 {
+    // The only way to read it is to change it and put it back.
+    unsafe {
+        let mask = libc::umask(0);
+        libc::umask(mask);
+        mask as u32
+    }
+}
+
+#[cfg(unix)]
+fn c_path(path: &Path) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Ok(std::ffi::CString::new(path.as_os_str().as_bytes())?)
+}
+
+/// Make a directory like the one `metadata` describes, writable by us
+/// until its permissions are settled on the way out.
+#[cfg(unix)]
+fn create_dir(path: &Path, metadata: &Metadata) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let mode = metadata.permissions().mode() & 0o7777;
+    fs::DirBuilder::new().mode(mode | 0o700).create(path)
+}
+
+#[cfg(windows)]
+fn create_dir(path: &Path, _metadata: &Metadata) -> io::Result<()> {
+    fs::create_dir(path)
+}
+
+/// Open `path` for a copy of the file `metadata` describes, made with
+/// its permissions if new.
+#[cfg(unix)]
+fn create_file(path: &Path, metadata: &Metadata) -> io::Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(metadata.permissions().mode() & 0o777)
+        .open(path)
+}
+
+#[cfg(windows)]
+fn create_file(path: &Path, _metadata: &Metadata) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path, _dir: bool) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/// Windows keeps links to directories and to files apart.
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path, dir: bool) -> io::Result<()> {
+    if dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// A FIFO, socket or device, which a recursive copy recreates rather
+/// than reading.
+#[cfg(unix)]
+fn is_special(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    let file_type = metadata.file_type();
+    file_type.is_fifo()
+        || file_type.is_socket()
+        || file_type.is_char_device()
+        || file_type.is_block_device()
+}
+
+#[cfg(windows)]
+fn is_special(_metadata: &Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn make_node(path: &Path, metadata: &Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = c_path(path)?;
+    let mode = metadata.mode() as libc::mode_t;
+    if unsafe { libc::mknod(path.as_ptr(), mode, metadata.rdev() as libc::dev_t) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn make_node(_path: &Path, _metadata: &Metadata) -> io::Result<()> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        "Operation not supported",
+    ))
+}
+
+/// A crude guess at whether the source has holes: fewer blocks than
+/// its length needs.
+#[cfg(unix)]
+fn is_sparse(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks().saturating_mul(512) < metadata.len()
+}
+
+#[cfg(windows)]
+fn is_sparse(metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use winapi::um::winnt::FILE_ATTRIBUTE_SPARSE_FILE;
+
+    metadata.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0
+}
+
+#[cfg(target_os = "linux")]
+fn clone(input: &File, output: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::ioctl(output.as_raw_fd(), libc::FICLONE, input.as_raw_fd()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn clone(_input: &File, _output: &File) -> io::Result<()> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        "Operation not supported",
+    ))
+}
+
+#[cfg(unix)]
+fn set_owner(path: &Path, metadata: &Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::os::unix::fs::lchown(path, Some(metadata.uid()), Some(metadata.gid()))
+}
+
+/// Owners on Windows live in security descriptors, which are left to
+/// the defaults of the destination.
+#[cfg(windows)]
+fn set_owner(_path: &Path, _metadata: &Metadata) -> io::Result<()> {
+    Ok(())
+}
+
+/// Set the access and modification times of `path`, or of the link
+/// itself when `link` is set.
+#[cfg(unix)]
+fn set_times(path: &Path, metadata: &Metadata, link: bool) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = c_path(path)?;
+    let times = [
+        libc::timespec {
+            tv_sec: metadata.atime() as libc::time_t,
+            tv_nsec: metadata.atime_nsec() as _,
+        },
+        libc::timespec {
+            tv_sec: metadata.mtime() as libc::time_t,
+            tv_nsec: metadata.mtime_nsec() as _,
+        },
+    ];
+    let flags = if link { libc::AT_SYMLINK_NOFOLLOW } else { 0 };
+    if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_times(path: &Path, metadata: &Metadata, link: bool) -> io::Result<()> {
+    use std::fs::FileTimes;
+    use std::os::windows::fs::OpenOptionsExt;
+    use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+    use winapi::um::winnt::FILE_WRITE_ATTRIBUTES;
+
+    let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+    if link {
+        flags |= FILE_FLAG_OPEN_REPARSE_POINT;
+    }
+    let file = OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .custom_flags(flags)
+        .open(path)?;
+    let times = FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    file.set_times(times)
+}
+
+/// Copy the extended attributes of `source` to `dest`.
+#[cfg(target_os = "linux")]
+fn copy_xattrs(source: &Path, dest: &Path, link: bool) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_void};
+    use std::ptr;
+
+    let source = c_path(source)?;
+    let dest = c_path(dest)?;
+    // Calls return a length, or -1 with errno set.
+    let checked = |len: libc::ssize_t| {
+        if len < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(len as usize)
+        }
+    };
+
+    let list = |buffer: *mut c_char, size: usize| unsafe {
+        match link {
+            true => libc::llistxattr(source.as_ptr(), buffer, size),
+            false => libc::listxattr(source.as_ptr(), buffer, size),
+        }
+    };
+    let mut names = vec![0u8; checked(list(ptr::null_mut(), 0))?];
+    let len = checked(list(names.as_mut_ptr() as *mut c_char, names.len()))?;
+    names.truncate(len);
+
+    for name in names
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+    {
+        let name = CString::new(name)?;
+        let get = |buffer: *mut c_void, size: usize| unsafe {
+            match link {
+                true => libc::lgetxattr(source.as_ptr(), name.as_ptr(), buffer, size),
+                false => libc::getxattr(source.as_ptr(), name.as_ptr(), buffer, size),
+            }
+        };
+        let mut value = vec![0u8; checked(get(ptr::null_mut(), 0))?];
+        let len = checked(get(value.as_mut_ptr() as *mut c_void, value.len()))?;
+        let value = value.as_ptr() as *const c_void;
+        let set = unsafe {
+            match link {
+                true => libc::lsetxattr(dest.as_ptr(), name.as_ptr(), value, len, 0),
+                false => libc::setxattr(dest.as_ptr(), name.as_ptr(), value, len, 0),
+            }
+        };
+        checked(set as libc::ssize_t)?;
+    }
+    Ok(())
+}
+
+/// Windows has no extended attributes in the Unix sense; the nearest
+/// are the hidden, system, archive and indexing flags.
+#[cfg(windows)]
+fn copy_xattrs(source: &Path, dest: &Path, link: bool) -> io::Result<()> {
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use winapi::um::fileapi::SetFileAttributesW;
+    use winapi::um::winnt::{
+        FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NOT_CONTENT_INDEXED,
+        FILE_ATTRIBUTE_SYSTEM,
+    };
+
+    const COPIED: u32 = FILE_ATTRIBUTE_ARCHIVE
+        | FILE_ATTRIBUTE_HIDDEN
+        | FILE_ATTRIBUTE_NOT_CONTENT_INDEXED
+        | FILE_ATTRIBUTE_SYSTEM;
+    let stat = |path: &Path| match link {
+        true => fs::symlink_metadata(path),
+        false => fs::metadata(path),
+    };
+    let wanted = stat(source)?.file_attributes() & COPIED;
+    let attributes = stat(dest)?.file_attributes() & !COPIED | wanted;
+    let wide: Vec<u16> = dest.as_os_str().encode_wide().chain(once(0)).collect();
+    if unsafe { SetFileAttributesW(wide.as_ptr(), attributes) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn copy_xattrs(_source: &Path, _dest: &Path, _link: bool) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::Control;
+
+    fn copier(options: Options) -> Copier<Vec<u8>> {
+        Copier::new("cp", options, Output::new("cp", Vec::new()))
+    }
+
+    fn recursive() -> Options {
+        Options {
+            recursive: true,
+            follow: Follow::Never,
+            ..Options::default()
+        }
+    }
+
+    fn verbose_output(copier: &mut Copier<Vec<u8>>) -> String {
+        copier.output().flush();
+        String::from_utf8(copier.output().get_ref().clone()).unwrap()
+    }
+
+    #[test]
+    fn single_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&source, "data").unwrap();
+        assert!(copier(Options::default()).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "data");
+
+        fs::write(&source, "new").unwrap();
+        assert!(copier(Options::default()).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+
+        assert!(!copier(Options::default()).copy(&source, &source));
+        assert!(!copier(Options::default()).copy(&temp.path().join("missing"), &dest));
+    }
+
+    #[test]
+    fn tree() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("src");
+        fs::create_dir_all(source.join("sub/deeper")).unwrap();
+        fs::write(source.join("top"), "top").unwrap();
+        fs::write(source.join("sub/deeper/file"), "deep").unwrap();
+
+        assert!(!copier(Options::default()).copy(&source, &temp.path().join("flat")));
+
+        let dest = temp.path().join("dest");
+        assert!(copier(recursive()).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(dest.join("top")).unwrap(), "top");
+        assert_eq!(
+            fs::read_to_string(dest.join("sub/deeper/file")).unwrap(),
+            "deep"
+        );
+
+        assert!(!copier(recursive()).copy(&source, &source.join("sub/inside")));
+        assert!(!source.join("sub/inside").exists());
+    }
+
+    #[test]
+    fn verbose_and_backups() {
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&source, "new").unwrap();
+        fs::write(&dest, "old").unwrap();
+
+        let options = Options {
+            verbose: true,
+            backup: Backup {
+                control: Control::Simple,
+                suffix: String::from(".orig"),
+            },
+            ..Options::default()
+        };
+        let mut copier = copier(options);
+        assert!(copier.copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+        let backup = temp.path().join("b.orig");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "old");
+        assert_eq!(
+            verbose_output(&mut copier),
+            format!(
+                "{} -> {} (backup: {})\n",
+                quoted(&source),
+                quoted(&dest),
+                quoted(&backup)
+            )
+        );
+
+        // A source given twice is copied once.
+        assert!(copier.copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+
+        // The copy just made is not replaced by a second source.
+        let other = temp.path().join("c");
+        fs::write(&other, "other").unwrap();
+        assert!(!copier.copy(&other, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+    }
+
+    #[test]
+    fn no_clobber_and_update() {
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&dest, "old").unwrap();
+        fs::write(&source, "new").unwrap();
+
+        let options = Options {
+            overwrite: Overwrite::Never,
+            ..Options::default()
+        };
+        assert!(copier(options).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+
+        let options = Options {
+            update: true,
+            ..Options::default()
+        };
+        let dest_file = File::options().write(true).open(&dest).unwrap();
+        let later =
+            fs::metadata(&source).unwrap().modified().unwrap() + std::time::Duration::from_secs(60);
+        dest_file.set_modified(later).unwrap();
+        assert!(copier(options.clone()).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+
+        let earlier = later - std::time::Duration::from_secs(3600);
+        dest_file.set_modified(earlier).unwrap();
+        assert!(copier(options).copy(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+    }
+
+    #[test]
+    fn sparse_copies_keep_their_data() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("holes");
+        let mut data = vec![0u8; 3 * BUFFER_SIZE + 10];
+        data[HOLE_SIZE + 1] = 1;
+        data[2 * BUFFER_SIZE] = 2;
+        fs::write(&source, &data).unwrap();
+
+        for sparse in &[Sparse::Never, Sparse::Auto, Sparse::Always] {
+            let options = Options {
+                sparse: *sparse,
+                ..Options::default()
+            };
+            let dest = temp.path().join(format!("{:?}", sparse));
+            assert!(copier(options).copy(&source, &dest));
+            assert_eq!(fs::read(&dest).unwrap(), data, "{:?}", sparse);
+        }
+    }
+
+    #[test]
+    fn hard_link_topology() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("src");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("one"), "shared").unwrap();
+        fs::hard_link(source.join("one"), source.join("sub/two")).unwrap();
+        fs::hard_link(source.join("one"), source.join("three")).unwrap();
+        fs::write(source.join("single"), "single").unwrap();
+
+        let id = |path: PathBuf| {
+            let metadata = fs::symlink_metadata(&path).unwrap();
+            walk::identify(&path, &metadata).unwrap()
+        };
+
+        let plain = temp.path().join("plain");
+        assert!(copier(recursive()).copy(&source, &plain));
+        assert_eq!(id(plain.join("one")).1, 1);
+        assert_ne!(id(plain.join("one")).0, id(plain.join("three")).0);
+
+        let mut options = recursive();
+        options.preserve.links = true;
+        let linked = temp.path().join("linked");
+        assert!(copier(options).copy(&source, &linked));
+        let (first, links) = id(linked.join("one"));
+        assert_eq!(links, 3);
+        assert_eq!(id(linked.join("sub/two")).0, first);
+        assert_eq!(id(linked.join("three")).0, first);
+        assert_eq!(id(linked.join("single")).1, 1);
+        assert_eq!(
+            fs::read_to_string(linked.join("sub/two")).unwrap(),
+            "shared"
+        );
+    }
+
+    #[test]
+    fn link_methods() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("a");
+        fs::write(&source, "data").unwrap();
+
+        let mut options = Options {
+            method: Method::HardLink,
+            ..Options::default()
+        };
+        let linked = temp.path().join("hard");
+        assert!(copier(options.clone()).copy(&source, &linked));
+        assert_eq!(id_of(&linked), id_of(&source));
+        // Linking again to the same file is already done.
+        assert!(copier(options.clone()).copy(&source, &linked));
+
+        let other = temp.path().join("other");
+        fs::write(&other, "other").unwrap();
+        assert!(!copier(options.clone()).copy(&source, &other));
+        options.force = true;
+        assert!(copier(options).copy(&source, &other));
+        assert_eq!(id_of(&other), id_of(&source));
+
+        let options = Options {
+            method: Method::SymbolicLink,
+            ..Options::default()
+        };
+        let relative = Path::new("relative").join("a");
+        assert!(!copier(options.clone()).copy(&relative, &temp.path().join("sym")));
+        assert!(copier(options).copy(&source, &temp.path().join("sym")));
+        assert_eq!(fs::read_link(temp.path().join("sym")).unwrap(), source);
+    }
+
+    fn id_of(path: &Path) -> FileId {
+        walk::identify(path, &fs::metadata(path).unwrap())
+            .unwrap()
+            .0
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn preserved_metadata() {
+        use std::os::unix::fs::{symlink, MetadataExt, PermissionsExt};
+
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("src");
+        fs::create_dir(&source).unwrap();
+        let file = source.join("file");
+        fs::write(&file, "data").unwrap();
+        symlink("file", source.join("link")).unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+        let past = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::new(1_000_000_000, 5);
+        File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+        // Read only, so filling the copy needs it writable for a while.
+        fs::set_permissions(&source, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let options = Options {
+            preserve: Preserve::all(),
+            ..recursive()
+        };
+        let dest = temp.path().join("dest");
+        let copied = copier(options).copy(&source, &dest);
+        fs::set_permissions(&source, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(copied);
+
+        let metadata = fs::metadata(dest.join("file")).unwrap();
+        assert_eq!(metadata.mode() & 0o7777, 0o640);
+        assert_eq!(metadata.modified().unwrap(), past);
+        assert_eq!(fs::metadata(&dest).unwrap().mode() & 0o7777, 0o555);
+        assert_eq!(fs::read_link(dest.join("link")).unwrap(), Path::new("file"));
+        fs::set_permissions(&dest, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Without preserving, a new file keeps its permissions but not
+        // its times.
+        let plain = temp.path().join("plain");
+        assert!(copier(Options::default()).copy(&file, &plain));
+        let metadata = fs::metadata(&plain).unwrap();
+        assert_eq!(metadata.mode() & 0o077, 0o040 & !current_umask());
+        assert_ne!(metadata.modified().unwrap(), past);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn followed_links() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("src");
+        fs::create_dir(&source).unwrap();
+        fs::write(source.join("file"), "data").unwrap();
+        symlink("file", source.join("link")).unwrap();
+
+        let options = Options {
+            follow: Follow::Always,
+            ..recursive()
+        };
+        let dest = temp.path().join("dest");
+        assert!(copier(options).copy(&source, &dest));
+        let metadata = fs::symlink_metadata(dest.join("link")).unwrap();
+        assert!(metadata.file_type().is_file());
+        assert_eq!(fs::read_to_string(dest.join("link")).unwrap(), "data");
+    }
+
+    #[test]
+    fn targets() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_string_lossy().into_owned();
+        let file = temp.path().join("file");
+        fs::write(&file, "").unwrap();
+        let file = file.to_string_lossy().into_owned();
+        let names = |list: &[&str]| list.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            target(&names(&["a", &dir]), None, false),
+            Ok(Target::Directory(
+                vec![PathBuf::from("a")],
+                PathBuf::from(&dir)
+            ))
+        );
+        assert_eq!(
+            target(&names(&["a", &dir]), None, true),
+            Ok(Target::File(PathBuf::from("a"), PathBuf::from(&dir)))
+        );
+        assert_eq!(
+            target(&names(&["a", "new"]), None, false),
+            Ok(Target::File(PathBuf::from("a"), PathBuf::from("new")))
+        );
+        assert_eq!(
+            target(&names(&["a", "b"]), Some(&dir), false),
+            Ok(Target::Directory(
+                vec![PathBuf::from("a"), PathBuf::from("b")],
+                PathBuf::from(&dir)
+            ))
+        );
+        assert!(target(&names(&["a", "b", &file]), None, false)
+            .unwrap_err()
+            .ends_with("Not a directory"));
+        assert!(target(&names(&["a"]), Some(&file), false)
+            .unwrap_err()
+            .starts_with("target directory"));
+        assert_eq!(
+            into_directory(Path::new("x/y/"), Path::new("d")),
+            Path::new("d/y")
+        );
+    }
+
+    #[test]
+    fn parents() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("a/b/c");
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, "c").unwrap();
+        let dir = temp.path().join("dest");
+        fs::create_dir(&dir).unwrap();
+
+        let mut copier = copier(Options::default());
+        let dest = copier.make_parents(&source, &dir).unwrap();
+        assert!(copier.copy(&source, &dest));
+        let relative = source.strip_prefix("/").unwrap_or(&source);
+        assert_eq!(dest, dir.join(relative));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "c");
+    }
+}
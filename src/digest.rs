@@ -0,0 +1,190 @@
+mod blake2b;
+mod md5;
+mod sha1;
+mod sha2;
+mod sum;
+
+/// A checksum or hash computed over data fed to it in pieces.
+pub trait Digest {
+    fn update(&mut self, data: &[u8]);
+    /// The checksum of everything fed in, most significant byte first.
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    /// The BSD `sum` checksum: 16 bits, rotated right before each byte.
+    Bsd,
+    /// The System V `sum` checksum: the bytes added up, folded to 16 bits.
+    Sysv,
+    /// The POSIX `cksum` CRC, which covers the length as well.
+    Crc,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Blake2b,
+}
+
+/// Names as `cksum -a` takes them, in the order it lists them.
+pub const NAMES: [(&str, Algorithm); 10] = [
+    ("bsd", Algorithm::Bsd),
+    ("sysv", Algorithm::Sysv),
+    ("crc", Algorithm::Crc),
+    ("md5", Algorithm::Md5),
+    ("sha1", Algorithm::Sha1),
+    ("sha224", Algorithm::Sha224),
+    ("sha256", Algorithm::Sha256),
+    ("sha384", Algorithm::Sha384),
+    ("sha512", Algorithm::Sha512),
+    ("blake2b", Algorithm::Blake2b),
+];
+
+impl Algorithm {
+    pub fn from_name(name: &str) -> Option<Algorithm> {
+        NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|&(_, algorithm)| algorithm)
+    }
+
+    /// The name BSD-style checksum lines start with.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Algorithm::Bsd => "BSD",
+            Algorithm::Sysv => "SYSV",
+            Algorithm::Crc => "CRC",
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha224 => "SHA224",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha384 => "SHA384",
+            Algorithm::Sha512 => "SHA512",
+            Algorithm::Blake2b => "BLAKE2b",
+        }
+    }
+
+    /// Bits in the result, the most for BLAKE2b.
+    pub fn bits(self) -> usize {
+        match self {
+            Algorithm::Bsd | Algorithm::Sysv => 16,
+            Algorithm::Crc => 32,
+            Algorithm::Md5 => 128,
+            Algorithm::Sha1 => 160,
+            Algorithm::Sha224 => 224,
+            Algorithm::Sha256 => 256,
+            Algorithm::Sha384 => 384,
+            Algorithm::Sha512 | Algorithm::Blake2b => 512,
+        }
+    }
+
+    /// Whether this is a cryptographic hash, printed in hex, rather than
+    /// one of the old checksums printed as a number.
+    pub fn is_hash(self) -> bool {
+        !matches!(self, Algorithm::Bsd | Algorithm::Sysv | Algorithm::Crc)
+    }
+
+    /// A fresh digest. `bits` sets the length of a BLAKE2b hash, a multiple
+    /// of 8 up to 512, and is ignored by the others.
+    pub fn digest(self, bits: usize) -> Box<dyn Digest> {
+        match self {
+            Algorithm::Bsd => Box::new(sum::Bsd::new()),
+            Algorithm::Sysv => Box::new(sum::Sysv::new()),
+            Algorithm::Crc => Box::new(sum::Crc::new()),
+            Algorithm::Md5 => Box::new(md5::Md5::new()),
+            Algorithm::Sha1 => Box::new(sha1::Sha1::new()),
+            Algorithm::Sha224 => Box::new(sha2::Sha256::new_224()),
+            Algorithm::Sha256 => Box::new(sha2::Sha256::new()),
+            Algorithm::Sha384 => Box::new(sha2::Sha512::new_384()),
+            Algorithm::Sha512 => Box::new(sha2::Sha512::new()),
+            Algorithm::Blake2b => Box::new(blake2b::Blake2b::new(bits / 8)),
+        }
+    }
+}
+
+/// Lower case hex digits for `bytes`.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Feeds `data` to `compress` a block at a time, holding back a partial
+/// block in `buffer` until the rest of it arrives.
+fn absorb<F: FnMut(&[u8])>(buffer: &mut Vec<u8>, block: usize, mut data: &[u8], mut compress: F) {
+    if !buffer.is_empty() {
+        let take = data.len().min(block - buffer.len());
+        buffer.extend_from_slice(&data[..take]);
+        data = &data[take..];
+        if buffer.len() < block {
+            return;
+        }
+        compress(buffer);
+        buffer.clear();
+    }
+    let mut blocks = data.chunks_exact(block);
+    for chunk in &mut blocks {
+        compress(chunk);
+    }
+    buffer.extend_from_slice(blocks.remainder());
+}
+
+/// The Merkle-Damgård padding MD5 and the SHA family end with: a 1 bit,
+/// zeros, then the length in bits in the last `length.len()` bytes.
+fn padding(buffered: usize, block: usize, length: &[u8]) -> Vec<u8> {
+    let mut pad = vec![0x80];
+    let room = block - length.len();
+    let zeros = (room + block - (buffered + 1) % block) % block;
+    pad.resize(1 + zeros, 0);
+    pad.extend_from_slice(length);
+    pad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hex digest of `data`, fed in pieces of every size from 1 up to
+    /// check that blocks split between calls come out the same.
+    pub fn hex_digest(algorithm: Algorithm, bits: usize, data: &[u8]) -> String {
+        let mut whole = algorithm.digest(bits);
+        whole.update(data);
+        let expected = hex(&whole.finish());
+        for piece in [1, 3, 63, 64, 65, 127, 128, 129] {
+            let mut digest = algorithm.digest(bits);
+            for chunk in data.chunks(piece) {
+                digest.update(chunk);
+            }
+            assert_eq!(hex(&digest.finish()), expected, "pieces of {}", piece);
+        }
+        expected
+    }
+
+    pub const TWO_BLOCKS: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    pub const TWO_LONG_BLOCKS: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+        hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    /// Hex digest of a million `a`s, fed in one go.
+    pub fn million_a(algorithm: Algorithm) -> String {
+        let mut digest = algorithm.digest(0);
+        digest.update(&vec![b'a'; 1_000_000]);
+        hex(&digest.finish())
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Algorithm::from_name("sha256"), Some(Algorithm::Sha256));
+        assert_eq!(Algorithm::from_name("SHA256"), None);
+        assert_eq!(Algorithm::Blake2b.tag(), "BLAKE2b");
+        assert!(!Algorithm::Crc.is_hash());
+    }
+
+    #[test]
+    fn padding_lengths() {
+        for buffered in 0..130 {
+            let pad = padding(buffered, 64, &[0; 8]);
+            assert_eq!((buffered + pad.len()) % 64, 0, "{}", buffered);
+            assert!(pad.len() >= 9);
+        }
+    }
+}
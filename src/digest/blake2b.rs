@@ -0,0 +1,174 @@
+use super::Digest;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The order message words are mixed in, one row per round; rounds 10 and
+/// 11 reuse the first two rows.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLOCK: usize = 128;
+
+/// The RFC 7693 hash, unkeyed, with a digest of 1 to 64 bytes.
+pub struct Blake2b {
+    state: [u64; 8],
+    size: usize,
+    /// Unlike the Merkle-Damgård hashes, the last block is compressed
+    /// differently, so a full block waits here until more data comes.
+    buffer: Vec<u8>,
+    length: u128,
+}
+
+fn mix(v: &mut [u64; 16], (a, b, c, d): (usize, usize, usize, usize), x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+fn compress(state: &mut [u64; 8], block: &[u8], length: u128, last: bool) {
+    let mut words = [0u64; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(8)) {
+        let mut array = [0; 8];
+        array.copy_from_slice(bytes);
+        *word = u64::from_le_bytes(array);
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(state);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= length as u64;
+    v[13] ^= (length >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for round in 0..12 {
+        let s = &SIGMA[round % 10];
+        mix(&mut v, (0, 4, 8, 12), words[s[0]], words[s[1]]);
+        mix(&mut v, (1, 5, 9, 13), words[s[2]], words[s[3]]);
+        mix(&mut v, (2, 6, 10, 14), words[s[4]], words[s[5]]);
+        mix(&mut v, (3, 7, 11, 15), words[s[6]], words[s[7]]);
+        mix(&mut v, (0, 5, 10, 15), words[s[8]], words[s[9]]);
+        mix(&mut v, (1, 6, 11, 12), words[s[10]], words[s[11]]);
+        mix(&mut v, (2, 7, 8, 13), words[s[12]], words[s[13]]);
+        mix(&mut v, (3, 4, 9, 14), words[s[14]], words[s[15]]);
+    }
+
+    for (idx, value) in state.iter_mut().enumerate() {
+        *value ^= v[idx] ^ v[idx + 8];
+    }
+}
+
+impl Blake2b {
+    pub fn new(size: usize) -> Blake2b {
+        let mut state = IV;
+        state[0] ^= 0x0101_0000 ^ size as u64;
+        Blake2b {
+            state,
+            size,
+            buffer: Vec::with_capacity(BLOCK),
+            length: 0,
+        }
+    }
+}
+
+impl Digest for Blake2b {
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.buffer.len() == BLOCK {
+                self.length += BLOCK as u128;
+                compress(&mut self.state, &self.buffer, self.length, false);
+                self.buffer.clear();
+            }
+            let take = data.len().min(BLOCK - self.buffer.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        self.length += self.buffer.len() as u128;
+        self.buffer.resize(BLOCK, 0);
+        compress(&mut self.state, &self.buffer, self.length, true);
+        let mut hash: Vec<u8> = self
+            .state
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        hash.truncate(self.size);
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::digest::tests::hex_digest;
+    use crate::digest::Algorithm;
+
+    #[test]
+    fn rfc_7693_vectors() {
+        let hash = |bits: usize, data: &[u8]| hex_digest(Algorithm::Blake2b, bits, data);
+        assert_eq!(
+            hash(512, b"abc"),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(
+            hash(512, b""),
+            "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+             d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+        );
+    }
+
+    #[test]
+    fn shorter_digests() {
+        let hash = |bits: usize, data: &[u8]| hex_digest(Algorithm::Blake2b, bits, data);
+        assert_eq!(
+            hash(256, b"abc"),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+        assert_eq!(
+            hash(256, b""),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+        assert_eq!(hash(8, b"abc"), "6b");
+        // Whole blocks, so the last one must wait for the end.
+        assert_eq!(
+            hash(512, &[b'a'; 128]),
+            "fc6c71f688f43ea7d60817478808f3cac753e61571865c95adbc2d9122c943a7\
+             6b92c2cb1047ef3fe7bf6e436ec1d0a99a9e5b216780bf7fed9d7ca91d3a8f3b"
+        );
+        assert_eq!(
+            hash(256, &[b'a'; 256]),
+            "eae4d3a7627549b383179dc18049964f91a6fed14c9f3fb26705eda3eeda5558"
+        );
+        assert_eq!(
+            hash(256, &[b'a'; 1000]),
+            "e00b0ddbf1e2cdaf5c898e1a5e8826ea3a2c339bcf2a478da2e5fca9ff126672"
+        );
+    }
+}
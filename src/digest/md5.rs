@@ -0,0 +1,117 @@
+use super::{absorb, padding, Digest};
+
+/// Left rotation for each step, four per round.
+const SHIFTS: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+/// The RFC 1321 message digest.
+pub struct Md5 {
+    state: [u32; 4],
+    table: [u32; 64],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+/// `floor(abs(sin(i + 1)) * 2^32)`, the constant added at step `i`.
+fn constants() -> [u32; 64] {
+    let mut table = [0; 64];
+    for (idx, value) in table.iter_mut().enumerate() {
+        *value = ((idx as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32;
+    }
+    table
+}
+
+fn compress(state: &mut [u32; 4], table: &[u32; 64], block: &[u8]) {
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for step in 0..64 {
+        let (mix, index) = match step / 16 {
+            0 => ((b & c) | (!b & d), step),
+            1 => ((d & b) | (!d & c), (5 * step + 1) % 16),
+            2 => (b ^ c ^ d, (3 * step + 5) % 16),
+            _ => (c ^ (b | !d), (7 * step) % 16),
+        };
+        let sum = a
+            .wrapping_add(mix)
+            .wrapping_add(table[step])
+            .wrapping_add(words[index]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(sum.rotate_left(SHIFTS[step / 16][step % 4]));
+    }
+
+    for (value, add) in state.iter_mut().zip([a, b, c, d]) {
+        *value = value.wrapping_add(add);
+    }
+}
+
+impl Md5 {
+    pub fn new() -> Md5 {
+        Md5 {
+            state: [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476],
+            table: constants(),
+            buffer: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+}
+
+impl Digest for Md5 {
+    fn update(&mut self, data: &[u8]) {
+        let (state, table) = (&mut self.state, &self.table);
+        absorb(&mut self.buffer, 64, data, |block| {
+            compress(state, table, block)
+        });
+        self.length = self.length.wrapping_add(data.len() as u64);
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.length.wrapping_mul(8);
+        let pad = padding(self.buffer.len(), 64, &bits.to_le_bytes());
+        self.update(&pad);
+        self.state
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::digest::tests::hex_digest;
+    use crate::digest::Algorithm;
+
+    #[test]
+    fn rfc_1321_suite() {
+        let cases = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(hex_digest(Algorithm::Md5, 0, text.as_bytes()), expected);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{absorb, padding, Digest};
+
+/// The FIPS 180-4 SHA-1 hash.
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let mut words = [0u32; 80];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for idx in 16..80 {
+        words[idx] =
+            (words[idx - 3] ^ words[idx - 8] ^ words[idx - 14] ^ words[idx - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (idx, &word) in words.iter().enumerate() {
+        let (mix, constant) = match idx / 20 {
+            0 => ((b & c) | (!b & d), 0x5a82_7999),
+            1 => (b ^ c ^ d, 0x6ed9_eba1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(mix)
+            .wrapping_add(e)
+            .wrapping_add(constant)
+            .wrapping_add(word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+        *value = value.wrapping_add(add);
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Sha1 {
+        Sha1 {
+            state: [
+                0x6745_2301,
+                0xefcd_ab89,
+                0x98ba_dcfe,
+                0x1032_5476,
+                0xc3d2_e1f0,
+            ],
+            buffer: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+}
+
+impl Digest for Sha1 {
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        absorb(&mut self.buffer, 64, data, |block| compress(state, block));
+        self.length = self.length.wrapping_add(data.len() as u64);
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.length.wrapping_mul(8);
+        let pad = padding(self.buffer.len(), 64, &bits.to_be_bytes());
+        self.update(&pad);
+        self.state
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::digest::tests::{hex_digest, million_a, TWO_BLOCKS, TWO_LONG_BLOCKS};
+    use crate::digest::Algorithm;
+
+    #[test]
+    fn fips_180_vectors() {
+        let hash = |data: &[u8]| hex_digest(Algorithm::Sha1, 0, data);
+        assert_eq!(hash(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hash(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hash(TWO_BLOCKS), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+        assert_eq!(
+            hash(TWO_LONG_BLOCKS),
+            "a49b2446a02c645bf419f995b67091253a04a259"
+        );
+        assert_eq!(
+            million_a(Algorithm::Sha1),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+}
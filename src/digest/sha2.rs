@@ -0,0 +1,397 @@
+use super::{absorb, padding, Digest};
+
+const ROUNDS_256: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const ROUNDS_512: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// SHA-256, and SHA-224 which starts from other values and drops the
+/// last word.
+pub struct Sha256 {
+    state: [u32; 8],
+    size: usize,
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+/// SHA-512, and SHA-384 which starts from other values and drops the last
+/// two words.
+pub struct Sha512 {
+    state: [u64; 8],
+    size: usize,
+    buffer: Vec<u8>,
+    length: u128,
+}
+
+fn compress_256(state: &mut [u32; 8], block: &[u8]) {
+    let mut words = [0u32; 64];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for idx in 16..64 {
+        let (early, late) = (words[idx - 15], words[idx - 2]);
+        let s0 = early.rotate_right(7) ^ early.rotate_right(18) ^ (early >> 3);
+        let s1 = late.rotate_right(17) ^ late.rotate_right(19) ^ (late >> 10);
+        words[idx] = words[idx - 16]
+            .wrapping_add(s0)
+            .wrapping_add(words[idx - 7])
+            .wrapping_add(s1);
+    }
+
+    let mut vars = *state;
+    for (&word, &constant) in words.iter().zip(ROUNDS_256.iter()) {
+        let [a, b, c, d, e, f, g, h] = vars;
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(constant)
+            .wrapping_add(word);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(majority);
+        vars = [
+            temp1.wrapping_add(temp2),
+            a,
+            b,
+            c,
+            d.wrapping_add(temp1),
+            e,
+            f,
+            g,
+        ];
+    }
+
+    for (value, add) in state.iter_mut().zip(vars) {
+        *value = value.wrapping_add(add);
+    }
+}
+
+fn compress_512(state: &mut [u64; 8], block: &[u8]) {
+    let mut words = [0u64; 80];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(8)) {
+        let mut array = [0; 8];
+        array.copy_from_slice(bytes);
+        *word = u64::from_be_bytes(array);
+    }
+    for idx in 16..80 {
+        let (early, late) = (words[idx - 15], words[idx - 2]);
+        let s0 = early.rotate_right(1) ^ early.rotate_right(8) ^ (early >> 7);
+        let s1 = late.rotate_right(19) ^ late.rotate_right(61) ^ (late >> 6);
+        words[idx] = words[idx - 16]
+            .wrapping_add(s0)
+            .wrapping_add(words[idx - 7])
+            .wrapping_add(s1);
+    }
+
+    let mut vars = *state;
+    for (&word, &constant) in words.iter().zip(ROUNDS_512.iter()) {
+        let [a, b, c, d, e, f, g, h] = vars;
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let choice = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(constant)
+            .wrapping_add(word);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(majority);
+        vars = [
+            temp1.wrapping_add(temp2),
+            a,
+            b,
+            c,
+            d.wrapping_add(temp1),
+            e,
+            f,
+            g,
+        ];
+    }
+
+    for (value, add) in state.iter_mut().zip(vars) {
+        *value = value.wrapping_add(add);
+    }
+}
+
+impl Sha256 {
+    pub fn new() -> Sha256 {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            size: 32,
+            buffer: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+
+    pub fn new_224() -> Sha256 {
+        Sha256 {
+            state: [
+                0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7,
+                0xbefa4fa4,
+            ],
+            size: 28,
+            buffer: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+}
+
+impl Digest for Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        absorb(&mut self.buffer, 64, data, |block| {
+            compress_256(state, block)
+        });
+        self.length = self.length.wrapping_add(data.len() as u64);
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.length.wrapping_mul(8);
+        let pad = padding(self.buffer.len(), 64, &bits.to_be_bytes());
+        self.update(&pad);
+        let mut hash: Vec<u8> = self
+            .state
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        hash.truncate(self.size);
+        hash
+    }
+}
+
+impl Sha512 {
+    pub fn new() -> Sha512 {
+        Sha512 {
+            state: [
+                0x6a09e667f3bcc908,
+                0xbb67ae8584caa73b,
+                0x3c6ef372fe94f82b,
+                0xa54ff53a5f1d36f1,
+                0x510e527fade682d1,
+                0x9b05688c2b3e6c1f,
+                0x1f83d9abfb41bd6b,
+                0x5be0cd19137e2179,
+            ],
+            size: 64,
+            buffer: Vec::with_capacity(128),
+            length: 0,
+        }
+    }
+
+    pub fn new_384() -> Sha512 {
+        Sha512 {
+            state: [
+                0xcbbb9d5dc1059ed8,
+                0x629a292a367cd507,
+                0x9159015a3070dd17,
+                0x152fecd8f70e5939,
+                0x67332667ffc00b31,
+                0x8eb44a8768581511,
+                0xdb0c2e0d64f98fa7,
+                0x47b5481dbefa4fa4,
+            ],
+            size: 48,
+            buffer: Vec::with_capacity(128),
+            length: 0,
+        }
+    }
+}
+
+impl Digest for Sha512 {
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        absorb(&mut self.buffer, 128, data, |block| {
+            compress_512(state, block)
+        });
+        self.length = self.length.wrapping_add(data.len() as u128);
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.length.wrapping_mul(8);
+        let pad = padding(self.buffer.len(), 128, &bits.to_be_bytes());
+        self.update(&pad);
+        let mut hash: Vec<u8> = self
+            .state
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        hash.truncate(self.size);
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::digest::tests::{hex_digest, million_a, TWO_BLOCKS, TWO_LONG_BLOCKS};
+    use crate::digest::Algorithm;
+
+    /// The hashes of "", "abc", the two FIPS 180 multi-block messages and
+    /// a million `a`s.
+    fn check(algorithm: Algorithm, expected: [&str; 5]) {
+        assert_eq!(hex_digest(algorithm, 0, b""), expected[0]);
+        assert_eq!(hex_digest(algorithm, 0, b"abc"), expected[1]);
+        assert_eq!(hex_digest(algorithm, 0, TWO_BLOCKS), expected[2]);
+        assert_eq!(hex_digest(algorithm, 0, TWO_LONG_BLOCKS), expected[3]);
+        assert_eq!(million_a(algorithm), expected[4]);
+    }
+
+    #[test]
+    fn sha224() {
+        check(
+            Algorithm::Sha224,
+            [
+                "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+                "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+                "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+                "c97ca9a559850ce97a04a96def6d99a9e0e0e2ab14e6b8df265fc0b3",
+                "20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha256() {
+        check(
+            Algorithm::Sha256,
+            [
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+                "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha384() {
+        check(
+            Algorithm::Sha384,
+            [
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da\
+                 274edebfe76f65fbd51ad2f14898b95b",
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+                 8086072ba1e7cc2358baeca134c825a7",
+                "3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05abfe8f450de5f36bc6\
+                 b0455a8520bc4e6f5fe95b1fe3c8452b",
+                "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712\
+                 fcc7c71a557e2db966c3e9fa91746039",
+                "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b\
+                 07b8b3dc38ecc4ebae97ddd87f3d8985",
+            ],
+        );
+    }
+
+    #[test]
+    fn sha512() {
+        check(
+            Algorithm::Sha512,
+            [
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                 47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+                "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c335\
+                 96fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+                "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+                 501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+                "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+                 de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b",
+            ],
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::Digest;
+
+/// The generator polynomial of the POSIX `cksum` CRC, the CRC-32 of
+/// Ethernet processed most significant bit first.
+const POLYNOMIAL: u32 = 0x04c1_1db7;
+
+pub struct Bsd {
+    checksum: u16,
+}
+
+pub struct Sysv {
+    total: u64,
+}
+
+pub struct Crc {
+    table: [u32; 256],
+    crc: u32,
+    length: u64,
+}
+
+impl Bsd {
+    pub fn new() -> Bsd {
+        Bsd { checksum: 0 }
+    }
+}
+
+impl Digest for Bsd {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.checksum = self.checksum.rotate_right(1).wrapping_add(u16::from(byte));
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.checksum.to_be_bytes().to_vec()
+    }
+}
+
+impl Sysv {
+    pub fn new() -> Sysv {
+        Sysv { total: 0 }
+    }
+}
+
+impl Digest for Sysv {
+    fn update(&mut self, data: &[u8]) {
+        let sum: u64 = data.iter().map(|&byte| u64::from(byte)).sum();
+        self.total = self.total.wrapping_add(sum);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        let total = self.total & 0xffff_ffff;
+        let folded = (total & 0xffff) + (total >> 16);
+        let checksum = (folded & 0xffff) + (folded >> 16);
+        (checksum as u16).to_be_bytes().to_vec()
+    }
+}
+
+impl Crc {
+    pub fn new() -> Crc {
+        let mut table = [0; 256];
+        for (idx, entry) in table.iter_mut().enumerate() {
+            let mut crc = (idx as u32) << 24;
+            for _ in 0..8 {
+                crc = if crc & 0x8000_0000 != 0 {
+                    crc << 1 ^ POLYNOMIAL
+                } else {
+                    crc << 1
+                };
+            }
+            *entry = crc;
+        }
+        Crc {
+            table,
+            crc: 0,
+            length: 0,
+        }
+    }
+
+    fn add(&mut self, byte: u8) {
+        self.crc = self.crc << 8 ^ self.table[usize::from((self.crc >> 24) as u8 ^ byte)];
+    }
+}
+
+impl Digest for Crc {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.add(byte);
+        }
+        self.length += data.len() as u64;
+    }
+
+    /// The length goes in after the data, least significant byte first
+    /// and without leading zeros.
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let mut length = self.length;
+        while length > 0 {
+            self.add(length as u8);
+            length >>= 8;
+        }
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::digest::tests::hex_digest;
+    use crate::digest::Algorithm;
+
+    fn sum(algorithm: Algorithm, data: &[u8]) -> u32 {
+        u32::from_str_radix(&hex_digest(algorithm, 0, data), 16).unwrap()
+    }
+
+    #[test]
+    fn posix_crc() {
+        assert_eq!(sum(Algorithm::Crc, b""), 4294967295);
+        assert_eq!(sum(Algorithm::Crc, b"abc"), 1219131554);
+        assert_eq!(sum(Algorithm::Crc, b"123456789"), 930766865);
+        assert_eq!(sum(Algorithm::Crc, &[b'a'; 100000]), 614267494);
+    }
+
+    #[test]
+    fn old_sums() {
+        assert_eq!(sum(Algorithm::Bsd, b""), 0);
+        assert_eq!(sum(Algorithm::Bsd, b"abc"), 16556);
+        assert_eq!(sum(Algorithm::Bsd, b"123456789"), 53615);
+        assert_eq!(sum(Algorithm::Bsd, &[b'a'; 100000]), 65461);
+        assert_eq!(sum(Algorithm::Sysv, b""), 0);
+        assert_eq!(sum(Algorithm::Sysv, b"abc"), 294);
+        assert_eq!(sum(Algorithm::Sysv, b"123456789"), 477);
+        assert_eq!(sum(Algorithm::Sysv, &[b'a'; 100000]), 820);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::config::Config;
+use crate::usage::{self, OptionSpec};
+
+/// A utility's help text split into the parts a reference page needs.
+#[derive(Debug)]
+pub struct Page {
+    pub name: String,
+    pub synopsis: Vec<String>,
+    pub summary: String,
+    pub description: Vec<String>,
+    pub options: Vec<OptionSpec>,
+    /// Free text after the option list, such as exit status tables.
+    pub notes: Vec<String>,
+}
+
+fn is_synopsis(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("Usage:") || line.starts_with("or:")
+}
+
+fn trim_blank(lines: &[&str]) -> Vec<String> {
+    let start = lines.iter().position(|line| !line.trim().is_empty());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end]
+            .iter()
+            .map(|line| line.trim_end().to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl Page {
+    pub fn new(name: &str, help: &str) -> Page {
+        let lines: Vec<&str> = help.lines().collect();
+        let synopsis: Vec<String> = lines
+            .iter()
+            .take_while(|line| is_synopsis(line))
+            .map(|line| line.trim().to_string())
+            .collect();
+        let rest = &lines[synopsis.len()..];
+
+        let is_option = |line: &&str| {
+            let trimmed = line.trim_start();
+            trimmed.len() < line.len() && trimmed.starts_with('-')
+        };
+        let first_option = rest.iter().position(is_option).unwrap_or(rest.len());
+        let last_option = rest.iter().rposition(is_option);
+
+        let header = trim_blank(&rest[..first_option]);
+        let (summary, description) = match header.split_first() {
+            Some((summary, description)) => (summary.clone(), description.to_vec()),
+            None => (String::new(), Vec::new()),
+        };
+
+        // Notes begin at the first blank line after the last option.
+        let notes = match last_option {
+            Some(idx) => {
+                let tail = &rest[idx..];
+                match tail.iter().position(|line| line.trim().is_empty()) {
+                    Some(blank) => trim_blank(&tail[blank..]),
+                    None => Vec::new(),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        Page {
+            name: name.to_string(),
+            synopsis,
+            summary,
+            description,
+            options: usage::parse_options(help),
+            notes,
+        }
+    }
+
+    fn option_label(spec: &OptionSpec) -> Vec<String> {
+        let mut labels = Vec::new();
+        if let Some(short) = spec.short {
+            labels.push(format!("-{}", short));
+        }
+        if let Some(long) = &spec.long {
+            let label = match (&spec.value, spec.optional_value) {
+                (Some(value), true) => format!("--{}[={}]", long, value),
+                (Some(value), false) => format!("--{}={}", long, value),
+                (None, _) => format!("--{}", long),
+            };
+            labels.push(label);
+        } else if let Some(value) = &spec.value {
+            if let Some(short) = labels.pop() {
+                labels.push(format!("{} {}", short, value));
+            }
+        }
+        labels
+    }
+
+    /// Render as a roff man page for section 1.
+    pub fn man(&self, version: &str) -> String {
+        let mut page = format!(
+            ".TH {} 1 \"\" \"Win32CoreUtils {}\" \"User Commands\"\n",
+            self.name.to_uppercase(),
+            version
+        );
+        page.push_str(".SH NAME\n");
+        page.push_str(&format!(
+            "{} \\- {}\n",
+            roff_escape(&self.name),
+            roff_escape(&self.summary)
+        ));
+
+        page.push_str(".SH SYNOPSIS\n");
+        for line in &self.synopsis {
+            let line = line
+                .trim_start_matches("Usage:")
+                .trim_start_matches("or:")
+                .trim();
+            let (command, args) = line.split_at(line.find(' ').unwrap_or(line.len()));
+            page.push_str(&format!(
+                ".B {}\n{}\n.br\n",
+                roff_escape(command),
+                roff_escape(args.trim())
+            ));
+        }
+
+        page.push_str(".SH DESCRIPTION\n");
+        page.push_str(&roff_line(&self.summary));
+        for line in &self.description {
+            page.push_str(&roff_line(line.trim()));
+        }
+
+        if !self.options.is_empty() {
+            page.push_str(".SH OPTIONS\n");
+            for spec in &self.options {
+                let labels: Vec<String> = Page::option_label(spec)
+                    .iter()
+                    .map(|label| format!("\\fB{}\\fR", roff_escape(label)))
+                    .collect();
+                page.push_str(".TP\n");
+                page.push_str(&format!("{}\n", labels.join(", ")));
+                page.push_str(&roff_line(&spec.description));
+            }
+        }
+
+        if !self.notes.is_empty() {
+            page.push_str(".PP\n.nf\n");
+            for line in &self.notes {
+                page.push_str(&roff_line(line));
+            }
+            page.push_str(".fi\n");
+        }
+        page
+    }
+
+    /// Render as a Markdown reference page.
+    pub fn markdown(&self) -> String {
+        let mut page = format!("# {}\n\n{}\n", self.name, self.summary);
+        for line in &self.description {
+            page.push_str(&format!("{}\n", line.trim()));
+        }
+
+        page.push_str("\n## Synopsis\n\n```\n");
+        for line in &self.synopsis {
+            page.push_str(&format!("{}\n", line));
+        }
+        page.push_str("```\n");
+
+        if !self.options.is_empty() {
+            page.push_str("\n## Options\n\n| Option | Description |\n|--------|-------------|\n");
+            for spec in &self.options {
+                let labels: Vec<String> = Page::option_label(spec)
+                    .iter()
+                    .map(|label| format!("`{}`", label.replace('|', "\\|")))
+                    .collect();
+                page.push_str(&format!(
+                    "| {} | {} |\n",
+                    labels.join(", "),
+                    spec.description.replace('|', "\\|")
+                ));
+            }
+        }
+
+        if !self.notes.is_empty() {
+            page.push_str("\n## Notes\n\n```\n");
+            for line in &self.notes {
+                page.push_str(&format!("{}\n", line));
+            }
+            page.push_str("```\n");
+        }
+        page
+    }
+
+    /// One row of the README utility table.
+    pub fn readme_row(&self) -> String {
+        format!(
+            "| [{name}](docs/{name}.md) | {summary} |",
+            name = self.name,
+            summary = self.summary.replace('|', "\\|")
+        )
+    }
+}
+
+pub const README_START: &str = "<!-- utilities:start -->";
+pub const README_END: &str = "<!-- utilities:end -->";
+
+/// Replace the generated table between the README markers.
+pub fn readme_table(readme: &str, pages: &[Page]) -> Result<String, String> {
+    let start = readme
+        .find(README_START)
+        .ok_or_else(|| format!("README is missing {}", README_START))?;
+    let end = readme
+        .find(README_END)
+        .ok_or_else(|| format!("README is missing {}", README_END))?;
+
+    let mut table = String::from("| Utility | Description |\n|:-------:|-------------|\n");
+    for page in pages {
+        table.push_str(&page.readme_row());
+        table.push('\n');
+    }
+
+    Ok(format!(
+        "{}{}\n{}{}",
+        &readme[..start],
+        README_START,
+        table,
+        &readme[end..]
+    ))
+}
+
+/// How to call a utility so each documented option can be tried on its own.
+pub struct Invocation {
+    /// Arguments to go after the option, such as operands; the option is
+    /// accepted if it parses with any one of these.
+    pub fixtures: &'static [&'static [&'static str]],
+    /// Values for options that want a word rather than `1`.
+    pub values: &'static [(&'static str, &'static str)],
+}
+
+impl Invocation {
+    fn value(&self, name: &str) -> &'static str {
+        self.values
+            .iter()
+            .find(|(option, _)| *option == name)
+            .map_or("1", |(_, value)| value)
+    }
+
+    /// Every documented spelling of every option apart from `--help` and
+    /// `--version`, as the argument(s) that select it.
+    fn options(&self, config: &dyn Config) -> Vec<Vec<String>> {
+        let mut invocations = Vec::new();
+        for spec in config.options() {
+            if matches!(spec.long.as_deref(), Some("help") | Some("version")) {
+                continue;
+            }
+            if let Some(short) = spec.short {
+                let name = format!("-{}", short);
+                let value = self.value(&name);
+                match spec.takes_value() {
+                    true => invocations.push(vec![name, value.to_string()]),
+                    false => invocations.push(vec![name]),
+                }
+            }
+            if let Some(long) = &spec.long {
+                let name = format!("--{}", long);
+                match spec.takes_value() {
+                    true => invocations.push(vec![format!("{}={}", name, self.value(&name))]),
+                    false => invocations.push(vec![name]),
+                }
+            }
+        }
+        invocations
+    }
+}
+
+/// The documented options of `config` that `parse`, the utility's own
+/// parser, rejects, each with the error it gave.
+pub fn rejected_options(
+    config: &dyn Config,
+    parse: &dyn Fn(Vec<String>) -> Result<(), String>,
+    invocation: &Invocation,
+) -> Vec<(String, String)> {
+    let mut rejected = Vec::new();
+    for option in invocation.options(config) {
+        let mut first_error = None;
+        let accepted = invocation.fixtures.iter().any(|fixture| {
+            let mut args = vec![config.bin_name().to_string()];
+            args.extend(option.iter().cloned());
+            args.extend(fixture.iter().map(|arg| arg.to_string()));
+            let result = parse(args);
+            if let Err(e) = &result {
+                first_error.get_or_insert_with(|| e.clone());
+            }
+            result.is_ok()
+        });
+        if !accepted {
+            rejected.push((option.join(" "), first_error.unwrap_or_default()));
+        }
+    }
+    rejected
+}
+
+fn roff_escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// A line of running text; a leading `.` or `'` would read as a request.
+fn roff_line(text: &str) -> String {
+    let escaped = roff_escape(text);
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}\n", escaped)
+    } else {
+        format!("{}\n", escaped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "Usage: demo [OPTION]... [FILE]...
+  or:  demo OPTION
+Print FILE(s) in a demo | format.
+With no FILE, read standard input.
+
+  -a, --all                  include everything
+  -n, --lines=NUM            print NUM lines
+  -f, --follow[={name|descriptor}]  follow the file
+      --help     display this help and exit
+
+Exit status:
+ 0  if OK,
+ 1  if trouble.
+";
+
+    #[test]
+    fn splits_sections() {
+        let page = Page::new("demo", HELP);
+        assert_eq!(
+            page.synopsis,
+            vec!["Usage: demo [OPTION]... [FILE]...", "or:  demo OPTION"]
+        );
+        assert_eq!(page.summary, "Print FILE(s) in a demo | format.");
+        assert_eq!(page.description, vec!["With no FILE, read standard input."]);
+        assert_eq!(page.options.len(), 4);
+        assert_eq!(
+            page.notes,
+            vec!["Exit status:", " 0  if OK,", " 1  if trouble."]
+        );
+    }
+
+    #[test]
+    fn man_page() {
+        let man = Page::new("demo", HELP).man("1.2.3");
+        assert!(man.starts_with(".TH DEMO 1 \"\" \"Win32CoreUtils 1.2.3\""));
+        assert!(man.contains(".SH NAME\ndemo \\- Print FILE(s) in a demo | format.\n"));
+        assert!(man.contains(".B demo\n[OPTION]... [FILE]...\n"));
+        assert!(man.contains(".TP\n\\fB\\-n\\fR, \\fB\\-\\-lines=NUM\\fR\nprint NUM lines\n"));
+        assert!(man.contains(".nf\nExit status:\n"));
+    }
+
+    #[test]
+    fn markdown_page() {
+        let markdown = Page::new("demo", HELP).markdown();
+        assert!(markdown.starts_with("# demo\n\nPrint FILE(s) in a demo | format.\n"));
+        assert!(markdown.contains("| `-a`, `--all` | include everything |\n"));
+        assert!(markdown.contains("| `-n`, `--lines=NUM` | print NUM lines |\n"));
+        assert!(markdown.contains("| `-f`, `--follow[={name\\|descriptor}]` | follow the file |\n"));
+        assert!(markdown.contains("## Notes\n\n```\nExit status:\n"));
+    }
+
+    #[test]
+    fn readme_rows() {
+        let readme = format!("# Title\n\n{}\nold\n{}\n\nMore\n", README_START, README_END);
+        let updated = readme_table(&readme, &[Page::new("demo", HELP)]).unwrap();
+        assert_eq!(
+            updated,
+            format!(
+                "# Title\n\n{}\n| Utility | Description |\n|:-------:|-------------|\n\
+                 | [demo](docs/demo.md) | Print FILE(s) in a demo \\| format. |\n{}\n\nMore\n",
+                README_START, README_END
+            )
+        );
+        assert!(readme_table("no markers", &[]).is_err());
+    }
+
+    struct Demo;
+
+    impl Config for Demo {
+        fn bin_name(&self) -> &'static str {
+            "demo"
+        }
+        fn usage(&self) -> &'static str {
+            HELP
+        }
+    }
+
+    #[test]
+    fn rejected() {
+        let parse = |args: Vec<String>| match args[1].as_str() {
+            "-a" | "--all" if args.len() == 2 => Err(String::from("missing operand")),
+            "-n" if args[2] != "5" => Err(format!("invalid number of lines: '{}'", args[2])),
+            _ => Ok(()),
+        };
+        let invocation = Invocation {
+            fixtures: &[&[], &["file"]],
+            values: &[("--lines", "5")],
+        };
+        assert_eq!(
+            rejected_options(&Demo, &parse, &invocation),
+            vec![(
+                String::from("-n 1"),
+                String::from("invalid number of lines: '1'")
+            )]
+        );
+    }
+}
@@ -0,0 +1,526 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::input::{self, BUFFER_SIZE};
+use crate::output;
+
+const Z85: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// The encodings of RFC 4648, plain binary and ZeroMQ's Z85.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Base64,
+    /// Base64 with `-` and `_` in place of `+` and `/`.
+    Base64Url,
+    Base32,
+    /// Base32 with the digits first, so encoded text sorts as the data does.
+    Base32Hex,
+    Base16,
+    /// One `0` or `1` per bit, most significant bit first.
+    Base2Msbf,
+    /// One `0` or `1` per bit, least significant bit first.
+    Base2Lsbf,
+    Z85,
+}
+
+impl Encoding {
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Encoding::Base64 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            Encoding::Base64Url => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+            }
+            Encoding::Base32 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+            Encoding::Base32Hex => b"0123456789ABCDEFGHIJKLMNOPQRSTUV",
+            Encoding::Base16 => b"0123456789ABCDEF",
+            Encoding::Base2Msbf | Encoding::Base2Lsbf => b"01",
+            Encoding::Z85 => Z85,
+        }
+    }
+
+    /// Bits each character stands for; Z85 works in base 85 instead.
+    fn bits(self) -> u32 {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => 6,
+            Encoding::Base32 | Encoding::Base32Hex => 5,
+            Encoding::Base16 => 4,
+            Encoding::Base2Msbf | Encoding::Base2Lsbf => 1,
+            Encoding::Z85 => 0,
+        }
+    }
+
+    /// Bytes in a block of data and the characters that encode it.
+    pub fn block(self) -> (usize, usize) {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => (3, 4),
+            Encoding::Base32 | Encoding::Base32Hex => (5, 8),
+            Encoding::Base16 => (1, 2),
+            Encoding::Base2Msbf | Encoding::Base2Lsbf => (1, 8),
+            Encoding::Z85 => (4, 5),
+        }
+    }
+
+    /// Whether a short final block is filled out with `=`, and after how
+    /// many characters of a group that may start.
+    fn padding_after(self, count: usize) -> bool {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => matches!(count, 2 | 3),
+            Encoding::Base32 | Encoding::Base32Hex => matches!(count, 2 | 4 | 5 | 7),
+            _ => false,
+        }
+    }
+
+    fn padded(self) -> bool {
+        matches!(
+            self,
+            Encoding::Base64 | Encoding::Base64Url | Encoding::Base32 | Encoding::Base32Hex
+        )
+    }
+
+    /// Encode whole blocks, and a short final block if it is allowed.
+    fn encode_blocks(self, bytes: &[u8], text: &mut Vec<u8>) {
+        let alphabet = self.alphabet();
+        let (size, chars) = self.block();
+        for block in bytes.chunks(size) {
+            let start = text.len();
+            if self == Encoding::Z85 {
+                let mut value = block
+                    .iter()
+                    .fold(0u32, |value, &byte| value << 8 | u32::from(byte));
+                let mut digits = [0; 5];
+                for digit in digits.iter_mut().rev() {
+                    *digit = alphabet[(value % 85) as usize];
+                    value /= 85;
+                }
+                text.extend_from_slice(&digits);
+                continue;
+            }
+
+            let bits = self.bits();
+            let mask = (1 << bits) - 1;
+            let (mut acc, mut held) = (0u64, 0);
+            for &byte in block {
+                let byte = match self {
+                    Encoding::Base2Lsbf => byte.reverse_bits(),
+                    _ => byte,
+                };
+                acc = acc << 8 | u64::from(byte);
+                held += 8;
+                while held >= bits {
+                    held -= bits;
+                    text.push(alphabet[(acc >> held & mask) as usize]);
+                }
+            }
+            if held > 0 {
+                text.push(alphabet[(acc << (bits - held) & mask) as usize]);
+            }
+            text.resize(start + chars, b'=');
+        }
+    }
+}
+
+/// Why encoding or decoding stopped.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The input cannot be encoded or decoded; `offset` counts bytes from
+    /// the start of the input, newlines included.
+    Invalid {
+        offset: u64,
+        reason: String,
+    },
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", output::describe(e)),
+            Error::Invalid { offset, reason } => {
+                write!(f, "invalid input at offset {}: {}", offset, reason)
+            }
+        }
+    }
+}
+
+/// Encode `input` a whole number of blocks at a time, breaking lines after
+/// `wrap` characters unless it is 0.
+pub fn encode<R: Read, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    encoding: Encoding,
+    wrap: usize,
+) -> Result<(), Error> {
+    let (size, _) = encoding.block();
+    let mut buf = vec![0; size * (BUFFER_SIZE / size)];
+    let mut text = Vec::new();
+    let mut offset: u64 = 0;
+    let mut column = 0;
+    loop {
+        let read = input::fill(input, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        // Only the last chunk can end in a short block.
+        if encoding == Encoding::Z85 && read % size != 0 {
+            return Err(Error::Invalid {
+                offset: offset + (read - read % size) as u64,
+                reason: String::from("length must be a multiple of 4 bytes"),
+            });
+        }
+
+        text.clear();
+        encoding.encode_blocks(&buf[..read], &mut text);
+        if wrap == 0 {
+            out.write_all(&text)?;
+        } else {
+            let mut rest = &text[..];
+            while !rest.is_empty() {
+                let take = rest.len().min(wrap - column);
+                out.write_all(&rest[..take])?;
+                rest = &rest[take..];
+                column += take;
+                if column == wrap {
+                    out.write_all(b"\n")?;
+                    column = 0;
+                }
+            }
+        }
+
+        offset += read as u64;
+        if read < buf.len() {
+            break;
+        }
+    }
+    if column > 0 {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+const NOT_IN_ALPHABET: u8 = 0xff;
+
+/// Decodes a group of characters at a time, keeping a group that is split
+/// between reads.
+struct Decoder {
+    encoding: Encoding,
+    ignore_garbage: bool,
+    values: [u8; 256],
+    /// The characters of the current group, as numbers.
+    acc: u64,
+    count: usize,
+    pads: usize,
+    /// Where the current group started.
+    start: u64,
+    offset: u64,
+}
+
+impl Decoder {
+    fn new(encoding: Encoding, ignore_garbage: bool) -> Decoder {
+        let mut values = [NOT_IN_ALPHABET; 256];
+        for (value, &chr) in encoding.alphabet().iter().enumerate() {
+            values[usize::from(chr)] = value as u8;
+        }
+        if encoding == Encoding::Base16 {
+            for (value, chr) in (b'a'..=b'f').enumerate() {
+                values[usize::from(chr)] = 10 + value as u8;
+            }
+        }
+        Decoder {
+            encoding,
+            ignore_garbage,
+            values,
+            acc: 0,
+            count: 0,
+            pads: 0,
+            start: 0,
+            offset: 0,
+        }
+    }
+
+    /// Write out the bytes the group holds and start a new one. A short
+    /// group gives the whole bytes it has; bits left over are dropped.
+    fn end_group(&mut self, out: &mut Vec<u8>) -> Result<(), Error> {
+        let (size, chars) = self.encoding.block();
+        let (acc, count) = (self.acc, self.count);
+        self.acc = 0;
+        self.count = 0;
+        self.pads = 0;
+
+        if self.encoding == Encoding::Z85 {
+            if count < chars {
+                return Ok(());
+            }
+            let value = u32::try_from(acc).map_err(|_| Error::Invalid {
+                offset: self.start,
+                reason: String::from("group out of range"),
+            })?;
+            out.extend_from_slice(&value.to_be_bytes());
+            return Ok(());
+        }
+
+        let bits = count * self.encoding.bits() as usize;
+        let value = acc >> (bits % 8);
+        let bytes = (bits / 8).min(size);
+        for idx in (0..bytes).rev() {
+            let byte = (value >> (idx * 8)) as u8;
+            out.push(match self.encoding {
+                Encoding::Base2Lsbf => byte.reverse_bits(),
+                _ => byte,
+            });
+        }
+        Ok(())
+    }
+
+    fn invalid(&mut self, out: &mut Vec<u8>, offset: u64, reason: String) -> Error {
+        match self.end_group(out) {
+            Ok(()) => Error::Invalid { offset, reason },
+            Err(e) => e,
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        let (_, chars) = self.encoding.block();
+        for &byte in bytes {
+            let offset = self.offset;
+            self.offset += 1;
+            if byte == b'\n' {
+                continue;
+            }
+
+            if byte == b'=' && self.encoding.padded() {
+                if self.pads == 0 && !self.encoding.padding_after(self.count) {
+                    return Err(self.invalid(out, offset, String::from("misplaced padding")));
+                }
+                self.pads += 1;
+                if self.count + self.pads == chars {
+                    self.end_group(out)?;
+                }
+                continue;
+            }
+
+            let value = self.values[usize::from(byte)];
+            if value == NOT_IN_ALPHABET {
+                if self.ignore_garbage {
+                    continue;
+                }
+                let reason = format!("unexpected '{}'", byte.escape_ascii());
+                return Err(self.invalid(out, offset, reason));
+            }
+            if self.pads > 0 {
+                let reason = format!("unexpected '{}' after padding", byte.escape_ascii());
+                return Err(self.invalid(out, offset, reason));
+            }
+
+            if self.count == 0 {
+                self.start = offset;
+            }
+            self.acc = match self.encoding {
+                Encoding::Z85 => self.acc * 85 + u64::from(value),
+                _ => self.acc << self.encoding.bits() | u64::from(value),
+            };
+            self.count += 1;
+            if self.count == chars {
+                self.end_group(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), Error> {
+        if self.count == 0 && self.pads == 0 {
+            return Ok(());
+        }
+        let start = self.start;
+        Err(self.invalid(out, start, String::from("incomplete group")))
+    }
+}
+
+/// Decode `input`, skipping newlines and, with `ignore_garbage`, anything
+/// else outside the alphabet. The data decoded before a fault is still
+/// written.
+pub fn decode<R: Read, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    encoding: Encoding,
+    ignore_garbage: bool,
+) -> Result<(), Error> {
+    let mut decoder = Decoder::new(encoding, ignore_garbage);
+    let mut buf = vec![0; BUFFER_SIZE];
+    let mut data = Vec::new();
+    loop {
+        let read = match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let result = decoder.feed(&buf[..read], &mut data);
+        out.write_all(&data)?;
+        data.clear();
+        result?;
+    }
+    let result = decoder.finish(&mut data);
+    out.write_all(&data)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_all(encoding: Encoding, bytes: &[u8], wrap: usize) -> Result<String, String> {
+        let mut out = Vec::new();
+        encode(&mut &bytes[..], &mut out, encoding, wrap).map_err(|e| e.to_string())?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    /// The bytes decoded, and the error if decoding stopped early.
+    fn decode_all(encoding: Encoding, text: &str, ignore: bool) -> (Vec<u8>, Option<String>) {
+        let mut out = Vec::new();
+        let result = decode(&mut text.as_bytes(), &mut out, encoding, ignore);
+        (out, result.err().map(|e| e.to_string()))
+    }
+
+    fn decoded(encoding: Encoding, text: &str) -> Vec<u8> {
+        let (bytes, error) = decode_all(encoding, text, false);
+        assert_eq!(error, None, "{:?}", text);
+        bytes
+    }
+
+    #[test]
+    fn rfc_4648_vectors() {
+        let cases = [
+            (Encoding::Base64, "Zm9vYmFy", "Zm9vYg=="),
+            (Encoding::Base32, "MZXW6YTBOI======", "MZXW6YQ="),
+            (Encoding::Base32Hex, "CPNMUOJ1E8======", "CPNMUOG="),
+            (Encoding::Base16, "666F6F626172", "666F6F62"),
+        ];
+        for (encoding, foobar, foob) in cases {
+            assert_eq!(encode_all(encoding, b"foobar", 0).unwrap(), foobar);
+            assert_eq!(encode_all(encoding, b"foob", 0).unwrap(), foob);
+            assert_eq!(decoded(encoding, foobar), b"foobar");
+            assert_eq!(decoded(encoding, foob), b"foob");
+        }
+        assert_eq!(encode_all(Encoding::Base64, b"", 76).unwrap(), "");
+    }
+
+    #[test]
+    fn other_alphabets() {
+        let bytes = [0xfb, 0xff, 0x61];
+        assert_eq!(encode_all(Encoding::Base64, &bytes, 0).unwrap(), "+/9h");
+        assert_eq!(encode_all(Encoding::Base64Url, &bytes, 0).unwrap(), "-_9h");
+        assert_eq!(
+            encode_all(Encoding::Base2Msbf, b"a", 0).unwrap(),
+            "01100001"
+        );
+        assert_eq!(
+            encode_all(Encoding::Base2Lsbf, b"a", 0).unwrap(),
+            "10000110"
+        );
+        assert_eq!(decoded(Encoding::Base2Lsbf, "10000110"), b"a");
+        assert_eq!(decoded(Encoding::Base16, "6a6B"), b"jk");
+        assert_eq!(
+            encode_all(Encoding::Z85, b"hello wo", 0).unwrap(),
+            "xK#0@zY<mx"
+        );
+        assert_eq!(decoded(Encoding::Z85, "xK#0@\nzY<mx"), b"hello wo");
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            encode_all(Encoding::Base64, b"hello world", 5).unwrap(),
+            "aGVsb\nG8gd2\n9ybGQ\n=\n"
+        );
+        assert_eq!(
+            encode_all(Encoding::Base16, b"hello", 5).unwrap(),
+            "68656\nC6C6F\n"
+        );
+        let long = vec![b'x'; 3 * BUFFER_SIZE + 1];
+        let text = encode_all(Encoding::Base64, &long, 76).unwrap();
+        assert!(text.lines().rev().skip(1).all(|line| line.len() == 76));
+        assert_eq!(decoded(Encoding::Base64, &text), long);
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(decoded(Encoding::Base64, "aGVsbG8=aGk="), b"hellohi");
+        assert_eq!(decoded(Encoding::Base64, "aGVsbG9="), b"hello");
+        assert_eq!(decoded(Encoding::Base64, "YQ=\n="), b"a");
+        for text in ["MFRGG===", "MFRG====", "MF======", "MFRGGZA="] {
+            assert!(!decoded(Encoding::Base32, text).is_empty());
+        }
+        assert_eq!(
+            decode_all(Encoding::Base32, "MFRGGZ==", false),
+            (
+                b"abc".to_vec(),
+                Some(String::from("invalid input at offset 6: misplaced padding"))
+            )
+        );
+        assert_eq!(
+            decode_all(Encoding::Base64, "a===", false).1.unwrap(),
+            "invalid input at offset 1: misplaced padding"
+        );
+        assert_eq!(
+            decode_all(Encoding::Base64, "YQ==YQ", false),
+            (
+                b"aa".to_vec(),
+                Some(String::from("invalid input at offset 4: incomplete group"))
+            )
+        );
+        assert_eq!(
+            decode_all(Encoding::Base64, "YQ=Y", false).1.unwrap(),
+            "invalid input at offset 3: unexpected 'Y' after padding"
+        );
+    }
+
+    #[test]
+    fn corrupt_input() {
+        assert_eq!(
+            decode_all(Encoding::Base64, "aGVsbG8", false),
+            (
+                b"hello".to_vec(),
+                Some(String::from("invalid input at offset 4: incomplete group"))
+            )
+        );
+        assert_eq!(
+            decode_all(Encoding::Base64, "aGVs\r\nbG8=", false),
+            (
+                b"hel".to_vec(),
+                Some(String::from("invalid input at offset 4: unexpected '\\r'"))
+            )
+        );
+        assert_eq!(
+            decode_all(Encoding::Base2Msbf, "0110000", false).1.unwrap(),
+            "invalid input at offset 0: incomplete group"
+        );
+        assert_eq!(
+            decode_all(Encoding::Z85, "%%%%%", false).1.unwrap(),
+            "invalid input at offset 0: group out of range"
+        );
+        assert_eq!(
+            encode_all(Encoding::Z85, b"hello", 0).unwrap_err(),
+            "invalid input at offset 4: length must be a multiple of 4 bytes"
+        );
+    }
+
+    #[test]
+    fn ignore_garbage() {
+        assert_eq!(
+            decode_all(Encoding::Base64, "Y Q = =", true),
+            (b"a".to_vec(), None)
+        );
+        assert_eq!(
+            decode_all(Encoding::Base16, "68*69\r\n", true),
+            (b"hi".to_vec(), None)
+        );
+    }
+}
@@ -0,0 +1,1221 @@
+//! The printf format language: backslash escapes, `%` directives and the
+//! conversion of their arguments. printf and echo use all of it; seq, stat
+//! and date reuse `Spec` for their own directives.
+
+/// Which backslash escapes are recognised, and how octal is written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escapes {
+    /// `echo -e`: octal only as `\0NNN`, no `\"` or `\u`.
+    Echo,
+    /// A printf format string: octal as `\NNN`.
+    Format,
+    /// A `%b` argument: octal as `\0NNN` or `\NNN`.
+    Argument,
+}
+
+/// Whether output should go on after an escape; `\c` stops it entirely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    Continue,
+    Stop,
+}
+
+fn simple_escape(byte: u8, mode: Escapes) -> Option<u8> {
+    match byte {
+        b'\\' => Some(b'\\'),
+        b'a' => Some(0x07),
+        b'b' => Some(0x08),
+        b'e' => Some(0x1b),
+        b'f' => Some(0x0c),
+        b'n' => Some(b'\n'),
+        b'r' => Some(b'\r'),
+        b't' => Some(b'\t'),
+        b'v' => Some(0x0b),
+        b'"' if mode != Escapes::Echo => Some(b'"'),
+        _ => None,
+    }
+}
+
+/// Expand the escape whose backslash precedes `rest`, returning how many
+/// bytes of `rest` it used.
+fn escape(rest: &str, mode: Escapes, out: &mut Vec<u8>) -> Result<(Flow, usize), String> {
+    let bytes = rest.as_bytes();
+    match bytes.first() {
+        None => {
+            out.push(b'\\');
+            Ok((Flow::Continue, 0))
+        }
+        Some(b'c') => Ok((Flow::Stop, 1)),
+        Some(b'x') => {
+            let digits = bytes[1..]
+                .iter()
+                .take(2)
+                .take_while(|b| b.is_ascii_hexdigit())
+                .count();
+            if digits == 0 {
+                if mode == Escapes::Echo {
+                    out.extend_from_slice(b"\\x");
+                    return Ok((Flow::Continue, 1));
+                }
+                return Err("missing hexadecimal number in escape".to_string());
+            }
+            out.push(u8::from_str_radix(&rest[1..=digits], 16).expect("hex digits"));
+            Ok((Flow::Continue, 1 + digits))
+        }
+        Some(&lead @ b'0'..=b'7') if mode != Escapes::Echo || lead == b'0' => {
+            let skip = usize::from(mode != Escapes::Format && lead == b'0');
+            let digits = bytes[skip..]
+                .iter()
+                .take(3)
+                .take_while(|b| (b'0'..=b'7').contains(b))
+                .count();
+            let value = bytes[skip..skip + digits]
+                .iter()
+                .fold(0u32, |value, b| value * 8 + u32::from(b - b'0'));
+            // Like C, \777 keeps only the low byte.
+            out.push(value as u8);
+            Ok((Flow::Continue, skip + digits))
+        }
+        Some(&kind @ (b'u' | b'U')) if mode != Escapes::Echo => {
+            let len = if kind == b'u' { 4 } else { 8 };
+            let hex = rest
+                .get(1..=len)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .ok_or("missing hexadecimal number in escape")?;
+            let code = u32::from_str_radix(hex, 16).expect("hex digits");
+            let chr = char::from_u32(code)
+                .filter(|_| code >= 0xa0 || matches!(code, 0x24 | 0x40 | 0x60))
+                .ok_or_else(|| {
+                    format!("invalid universal character name \\{}{}", kind as char, hex)
+                })?;
+            out.extend_from_slice(chr.encode_utf8(&mut [0; 4]).as_bytes());
+            Ok((Flow::Continue, 1 + len))
+        }
+        Some(&byte) => match simple_escape(byte, mode) {
+            Some(value) => {
+                out.push(value);
+                Ok((Flow::Continue, 1))
+            }
+            None => {
+                let len = rest.chars().next().map_or(1, char::len_utf8);
+                out.push(b'\\');
+                out.extend_from_slice(&bytes[..len]);
+                Ok((Flow::Continue, len))
+            }
+        },
+    }
+}
+
+/// Append `text` to `out` with its backslash escapes expanded. Unknown
+/// escapes are kept as written.
+pub fn unescape(text: &str, mode: Escapes, out: &mut Vec<u8>) -> Result<Flow, String> {
+    let mut rest = text;
+    while let Some(idx) = rest.find('\\') {
+        out.extend_from_slice(&rest.as_bytes()[..idx]);
+        let (flow, used) = escape(&rest[idx + 1..], mode, out)?;
+        if flow == Flow::Stop {
+            return Ok(Flow::Stop);
+        }
+        rest = &rest[idx + 1 + used..];
+    }
+    out.extend_from_slice(rest.as_bytes());
+    Ok(Flow::Continue)
+}
+
+/// One `%` directive: flags, field width, precision and conversion.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Spec {
+    pub left: bool,
+    pub plus: bool,
+    pub space: bool,
+    pub alternate: bool,
+    pub zero: bool,
+    pub grouping: bool,
+    pub width: usize,
+    /// `*`: the width comes from the next argument.
+    pub width_from_arg: bool,
+    pub precision: Option<usize>,
+    pub precision_from_arg: bool,
+    pub conversion: char,
+}
+
+fn number(chars: &mut std::iter::Peekable<std::str::CharIndices>) -> usize {
+    let mut value: usize = 0;
+    while let Some(digit) = chars.peek().and_then(|&(_, chr)| chr.to_digit(10)) {
+        value = value.saturating_mul(10).saturating_add(digit as usize);
+        chars.next();
+    }
+    value
+}
+
+impl Spec {
+    /// Parse the directive after a `%`, through its conversion character.
+    /// Returns the spec and the bytes used, or None if `text` ends first.
+    /// Length modifiers such as `l` are accepted and ignored.
+    pub fn parse(text: &str) -> Option<(Spec, usize)> {
+        let mut spec = Spec::default();
+        let mut chars = text.char_indices().peekable();
+
+        while let Some(&(_, chr)) = chars.peek() {
+            match chr {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alternate = true,
+                '0' => spec.zero = true,
+                '\'' => spec.grouping = true,
+                _ => break,
+            }
+            chars.next();
+        }
+
+        if chars.next_if(|&(_, chr)| chr == '*').is_some() {
+            spec.width_from_arg = true;
+        } else {
+            spec.width = number(&mut chars);
+        }
+
+        if chars.next_if(|&(_, chr)| chr == '.').is_some() {
+            if chars.next_if(|&(_, chr)| chr == '*').is_some() {
+                spec.precision_from_arg = true;
+            } else {
+                spec.precision = Some(number(&mut chars));
+            }
+        }
+
+        while chars.next_if(|&(_, chr)| "hlLjzt".contains(chr)).is_some() {}
+
+        let (idx, chr) = chars.next()?;
+        spec.conversion = chr;
+        Some((spec, idx + chr.len_utf8()))
+    }
+
+    fn has_width(&self) -> bool {
+        self.width > 0 || self.width_from_arg
+    }
+
+    fn has_precision(&self) -> bool {
+        self.precision.is_some() || self.precision_from_arg
+    }
+
+    /// Whether printf accepts this combination of flags and conversion.
+    pub fn valid_for_printf(&self) -> bool {
+        let flagged = self.left || self.plus || self.space || self.alternate || self.zero;
+        match self.conversion {
+            'b' | 'q' => !(flagged || self.grouping || self.has_width() || self.has_precision()),
+            'd' | 'i' | 'u' => !self.alternate,
+            'o' | 'x' | 'X' | 'f' | 'F' | 'g' | 'G' => true,
+            'a' | 'A' | 'e' | 'E' => !self.grouping,
+            'c' => !(self.alternate || self.zero || self.grouping || self.has_precision()),
+            's' => !(self.alternate || self.zero || self.grouping),
+            _ => false,
+        }
+    }
+
+    /// Pad `body` with spaces to the field width.
+    pub fn pad(&self, body: &str) -> String {
+        let len = body.chars().count();
+        if len >= self.width {
+            return body.to_string();
+        }
+        let fill = " ".repeat(self.width - len);
+        if self.left {
+            format!("{}{}", body, fill)
+        } else {
+            format!("{}{}", fill, body)
+        }
+    }
+
+    /// Pad a number, zero filling between its sign or prefix and digits
+    /// when the `0` flag allows.
+    fn pad_number(&self, prefix: &str, digits: &str, zero_fill: bool) -> String {
+        if self.zero && zero_fill && !self.left {
+            let zeros = self.width.saturating_sub(prefix.len() + digits.len());
+            return format!("{}{}{}", prefix, "0".repeat(zeros), digits);
+        }
+        self.pad(&format!("{}{}", prefix, digits))
+    }
+
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// Integer precision is a minimum digit count; zero with precision 0
+    /// prints no digits at all.
+    fn min_digits(&self, digits: String, zero: bool) -> String {
+        match self.precision {
+            Some(0) if zero => String::new(),
+            Some(precision) if digits.len() < precision => {
+                format!("{}{}", "0".repeat(precision - digits.len()), digits)
+            }
+            _ => digits,
+        }
+    }
+
+    pub fn signed(&self, value: i128) -> String {
+        let digits = self.min_digits(value.unsigned_abs().to_string(), value == 0);
+        self.pad_number(self.sign(value < 0), &digits, self.precision.is_none())
+    }
+
+    /// Format for the `o`, `x`, `X` and `u` conversions.
+    pub fn unsigned(&self, value: u128) -> String {
+        let (digits, prefix) = match self.conversion {
+            'o' => {
+                let mut digits = self.min_digits(format!("{:o}", value), value == 0);
+                if self.alternate && !digits.starts_with('0') {
+                    digits.insert(0, '0');
+                }
+                (digits, "")
+            }
+            'x' | 'X' => {
+                let digits = self.min_digits(format!("{:x}", value), value == 0);
+                match (self.conversion, self.alternate && value != 0) {
+                    ('X', alternate) => (digits.to_uppercase(), if alternate { "0X" } else { "" }),
+                    (_, alternate) => (digits, if alternate { "0x" } else { "" }),
+                }
+            }
+            _ => (self.min_digits(value.to_string(), value == 0), ""),
+        };
+        self.pad_number(prefix, &digits, self.precision.is_none())
+    }
+
+    /// Format for the `f`, `e`, `g` and `a` conversions and their upper
+    /// case forms.
+    pub fn float(&self, value: f64) -> String {
+        let upper = self.conversion.is_ascii_uppercase();
+        let sign = self.sign(value.is_sign_negative());
+        if !value.is_finite() {
+            let body = if value.is_nan() { "nan" } else { "inf" };
+            let body = if upper {
+                body.to_uppercase()
+            } else {
+                body.to_string()
+            };
+            return self.pad(&format!("{}{}", sign, body));
+        }
+
+        let abs = value.abs();
+        let precision = self.precision.unwrap_or(6);
+        let body = match self.conversion.to_ascii_lowercase() {
+            'e' => exponential(abs, precision, self.alternate),
+            'g' => general(abs, precision, self.alternate),
+            'a' => hexadecimal(abs, self.precision, self.alternate),
+            _ => fixed(abs, precision, self.alternate),
+        };
+        let body = if upper { body.to_uppercase() } else { body };
+        self.pad_number(sign, &body, true)
+    }
+
+    /// Truncate to the precision in characters, then pad.
+    pub fn string(&self, text: &str) -> String {
+        match self.precision {
+            Some(precision) => self.pad(&text.chars().take(precision).collect::<String>()),
+            None => self.pad(text),
+        }
+    }
+}
+
+fn fixed(value: f64, precision: usize, alternate: bool) -> String {
+    let mut body = format!("{:.*}", precision, value);
+    if alternate && precision == 0 {
+        body.push('.');
+    }
+    body
+}
+
+fn exponential(value: f64, precision: usize, alternate: bool) -> String {
+    let body = format!("{:.*e}", precision, value);
+    let (mantissa, exponent) = body.split_at(body.find('e').expect("exponent"));
+    let exponent: i32 = exponent[1..].parse().expect("exponent digits");
+    format!(
+        "{}{}e{}{:02}",
+        mantissa,
+        if alternate && precision == 0 { "." } else { "" },
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+/// `%g`: fixed or exponential notation, whichever C would pick, without
+/// trailing zeros unless the `#` flag is given.
+fn general(value: f64, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+    let exponent = if value == 0.0 {
+        0
+    } else {
+        let body = format!("{:.*e}", precision - 1, value);
+        body[body.find('e').expect("exponent") + 1..]
+            .parse()
+            .expect("exponent digits")
+    };
+
+    let body = if exponent < -4 || exponent >= precision as i32 {
+        exponential(value, precision - 1, alternate)
+    } else {
+        fixed(value, (precision as i32 - 1 - exponent) as usize, alternate)
+    };
+    if alternate {
+        return body;
+    }
+
+    let (mantissa, exponent) = body.split_at(body.find('e').unwrap_or(body.len()));
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exponent)
+}
+
+/// `%a`: hexadecimal with a leading digit of 1, as C prints a double.
+fn hexadecimal(value: f64, precision: Option<usize>, alternate: bool) -> String {
+    const FRACTION_DIGITS: usize = 13;
+
+    let bits = value.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (lead, exponent) = match (biased, fraction) {
+        (0, 0) => (0, 0),
+        (0, _) => (0, -1022),
+        _ => (1, biased - 1023),
+    };
+
+    let (lead, digits) = match precision {
+        Some(precision) if precision < FRACTION_DIGITS => {
+            let shift = (FRACTION_DIGITS - precision) * 4;
+            let rounded = (((lead as u64) << 52 | fraction) + (1 << (shift - 1))) >> shift;
+            let digits = rounded & ((1 << (precision * 4)) - 1);
+            let digits = if precision == 0 {
+                String::new()
+            } else {
+                format!("{:0width$x}", digits, width = precision)
+            };
+            (rounded >> (precision * 4), digits)
+        }
+        Some(precision) => (
+            lead as u64,
+            format!(
+                "{:013x}{}",
+                fraction,
+                "0".repeat(precision - FRACTION_DIGITS)
+            ),
+        ),
+        None => (
+            lead as u64,
+            format!("{:013x}", fraction)
+                .trim_end_matches('0')
+                .to_string(),
+        ),
+    };
+
+    let point = if digits.is_empty() && !alternate {
+        ""
+    } else {
+        "."
+    };
+    format!("0x{:x}{}{}p{:+}", lead, point, digits, exponent)
+}
+
+/// `'c` or `"c` as a numeric argument means the code of `c`.
+fn character_code(arg: &str) -> Option<u32> {
+    let mut chars = arg.chars();
+    match chars.next() {
+        Some('\'') | Some('"') => Some(chars.next().map_or(0, u32::from)),
+        _ => None,
+    }
+}
+
+fn expected_number(arg: &str) -> String {
+    format!("'{}': expected a numeric value", arg)
+}
+
+fn not_converted(arg: &str) -> String {
+    format!("'{}': value not completely converted", arg)
+}
+
+fn out_of_range(arg: &str) -> String {
+    format!("'{}': Numerical result out of range", arg)
+}
+
+/// Read an integer argument the way C's strtol does with base 0: `0x`
+/// for hex, a leading `0` for octal. Returns the value to use and a
+/// complaint to report, if any.
+pub fn parse_integer(arg: &str) -> (i128, Option<String>) {
+    if let Some(code) = character_code(arg) {
+        return (i128::from(code), None);
+    }
+
+    let text = arg.trim_start();
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let hex = body.len() > 2
+        && (body.starts_with("0x") || body.starts_with("0X"))
+        && body.as_bytes()[2].is_ascii_hexdigit();
+    let (radix, digits) = if hex {
+        (16, &body[2..])
+    } else if body.starts_with('0') {
+        (8, body)
+    } else {
+        (10, body)
+    };
+
+    let count = digits.chars().take_while(|chr| chr.is_digit(radix)).count();
+    if count == 0 {
+        return (0, Some(expected_number(arg)));
+    }
+
+    let mut overflow = false;
+    let mut value: i128 = 0;
+    for chr in digits[..count].chars() {
+        let digit = i128::from(chr.to_digit(radix).expect("checked digit"));
+        value = match value
+            .checked_mul(i128::from(radix))
+            .and_then(|v| v.checked_add(digit))
+        {
+            Some(value) => value,
+            None => {
+                overflow = true;
+                i128::MAX
+            }
+        };
+    }
+    let value = if negative { -value } else { value };
+
+    if count < digits.len() {
+        (value, Some(not_converted(arg)))
+    } else if overflow {
+        (value, Some(out_of_range(arg)))
+    } else {
+        (value, None)
+    }
+}
+
+/// Length of the longest prefix of `text` that reads as a number.
+fn float_prefix(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut idx = usize::from(matches!(bytes.first(), Some(b'+') | Some(b'-')));
+    let starts_with = |word: &[u8]| {
+        bytes
+            .get(idx..idx + word.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(word))
+    };
+    if starts_with(b"infinity") {
+        return idx + 8;
+    }
+    if starts_with(b"inf") || starts_with(b"nan") {
+        return idx + 3;
+    }
+
+    let hex = starts_with(b"0x");
+    let (is_digit, exponent): (fn(&u8) -> bool, u8) = if hex {
+        idx += 2;
+        (u8::is_ascii_hexdigit, b'p')
+    } else {
+        (u8::is_ascii_digit, b'e')
+    };
+
+    let run =
+        |from: usize, test: fn(&u8) -> bool| bytes[from..].iter().take_while(|b| test(b)).count();
+    let mut digits = run(idx, is_digit);
+    idx += digits;
+    if bytes.get(idx) == Some(&b'.') {
+        let fraction = run(idx + 1, is_digit);
+        if digits + fraction > 0 {
+            digits += fraction;
+            idx += 1 + fraction;
+        }
+    }
+    if digits == 0 {
+        // A bare "0x" is the number 0 followed by junk.
+        return if hex { idx - 1 } else { 0 };
+    }
+
+    if bytes.get(idx).map(u8::to_ascii_lowercase) == Some(exponent) {
+        let sign = usize::from(matches!(bytes.get(idx + 1), Some(b'+') | Some(b'-')));
+        let power = run(idx + 1 + sign, u8::is_ascii_digit);
+        if power > 0 {
+            idx += 1 + sign + power;
+        }
+    }
+    idx
+}
+
+fn parse_hex_float(text: &str) -> f64 {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let body = &body[2..];
+    let (mantissa, power) = match body.find(['p', 'P']) {
+        Some(idx) => (&body[..idx], body[idx + 1..].parse::<i32>().unwrap_or(0)),
+        None => (body, 0),
+    };
+
+    let mut value = 0.0;
+    let mut scale = 0;
+    let mut fraction = false;
+    for chr in mantissa.chars() {
+        match chr.to_digit(16) {
+            Some(digit) => {
+                value = value * 16.0 + f64::from(digit);
+                if fraction {
+                    scale -= 4;
+                }
+            }
+            None => fraction = true,
+        }
+    }
+    let value = value * 2f64.powi(power + scale);
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// The number at the start of `text` and the length it spans, read the
+/// way `strtod` does but without skipping blanks. None when `text` does
+/// not start with a number.
+pub fn leading_float(text: &str) -> Option<(f64, usize)> {
+    let len = float_prefix(text);
+    if len == 0 {
+        return None;
+    }
+    let number = &text[..len];
+    let unsigned = number.trim_start_matches(['+', '-']);
+    let value = if unsigned.len() > 1 && unsigned[..2].eq_ignore_ascii_case("0x") {
+        parse_hex_float(number)
+    } else {
+        number.parse().expect("validated float")
+    };
+    Some((value, len))
+}
+
+/// Read a floating point argument, including hexadecimal floats, `inf`
+/// and `nan`. Returns the value to use and a complaint, if any.
+pub fn parse_float(arg: &str) -> (f64, Option<String>) {
+    if let Some(code) = character_code(arg) {
+        return (f64::from(code), None);
+    }
+
+    let text = arg.trim_start();
+    let (value, len) = match leading_float(text) {
+        Some(found) => found,
+        None => return (0.0, Some(expected_number(arg))),
+    };
+
+    let unsigned = text[..len].trim_start_matches(['+', '-']);
+    let literal_infinity = unsigned.to_ascii_lowercase().starts_with("inf");
+    if len < text.len() {
+        (value, Some(not_converted(arg)))
+    } else if value.is_infinite() && !literal_infinity {
+        (value, Some(out_of_range(arg)))
+    } else {
+        (value, None)
+    }
+}
+
+fn needs_quoting(idx: usize, chr: char) -> bool {
+    match chr {
+        ' ' | '!' | '"' | '$' | '&' | '\'' | '(' | ')' | '*' | ';' | '<' | '=' | '>' | '?'
+        | '[' | '\\' | '^' | '`' | '|' => true,
+        '#' | '~' => idx == 0,
+        chr => chr.is_control(),
+    }
+}
+
+fn control_escape(chr: char, out: &mut String) {
+    match chr {
+        '\x07' => out.push_str("\\a"),
+        '\x08' => out.push_str("\\b"),
+        '\t' => out.push_str("\\t"),
+        '\n' => out.push_str("\\n"),
+        '\x0b' => out.push_str("\\v"),
+        '\x0c' => out.push_str("\\f"),
+        '\r' => out.push_str("\\r"),
+        _ => {
+            for byte in chr.encode_utf8(&mut [0; 4]).bytes() {
+                out.push_str(&format!("\\{:03o}", byte));
+            }
+        }
+    }
+}
+
+/// Quote `text` so a POSIX shell reads it back unchanged, as `%q` does.
+pub fn shell_quote(text: &str) -> String {
+    if text.is_empty() {
+        return String::from("''");
+    }
+    if !text
+        .chars()
+        .enumerate()
+        .any(|(idx, chr)| needs_quoting(idx, chr))
+    {
+        return text.to_string();
+    }
+
+    let control = text.chars().any(char::is_control);
+    if !control && text.contains('\'') && !text.contains(['$', '`', '\\', '"', '!']) {
+        return format!("\"{}\"", text);
+    }
+
+    // Single quotes throughout, with control characters in $'...' runs.
+    let mut quoted = String::from("'");
+    let mut in_dollar = false;
+    for chr in text.chars() {
+        if chr.is_control() {
+            if !in_dollar {
+                quoted.push_str("'$'");
+                in_dollar = true;
+            }
+            control_escape(chr, &mut quoted);
+        } else {
+            if in_dollar {
+                quoted.push_str("''");
+                in_dollar = false;
+            }
+            if chr == '\'' {
+                quoted.push_str("'\\''");
+            } else {
+                quoted.push(chr);
+            }
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Quote a file name for a diagnostic the way GNU tools do: as
+/// `shell_quote` would, but always inside quotes, so `'a'` rather than `a`.
+pub fn quote(text: &str) -> String {
+    let quoted = shell_quote(text);
+    if quoted.starts_with(['\'', '"']) {
+        quoted
+    } else {
+        format!("'{}'", quoted)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Literal(Vec<u8>),
+    Directive(Spec),
+    /// `\c`: nothing after this is printed.
+    Stop,
+}
+
+/// Arguments consumed by directives, with the complaints they produced.
+struct Arguments<'a> {
+    args: &'a [String],
+    next: usize,
+    warnings: Vec<String>,
+}
+
+impl<'a> Arguments<'a> {
+    fn string(&mut self) -> &'a str {
+        let arg = self.args.get(self.next).map_or("", String::as_str);
+        self.next += 1;
+        arg
+    }
+
+    fn integer(&mut self) -> i128 {
+        let arg = self.string();
+        if arg.is_empty() {
+            return 0;
+        }
+        let (value, warning) = parse_integer(arg);
+        self.warnings.extend(warning);
+        value
+    }
+
+    fn signed(&mut self) -> i128 {
+        let arg = self.args.get(self.next).cloned().unwrap_or_default();
+        let value = self.integer();
+        let clamped = value.clamp(i128::from(i64::MIN), i128::from(i64::MAX));
+        if clamped != value && self.warnings.last() != Some(&out_of_range(&arg)) {
+            self.warnings.push(out_of_range(&arg));
+        }
+        clamped
+    }
+
+    /// Negative values wrap around as C's strtoumax makes them.
+    fn unsigned(&mut self) -> u128 {
+        let arg = self.args.get(self.next).cloned().unwrap_or_default();
+        let value = self.integer();
+        let converted = if value < 0 && value >= i128::from(i64::MIN) {
+            Some(u128::from(value as i64 as u64))
+        } else if (0..=i128::from(u64::MAX)).contains(&value) {
+            Some(value as u128)
+        } else {
+            None
+        };
+        converted.unwrap_or_else(|| {
+            if self.warnings.last() != Some(&out_of_range(&arg)) {
+                self.warnings.push(out_of_range(&arg));
+            }
+            u128::from(u64::MAX)
+        })
+    }
+
+    fn float(&mut self) -> f64 {
+        let arg = self.string();
+        if arg.is_empty() {
+            return 0.0;
+        }
+        let (value, warning) = parse_float(arg);
+        self.warnings.extend(warning);
+        value
+    }
+}
+
+/// A parsed printf format string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    items: Vec<Item>,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Format, String> {
+        let mut items = Vec::new();
+        let mut literal = Vec::new();
+        let mut rest = format;
+
+        while let Some(idx) = rest.find(['\\', '%']) {
+            literal.extend_from_slice(&rest.as_bytes()[..idx]);
+            let after = &rest[idx + 1..];
+            if rest.as_bytes()[idx] == b'\\' {
+                let (flow, used) = escape(after, Escapes::Format, &mut literal)?;
+                if flow == Flow::Stop {
+                    items.push(Item::Literal(literal));
+                    items.push(Item::Stop);
+                    return Ok(Format { items });
+                }
+                rest = &after[used..];
+            } else if let Some(after) = after.strip_prefix('%') {
+                literal.push(b'%');
+                rest = after;
+            } else {
+                let invalid = |text: &str| format!("%{}: invalid conversion specification", text);
+                let (spec, used) = Spec::parse(after).ok_or_else(|| invalid(after))?;
+                if !spec.valid_for_printf() {
+                    return Err(invalid(&after[..used]));
+                }
+                if !literal.is_empty() {
+                    items.push(Item::Literal(std::mem::take(&mut literal)));
+                }
+                items.push(Item::Directive(spec));
+                rest = &after[used..];
+            }
+        }
+
+        literal.extend_from_slice(rest.as_bytes());
+        if !literal.is_empty() {
+            items.push(Item::Literal(literal));
+        }
+        Ok(Format { items })
+    }
+
+    /// Whether any directive consumes an argument.
+    pub fn takes_arguments(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, Item::Directive(_)))
+    }
+
+    /// Append the format applied to `args` to `out`, repeating it while
+    /// arguments remain. Missing arguments read as empty or zero. Returns
+    /// complaints about arguments that were not valid numbers; an error
+    /// means output had to stop.
+    pub fn render(&self, args: &[String], out: &mut Vec<u8>) -> Result<Vec<String>, String> {
+        let mut args = Arguments {
+            args,
+            next: 0,
+            warnings: Vec::new(),
+        };
+        loop {
+            for item in &self.items {
+                let flow = match item {
+                    Item::Literal(bytes) => {
+                        out.extend_from_slice(bytes);
+                        Flow::Continue
+                    }
+                    Item::Directive(spec) => Format::directive(spec, &mut args, out)?,
+                    Item::Stop => Flow::Stop,
+                };
+                if flow == Flow::Stop {
+                    return Ok(args.warnings);
+                }
+            }
+            if !self.takes_arguments() || args.next >= args.args.len() {
+                return Ok(args.warnings);
+            }
+        }
+    }
+
+    fn directive(spec: &Spec, args: &mut Arguments, out: &mut Vec<u8>) -> Result<Flow, String> {
+        let mut spec = spec.clone();
+        if spec.width_from_arg {
+            let width = args.integer();
+            if width.unsigned_abs() > i32::MAX as u128 {
+                return Err(format!("invalid field width: {}", width));
+            }
+            spec.left |= width < 0;
+            spec.width = width.unsigned_abs() as usize;
+        }
+        if spec.precision_from_arg {
+            let precision = args.integer();
+            if precision > i128::from(i32::MAX) {
+                return Err(format!("invalid precision: {}", precision));
+            }
+            spec.precision = if precision < 0 {
+                None
+            } else {
+                Some(precision as usize)
+            };
+        }
+
+        let text = match spec.conversion {
+            'd' | 'i' => spec.signed(args.signed()),
+            'o' | 'u' | 'x' | 'X' => spec.unsigned(args.unsigned()),
+            'a' | 'A' | 'e' | 'E' | 'f' | 'F' | 'g' | 'G' => spec.float(args.float()),
+            'c' => match args.string().chars().next() {
+                Some(chr) => spec.pad(&chr.to_string()),
+                None => spec.pad("\0"),
+            },
+            's' => spec.string(args.string()),
+            'q' => shell_quote(args.string()),
+            'b' => return unescape(args.string(), Escapes::Argument, out),
+            other => unreachable!("%{} is rejected when the format is parsed", other),
+        };
+        out.extend_from_slice(text.as_bytes());
+        Ok(Flow::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn printf(format: &str, list: &[&str]) -> String {
+        let mut out = Vec::new();
+        Format::parse(format)
+            .unwrap()
+            .render(&args(list), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn expand(text: &str, mode: Escapes) -> (Vec<u8>, Flow) {
+        let mut out = Vec::new();
+        let flow = unescape(text, mode, &mut out).unwrap();
+        (out, flow)
+    }
+
+    #[test]
+    fn simple_escapes() {
+        let (out, flow) = expand("a\\tb\\n\\\\\\a\\e", Escapes::Format);
+        assert_eq!(out, b"a\tb\n\\\x07\x1b");
+        assert_eq!(flow, Flow::Continue);
+    }
+
+    #[test]
+    fn unknown_escape_kept() {
+        assert_eq!(expand("a\\qb\\", Escapes::Format).0, b"a\\qb\\");
+        assert_eq!(expand("\\\"", Escapes::Echo).0, b"\\\"");
+        assert_eq!(expand("\\\"", Escapes::Format).0, b"\"");
+    }
+
+    #[test]
+    fn octal_escapes() {
+        assert_eq!(expand("\\101\\0101", Escapes::Format).0, b"A\x081");
+        assert_eq!(expand("\\0101\\101", Escapes::Argument).0, b"AA");
+        assert_eq!(expand("\\0101\\101", Escapes::Echo).0, b"A\\101");
+        assert_eq!(expand("\\0", Escapes::Echo).0, b"\0");
+    }
+
+    #[test]
+    fn hex_escapes() {
+        assert_eq!(expand("\\x41\\x4a2", Escapes::Format).0, b"AJ2");
+        assert_eq!(expand("\\xg", Escapes::Echo).0, b"\\xg");
+        let mut out = Vec::new();
+        assert!(unescape("\\xg", Escapes::Format, &mut out)
+            .unwrap_err()
+            .contains("missing hexadecimal"));
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        assert_eq!(
+            expand("\\u00e9\\U0001F600", Escapes::Format).0,
+            "é😀".as_bytes()
+        );
+        assert_eq!(expand("\\u00e9", Escapes::Echo).0, b"\\u00e9");
+        let mut out = Vec::new();
+        assert!(unescape("\\u12", Escapes::Format, &mut out).is_err());
+        assert!(unescape("\\u0041", Escapes::Format, &mut out)
+            .unwrap_err()
+            .contains("invalid universal character name"));
+    }
+
+    #[test]
+    fn stop_escape() {
+        let (out, flow) = expand("ab\\cde", Escapes::Echo);
+        assert_eq!(out, b"ab");
+        assert_eq!(flow, Flow::Stop);
+        assert_eq!(printf("x\\c y\\n", &[]), "x");
+        assert_eq!(printf("%b%s\\n", &["a\\cb", "never"]), "a");
+    }
+
+    #[test]
+    fn spec_parsing() {
+        let (spec, used) = Spec::parse("-08.3ldrest").unwrap();
+        assert!(spec.left && spec.zero);
+        assert_eq!(spec.width, 8);
+        assert_eq!(spec.precision, Some(3));
+        assert_eq!(spec.conversion, 'd');
+        assert_eq!(used, 7);
+
+        let (spec, _) = Spec::parse("*.*f").unwrap();
+        assert!(spec.width_from_arg && spec.precision_from_arg);
+        assert!(Spec::parse("5").is_none());
+    }
+
+    #[test]
+    fn invalid_specifications() {
+        for format in &["%5%", "%05s", "%.2c", "%#d", "%-5q", "%5b", "%z", "%"] {
+            assert!(
+                Format::parse(format)
+                    .unwrap_err()
+                    .contains("invalid conversion specification"),
+                "{}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(
+            printf(
+                "%d|%i|%5d|%-4d|%04d|%+.3d",
+                &["1", "-2", "3", "4", "-7", "5"]
+            ),
+            "1|-2|    3|4   |-007|+005"
+        );
+        assert_eq!(printf("% d|%.0d|%ld", &["4", "0", "9"]), " 4||9");
+    }
+
+    #[test]
+    fn unsigned_integers() {
+        assert_eq!(
+            printf(
+                "%o|%#o|%u|%x|%#X|%.3x",
+                &["8", "8", "7", "255", "255", "10"]
+            ),
+            "10|010|7|ff|0XFF|00a"
+        );
+        assert_eq!(printf("%u", &["-1"]), "18446744073709551615");
+        assert_eq!(printf("%#x|%#.0o", &["0", "0"]), "0|0");
+    }
+
+    #[test]
+    fn integer_arguments() {
+        assert_eq!(
+            printf("%d %d %d %d", &["0x1f", "010", "'A", "\"b"]),
+            "31 8 65 98"
+        );
+        assert_eq!(printf("%d %d", &[" 7", "+8"]), "7 8");
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            printf(
+                "%f|%.2f|%8.3f|%-8.2f|%08.3f",
+                &["1.5", "3.14159", "2", "2.5", "-1.5"]
+            ),
+            "1.500000|3.14|   2.000|2.50    |-001.500"
+        );
+        assert_eq!(
+            printf("%e|%.0e|%10.3e|%E", &["123.456", "5", "-3.14159", "1e-10"]),
+            "1.234560e+02|5e+00|-3.142e+00|1.000000E-10"
+        );
+        assert_eq!(printf("%#.0f|%#.0e", &["3", "3"]), "3.|3.e+00");
+    }
+
+    #[test]
+    fn general_floats() {
+        assert_eq!(
+            printf(
+                "%g %g %g %g %g %g %g",
+                &[
+                    "100000",
+                    "1000000",
+                    "1e-5",
+                    "0.0001",
+                    "0",
+                    "123456789",
+                    "1e10"
+                ]
+            ),
+            "100000 1e+06 1e-05 0.0001 0 1.23457e+08 1e+10"
+        );
+        assert_eq!(
+            printf("%#g|%G|%.3g", &["1", "1e-10", "3.14159"]),
+            "1.00000|1E-10|3.14"
+        );
+    }
+
+    #[test]
+    fn hex_floats() {
+        assert_eq!(
+            printf("%a %a %a", &["0", "1", "-2.5"]),
+            "0x0p+0 0x1p+0 -0x1.4p+1"
+        );
+        assert_eq!(
+            printf("%.2a %A %.0a", &["1", "255", "1.5"]),
+            "0x1.00p+0 0X1.FEP+7 0x2p+0"
+        );
+    }
+
+    #[test]
+    fn special_floats() {
+        assert_eq!(
+            printf("%f %e %g %F %5f", &["inf", "-inf", "nan", "inf", "inf"]),
+            "inf -inf nan INF   inf"
+        );
+    }
+
+    #[test]
+    fn float_arguments() {
+        assert_eq!(printf("%g %g %g", &["0x1p3", " 2", "'a"]), "8 2 97");
+        assert_eq!(parse_float("1.5e3"), (1500.0, None));
+        assert_eq!(parse_float("-.5"), (-0.5, None));
+        assert_eq!(parse_float("1e"), (1.0, Some(not_converted("1e"))));
+    }
+
+    #[test]
+    fn leading_floats() {
+        assert_eq!(leading_float("12.5kg"), Some((12.5, 4)));
+        assert_eq!(leading_float("-INF and more"), Some((f64::NEG_INFINITY, 4)));
+        assert_eq!(leading_float("0x10p1"), Some((32.0, 6)));
+        assert_eq!(leading_float(" 1"), None);
+        assert_eq!(leading_float("abc"), None);
+    }
+
+    #[test]
+    fn numeric_warnings() {
+        let mut out = Vec::new();
+        let warnings = Format::parse("%d %d %d %f\n")
+            .unwrap()
+            .render(
+                &args(&["abc", "3x", "99999999999999999999", "1,5"]),
+                &mut out,
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0 3 9223372036854775807 1.000000\n"
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "'abc': expected a numeric value",
+                "'3x': value not completely converted",
+                "'99999999999999999999': Numerical result out of range",
+                "'1,5': value not completely converted",
+            ]
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            printf("%s|%5s|%-5s|%.1s", &["é", "é", "é", "éa"]),
+            "é|    é|é    |é"
+        );
+        assert_eq!(printf("%c|%c|%3c", &["hello", "", "x"]), "h|\0|  x");
+    }
+
+    #[test]
+    fn star_width_and_precision() {
+        assert_eq!(
+            printf(
+                "%*d|%-*d|%.*f|%*d",
+                &["5", "1", "4", "2", "2", "3.14159", "-3", "7"]
+            ),
+            "    1|2   |3.14|7  "
+        );
+    }
+
+    #[test]
+    fn reuses_format() {
+        assert_eq!(
+            printf("%s=%d\n", &["a", "1", "b", "2", "c"]),
+            "a=1\nb=2\nc=0\n"
+        );
+        assert_eq!(printf("no args %s %d|\n", &[]), "no args  0|\n");
+        assert_eq!(printf("plain\n", &["ignored"]), "plain\n");
+    }
+
+    #[test]
+    fn percent_literal() {
+        assert_eq!(printf("100%%\n", &[]), "100%\n");
+    }
+
+    #[test]
+    fn quoting() {
+        let cases = [
+            ("plain", "plain"),
+            ("", "''"),
+            ("a b", "'a b'"),
+            ("it's", "\"it's\""),
+            ("it's $x", "'it'\\''s $x'"),
+            ("a'b\"c", "'a'\\''b\"c'"),
+            ("a=b", "'a=b'"),
+            ("~x", "'~x'"),
+            ("x~", "x~"),
+            ("ü", "ü"),
+            ("{a}", "{a}"),
+            ("a\nb", "'a'$'\\n''b'"),
+            ("\x01\x02", "''$'\\001\\002'"),
+            ("a b\t", "'a b'$'\\t'"),
+        ];
+        for (text, quoted) in &cases {
+            assert_eq!(shell_quote(text), *quoted, "{:?}", text);
+        }
+        assert_eq!(printf("%q\n", &["a b"]), "'a b'\n");
+    }
+
+    #[test]
+    fn always_quoted() {
+        assert_eq!(quote("plain"), "'plain'");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote("it's"), "\"it's\"");
+        assert_eq!(quote("a\nb"), "'a'$'\\n''b'");
+    }
+
+    #[test]
+    fn escapes_in_argument() {
+        assert_eq!(printf("%b|", &["a\\0101", "b\\101", "\\x41é"]), "aA|bA|Aé|");
+    }
+
+    #[test]
+    fn unicode_in_format() {
+        assert_eq!(printf("é\\U0001F600A\\n", &[]), "é😀A\n");
+    }
+}
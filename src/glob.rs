@@ -0,0 +1,662 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(char),
+    Any,
+    Star,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Glob(Vec<Token>),
+    Recursive,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MatchOptions {
+    pub case_sensitive: bool,
+}
+
+impl Default for MatchOptions {
+    /// Windows file systems ignore case, so matching does too.
+    fn default() -> Self {
+        MatchOptions {
+            case_sensitive: !cfg!(windows),
+        }
+    }
+}
+
+/// A wildcard pattern supporting `*`, `?`, `[...]` and `**` for any
+/// number of directories.
+#[derive(Debug)]
+pub struct Pattern {
+    absolute: bool,
+    separator: char,
+    segments: Vec<Segment>,
+    options: MatchOptions,
+}
+
+fn is_separator(chr: char) -> bool {
+    chr == '/' || chr == '\\'
+}
+
+fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+    let mut idx = 1;
+    let negated = matches!(chars.get(idx), Some('!') | Some('^'));
+    if negated {
+        idx += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    while idx < chars.len() {
+        let chr = chars[idx];
+        if chr == ']' && !first {
+            return Some((Token::Class { negated, ranges }, idx + 1));
+        }
+        if is_separator(chr) {
+            return None;
+        }
+        first = false;
+        if chars.get(idx + 1) == Some(&'-') && chars.get(idx + 2).is_some_and(|&c| c != ']') {
+            ranges.push((chr, chars[idx + 2]));
+            idx += 3;
+        } else {
+            ranges.push((chr, chr));
+            idx += 1;
+        }
+    }
+    None
+}
+
+fn tokenize(component: &str) -> Vec<Token> {
+    let chars: Vec<char> = component.chars().collect();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' => {
+                if tokens.last() != Some(&Token::Star) {
+                    tokens.push(Token::Star);
+                }
+                idx += 1;
+            }
+            '?' => {
+                tokens.push(Token::Any);
+                idx += 1;
+            }
+            '[' => match parse_class(&chars[idx..]) {
+                Some((class, len)) => {
+                    tokens.push(class);
+                    idx += len;
+                }
+                None => {
+                    tokens.push(Token::Literal('['));
+                    idx += 1;
+                }
+            },
+            chr => {
+                tokens.push(Token::Literal(chr));
+                idx += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// True if `arg` contains a `*`, `?` or a complete `[...]` class.
+pub fn has_wildcards(arg: &str) -> bool {
+    arg.split(is_separator)
+        .flat_map(tokenize)
+        .any(|token| !matches!(token, Token::Literal(_)))
+}
+
+fn same_char(a: char, b: char, options: MatchOptions) -> bool {
+    if options.case_sensitive {
+        a == b
+    } else {
+        a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+fn in_range(chr: char, low: char, high: char, options: MatchOptions) -> bool {
+    if low <= chr && chr <= high {
+        return true;
+    }
+    if options.case_sensitive {
+        return false;
+    }
+    chr.to_lowercase()
+        .chain(chr.to_uppercase())
+        .any(|c| low <= c && c <= high)
+}
+
+fn token_matches(token: &Token, chr: char, options: MatchOptions) -> bool {
+    match token {
+        Token::Literal(lit) => same_char(*lit, chr, options),
+        Token::Any => true,
+        Token::Star => false,
+        Token::Class { negated, ranges } => {
+            let found = ranges
+                .iter()
+                .any(|&(low, high)| in_range(chr, low, high, options));
+            found != *negated
+        }
+    }
+}
+
+fn matches_tokens(tokens: &[Token], name: &str, options: MatchOptions) -> bool {
+    let name: Vec<char> = name.chars().collect();
+
+    // Hidden entries only match a pattern that names the dot explicitly.
+    if name.first() == Some(&'.') && tokens.first() != Some(&Token::Literal('.')) {
+        return false;
+    }
+
+    let (mut t, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match tokens.get(t) {
+            Some(Token::Star) => {
+                backtrack = Some((t, n));
+                t += 1;
+            }
+            Some(token) if token_matches(token, name[n], options) => {
+                t += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, consumed)) => {
+                    t = star + 1;
+                    n = consumed + 1;
+                    backtrack = Some((star, consumed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[t..].iter().all(|token| *token == Token::Star)
+}
+
+fn join(current: &str, name: &str, separator: char) -> String {
+    if current.is_empty() {
+        name.to_string()
+    } else if current.ends_with(separator) {
+        format!("{}{}", current, name)
+    } else {
+        format!("{}{}{}", current, separator, name)
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, options: MatchOptions) -> Pattern {
+        let separator = pattern
+            .chars()
+            .find(|&c| is_separator(c))
+            .unwrap_or(std::path::MAIN_SEPARATOR);
+        let absolute = pattern.starts_with(is_separator);
+
+        let mut segments: Vec<Segment> = pattern
+            .split(is_separator)
+            .filter(|component| !component.is_empty())
+            .map(|component| {
+                if component == "**" {
+                    return Segment::Recursive;
+                }
+                let tokens = tokenize(component);
+                if tokens
+                    .iter()
+                    .all(|token| matches!(token, Token::Literal(_)))
+                {
+                    Segment::Literal(component.to_string())
+                } else {
+                    Segment::Glob(tokens)
+                }
+            })
+            .collect();
+
+        // A trailing `**` means everything below, as in `**/*`.
+        if segments.last() == Some(&Segment::Recursive) {
+            segments.push(Segment::Glob(vec![Token::Star]));
+        }
+
+        Pattern {
+            absolute,
+            separator,
+            segments,
+            options,
+        }
+    }
+
+    /// Whether `name` matches a single-component pattern.
+    pub fn matches(&self, name: &str) -> bool {
+        match self.segments.as_slice() {
+            [Segment::Literal(lit)] => matches_tokens(&tokenize(lit), name, self.options),
+            [Segment::Glob(tokens)] => matches_tokens(tokens, name, self.options),
+            _ => false,
+        }
+    }
+
+    /// Every existing path matching the pattern, sorted. Relative patterns
+    /// are resolved against `root` but returned relative, as typed.
+    pub fn expand_from(&self, root: &Path) -> Vec<String> {
+        let mut found = Vec::new();
+        let (start, path) = if self.absolute {
+            let start = self.separator.to_string();
+            let path = PathBuf::from(&start);
+            (start, path)
+        } else {
+            (String::new(), root.to_path_buf())
+        };
+        self.walk(0, start, path, &mut found);
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// `current` is the match as it will be printed, `path` the same
+    /// location on disk.
+    fn walk(&self, idx: usize, current: String, path: PathBuf, found: &mut Vec<String>) {
+        if idx == self.segments.len() {
+            if !current.is_empty() && fs::symlink_metadata(&path).is_ok() {
+                found.push(current);
+            }
+            return;
+        }
+
+        match &self.segments[idx] {
+            Segment::Literal(name) => {
+                let next = join(&current, name, self.separator);
+                // A first segment of `C:` comes from a pattern like
+                // `C:\*`, so it stands for the root of that drive; joined
+                // bare it would be the current directory there instead.
+                let next_path = if idx == 0 && name.ends_with(':') {
+                    PathBuf::from(format!("{}{}", name, self.separator))
+                } else {
+                    path.join(name)
+                };
+                self.walk(idx + 1, next, next_path, found);
+            }
+            Segment::Glob(tokens) => {
+                let last = idx + 1 == self.segments.len();
+                for (name, is_dir, _) in list_dir(&path) {
+                    if matches_tokens(tokens, &name, self.options) && (last || is_dir) {
+                        let next = join(&current, &name, self.separator);
+                        self.walk(idx + 1, next, path.join(&name), found);
+                    }
+                }
+            }
+            Segment::Recursive => {
+                self.walk(idx + 1, current.clone(), path.clone(), found);
+                for (name, is_dir, is_link) in list_dir(&path) {
+                    if is_dir && !is_link && !name.starts_with('.') {
+                        let next = join(&current, &name, self.separator);
+                        self.walk(idx, next, path.join(&name), found);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn list_dir(path: &Path) -> Vec<(String, bool, bool)> {
+    let path = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    };
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+            let is_dir = entry.path().is_dir();
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                is_dir,
+                is_link,
+            )
+        })
+        .collect()
+}
+
+/// Expand one operand against `root`. Operands without wildcards, or whose
+/// pattern matches nothing, are passed through unchanged.
+pub fn expand_operand_from(root: &Path, operand: &str, options: MatchOptions) -> Vec<String> {
+    if !has_wildcards(operand) {
+        return vec![operand.to_string()];
+    }
+    let matched = Pattern::new(operand, options).expand_from(root);
+    if matched.is_empty() {
+        vec![operand.to_string()]
+    } else {
+        matched
+    }
+}
+
+pub fn expand_operand(operand: &str) -> Vec<String> {
+    expand_operand_from(&PathBuf::new(), operand, MatchOptions::default())
+}
+
+/// Split a raw Windows command line the way the MSVC runtime does. Each
+/// argument is paired with whether a wildcard appeared inside quotes, in
+/// which case it must be taken literally.
+pub fn split_command_line(line: &str) -> Vec<(String, bool)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut args = Vec::new();
+    let mut idx = 0;
+
+    // The program name ends at the closing quote and takes no escapes.
+    let mut program = String::new();
+    let mut in_quotes = false;
+    while idx < chars.len() && (in_quotes || !chars[idx].is_whitespace()) {
+        if chars[idx] == '"' {
+            in_quotes = !in_quotes;
+        } else {
+            program.push(chars[idx]);
+        }
+        idx += 1;
+    }
+    args.push((program, false));
+
+    loop {
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        if idx >= chars.len() {
+            break;
+        }
+
+        let mut arg = String::new();
+        let mut quoted_wildcard = false;
+        let mut in_quotes = false;
+        while idx < chars.len() && (in_quotes || !chars[idx].is_whitespace()) {
+            match chars[idx] {
+                '\\' => {
+                    let start = idx;
+                    while idx < chars.len() && chars[idx] == '\\' {
+                        idx += 1;
+                    }
+                    let count = idx - start;
+                    if chars.get(idx) == Some(&'"') {
+                        arg.extend(std::iter::repeat_n('\\', count / 2));
+                        if count % 2 == 1 {
+                            arg.push('"');
+                            idx += 1;
+                        }
+                    } else {
+                        arg.extend(std::iter::repeat_n('\\', count));
+                    }
+                }
+                '"' => {
+                    if in_quotes && chars.get(idx + 1) == Some(&'"') {
+                        arg.push('"');
+                        idx += 2;
+                    } else {
+                        in_quotes = !in_quotes;
+                        idx += 1;
+                    }
+                }
+                chr => {
+                    if in_quotes && matches!(chr, '*' | '?' | '[') {
+                        quoted_wildcard = true;
+                    }
+                    arg.push(chr);
+                    idx += 1;
+                }
+            }
+        }
+        args.push((arg, quoted_wildcard));
+    }
+    args
+}
+
+#[cfg(windows)]
+fn command_line() -> Option<String> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::um::processenv::GetCommandLineW;
+
+    unsafe {
+        let ptr = GetCommandLineW();
+        if ptr.is_null() {
+            return None;
+        }
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        let wide = std::slice::from_raw_parts(ptr, len);
+        Some(OsString::from_wide(wide).to_string_lossy().into_owned())
+    }
+}
+
+/// Unix shells expand wildcards before we ever see them, so whatever is
+/// left was quoted on purpose.
+#[cfg(not(windows))]
+fn command_line() -> Option<String> {
+    None
+}
+
+/// For each of `args`, whether it must be taken literally: either its
+/// wildcards were quoted, or the raw command line can't be matched up.
+pub fn literal_args(args: &[String]) -> Vec<bool> {
+    let parsed = match command_line() {
+        Some(line) => split_command_line(&line),
+        None => return vec![true; args.len()],
+    };
+
+    let lines_up = parsed.len() == args.len()
+        && parsed
+            .iter()
+            .zip(args)
+            .skip(1)
+            .all(|((parsed, _), arg)| parsed == arg);
+    if !lines_up {
+        return vec![true; args.len()];
+    }
+    parsed.into_iter().map(|(_, quoted)| quoted).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    const SENSITIVE: MatchOptions = MatchOptions {
+        case_sensitive: true,
+    };
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        Pattern::new(pattern, SENSITIVE).matches(name)
+    }
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for dir_name in &["src", "src/bin", "src/bin/cat", ".hidden", "docs"] {
+            fs::create_dir(dir.path().join(dir_name)).unwrap();
+        }
+        for file in &[
+            "a.txt",
+            "b.txt",
+            "c.log",
+            "Readme.md",
+            ".secret.txt",
+            "src/lib.rs",
+            "src/config.rs",
+            "src/bin/cat/main.rs",
+            ".hidden/inner.rs",
+            "docs/x1.md",
+            "docs/x2.md",
+            "docs/y3.md",
+        ] {
+            File::create(dir.path().join(file)).unwrap();
+        }
+        dir
+    }
+
+    fn expand(dir: &tempfile::TempDir, pattern: &str) -> Vec<String> {
+        Pattern::new(pattern, SENSITIVE).expand_from(dir.path())
+    }
+
+    #[test]
+    fn detects_wildcards() {
+        assert!(has_wildcards("*.txt"));
+        assert!(has_wildcards("file?.log"));
+        assert!(has_wildcards("[ab].txt"));
+        assert!(has_wildcards("src/**/main.rs"));
+        assert!(!has_wildcards("plain.txt"));
+        assert!(!has_wildcards("unclosed[.txt"));
+    }
+
+    #[test]
+    fn star_and_question() {
+        assert!(matches("*.txt", "a.txt"));
+        assert!(matches("*", "anything"));
+        assert!(matches("a*b*c", "aXXbYYc"));
+        assert!(!matches("a*b*c", "aXXbYY"));
+        assert!(matches("file?.log", "file1.log"));
+        assert!(!matches("file?.log", "file10.log"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("[ab].txt", "a.txt"));
+        assert!(!matches("[ab].txt", "c.txt"));
+        assert!(matches("x[0-9]", "x5"));
+        assert!(matches("x[!0-9]", "xa"));
+        assert!(matches("x[^0-9]", "xa"));
+        assert!(!matches("x[!0-9]", "x1"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("x[a-]", "x-"));
+    }
+
+    #[test]
+    fn hidden_needs_dot() {
+        assert!(!matches("*", ".secret"));
+        assert!(!matches("?secret", ".secret"));
+        assert!(matches(".*", ".secret"));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let options = MatchOptions {
+            case_sensitive: false,
+        };
+        assert!(Pattern::new("*.TXT", options).matches("a.txt"));
+        assert!(Pattern::new("[A-C].txt", options).matches("b.txt"));
+        assert!(!matches("*.TXT", "a.txt"));
+    }
+
+    #[test]
+    fn expand_sorted() {
+        let dir = tree();
+        assert_eq!(expand(&dir, "*.txt"), vec!["a.txt", "b.txt"]);
+        assert_eq!(
+            expand(&dir, "*"),
+            vec!["Readme.md", "a.txt", "b.txt", "c.log", "docs", "src"]
+        );
+    }
+
+    #[test]
+    fn expand_in_subdirectory() {
+        let dir = tree();
+        assert_eq!(expand(&dir, "docs/x?.md"), vec!["docs/x1.md", "docs/x2.md"]);
+        assert_eq!(expand(&dir, "docs/[!x]*"), vec!["docs/y3.md"]);
+        assert_eq!(expand(&dir, "*/lib.rs"), vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn expand_recursive() {
+        let dir = tree();
+        assert_eq!(
+            expand(&dir, "**/*.rs"),
+            vec!["src/bin/cat/main.rs", "src/config.rs", "src/lib.rs"]
+        );
+        assert_eq!(
+            expand(&dir, "src/**"),
+            vec![
+                "src/bin",
+                "src/bin/cat",
+                "src/bin/cat/main.rs",
+                "src/config.rs",
+                "src/lib.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_backslash_separator() {
+        let dir = tree();
+        assert_eq!(expand(&dir, "docs\\y*"), vec!["docs\\y3.md"]);
+    }
+
+    #[test]
+    fn expand_absolute() {
+        let dir = tree();
+        let pattern = format!("{}/*.log", dir.path().display());
+        let expected = format!("{}/c.log", dir.path().display());
+        assert_eq!(
+            Pattern::new(&pattern, SENSITIVE).expand_from(Path::new("unused")),
+            vec![expected]
+        );
+    }
+
+    #[test]
+    fn operand_without_match_kept() {
+        let dir = tree();
+        assert_eq!(
+            expand_operand_from(dir.path(), "*.nothing", SENSITIVE),
+            vec!["*.nothing"]
+        );
+        assert_eq!(
+            expand_operand_from(dir.path(), "a.txt", SENSITIVE),
+            vec!["a.txt"]
+        );
+        assert_eq!(
+            expand_operand_from(dir.path(), "?.txt", SENSITIVE),
+            vec!["a.txt", "b.txt"]
+        );
+    }
+
+    #[test]
+    fn split_plain() {
+        let args = split_command_line("cat.exe a.txt  *.log");
+        assert_eq!(
+            args,
+            vec![
+                ("cat.exe".to_string(), false),
+                ("a.txt".to_string(), false),
+                ("*.log".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_quoted() {
+        let args = split_command_line(r#""C:\Program Files\cat.exe" "*.log" "my file.txt" x"*""#);
+        assert_eq!(args[0], (r"C:\Program Files\cat.exe".to_string(), false));
+        assert_eq!(args[1], ("*.log".to_string(), true));
+        assert_eq!(args[2], ("my file.txt".to_string(), false));
+        assert_eq!(args[3], ("x*".to_string(), true));
+    }
+
+    #[test]
+    fn split_backslashes() {
+        let args = split_command_line(r#"cat a\\\"b c\\"d e" f\g "h""i""#);
+        assert_eq!(args[1].0, r#"a\"b"#);
+        assert_eq!(args[2].0, r"c\d e");
+        assert_eq!(args[3].0, r"f\g");
+        assert_eq!(args[4].0, r#"h"i"#);
+    }
+}
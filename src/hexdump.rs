@@ -0,0 +1,43 @@
+/// Bytes on each line of a `hexdump -C` style dump.
+pub const CANONICAL_WIDTH: usize = 16;
+
+/// One line of a `hexdump -C` style dump: the address, up to
+/// `CANONICAL_WIDTH` bytes in hex in two groups of eight, and the bytes
+/// again as characters between bars.
+pub fn canonical_line(address: u64, bytes: &[u8], uppercase: bool) -> String {
+    let mut line = format!("{:08x}  ", address);
+    for idx in 0..CANONICAL_WIDTH {
+        match bytes.get(idx) {
+            Some(byte) if uppercase => line.push_str(&format!("{:02X} ", byte)),
+            Some(byte) => line.push_str(&format!("{:02x} ", byte)),
+            None => line.push_str("   "),
+        }
+        if idx == 7 {
+            line.push(' ');
+        }
+    }
+    line.push_str(" |");
+    line.extend(bytes.iter().map(|&byte| match byte {
+        0x20..=0x7e => char::from(byte),
+        _ => '.',
+    }));
+    line.push_str("|\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            canonical_line(0, b"hello world\n\0\x01\x02\xff", false),
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 00 01 02 ff  |hello world.....|\n"
+        );
+        assert_eq!(
+            canonical_line(0x10, b"ab\xfe", true),
+            "00000010  61 62 FE                                          |ab.|\n"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+/// The operand that stands for standard input.
+pub const STDIN: &str = "-";
+
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+/// How an operand is named in messages and headers.
+pub fn display_name(path: &str) -> &str {
+    if path == STDIN {
+        "standard input"
+    } else {
+        path
+    }
+}
+
+/// Open an operand for buffered reading; `-` is standard input.
+/// Directories are refused up front since reading one fails differently
+/// on every platform.
+pub fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == STDIN {
+        return Ok(Box::new(BufReader::with_capacity(
+            BUFFER_SIZE,
+            io::stdin().lock(),
+        )));
+    }
+
+    if fs::metadata(path)?.is_dir() {
+        return Err(io::Error::other("Is a directory"));
+    }
+    let file = File::open(path)?;
+    Ok(Box::new(BufReader::with_capacity(BUFFER_SIZE, file)))
+}
+
+/// The length of a file whose metadata shows it can be sized and sought.
+/// Some special files report a length of zero but still have contents, so
+/// only a regular file with something in it counts; anything else has to
+/// be read whole with `read_whole`.
+pub fn seekable_len(metadata: &Metadata) -> Option<u64> {
+    if metadata.is_file() && metadata.len() > 0 {
+        Some(metadata.len())
+    } else {
+        None
+    }
+}
+
+/// Read everything `reader` has into memory, along with its length.
+pub fn read_whole<R: Read>(mut reader: R) -> io::Result<(Cursor<Vec<u8>>, u64)> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    let len = contents.len() as u64;
+    Ok((Cursor::new(contents), len))
+}
+
+/// Read until `buf` is full or the input ends, returning the bytes read.
+pub fn fill<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    #[test]
+    fn names() {
+        assert_eq!(display_name("-"), "standard input");
+        assert_eq!(display_name("file.txt"), "file.txt");
+    }
+
+    #[test]
+    fn open_file() {
+        let path = test_utils::get_path("hello.bin");
+        let mut contents = Vec::new();
+        open(&path.to_string_lossy())
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        assert!(String::from_utf8_lossy(&contents).contains("Hello"));
+    }
+
+    #[test]
+    fn open_directory() {
+        let path = test_utils::get_dir();
+        let err = open(&path.to_string_lossy()).err().unwrap();
+        assert_eq!(err.to_string(), "Is a directory");
+    }
+
+    #[test]
+    fn seekable() {
+        let file = fs::metadata(test_utils::get_path("hello.bin")).unwrap();
+        assert_eq!(seekable_len(&file), Some(file.len()));
+        let dir = fs::metadata(test_utils::get_dir()).unwrap();
+        assert_eq!(seekable_len(&dir), None);
+    }
+
+    #[test]
+    fn whole() {
+        let (contents, len) = read_whole(&b"some text"[..]).unwrap();
+        assert_eq!(len, 9);
+        assert_eq!(contents.into_inner(), b"some text");
+    }
+
+    /// Gives a byte at a time, interrupted before each one.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        interrupted: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupted = !self.interrupted;
+            if self.interrupted {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let read = self.data.len().min(buf.len()).min(1);
+            buf[..read].copy_from_slice(&self.data[..read]);
+            self.data = &self.data[read..];
+            Ok(read)
+        }
+    }
+
+    #[test]
+    fn fill_buffer() {
+        let mut input = Trickle {
+            data: b"abcde",
+            interrupted: false,
+        };
+        let mut buf = [0; 3];
+        assert_eq!(fill(&mut input, &mut buf).unwrap(), 3);
+        assert_eq!(&buf, b"abc");
+        assert_eq!(fill(&mut input, &mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"de");
+        assert_eq!(fill(&mut input, &mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn open_missing() {
+        assert!(open("no/such/file").is_err());
+    }
+}
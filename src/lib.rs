@@ -1,2 +1,22 @@
+pub mod backup;
+pub mod checksum;
+pub mod completion;
 pub mod config;
+pub mod copy;
+pub mod digest;
+pub mod docs;
+pub mod encoding;
+pub mod format;
+pub mod glob;
+pub mod hexdump;
+pub mod input;
+pub mod lines;
+pub mod output;
+pub mod ranges;
+pub mod regex;
+pub mod rename;
+pub mod size;
 pub mod test_utils;
+pub mod usage;
+pub mod walk;
+pub mod width;
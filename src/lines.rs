@@ -0,0 +1,128 @@
+use std::io::{self, BufRead};
+
+/// Length of `line` without its terminator. A carriage return before a
+/// newline terminator belongs to the terminator, so text written on
+/// Windows compares equal to the same text written elsewhere.
+pub fn content_len(line: &[u8], delimiter: u8) -> usize {
+    let mut len = line.len();
+    if len > 0 && line[len - 1] == delimiter {
+        len -= 1;
+        if delimiter == b'\n' && len > 0 && line[len - 1] == b'\r' {
+            len -= 1;
+        }
+    }
+    len
+}
+
+/// Reads delimited lines one at a time into a buffer that is reused, so
+/// memory stays bounded by the longest line.
+pub struct LineReader<R: BufRead> {
+    reader: R,
+    delimiter: u8,
+    buffer: Vec<u8>,
+    content: usize,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R, delimiter: u8) -> Self {
+        LineReader {
+            reader,
+            delimiter,
+            buffer: Vec::new(),
+            content: 0,
+        }
+    }
+
+    /// Advance to the next line. Returns false at the end of input.
+    pub fn next_line(&mut self) -> io::Result<bool> {
+        self.buffer.clear();
+        if self.reader.read_until(self.delimiter, &mut self.buffer)? == 0 {
+            self.content = 0;
+            return Ok(false);
+        }
+        self.content = content_len(&self.buffer, self.delimiter);
+        Ok(true)
+    }
+
+    /// The current line without its terminator.
+    pub fn line(&self) -> &[u8] {
+        &self.buffer[..self.content]
+    }
+
+    /// The terminator of the current line as it was read; empty for a
+    /// last line without one.
+    pub fn terminator(&self) -> &[u8] {
+        &self.buffer[self.content..]
+    }
+
+    /// The current line exactly as it was read.
+    pub fn raw(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn lines(text: &[u8], delimiter: u8) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut reader = LineReader::new(Cursor::new(text), delimiter);
+        let mut lines = Vec::new();
+        while reader.next_line().unwrap() {
+            assert_eq!([reader.line(), reader.terminator()].concat(), reader.raw());
+            lines.push((reader.line().to_vec(), reader.terminator().to_vec()));
+        }
+        lines
+    }
+
+    #[test]
+    fn newline_terminated() {
+        assert_eq!(
+            lines(b"one\ntwo\n\nlast", b'\n'),
+            vec![
+                (b"one".to_vec(), b"\n".to_vec()),
+                (b"two".to_vec(), b"\n".to_vec()),
+                (b"".to_vec(), b"\n".to_vec()),
+                (b"last".to_vec(), b"".to_vec()),
+            ]
+        );
+        assert!(lines(b"", b'\n').is_empty());
+    }
+
+    #[test]
+    fn carriage_returns() {
+        assert_eq!(
+            lines(b"dos\r\nunix\n\r\nstray\r", b'\n'),
+            vec![
+                (b"dos".to_vec(), b"\r\n".to_vec()),
+                (b"unix".to_vec(), b"\n".to_vec()),
+                (b"".to_vec(), b"\r\n".to_vec()),
+                (b"stray\r".to_vec(), b"".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn nul_terminated() {
+        assert_eq!(
+            lines(b"a\r\n\0b\0", b'\0'),
+            vec![
+                (b"a\r\n".to_vec(), b"\0".to_vec()),
+                (b"b".to_vec(), b"\0".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_lengths() {
+        assert_eq!(content_len(b"abc\r\n", b'\n'), 3);
+        assert_eq!(content_len(b"abc\r", b'\n'), 4);
+        assert_eq!(content_len(b"\n", b'\n'), 0);
+        assert_eq!(content_len(b"abc", b'\0'), 3);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io::{self, BufWriter, ErrorKind, StdoutLock, Write};
+use std::process;
+
+/// Exit status when the reader goes away. Unix shells report death by
+/// SIGPIPE as 128 + 13; Windows has no such signal, so a closed pipe is
+/// treated as a normal end of output.
+#[cfg(unix)]
+pub const BROKEN_PIPE: i32 = 128 + libc::SIGPIPE;
+#[cfg(not(unix))]
+pub const BROKEN_PIPE: i32 = 0;
+
+pub const WRITE_ERROR: i32 = 1;
+
+/// Rust ignores SIGPIPE at startup so that writes fail with `BrokenPipe`.
+/// Put the default action back so a closed pipe ends us quietly, the way
+/// every other Unix tool behaves.
+pub fn restore_sigpipe() {
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+/// Render an io::Error without the trailing "(os error N)" std appends.
+pub fn describe(err: &io::Error) -> String {
+    let message = err.to_string();
+    match message.find(" (os error ") {
+        Some(idx) => message[..idx].to_string(),
+        None => message,
+    }
+}
+
+/// The message to print for a failed write, or None when the failure is
+/// a closed pipe and should pass silently.
+pub fn write_error_message(prog: &str, err: &io::Error) -> Option<String> {
+    if err.kind() == ErrorKind::BrokenPipe {
+        return None;
+    }
+    Some(format!("{}: write error: {}", prog, describe(err)))
+}
+
+pub fn write_error_status(err: &io::Error) -> i32 {
+    if err.kind() == ErrorKind::BrokenPipe {
+        BROKEN_PIPE
+    } else {
+        WRITE_ERROR
+    }
+}
+
+/// Report a failed write to stdout and exit.
+pub fn write_failed(prog: &str, err: &io::Error) -> ! {
+    if let Some(message) = write_error_message(prog, err) {
+        eprintln!("{}", message);
+    }
+    process::exit(write_error_status(err))
+}
+
+/// Buffered writer whose methods never return errors: a broken pipe exits
+/// silently and anything else is reported as `prog: write error: ...`.
+///
+/// `write!` and `writeln!` work directly on an `Output`. It also implements
+/// `io::Write`, so any error returned by code writing to one came from
+/// somewhere else, such as the input being read.
+pub struct Output<W: Write> {
+    prog: &'static str,
+    writer: W,
+}
+
+impl Output<BufWriter<StdoutLock<'static>>> {
+    pub fn stdout(prog: &'static str) -> Self {
+        restore_sigpipe();
+        Output::new(prog, BufWriter::new(io::stdout().lock()))
+    }
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(prog: &'static str, writer: W) -> Self {
+        Output { prog, writer }
+    }
+
+    pub fn write_all(&mut self, bytes: &[u8]) {
+        if let Err(e) = self.writer.write_all(bytes) {
+            write_failed(self.prog, &e);
+        }
+    }
+
+    pub fn write_str(&mut self, text: &str) {
+        self.write_all(text.as_bytes())
+    }
+
+    pub fn write_fmt(&mut self, args: fmt::Arguments) {
+        if let Err(e) = self.writer.write_fmt(args) {
+            write_failed(self.prog, &e);
+        }
+    }
+
+    /// Must be called before `process::exit`, which skips destructors.
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            write_failed(self.prog, &e);
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Output::flush(self);
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for Output<W> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_through() {
+        let mut output = Output::new("test", Vec::new());
+        output.write_str("hello ");
+        let count = 1;
+        write!(output, "line {}", count);
+        output.write_all(b"\n");
+        assert_eq!(output.get_ref(), b"hello line 1\n");
+    }
+
+    #[test]
+    fn broken_pipe_is_silent() {
+        let err = io::Error::from(ErrorKind::BrokenPipe);
+        assert_eq!(write_error_message("cat", &err), None);
+        assert_eq!(write_error_status(&err), BROKEN_PIPE);
+    }
+
+    #[test]
+    fn other_errors_reported() {
+        let err = io::Error::other("No space left on device");
+        assert_eq!(
+            write_error_message("cat", &err).unwrap(),
+            "cat: write error: No space left on device"
+        );
+        assert_eq!(write_error_status(&err), WRITE_ERROR);
+    }
+
+    #[test]
+    fn describe_strips_os_error() {
+        let err = io::Error::from_raw_os_error(2);
+        assert!(!describe(&err).contains("os error"));
+        assert!(err.to_string().starts_with(&describe(&err)));
+    }
+}
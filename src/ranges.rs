@@ -0,0 +1,255 @@
+/// What the numbers in a list count, which decides the wording of errors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Fields,
+    Positions,
+}
+
+impl Unit {
+    fn numbered_from_one(self) -> String {
+        match self {
+            Unit::Fields => String::from("fields are numbered from 1"),
+            Unit::Positions => String::from("byte/character positions are numbered from 1"),
+        }
+    }
+}
+
+/// An inclusive range of 1-based positions. An open end is `usize::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The positions selected by a list such as `1,3-5,7-` as sorted ranges
+/// that do not overlap. Ranges that only touch stay apart, so output
+/// delimiters can still go between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeList {
+    ranges: Vec<Range>,
+}
+
+impl RangeList {
+    /// Parse a list of `N`, `N-`, `N-M` and `-M` items separated by commas
+    /// or blanks, as `cut -b`, `-c` and `-f` take them.
+    pub fn parse(list: &str, unit: Unit) -> Result<RangeList, String> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        for item in list.split([',', ' ', '\t']) {
+            ranges.push(parse_item(list, offset, item.len(), unit)?);
+            offset += item.len() + 1;
+        }
+
+        ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Ok(RangeList { ranges: merged })
+    }
+
+    /// Every position this list leaves out.
+    pub fn complement(&self) -> RangeList {
+        let mut ranges = Vec::new();
+        let mut next = 1;
+        for range in &self.ranges {
+            if range.start > next {
+                ranges.push(Range {
+                    start: next,
+                    end: range.start - 1,
+                });
+            }
+            if range.end == usize::MAX {
+                return RangeList { ranges };
+            }
+            next = range.end + 1;
+        }
+        ranges.push(Range {
+            start: next,
+            end: usize::MAX,
+        });
+        RangeList { ranges }
+    }
+
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, position: usize) -> bool {
+        let after = self.ranges.partition_point(|range| range.end < position);
+        self.ranges
+            .get(after)
+            .is_some_and(|range| range.start <= position)
+    }
+
+    /// The highest selected position, so scanning can stop there.
+    pub fn last(&self) -> usize {
+        self.ranges.last().map_or(0, |range| range.end)
+    }
+
+    /// Whether `position` is the first of a range.
+    pub fn starts_range(&self, position: usize) -> bool {
+        self.ranges
+            .binary_search_by_key(&position, |range| range.start)
+            .is_ok()
+    }
+}
+
+/// Parse `list[start..end]`, which may be empty. Errors quote the rest of
+/// the list from the first bad character, as GNU cut does.
+fn parse_number(list: &str, start: usize, end: usize, unit: Unit) -> Result<Option<usize>, String> {
+    let text = &list[start..end];
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if let Some(bad) = text.find(|c: char| !c.is_ascii_digit()) {
+        let rest = &list[start + bad..];
+        return Err(match unit {
+            Unit::Fields => format!("invalid field value '{}'", rest),
+            Unit::Positions => format!("invalid byte/character position '{}'", rest),
+        });
+    }
+    match text.parse::<usize>() {
+        Ok(number) if number != usize::MAX => Ok(Some(number)),
+        _ => Err(match unit {
+            Unit::Fields => format!("field number '{}' is too large", text),
+            Unit::Positions => format!("byte/character offset '{}' is too large", text),
+        }),
+    }
+}
+
+/// Parse the item of `list` that starts at `start` and is `len` bytes long.
+fn parse_item(list: &str, start: usize, len: usize, unit: Unit) -> Result<Range, String> {
+    let end = start + len;
+    let item = &list[start..end];
+    let dash = item.find('-').map(|dash| start + dash);
+    let low = parse_number(list, start, dash.unwrap_or(end), unit)?;
+    let high = match dash {
+        Some(dash) => {
+            if low == Some(0) {
+                return Err(unit.numbered_from_one());
+            }
+            if let Some(second) = list[dash + 1..end].find('-') {
+                // Complain about a bad number before the extra dash.
+                parse_number(list, dash + 1, dash + 1 + second, unit)?;
+                return Err(match unit {
+                    Unit::Fields => String::from("invalid field range"),
+                    Unit::Positions => String::from("invalid byte or character range"),
+                });
+            }
+            Some(parse_number(list, dash + 1, end, unit)?)
+        }
+        None => None,
+    };
+
+    match (low, high) {
+        (Some(0), None) | (None, None) => Err(unit.numbered_from_one()),
+        (Some(number), None) => Ok(Range {
+            start: number,
+            end: number,
+        }),
+        (None, Some(None)) => Err(String::from("invalid range with no endpoint: -")),
+        (low, Some(high)) => {
+            let low = low.unwrap_or(1);
+            let high = high.unwrap_or(usize::MAX);
+            if high < low {
+                return Err(String::from("invalid decreasing range"));
+            }
+            Ok(Range {
+                start: low,
+                end: high,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &str) -> Vec<(usize, usize)> {
+        RangeList::parse(list, Unit::Fields)
+            .unwrap()
+            .ranges()
+            .iter()
+            .map(|range| (range.start, range.end))
+            .collect()
+    }
+
+    const MAX: usize = usize::MAX;
+
+    #[test]
+    fn items() {
+        assert_eq!(pairs("3"), vec![(3, 3)]);
+        assert_eq!(pairs("2-4"), vec![(2, 4)]);
+        assert_eq!(pairs("-4"), vec![(1, 4)]);
+        assert_eq!(pairs("4-"), vec![(4, MAX)]);
+        assert_eq!(pairs("1 3\t5"), vec![(1, 1), (3, 3), (5, 5)]);
+    }
+
+    #[test]
+    fn sorts_and_merges() {
+        assert_eq!(pairs("7-,1,3-5"), vec![(1, 1), (3, 5), (7, MAX)]);
+        assert_eq!(pairs("2-5,3-7"), vec![(2, 7)]);
+        assert_eq!(pairs("1-2,2-3"), vec![(1, 3)]);
+        assert_eq!(pairs("1,2"), vec![(1, 1), (2, 2)]);
+        assert_eq!(pairs("5-,2-"), vec![(2, MAX)]);
+    }
+
+    #[test]
+    fn complement() {
+        let list = RangeList::parse("2,4-5", Unit::Fields).unwrap();
+        let complement: Vec<(usize, usize)> = list
+            .complement()
+            .ranges()
+            .iter()
+            .map(|range| (range.start, range.end))
+            .collect();
+        assert_eq!(complement, vec![(1, 1), (3, 3), (6, MAX)]);
+
+        let list = RangeList::parse("1-", Unit::Fields).unwrap();
+        assert!(list.complement().ranges().is_empty());
+        let list = RangeList::parse("3-", Unit::Fields).unwrap();
+        assert_eq!(list.complement().ranges(), &[Range { start: 1, end: 2 }]);
+    }
+
+    #[test]
+    fn lookups() {
+        let list = RangeList::parse("2,4-6", Unit::Positions).unwrap();
+        let selected: Vec<usize> = (1..=8).filter(|&n| list.contains(n)).collect();
+        assert_eq!(selected, vec![2, 4, 5, 6]);
+        assert!(list.starts_range(4) && !list.starts_range(5));
+        assert_eq!(list.last(), 6);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |list: &str, unit: Unit| RangeList::parse(list, unit).unwrap_err();
+        assert_eq!(error("0", Unit::Fields), "fields are numbered from 1");
+        assert_eq!(error("", Unit::Fields), "fields are numbered from 1");
+        assert_eq!(error("1,,2", Unit::Fields), "fields are numbered from 1");
+        assert_eq!(
+            error("0-", Unit::Positions),
+            "byte/character positions are numbered from 1"
+        );
+        assert_eq!(error("5-3", Unit::Fields), "invalid decreasing range");
+        assert_eq!(error("a", Unit::Fields), "invalid field value 'a'");
+        assert_eq!(error("1-a", Unit::Fields), "invalid field value 'a'");
+        assert_eq!(
+            error("2x,3", Unit::Positions),
+            "invalid byte/character position 'x,3'"
+        );
+        assert_eq!(
+            error("-", Unit::Fields),
+            "invalid range with no endpoint: -"
+        );
+        assert_eq!(error("1-2-3", Unit::Fields), "invalid field range");
+        assert_eq!(
+            error("99999999999999999999999", Unit::Fields),
+            "field number '99999999999999999999999' is too large"
+        );
+    }
+}
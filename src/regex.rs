@@ -0,0 +1,667 @@
+//! POSIX basic regular expressions, for the utilities that take patterns,
+//! with the GNU extensions `\+`, `\?`, `\|`, `\<`, `\>`, `\b`, `\B`, `\w`, `\W`,
+//! `\s` and `\S`. Patterns and text are bytes, as in the C locale.
+//!
+//! A pattern compiles to a small program that is run on every path at
+//! once, so matching takes time linear in the text and always finds the
+//! leftmost-longest match POSIX asks for. Back-references are not
+//! supported.
+
+use std::ops::Range;
+
+/// Largest count allowed in `\{M,N\}`, as RE_DUP_MAX.
+const DUP_MAX: u32 = 0x7fff;
+
+/// Largest program a pattern may compile to.
+const PROGRAM_MAX: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Assertion {
+    TextStart,
+    TextEnd,
+    WordStart,
+    WordEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+impl Assertion {
+    fn holds(self, text: &[u8], pos: usize) -> bool {
+        let before = pos > 0 && is_word(text[pos - 1]);
+        let after = pos < text.len() && is_word(text[pos]);
+        match self {
+            Assertion::TextStart => pos == 0,
+            Assertion::TextEnd => pos == text.len(),
+            Assertion::WordStart => !before && after,
+            Assertion::WordEnd => before && !after,
+            Assertion::WordBoundary => before != after,
+            Assertion::NotWordBoundary => before == after,
+        }
+    }
+}
+
+type ByteSet = Box<[bool; 256]>;
+
+fn byte_set(test: impl Fn(u8) -> bool) -> ByteSet {
+    let mut set = Box::new([false; 256]);
+    for byte in 0..=u8::MAX {
+        set[usize::from(byte)] = test(byte);
+    }
+    set
+}
+
+/// The members of a `[:name:]` class in the C locale.
+fn class(name: &[u8]) -> Option<ByteSet> {
+    let test: fn(u8) -> bool = match name {
+        b"alnum" => |b| b.is_ascii_alphanumeric(),
+        b"alpha" => |b| b.is_ascii_alphabetic(),
+        b"blank" => |b| b == b' ' || b == b'\t',
+        b"cntrl" => |b| b.is_ascii_control(),
+        b"digit" => |b| b.is_ascii_digit(),
+        b"graph" => |b| b.is_ascii_graphic(),
+        b"lower" => |b| b.is_ascii_lowercase(),
+        b"print" => |b| b.is_ascii_graphic() || b == b' ',
+        b"punct" => |b| b.is_ascii_punctuation(),
+        b"space" => |b| matches!(b, b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r'),
+        b"upper" => |b| b.is_ascii_uppercase(),
+        b"xdigit" => |b| b.is_ascii_hexdigit(),
+        _ => return None,
+    };
+    Some(byte_set(test))
+}
+
+#[derive(Debug)]
+enum Node {
+    /// Any one byte of the set.
+    Bytes(ByteSet),
+    Assert(Assertion),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+struct Parser<'a> {
+    pattern: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.pattern.get(self.pos).copied()
+    }
+
+    fn at(&self, text: &[u8]) -> bool {
+        self.pattern[self.pos..].starts_with(text)
+    }
+
+    /// Branches separated by `\|`, up to the end of the pattern or of the
+    /// enclosing group.
+    fn alternation(&mut self, depth: usize) -> Result<Node, String> {
+        let mut branches = vec![self.branch(depth)?];
+        while self.at(b"\\|") {
+            self.pos += 2;
+            branches.push(self.branch(depth)?);
+        }
+        Ok(match branches.len() {
+            1 => branches.pop().expect("one branch"),
+            _ => Node::Alternate(branches),
+        })
+    }
+
+    fn branch(&mut self, depth: usize) -> Result<Node, String> {
+        let mut items = Vec::new();
+        if self.peek() == Some(b'^') {
+            self.pos += 1;
+            items.push(Node::Assert(Assertion::TextStart));
+        }
+        // A `*` with nothing before it stands for itself.
+        if self.peek() == Some(b'*') {
+            self.pos += 1;
+            items.push(Node::Bytes(byte_set(|b| b == b'*')));
+        }
+        loop {
+            if self.pos == self.pattern.len() || self.at(b"\\|") {
+                break;
+            }
+            if self.at(b"\\)") {
+                if depth == 0 {
+                    return Err(String::from("Unmatched ) or \\)"));
+                }
+                break;
+            }
+            let atom = self.atom(depth)?;
+            let atom = self.postfix(atom)?;
+            items.push(atom);
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn atom(&mut self, depth: usize) -> Result<Node, String> {
+        let byte = self.pattern[self.pos];
+        self.pos += 1;
+        match byte {
+            b'.' => Ok(Node::Bytes(byte_set(|_| true))),
+            b'[' => self.bracket(),
+            b'$' if self.pos == self.pattern.len() || self.at(b"\\)") || self.at(b"\\|") => {
+                Ok(Node::Assert(Assertion::TextEnd))
+            }
+            b'\\' => {
+                let escaped = self
+                    .peek()
+                    .ok_or_else(|| String::from("Trailing backslash"))?;
+                self.pos += 1;
+                match escaped {
+                    b'(' => {
+                        let group = self.alternation(depth + 1)?;
+                        if !self.at(b"\\)") {
+                            return Err(String::from("Unmatched ( or \\("));
+                        }
+                        self.pos += 2;
+                        Ok(group)
+                    }
+                    b'{' => Err(String::from("Invalid preceding regular expression")),
+                    b'1'..=b'9' => Err(String::from("back-references are not supported")),
+                    b'w' => Ok(Node::Bytes(byte_set(is_word))),
+                    b'W' => Ok(Node::Bytes(byte_set(|b| !is_word(b)))),
+                    b's' => Ok(Node::Bytes(class(b"space").expect("space class"))),
+                    b'S' => {
+                        let space = class(b"space").expect("space class");
+                        Ok(Node::Bytes(byte_set(|b| !space[usize::from(b)])))
+                    }
+                    b'<' => Ok(Node::Assert(Assertion::WordStart)),
+                    b'>' => Ok(Node::Assert(Assertion::WordEnd)),
+                    b'b' => Ok(Node::Assert(Assertion::WordBoundary)),
+                    b'B' => Ok(Node::Assert(Assertion::NotWordBoundary)),
+                    other => Ok(Node::Bytes(byte_set(|b| b == other))),
+                }
+            }
+            other => Ok(Node::Bytes(byte_set(|b| b == other))),
+        }
+    }
+
+    /// `*`, `\+`, `\?` and `\{M,N\}` after an atom, any number of them.
+    fn postfix(&mut self, mut node: Node) -> Result<Node, String> {
+        loop {
+            let (min, max) = if self.peek() == Some(b'*') {
+                self.pos += 1;
+                (0, None)
+            } else if self.at(b"\\+") {
+                self.pos += 2;
+                (1, None)
+            } else if self.at(b"\\?") {
+                self.pos += 2;
+                (0, Some(1))
+            } else if self.at(b"\\{") {
+                self.pos += 2;
+                self.interval()?
+            } else {
+                return Ok(node);
+            };
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            };
+        }
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let digits = self.pattern[self.pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let text = std::str::from_utf8(&self.pattern[self.pos..self.pos + digits]).ok()?;
+        self.pos += digits;
+        match text {
+            "" => None,
+            text => Some(text.parse().unwrap_or(u32::MAX)),
+        }
+    }
+
+    /// The bounds of `\{M\}`, `\{M,\}` or `\{M,N\}`, after the `\{`.
+    fn interval(&mut self) -> Result<(u32, Option<u32>), String> {
+        let invalid = || String::from("Invalid content of \\{\\}");
+        let min = self.number().ok_or_else(invalid)?;
+        let max = if self.peek() == Some(b',') {
+            self.pos += 1;
+            self.number()
+        } else {
+            Some(min)
+        };
+        if !self.at(b"\\}") {
+            let closed = self.pattern[self.pos..].windows(2).any(|w| w == b"\\}");
+            return Err(if closed {
+                invalid()
+            } else {
+                String::from("Unmatched \\{")
+            });
+        }
+        self.pos += 2;
+        if min > DUP_MAX || max.is_some_and(|max| max > DUP_MAX) {
+            return Err(String::from("Regular expression too big"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(invalid());
+        }
+        Ok((min, max))
+    }
+
+    /// A bracket expression, after its `[`.
+    fn bracket(&mut self) -> Result<Node, String> {
+        let unmatched = || String::from("Unmatched [, [^, [:, [., or [=");
+        let mut set = Box::new([false; 256]);
+        let negate = self.peek() == Some(b'^');
+        if negate {
+            self.pos += 1;
+        }
+        let mut first = true;
+        loop {
+            let byte = self.peek().ok_or_else(unmatched)?;
+            if byte == b']' && !first {
+                self.pos += 1;
+                break;
+            }
+            first = false;
+
+            if self.at(b"[:") {
+                let end = self.closing(b":]").ok_or_else(unmatched)?;
+                let members = class(&self.pattern[self.pos + 2..end])
+                    .ok_or_else(|| String::from("Invalid character class name"))?;
+                for (member, &inside) in set.iter_mut().zip(members.iter()) {
+                    *member |= inside;
+                }
+                self.pos = end + 2;
+                continue;
+            }
+
+            let low = self.bracket_byte()?;
+            if self.peek() == Some(b'-') && self.pattern.get(self.pos + 1) != Some(&b']') {
+                self.pos += 1;
+                let high = self.bracket_byte()?;
+                if high < low {
+                    return Err(String::from("Invalid range end"));
+                }
+                for byte in low..=high {
+                    set[usize::from(byte)] = true;
+                }
+            } else {
+                set[usize::from(low)] = true;
+            }
+        }
+        if negate {
+            set.iter_mut().for_each(|member| *member = !*member);
+        }
+        Ok(Node::Bytes(set))
+    }
+
+    /// One byte of a bracket expression: itself, or `[.c.]` or `[=c=]`.
+    fn bracket_byte(&mut self) -> Result<u8, String> {
+        for (open, close) in [(b"[.", b".]"), (b"[=", b"=]")] {
+            if self.at(open) {
+                let end = self
+                    .closing(close)
+                    .ok_or_else(|| String::from("Unmatched [, [^, [:, [., or [="))?;
+                return match self.pattern[self.pos + 2..end] {
+                    [byte] => {
+                        self.pos = end + 2;
+                        Ok(byte)
+                    }
+                    _ => Err(String::from("Invalid collation character")),
+                };
+            }
+        }
+        let byte = self
+            .peek()
+            .ok_or_else(|| String::from("Unmatched [, [^, [:, [., or [="))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Where `close` next appears after the opening pair at `pos`.
+    fn closing(&self, close: &[u8]) -> Option<usize> {
+        self.pattern[self.pos + 2..]
+            .windows(2)
+            .position(|window| window == close)
+            .map(|idx| self.pos + 2 + idx)
+    }
+}
+
+#[derive(Debug)]
+enum Inst {
+    Bytes(ByteSet),
+    Assert(Assertion),
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), String> {
+    if program.len() > PROGRAM_MAX {
+        return Err(String::from("Regular expression too big"));
+    }
+    match node {
+        Node::Bytes(set) => program.push(Inst::Bytes(set.clone())),
+        Node::Assert(assertion) => program.push(Inst::Assert(*assertion)),
+        Node::Concat(items) => {
+            for item in items {
+                compile(item, program)?;
+            }
+        }
+        Node::Alternate(branches) => {
+            let mut jumps = Vec::new();
+            for (idx, branch) in branches.iter().enumerate() {
+                if idx + 1 == branches.len() {
+                    compile(branch, program)?;
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile(branch, program)?;
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(node, program)?;
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The threads alive at one position: a program counter and the start of
+/// the match it is part of, kept in order of start.
+struct Threads {
+    pcs: Vec<usize>,
+    starts: Vec<usize>,
+    present: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads {
+            pcs: Vec::with_capacity(len),
+            starts: vec![0; len],
+            present: vec![false; len],
+        }
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.present[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+/// A compiled basic regular expression.
+#[derive(Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        let mut parser = Parser {
+            pattern: pattern.as_bytes(),
+            pos: 0,
+        };
+        let node = parser.alternation(0)?;
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Inst::Match);
+        Ok(Regex { program })
+    }
+
+    /// Add the thread at `pc` and everything it reaches without reading a
+    /// byte. A thread reaching the end records its match in `best`.
+    fn add(
+        &self,
+        threads: &mut Threads,
+        pc: usize,
+        start: usize,
+        text: &[u8],
+        pos: usize,
+        best: &mut Option<Range<usize>>,
+    ) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.present[pc] {
+                continue;
+            }
+            threads.present[pc] = true;
+            threads.starts[pc] = start;
+            threads.pcs.push(pc);
+            match self.program[pc] {
+                Inst::Jump(target) => stack.push(target),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                Inst::Assert(assertion) => {
+                    if assertion.holds(text, pos) {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::Match => {
+                    let better = match best {
+                        Some(found) => {
+                            start < found.start || (start == found.start && pos > found.end)
+                        }
+                        None => true,
+                    };
+                    if better {
+                        *best = Some(start..pos);
+                    }
+                }
+                Inst::Bytes(_) => {}
+            }
+        }
+    }
+
+    /// The leftmost-longest match in `text` starting at or after `from`,
+    /// or the first one found when `any` is set.
+    fn search(&self, text: &[u8], from: usize, any: bool) -> Option<Range<usize>> {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut best: Option<Range<usize>> = None;
+        for pos in from..=text.len() {
+            // Threads are kept in order of start, so a later start only
+            // gets the program counters no earlier one reached.
+            if best.is_none() {
+                self.add(&mut current, 0, pos, text, pos, &mut best);
+            }
+            if best.is_some() && any {
+                break;
+            }
+            if current.pcs.is_empty() || pos == text.len() {
+                break;
+            }
+            next.clear();
+            for idx in 0..current.pcs.len() {
+                let pc = current.pcs[idx];
+                let start = current.starts[pc];
+                if best.as_ref().is_some_and(|found| start > found.start) {
+                    continue;
+                }
+                if let Inst::Bytes(set) = &self.program[pc] {
+                    if set[usize::from(text[pos])] {
+                        self.add(&mut next, pc + 1, start, text, pos + 1, &mut best);
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        best
+    }
+
+    /// The leftmost-longest match in `text`.
+    pub fn find(&self, text: &[u8]) -> Option<Range<usize>> {
+        self.search(text, 0, false)
+    }
+
+    /// The leftmost-longest match starting at or after `from`. Anchors
+    /// and word boundaries still see the whole of `text`.
+    pub fn find_at(&self, text: &[u8], from: usize) -> Option<Range<usize>> {
+        self.search(text, from, false)
+    }
+
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.search(text, 0, true).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<Range<usize>> {
+        Regex::new(pattern).unwrap().find(text.as_bytes())
+    }
+
+    fn error(pattern: &str) -> String {
+        Regex::new(pattern).unwrap_err()
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(find("abc", "xxabcxx"), Some(2..5));
+        assert_eq!(find("abc", "ab"), None);
+        assert_eq!(find("a.c", "abc"), Some(0..3));
+        assert_eq!(find("", "abc"), Some(0..0));
+        assert_eq!(find("a\\.c", "abc a.c"), Some(4..7));
+        assert_eq!(find("+?{|()", "+?{|()"), Some(0..6));
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^ab", "abab"), Some(0..2));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("b$", "abab"), Some(3..4));
+        assert_eq!(find("a$b", "a$b"), Some(0..3));
+        assert_eq!(find("a^b", "a^b"), Some(0..3));
+        assert_eq!(find("^$", ""), Some(0..0));
+        assert_eq!(find("\\(^a\\)", "a"), Some(0..1));
+    }
+
+    #[test]
+    fn find_at() {
+        let regex = Regex::new("ab").unwrap();
+        assert_eq!(regex.find_at(b"abab", 1), Some(2..4));
+        assert_eq!(regex.find_at(b"abab", 3), None);
+        assert_eq!(regex.find_at(b"ab", 2), None);
+        // Anchors see the text before `from`.
+        assert_eq!(Regex::new("^b").unwrap().find_at(b"ab", 1), None);
+        assert_eq!(Regex::new("\\<b").unwrap().find_at(b"a b", 1), Some(2..3));
+    }
+
+    #[test]
+    fn repetition() {
+        assert_eq!(find("ab*", "abbbc"), Some(0..4));
+        assert_eq!(find("*a", "x*a"), Some(1..3));
+        assert_eq!(find("^*", "*"), Some(0..1));
+        assert_eq!(find("ab\\+", "a ab"), Some(2..4));
+        assert_eq!(find("ab\\?c", "ac abc"), Some(0..2));
+        assert_eq!(find("a\\{2,3\\}", "aaaa"), Some(0..3));
+        assert_eq!(find("a\\{2\\}", "a aa"), Some(2..4));
+        assert_eq!(find("a\\{2,\\}", "aaaaa"), Some(0..5));
+        assert_eq!(find("\\(ab\\)*c", "ababc"), Some(0..5));
+        assert_eq!(find("\\(a*\\)*b", "aab"), Some(0..3));
+    }
+
+    #[test]
+    fn leftmost_longest() {
+        assert_eq!(find("a\\|ab", "ab"), Some(0..2));
+        assert_eq!(find("b\\|abcd", "abcd"), Some(0..4));
+        assert_eq!(find("x*", "abc"), Some(0..0));
+        assert_eq!(find("\\(a\\|ab\\)\\(c\\|bcd\\)", "abcd"), Some(0..4));
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(find("[0-9][0-9]*", "ab 123 c"), Some(3..6));
+        assert_eq!(find("[^a-z ]", "ab C"), Some(3..4));
+        assert_eq!(find("[]a]*", "]a]b"), Some(0..3));
+        assert_eq!(find("[^]]", "]]x"), Some(2..3));
+        assert_eq!(find("[a-]*", "a-a-b"), Some(0..4));
+        assert_eq!(find("[[:digit:][:upper:]]*", "A1b"), Some(0..2));
+        assert_eq!(find("[[.-.]x]", "-"), Some(0..1));
+        assert_eq!(find("[[=e=]]", "e"), Some(0..1));
+        assert_eq!(find("[\\]", "\\"), Some(0..1));
+    }
+
+    #[test]
+    fn gnu_escapes() {
+        assert_eq!(find("\\<is\\>", "this is"), Some(5..7));
+        assert_eq!(find("\\bb", "ab b"), Some(3..4));
+        assert_eq!(find("a\\B", "a ba"), None);
+        assert_eq!(find("\\w\\w*", "  ab_1 "), Some(2..6));
+        assert_eq!(find("\\s\\S", "a b"), Some(1..3));
+        assert_eq!(find("\\W", "ab-"), Some(2..3));
+    }
+
+    #[test]
+    fn bytes() {
+        let regex = Regex::new("\u{e9}").unwrap();
+        assert_eq!(regex.find("caf\u{e9}".as_bytes()), Some(3..5));
+        assert!(Regex::new(".").unwrap().is_match(&[0xff]));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(error("[abc").contains("Unmatched ["));
+        assert!(error("[[:bogus:]]").contains("Invalid character class name"));
+        assert!(error("[z-a]").contains("Invalid range end"));
+        assert!(error("\\(a").contains("Unmatched ( or \\("));
+        assert!(error("a\\)").contains("Unmatched ) or \\)"));
+        assert!(error("a\\{1").contains("Unmatched \\{"));
+        assert!(error("a\\{2,1\\}").contains("Invalid content of \\{\\}"));
+        assert!(error("a\\{x\\}").contains("Invalid content of \\{\\}"));
+        assert!(error("a\\{99999\\}").contains("too big"));
+        assert!(error("\\{1\\}").contains("Invalid preceding regular expression"));
+        assert!(error("a\\").contains("Trailing backslash"));
+        assert!(error("\\(a\\)\\1").contains("back-references are not supported"));
+    }
+
+    #[test]
+    fn is_match() {
+        let regex = Regex::new("b\\|abcd").unwrap();
+        assert!(regex.is_match(b"xxb"));
+        assert!(!regex.is_match(b"acd"));
+    }
+}
@@ -0,0 +1,475 @@
+use std::fs::{self, Metadata};
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+
+use crate::backup::Backup;
+use crate::copy::{self, confirm, quoted, Copier, Overwrite, Preserve, Reflink};
+use crate::output::{describe, Output};
+use crate::walk::{self, Event, Follow, Walk};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub overwrite: Overwrite,
+    /// Leave destinations at least as new as their source alone.
+    pub update: bool,
+    pub verbose: bool,
+    pub backup: Backup,
+    /// Swap source and destination instead, which replaces nothing.
+    pub exchange: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            overwrite: Overwrite::Always,
+            update: false,
+            verbose: false,
+            backup: Backup::none(),
+            exchange: false,
+        }
+    }
+}
+
+/// Moves files and trees, renaming them where it can and copying them
+/// to the new place and removing the old one where the move crosses
+/// file systems.
+pub struct Mover<W: Write> {
+    prog: &'static str,
+    options: Options,
+    /// Does the copying half of moves across file systems, and owns the
+    /// output.
+    copier: Copier<W>,
+}
+
+impl<W: Write> Mover<W> {
+    pub fn new(prog: &'static str, options: Options, out: Output<W>) -> Mover<W> {
+        let copy_options = copy::Options {
+            recursive: true,
+            follow: Follow::Never,
+            force: true,
+            verbose: options.verbose,
+            moving: true,
+            preserve: Preserve::all(),
+            reflink: Reflink::Auto,
+            ..copy::Options::default()
+        };
+        Mover {
+            prog,
+            options,
+            copier: Copier::new(prog, copy_options, out),
+        }
+    }
+
+    pub fn output(&mut self) -> &mut Output<W> {
+        self.copier.output()
+    }
+
+    fn fail(&mut self, message: String) -> bool {
+        self.output().flush();
+        eprintln!("{}: {}", self.prog, message);
+        false
+    }
+
+    /// Move `source` to `dest`, which names the new place itself rather
+    /// than a directory to move into. False if anything went wrong.
+    pub fn rename(&mut self, source: &Path, dest: &Path) -> bool {
+        let metadata = match fs::symlink_metadata(source) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return self.fail(format!("cannot stat {}: {}", quoted(source), describe(&e)))
+            }
+        };
+        if self.options.exchange {
+            return self.exchange(source, dest);
+        }
+
+        let existing = fs::symlink_metadata(dest).ok();
+        if let Some(existing) = &existing {
+            if is_same_file(source, &metadata, dest, existing) {
+                return self.fail(format!(
+                    "{} and {} are the same file",
+                    quoted(source),
+                    quoted(dest)
+                ));
+            }
+        }
+        if metadata.is_dir() && walk::is_within(dest, source).unwrap_or(false) {
+            return self.fail(format!(
+                "cannot move {} to a subdirectory of itself, {}",
+                quoted(source),
+                quoted(dest)
+            ));
+        }
+
+        let mut backup = None;
+        if let Some(existing) = &existing {
+            if existing.is_dir() && !metadata.is_dir() {
+                return self.fail(format!(
+                    "cannot overwrite directory {} with non-directory",
+                    quoted(dest)
+                ));
+            }
+            if !existing.is_dir() && metadata.is_dir() {
+                return self.fail(format!(
+                    "cannot overwrite non-directory {} with directory {}",
+                    quoted(dest),
+                    quoted(source)
+                ));
+            }
+            if self.options.update && is_up_to_date(existing, &metadata) {
+                return true;
+            }
+            match self.options.overwrite {
+                Overwrite::Always => {}
+                Overwrite::Never => return true,
+                Overwrite::Prompt => {
+                    self.output().flush();
+                    let question = format!("{}: overwrite {}? ", self.prog, quoted(dest));
+                    if !confirm(&question) {
+                        return true;
+                    }
+                }
+            }
+            match self.options.backup.make(dest) {
+                Ok(made) => backup = made,
+                Err(e) => {
+                    return self.fail(format!("cannot backup {}: {}", quoted(dest), describe(&e)))
+                }
+            }
+        }
+
+        match fs::rename(source, dest) {
+            Ok(()) => {
+                if self.options.verbose {
+                    let out = self.copier.output();
+                    write!(out, "renamed {} -> {}", quoted(source), quoted(dest));
+                    match &backup {
+                        Some(backup) => writeln!(out, " (backup: {})", quoted(backup)),
+                        None => writeln!(out),
+                    }
+                }
+                true
+            }
+            Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+                let replaced = existing.filter(|_| backup.is_none());
+                self.move_across(source, dest, replaced.as_ref())
+            }
+            Err(e) => self.fail(format!(
+                "cannot move {} to {}: {}",
+                quoted(source),
+                quoted(dest),
+                describe(&e)
+            )),
+        }
+    }
+
+    /// Copy `source` to `dest` on another file system, keeping everything
+    /// the copy can, and remove `source` once all of it got there.
+    /// `existing` describes what is still in the way at `dest`.
+    fn move_across(&mut self, source: &Path, dest: &Path, existing: Option<&Metadata>) -> bool {
+        // A directory goes in place of an empty one, not into it.
+        if existing.is_some_and(Metadata::is_dir) {
+            if let Err(e) = fs::remove_dir(dest) {
+                return self.fail(format!(
+                    "inter-device move failed: {} to {}; unable to remove target: {}",
+                    quoted(source),
+                    quoted(dest),
+                    describe(&e)
+                ));
+            }
+        }
+        if !self.copier.copy(source, dest) {
+            return false;
+        }
+
+        let mut ok = true;
+        for event in Walk::new(source, Follow::Never) {
+            let (result, entry, kind) = match event {
+                Ok(Event::Enter(_)) => continue,
+                Ok(Event::File(entry)) => (fs::remove_file(&entry.path), entry, ""),
+                Ok(Event::Leave(entry)) => (fs::remove_dir(&entry.path), entry, "directory "),
+                Err(walk::Error::Stat(path, e)) | Err(walk::Error::Read(path, e)) => {
+                    ok = self.fail(format!("cannot remove {}: {}", quoted(&path), describe(&e)));
+                    continue;
+                }
+                Err(walk::Error::Cycle(_)) => continue,
+            };
+            match result {
+                Ok(()) if self.options.verbose => {
+                    writeln!(self.output(), "removed {}{}", kind, quoted(&entry.path))
+                }
+                Ok(()) => {}
+                Err(e) => {
+                    ok = self.fail(format!(
+                        "cannot remove {}: {}",
+                        quoted(&entry.path),
+                        describe(&e)
+                    ))
+                }
+            }
+        }
+        ok
+    }
+
+    fn exchange(&mut self, source: &Path, dest: &Path) -> bool {
+        match exchange(source, dest) {
+            Ok(()) => {
+                if self.options.verbose {
+                    writeln!(
+                        self.output(),
+                        "exchanged {} <-> {}",
+                        quoted(source),
+                        quoted(dest)
+                    );
+                }
+                true
+            }
+            Err(e) => self.fail(format!(
+                "cannot exchange {} and {}: {}",
+                quoted(source),
+                quoted(dest),
+                describe(&e)
+            )),
+        }
+    }
+}
+
+/// Whether `source` and `dest` are two names for one file, which a move
+/// would lose. Names differing only in case are a rename on file systems
+/// that ignore it.
+fn is_same_file(source: &Path, metadata: &Metadata, dest: &Path, existing: &Metadata) -> bool {
+    let ids = (
+        walk::identify(source, metadata),
+        walk::identify(dest, existing),
+    );
+    match ids {
+        (Ok((source_id, _)), Ok((dest_id, _))) if source_id == dest_id => {
+            let (source, dest) = (source.to_string_lossy(), dest.to_string_lossy());
+            source == dest || source.to_lowercase() != dest.to_lowercase()
+        }
+        _ => false,
+    }
+}
+
+fn is_up_to_date(dest: &Metadata, source: &Metadata) -> bool {
+    match (dest.modified(), source.modified()) {
+        (Ok(dest), Ok(source)) => dest >= source,
+        _ => false,
+    }
+}
+
+/// Swap the files at `a` and `b` in one step.
+#[cfg(target_os = "linux")]
+fn exchange(a: &Path, b: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = |path: &Path| {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+    };
+    let (a, b) = (c_path(a)?, c_path(b)?);
+    // Called through syscall as not every C library wraps renameat2.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_renameat2,
+            libc::AT_FDCWD,
+            a.as_ptr(),
+            libc::AT_FDCWD,
+            b.as_ptr(),
+            libc::RENAME_EXCHANGE,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Swap the files at `a` and `b` in one step, which only Linux offers.
+#[cfg(not(target_os = "linux"))]
+fn exchange(_a: &Path, _b: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        "Operation not supported",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::Control;
+
+    fn mover(options: Options) -> Mover<Vec<u8>> {
+        Mover::new("mv", options, Output::new("mv", Vec::new()))
+    }
+
+    fn verbose() -> Options {
+        Options {
+            verbose: true,
+            ..Options::default()
+        }
+    }
+
+    fn verbose_output(mover: &mut Mover<Vec<u8>>) -> String {
+        mover.output().flush();
+        String::from_utf8(mover.output().get_ref().clone()).unwrap()
+    }
+
+    #[test]
+    fn renames() {
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&source, "a").unwrap();
+        fs::write(&dest, "b").unwrap();
+
+        let options = Options {
+            backup: Backup {
+                control: Control::Simple,
+                suffix: String::from("~"),
+            },
+            ..verbose()
+        };
+        let mut mover = mover(options);
+        assert!(mover.rename(&source, &dest));
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "a");
+        let backup = temp.path().join("b~");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "b");
+        assert_eq!(
+            verbose_output(&mut mover),
+            format!(
+                "renamed {} -> {} (backup: {})\n",
+                quoted(&source),
+                quoted(&dest),
+                quoted(&backup)
+            )
+        );
+
+        assert!(!mover.rename(&source, &dest));
+    }
+
+    #[test]
+    fn kept_destinations() {
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&dest, "old").unwrap();
+        fs::write(&source, "new").unwrap();
+
+        let never = Options {
+            overwrite: Overwrite::Never,
+            ..Options::default()
+        };
+        assert!(mover(never).rename(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+
+        // Written after the source, so at least as new.
+        fs::write(&dest, "newer").unwrap();
+        let update = Options {
+            update: true,
+            ..Options::default()
+        };
+        assert!(mover(update.clone()).rename(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "newer");
+
+        fs::remove_file(&dest).unwrap();
+        assert!(mover(update).rename(&source, &dest));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+    }
+
+    #[test]
+    fn refusals() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("dir/sub")).unwrap();
+        fs::write(root.join("file"), "").unwrap();
+        fs::hard_link(root.join("file"), root.join("link")).unwrap();
+
+        let mut mover = mover(Options::default());
+        assert!(!mover.rename(&root.join("file"), &root.join("link")));
+        assert!(!mover.rename(&root.join("dir"), &root.join("dir/sub/dir")));
+        assert!(!mover.rename(&root.join("dir"), &root.join("dir")));
+        assert!(!mover.rename(&root.join("file"), &root.join("dir")));
+        assert!(!mover.rename(&root.join("dir"), &root.join("file")));
+        assert!(!mover.rename(&root.join("missing"), &root.join("new")));
+        assert!(root.join("file").exists() && root.join("dir/sub").is_dir());
+    }
+
+    #[test]
+    fn directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("a/sub")).unwrap();
+        fs::create_dir(root.join("empty")).unwrap();
+        fs::create_dir_all(root.join("full/thing")).unwrap();
+
+        let mut mover = mover(Options::default());
+        assert!(!mover.rename(&root.join("a"), &root.join("full")));
+        assert!(mover.rename(&root.join("a"), &root.join("empty")));
+        assert!(root.join("empty/sub").is_dir() && !root.join("a").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn across_file_systems() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let (source, dest) = (temp.path().join("src"), temp.path().join("dest"));
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("sub/file"), "data").unwrap();
+        fs::set_permissions(source.join("sub/file"), fs::Permissions::from_mode(0o640)).unwrap();
+        fs::create_dir(&dest).unwrap();
+
+        let mut mover = mover(verbose());
+        let existing = fs::symlink_metadata(&dest).unwrap();
+        assert!(mover.move_across(&source, &dest, Some(&existing)));
+        assert!(!source.exists());
+        let file = dest.join("sub/file");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "data");
+        let mode = fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+
+        let q = |path: &Path| quoted(path);
+        assert_eq!(
+            verbose_output(&mut mover),
+            format!(
+                "created directory {}\ncreated directory {}\ncopied {} -> {}\n\
+                 removed {}\nremoved directory {}\nremoved directory {}\n",
+                q(&dest),
+                q(&dest.join("sub")),
+                q(&source.join("sub/file")),
+                q(&file),
+                q(&source.join("sub/file")),
+                q(&source.join("sub")),
+                q(&source)
+            )
+        );
+
+        // Nothing is removed unless everything was copied.
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::create_dir_all(dest.join("x")).unwrap();
+        let existing = fs::symlink_metadata(&dest).unwrap();
+        assert!(!mover.move_across(&source, &dest, Some(&existing)));
+        assert!(source.join("sub").is_dir());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn exchanges() {
+        let temp = tempfile::tempdir().unwrap();
+        let (a, b) = (temp.path().join("a"), temp.path().join("b"));
+        fs::write(&a, "a").unwrap();
+        fs::create_dir(&b).unwrap();
+
+        let options = Options {
+            exchange: true,
+            ..Options::default()
+        };
+        let mut mover = mover(options);
+        assert!(mover.rename(&a, &b));
+        assert!(a.is_dir());
+        assert_eq!(fs::read_to_string(&b).unwrap(), "a");
+        assert!(!mover.rename(&a, &temp.path().join("missing")));
+    }
+}
@@ -0,0 +1,138 @@
+/// Multipliers for the size suffixes GNU tools accept: a bare letter or
+/// `XiB` is a power of 1024, `XB` a power of 1000, and `b` is a 512 byte
+/// block.
+const SUFFIXES: &[(&str, u32, u32)] = &[
+    ("K", 1024, 1),
+    ("M", 1024, 2),
+    ("G", 1024, 3),
+    ("T", 1024, 4),
+    ("P", 1024, 5),
+    ("E", 1024, 6),
+];
+
+fn multiplier(suffix: &str) -> Option<u64> {
+    match suffix {
+        "" | "c" => return Some(1),
+        "w" => return Some(2),
+        "b" => return Some(512),
+        "k" => return Some(1024),
+        _ => {}
+    }
+
+    let mut chars = suffix.chars();
+    let letter = match chars.next()? {
+        'k' => String::from("K"),
+        letter => letter.to_string(),
+    };
+    let rest = chars.as_str();
+    let &(_, base, power) = SUFFIXES.iter().find(|(name, _, _)| *name == letter)?;
+    let base = match rest {
+        "" | "iB" => base,
+        "B" => 1000,
+        _ => return None,
+    };
+    (base as u64).checked_pow(power)
+}
+
+/// Parse a byte or line count such as `10`, `4K`, `2MiB` or `1GB`.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    if digits == 0 {
+        return Err(format!("invalid number: '{}'", text));
+    }
+
+    let number: u64 = text[..digits]
+        .parse()
+        .map_err(|_| format!("number too large: '{}'", text))?;
+    let multiplier =
+        multiplier(&text[digits..]).ok_or_else(|| format!("invalid suffix in '{}'", text))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("number too large: '{}'", text))
+}
+
+/// Parse an offset as `strtol` would read it before the suffix: a `0x`
+/// prefix makes it hex and a leading `0` octal. Suffixes are as for
+/// `parse_size`.
+pub fn parse_offset(text: &str) -> Result<u64, String> {
+    let (radix, digits) = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) if hex.starts_with(|c: char| c.is_ascii_hexdigit()) => (16, hex),
+        _ if text.len() > 1 && text.starts_with('0') => (8, &text[1..]),
+        _ => return parse_size(text),
+    };
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if end == 0 {
+        return Err(format!("invalid number: '{}'", text));
+    }
+
+    let number = u64::from_str_radix(&digits[..end], radix)
+        .map_err(|_| format!("number too large: '{}'", text))?;
+    let multiplier =
+        multiplier(&digits[end..]).ok_or_else(|| format!("invalid suffix in '{}'", text))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("number too large: '{}'", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_numbers() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("42"), Ok(42));
+    }
+
+    #[test]
+    fn binary_suffixes() {
+        assert_eq!(parse_size("1K"), Ok(1024));
+        assert_eq!(parse_size("1k"), Ok(1024));
+        assert_eq!(parse_size("2KiB"), Ok(2048));
+        assert_eq!(parse_size("3M"), Ok(3 * 1024 * 1024));
+        assert_eq!(parse_size("1MiB"), Ok(1024 * 1024));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+        assert_eq!(parse_size("1E"), Ok(1 << 60));
+    }
+
+    #[test]
+    fn decimal_suffixes() {
+        assert_eq!(parse_size("1KB"), Ok(1000));
+        assert_eq!(parse_size("1kB"), Ok(1000));
+        assert_eq!(parse_size("5MB"), Ok(5_000_000));
+        assert_eq!(parse_size("1GB"), Ok(1_000_000_000));
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(parse_size("2b"), Ok(1024));
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(parse_offset("10"), Ok(10));
+        assert_eq!(parse_offset("0"), Ok(0));
+        assert_eq!(parse_offset("0x1b"), Ok(27));
+        assert_eq!(parse_offset("0X10K"), Ok(16 * 1024));
+        assert_eq!(parse_offset("010"), Ok(8));
+        assert_eq!(parse_offset("02b"), Ok(1024));
+        assert!(parse_offset("0x").is_err());
+        assert!(parse_offset("08").is_err());
+        assert!(parse_offset("0x1q").is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("-5").is_err());
+        assert!(parse_size("5X").is_err());
+        assert!(parse_size("5KiBs").is_err());
+        assert!(parse_size("16E").is_err());
+        assert!(parse_size("99999999999999999999").is_err());
+    }
+}
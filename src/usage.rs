@@ -0,0 +1,169 @@
+/// One option as documented in a utility's help text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionSpec {
+    pub short: Option<char>,
+    pub long: Option<String>,
+    /// Placeholder for the option's argument, e.g. `NUM` in `--lines=NUM`.
+    pub value: Option<String>,
+    /// True for `--color[=WHEN]` style arguments that may be left off.
+    pub optional_value: bool,
+    pub description: String,
+}
+
+impl OptionSpec {
+    /// Every spelling of the option, short form first.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(short) = self.short {
+            names.push(format!("-{}", short));
+        }
+        if let Some(long) = &self.long {
+            names.push(format!("--{}", long));
+        }
+        names
+    }
+
+    pub fn takes_value(&self) -> bool {
+        self.value.is_some() && !self.optional_value
+    }
+}
+
+/// Split an option line into the flags column and the description,
+/// separated by the first run of two or more spaces.
+fn split_columns(line: &str) -> (&str, &str) {
+    match line.find("  ") {
+        Some(idx) => (&line[..idx], line[idx..].trim()),
+        None => (line, ""),
+    }
+}
+
+fn parse_flags(flags: &str) -> Option<OptionSpec> {
+    let mut spec = OptionSpec {
+        short: None,
+        long: None,
+        value: None,
+        optional_value: false,
+        description: String::new(),
+    };
+
+    for flag in flags.split(", ").map(str::trim) {
+        let (name, value) = if let Some(idx) = flag.find("[=") {
+            spec.optional_value = true;
+            (&flag[..idx], Some(flag[idx + 2..].trim_end_matches(']')))
+        } else if let Some(idx) = flag.find(['=', ' ']) {
+            (&flag[..idx], Some(&flag[idx + 1..]))
+        } else {
+            (flag, None)
+        };
+
+        if let Some(long) = name.strip_prefix("--") {
+            spec.long = Some(long.to_string());
+        } else if let Some(short) = name.strip_prefix('-') {
+            let mut chars = short.chars();
+            match (chars.next(), chars.next()) {
+                (Some(chr), None) => spec.short = Some(chr),
+                _ => return None,
+            }
+        } else {
+            return None;
+        }
+
+        if let Some(value) = value {
+            spec.value = Some(value.to_string());
+        }
+    }
+    Some(spec)
+}
+
+/// Extract the options listed in a GNU style help text. An option line
+/// starts with indentation and a dash; more deeply indented lines that
+/// follow continue its description.
+pub fn parse_options(help: &str) -> Vec<OptionSpec> {
+    let mut specs: Vec<OptionSpec> = Vec::new();
+    let mut continuing = false;
+
+    for line in help.lines() {
+        let trimmed = line.trim_start();
+        let indented = trimmed.len() < line.len();
+
+        if indented && trimmed.starts_with('-') {
+            let (flags, description) = split_columns(trimmed);
+            match parse_flags(flags) {
+                Some(mut spec) => {
+                    spec.description = description.to_string();
+                    specs.push(spec);
+                    continuing = true;
+                }
+                None => continuing = false,
+            }
+        } else if continuing && indented && !trimmed.is_empty() {
+            let spec = specs.last_mut().expect("continuation without an option");
+            if !spec.description.is_empty() {
+                spec.description.push(' ');
+            }
+            spec.description.push_str(trimmed);
+        } else {
+            continuing = false;
+        }
+    }
+    specs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "Usage: demo [OPTION]... [FILE]...
+Do things to FILE(s).
+
+  -a, --all                  include everything
+  -c                         with -t: sort by, and show, creation time.
+                               otherwise: show creation time;
+  -n, --lines=[-]NUM         print the first NUM lines
+      --color[=WHEN]         colorize the output
+  -k KEYDEF                  sort via a key
+      --help     display this help and exit
+
+Exit status:
+ 0  if OK,
+";
+
+    #[test]
+    fn finds_every_option() {
+        let specs = parse_options(HELP);
+        let names: Vec<Vec<String>> = specs.iter().map(OptionSpec::names).collect();
+        assert_eq!(
+            names,
+            vec![
+                vec!["-a", "--all"],
+                vec!["-c"],
+                vec!["-n", "--lines"],
+                vec!["--color"],
+                vec!["-k"],
+                vec!["--help"],
+            ]
+        );
+    }
+
+    #[test]
+    fn descriptions() {
+        let specs = parse_options(HELP);
+        assert_eq!(specs[0].description, "include everything");
+        assert_eq!(
+            specs[1].description,
+            "with -t: sort by, and show, creation time. otherwise: show creation time;"
+        );
+    }
+
+    #[test]
+    fn values() {
+        let specs = parse_options(HELP);
+        assert_eq!(specs[0].value, None);
+        assert_eq!(specs[2].value.as_deref(), Some("[-]NUM"));
+        assert!(specs[2].takes_value());
+        assert_eq!(specs[3].value.as_deref(), Some("WHEN"));
+        assert!(specs[3].optional_value);
+        assert!(!specs[3].takes_value());
+        assert_eq!(specs[4].value.as_deref(), Some("KEYDEF"));
+    }
+}
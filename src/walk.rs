@@ -0,0 +1,382 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Which symbolic links the walk looks through rather than reporting
+/// as links.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Follow {
+    Never,
+    /// Only the starting point, as `-H` asks.
+    Roots,
+    Always,
+}
+
+/// Identifies a file independently of the names it is reached by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId {
+    device: u64,
+    index: u64,
+}
+
+/// The identity of the file at `path`, described by `metadata`, and the
+/// number of hard links it has.
+#[cfg(unix)]
+pub fn identify(_path: &Path, metadata: &Metadata) -> io::Result<(FileId, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let id = FileId {
+        device: metadata.dev(),
+        index: metadata.ino(),
+    };
+    Ok((id, metadata.nlink()))
+}
+
+/// The identity of the file at `path`, described by `metadata`, and the
+/// number of hard links it has.
+#[cfg(windows)]
+pub fn identify(path: &Path, metadata: &Metadata) -> io::Result<(FileId, u64)> {
+    use std::fs::OpenOptions;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+    use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+
+    // Metadata does not carry the file index on stable Rust, so ask the
+    // handle. Directories need backup semantics to be opened at all.
+    let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+    if metadata.file_type().is_symlink() {
+        flags |= FILE_FLAG_OPEN_REPARSE_POINT;
+    }
+    let file = OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(flags)
+        .open(path)?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let id = FileId {
+        device: u64::from(info.dwVolumeSerialNumber),
+        index: u64::from(info.nFileIndexHigh) << 32 | u64::from(info.nFileIndexLow),
+    };
+    Ok((id, u64::from(info.nNumberOfLinks)))
+}
+
+/// Whether `path`, which need not exist yet, is `dir` or lies beneath it
+/// once links and `..` are resolved.
+pub fn is_within(path: &Path, dir: &Path) -> io::Result<bool> {
+    let dir = fs::canonicalize(dir)?;
+    let mut existing = path;
+    let mut missing = Vec::new();
+    let mut real = loop {
+        match fs::canonicalize(existing) {
+            Ok(real) => break real,
+            Err(e) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name);
+                    existing = if parent.as_os_str().is_empty() {
+                        Path::new(".")
+                    } else {
+                        parent
+                    };
+                }
+                _ => return Err(e),
+            },
+        }
+    };
+    for name in missing.into_iter().rev() {
+        real.push(name);
+    }
+    Ok(real.starts_with(&dir))
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// The starting point with the names leading here joined on.
+    pub path: PathBuf,
+    /// 0 for the starting point itself.
+    pub depth: usize,
+    /// Describes the link itself unless the walk followed it.
+    pub metadata: Metadata,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// Anything but a directory: a file, an unfollowed link or a device.
+    File(Entry),
+    /// A directory, before anything in it.
+    Enter(Entry),
+    /// The same directory once everything in it has been visited.
+    Leave(Entry),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The entry could not be examined and is skipped.
+    Stat(PathBuf, io::Error),
+    /// The directory was entered but could not be listed; its `Leave`
+    /// still follows.
+    Read(PathBuf, io::Error),
+    /// A followed link leads back to a directory being walked, which is
+    /// skipped rather than walked forever.
+    Cycle(PathBuf),
+}
+
+struct Dir {
+    entry: Entry,
+    /// Known only when links are followed, the one way to loop.
+    id: Option<FileId>,
+    /// Names still to visit, in order; None until the directory is read.
+    names: Option<std::vec::IntoIter<PathBuf>>,
+}
+
+/// A depth-first walk of a tree, visiting the names in each directory in
+/// sorted order so results do not depend on the file system.
+pub struct Walk {
+    follow: Follow,
+    root: Option<PathBuf>,
+    /// Directories entered and not yet left, outermost first.
+    stack: Vec<Dir>,
+}
+
+impl Walk {
+    pub fn new<P: AsRef<Path>>(root: P, follow: Follow) -> Walk {
+        Walk {
+            follow,
+            root: Some(root.as_ref().to_path_buf()),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Leave out the rest of the directory entered last, without a
+    /// `Leave` for it. Used when whatever was to be done there failed.
+    pub fn skip_dir(&mut self) {
+        self.stack.pop();
+    }
+
+    fn visit(&mut self, path: PathBuf, depth: usize) -> Result<Event, Error> {
+        let follow = match self.follow {
+            Follow::Never => false,
+            Follow::Roots => depth == 0,
+            Follow::Always => true,
+        };
+        let metadata = if follow {
+            fs::metadata(&path)
+        } else {
+            fs::symlink_metadata(&path)
+        };
+        let metadata = metadata.map_err(|e| Error::Stat(path.clone(), e))?;
+        let entry = Entry {
+            path,
+            depth,
+            metadata,
+        };
+        if !entry.metadata.is_dir() {
+            return Ok(Event::File(entry));
+        }
+
+        let id = match self.follow {
+            Follow::Never => None,
+            _ => {
+                let (id, _) = identify(&entry.path, &entry.metadata)
+                    .map_err(|e| Error::Stat(entry.path.clone(), e))?;
+                if self.stack.iter().any(|dir| dir.id == Some(id)) {
+                    return Err(Error::Cycle(entry.path));
+                }
+                Some(id)
+            }
+        };
+        self.stack.push(Dir {
+            entry: entry.clone(),
+            id,
+            names: None,
+        });
+        Ok(Event::Enter(entry))
+    }
+}
+
+fn read_names(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut names = Vec::new();
+    for item in fs::read_dir(dir)? {
+        names.push(PathBuf::from(item?.file_name()));
+    }
+    names.sort();
+    Ok(names)
+}
+
+impl Iterator for Walk {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            return Some(self.visit(root, 0));
+        }
+
+        let dir = self.stack.last_mut()?;
+        if dir.names.is_none() {
+            match read_names(&dir.entry.path) {
+                Ok(names) => dir.names = Some(names.into_iter()),
+                Err(e) => {
+                    dir.names = Some(Vec::new().into_iter());
+                    return Some(Err(Error::Read(dir.entry.path.clone(), e)));
+                }
+            }
+        }
+        match dir.names.as_mut().and_then(Iterator::next) {
+            Some(name) => {
+                let path = dir.entry.path.join(name);
+                let depth = dir.entry.depth + 1;
+                Some(self.visit(path, depth))
+            }
+            None => {
+                let dir = self.stack.pop()?;
+                Some(Ok(Event::Leave(dir.entry)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each event as a letter and the path below the root.
+    fn trace(root: &Path, walk: Walk) -> Vec<String> {
+        walk.map(|event| {
+            let (kind, path) = match event {
+                Ok(Event::File(entry)) => ("F", entry.path),
+                Ok(Event::Enter(entry)) => ("E", entry.path),
+                Ok(Event::Leave(entry)) => ("L", entry.path),
+                Err(Error::Stat(path, _)) => ("stat", path),
+                Err(Error::Read(path, _)) => ("read", path),
+                Err(Error::Cycle(path)) => ("cycle", path),
+            };
+            let relative = path.strip_prefix(root).unwrap();
+            format!("{} {}", kind, relative.display())
+        })
+        .collect()
+    }
+
+    fn tree() -> tempfile::TempDir {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("b/d")).unwrap();
+        fs::write(root.join("c"), "c").unwrap();
+        fs::write(root.join("b/a"), "a").unwrap();
+        fs::create_dir(root.join("a")).unwrap();
+        temp
+    }
+
+    #[test]
+    fn depth_first_in_order() {
+        let temp = tree();
+        let root = temp.path();
+        assert_eq!(
+            trace(root, Walk::new(root, Follow::Never)),
+            vec!["E ", "E a", "L a", "E b", "F b/a", "E b/d", "L b/d", "L b", "F c", "L "]
+        );
+    }
+
+    #[test]
+    fn depths() {
+        let temp = tree();
+        let depths: Vec<usize> = Walk::new(temp.path().join("b"), Follow::Never)
+            .map(|event| match event.unwrap() {
+                Event::File(entry) | Event::Enter(entry) | Event::Leave(entry) => entry.depth,
+            })
+            .collect();
+        assert_eq!(depths, vec![0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn skip_dir() {
+        let temp = tree();
+        let root = temp.path();
+        let mut walk = Walk::new(root, Follow::Never);
+        let mut seen = Vec::new();
+        while let Some(event) = walk.next() {
+            if let Ok(Event::Enter(entry)) = event {
+                if entry.path.ends_with("b") {
+                    walk.skip_dir();
+                    continue;
+                }
+                seen.push(entry.path);
+            }
+        }
+        assert_eq!(seen, vec![root.to_path_buf(), root.join("a")]);
+    }
+
+    #[test]
+    fn single_file() {
+        let temp = tree();
+        let root = temp.path();
+        assert_eq!(
+            trace(root, Walk::new(root.join("c"), Follow::Never)),
+            vec!["F c"]
+        );
+        assert_eq!(
+            trace(root, Walk::new(root.join("missing"), Follow::Never)),
+            vec!["stat missing"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tree();
+        let root = temp.path();
+        symlink("../b", root.join("a/to_b")).unwrap();
+        symlink("..", root.join("b/d/up")).unwrap();
+
+        let never = trace(root, Walk::new(root.join("a"), Follow::Never));
+        assert_eq!(never, vec!["E a", "F a/to_b", "L a"]);
+
+        let roots = trace(root, Walk::new(root.join("a/to_b"), Follow::Roots));
+        assert_eq!(
+            roots,
+            vec![
+                "E a/to_b",
+                "F a/to_b/a",
+                "E a/to_b/d",
+                "F a/to_b/d/up",
+                "L a/to_b/d",
+                "L a/to_b"
+            ]
+        );
+
+        let always = trace(root, Walk::new(root.join("b"), Follow::Always));
+        assert_eq!(
+            always,
+            vec!["E b", "F b/a", "E b/d", "cycle b/d/up", "L b/d", "L b"]
+        );
+    }
+
+    #[test]
+    fn within() {
+        let temp = tree();
+        let root = temp.path();
+        assert!(is_within(&root.join("b/d"), &root.join("b")).unwrap());
+        assert!(is_within(&root.join("b/new/deeper"), &root.join("b")).unwrap());
+        assert!(is_within(&root.join("a/../b"), &root.join("b")).unwrap());
+        assert!(is_within(&root.join("b"), &root.join("b")).unwrap());
+        assert!(!is_within(&root.join("a/new"), &root.join("b")).unwrap());
+        assert!(!is_within(&root.join("bb"), &root.join("b")).unwrap());
+    }
+
+    #[test]
+    fn identities() {
+        let temp = tree();
+        let root = temp.path();
+        fs::hard_link(root.join("c"), root.join("e")).unwrap();
+        let id = |name: &str| {
+            let path = root.join(name);
+            identify(&path, &fs::symlink_metadata(&path).unwrap()).unwrap()
+        };
+        assert_eq!(id("c"), id("e"));
+        assert_eq!(id("c").1, 2);
+        assert_ne!(id("c").0, id("b/a").0);
+    }
+}
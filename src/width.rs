@@ -0,0 +1,110 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Distance between tab stops on a terminal.
+pub const TAB_STOP: usize = 8;
+
+/// Columns a character occupies on a terminal: two for wide East Asian
+/// characters, none for control and combining characters.
+pub fn char_width(chr: char) -> usize {
+    chr.width().unwrap_or(0)
+}
+
+/// Columns a string occupies, ignoring tabs and other control characters.
+pub fn str_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// The character at the start of `bytes` and its length: a valid UTF-8
+/// sequence, or else a single byte that stands for no character.
+pub fn decode(bytes: &[u8]) -> (Option<char>, usize) {
+    let len = match bytes[0] {
+        0x00..=0x7f => return (Some(char::from(bytes[0])), 1),
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return (None, 1),
+    };
+    match bytes.get(..len).map(std::str::from_utf8) {
+        Some(Ok(text)) => (text.chars().next(), len),
+        _ => (None, 1),
+    }
+}
+
+/// Columns `bytes` occupy, as `str_width` does, with each byte that is
+/// not valid UTF-8 taking one column.
+pub fn bytes_width(bytes: &[u8]) -> usize {
+    let mut width = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let (chr, len) = decode(&bytes[pos..]);
+        width += chr.map_or(1, char_width);
+        pos += len;
+    }
+    width
+}
+
+/// The column after printing `chr` at `column`. Tabs advance to the next
+/// tab stop, and carriage returns and form feeds return to the margin.
+pub fn advance(column: usize, chr: char) -> usize {
+    match chr {
+        '\t' => column + TAB_STOP - column % TAB_STOP,
+        '\r' | '\x0c' => 0,
+        '\x08' => column.saturating_sub(1),
+        _ => column + char_width(chr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii() {
+        assert_eq!(str_width("hello"), 5);
+        assert_eq!(char_width('a'), 1);
+    }
+
+    #[test]
+    fn wide_and_combining() {
+        assert_eq!(str_width("日本語"), 6);
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(char_width('\u{200b}'), 0);
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(char_width('\0'), 0);
+        assert_eq!(char_width('\x1b'), 0);
+    }
+
+    #[test]
+    fn decoding() {
+        assert_eq!(decode(b"ab"), (Some('a'), 1));
+        assert_eq!(decode("é!".as_bytes()), (Some('é'), 2));
+        assert_eq!(decode("日".as_bytes()), (Some('日'), 3));
+        assert_eq!(decode(b"\xffa"), (None, 1));
+        // A truncated sequence is one invalid byte.
+        assert_eq!(decode(b"\xe6\x97"), (None, 1));
+    }
+
+    #[test]
+    fn byte_widths() {
+        assert_eq!(bytes_width("日本".as_bytes()), 4);
+        assert_eq!(bytes_width(b"a\xff\xfeb"), 4);
+        assert_eq!(bytes_width(b"a\tb"), 2);
+    }
+
+    #[test]
+    fn tabs() {
+        assert_eq!(advance(0, '\t'), 8);
+        assert_eq!(advance(3, '\t'), 8);
+        assert_eq!(advance(8, '\t'), 16);
+    }
+
+    #[test]
+    fn carriage_return_and_backspace() {
+        assert_eq!(advance(12, '\r'), 0);
+        assert_eq!(advance(12, '\x08'), 11);
+        assert_eq!(advance(0, '\x08'), 0);
+    }
+}